@@ -0,0 +1,200 @@
+use std::env;
+use std::fs;
+use std::path::Path;
+
+fn main() {
+    set_bat_version();
+    #[cfg(feature = "minimal-syntax-set")]
+    minimal_syntax_set::dump();
+}
+
+/// The version of bat required in Cargo.toml, for when no Cargo.lock is found.
+const BAT_VERSION_REQUIRED: &str = "0.24.0";
+
+/// Set BAT_VERSION to the version of the bat library which delta is built against, as locked in
+/// Cargo.lock: that of delta, or of the workspace containing it. When delta is built as a
+/// dependency there may be none, and the version required in Cargo.toml is used.
+fn set_bat_version() {
+    let manifest_dir = env::var("CARGO_MANIFEST_DIR").unwrap();
+    let lock_file = Path::new(&manifest_dir)
+        .ancestors()
+        .map(|dir| dir.join("Cargo.lock"))
+        .find(|lock_file| lock_file.is_file());
+    let version = match &lock_file {
+        Some(lock_file) => {
+            println!("cargo:rerun-if-changed={}", lock_file.display());
+            fs::read_to_string(lock_file)
+                .ok()
+                .and_then(|lock| locked_bat_version(&lock))
+        }
+        None => None,
+    };
+    println!(
+        "cargo:rustc-env=BAT_VERSION={}",
+        version.as_deref().unwrap_or(BAT_VERSION_REQUIRED)
+    );
+}
+
+fn locked_bat_version(lock: &str) -> Option<String> {
+    lock.split("[[package]]")
+        .find(|package| package.lines().any(|line| line == "name = \"bat\""))
+        .and_then(|package| {
+            package
+                .lines()
+                .find_map(|line| line.strip_prefix("version = \""))
+        })
+        .and_then(|version| version.strip_suffix('"'))
+        .map(String::from)
+}
+
+/// With the `minimal-syntax-set` feature, write a dump of the syntaxes of a curated list of common
+/// languages, taken from those included in bat, which delta includes in the binary in place of the
+/// complete set.
+#[cfg(feature = "minimal-syntax-set")]
+mod minimal_syntax_set {
+    use std::collections::HashMap;
+    use std::env;
+    use std::path::Path;
+
+    use bat::assets::HighlightingAssets;
+    use syntect::dumps;
+    use syntect::parsing::syntax_definition::{
+        ContextId, ContextReference, MatchOperation, Pattern,
+    };
+    use syntect::parsing::{SyntaxDefinition, SyntaxSet, SyntaxSetBuilder};
+
+    /// The languages highlighted when delta is built with the `minimal-syntax-set` feature. The
+    /// syntaxes which they embed, e.g. JavaScript in HTML, are included as well.
+    const LANGUAGES: &[&str] = &[
+        "Bourne Again Shell (bash)",
+        "C",
+        "C#",
+        "C++",
+        "CSS",
+        "Diff",
+        "Dockerfile",
+        "Git Commit",
+        "Git Config",
+        "Go",
+        "HTML",
+        "INI",
+        "Java",
+        "JavaScript",
+        "JavaScript (Babel)",
+        "JSON",
+        "Kotlin",
+        "Lua",
+        "Makefile",
+        "Markdown",
+        "PHP",
+        "Plain Text",
+        "Python",
+        "Ruby",
+        "Rust",
+        "SQL",
+        "Swift",
+        "TOML",
+        "TypeScript",
+        "TypeScriptReact",
+        "XML",
+        "YAML",
+    ];
+
+    pub fn dump() {
+        let assets = HighlightingAssets::from_binary();
+        let syntax_set = assets.get_syntax_set().unwrap().clone();
+        let path = Path::new(&env::var("OUT_DIR").unwrap()).join("minimal_syntaxes.bin");
+        dumps::dump_to_uncompressed_file(&minimal_syntax_set(syntax_set), path).unwrap();
+    }
+
+    fn minimal_syntax_set(syntax_set: SyntaxSet) -> SyntaxSet {
+        let mut syntaxes = syntax_set.into_builder().syntaxes().to_vec();
+        // The syntaxes to include, by index: those listed, and those which they refer to. The
+        // references between syntaxes are linked, i.e. they are indices in the complete set.
+        let mut included = LANGUAGES
+            .iter()
+            .map(|name| {
+                syntaxes
+                    .iter()
+                    .position(|syntax| &syntax.name == name)
+                    .unwrap_or_else(|| panic!("There is no syntax named '{}'.", name))
+            })
+            .collect::<Vec<_>>();
+        let mut i = 0;
+        while i < included.len() {
+            let mut referenced = Vec::new();
+            for context in syntaxes[included[i]].contexts.values_mut() {
+                for_each_context_id(&mut context.patterns, &mut |id| {
+                    referenced.push(syntax_index(id))
+                });
+            }
+            for index in referenced {
+                if !included.contains(&index) {
+                    included.push(index);
+                }
+            }
+            i += 1;
+        }
+        included.sort_unstable();
+
+        let new_index = included
+            .iter()
+            .enumerate()
+            .map(|(new_index, old_index)| (*old_index, new_index))
+            .collect::<HashMap<_, _>>();
+        let mut remap = |id: &mut ContextId| {
+            *id = with_syntax_index(id, new_index[&syntax_index(id)]);
+        };
+        let mut builder = SyntaxSetBuilder::new();
+        for index in included {
+            let mut syntax = std::mem::take(&mut syntaxes[index].contexts);
+            for context in syntax.values_mut() {
+                if let Some(prototype) = context.prototype.as_mut() {
+                    remap(prototype);
+                }
+                for_each_context_id(&mut context.patterns, &mut remap);
+            }
+            builder.add(SyntaxDefinition {
+                contexts: syntax,
+                ..syntaxes[index].clone()
+            });
+        }
+        builder.build()
+    }
+
+    fn for_each_context_id(patterns: &mut [Pattern], f: &mut impl FnMut(&mut ContextId)) {
+        for pattern in patterns {
+            let references = match pattern {
+                Pattern::Include(reference) => vec![reference],
+                Pattern::Match(match_pattern) => {
+                    let mut references = match &mut match_pattern.operation {
+                        MatchOperation::Push(references) | MatchOperation::Set(references) => {
+                            references.iter_mut().collect()
+                        }
+                        MatchOperation::Pop | MatchOperation::None => vec![],
+                    };
+                    references.extend(match_pattern.with_prototype.as_mut());
+                    references
+                }
+            };
+            for reference in references {
+                if let ContextReference::Direct { 0: id, .. } = reference {
+                    f(id);
+                }
+            }
+        }
+    }
+
+    // ContextId is opaque: its fields are read and written through its serialization.
+    fn syntax_index(id: &ContextId) -> usize {
+        serde_json::to_value(id).unwrap()["syntax_index"]
+            .as_u64()
+            .unwrap() as usize
+    }
+
+    fn with_syntax_index(id: &ContextId, syntax_index: usize) -> ContextId {
+        let mut value = serde_json::to_value(id).unwrap();
+        value["syntax_index"] = syntax_index.into();
+        serde_json::from_value(value).unwrap()
+    }
+}
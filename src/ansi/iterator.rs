@@ -3,6 +3,8 @@ use core::str::Bytes;
 use std::convert::TryFrom;
 use std::iter;
 
+use itertools::Itertools;
+
 pub struct AnsiElementIterator<'a> {
     // The input bytes
     bytes: Bytes<'a>,
@@ -36,17 +38,61 @@ struct Performer {
 
 #[derive(Clone, Debug, PartialEq)]
 pub enum Element {
-    Sgr(ansi_term::Style, usize, usize),
+    Sgr(ansi_term::Style, Option<SgrParams>, usize, usize),
     Csi(usize, usize),
     Esc(usize, usize),
     Osc(usize, usize),
     Text(usize, usize),
 }
 
+/// The parameters of an SGR sequence which ansi_term cannot represent, e.g. "53" (overline) or
+/// "58:5:3" (underline color), as they are written in the sequence, so that they can be emitted
+/// again. They are stored inline, so that styles holding them can be copied; parameters beyond
+/// its capacity are dropped.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SgrParams {
+    len: u8,
+    bytes: [u8; SgrParams::CAPACITY],
+}
+
+impl SgrParams {
+    const CAPACITY: usize = 31;
+
+    // The parameters which fit, separated by ';', or None if none do.
+    fn new(params: &[String]) -> Option<Self> {
+        let mut sgr_params = Self {
+            len: 0,
+            bytes: [0; Self::CAPACITY],
+        };
+        for param in params {
+            let len = sgr_params.len as usize;
+            let separator = if len > 0 { ";" } else { "" };
+            let end = len + separator.len() + param.len();
+            if end <= Self::CAPACITY {
+                sgr_params.bytes[len..end]
+                    .copy_from_slice(format!("{separator}{param}").as_bytes());
+                sgr_params.len = end as u8;
+            }
+        }
+        (sgr_params.len > 0).then_some(sgr_params)
+    }
+
+    pub fn as_str(&self) -> &str {
+        // Only ASCII digits, ':' and ';' are written.
+        std::str::from_utf8(&self.bytes[..self.len as usize]).unwrap_or_default()
+    }
+}
+
+impl std::fmt::Debug for SgrParams {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("SgrParams").field(&self.as_str()).finish()
+    }
+}
+
 impl Element {
     fn set_range(&mut self, start: usize, end: usize) {
         let (from, to) = match self {
-            Element::Sgr(_, from, to) => (from, to),
+            Element::Sgr(_, _, from, to) => (from, to),
             Element::Csi(from, to) => (from, to),
             Element::Esc(from, to) => (from, to),
             Element::Osc(from, to) => (from, to),
@@ -126,15 +172,18 @@ impl anstyle_parse::Perform for Performer {
             return;
         }
 
-        let is_sgr = byte == b'm' && intermediates.first().is_none();
+        let is_sgr = byte == b'm' && intermediates.is_empty();
         let element = if is_sgr {
             if params.is_empty() {
                 // Attr::Reset
                 // Probably doesn't need to be handled: https://github.com/dandavison/delta/pull/431#discussion_r536883568
                 None
             } else {
-                let style = ansi_term_style_from_sgr_parameters(&mut params.iter());
-                Some(Element::Sgr(style, 0, 0))
+                let mut other_params = Vec::new();
+                let style =
+                    ansi_term_style_from_sgr_parameters(&mut params.iter(), &mut other_params);
+                let other_params = SgrParams::new(&other_params);
+                Some(Element::Sgr(style, other_params, 0, 0))
             }
         } else {
             Some(Element::Csi(0, 0))
@@ -170,28 +219,44 @@ impl anstyle_parse::Perform for Performer {
 }
 
 // Based on https://github.com/alacritty/alacritty/blob/9e71002e40d5487c6fa2571a3a3c4f5c8f679334/alacritty_terminal/src/ansi.rs#L1175
-fn ansi_term_style_from_sgr_parameters(params: &mut ParamsIter<'_>) -> ansi_term::Style {
+//
+// Parameters which ansi_term cannot represent (e.g. overline, underline color, fonts) are pushed
+// to `other_params` as they are written, with any arguments they take, so that they can be emitted
+// again; their arguments are never misinterpreted as attributes in their own right.
+fn ansi_term_style_from_sgr_parameters(
+    params: &mut ParamsIter<'_>,
+    other_params: &mut Vec<String>,
+) -> ansi_term::Style {
     let mut style = ansi_term::Style::new();
     while let Some(param) = params.next() {
         match param {
-            // [0] => Some(Attr::Reset),
+            [0] => {
+                style = ansi_term::Style::new();
+                other_params.clear();
+            }
             [1] => style.is_bold = true,
             [2] => style.is_dimmed = true,
             [3] => style.is_italic = true,
+            // "4:0" is the colon-separated form of "no underline"; "4:1" to "4:5" are single,
+            // double, curly, dotted, and dashed underlines.
+            [4, 0] => style.is_underline = false,
             [4, ..] => style.is_underline = true,
             [5] => style.is_blink = true, // blink slow
             [6] => style.is_blink = true, // blink fast
             [7] => style.is_reverse = true,
             [8] => style.is_hidden = true,
             [9] => style.is_strikethrough = true,
-            // [21] => Some(Attr::CancelBold),
-            // [22] => Some(Attr::CancelBoldDim),
-            // [23] => Some(Attr::CancelItalic),
-            // [24] => Some(Attr::CancelUnderline),
-            // [25] => Some(Attr::CancelBlink),
-            // [27] => Some(Attr::CancelReverse),
-            // [28] => Some(Attr::CancelHidden),
-            // [29] => Some(Attr::CancelStrike),
+            [21] => style.is_underline = true, // double underline
+            [22] => {
+                style.is_bold = false;
+                style.is_dimmed = false;
+            }
+            [23] => style.is_italic = false,
+            [24] => style.is_underline = false,
+            [25] => style.is_blink = false,
+            [27] => style.is_reverse = false,
+            [28] => style.is_hidden = false,
+            [29] => style.is_strikethrough = false,
             [30] => style.foreground = Some(ansi_term::Color::Black),
             [31] => style.foreground = Some(ansi_term::Color::Red),
             [32] => style.foreground = Some(ansi_term::Color::Green),
@@ -207,15 +272,11 @@ fn ansi_term_style_from_sgr_parameters(params: &mut ParamsIter<'_>) -> ansi_term
                 }
             }
             [38, params @ ..] => {
-                let rgb_start = if params.len() > 4 { 2 } else { 1 };
-                let rgb_iter = params[rgb_start..].iter().copied();
-                let mut iter = iter::once(params[0]).chain(rgb_iter);
-
-                if let Some(color) = parse_sgr_color(&mut iter) {
+                if let Some(color) = parse_colon_separated_sgr_color(params) {
                     style.foreground = Some(color);
                 }
             }
-            // [39] => Some(Attr::Foreground(Color::Named(NamedColor::Foreground))),
+            [39] => style.foreground = None,
             [40] => style.background = Some(ansi_term::Color::Black),
             [41] => style.background = Some(ansi_term::Color::Red),
             [42] => style.background = Some(ansi_term::Color::Green),
@@ -231,14 +292,19 @@ fn ansi_term_style_from_sgr_parameters(params: &mut ParamsIter<'_>) -> ansi_term
                 }
             }
             [48, params @ ..] => {
-                let rgb_start = if params.len() > 4 { 2 } else { 1 };
-                let rgb_iter = params[rgb_start..].iter().copied();
-                let mut iter = iter::once(params[0]).chain(rgb_iter);
-                if let Some(color) = parse_sgr_color(&mut iter) {
+                if let Some(color) = parse_colon_separated_sgr_color(params) {
                     style.background = Some(color);
                 }
             }
-            // [49] => Some(Attr::Background(Color::Named(NamedColor::Background))),
+            [49] => style.background = None,
+            // Underline color, whose arguments are consumed when given in the semicolon-separated
+            // form.
+            [58] => {
+                let mut args = Vec::new();
+                let mut iter = params.map(|param| param[0]).inspect(|arg| args.push(*arg));
+                let _ = parse_sgr_color(&mut iter);
+                other_params.push(iter::once(58).chain(args).join(";"));
+            }
             // "bright" colors. ansi_term doesn't offer a way to emit them as, e.g., 90m; instead
             // that would be 38;5;8.
             [90] => style.foreground = Some(ansi_term::Color::Fixed(8)),
@@ -257,12 +323,22 @@ fn ansi_term_style_from_sgr_parameters(params: &mut ParamsIter<'_>) -> ansi_term
             [105] => style.background = Some(ansi_term::Color::Fixed(13)),
             [106] => style.background = Some(ansi_term::Color::Fixed(14)),
             [107] => style.background = Some(ansi_term::Color::Fixed(15)),
-            _ => {}
+            _ => other_params.push(param.iter().join(":")),
         };
     }
     style
 }
 
+// Parse the sub-parameters of a colon-separated color, i.e. the part after "38:" or "48:". These
+// are "5:N" for an 8-bit color, and "2:R:G:B" or "2:CS:R:G:B" (with a color space id, which is
+// ignored) for a 24-bit color.
+fn parse_colon_separated_sgr_color(params: &[u16]) -> Option<ansi_term::Color> {
+    let rgb_start = if params.len() > 4 { 2 } else { 1 };
+    let rgb_iter = params.get(rgb_start..)?.iter().copied();
+    let mut iter = iter::once(*params.first()?).chain(rgb_iter);
+    parse_sgr_color(&mut iter)
+}
+
 // Based on https://github.com/alacritty/alacritty/blob/57c4ac9145a20fb1ae9a21102503458d3da06c7b/alacritty_terminal/src/ansi.rs#L1258
 fn parse_sgr_color(params: &mut dyn Iterator<Item = u16>) -> Option<ansi_term::Color> {
     match params.next() {
@@ -291,13 +367,13 @@ mod tests {
             if *git_style_string == "normal" {
                 // This one has a different pattern
                 assert!(
-                    matches!(it.next().unwrap(), Element::Sgr(s, _, _) if s == ansi_term::Style::default())
+                    matches!(it.next().unwrap(), Element::Sgr(s, _, _, _) if s == ansi_term::Style::default())
                 );
                 assert!(
                     matches!(it.next().unwrap(), Element::Text(i, j) if &git_output[i..j] == "text")
                 );
                 assert!(
-                    matches!(it.next().unwrap(), Element::Sgr(s, _, _) if s == ansi_term::Style::default())
+                    matches!(it.next().unwrap(), Element::Sgr(s, _, _, _) if s == ansi_term::Style::default())
                 );
                 continue;
             }
@@ -305,7 +381,7 @@ mod tests {
             // First element should be a style
             let element = it.next().unwrap();
             match element {
-                Element::Sgr(style, _, _) => assert!(style::ansi_term_style_equality(
+                Element::Sgr(style, _, _, _) => assert!(style::ansi_term_style_equality(
                     style,
                     style::Style::from_git_str(git_style_string).ansi_term_style
                 )),
@@ -320,12 +396,12 @@ mod tests {
             // Third element is the reset style
             assert!(matches!(
                 it.next().unwrap(),
-                Element::Sgr(s, _, _) if s == ansi_term::Style::default()));
+                Element::Sgr(s, _, _, _) if s == ansi_term::Style::default()));
 
             // Fourth element should be a style
             let element = it.next().unwrap();
             match element {
-                Element::Sgr(style, _, _) => assert!(style::ansi_term_style_equality(
+                Element::Sgr(style, _, _, _) => assert!(style::ansi_term_style_equality(
                     style,
                     style::Style::from_git_str(git_style_string).ansi_term_style
                 )),
@@ -340,7 +416,7 @@ mod tests {
             // Sixth element is the reset style
             assert!(matches!(
                 it.next().unwrap(),
-                Element::Sgr(s, _, _) if s == ansi_term::Style::default()));
+                Element::Sgr(s, _, _, _) if s == ansi_term::Style::default()));
 
             assert!(matches!(
                 it.next().unwrap(),
@@ -362,11 +438,12 @@ mod tests {
                         foreground: Some(ansi_term::Color::Red),
                         ..ansi_term::Style::default()
                     },
+                    None,
                     0,
                     5
                 ),
                 Element::Text(5, 9),
-                Element::Sgr(ansi_term::Style::default(), 9, 12),
+                Element::Sgr(ansi_term::Style::default(), None, 9, 12),
                 Element::Text(12, 13),
             ]
         );
@@ -386,11 +463,12 @@ mod tests {
                         foreground: Some(ansi_term::Color::Red),
                         ..ansi_term::Style::default()
                     },
+                    None,
                     0,
                     5
                 ),
                 Element::Text(5, 9),
-                Element::Sgr(ansi_term::Style::default(), 9, 12),
+                Element::Sgr(ansi_term::Style::default(), None, 9, 12),
                 Element::Text(12, 16),
             ]
         );
@@ -410,11 +488,12 @@ mod tests {
                         foreground: Some(ansi_term::Color::Red),
                         ..ansi_term::Style::default()
                     },
+                    None,
                     0,
                     5
                 ),
                 Element::Text(5, 11),
-                Element::Sgr(ansi_term::Style::default(), 11, 15),
+                Element::Sgr(ansi_term::Style::default(), None, 11, 15),
             ]
         );
         assert_eq!("バー", &s[5..11]);
@@ -429,7 +508,7 @@ mod tests {
             vec![
                 Element::Csi(0, 4),
                 Element::Text(4, 8),
-                Element::Sgr(ansi_term::Style::default(), 8, 11),
+                Element::Sgr(ansi_term::Style::default(), None, 8, 11),
             ]
         );
         assert_eq!("あ.", &s[4..8]);
@@ -444,7 +523,7 @@ mod tests {
             vec![
                 Element::Csi(0, 3),
                 Element::Text(3, 7),
-                Element::Sgr(ansi_term::Style::default(), 7, 10),
+                Element::Sgr(ansi_term::Style::default(), None, 7, 10),
             ]
         );
         assert_eq!("あ.", &s[3..7]);
@@ -473,6 +552,7 @@ mod tests {
                         foreground: Some(ansi_term::Color::Fixed(4)),
                         ..ansi_term::Style::default()
                     },
+                    None,
                     0,
                     9
                 ),
@@ -481,9 +561,112 @@ mod tests {
                 Element::Text(59, 80),
                 Element::Osc(80, 86),
                 Element::Esc(86, 87),
-                Element::Sgr(ansi_term::Style::default(), 87, 91),
+                Element::Sgr(ansi_term::Style::default(), None, 87, 91),
                 Element::Text(91, 92),
             ]
         );
     }
+
+    fn first_sgr_style(s: &str) -> ansi_term::Style {
+        AnsiElementIterator::new(s)
+            .find_map(|el| match el {
+                Element::Sgr(style, _, _, _) => Some(style),
+                _ => None,
+            })
+            .unwrap()
+    }
+
+    #[test]
+    fn test_iterator_sgr_bright_background() {
+        let style = first_sgr_style("\x1b[97;104mx");
+        assert_eq!(style.foreground, Some(ansi_term::Color::Fixed(15)));
+        assert_eq!(style.background, Some(ansi_term::Color::Fixed(12)));
+    }
+
+    #[test]
+    fn test_iterator_sgr_colon_separated_colors() {
+        let style = first_sgr_style("\x1b[38:5:208;48:5:17mx");
+        assert_eq!(style.foreground, Some(ansi_term::Color::Fixed(208)));
+        assert_eq!(style.background, Some(ansi_term::Color::Fixed(17)));
+
+        let style = first_sgr_style("\x1b[38:2:1:2:3mx");
+        assert_eq!(style.foreground, Some(ansi_term::Color::RGB(1, 2, 3)));
+
+        // With a (ignored) color space id.
+        let style = first_sgr_style("\x1b[48:2::1:2:3mx");
+        assert_eq!(style.background, Some(ansi_term::Color::RGB(1, 2, 3)));
+    }
+
+    #[test]
+    fn test_iterator_sgr_reverse_conceal_strike() {
+        let style = first_sgr_style("\x1b[7;8;9mx");
+        assert!(style.is_reverse);
+        assert!(style.is_hidden);
+        assert!(style.is_strikethrough);
+
+        let style = first_sgr_style("\x1b[7;8;9;27;28;29mx");
+        assert!(!style.is_reverse);
+        assert!(!style.is_hidden);
+        assert!(!style.is_strikethrough);
+    }
+
+    #[test]
+    fn test_iterator_sgr_resets_within_sequence() {
+        let style = first_sgr_style("\x1b[1;31;42;22;39mx");
+        assert!(!style.is_bold);
+        assert_eq!(style.foreground, None);
+        assert_eq!(style.background, Some(ansi_term::Color::Green));
+
+        let style = first_sgr_style("\x1b[1;31;0;4mx");
+        assert!(!style.is_bold);
+        assert_eq!(style.foreground, None);
+        assert!(style.is_underline);
+    }
+
+    #[test]
+    fn test_iterator_sgr_unknown_params_do_not_leak() {
+        // 58;5;5 is an underline color, and the second 5 must not be read as "blink"; 53 is
+        // overline, which is valid but not representable.
+        let style = first_sgr_style("\x1b[58;5;5;53;32mx");
+        assert!(!style.is_blink);
+        assert_eq!(style.foreground, Some(ansi_term::Color::Green));
+
+        let style = first_sgr_style("\x1b[58;2;1;7;9;33mx");
+        assert!(!style.is_reverse);
+        assert!(!style.is_strikethrough);
+        assert_eq!(style.foreground, Some(ansi_term::Color::Yellow));
+
+        let style = first_sgr_style("\x1b[4:3mx");
+        assert!(style.is_underline);
+        let style = first_sgr_style("\x1b[4;4:0mx");
+        assert!(!style.is_underline);
+    }
+
+    #[test]
+    fn test_iterator_sgr_unknown_params_are_kept() {
+        let other_params = |s| {
+            AnsiElementIterator::new(s).find_map(|el| match el {
+                Element::Sgr(_, params, _, _) => {
+                    Some(params.map(|params| params.as_str().to_string()))
+                }
+                _ => None,
+            })
+        };
+        assert_eq!(other_params("\x1b[31mx"), Some(None));
+        assert_eq!(
+            other_params("\x1b[58;5;5;53;32mx"),
+            Some(Some("58;5;5;53".to_string()))
+        );
+        assert_eq!(
+            other_params("\x1b[1;58:2::1:2:3;73mx"),
+            Some(Some("58:2:0:1:2:3;73".to_string()))
+        );
+        // A reset within the sequence also resets the parameters before it.
+        assert_eq!(other_params("\x1b[53;0;31mx"), Some(None));
+        // Parameters beyond the capacity are dropped.
+        assert_eq!(
+            other_params("\x1b[58:2::100:100:100;58:2::200:200:200;53mx"),
+            Some(Some("58:2:0:100:100:100;53".to_string()))
+        );
+    }
 }
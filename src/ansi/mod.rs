@@ -1,5 +1,5 @@
 mod console_tests;
-mod iterator;
+pub mod iterator;
 
 use std::borrow::Cow;
 
@@ -8,7 +8,7 @@ use itertools::Itertools;
 use unicode_segmentation::UnicodeSegmentation;
 use unicode_width::UnicodeWidthStr;
 
-use iterator::{AnsiElementIterator, Element};
+use iterator::{AnsiElementIterator, Element, SgrParams};
 
 pub const ANSI_CSI_CLEAR_TO_EOL: &str = "\x1b[0K";
 pub const ANSI_CSI_CLEAR_TO_BOL: &str = "\x1b[1K";
@@ -16,11 +16,23 @@ pub const ANSI_SGR_BOLD: &str = "\x1b[1m";
 pub const ANSI_SGR_RESET: &str = "\x1b[0m";
 pub const ANSI_SGR_REVERSE: &str = "\x1b[7m";
 pub const ANSI_SGR_UNDERLINE: &str = "\x1b[4m";
+pub const OSC8_HYPERLINK_END: &str = "\x1b]8;;\x1b\\";
+
+// Introducers of the inline image sequences of the kitty graphics protocol (an APC sequence) and
+// of iTerm2 (an OSC 1337 sequence).
+const KITTY_GRAPHICS_INTRODUCER: &str = "\x1b_G";
+const ITERM2_INLINE_IMAGE_INTRODUCER: &str = "\x1b]1337;File=";
 
 pub fn strip_ansi_codes(s: &str) -> String {
     strip_ansi_codes_from_strings_iterator(ansi_strings_iterator(s))
 }
 
+/// Does `s` contain a terminal graphics (inline image) escape sequence? These carry large encoded
+/// payloads which must be forwarded verbatim: they cannot survive truncation or re-painting.
+pub fn contains_graphics_sequence(s: &str) -> bool {
+    s.contains(KITTY_GRAPHICS_INTRODUCER) || s.contains(ITERM2_INLINE_IMAGE_INTRODUCER)
+}
+
 pub fn measure_text_width(s: &str) -> usize {
     ansi_strings_iterator(s).fold(0, |acc, (element, is_ansi)| {
         acc + if is_ansi { 0 } else { element.width() }
@@ -45,6 +57,7 @@ fn truncate_str_impl<'a>(
     };
     let mut used = measure_text_width(&result_tail);
     let mut result = String::new();
+    let mut in_hyperlink = false;
     for (t, is_ansi) in items {
         if !is_ansi {
             for g in t.graphemes(true) {
@@ -71,10 +84,17 @@ fn truncate_str_impl<'a>(
                 used += width_of_grapheme;
             }
         } else {
+            if let Some(url) = osc8_hyperlink_url(t) {
+                in_hyperlink = !url.is_empty();
+            }
             result.push_str(t);
         }
     }
 
+    // Do not let a hyperlink whose end was truncated extend over the tail and beyond.
+    if in_hyperlink {
+        result.push_str(OSC8_HYPERLINK_END);
+    }
     result.push_str(&result_tail);
     Cow::from(result)
 }
@@ -100,17 +120,70 @@ pub fn truncate_str<'a>(s: &'a str, display_width: usize, tail: &str) -> Cow<'a,
 /// calculating the width. If a double-width ("fullwidth") grapheme has to be cut, it is omitted and
 /// the resulting string is *shorter* than `display_width`. But this way the result is always a
 /// prefix of the input `s`.
-pub fn truncate_str_short(s: &str, display_width: usize) -> Cow<str> {
+pub fn truncate_str_short(s: &str, display_width: usize) -> Cow<'_, str> {
     truncate_str_impl(s, display_width, "", None)
 }
 
+/// Return the (byte length, display width) of each grapheme of `s`, treating every ANSI escape
+/// sequence as a single zero-width unit, so that splitting `s` at one of these boundaries never
+/// breaks an escape sequence.
+pub fn grapheme_lengths_and_widths(s: &str) -> Vec<(usize, usize)> {
+    ansi_strings_iterator(s)
+        .flat_map(|(t, is_ansi)| {
+            if is_ansi {
+                vec![(t.len(), 0)]
+            } else {
+                t.graphemes(true).map(|g| (g.len(), g.width())).collect()
+            }
+        })
+        .collect()
+}
+
+/// Return the OSC 8 sequence of the hyperlink that is open at the end of `s`, given the one that
+/// is open (if any) at its start.
+pub fn open_hyperlink_at_end<'a>(s: &'a str, open: Option<&'a str>) -> Option<&'a str> {
+    let mut open = open;
+    for element in AnsiElementIterator::new(s) {
+        if let Element::Osc(i, j) = element {
+            if let Some(url) = osc8_hyperlink_url(&s[i..j]) {
+                // The OSC element does not include the final byte of an ESC \ terminator.
+                let j = if s[j..].starts_with('\\') { j + 1 } else { j };
+                open = if url.is_empty() { None } else { Some(&s[i..j]) };
+            }
+        }
+    }
+    open
+}
+
+/// If `s` is an OSC 8 hyperlink sequence, return its URL. This is empty if the sequence ends a
+/// hyperlink.
+pub fn osc8_hyperlink_url(s: &str) -> Option<&str> {
+    let rest = s.strip_prefix("\x1b]8;")?;
+    let (_params, rest) = rest.split_once(';')?;
+    let end = rest.find(['\x1b', '\x07']).unwrap_or(rest.len());
+    Some(&rest[..end])
+}
+
 pub fn parse_style_sections(s: &str) -> Vec<(ansi_term::Style, &str)> {
+    parse_style_sections_with_sgr_params(s)
+        .into_iter()
+        .map(|(style, _, text)| (style, text))
+        .collect()
+}
+
+/// The style sections of `s`, with the parameters of their styles which ansi_term cannot
+/// represent.
+pub fn parse_style_sections_with_sgr_params(
+    s: &str,
+) -> Vec<(ansi_term::Style, Option<SgrParams>, &str)> {
     let mut sections = Vec::new();
-    let mut curr_style = Style::default();
+    let mut curr_style = (Style::default(), None);
     for element in AnsiElementIterator::new(s) {
         match element {
-            Element::Text(start, end) => sections.push((curr_style, &s[start..end])),
-            Element::Sgr(style, _, _) => curr_style = style,
+            Element::Text(start, end) => {
+                sections.push((curr_style.0, curr_style.1, &s[start..end]))
+            }
+            Element::Sgr(style, params, _, _) => curr_style = (style, params),
             _ => {}
         }
     }
@@ -120,7 +193,7 @@ pub fn parse_style_sections(s: &str) -> Vec<(ansi_term::Style, &str)> {
 // Return the first CSI element, if any, as an `ansi_term::Style`.
 pub fn parse_first_style(s: &str) -> Option<ansi_term::Style> {
     AnsiElementIterator::new(s).find_map(|el| match el {
-        Element::Sgr(style, _, _) => Some(style),
+        Element::Sgr(style, _, _, _) => Some(style),
         _ => None,
     })
 }
@@ -128,7 +201,7 @@ pub fn parse_first_style(s: &str) -> Option<ansi_term::Style> {
 pub fn string_starts_with_ansi_style_sequence(s: &str) -> bool {
     AnsiElementIterator::new(s)
         .next()
-        .map(|el| matches!(el, Element::Sgr(_, _, _)))
+        .map(|el| matches!(el, Element::Sgr(_, _, _, _)))
         .unwrap_or(false)
 }
 
@@ -140,7 +213,7 @@ pub fn ansi_preserving_slice(s: &str, start: usize) -> String {
         .scan(0, |index, element| {
             // `index` is the index in non-ANSI-escape-sequence content.
             Some(match element {
-                Element::Sgr(_, a, b) => &s[a..b],
+                Element::Sgr(_, _, a, b) => &s[a..b],
                 Element::Csi(a, b) => &s[a..b],
                 Element::Esc(a, b) => &s[a..b],
                 Element::Osc(a, b) => &s[a..b],
@@ -180,7 +253,7 @@ pub fn ansi_preserving_index(s: &str, i: usize) -> Option<usize> {
 
 fn ansi_strings_iterator(s: &str) -> impl Iterator<Item = (&str, bool)> {
     AnsiElementIterator::new(s).map(move |el| match el {
-        Element::Sgr(_, i, j) => (&s[i..j], true),
+        Element::Sgr(_, _, i, j) => (&s[i..j], true),
         Element::Csi(i, j) => (&s[i..j], true),
         Element::Esc(i, j) => (&s[i..j], true),
         Element::Osc(i, j) => (&s[i..j], true),
@@ -221,8 +294,10 @@ mod tests {
 
     // Note that src/ansi/console_tests.rs contains additional test coverage for this module.
     use super::{
-        ansi_preserving_index, ansi_preserving_slice, measure_text_width, parse_first_style,
+        ansi_preserving_index, ansi_preserving_slice, contains_graphics_sequence,
+        grapheme_lengths_and_widths, measure_text_width, open_hyperlink_at_end, parse_first_style,
         string_starts_with_ansi_style_sequence, strip_ansi_codes, truncate_str, truncate_str_short,
+        OSC8_HYPERLINK_END,
     };
 
     #[test]
@@ -260,6 +335,20 @@ mod tests {
                    measure_text_width("src/ansi/modバー.rs"));
     }
 
+    #[test]
+    fn test_contains_graphics_sequence() {
+        assert!(contains_graphics_sequence(
+            "\x1b_Ga=T,f=100;iVBORw0KGgo=\x1b\\"
+        ));
+        assert!(contains_graphics_sequence(
+            "before\x1b]1337;File=inline=1:iVBORw0KGgo=\x07after"
+        ));
+        assert!(!contains_graphics_sequence(
+            "\x1b]8;;file:///a.rs\x1b\\a.rs\x1b]8;;\x1b\\"
+        ));
+        assert!(!contains_graphics_sequence("\x1b[31m-a\x1b[m"));
+    }
+
     #[test]
     fn test_parse_first_style() {
         let minus_line_from_unconfigured_git = "\x1b[31m-____\x1b[m\n";
@@ -364,4 +453,47 @@ mod tests {
         assert_eq!(truncate_str_short(double, 1), "");
         assert_eq!(truncate_str_short(double, 2), double);
     }
+
+    #[test]
+    fn test_truncate_str_closes_hyperlink() {
+        let link = "\x1b]8;;https://example.com\x1b\\example.com\x1b]8;;\x1b\\";
+        let truncated = truncate_str(link, 4, "→");
+        assert_eq!(strip_ansi_codes(&truncated), "exa→");
+        assert_eq!(
+            truncated,
+            format!("\x1b]8;;https://example.com\x1b\\exa{OSC8_HYPERLINK_END}→")
+        );
+        // Nothing is added if the hyperlink ends before the truncation point.
+        let s = format!("{link} and more");
+        assert_eq!(truncate_str(&s, 13, ""), format!("{link} a"));
+    }
+
+    #[test]
+    fn test_grapheme_lengths_and_widths() {
+        assert_eq!(grapheme_lengths_and_widths("aバ"), vec![(1, 1), (3, 2)]);
+        assert_eq!(
+            grapheme_lengths_and_widths("\x1b]8;;file:///a\x1b\\a"),
+            vec![(15, 0), (1, 0), (1, 1)]
+        );
+    }
+
+    #[test]
+    fn test_open_hyperlink_at_end() {
+        let open = "\x1b]8;;https://example.com\x1b\\";
+        assert_eq!(open_hyperlink_at_end("abc", None), None);
+        assert_eq!(open_hyperlink_at_end("abc", Some(open)), Some(open));
+        assert_eq!(
+            open_hyperlink_at_end(&format!("x{open}abc"), None),
+            Some(open)
+        );
+        assert_eq!(
+            open_hyperlink_at_end(&format!("ab{OSC8_HYPERLINK_END}c"), Some(open)),
+            None
+        );
+        let bel_terminated = "\x1b]8;;https://example.com\x07";
+        assert_eq!(
+            open_hyperlink_at_end(&format!("{bel_terminated}abc"), None),
+            Some(bel_terminated)
+        );
+    }
 }
@@ -11,13 +11,14 @@ use syntect::highlighting::Theme as SyntaxTheme;
 use syntect::parsing::SyntaxSet;
 
 use crate::ansi::{ANSI_SGR_BOLD, ANSI_SGR_RESET, ANSI_SGR_UNDERLINE};
-use crate::color::ColorMode;
+use crate::color::{ColorDepth, ColorMode};
 use crate::config::delta_unreachable;
 use crate::env::DeltaEnv;
 use crate::git_config::GitConfig;
 use crate::options;
 use crate::utils;
 use crate::utils::bat::output::PagingMode;
+use crate::utils::process::{CallingProcess, CommandLine};
 
 const TERM_FALLBACK_WIDTH: usize = 79;
 
@@ -32,6 +33,47 @@ const TERM_FALLBACK_WIDTH: usize = 79;
     max_term_width = usize::MAX,
 )]
 pub struct Opt {
+    #[arg(long = "added-keyword-styles", value_name = "RULES")]
+    /// Styles for matches of regular expressions in added lines.
+    ///
+    /// A comma-separated list of rules of the form REGEX => STYLE, applied on top of syntax
+    /// highlighting, so that markers which deserve attention in a review stand out. E.g.
+    /// --added-keyword-styles='TODO|FIXME => bold yellow, \bunsafe\b => red'. A rule of the form
+    /// KEYWORD=STYLE, e.g. 'TODO=bold yellow', matches the keyword literally, as a whole word. A
+    /// style without a background color keeps the background of the added line. The rules cannot
+    /// contain commas. See STYLES section.
+    pub added_keyword_styles: Option<String>,
+
+    #[arg(long = "amended-commit-messages")]
+    /// Highlight the changes to the message of a commit shown after a previous version of it.
+    ///
+    /// When two consecutive commits of `git show` or `git log --walk-reflogs` output have the same
+    /// author and author date, e.g. in `git show HEAD@{1} HEAD` after `git commit --amend`, the
+    /// lines of the message of the second which are not in that of the first are painted in
+    /// plus-style, with the words which changed in plus-emph-style. Consecutive commits of other
+    /// `git log` output, a commit and its parent, are never compared.
+    pub amended_commit_messages: bool,
+
+    #[arg(long = "ascii-decorations")]
+    /// Draw boxes and lines with ASCII characters.
+    ///
+    /// The boxes and lines of the decoration styles of file, hunk header and commit lines are drawn
+    /// with '-', '|' and '+' (and '=' for bold lines) instead of box-drawing characters, for
+    /// terminals and log viewers lacking them.
+    pub ascii_decorations: bool,
+
+    #[arg(
+        long = "blame-boundary-style",
+        default_value = "dim",
+        value_name = "STYLE"
+    )]
+    /// Style string for the blame metadata of boundary commits.
+    ///
+    /// Boundary commits (marked with '^' by git) are the oldest commits reached by git blame: their
+    /// lines may not really have been authored by them. The style is applied on top of the color of
+    /// the commit in --blame-palette, which is kept unless the style sets a background color.
+    pub blame_boundary_style: String,
+
     #[arg(long = "blame-code-style", value_name = "STYLE")]
     /// Style string for the code section of a git blame line.
     ///
@@ -47,9 +89,54 @@ pub struct Opt {
     )]
     /// Format string for git blame commit metadata.
     ///
-    /// Available placeholders are "{timestamp}", "{author}", and "{commit}".
+    /// Available placeholders are "{timestamp}", "{author}", "{commit}", "{ignored}" and "{path}".
+    /// "{ignored}" is empty unless the commit is ignored (see blame-ignored-style), in which case
+    /// it is "(ignored)", or "(ignored: COMMENT)" if the commit is preceded by a comment in the
+    /// file listing the ignored commits. "{path}" is the file name displayed by git blame -f (or
+    /// for lines from a renamed file), and is empty otherwise; it is affected by --relative-paths
+    /// and --hyperlinks.
     pub blame_format: String,
 
+    #[arg(long = "blame-heatmap")]
+    /// Color the blame metadata by the age of the commit.
+    ///
+    /// The metadata of the oldest commit of the blamed lines is styled with
+    /// blame-heatmap-oldest, that of the newest with blame-heatmap-newest, and that of the others
+    /// with a color in between, so that old and recently changed code stand out. The colors of
+    /// the code are still those of blame-palette.
+    pub blame_heatmap: bool,
+
+    #[arg(
+        long = "blame-heatmap-newest",
+        default_value = "#ff8700",
+        value_name = "STYLE"
+    )]
+    /// Style string for the blame metadata of the newest commit with --blame-heatmap.
+    pub blame_heatmap_newest: String,
+
+    #[arg(
+        long = "blame-heatmap-oldest",
+        default_value = "#5f87af",
+        value_name = "STYLE"
+    )]
+    /// Style string for the blame metadata of the oldest commit with --blame-heatmap.
+    pub blame_heatmap_oldest: String,
+
+    #[arg(
+        long = "blame-ignored-style",
+        default_value = "dim",
+        value_name = "STYLE"
+    )]
+    /// Style string for the blame metadata of ignored commits.
+    ///
+    /// Ignored commits are those listed in the file named by the git config option
+    /// blame.ignoreRevsFile, or else in .git-blame-ignore-revs, typically mass-reformatting commits.
+    /// Lines which git could not attribute to another commit than an ignored one (marked with '*'
+    /// by git when blame.markUnblamableLines is set) are also styled as ignored. As with
+    /// --blame-boundary-style, the style is applied on top of the color of the commit in
+    /// --blame-palette.
+    pub blame_ignored_style: String,
+
     #[arg(long = "blame-palette", value_name = "COLORS")]
     /// Background colors used for git blame lines (space-separated string).
     ///
@@ -76,6 +163,15 @@ pub struct Opt {
     /// Style string for the blame-separator-format.
     pub blame_separator_style: Option<String>,
 
+    #[arg(long = "blame-side-by-side")]
+    /// Display git blame output in two panels, instead of prefixing each line with its commit.
+    ///
+    /// The left panel shows the line numbers (see blame-separator-format) and the
+    /// syntax-highlighted code, and the right panel the metadata of the commit of each block of
+    /// lines (see blame-format), followed by the subject of the commit. The subjects are read
+    /// with `git show -s --format=%s`. The widths of the panels are set by --side-by-side-split.
+    pub blame_side_by_side: bool,
+
     #[arg(
         long = "blame-timestamp-format",
         default_value = "%Y-%m-%d %H:%M:%S %z",
@@ -89,11 +185,71 @@ pub struct Opt {
     ///
     /// This string is used for formatting the timestamps in git blame output. It must follow
     /// the `strftime` format syntax specification. If it is not present, the timestamps will
-    /// be formatted in a human-friendly but possibly less accurate form.
+    /// be formatted in a human-friendly but possibly less accurate form. In it, %x stands for a
+    /// date in the format of the locale (see --locale).
     ///
     /// See: <https://docs.rs/chrono/latest/chrono/format/strftime/index.html>
     pub blame_timestamp_output_format: Option<String>,
 
+    #[arg(long = "canonicalize-json", value_name = "GLOBS")]
+    /// Display only the meaningful changes of JSON files matching the comma-separated globs.
+    ///
+    /// E.g. '*.lock, *.pb.json'. The old and new versions of a matching file are read from the git
+    /// repository (or the new version from the working tree), and are diffed with their object
+    /// keys sorted and in a canonical layout, so that keys which were only reordered or
+    /// reformatted are not displayed. A glob containing '/' is matched against the path of the
+    /// file, and otherwise against its name; '*' matches any characters except '/', and '**'
+    /// any characters. Files which are not valid JSON, or whose versions cannot be read, are
+    /// displayed as usual.
+    pub canonicalize_json: Option<String>,
+
+    #[arg(long = "check-contrast")]
+    /// Check that the text of each style is readable on its background.
+    ///
+    /// The contrast ratio, as defined by the Web Content Accessibility Guidelines, is computed
+    /// between the foreground and background colors of each style set by the configuration, and a
+    /// warning is displayed for each below 4.5:1, the minimum for text in WCAG level AA. Where a
+    /// style leaves a color unset, the terminal's default is assumed to be black or white
+    /// according to --light or --dark, and syntax-highlighted text is taken to be in the default
+    /// foreground color of the syntax theme. Exits with status 1 if any style is unreadable. E.g.
+    /// `delta --high-contrast --check-contrast`.
+    pub check_contrast: bool,
+
+    #[arg(long = "ci")]
+    /// Use settings producing reproducible output in CI logs.
+    ///
+    /// Sets a fixed width of 120 columns, disables paging, hyperlinks and calling process
+    /// detection, draws decorations with ASCII characters and uses only the 8 standard terminal
+    /// colors, so that the output is the same on any runner and readable in web log viewers.
+    pub ci: bool,
+
+    #[arg(
+        long = "color-depth",
+        default_value = "auto",
+        value_name = "auto|24bit|256|16",
+        value_parser = ["auto", "24bit", "256", "16"],
+    )]
+    /// The number of colors supported by the terminal.
+    ///
+    /// Colors are mapped to the nearest of the colors available: 24-bit colors to those of the
+    /// 256-color palette, and, with "16", any color to one of the 16 colors of the terminal's own
+    /// palette. "auto" uses 24-bit colors as --true-color does, and otherwise 16 colors if the
+    /// terminal named by the TERM environment variable only supports those, e.g. "linux" or
+    /// "xterm-16color", and 256 colors if not.
+    pub color_depth: String,
+
+    #[arg(long = "color-moved")]
+    /// Color moved blocks of lines differently from other removed and added lines.
+    ///
+    /// Like `git diff --color-moved=zebra`, but detected by delta, so that it works with any input
+    /// and git does not need to color its output. A block of consecutive removed lines which is
+    /// also added as consecutive lines anywhere in the input (or vice versa) is moved, if it
+    /// contains at least 20 alphanumeric characters. Adjacent moved blocks are colored
+    /// alternately with moved-minus-style and moved-minus-alt-style (or moved-plus-style and
+    /// moved-plus-alt-style), to show where one block ends and the next starts. The whole input is
+    /// read before it is displayed.
+    pub color_moved: bool,
+
     #[arg(long = "color-only")]
     /// Do not alter the input structurally in any way.
     ///
@@ -101,10 +257,63 @@ pub struct Opt {
     /// intended for other tools that use delta.
     pub color_only: bool,
 
+    #[arg(long = "comment-check-command", value_name = "COMMAND")]
+    /// Command checking the spelling or style of added comment lines.
+    ///
+    /// The added lines starting with a comment marker, such as "//" or "#", are written to the
+    /// standard input of the command, one per line. Each line of its output reports a problem in
+    /// one of them, by its line number and either a column or the problematic text in backquotes,
+    /// e.g. "3:12:" or "3: `teh` -> `the`", as in the output of `typos --format brief -`. The
+    /// text is then styled with comment-check-style, so that typos are found during review rather
+    /// than in CI. E.g. --comment-check-command='typos --format brief -'.
+    pub comment_check_command: Option<String>,
+
+    #[arg(
+        long = "comment-check-style",
+        default_value = "ul",
+        value_name = "STYLE"
+    )]
+    /// Style string for the text of comments reported by comment-check-command.
+    ///
+    /// A style without a foreground or background color keeps that of the added line. See STYLES
+    /// section.
+    pub comment_check_style: String,
+
     #[arg(long = "config", default_value = "", value_name = "PATH", value_hint = ValueHint::FilePath)]
     /// Load the config file at PATH instead of ~/.gitconfig.
     pub config: String,
 
+    #[arg(long = "commit-block-format", value_name = "FMT")]
+    /// Format string condensing each commit's metadata block into a single line.
+    ///
+    /// By default, the commit, author and date lines and the commit message are displayed as git
+    /// printed them. If this is set, the whole block is replaced by one line, styled with
+    /// commit-style and commit-decoration-style. The placeholders are {hash}, {refs} (the
+    /// decorations in parentheses, as shown by git, or nothing), {author}, {email}, {date}
+    /// (relative to now, if git's date could be parsed) and {subject}. As with other format
+    /// strings, placeholders may be given a width and precision, e.g.
+    /// '{hash:.8} {date:>14} {author:<16} {subject}'.
+    pub commit_block_format: Option<String>,
+
+    #[arg(long = "commit-columns")]
+    /// Display each commit's metadata and the start of its message side by side.
+    ///
+    /// On terminals at least commit-columns-min-width columns wide, the commit, author and date
+    /// lines are displayed on the left, and as many lines of the commit message on the right; the
+    /// rest of the message follows below, as usual. Lines of the message too long for the right
+    /// column are truncated. The commit line is styled with commit-style, without
+    /// commit-decoration-style. Ignored if commit-block-format is set.
+    pub commit_columns: bool,
+
+    #[arg(
+        long = "commit-columns-min-width",
+        default_value = "140",
+        value_name = "N"
+    )]
+    /// The minimum width of the terminal (or of --width) at which commit-columns lays out commits
+    /// in two columns.
+    pub commit_columns_min_width: usize,
+
     #[arg(
         long = "commit-decoration-style",
         default_value = "",
@@ -116,6 +325,22 @@ pub struct Opt {
     /// 'ul' (underline), 'ol' (overline), or the combination 'ul ol'.
     pub commit_decoration_style: String,
 
+    #[arg(
+        long = "commit-message-code-style",
+        default_value = "yellow",
+        value_name = "STYLE"
+    )]
+    /// Style string for inline code in commit messages rendered by --commit-message-markdown.
+    pub commit_message_code_style: String,
+
+    #[arg(long = "commit-message-markdown")]
+    /// Render the bodies of commit messages as markdown.
+    ///
+    /// In `git log` and `git show` output, **bold** text is displayed in bold, `inline code` with
+    /// commit-message-code-style, list items with a bullet, and fenced code blocks are syntax
+    /// highlighted according to the language given after the opening ```.
+    pub commit_message_markdown: bool,
+
     #[arg(
         long = "commit-regex",
         default_value = r"^commit ",
@@ -131,6 +356,102 @@ pub struct Opt {
     /// output.
     pub commit_style: String,
 
+    #[arg(long = "context", value_name = "N")]
+    /// Number of lines of context around changes, when using delta to diff two files.
+    ///
+    /// Equivalent to -U<N> in --diff-args. The default is that of the differ, i.e. 3 lines.
+    pub context: Option<String>,
+
+    #[arg(long = "context-dim", default_value = "0", value_name = "FACTOR")]
+    /// Dim the syntax highlighting colors of unchanged lines by this factor.
+    ///
+    /// The foreground colors of unchanged lines are blended towards the background color of the
+    /// syntax theme, reducing their brightness and saturation, so that changed lines stand out
+    /// more. A number between 0 (no dimming, the default) and 1 (colors disappear into the
+    /// background); e.g. 0.4. This does not affect lines with an explicit zero-style foreground
+    /// color, nor syntax themes using the terminal's palette colors, such as "ansi" and "base16".
+    pub context_dim: f64,
+
+    #[arg(long = "context-fade", default_value = "0", value_name = "N")]
+    /// Dim unchanged lines progressively, the farther they are from a changed line.
+    ///
+    /// With --context-dim, the unchanged lines of a hunk are dimmed by a factor growing with their
+    /// distance from the nearest removed or added line of the hunk: from a fraction of
+    /// --context-dim next to it, to all of --context-dim at N lines or more away. This draws the
+    /// eye to the changes in diffs with many lines of context, e.g. from `git diff -U20`. 0 (the
+    /// default) dims all unchanged lines alike.
+    pub context_fade: usize,
+
+    #[arg(
+        long = "context-fold-style",
+        default_value = "dim",
+        value_name = "STYLE"
+    )]
+    /// Style string for the marker replacing unchanged lines folded by --max-context-lines.
+    ///
+    /// See STYLES section.
+    pub context_fold_style: String,
+
+    #[arg(long = "conventional-commits")]
+    /// Highlight conventional commit prefixes of commit subjects.
+    ///
+    /// In a subject such as "feat(parser)!: drop support for X", the type, the scope and the
+    /// breaking change marker are styled with conventional-commit-type-style,
+    /// conventional-commit-scope-style and conventional-commit-breaking-style.
+    /// See <https://www.conventionalcommits.org>.
+    pub conventional_commits: bool,
+
+    #[arg(
+        long = "conventional-commit-breaking-style",
+        default_value = "bold red",
+        value_name = "STYLE"
+    )]
+    /// Style string for the breaking change marker "!" of conventional commit subjects.
+    pub conventional_commit_breaking_style: String,
+
+    #[arg(
+        long = "conventional-commit-scope-style",
+        default_value = "blue",
+        value_name = "STYLE"
+    )]
+    /// Style string for the scope of conventional commit subjects, e.g. "(parser)".
+    pub conventional_commit_scope_style: String,
+
+    #[arg(
+        long = "conventional-commit-type-style",
+        default_value = "bold blue",
+        value_name = "STYLE"
+    )]
+    /// Style string for the type of conventional commit subjects, e.g. "feat" or "fix".
+    pub conventional_commit_type_style: String,
+
+    #[arg(
+        long = "coverage-covered-style",
+        default_value = "green",
+        value_name = "STYLE"
+    )]
+    /// Style string for the marker of added lines covered by tests (see --coverage-file).
+    pub coverage_covered_style: String,
+
+    #[arg(long = "coverage-file", value_name = "PATH", value_hint = ValueHint::FilePath)]
+    /// Mark the added lines covered by tests, according to a coverage file.
+    ///
+    /// The file is in the lcov tracefile format (e.g. lcov.info, as written by cargo-llvm-cov or
+    /// gcov) or in the cobertura XML format (e.g. as written by coverage.py or cargo-tarpaulin).
+    /// A column before the lines of the files it covers marks each added line which can run in
+    /// coverage-covered-style if it ran, else in coverage-uncovered-style. The paths of the
+    /// coverage file may be absolute, or relative to a directory of the repository. Only for
+    /// unified (not side-by-side) diffs.
+    pub coverage_file: Option<String>,
+
+    #[arg(
+        long = "coverage-uncovered-style",
+        default_value = "red",
+        value_name = "STYLE"
+    )]
+    /// Style string for the marker of added lines not covered by tests (see --coverage-file).
+    pub coverage_uncovered_style: String,
+
     #[arg(long = "dark")]
     /// Use default colors appropriate for a dark terminal background.
     ///
@@ -167,6 +488,57 @@ pub struct Opt {
     #[arg(long = "detect-dark-light", value_enum, default_value_t = DetectDarkLight::default())]
     pub detect_dark_light: DetectDarkLight,
 
+    #[arg(
+        long = "detect-dark-light-timeout",
+        default_value = "1000",
+        value_name = "MILLISECONDS"
+    )]
+    /// How long to wait for the terminal to report its colors.
+    ///
+    /// When detecting whether the terminal is dark or light, delta asks the terminal for its
+    /// background color (with the OSC 11 escape sequence), and uses light mode if its luminance is
+    /// high. Terminals which do not support the query are usually recognized at once, but one
+    /// which does may be slow to answer, e.g. over ssh. If it does not answer in time, the mode is
+    /// inferred from the syntax theme, as when the terminal is not queried.
+    pub detect_dark_light_timeout: u64,
+
+    #[arg(long = "deuteranopia")]
+    /// Use colors which can be told apart with red-green color blindness.
+    ///
+    /// Removed lines are shown in orange and added lines in blue, in light or dark colors according
+    /// to --light and --dark (or the detected terminal background). This suits deuteranopia and
+    /// protanopia.
+    pub deuteranopia: bool,
+
+    #[arg(long = "detect-secrets")]
+    /// Warn about secrets in added lines.
+    ///
+    /// Added lines are checked for common kinds of credentials (AWS keys, private keys, bearer,
+    /// GitHub and Slack tokens) and for random-looking strings which may be passwords or API keys.
+    /// When one is found, a warning is displayed above the changed lines containing it. See
+    /// --secret-warning-style.
+    pub detect_secrets: bool,
+
+    #[arg(long = "diagnostics-file", value_name = "PATH", value_hint = ValueHint::FilePath)]
+    /// Display the messages of a linter or compiler beneath the added lines they are about.
+    ///
+    /// The file is in the SARIF format (e.g. as written by clippy-sarif, semgrep or CodeQL), or
+    /// made of lines "PATH:LINE:COLUMN: MESSAGE", where the column is optional (e.g. as written by
+    /// gcc, clang or most linters). Each message is indented to its column, marked by '^', in
+    /// diagnostics-style. The paths may be absolute, or relative to a directory of the
+    /// repository. Only for unified (not side-by-side) diffs.
+    pub diagnostics_file: Option<String>,
+
+    #[arg(
+        long = "diagnostics-style",
+        default_value = "yellow",
+        value_name = "STYLE"
+    )]
+    /// Style string for the messages of --diagnostics-file.
+    ///
+    /// See STYLES section.
+    pub diagnostics_style: String,
+
     #[arg(
         long = "diff-args",
         short = '@',
@@ -182,6 +554,17 @@ pub struct Opt {
     /// doesn't support it, then delta will fall back to `diff` instead of `git diff`.
     pub diff_args: String,
 
+    #[arg(
+        long = "diff-algorithm",
+        value_name = "ALGORITHM",
+        value_parser = ["myers", "minimal", "patience", "histogram"]
+    )]
+    /// Diff algorithm to use when using delta to diff two files.
+    ///
+    /// Passed to `git diff` as --diff-algorithm. When delta falls back to `diff` (see diff-args),
+    /// only 'minimal' has an effect.
+    pub diff_algorithm: Option<String>,
+
     #[arg(long = "diff-highlight")]
     /// Emulate diff-highlight.
     ///
@@ -194,12 +577,41 @@ pub struct Opt {
     /// <https://github.com/so-fancy/diff-so-fancy>
     pub diff_so_fancy: bool,
 
+    #[arg(long = "diff-so-fancy-strict")]
+    /// Emulate diff-so-fancy, including the wording of its file headers.
+    ///
+    /// Like --diff-so-fancy, but files are labeled "modified:", "deleted:" etc. as in
+    /// diff-so-fancy's output.
+    pub diff_so_fancy_strict: bool,
+
     #[arg(long = "diff-stat-align-width", default_value = "48", value_name = "N")]
     /// Width allocated for file paths in a diff stat section.
     ///
     /// If a relativized file path exceeds this width then the diff stat will be misaligned.
     pub diff_stat_align_width: usize,
 
+    #[arg(long = "dry-run", requires = "setup_git")]
+    /// With --setup-git, print the settings which would be added instead of writing them.
+    pub dry_run: bool,
+
+    #[arg(long = "exclude", value_name = "GLOBS")]
+    /// Skip the files matching the comma-separated globs when comparing two directories.
+    ///
+    /// When delta is given two directories, e.g. `delta old/ new/`, it compares the files with
+    /// the same path in both, and displays a file found in only one of them as added or removed.
+    /// As in .gitignore, a glob containing "/" other than at its end, e.g. "docs/*.html", matches
+    /// paths relative to the directories, and otherwise the names of files and directories at any
+    /// depth, e.g. "*.log" or "target/". A directory matching a glob is skipped entirely.
+    pub exclude: Option<String>,
+
+    #[arg(long = "expand-renames")]
+    /// Display a header for each file renamed without changes.
+    ///
+    /// By default, consecutive files renamed without changes are displayed together, summarized by
+    /// the directories from and to which they were moved, e.g. "renamed: src/foo/** ⟶ src/bar/**
+    /// (48 files)", rather than with a header each.
+    pub expand_renames: bool,
+
     #[arg(long = "features", value_name = "FEATURES")]
     /// Names of delta features to activate (space-separated).
     ///
@@ -279,10 +691,74 @@ pub struct Opt {
     /// Sed-style command transforming file paths for display.
     pub file_regex_replacement: Option<String>,
 
+    #[arg(long = "filter", value_name = "CMD")]
+    /// Pipe the hunks of each file through CMD before displaying them.
+    ///
+    /// The hunks of each file, with their headers and without ANSI escape sequences, are the
+    /// standard input of CMD, and its standard output is displayed instead of them, so it should
+    /// be hunks of a diff too. The path of the file is in the environment variable
+    /// DELTA_FILTER_PATH. This allows transforming files before they are displayed, e.g.
+    /// decrypting them. If CMD fails, the hunks are displayed unchanged. E.g. --filter='sed
+    /// s/secret/******/'.
+    pub filter: Option<String>,
+
+    #[arg(long = "follow-color-scheme")]
+    /// Switch between light and dark colors when the terminal's color scheme changes.
+    ///
+    /// Before displaying each file, delta queries the terminal for its background color (at most
+    /// once every 2 seconds), and displays the file with the colors delta would use if it were
+    /// started with the terminal's current background, so that a long-running session (e.g. `git
+    /// log -p` over a slow connection) remains readable after the terminal switches between light
+    /// and dark mode. Ignored if either --dark or --light is specified, or with
+    /// --detect-dark-light=never. Only has an effect when delta writes directly to the terminal,
+    /// i.e. with --paging=never, since a pager would read the terminal's replies (see
+    /// --detect-dark-light).
+    pub follow_color_scheme: bool,
+
+    #[arg(long = "force", requires = "setup_git")]
+    /// With --setup-git, also replace the settings which have another value.
+    pub force: bool,
+
+    #[arg(long = "function-separators")]
+    /// Display a separator line above a hunk which is in a different function than the previous one.
+    ///
+    /// The separator is labeled with the innermost symbol (function, method, class, etc.)
+    /// containing the first line of the hunk. Symbols are read from the new version of the file in
+    /// the working tree using universal-ctags, which must be installed (`ctags` in PATH). Unlike
+    /// the code fragment of git's hunk headers, this shows the symbol the hunk is in, rather than
+    /// the last symbol defined before it. See --function-separator-style.
+    pub function_separators: bool,
+
+    #[arg(
+        long = "function-separator-style",
+        default_value = "dim",
+        value_name = "STYLE"
+    )]
+    /// Style string for the separator lines of --function-separators.
+    ///
+    /// See STYLES section.
+    pub function_separator_style: String,
+
     #[arg(long = "generate-completion")]
     /// Print completion file for the given shell.
     pub generate_completion: Option<Shell>,
 
+    #[arg(long = "github")]
+    /// Emulate the colors of GitHub's pull request diff view.
+    ///
+    /// Light or dark colors are used according to --light and --dark (or the detected terminal
+    /// background).
+    pub github: bool,
+
+    #[arg(long = "github-actions-groups")]
+    /// Make each file a collapsible group in GitHub Actions logs.
+    ///
+    /// When running in GitHub Actions (GITHUB_ACTIONS=true), the output of each file is enclosed
+    /// in ::group::PATH and ::endgroup:: workflow commands, so that the web UI of the job log
+    /// shows a collapsible list of the files. Elsewhere, and with --color-only, this option has no
+    /// effect.
+    pub github_actions_groups: bool,
+
     #[arg(long = "grep-context-line-style", value_name = "STYLE")]
     /// Style string for non-matching lines of grep output.
     ///
@@ -353,6 +829,96 @@ pub struct Opt {
     /// lines: set this option to "keep" to keep the original separator symbols.
     pub grep_separator_symbol: String,
 
+    #[arg(long = "gutter-command", value_name = "COMMAND")]
+    /// Command computing a column displayed before the lines of the diff.
+    ///
+    /// The command is run once, when the first hunk is displayed. Each line of its output gives
+    /// the cell of a line of a file, by its path and line number in the new version of the file,
+    /// followed by a tab and the text of the cell, optionally followed by a tab and a style
+    /// string: e.g. "src/lib.rs:12<TAB>✗<TAB>red". Removed lines, and lines without a cell, have a
+    /// blank cell. This can display the coverage of the lines by tests, or the problems found by
+    /// a linter, during review. Only for unified (not side-by-side) diffs.
+    pub gutter_command: Option<String>,
+
+    #[arg(long = "gutter-style", default_value = "normal", value_name = "STYLE")]
+    /// Style string for the cells of the --gutter-command column without a style of their own.
+    ///
+    /// See STYLES section.
+    pub gutter_style: String,
+
+    #[arg(long = "high-contrast")]
+    /// Use black or white text on strongly colored backgrounds.
+    ///
+    /// Syntax highlighting is turned off for the changed and unchanged lines, so that all text
+    /// meets the contrast checked by --check-contrast.
+    pub high_contrast: bool,
+
+    #[arg(long = "html")]
+    /// Render the input diff as a static HTML page, instead of displaying it.
+    ///
+    /// The output is the same as would be displayed in the terminal, with the current options,
+    /// e.g. --side-by-side, but its colors and font styles are inline CSS, and the page has the
+    /// background and foreground colors of the syntax theme. Unless --true-color is given, 24-bit
+    /// colors are used. This is intended for pasting reviewed diffs into wikis and emails, e.g.
+    /// `git diff | delta --html > diff.html`. Two files can also be given, e.g.
+    /// `delta --html file_A file_B`.
+    ///
+    /// The page can be linked to its files and lines: the header of the nth file has the anchor
+    /// #F<n>, and its unchanged and added lines have the anchors #F<n>-L<line>, by line number in
+    /// the new file, e.g. #F3-L120. With --side-by-side, only the unchanged lines have anchors.
+    /// The files are listed beside the diff, linking to their headers.
+    pub html: bool,
+
+    #[arg(long = "hunk-apply-commands")]
+    /// Display a `git apply` command below each hunk, applying that hunk alone.
+    ///
+    /// The hunk is copied, with the "---" and "+++" lines of its file, to a patch in a directory
+    /// of delta's cache directory which only the user can access, so that hunks found while
+    /// reading a long diff can be picked individually, e.g.
+    /// `git apply ~/.cache/delta/hunks/5c1e0f3b2a9d8e47.patch`. With --list-hunks, the commands are
+    /// listed with the hunks instead. See hunk-apply-command-style.
+    pub hunk_apply_commands: bool,
+
+    #[arg(
+        long = "hunk-apply-command-style",
+        default_value = "dim",
+        value_name = "STYLE"
+    )]
+    /// Style string for the commands of --hunk-apply-commands.
+    ///
+    /// See STYLES section.
+    pub hunk_apply_command_style: String,
+
+    #[arg(long = "hunk-change-density")]
+    /// Display the density of changes of each hunk in its header.
+    ///
+    /// This is a bar whose filled cells are the fraction of lines of the hunk which are added or
+    /// removed, followed by the numbers of added and removed lines, e.g. `▰▰▰▱▱▱▱▱ 12+/31-`. It
+    /// helps to decide which hunks to read carefully. The lines of each hunk are read before its
+    /// header is displayed. See hunk-change-density-plus-style and
+    /// hunk-change-density-minus-style.
+    pub hunk_change_density: bool,
+
+    #[arg(
+        long = "hunk-change-density-minus-style",
+        default_value = "red",
+        value_name = "STYLE"
+    )]
+    /// Style string for the removed lines in the change density of hunk headers.
+    ///
+    /// See hunk-change-density and the STYLES section.
+    pub hunk_change_density_minus_style: String,
+
+    #[arg(
+        long = "hunk-change-density-plus-style",
+        default_value = "green",
+        value_name = "STYLE"
+    )]
+    /// Style string for the added lines in the change density of hunk headers.
+    ///
+    /// See hunk-change-density and the STYLES section.
+    pub hunk_change_density_plus_style: String,
+
     #[arg(
         long = "hunk-header-decoration-style",
         default_value = "blue box",
@@ -401,7 +967,12 @@ pub struct Opt {
     #[arg(long = "hunk-label", default_value = "", value_name = "STRING")]
     /// Text to display before a hunk header.
     ///
-    /// Used in the default value of navigate-regex.
+    /// Different labels can be given for different file types, as comma-separated entries keyed by
+    /// file name, extension, or language, with * as the fallback, e.g. 'rs:"fn", py:"def",
+    /// md:"§", *:"•"' (in gitconfig, the double quotes must be escaped as \"). Hunks of files
+    /// matching no entry have no label. The placeholder {language} is replaced with the name of
+    /// the language used for syntax highlighting, e.g. '{language} •'. Used in the default value
+    /// of navigate-regex.
     pub hunk_label: String,
 
     #[arg(long = "hyperlinks")]
@@ -410,12 +981,18 @@ pub struct Opt {
     /// Following the hyperlink spec for terminal emulators:
     /// <https://gist.github.com/egmontkob/eb114294efbcd5adb1944c9f3cb5feda>. By default, file names
     /// and line numbers link to the local file using a file URL, whereas commit hashes link to the
-    /// commit in GitHub, if the remote repository is hosted by GitHub. See
-    /// --hyperlinks-file-link-format for full control over the file URLs emitted. Hyperlinks are
+    /// commit on the forge hosting the remote repository "origin", if it is GitHub, GitLab,
+    /// SourceHut, Codeberg or Bitbucket. For a self-hosted forge, name its software in git config,
+    /// e.g. `git config remote.origin.delta-forge gitlab` (one of github, gitlab, bitbucket,
+    /// gerrit, gitea or sourcehut). References to issues such as "#1234" in commit messages and
+    /// unchanged lines of hunks link to the issue on the forge; see --hyperlinks-issue-rules for
+    /// other references. See --hyperlinks-file-link-format for full control over the file URLs
+    /// emitted. Hyperlinks are
     /// supported by several common terminal emulators. To make them work, you must use less
     /// version >= 581 with the -R flag (or use -r with older less versions, but this will break
     /// e.g. --navigate). If you use tmux, then you will also need a patched fork of tmux (see
-    /// <https://github.com/dandavison/tmux>).
+    /// <https://github.com/dandavison/tmux>). On Windows, hyperlinks enabled by a feature are only
+    /// emitted in Windows Terminal, since the classic console cannot display them.
     pub hyperlinks: bool,
 
     #[arg(long = "hyperlinks-commit-link-format", value_name = "FMT")]
@@ -442,6 +1019,70 @@ pub struct Opt {
     /// <https://github.com/dandavison/open-in-editor> for an example.
     pub hyperlinks_file_link_format: String,
 
+    #[arg(long = "hyperlinks-issue-link-format", value_name = "FMT")]
+    /// Format string for hyperlinks of issue references such as "#1234" (requires --hyperlinks).
+    ///
+    /// The placeholder "{issue}" will be replaced by the number of the issue. By default, the
+    /// issue is that of the forge hosting the remote repository (see --hyperlinks). For example:
+    /// --hyperlinks-issue-link-format='https://bugs.example.com/show_bug.cgi?id={issue}'
+    pub hyperlinks_issue_link_format: Option<String>,
+
+    #[arg(long = "hyperlinks-issue-rules", value_name = "RULES")]
+    /// Rules hyperlinking references to issues in commit messages and unchanged lines of hunks.
+    ///
+    /// The rules are of the form 'REGEX => URL', separated by commas, in which $0 is replaced by
+    /// the reference matched by the regex, and $1, $2... by its groups. For example:
+    /// --hyperlinks-issue-rules='[A-Z]+-\d+ => https://jira.example.com/browse/$0'. A reference
+    /// is hyperlinked by the first rule matching it, before "#1234" is hyperlinked as by
+    /// --hyperlinks-issue-link-format. Requires --hyperlinks.
+    pub hyperlinks_issue_rules: Option<String>,
+
+    #[arg(long = "hyperlinks-minus-file-link-format", value_name = "FMT")]
+    /// Format string for hyperlinks of removed line numbers (requires --hyperlinks).
+    ///
+    /// Removed lines are not in the file in the working tree, so by default their line numbers are
+    /// not hyperlinks. With this option, they link to the line of the old version of the file: the
+    /// placeholders "{blob}", "{path}" and "{line}" will be replaced by the abbreviated object id
+    /// of the old version (from the "index" line of the diff header), the path of the old file in
+    /// the diff, and the line number, respectively. For example, a handler registered for a custom
+    /// URL scheme could open --hyperlinks-minus-file-link-format='git-blob://{blob}/{path}#{line}'
+    /// by running `git show <blob>` and opening the output at the line.
+    pub hyperlinks_minus_file_link_format: Option<String>,
+
+    #[arg(long = "hyperlinks-only")]
+    /// Leave the input unchanged, except for adding hyperlinks.
+    ///
+    /// The colors and layout of the input (e.g. git's own coloring) are kept, and hyperlinks are
+    /// added to the commit hashes of commit lines, the file paths of diff headers and grep output,
+    /// and the line numbers of hunk headers and grep output. The targets of the hyperlinks are as
+    /// for --hyperlinks, see hyperlinks-commit-link-format and hyperlinks-file-link-format.
+    pub hyperlinks_only: bool,
+
+    #[arg(long = "ignore-whitespace")]
+    /// Ignore whitespace when using delta to diff two files.
+    ///
+    /// Lines differing only in whitespace are not displayed as changed, as with `git diff -w`.
+    pub ignore_whitespace: bool,
+
+    #[arg(long = "inline-blame")]
+    /// Show the author and age of removed and unchanged lines in a column before them.
+    ///
+    /// For each hunk, delta runs git blame on the lines of the old file covered by the hunk, at the
+    /// parent of the commit being displayed (or at HEAD for the output of git diff). Lines which
+    /// cannot be blamed, and added lines, have a blank column. Only for unified (not side-by-side)
+    /// diffs. This runs git blame once per hunk, so it may be slow for large diffs.
+    pub inline_blame: bool,
+
+    #[arg(
+        long = "inline-blame-style",
+        default_value = "dim",
+        value_name = "STYLE"
+    )]
+    /// Style string for the --inline-blame column.
+    ///
+    /// See STYLES section.
+    pub inline_blame_style: String,
+
     #[arg(
         long = "inline-hint-style",
         default_value = "blue",
@@ -453,6 +1094,21 @@ pub struct Opt {
     /// to highlight tabs, and the symbols used to indicate wrapped lines. See STYLES section.
     pub inline_hint_style: String,
 
+    #[arg(
+        long = "input-type",
+        value_enum,
+        default_value_t = InputType::default(),
+        value_name = "TYPE"
+    )]
+    /// The type of the input, instead of the one detected.
+    ///
+    /// By default delta recognizes the lines of its input, and the process which called it, to
+    /// tell e.g. grep output from a diff. Set this when the calling process is unknown, e.g. when
+    /// delta is given saved output or output received over ssh: `diff` handles the input as a
+    /// diff only, `grep` and `blame` as the output of grep and git blame, with paths relative to
+    /// the current directory, and `raw` writes it unchanged.
+    pub input_type: InputType,
+
     #[arg(
         long = "inspect-raw-lines",
         default_value = "true",
@@ -497,6 +1153,19 @@ pub struct Opt {
     /// --width=variable is given.
     pub line_fill_method: Option<String>,
 
+    #[arg(long = "line-map", value_name = "FILE")]
+    /// With --color-only, write a map of the lines of the input to FILE.
+    ///
+    /// This is for tools which, like `git add -p`, use delta as interactive.diffFilter and rely on
+    /// each line of the output being the rendering of the line of the input with the same number.
+    /// For each line, a JSON object is written on a line of FILE, with the line number ("line"),
+    /// the kind of the line ("commit", "file-header", "hunk-header", "minus", "zero", "plus" or
+    /// "other"), and for the lines of a file's diff, the path of the file ("path") and the line
+    /// numbers of the line in the old and new versions of the file ("old_line" and "new_line"). A
+    /// file descriptor inherited from the calling process can be given as e.g. /dev/fd/3. The
+    /// file can also be set with the DELTA_LINE_MAP environment variable.
+    pub line_map: Option<String>,
+
     #[arg(short = 'n', long = "line-numbers")]
     /// Display line numbers next to the diff.
     ///
@@ -577,6 +1246,24 @@ pub struct Opt {
     /// See STYLES and LINE NUMBERS sections.
     pub line_numbers_zero_style: String,
 
+    #[arg(
+        long = "list-hunks",
+        value_name = "tsv|json",
+        num_args = 0..=1,
+        default_missing_value = "tsv",
+        value_parser = ["tsv", "json"],
+    )]
+    /// List the hunks of the input instead of displaying it.
+    ///
+    /// One record is printed per hunk: the file path, the start and length of the hunk in the old
+    /// and new file, and the byte offset and line number at which delta would render the hunk
+    /// header. The default format is tab-separated values, in that order, in which a tab, newline,
+    /// carriage return or backslash in a value is escaped as \t, \n, \r or \\; with json one
+    /// JSON object is printed per line. This is intended for building menus over a diff, e.g.
+    /// `git diff | delta --list-hunks | fzf`. With --hunk-apply-commands, each record ends with the
+    /// command applying its hunk.
+    pub list_hunks: Option<String>,
+
     #[arg(long = "list-languages")]
     /// List supported languages and associated file extensions.
     pub list_languages: bool,
@@ -585,12 +1272,97 @@ pub struct Opt {
     /// List available syntax-highlighting color themes.
     pub list_syntax_themes: bool,
 
+    #[arg(long = "locale", value_name = "LOCALE")]
+    /// Locale in which numbers and dates are formatted, e.g. 'de_DE.UTF-8'.
+    ///
+    /// It decides the separator of the groups of thousands of the numbers of diffstats and of
+    /// `delta stats`, and the date format which %x stands for in
+    /// --blame-timestamp-output-format. By default, it is that of the environment variables
+    /// LC_ALL, LC_NUMERIC (numbers) or LC_TIME (dates), and LANG. In the 'C' locale, numbers are
+    /// not grouped.
+    pub locale: Option<String>,
+
+    #[arg(long = "log-graph-style", default_value = "raw", value_name = "STYLE")]
+    /// Style string for the graph drawn by `git log --graph` to the left of commits and diffs.
+    ///
+    /// When the input starts with a commit line with a graph, the graph is removed from each line,
+    /// so that the commits and diffs are displayed as usual, and drawn again to the left of the
+    /// lines displaying them. The default, raw, keeps the colors of git. In side-by-side mode,
+    /// the lines are truncated so that the graph does not make them exceed the width.
+    pub log_graph_style: String,
+
     #[arg(long = "map-styles", value_name = "STYLES_MAP")]
     /// Map styles encountered in raw input to desired output styles.
     ///
     /// An example is --map-styles='bold purple => red "#eeeeee", bold cyan => syntax "#eeeeee"'
     pub map_styles: Option<String>,
 
+    #[arg(long = "mark-reviewed", value_name = "FILE")]
+    /// Mark the file as reviewed, until it changes again.
+    ///
+    /// The file's current contents and the commit checked out are recorded in
+    /// .git/delta/reviewed. As long as the file's contents in a diff are those recorded, its
+    /// header is dimmed and reads e.g. "src/lib.rs ✓ reviewed at 1a2b3c4", so that the files
+    /// remaining to review stand out when reviewing a long-running branch with e.g. `git diff
+    /// main...`. This can be run from the pager showing the diff, e.g. with `!delta
+    /// --mark-reviewed src/lib.rs` in less.
+    pub mark_reviewed: Option<String>,
+
+    #[arg(long = "max-buffer-lines", default_value = "0", value_name = "N")]
+    /// Never hold more than N lines of the input before displaying them.
+    ///
+    /// Delta holds lines back to pair removed lines with added ones (see --line-buffer-size), to
+    /// fold unchanged lines (--max-context-lines), and reads lines ahead of those it displays for
+    /// --hunk-change-density and --threads. With a large input such as the `git log -p` of a big
+    /// repository, this can delay the first screenful. With N greater than zero, the held lines
+    /// are displayed as soon as there are N of them, and at most N lines are read ahead: the
+    /// change density of longer hunks is not displayed. --filter and --canonicalize-json, which
+    /// replace whole hunks, are not limited. Set to zero (the default) for no limit.
+    pub max_buffer_lines: usize,
+
+    #[arg(long = "max-code-line-warn", default_value = "0", value_name = "N")]
+    /// Mark the part of added lines beyond column N.
+    ///
+    /// The characters of an added line after its first N columns are styled with
+    /// max-code-line-warn-style, so that the lines exceeding a line-length policy stand out, and
+    /// where the limit falls within them is visible. Tabs count as expanded, see --tabs. Set to
+    /// zero (the default) to mark no lines.
+    pub max_code_line_warn: usize,
+
+    #[arg(
+        long = "max-code-line-warn-style",
+        default_value = "ul",
+        value_name = "STYLE"
+    )]
+    /// Style string for the part of added lines beyond column max-code-line-warn.
+    ///
+    /// A style without a foreground or background color keeps that of the added line. See STYLES
+    /// section.
+    pub max_code_line_warn_style: String,
+
+    #[arg(long = "max-context-lines", default_value = "0", value_name = "N")]
+    /// Fold runs of unchanged lines, keeping at most N lines next to each change.
+    ///
+    /// This is useful with diffs showing a lot of context, e.g. `git diff -U20` or `git diff
+    /// --function-context`: the unchanged lines in between the N lines after a change and the N
+    /// lines before the next change of a hunk are replaced by a marker stating how many lines were
+    /// folded, see --context-fold-style. Set to zero (the default) to display all unchanged lines.
+    pub max_context_lines: usize,
+
+    #[arg(
+        long = "max-highlight-length",
+        default_value = "3000",
+        value_name = "N"
+    )]
+    /// Do not highlight lines longer than this.
+    ///
+    /// Lines with more than this many characters are displayed without syntax highlighting and
+    /// without within-line (emph) highlighting of their changes, which would be slow for very long
+    /// lines. Together with max-line-length this makes it possible to display such lines in full
+    /// but cheaply, e.g. with max-line-length = 0 and max-highlight-length = 1000. To highlight lines
+    /// of any length, set to zero.
+    pub max_highlight_length: usize,
+
     #[arg(long = "max-line-distance", default_value = "0.6", value_name = "DIST")]
     /// Maximum line pair distance parameter in within-line diff algorithm.
     ///
@@ -613,8 +1385,9 @@ pub struct Opt {
     #[arg(long = "max-line-length", default_value = "3000", value_name = "N")]
     /// Truncate lines longer than this.
     ///
-    /// To prevent any truncation, set to zero. When wrapping lines this does nothing as it is
-    /// overwritten to fit at least all visible characters, see `--wrap-max-lines`.
+    /// To prevent any truncation, set to zero; see also max-highlight-length. When wrapping lines
+    /// this does nothing as it is overwritten to fit at least all visible characters, see
+    /// `--wrap-max-lines`.
     pub max_line_length: usize,
 
     #[arg(
@@ -682,6 +1455,23 @@ pub struct Opt {
     /// STYLES section.
     pub merge_conflict_theirs_diff_header_style: String,
 
+    #[arg(
+        long = "merge-parent-style",
+        default_value = "raw",
+        value_name = "STYLE"
+    )]
+    /// Style string for the parent commits listed by the "Merge:" line of a merge commit.
+    ///
+    /// See STYLES section. With --hyperlinks, the parent commits are also hyperlinked.
+    pub merge_parent_style: String,
+
+    #[arg(long = "merge-parent-subjects")]
+    /// List the subject of each parent commit of a merge commit.
+    ///
+    /// The subjects are read with `git show -s --format=%s`, and so are only displayed when the
+    /// commits exist in the repository delta is run in.
+    pub merge_parent_subjects: bool,
+
     #[arg(
         long = "minus-empty-line-marker-style",
         default_value = "normal auto",
@@ -722,23 +1512,170 @@ pub struct Opt {
     /// See STYLES section.
     pub minus_style: String,
 
+    #[arg(
+        long = "missing-newline-marker",
+        default_value = "⏎∅",
+        value_name = "STRING"
+    )]
+    /// Text displayed after a line which is not terminated by a newline.
+    ///
+    /// This replaces the "\\ No newline at end of file" line of the diff. When the only change in a
+    /// hunk is that a newline was added or removed at the end of the file, the line is displayed
+    /// once, followed by a notice. Set to an empty string to display the diff's line unaltered.
+    pub missing_newline_marker: String,
+
+    #[arg(
+        long = "missing-newline-marker-style",
+        default_value = "normal",
+        value_name = "STYLE"
+    )]
+    /// Style string for the missing newline marker.
+    ///
+    /// A style without a foreground or background color keeps that of the line. See STYLES
+    /// section.
+    pub missing_newline_marker_style: String,
+
+    #[arg(
+        long = "moved-minus-alt-style",
+        default_value = "bold blue",
+        value_name = "STYLE"
+    )]
+    /// Style string for removed lines of moved blocks adjacent to a moved block.
+    ///
+    /// See --color-moved and the STYLES section.
+    pub moved_minus_alt_style: String,
+
+    #[arg(
+        long = "moved-minus-style",
+        default_value = "bold magenta",
+        value_name = "STYLE"
+    )]
+    /// Style string for removed lines of moved blocks.
+    ///
+    /// See --color-moved and the STYLES section.
+    pub moved_minus_style: String,
+
+    #[arg(
+        long = "moved-plus-alt-style",
+        default_value = "bold yellow",
+        value_name = "STYLE"
+    )]
+    /// Style string for added lines of moved blocks adjacent to a moved block.
+    ///
+    /// See --color-moved and the STYLES section.
+    pub moved_plus_alt_style: String,
+
+    #[arg(
+        long = "moved-plus-style",
+        default_value = "bold cyan",
+        value_name = "STYLE"
+    )]
+    /// Style string for added lines of moved blocks.
+    ///
+    /// See --color-moved and the STYLES section.
+    pub moved_plus_style: String,
+
     #[arg(long = "navigate")]
     /// Activate diff navigation.
     ///
-    /// Use n to jump forwards and N to jump backwards. To change the file labels used see
+    /// Use n to jump forwards and N to jump backwards, and } and { to jump to the next and previous
+    /// file (see --navigate-keymap). To change the file labels used see
     /// --file-added-label, --file-copied-label, --file-modified-label, --file-removed-label, --file-renamed-label.
     pub navigate: bool,
 
+    #[arg(
+        long = "navigate-keymap",
+        default_value = "n=next-hunk N=previous-hunk }=next-file {=previous-file",
+        value_name = "BINDINGS"
+    )]
+    /// Keys of less jumping between the stop points of diff navigation.
+    ///
+    /// When delta starts less with --navigate, it passes less a lesskey file binding each KEY of
+    /// the space-separated KEY=ACTION bindings to a search for the next or previous stop point:
+    /// next-hunk and previous-hunk search for --navigate-regex, which matches commits, files and
+    /// hunks, and next-file and previous-file for the commits and files only. A key may be several
+    /// characters, e.g. ']f'. Set to an empty string to leave the keys of less unchanged. This
+    /// requires less 582 or later.
+    pub navigate_keymap: String,
+
+    #[arg(long = "navigate-prefill-search")]
+    /// Make n and N search for navigation stop points as soon as less starts.
+    ///
+    /// The navigate regex is passed to less as its initial search pattern (without moving away from
+    /// the top of the output), so that n and N work before any search has been typed. less then
+    /// highlights the stop points from the start. Only used with --navigate.
+    pub navigate_prefill_search: bool,
+
     #[arg(long = "navigate-regex", value_name = "REGEX")]
     /// Regular expression defining navigation stop points.
     pub navigate_regex: Option<String>,
 
+    #[arg(long = "no-bat-cache")]
+    /// Do not use syntax definitions and themes from bat's cache.
+    ///
+    /// By default delta loads the user syntaxes and themes built with `bat cache --build`, picking
+    /// up changes the next time it runs. As for bat, the cache is in the directory of the
+    /// BAT_CACHE_PATH environment variable if it is set, and otherwise e.g. in ~/.cache/bat. The
+    /// cache is ignored anyway if it was built by an incompatible version of bat.
+    pub no_bat_cache: bool,
+
     #[arg(long = "no-gitconfig")]
     /// Do not read any settings from git config.
     ///
     /// See GIT CONFIG section.
     pub no_gitconfig: bool,
 
+    #[arg(long = "no-process-detection")]
+    /// Do not inspect the process which called delta.
+    ///
+    /// By default delta looks at the command line of its parent process, e.g. to know the paths in
+    /// git grep output are relative to the current directory, or which file git show displays. With
+    /// this option the output depends only on the input and the options.
+    pub no_process_detection: bool,
+
+    #[arg(long = "no-style")]
+    /// Write the input unchanged, byte for byte, except for the transformations requested.
+    ///
+    /// No colors, decorations or layout are added, so that the output can be parsed by other
+    /// programs just as the input could be, e.g. in scripted pipelines. The only transformations
+    /// are those of --hyperlinks, which adds hyperlinks as --hyperlinks-only does, and
+    /// --line-numbers, which prefixes each line of a hunk with its line numbers in the old and new
+    /// file, e.g. "  12   13 │". See --no-style-verify.
+    pub no_style: bool,
+
+    #[arg(long = "no-style-verify")]
+    /// Check that the input is recovered from the output of --no-style.
+    ///
+    /// Each line of output is checked to give back its line of input once its hyperlinks and line
+    /// numbers are removed. A line for which it does not, e.g. because the input already contains
+    /// hyperlinks, is written unchanged, with a warning (an error with --strict-warnings).
+    pub no_style_verify: bool,
+
+    #[arg(long = "offline")]
+    /// Do not fetch data from the network.
+    ///
+    /// Features which display data fetched from forges, such as --pull-request-titles, use the
+    /// data cached by previous runs of delta, in delta's cache directory (e.g. ~/.cache/delta on
+    /// Linux), however old, and otherwise omit it. Without --offline, cached data is fetched again
+    /// after a day.
+    pub offline: bool,
+
+    #[arg(
+        long = "output-format",
+        value_enum,
+        default_value_t = OutputFormat::default(),
+        value_name = "FORMAT"
+    )]
+    /// The format of the output.
+    ///
+    /// `terminal` displays the diff with ANSI escape sequences, `html` renders it as an HTML page
+    /// (see --html), and `html-email` as HTML for the body of an email, e.g.
+    /// `git format-patch --stdout | delta --output-format=html-email`. Mail clients ignore
+    /// stylesheets and much of CSS, so its lines are the rows of a table, with the styles of each
+    /// span of text inline. The HTML is 7-bit ASCII with short lines, so that it is unchanged by
+    /// quoted-printable encoding.
+    pub output_format: OutputFormat,
+
     #[arg(long = "pager", value_name = "CMD")]
     /// Which pager to use.
     ///
@@ -755,7 +1692,9 @@ pub struct Opt {
     )]
     /// Whether to use a pager when displaying output.
     ///
-    /// Options are: auto, always, and never.
+    /// Options are: auto, always, and never. With auto, no pager is used when the output is not
+    /// going to a terminal and GIT_PAGER_IN_USE is set, since the wrapping tool (e.g. tig or
+    /// magit) is then paging the output itself. In that case the width is taken from COLUMNS.
     pub paging_mode: String,
 
     #[arg(long = "parse-ansi")]
@@ -765,6 +1704,79 @@ pub struct Opt {
     /// This can be used to help identify input style strings to use with map-styles.
     pub parse_ansi: bool,
 
+    #[arg(
+        long = "patch-applying-style",
+        default_value = "bold",
+        value_name = "STYLE"
+    )]
+    /// Style string for the "Applying: <subject>" lines of git am.
+    ///
+    /// See STYLES section.
+    pub patch_applying_style: String,
+
+    #[arg(
+        long = "patch-failure-style",
+        default_value = "bold red",
+        value_name = "STYLE"
+    )]
+    /// Style string for messages reporting that a patch failed to apply.
+    ///
+    /// This styles the error messages of git am and git apply (e.g. "error: patch failed:
+    /// src/main.rs:12"), and the references to rejected hunks and .rej files of git apply --reject
+    /// and patch. The file referred to by such a message is styled with file-style, and hyperlinked
+    /// (to the line at which the patch failed, if known) when hyperlinks is enabled. See STYLES
+    /// section.
+    pub patch_failure_style: String,
+
+    #[arg(long = "path-rewrite", value_name = "RULES")]
+    /// Rules transforming file paths for display, of the form 'REGEX => REPLACEMENT, ...'.
+    ///
+    /// The rules are applied in order to the file paths of diff headers, hunk headers and grep
+    /// output, after file-transformation. The first match of the regex of each rule is replaced,
+    /// and the replacement may refer to capture groups as $1 or ${name}. This can be used to
+    /// shorten deeply nested paths, e.g. --path-rewrite='^services/(\w+)/src/main/java/com/corp/ =>
+    /// $1/…/'. Hyperlinks still target the original paths, unless path-rewrite-hyperlinks is set.
+    pub path_rewrite: Option<String>,
+
+    #[arg(long = "path-rewrite-hyperlinks")]
+    /// Apply the path-rewrite rules to the file paths targeted by hyperlinks too.
+    ///
+    /// This is useful when the rules map paths to the location of the files on this machine.
+    pub path_rewrite_hyperlinks: bool,
+
+    #[arg(long = "path-strip", default_value = "0", value_name = "N")]
+    /// Remove N leading components from the file paths of diff headers, like `patch -pN`.
+    ///
+    /// This is useful when displaying patches which were generated in a different directory
+    /// layout. The stripped paths are used for the file headers, hyperlinks and navigate.
+    /// Paths with fewer components are left unchanged.
+    pub path_strip: usize,
+
+    #[arg(
+        long = "pick",
+        value_name = "file|hunk",
+        num_args = 0..=1,
+        default_missing_value = "file",
+        value_parser = ["file", "hunk"],
+    )]
+    /// Choose files (or hunks) of the input with an interactive picker, and display only those.
+    ///
+    /// The picker is the command in the DELTA_PICKER environment variable, fzf by default. It
+    /// receives one line per file or hunk on stdin, and should print the chosen lines. With fzf or
+    /// sk, the chosen section is previewed as rendered by delta, with the other options given on
+    /// the command line, and several sections can be chosen. Example usage:
+    /// `git log -p | delta --pick=hunk`.
+    pub pick: Option<String>,
+
+    #[arg(long = "pick-theme")]
+    /// Choose a syntax theme, or a feature setting delta's colors, with an interactive picker.
+    ///
+    /// The diff on standard input (or a sample diff) is previewed as rendered with the selected
+    /// theme, and the chosen theme is written to your global gitconfig, as delta.syntax-theme or
+    /// in delta.features. The picker is the command in the DELTA_PICKER environment variable, fzf
+    /// by default; the preview requires fzf or sk. E.g. `git show | delta --pick-theme`.
+    pub pick_theme: bool,
+
     #[arg(
         long = "plus-emph-style",
         default_value = "syntax auto",
@@ -805,12 +1817,84 @@ pub struct Opt {
     /// See STYLES section.
     pub plus_style: String,
 
+    #[arg(long = "preprocess", value_name = "CMD")]
+    /// Command normalizing both files before they are diffed, when using delta to diff two files.
+    ///
+    /// Each file is piped through the command, e.g. 'jq -S .' or 'rustfmt', and the outputs are
+    /// diffed instead of the files, so that differences in formatting alone are not displayed. The
+    /// diff is still labeled with the paths of the files.
+    pub preprocess: Option<String>,
+
+    #[arg(long = "pull-request-titles")]
+    /// Display the titles of the issues and pull requests referenced in commit messages.
+    ///
+    /// A reference such as "#123", e.g. in the subject "Fix the parser (#123)", is followed by the
+    /// title of the issue or pull request, fetched from the API of the forge of the repository's
+    /// remote (GitHub, GitLab or Codeberg). This requires curl. The titles are cached in delta's
+    /// cache directory; see --offline. So that a slow network does not hold up the output, the
+    /// fetches of a run of delta take at most 10 seconds in total, and nothing more is fetched
+    /// after a fetch fails; titles which are not fetched are omitted.
+    pub pull_request_titles: bool,
+
+    #[arg(
+        long = "pull-request-title-style",
+        default_value = "dim",
+        value_name = "STYLE"
+    )]
+    /// Style string for the titles displayed by --pull-request-titles.
+    ///
+    /// See STYLES section.
+    pub pull_request_title_style: String,
+
+    #[arg(long = "quiet")]
+    /// Do not display warnings.
+    ///
+    /// Warnings, e.g. of an unknown feature or syntax theme, are displayed on stderr; see
+    /// --strict-warnings and --warnings-format.
+    pub quiet: bool,
+
+    #[arg(
+        long = "range-diff-header-style",
+        default_value = "bold",
+        value_name = "STYLE"
+    )]
+    /// Style string for the header lines of pairs of commits in `git range-diff` output.
+    ///
+    /// E.g. "2:  1b2c3d4 ! 2:  5e6f7a8 Subject". See STYLES section.
+    pub range_diff_header_style: String,
+
     #[arg(long = "raw")]
     /// Do not alter the input in any way.
     ///
     /// This is mainly intended for testing delta.
     pub raw: bool,
 
+    #[arg(long = "redact-secrets")]
+    /// Replace secrets in the lines of hunks by a token.
+    ///
+    /// AWS keys, the contents of private key blocks, bearer tokens and GitHub and Slack tokens are
+    /// replaced by "████ REDACTED ████", styled with redacted-style, so that the output can be
+    /// shared, e.g. during a screen share, without them. See --redact-secrets-regex to redact other
+    /// secrets. Note that the secrets are not removed from file paths and commit messages.
+    pub redact_secrets: bool,
+
+    #[arg(long = "redact-secrets-regex", value_name = "REGEX")]
+    /// Regular expression matching further secrets to redact with --redact-secrets.
+    ///
+    /// If the regular expression has a group named "secret", only the text it matches is redacted,
+    /// e.g. --redact-secrets-regex='password\s*=\s*"(?P<secret>[^"]+)"'.
+    pub redact_secrets_regex: Option<String>,
+
+    #[arg(
+        long = "redacted-style",
+        default_value = "bold white black",
+        value_name = "STYLE"
+    )]
+    /// Style string for the token replacing secrets with --redact-secrets.
+    ///
+    /// See STYLES section.
+    pub redacted_style: String,
+
     #[arg(long = "relative-paths")]
     /// Output all file paths relative to the current directory.
     ///
@@ -823,6 +1907,36 @@ pub struct Opt {
     /// For example, a unified diff heading, a rename, or a chmod.
     pub right_arrow: String,
 
+    #[arg(
+        long = "secret-warning-style",
+        default_value = "bold white red",
+        value_name = "STYLE"
+    )]
+    /// Style string for the warning displayed by --detect-secrets.
+    ///
+    /// See STYLES section.
+    pub secret_warning_style: String,
+
+    #[cfg(feature = "self-update")]
+    #[arg(long = "self-update")]
+    /// Update delta to the latest release.
+    ///
+    /// The release artifact for this platform is downloaded from GitHub, its checksum is verified,
+    /// and it replaces the delta executable. This requires curl, tar and sha256sum (shasum on
+    /// macOS, PowerShell on Windows).
+    pub self_update: bool,
+
+    #[arg(long = "setup-git")]
+    /// Add the settings recommended for using delta to your global gitconfig.
+    ///
+    /// These are core.pager, interactive.diffFilter, merge.conflictStyle and diff.colorMoved.
+    /// Settings which are not set are added. Those which already have the recommended value, or
+    /// already run delta (e.g. core.pager = delta --side-by-side), are left unchanged. Those set to
+    /// another value are listed and kept, unless --force is given, in which case they are replaced
+    /// and their previous value is reported. Use --dry-run to see the settings which would be
+    /// added, in gitconfig syntax, without changing anything.
+    pub setup_git: bool,
+
     #[arg(long = "show-colors")]
     /// Show available named colors.
     ///
@@ -860,6 +1974,157 @@ pub struct Opt {
     /// Display diffs in side-by-side layout.
     pub side_by_side: bool,
 
+    #[arg(long = "side-by-side-center-gutter")]
+    /// In side-by-side mode, show change markers in a column between the panels.
+    ///
+    /// As in sdiff, '|' marks a changed line, '<' a removed line and '>' an added line. This makes
+    /// changes easier to spot when the background colors of removed and added lines are hard to
+    /// tell apart.
+    pub side_by_side_center_gutter: bool,
+
+    #[arg(
+        long = "side-by-side-center-gutter-style",
+        default_value = "bold",
+        value_name = "STYLE"
+    )]
+    /// Style string for the change markers of side-by-side-center-gutter.
+    ///
+    /// See STYLES section.
+    pub side_by_side_center_gutter_style: String,
+
+    #[arg(long = "side-by-side-center-line-numbers")]
+    /// In side-by-side mode, show the line numbers of both panels in one column between them.
+    ///
+    /// Instead of a line number column at the start of each panel, the old and new line numbers
+    /// are shown next to each other between the panels, e.g. '12│13', which takes less
+    /// horizontal space. With --side-by-side-center-gutter, the change marker separates them. With
+    /// --hyperlinks, both numbers link to the new file: the number of a removed line to where it
+    /// was removed.
+    pub side_by_side_center_line_numbers: bool,
+
+    #[arg(
+        long = "side-by-side-order",
+        default_value = "minus-first",
+        value_name = "ORDER"
+    )]
+    /// Which side-by-side panel is on the left: "minus-first" or "plus-first".
+    ///
+    /// With "plus-first", the new version of the lines is on the left, and the change markers of
+    /// --side-by-side-center-gutter point the other way. Each panel keeps its line number format,
+    /// e.g. --line-numbers-left-format is that of the minus panel.
+    pub side_by_side_order: String,
+
+    #[arg(
+        long = "side-by-side-split",
+        default_value = "50:50",
+        value_name = "LEFT:RIGHT"
+    )]
+    /// Proportions of the width of the left and right side-by-side panels.
+    ///
+    /// For example, "30:70" gives 30% of the width to the left panel and 70% to the right one.
+    pub side_by_side_split: String,
+
+    #[arg(long = "split-hunks", default_value = "0", value_name = "N")]
+    /// Subdivide hunks longer than N lines at blank lines.
+    ///
+    /// After a blank line which follows at least N lines of a hunk (or of the previous part of
+    /// it), a thin separator labeled with the line number of the next line is displayed, see
+    /// --split-hunks-style. A hunk is only split at unchanged blank lines, and at added (or
+    /// removed) blank lines which are not paired with removed (or added) lines, so that the
+    /// highlighting of changes within lines is unaffected. Set to zero (the default) to display
+    /// hunks without separators.
+    pub split_hunks: usize,
+
+    #[arg(
+        long = "split-hunks-style",
+        default_value = "dim",
+        value_name = "STYLE"
+    )]
+    /// Style string for the separator lines of --split-hunks.
+    ///
+    /// See STYLES section.
+    pub split_hunks_style: String,
+
+    #[arg(long = "split-output", value_name = "DIR")]
+    /// Render each commit of the input to its own file in DIR, instead of displaying the input.
+    ///
+    /// This is for archiving or publishing changes, e.g. those of a release with
+    /// `git log -p v1.0..v1.1 | delta --split-output=changes --output-format=html`. The files are
+    /// named by the abbreviated hash and the subject of their commit, e.g.
+    /// changes/1a2b3c4d5e6f-fix-the-parser.html, and are in the format of --output-format: HTML
+    /// pages, HTML for email, or delta's output with ANSI escape sequences, in a .txt file. The
+    /// path of each file is printed as it is written.
+    pub split_output: Option<String>,
+
+    #[arg(long = "stats")]
+    /// Report the number of changed lines in the input diff, instead of displaying it.
+    ///
+    /// The added and removed lines are totalled per directory, per file extension and per language,
+    /// and the files with the most changes are listed. E.g. `git diff main | delta --stats`.
+    pub stats: bool,
+
+    #[arg(
+        long = "strip-cr",
+        default_value = "auto",
+        value_name = "auto|always|never",
+        value_parser = ["auto", "always", "never"],
+    )]
+    /// Whether to remove carriage returns from input lines.
+    ///
+    /// With auto, a carriage return ending a line (e.g. from a file with Windows line endings) is
+    /// removed; where a removed line and the added line paired with it differ only in whether
+    /// they have one, it is shown as ␍ so that the change is visible. With always, all carriage
+    /// returns are removed and never marked, and with never, input lines are left unchanged.
+    pub strip_cr: String,
+
+    #[arg(long = "strict-warnings")]
+    /// Treat warnings as errors.
+    ///
+    /// The first warning is displayed as an error, even with --quiet, and delta exits with status
+    /// 2. This is intended for checking a configuration of delta, e.g. in CI.
+    pub strict_warnings: bool,
+
+    #[arg(long = "strip-path-prefix", value_name = "PATH")]
+    /// Remove this directory from the start of the file paths displayed in diff headers.
+    ///
+    /// For example, with `git diff --no-index /home/user/a /home/user/b`,
+    /// --strip-path-prefix=/home/user displays the files as a/... and b/....
+    pub strip_path_prefix: Option<String>,
+
+    #[arg(
+        long = "summary-create-style",
+        default_value = "green",
+        value_name = "STYLE"
+    )]
+    /// Style string for the "create mode" lines of git's --summary output.
+    pub summary_create_style: String,
+
+    #[arg(
+        long = "summary-delete-style",
+        default_value = "red",
+        value_name = "STYLE"
+    )]
+    /// Style string for the "delete mode" lines of git's --summary output.
+    pub summary_delete_style: String,
+
+    #[arg(
+        long = "summary-similarity-style",
+        default_value = "dim",
+        value_name = "STYLE"
+    )]
+    /// Style string for the similarity index of renames and copies in git's --summary output.
+    pub summary_similarity_style: String,
+
+    #[arg(long = "syntax-highlight-changes-only")]
+    /// Syntax-highlight only the changed parts of lines.
+    ///
+    /// Unchanged lines, and the unchanged parts of changed lines, are displayed in the terminal's
+    /// default foreground color, while the emphasized (changed) parts of changed lines are
+    /// syntax-highlighted. This is the inverse of the default, and makes it easy to spot the
+    /// changes in a long line. Explicit foreground colors of zero-style, minus-emph-style,
+    /// minus-non-emph-style, plus-emph-style and plus-non-emph-style are kept.
+    pub syntax_highlight_changes_only: bool,
+
     #[arg(long = "syntax-theme", value_name = "SYNTAX_THEME")]
     /// The syntax-highlighting theme to use.
     ///
@@ -877,6 +2142,34 @@ pub struct Opt {
     /// incorrect.
     pub tab_width: usize,
 
+    #[arg(long = "threads", default_value = "1", value_name = "N")]
+    /// The number of threads highlighting the syntax of hunks.
+    ///
+    /// Highlighting the syntax of a large diff takes most of the time delta spends on it. With N
+    /// greater than 1, N threads highlight the hunks of the lines read ahead of those displayed,
+    /// so that the hunks are highlighted in parallel, and the output is unchanged. Use 0 for the
+    /// number of CPUs. At most 64 threads are started. Since delta then reads thousands of lines before displaying them, this is
+    /// intended for large diffs which are not read as they are produced.
+    pub threads: usize,
+
+    #[arg(long = "tree-sitter-tokenization")]
+    /// Split changed lines into the tokens of their language to highlight the changes within them.
+    ///
+    /// By default, the words of changed lines are the matches of --word-diff-regex, and other
+    /// characters are compared one at a time. With this option, lines of Go, JavaScript, Python
+    /// and Rust are parsed with tree-sitter grammars, so that e.g. identifiers, numbers and
+    /// operators such as "::" and "+=" are compared as a whole, and only the text of strings and
+    /// comments is split into words. This requires delta to be built with the tree-sitter
+    /// feature; otherwise, and for other languages, the option has no effect.
+    pub tree_sitter_tokenization: bool,
+
+    #[arg(long = "tritanopia")]
+    /// Use colors which can be told apart with blue-yellow color blindness.
+    ///
+    /// Removed lines are shown in red and added lines in teal, in light or dark colors according to
+    /// --light and --dark (or the detected terminal background).
+    pub tritanopia: bool,
+
     #[arg(
         long = "true-color",
         default_value = "auto",
@@ -892,6 +2185,27 @@ pub struct Opt {
     /// need to do anything.
     pub true_color: String,
 
+    #[arg(long = "watch")]
+    /// Diff the two files again whenever either of them changes.
+    ///
+    /// When using delta to diff two files or directories, the diff is displayed without a pager,
+    /// and redisplayed on a cleared screen each time a file is modified, until delta is
+    /// interrupted with Ctrl-C. The files are checked for modifications twice a second.
+    pub watch: bool,
+
+    #[arg(
+        long = "warnings-format",
+        default_value = "text",
+        value_name = "FORMAT"
+    )]
+    /// The format of the warnings displayed on stderr: text or json.
+    ///
+    /// With json, each warning is a line holding a JSON object, e.g.
+    /// {"level":"warning","kind":"unknown-option","message":"..."}, so that programs running
+    /// delta can display its warnings themselves. The kinds are unknown-option, invalid-value,
+    /// assets, command and system.
+    pub warnings_format: String,
+
     #[arg(
         long = "whitespace-error-style",
         default_value = "auto auto",
@@ -910,6 +2224,10 @@ pub struct Opt {
     /// has a variable holding the value "74"). Use --width=variable to extend decorations and
     /// background colors to the end of the text only. Otherwise background colors extend to the
     /// full terminal width.
+    ///
+    /// Without a width fixed by this option, when delta writes to the terminal without a pager
+    /// (--paging=never), the layout of each file follows the terminal's width as it is resized.
+    /// It adapts to at most 8 different widths, after which new widths keep the current layout.
     pub width: Option<String>,
 
     #[arg(long = "word-diff-regex", default_value = r"\w+", value_name = "REGEX")]
@@ -920,6 +2238,16 @@ pub struct Opt {
     /// --max-line-distance=1.0 (this is more similar to `git --word-diff`).
     pub tokenization_regex: String,
 
+    #[arg(
+        long = "wrap-continuation-indent",
+        default_value = "0",
+        value_name = "N"
+    )]
+    /// Number of spaces before the content of wrapped lines after the first.
+    ///
+    /// Right-aligned wrapped content (see --wrap-right-percent) is not indented.
+    pub wrap_continuation_indent: usize,
+
     #[arg(long = "wrap-left-symbol", default_value = "↵", value_name = "STRING")]
     /// End-of-line wrapped content symbol (left-aligned).
     ///
@@ -934,6 +2262,14 @@ pub struct Opt {
     /// value of "unlimited" means a line will be wrapped as many times as required.
     pub wrap_max_lines: String,
 
+    #[arg(long = "wrap-mode", default_value = "char", value_name = "MODE")]
+    /// Where to break lines which are wrapped.
+    ///
+    /// Possible values are "char", to fill each line with as many characters as fit, and "word",
+    /// to break lines after the last whitespace or punctuation character which fits, if any. The
+    /// syntax highlighting and emphasis of the text are the same either way.
+    pub wrap_mode: String,
+
     #[arg(
         long = "wrap-right-percent",
         default_value = "37.0",
@@ -992,6 +2328,10 @@ pub struct Opt {
 
     #[arg(skip)]
     pub env: DeltaEnv,
+
+    #[arg(skip)]
+    /// The options of the languages with a [delta "lang:<language>"] section in git config.
+    pub language_opts: Vec<(String, Opt)>,
 }
 
 fn get_after_long_help(is_term: bool, no_indent: &str, no_wrap: &str) -> String {
@@ -1186,6 +2526,10 @@ pub struct ComputedValues {
     pub syntax_set: SyntaxSet,
     pub syntax_theme: Option<SyntaxTheme>,
     pub true_color: bool,
+    pub color_depth: ColorDepth,
+    // The language whose [delta "lang:<language>"] section in git config overrides the other
+    // options, if any.
+    pub language: Option<String>,
 }
 
 #[derive(Clone, Debug, Default, PartialEq, Eq)]
@@ -1213,6 +2557,45 @@ pub enum DetectDarkLight {
     Never,
 }
 
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum InputType {
+    /// Detect the type of each line of the input.
+    #[default]
+    Auto,
+    /// A diff, or the output of git log or git show.
+    Diff,
+    /// The output of grep, git grep, ripgrep, etc.
+    Grep,
+    /// The output of git blame.
+    Blame,
+    /// Any other text, which is written unchanged.
+    Raw,
+}
+
+impl InputType {
+    /// The process whose output is of this type, if the input type is not detected.
+    pub fn calling_process(self) -> Option<CallingProcess> {
+        match self {
+            InputType::Auto => None,
+            InputType::Diff => Some(CallingProcess::GitDiff(CommandLine::default())),
+            InputType::Grep => Some(CallingProcess::OtherGrep),
+            InputType::Blame => Some(CallingProcess::GitBlame(CommandLine::default())),
+            InputType::Raw => Some(CallingProcess::None),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// ANSI escape sequences for the terminal.
+    #[default]
+    Terminal,
+    /// A static HTML page.
+    Html,
+    /// HTML for the body of an email.
+    HtmlEmail,
+}
+
 // Which call path to take
 #[derive(Debug)]
 pub enum Call<T> {
@@ -1235,8 +2618,8 @@ impl<A> Call<A> {
 }
 
 impl Opt {
-    fn handle_help_and_version(args: &[OsString]) -> Call<ArgMatches> {
-        match Self::command().try_get_matches_from(args) {
+    fn handle_help_and_version(args: &[OsString]) -> Result<Call<ArgMatches>, clap::Error> {
+        Ok(match Self::command().try_get_matches_from(args) {
             Err(e) if e.kind() == clap::error::ErrorKind::DisplayVersion => {
                 let version = Self::command().render_version();
                 Call::Version(version)
@@ -1246,11 +2629,11 @@ impl Opt {
                 // No wrapping if short -h instead of --help was used:
                 if args.iter().any(|arg| arg == "-h") {
                     let help_clap = Self::command().render_help();
-                    return Call::Help(if term.is_term() {
+                    return Ok(Call::Help(if term.is_term() {
                         help_clap.ansi().to_string()
                     } else {
                         help_clap.to_string()
-                    });
+                    }));
                 }
 
                 let help_clap = Self::command().render_long_help();
@@ -1282,11 +2665,9 @@ impl Opt {
 
                 Call::Help(help)
             }
-            Err(e) => {
-                e.exit();
-            }
+            Err(e) => return Err(e),
             Ok(matches) => Call::Delta(matches),
-        }
+        })
     }
 
     pub fn from_args_and_git_config(
@@ -1301,12 +2682,22 @@ impl Opt {
             args.insert(0, OsString::from("delta"));
             args
         };
-        let matches = match Self::handle_help_and_version(&args) {
+        Self::try_from_args_and_git_config(args, env, assets).unwrap_or_else(|e| e.exit())
+    }
+
+    /// As `from_args_and_git_config`, but return the error instead of exiting if the arguments are
+    /// invalid. The first argument is the name of the program.
+    pub fn try_from_args_and_git_config(
+        args: Vec<OsString>,
+        env: &DeltaEnv,
+        assets: HighlightingAssets,
+    ) -> Result<Call<Self>, clap::Error> {
+        let matches = match Self::handle_help_and_version(&args)? {
             Call::Delta(t) => t,
             msg => {
-                return msg
+                return Ok(msg
                     .try_convert()
-                    .unwrap_or_else(|| panic!("Call<_> conversion failed"))
+                    .unwrap_or_else(|| panic!("Call<_> conversion failed")))
             }
         };
 
@@ -1323,12 +2714,12 @@ impl Opt {
             }
         }
 
-        Call::Delta(Self::from_clap_and_git_config(
+        Ok(Call::Delta(Self::from_clap_and_git_config(
             env,
             matches,
             final_config,
             assets,
-        ))
+        )))
     }
 
     pub fn from_iter_and_git_config<I>(
@@ -1359,6 +2750,22 @@ impl Opt {
             .unwrap_or_else(|_| delta_unreachable("Opt::from_arg_matches failed"));
         opt.env = env.clone();
         options::set::set_options(&mut opt, &mut git_config, &arg_matches, assets);
+        opt.language_opts = options::get::get_languages(git_config.as_ref())
+            .into_iter()
+            .map(|language| {
+                let mut language_opt = Opt::from_arg_matches(&arg_matches)
+                    .unwrap_or_else(|_| delta_unreachable("Opt::from_arg_matches failed"));
+                language_opt.env = env.clone();
+                language_opt.computed.language = Some(language.clone());
+                options::set::set_options(
+                    &mut language_opt,
+                    &mut git_config,
+                    &arg_matches,
+                    utils::bat::assets::load_highlighting_assets(),
+                );
+                (language, language_opt)
+            })
+            .collect();
         opt.git_config = git_config;
         opt
     }
@@ -1390,11 +2797,25 @@ impl Opt {
 // pseudo-flag commands such as --list-languages
 lazy_static! {
     static ref IGNORED_OPTION_NAMES: HashSet<&'static str> = vec![
+        "check-contrast",
+        "dry-run",
+        "force",
         "generate-completion",
+        "html",
+        "input-type",
+        "list-hunks",
         "list-languages",
         "list-syntax-themes",
+        "mark-reviewed",
+        "output-format",
+        "pick",
+        "pick-theme",
+        "self-update",
+        "setup-git",
         "show-config",
         "show-syntax-themes",
+        "split-output",
+        "stats",
     ]
     .into_iter()
     .collect();
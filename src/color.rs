@@ -1,8 +1,11 @@
+use std::borrow::Cow;
 use std::collections::HashMap;
+use std::io::{self, Write};
 use std::str::FromStr;
 
 use ansi_term::Color;
 use lazy_static::lazy_static;
+use regex::{Captures, Regex};
 use syntect::highlighting::Color as SyntectColor;
 
 use crate::fatal;
@@ -55,6 +58,34 @@ pub fn color_to_string(color: Color) -> String {
     }
 }
 
+/// The RGB values of a terminal color, using the xterm values of the 256 colors.
+pub fn rgb(color: Color) -> (u8, u8, u8) {
+    match color {
+        Color::RGB(r, g, b) => (r, g, b),
+        Color::Fixed(n) => ansi_colours::rgb_from_ansi256(n),
+        Color::Black => ansi_colours::rgb_from_ansi256(0),
+        Color::Red => ansi_colours::rgb_from_ansi256(1),
+        Color::Green => ansi_colours::rgb_from_ansi256(2),
+        Color::Yellow => ansi_colours::rgb_from_ansi256(3),
+        Color::Blue => ansi_colours::rgb_from_ansi256(4),
+        Color::Purple => ansi_colours::rgb_from_ansi256(5),
+        Color::Cyan => ansi_colours::rgb_from_ansi256(6),
+        Color::White => ansi_colours::rgb_from_ansi256(7),
+    }
+}
+
+/// The color at `fraction` (from 0 to 1) of the way from one color to another, blended in RGB.
+pub fn blend_colors(from: Color, to: Color, fraction: f64, true_color: bool) -> Color {
+    let ((r1, g1, b1), (r2, g2, b2)) = (rgb(from), rgb(to));
+    let blend = |a: u8, b: u8| (a as f64 + (b as f64 - a as f64) * fraction).round() as u8;
+    let (r, g, b) = (blend(r1, r2), blend(g1, g2), blend(b1, b2));
+    if true_color {
+        Color::RGB(r, g, b)
+    } else {
+        Color::Fixed(ansi_colours::ansi256_from_rgb((r, g, b)))
+    }
+}
+
 // See
 // https://en.wikipedia.org/wiki/ANSI_escape_code#8-bit
 lazy_static! {
@@ -117,6 +148,129 @@ pub enum ColorMode {
     Light,
 }
 
+/// The number of colors supported by the terminal (--color-depth).
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ColorDepth {
+    #[default]
+    /// 24-bit RGB colors.
+    TrueColor,
+    /// The 256 colors of the xterm palette.
+    Ansi256,
+    /// The 16 colors of the terminal's own palette.
+    Ansi16,
+}
+
+lazy_static! {
+    static ref SGR_REGEX: Regex = Regex::new(r"\x1b\[([0-9;]*)m").unwrap();
+}
+
+/// The number of the color among the 16 colors of the terminal's palette which is nearest to the
+/// RGB color, taking the xterm values of those colors.
+pub fn ansi_16_from_rgb((r, g, b): (u8, u8, u8)) -> u8 {
+    let distance = |n: &u8| {
+        let (r2, g2, b2) = ansi_colours::rgb_from_ansi256(*n);
+        [(r, r2), (g, g2), (b, b2)]
+            .iter()
+            .map(|(a, b)| (*a as i32 - *b as i32).pow(2))
+            .sum::<i32>()
+    };
+    (0..16).min_by_key(distance).unwrap()
+}
+
+/// Replace the 256-color and 24-bit colors of the SGR escape sequences in the text by the
+/// nearest of the 16 colors of the terminal's palette.
+pub fn to_ansi_16_colors(text: &str) -> Cow<'_, str> {
+    SGR_REGEX.replace_all(text, |caps: &Captures| {
+        let params: Vec<&str> = caps[1].split(';').collect();
+        let mut new_params = Vec::with_capacity(params.len());
+        let mut i = 0;
+        while i < params.len() {
+            let number = |j: usize| params.get(j).and_then(|n| n.parse::<u8>().ok());
+            let color = match (params[i], params.get(i + 1)) {
+                ("38" | "48", Some(&"5")) => number(i + 2).map(|n| (n, 3)),
+                ("38" | "48", Some(&"2")) => match (number(i + 2), number(i + 3), number(i + 4)) {
+                    (Some(r), Some(g), Some(b)) => Some((ansi_16_from_rgb((r, g, b)), 5)),
+                    _ => None,
+                },
+                _ => None,
+            };
+            match color {
+                Some((n, length)) => {
+                    let n = if n < 16 {
+                        n
+                    } else {
+                        ansi_16_from_rgb(ansi_colours::rgb_from_ansi256(n))
+                    };
+                    // E.g. 31 or 91 for the foreground, and 41 or 101 for the background.
+                    let base = match (params[i], n < 8) {
+                        ("38", true) => 30,
+                        ("38", false) => 90 - 8,
+                        (_, true) => 40,
+                        (_, false) => 100 - 8,
+                    };
+                    new_params.push((base + n as u16).to_string());
+                    i += length;
+                }
+                None => {
+                    new_params.push(params[i].to_string());
+                    i += 1;
+                }
+            }
+        }
+        format!("\x1b[{}m", new_params.join(";"))
+    })
+}
+
+/// A writer passing the text written to it on, with its colors mapped to the 16 colors of the
+/// terminal's palette (--color-depth=16).
+pub struct Ansi16ColorWriter<'a> {
+    writer: &'a mut dyn Write,
+    line: Vec<u8>,
+}
+
+impl<'a> Ansi16ColorWriter<'a> {
+    pub fn new(writer: &'a mut dyn Write) -> Self {
+        Self {
+            writer,
+            line: Vec::new(),
+        }
+    }
+
+    fn write_line(&mut self) -> io::Result<()> {
+        let line = String::from_utf8_lossy(&self.line);
+        self.writer.write_all(to_ansi_16_colors(&line).as_bytes())?;
+        self.line.clear();
+        Ok(())
+    }
+}
+
+impl<'a> Write for Ansi16ColorWriter<'a> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        for line in buf.split_inclusive(|&byte| byte == b'\n') {
+            self.line.extend_from_slice(line);
+            if line.ends_with(b"\n") {
+                self.write_line()?;
+            }
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if !self.line.is_empty() {
+            self.write_line()?;
+        }
+        self.writer.flush()
+    }
+}
+
+impl<'a> Drop for Ansi16ColorWriter<'a> {
+    fn drop(&mut self) {
+        if !self.line.is_empty() {
+            let _ = self.write_line();
+        }
+    }
+}
+
 pub fn get_minus_background_color_default(mode: ColorMode, is_true_color: bool) -> Color {
     match (mode, is_true_color) {
         (Light, true) => LIGHT_THEME_MINUS_COLOR,
@@ -190,3 +344,40 @@ const DARK_THEME_PLUS_EMPH_COLOR_256: Color = Color::Fixed(28);
 pub const LIGHT_THEME_BLAME_PALETTE: &[&str] = &["#FFFFFF", "#DDDDDD", "#BBBBBB"];
 
 pub const DARK_THEME_BLAME_PALETTE: &[&str] = &["#000000", "#222222", "#444444"];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ansi_16_from_rgb() {
+        assert_eq!(ansi_16_from_rgb((0, 0, 0)), 0);
+        assert_eq!(ansi_16_from_rgb((0xd0, 0x10, 0x10)), 1);
+        assert_eq!(ansi_16_from_rgb((0xff, 0x10, 0x10)), 9);
+        assert_eq!(ansi_16_from_rgb((0xff, 0xff, 0xff)), 15);
+    }
+
+    #[test]
+    fn test_to_ansi_16_colors() {
+        assert_eq!(
+            to_ansi_16_colors("\x1b[1;38;2;208;16;16;48;5;21mA\x1b[0m \x1b[38;5;9;4mB\x1b[m"),
+            "\x1b[1;31;44mA\x1b[0m \x1b[91;4mB\x1b[m"
+        );
+        assert_eq!(to_ansi_16_colors("\x1b[31mA\x1b[0m"), "\x1b[31mA\x1b[0m");
+    }
+
+    #[test]
+    fn test_ansi_16_color_writer() {
+        let mut output = Vec::new();
+        {
+            let mut writer = Ansi16ColorWriter::new(&mut output);
+            write!(writer, "\x1b[38;5;").unwrap();
+            writeln!(writer, "196mA\x1b[0m").unwrap();
+            write!(writer, "\x1b[48;2;0;0;0mB").unwrap();
+        }
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "\x1b[91mA\x1b[0m\n\x1b[40mB"
+        );
+    }
+}
@@ -1,32 +1,41 @@
 use std::collections::HashMap;
 use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
 
 use clap::parser::ValueSource;
 use regex::Regex;
+use syntect::highlighting::Color as SyntectColor;
 use syntect::highlighting::Style as SyntectStyle;
 use syntect::highlighting::Theme as SyntaxTheme;
 use syntect::parsing::SyntaxSet;
 
 use crate::ansi;
 use crate::cli;
-use crate::color::{self, ColorMode};
+use crate::color::{self, ColorDepth, ColorMode};
 use crate::delta::State;
 use crate::fatal;
+use crate::features::hyperlinks;
 use crate::features::navigate;
+use crate::features::parallel_highlighting;
+use crate::features::review_bookmarks::{self, ReviewBookmarks};
 use crate::features::side_by_side::{self, ansifill, LeftRight};
 use crate::git_config::GitConfig;
 use crate::handlers;
 use crate::handlers::blame::parse_blame_line_numbers;
 use crate::handlers::blame::BlameLineNumbers;
+use crate::handlers::diff_header::DiffPathPrefixes;
+use crate::handlers::hunk_header::HunkLabel;
 use crate::minusplus::MinusPlus;
 use crate::paint::BgFillMethod;
 use crate::parse_styles;
 use crate::style;
 use crate::style::Style;
-use crate::tests::TESTING;
 use crate::utils;
 use crate::utils::bat::output::PagingMode;
+use crate::utils::locale::Locale;
 use crate::utils::regex_replacement::RegexReplacement;
+use crate::utils::warnings::{self, WarningKind};
 use crate::wrapping::WrapConfig;
 
 pub const INLINE_SYMBOL_WIDTH_1: usize = 1;
@@ -34,39 +43,84 @@ pub const INLINE_SYMBOL_WIDTH_1: usize = 1;
 // Used if an invalid default-language was specified.
 pub const SYNTAX_FALLBACK_LANG: &str = "txt";
 
-#[cfg_attr(test, derive(Clone))]
+#[derive(Clone)]
 pub struct Config {
+    pub added_keyword_styles: Vec<(Regex, Style)>,
+    pub amended_commit_messages: bool,
+    pub ascii_decorations: bool,
     pub available_terminal_width: usize,
     pub background_color_extends_to_terminal_width: bool,
+    pub blame_boundary_style: Style,
     pub blame_code_style: Option<Style>,
     pub blame_format: String,
+    pub blame_heatmap: bool,
+    pub blame_heatmap_newest: Style,
+    pub blame_heatmap_oldest: Style,
+    pub blame_ignored_style: Style,
     pub blame_separator_format: BlameLineNumbers,
     pub blame_palette: Vec<String>,
     pub blame_separator_style: Option<Style>,
+    pub blame_side_by_side: bool,
     pub blame_timestamp_format: String,
     pub blame_timestamp_output_format: Option<String>,
+    pub canonicalize_json: Vec<Regex>,
+    pub color_depth: ColorDepth,
+    pub color_mode: ColorMode,
+    pub color_moved: bool,
     pub color_only: bool,
+    pub comment_check_command: Option<String>,
+    pub comment_check_style: Style,
+    pub commit_block_format: Option<String>,
+    pub commit_columns: bool,
+    pub commit_columns_min_width: usize,
+    pub commit_message_code_style: Style,
+    pub commit_message_markdown: bool,
     pub commit_regex: Regex,
     pub commit_style: Style,
+    pub context: Option<usize>,
+    pub context_dim: f64,
+    pub context_dim_background: SyntectColor,
+    pub context_fade: usize,
+    pub context_fold_style: Style,
+    pub coverage_covered_style: Style,
+    pub coverage_file: Option<String>,
+    pub coverage_uncovered_style: Style,
+    pub conventional_commits: bool,
+    pub conventional_commit_breaking_style: Style,
+    pub conventional_commit_scope_style: Style,
+    pub conventional_commit_type_style: Style,
     pub cwd_of_delta_process: Option<PathBuf>,
     pub cwd_of_user_shell_process: Option<PathBuf>,
     pub cwd_relative_to_repo_root: Option<String>,
     pub decorations_width: cli::Width,
     pub default_language: String,
+    pub detect_dark_light_timeout: Duration,
+    pub detect_secrets: bool,
+    pub diagnostics_file: Option<String>,
+    pub diagnostics_style: Style,
+    pub diff_algorithm: Option<String>,
     pub diff_args: String,
+    pub diff_path_prefixes: DiffPathPrefixes,
     pub diff_stat_align_width: usize,
     pub error_exit_code: i32,
+    pub exclude: Vec<Regex>,
     pub file_added_label: String,
     pub file_copied_label: String,
     pub file_modified_label: String,
     pub file_removed_label: String,
     pub file_renamed_label: String,
     pub file_regex_replacement: Option<RegexReplacement>,
+    pub filter: Option<String>,
+    pub follow_color_scheme: bool,
     pub right_arrow: String,
     pub file_style: Style,
-    pub git_config: Option<GitConfig>,
+    pub function_separators: bool,
+    pub function_separator_style: Style,
+    // Shared with the configs for other sizes of the terminal (see terminal_resize).
+    pub git_config: Option<Arc<GitConfig>>,
     pub git_minus_style: Style,
     pub git_plus_style: Style,
+    pub github_actions_groups: bool,
     pub grep_context_line_style: Style,
     pub grep_file_style: Style,
     pub classic_grep_header_file_style: Style,
@@ -77,86 +131,161 @@ pub struct Config {
     pub grep_match_word_style: Style,
     pub grep_output_type: Option<GrepType>,
     pub grep_separator_symbol: String,
+    pub group_renames: bool,
     pub handle_merge_conflicts: bool,
+    // Whether the lines of the output are marked with the anchors of the HTML page (--html).
+    pub html_anchors: bool,
+    pub hunk_apply_commands: bool,
+    pub hunk_apply_command_style: Style,
+    pub hunk_change_density: bool,
+    pub hunk_change_density_minus_style: Style,
+    pub hunk_change_density_plus_style: Style,
     pub hunk_header_file_style: Style,
     pub hunk_header_line_number_style: Style,
     pub hunk_header_style_include_file_path: HunkHeaderIncludeFilePath,
     pub hunk_header_style_include_line_number: HunkHeaderIncludeLineNumber,
     pub hunk_header_style_include_code_fragment: HunkHeaderIncludeCodeFragment,
     pub hunk_header_style: Style,
-    pub hunk_label: String,
+    pub hunk_label: HunkLabel,
     pub hyperlinks_commit_link_format: Option<String>,
     pub hyperlinks_file_link_format: String,
+    pub hyperlinks_issue_rules: Vec<RegexReplacement>,
+    pub hyperlinks_minus_file_link_format: Option<String>,
     pub hyperlinks: bool,
+    pub hyperlinks_only: bool,
+    pub ignore_whitespace: bool,
+    pub gutter_command: Option<String>,
+    pub gutter_style: Style,
+    pub inline_blame: bool,
+    pub inline_blame_style: Style,
     pub inline_hint_style: Style,
+    pub input_type: cli::InputType,
     pub inspect_raw_lines: cli::InspectRawLines,
     pub keep_plus_minus_markers: bool,
+    // The configs of the languages with a [delta "lang:<language>"] section in git config.
+    pub language_configs: HashMap<String, Config>,
     pub line_buffer_size: usize,
     pub line_fill_method: BgFillMethod,
+    pub line_map: Option<PathBuf>,
     pub line_numbers_format: LeftRight<String>,
     pub line_numbers_style_leftright: LeftRight<Style>,
     pub line_numbers_style_minusplus: MinusPlus<Style>,
     pub line_numbers_zero_style: Style,
     pub line_numbers: bool,
+    pub locale: Locale,
+    pub log_graph_style: Style,
     pub styles_map: Option<HashMap<style::AnsiTermStyleEqualityKey, Style>>,
+    pub max_buffer_lines: usize,
+    pub max_code_line_warn: usize,
+    pub max_code_line_warn_style: Style,
+    pub max_context_lines: usize,
     pub max_line_distance_for_naively_paired_lines: f64,
     pub max_line_distance: f64,
+    pub max_highlight_length: usize,
     pub max_line_length: usize,
     pub max_syntax_length: usize,
     pub merge_conflict_begin_symbol: String,
     pub merge_conflict_ours_diff_header_style: Style,
     pub merge_conflict_theirs_diff_header_style: Style,
     pub merge_conflict_end_symbol: String,
+    pub merge_parent_style: Style,
+    pub merge_parent_subjects: bool,
     pub minus_emph_style: Style,
     pub minus_empty_line_marker_style: Style,
     pub minus_file: Option<PathBuf>,
     pub minus_non_emph_style: Style,
     pub minus_style: Style,
+    pub missing_newline_marker: Option<String>,
+    pub missing_newline_marker_style: Style,
+    pub moved_alt_style_minusplus: MinusPlus<Style>,
+    pub moved_style_minusplus: MinusPlus<Style>,
+    pub navigate_file_regex: Option<String>,
+    pub navigate_keymap: Vec<(String, navigate::NavigateAction)>,
+    pub navigate_prefill_search: bool,
     pub navigate_regex: Option<String>,
     pub navigate: bool,
+    pub no_style: bool,
+    pub no_style_verify: bool,
     pub null_style: Style,
     pub null_syntect_style: SyntectStyle,
+    pub offline: bool,
     pub pager: Option<String>,
     pub paging_mode: PagingMode,
+    pub patch_applying_style: Style,
+    pub patch_failure_style: Style,
+    pub path_rewrite: Vec<RegexReplacement>,
+    pub path_rewrite_hyperlinks: bool,
+    pub path_strip: usize,
     pub plus_emph_style: Style,
     pub plus_empty_line_marker_style: Style,
     pub plus_file: Option<PathBuf>,
     pub plus_non_emph_style: Style,
     pub plus_style: Style,
+    pub preprocess: Option<String>,
+    pub pull_request_title_style: Style,
+    pub pull_request_titles: bool,
+    pub range_diff_header_style: Style,
+    pub redact_secrets: bool,
+    pub redact_secrets_regex: Option<Regex>,
+    pub redacted_style: Style,
     pub relative_paths: bool,
+    pub review_bookmarks: ReviewBookmarks,
+    pub secret_warning_style: Style,
     pub show_themes: bool,
+    pub side_by_side_center_gutter: bool,
+    pub side_by_side_center_gutter_style: Style,
+    pub side_by_side_center_line_numbers: bool,
     pub side_by_side_data: side_by_side::SideBySideData,
+    pub side_by_side_order: side_by_side::PanelOrder,
+    pub side_by_side_split: (usize, usize),
     pub side_by_side: bool,
+    pub split_hunks: usize,
+    pub split_hunks_style: Style,
+    pub strip_cr: StripCr,
+    pub strip_path_prefix: Option<String>,
+    pub summary_create_style: Style,
+    pub summary_delete_style: Style,
+    pub summary_similarity_style: Style,
     pub syntax_set: SyntaxSet,
     pub syntax_theme: Option<SyntaxTheme>,
     pub tab_cfg: utils::tabs::TabCfg,
+    pub threads: usize,
     pub tokenization_regex: Regex,
+    pub tree_sitter_tokenization: bool,
     pub true_color: bool,
     pub truncation_symbol: String,
+    pub watch: bool,
     pub whitespace_error_style: Style,
     pub wrap_config: WrapConfig,
     pub zero_style: Style,
 }
 
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub enum StripCr {
+    Auto,
+    Always,
+    Never,
+}
+
 #[derive(Debug, Eq, PartialEq, Clone)]
 pub enum GrepType {
     Ripgrep,
     Classic,
 }
 
-#[cfg_attr(test, derive(Clone))]
+#[derive(Clone)]
 pub enum HunkHeaderIncludeFilePath {
     Yes,
     No,
 }
 
-#[cfg_attr(test, derive(Clone))]
+#[derive(Clone)]
 pub enum HunkHeaderIncludeLineNumber {
     Yes,
     No,
 }
 
-#[cfg_attr(test, derive(Clone))]
+#[derive(Clone)]
 pub enum HunkHeaderIncludeCodeFragment {
     Yes,
     No,
@@ -178,17 +307,57 @@ impl Config {
     }
 
     pub fn git_config(&self) -> Option<&GitConfig> {
-        self.git_config.as_ref()
+        self.git_config.as_deref()
     }
 }
 
 impl From<cli::Opt> for Config {
-    fn from(opt: cli::Opt) -> Self {
+    fn from(mut opt: cli::Opt) -> Self {
+        let language_configs = std::mem::take(&mut opt.language_opts)
+            .into_iter()
+            .map(|(language, opt)| (language, Config::from(opt)))
+            .collect();
         let mut styles = parse_styles::parse_styles(&opt);
         let styles_map = parse_styles::parse_styles_map(&opt);
+        let added_keyword_styles = opt
+            .added_keyword_styles
+            .as_deref()
+            .map(|rules| parse_styles::parse_keyword_styles(rules, &opt))
+            .unwrap_or_default();
 
         let wrap_config = WrapConfig::from_opt(&opt, styles["inline-hint-style"]);
 
+        let diff_path_prefixes = DiffPathPrefixes::from_git_config(opt.git_config());
+
+        let review_bookmarks = match opt.git_config().and_then(GitConfig::repo) {
+            Some(repo) if !opt.color_only => {
+                ReviewBookmarks::read(&review_bookmarks::state_file(&repo))
+            }
+            _ => ReviewBookmarks::default(),
+        };
+
+        let hyperlinks_issue_rules = if opt.hyperlinks {
+            hyperlinks::make_issue_link_rules(
+                opt.hyperlinks_issue_rules.as_deref(),
+                opt.hyperlinks_issue_link_format.as_deref(),
+                opt.git_config().and_then(GitConfig::get_remote_url),
+            )
+        } else {
+            Vec::new()
+        };
+
+        if opt.side_by_side && !opt.color_only {
+            if let Some(diagnostics_file) = &opt.diagnostics_file {
+                warnings::warn(
+                    WarningKind::InvalidValue,
+                    format!(
+                        "Ignoring --diagnostics-file {diagnostics_file}: \
+                         the messages cannot be displayed with --side-by-side."
+                    ),
+                );
+            }
+        }
+
         let max_line_distance_for_naively_paired_lines = opt
             .env
             .experimental_max_line_distance_for_naively_paired_lines
@@ -214,6 +383,32 @@ impl From<cli::Opt> for Config {
             ));
         });
 
+        let redact_secrets_regex = opt.redact_secrets_regex.as_ref().map(|regex| {
+            Regex::new(regex).unwrap_or_else(|_| {
+                fatal(format!(
+                    "Invalid redact-secrets-regex: {regex}. \
+                     The value must be a valid Rust regular expression. \
+                     See https://docs.rs/regex."
+                ));
+            })
+        });
+
+        if !(0.0..=1.0).contains(&opt.context_dim) {
+            fatal(format!(
+                "Invalid value for context-dim: {}. The value must be a number between 0 and 1.",
+                opt.context_dim
+            ));
+        }
+        let context_dim_background = opt
+            .computed
+            .syntax_theme
+            .as_ref()
+            .and_then(|theme| theme.settings.background)
+            .unwrap_or(match opt.computed.color_mode {
+                ColorMode::Light => SyntectColor::WHITE,
+                ColorMode::Dark => SyntectColor::BLACK,
+            });
+
         let blame_palette = make_blame_palette(opt.blame_palette, opt.computed.color_mode);
 
         if blame_palette.is_empty() {
@@ -226,7 +421,7 @@ impl From<cli::Opt> for Config {
         let file_removed_label = opt.file_removed_label;
         let file_renamed_label = opt.file_renamed_label;
         let right_arrow = opt.right_arrow;
-        let hunk_label = opt.hunk_label;
+        let hunk_label = HunkLabel::parse(&opt.hunk_label);
 
         let line_fill_method = match opt.line_fill_method.as_deref() {
             // Note that "default" is not documented
@@ -235,13 +430,26 @@ impl From<cli::Opt> for Config {
             _ => fatal("Invalid option for line-fill-method: Expected \"ansi\" or \"spaces\"."),
         };
 
+        let side_by_side_center_line_numbers =
+            opt.side_by_side_center_line_numbers && opt.side_by_side && opt.line_numbers;
+        let center_gutter_width = side_by_side::center_gutter_width(
+            opt.side_by_side_center_gutter,
+            side_by_side_center_line_numbers,
+        );
+        let side_by_side_order = side_by_side::PanelOrder::parse(&opt.side_by_side_order);
+        let side_by_side_split = side_by_side::parse_split(&opt.side_by_side_split);
         let side_by_side_data = side_by_side::SideBySideData::new_sbs(
             &opt.computed.decorations_width,
             &opt.computed.available_terminal_width,
+            center_gutter_width,
+            side_by_side_split,
+            side_by_side_order,
         );
         let side_by_side_data = ansifill::UseFullPanelWidth::sbs_odd_fix(
             &opt.computed.decorations_width,
             &line_fill_method,
+            center_gutter_width,
+            side_by_side_split,
             side_by_side_data,
         );
 
@@ -259,6 +467,19 @@ impl From<cli::Opt> for Config {
         } else {
             opt.navigate_regex
         };
+        // The stop points of the keys which jump between files (--navigate-keymap).
+        let navigate_file_regex = opt.navigate.then(|| {
+            navigate::make_navigate_regex(
+                false,
+                &file_modified_label,
+                &file_added_label,
+                &file_removed_label,
+                &file_renamed_label,
+                &HunkLabel::default(),
+            )
+        });
+        let navigate_keymap = navigate::parse_navigate_keymap(&opt.navigate_keymap)
+            .unwrap_or_else(|err| fatal(format!("Invalid value for navigate-keymap: {err}")));
 
         let grep_output_type = match opt.grep_output_type.as_deref() {
             Some("ripgrep") => Some(GrepType::Ripgrep),
@@ -267,6 +488,27 @@ impl From<cli::Opt> for Config {
             _ => fatal("Invalid option for grep-output-type: Expected \"ripgrep\" or \"classic\"."),
         };
 
+        let strip_cr = match opt.strip_cr.as_str() {
+            "auto" => StripCr::Auto,
+            "always" => StripCr::Always,
+            "never" => StripCr::Never,
+            _ => fatal("Invalid option for strip-cr: Expected \"auto\", \"always\", or \"never\"."),
+        };
+
+        let context = opt.context.as_deref().map(|n| {
+            n.parse::<usize>().unwrap_or_else(|_| {
+                fatal(format!(
+                    "Invalid value for context: {n}. The value must be a number of lines."
+                ))
+            })
+        });
+
+        let github_actions_groups = opt.github_actions_groups
+            && !opt.color_only
+            && opt.env.github_actions.as_deref() == Some("true");
+
+        let locale = Locale::from_env(opt.locale.as_deref(), &opt.env);
+
         #[cfg(not(test))]
         let cwd_of_delta_process = opt.env.current_dir;
         #[cfg(test)]
@@ -280,28 +522,92 @@ impl From<cli::Opt> for Config {
         );
 
         Self {
+            added_keyword_styles,
+            amended_commit_messages: opt.amended_commit_messages && !opt.color_only,
+            ascii_decorations: opt.ascii_decorations,
             available_terminal_width: opt.computed.available_terminal_width,
             background_color_extends_to_terminal_width: opt
                 .computed
                 .background_color_extends_to_terminal_width,
+            blame_boundary_style: styles["blame-boundary-style"],
             blame_format: opt.blame_format,
             blame_code_style: styles.remove("blame-code-style"),
+            blame_heatmap: opt.blame_heatmap && !opt.color_only,
+            blame_heatmap_newest: styles["blame-heatmap-newest"],
+            blame_heatmap_oldest: styles["blame-heatmap-oldest"],
+            blame_ignored_style: styles["blame-ignored-style"],
             blame_palette,
             blame_separator_format: parse_blame_line_numbers(&opt.blame_separator_format),
             blame_separator_style: styles.remove("blame-separator-style"),
+            blame_side_by_side: opt.blame_side_by_side,
             blame_timestamp_format: opt.blame_timestamp_format,
             blame_timestamp_output_format: opt.blame_timestamp_output_format,
+            // --color-only must preserve the lines of the input.
+            canonicalize_json: match opt.canonicalize_json.as_deref() {
+                Some(globs) if !opt.color_only => utils::path::parse_globs(globs),
+                _ => Vec::new(),
+            },
             commit_style: styles["commit-style"],
+            context,
+            context_dim: opt.context_dim,
+            context_dim_background,
+            context_fade: opt.context_fade,
+            context_fold_style: styles["context-fold-style"],
+            coverage_covered_style: styles["coverage-covered-style"],
+            coverage_file: if opt.color_only || opt.side_by_side {
+                None
+            } else {
+                opt.coverage_file
+            },
+            coverage_uncovered_style: styles["coverage-uncovered-style"],
+            conventional_commits: opt.conventional_commits && !opt.color_only,
+            conventional_commit_breaking_style: styles["conventional-commit-breaking-style"],
+            conventional_commit_scope_style: styles["conventional-commit-scope-style"],
+            conventional_commit_type_style: styles["conventional-commit-type-style"],
+            color_depth: opt.computed.color_depth,
+            color_mode: opt.computed.color_mode,
+            color_moved: opt.color_moved,
             color_only: opt.color_only,
+            comment_check_command: if opt.color_only {
+                None
+            } else {
+                opt.comment_check_command
+            },
+            comment_check_style: styles["comment-check-style"],
+            // --color-only must preserve the lines of the input.
+            commit_block_format: if opt.color_only {
+                None
+            } else {
+                opt.commit_block_format
+            },
+            commit_columns: opt.commit_columns && !opt.color_only,
+            commit_columns_min_width: opt.commit_columns_min_width,
+            commit_message_code_style: styles["commit-message-code-style"],
+            commit_message_markdown: opt.commit_message_markdown && !opt.color_only,
             commit_regex,
             cwd_of_delta_process,
             cwd_of_user_shell_process,
             cwd_relative_to_repo_root,
             decorations_width: opt.computed.decorations_width,
             default_language: opt.default_language,
+            detect_dark_light_timeout: Duration::from_millis(opt.detect_dark_light_timeout),
+            detect_secrets: opt.detect_secrets && !opt.color_only,
+            diagnostics_file: if opt.color_only || opt.side_by_side {
+                None
+            } else {
+                opt.diagnostics_file
+            },
+            diagnostics_style: styles["diagnostics-style"],
+            diff_algorithm: opt.diff_algorithm,
             diff_args: opt.diff_args,
+            diff_path_prefixes,
             diff_stat_align_width: opt.diff_stat_align_width,
             error_exit_code: 2, // Use 2 for error because diff uses 0 and 1 for non-error.
+            exclude: opt
+                .exclude
+                .as_deref()
+                .map(utils::path::parse_globs)
+                .unwrap_or_default(),
             file_added_label,
             file_copied_label,
             file_modified_label,
@@ -311,10 +617,19 @@ impl From<cli::Opt> for Config {
                 .file_regex_replacement
                 .as_deref()
                 .and_then(RegexReplacement::from_sed_command),
+            // --color-only must preserve the lines of the input.
+            filter: if opt.color_only { None } else { opt.filter },
+            follow_color_scheme: opt.follow_color_scheme
+                && !opt.light
+                && !opt.dark
+                && opt.detect_dark_light != cli::DetectDarkLight::Never,
             right_arrow,
             hunk_label,
             file_style: styles["file-style"],
-            git_config: opt.git_config,
+            function_separators: opt.function_separators && !opt.color_only,
+            function_separator_style: styles["function-separator-style"],
+            git_config: opt.git_config.map(Arc::new),
+            github_actions_groups,
             grep_context_line_style: styles["grep-context-line-style"],
             grep_file_style: styles["grep-file-style"],
             classic_grep_header_file_style: styles["classic-grep-header-file-style"],
@@ -325,7 +640,14 @@ impl From<cli::Opt> for Config {
             grep_match_word_style: styles["grep-match-word-style"],
             grep_output_type,
             grep_separator_symbol: opt.grep_separator_symbol,
+            group_renames: !opt.expand_renames && !opt.color_only,
             handle_merge_conflicts: !opt.raw,
+            html_anchors: opt.output_format == cli::OutputFormat::Html,
+            hunk_apply_commands: opt.hunk_apply_commands && !opt.color_only,
+            hunk_apply_command_style: styles["hunk-apply-command-style"],
+            hunk_change_density: opt.hunk_change_density && !opt.color_only,
+            hunk_change_density_minus_style: styles["hunk-change-density-minus-style"],
+            hunk_change_density_plus_style: styles["hunk-change-density-plus-style"],
             hunk_header_file_style: styles["hunk-header-file-style"],
             hunk_header_line_number_style: styles["hunk-header-line-number-style"],
             hunk_header_style: styles["hunk-header-style"],
@@ -359,22 +681,47 @@ impl From<cli::Opt> for Config {
             hyperlinks: opt.hyperlinks,
             hyperlinks_commit_link_format: opt.hyperlinks_commit_link_format,
             hyperlinks_file_link_format: opt.hyperlinks_file_link_format,
+            hyperlinks_issue_rules,
+            hyperlinks_minus_file_link_format: opt.hyperlinks_minus_file_link_format,
+            hyperlinks_only: opt.hyperlinks_only,
+            ignore_whitespace: opt.ignore_whitespace,
+            input_type: opt.input_type,
             inspect_raw_lines: opt.computed.inspect_raw_lines,
+            gutter_command: if opt.color_only || opt.side_by_side {
+                None
+            } else {
+                opt.gutter_command
+            },
+            gutter_style: styles["gutter-style"],
+            inline_blame: opt.inline_blame && !opt.color_only && !opt.side_by_side,
+            inline_blame_style: styles["inline-blame-style"],
             inline_hint_style: styles["inline-hint-style"],
             keep_plus_minus_markers: opt.keep_plus_minus_markers,
-            line_fill_method: if !opt.computed.stdout_is_term && !TESTING {
+            language_configs,
+            line_fill_method: if (!opt.computed.stdout_is_term
+                || opt.output_format != cli::OutputFormat::Terminal)
+                && !cfg!(test)
+            {
                 // Don't write ANSI sequences (which rely on the width of the
-                // current terminal) into a file. Also see UseFullPanelWidth.
+                // current terminal) into a file, or into HTML. Also see UseFullPanelWidth.
                 // But when testing always use given value.
                 BgFillMethod::Spaces
             } else {
                 line_fill_method
             },
+            // Only the lines of the output of --color-only correspond to those of the input.
+            line_map: if opt.color_only {
+                opt.line_map.map(PathBuf::from)
+            } else {
+                None
+            },
             line_numbers: opt.line_numbers && !handlers::hunk::is_word_diff(),
-            line_numbers_format: LeftRight::new(
-                opt.line_numbers_left_format,
-                opt.line_numbers_right_format,
-            ),
+            // The line numbers are not shown in the panels but between them.
+            line_numbers_format: if side_by_side_center_line_numbers {
+                LeftRight::new(String::new(), String::new())
+            } else {
+                LeftRight::new(opt.line_numbers_left_format, opt.line_numbers_right_format)
+            },
             line_numbers_style_leftright: LeftRight::new(
                 styles["line-numbers-left-style"],
                 styles["line-numbers-right-style"],
@@ -385,8 +732,23 @@ impl From<cli::Opt> for Config {
             ),
             line_numbers_zero_style: styles["line-numbers-zero-style"],
             line_buffer_size: opt.line_buffer_size,
+            locale,
+            log_graph_style: styles["log-graph-style"],
+            max_buffer_lines: opt.max_buffer_lines,
+            max_code_line_warn: if opt.color_only {
+                0
+            } else {
+                opt.max_code_line_warn
+            },
+            max_code_line_warn_style: styles["max-code-line-warn-style"],
+            max_context_lines: if opt.color_only {
+                0
+            } else {
+                opt.max_context_lines
+            },
             max_line_distance: opt.max_line_distance,
             max_line_distance_for_naively_paired_lines,
+            max_highlight_length: opt.max_highlight_length,
             max_line_length: if opt.side_by_side {
                 wrap_config.config_max_line_length(
                     opt.max_line_length,
@@ -401,36 +763,96 @@ impl From<cli::Opt> for Config {
             merge_conflict_theirs_diff_header_style: styles
                 ["merge-conflict-theirs-diff-header-style"],
             merge_conflict_end_symbol: opt.merge_conflict_end_symbol,
+            merge_parent_style: styles["merge-parent-style"],
+            merge_parent_subjects: opt.merge_parent_subjects && !opt.color_only,
             minus_emph_style: styles["minus-emph-style"],
             minus_empty_line_marker_style: styles["minus-empty-line-marker-style"],
             minus_file: opt.minus_file,
             minus_non_emph_style: styles["minus-non-emph-style"],
             minus_style: styles["minus-style"],
+            missing_newline_marker: if opt.color_only || opt.missing_newline_marker.is_empty() {
+                None
+            } else {
+                Some(opt.missing_newline_marker)
+            },
+            missing_newline_marker_style: styles["missing-newline-marker-style"],
+            moved_alt_style_minusplus: MinusPlus::new(
+                styles["moved-minus-alt-style"],
+                styles["moved-plus-alt-style"],
+            ),
+            moved_style_minusplus: MinusPlus::new(
+                styles["moved-minus-style"],
+                styles["moved-plus-style"],
+            ),
             navigate: opt.navigate,
+            no_style: opt.no_style,
+            no_style_verify: opt.no_style_verify,
+            navigate_file_regex,
+            navigate_keymap,
+            navigate_prefill_search: opt.navigate_prefill_search,
             navigate_regex,
             null_style: Style::new(),
             null_syntect_style: SyntectStyle::default(),
+            offline: opt.offline,
             pager: opt.pager,
             paging_mode: opt.computed.paging_mode,
+            patch_applying_style: styles["patch-applying-style"],
+            patch_failure_style: styles["patch-failure-style"],
+            path_rewrite: opt
+                .path_rewrite
+                .as_deref()
+                .map(RegexReplacement::from_rules)
+                .unwrap_or_default(),
+            path_rewrite_hyperlinks: opt.path_rewrite_hyperlinks,
+            path_strip: opt.path_strip,
             plus_emph_style: styles["plus-emph-style"],
             plus_empty_line_marker_style: styles["plus-empty-line-marker-style"],
             plus_file: opt.plus_file,
             plus_non_emph_style: styles["plus-non-emph-style"],
             plus_style: styles["plus-style"],
+            preprocess: opt.preprocess,
+            pull_request_title_style: styles["pull-request-title-style"],
+            pull_request_titles: opt.pull_request_titles && !opt.color_only,
             git_minus_style: styles["git-minus-style"],
             git_plus_style: styles["git-plus-style"],
+            range_diff_header_style: styles["range-diff-header-style"],
+            redact_secrets: opt.redact_secrets && !opt.color_only,
+            redact_secrets_regex,
+            redacted_style: styles["redacted-style"],
             relative_paths: opt.relative_paths,
+            review_bookmarks,
+            secret_warning_style: styles["secret-warning-style"],
             show_themes: opt.show_themes,
+            side_by_side_center_gutter: opt.side_by_side_center_gutter,
+            side_by_side_center_gutter_style: styles["side-by-side-center-gutter-style"],
+            side_by_side_center_line_numbers,
             side_by_side: opt.side_by_side && !handlers::hunk::is_word_diff(),
             side_by_side_data,
+            side_by_side_order,
+            side_by_side_split,
+            // --color-only must preserve the lines of the input.
+            split_hunks: if opt.color_only { 0 } else { opt.split_hunks },
+            split_hunks_style: styles["split-hunks-style"],
+            strip_cr,
+            strip_path_prefix: opt.strip_path_prefix,
             styles_map,
+            summary_create_style: styles["summary-create-style"],
+            summary_delete_style: styles["summary-delete-style"],
+            summary_similarity_style: styles["summary-similarity-style"],
             syntax_set: opt.computed.syntax_set,
             syntax_theme: opt.computed.syntax_theme,
             tab_cfg: utils::tabs::TabCfg::new(opt.tab_width),
+            threads: match opt.threads {
+                0 => std::thread::available_parallelism().map_or(1, usize::from),
+                threads => threads,
+            }
+            .min(parallel_highlighting::MAX_THREADS),
             tokenization_regex,
+            tree_sitter_tokenization: opt.tree_sitter_tokenization,
             true_color: opt.computed.true_color,
             truncation_symbol: format!("{}→{}", ansi::ANSI_SGR_REVERSE, ansi::ANSI_SGR_RESET),
             wrap_config,
+            watch: opt.watch,
             whitespace_error_style: styles["whitespace-error-style"],
             zero_style: styles["zero-style"],
         }
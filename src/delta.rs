@@ -1,20 +1,35 @@
 use std::borrow::Cow;
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::io::{self, BufRead, IsTerminal, Write};
 
 use bytelines::ByteLines;
 
 use crate::ansi;
+use crate::cli::InputType;
 use crate::config::delta_unreachable;
 use crate::config::Config;
-use crate::config::GrepType;
+use crate::config::{GrepType, StripCr};
 use crate::features;
+use crate::features::change_density::ChangeDensity;
+use crate::features::color_scheme::ColorSchemeFollower;
+use crate::features::function_separators::FileSymbols;
+use crate::features::hunk_apply::HunkPatch;
+use crate::features::line_map::LineMap;
+use crate::features::log_graph::{LogGraph, LogGraphWriter};
+use crate::features::parallel_highlighting::HighlightPool;
+use crate::features::split_hunks::HunkSplitter;
+use crate::features::terminal_resize::TerminalResizeFollower;
+use crate::handlers::amended_commit::CommitVersion;
+use crate::handlers::blame::BlameIgnoreRevs;
+use crate::handlers::commit_meta::{CommitBlock, CommitColumns};
 use crate::handlers::grep;
 use crate::handlers::hunk_header::{AmbiguousDiffMinusCounter, ParsedHunkHeader};
 use crate::handlers::{self, merge_conflict};
+use crate::minusplus::MinusPlus;
 use crate::paint::Painter;
 use crate::style::DecorationStyle;
-use crate::utils;
+use crate::subcommands::list_hunks::HunkIndex;
+use crate::utils::{self, warnings};
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum State {
@@ -29,6 +44,7 @@ pub enum State {
     SubmoduleShort(String), // In a submodule section, with gitconfig diff.submodule = short
     Blame(String), // In a line of `git blame` output (key).
     GitShowFile,  // In a line of `git show $revision:./path/to/file.ext` output
+    RangeDiff,    // In the diff between the patches of a pair of commits of `git range-diff` output
     Grep(GrepType, grep::LineType, String, Option<usize>), // In a line of `git grep` output (grep_type, line_type, path, line_number)
     Unknown,
     // The following elements are created when a line is wrapped to display it:
@@ -74,6 +90,9 @@ impl DiffType {
 pub enum Source {
     GitDiff,     // Coming from a `git diff` command
     DiffUnified, // Coming from a `diff -u` command
+    Jujutsu,     // Coming from a `jj diff` command, in its default format
+    Mercurial,   // Coming from a `hg diff` command
+    Subversion,  // Coming from a `svn diff` command
     Unknown,
 }
 
@@ -102,6 +121,11 @@ pub struct StateMachine<'a> {
     pub mode_info: String,
     pub painter: Painter<'a>,
     pub config: &'a Config,
+    // The config given to delta. `config` is the config of the language of the current file
+    // instead, if git config has a [delta "lang:<language>"] section for it.
+    pub root_config: &'a Config,
+    // The name of the current file, by which the config of its language is selected.
+    pub syntax_filename: Option<String>,
 
     // When a file is modified, we use lines starting with '---' or '+++' to obtain the file name.
     // When a file is renamed without changes, we use lines starting with 'rename' to obtain the
@@ -110,15 +134,91 @@ pub struct StateMachine<'a> {
     // avoid emitting the file meta header line twice (#245).
     pub current_file_pair: Option<(String, String)>,
     pub handled_diff_header_header_line_file_pair: Option<(String, String)>,
+    // The files renamed without changes, displayed together (see --expand-renames), and whether
+    // the header of the current file shows it to be one.
+    pub pure_renames: Vec<(String, String)>,
+    pub is_pure_rename: bool,
     pub blame_key_colors: HashMap<String, String>,
+    // The commits ignored by git blame, read when the first blame line is encountered.
+    pub blame_ignore_revs: Option<BlameIgnoreRevs>,
+    // The timestamps of the oldest and newest commits of the blame lines (--blame-heatmap).
+    pub blame_time_range: Option<(i64, i64)>,
+    // The subjects of the commits of the blame lines, read when first needed
+    // (--blame-side-by-side).
+    pub blame_commit_subjects: HashMap<String, Option<String>>,
     pub minus_line_counter: AmbiguousDiffMinusCounter,
+    // Whether the current line is within a private key block (--redact-secrets).
+    pub in_private_key_block: bool,
+    // Whether a carriage return was removed from the end of the current line.
+    pub line_had_cr: bool,
+    // Set when only the location of hunks is wanted (--list-hunks).
+    pub hunk_index: Option<HunkIndex>,
+    // The commit metadata block being condensed (--commit-block-format).
+    pub commit_block: Option<CommitBlock>,
+    // The commit metadata block being laid out in two columns (--commit-columns).
+    pub commit_columns: Option<CommitColumns>,
+    // Whether the next line of a commit message is its subject.
+    pub commit_subject_pending: bool,
+    // Whether the current line is in a fenced code block of a commit message
+    // (--commit-message-markdown).
+    pub in_commit_message_code_block: bool,
+    // The hash of the commit whose diff is being displayed, if any.
+    pub current_commit: Option<String>,
+    // The symbols of the current file (--function-separators).
+    pub file_symbols: Option<FileSymbols>,
+    // The lines read ahead of the current line: those of the current hunk, to count them
+    // (--hunk-change-density), or the output of the filter command (--filter).
+    pub hunk_lookahead: VecDeque<Vec<u8>>,
+    pub change_density: Option<ChangeDensity>,
+    // Whether the output of a file has been opened as a group in the GitHub Actions log and not
+    // yet closed (--github-actions-groups).
+    pub log_group_open: bool,
+    // The configs between which to switch when the terminal's color scheme changes
+    // (--follow-color-scheme).
+    pub color_scheme_follower: Option<ColorSchemeFollower<'a>>,
+    pub terminal_resize_follower: Option<TerminalResizeFollower<'a>>,
+    // Whether the hunks of the current file have been replaced by the output of the filter command
+    // (--filter).
+    pub hunks_filtered: bool,
+    // The abbreviated object ids of the old and new versions of the current file, from its index
+    // line.
+    pub blob_ids: Option<(String, String)>,
+    // Whether the hunks of the current file have been replaced by those of its canonical forms
+    // (--canonicalize-json).
+    pub hunks_canonicalized: bool,
+    // The position in the current hunk, to split it when it is long (--split-hunks).
+    pub hunk_splitter: Option<HunkSplitter>,
+    // The removed and added lines of the diff between patches of `git range-diff` output, painted
+    // together to highlight changes within lines.
+    pub range_diff_lines: MinusPlus<Vec<String>>,
+    // The previous and the current commit of `git show` or `git log` output, to highlight the
+    // changes to the message of an amended commit.
+    pub commit_versions: MinusPlus<CommitVersion>,
+    // The file to which the kind and line numbers of each line are written (--line-map).
+    pub line_map: Option<LineMap>,
+    // The threads highlighting the hunks of the lines read ahead (--threads).
+    pub highlight_pool: Option<HighlightPool>,
+    // The current hunk, copied into a patch of its own (--hunk-apply-commands).
+    pub hunk_patch: Option<HunkPatch>,
+    // The graph to the left of the current line of `git log --graph` output, shared with the
+    // writer adding it to the output lines, and its width at the last commit line.
+    pub log_graph: Option<LogGraph>,
+    pub log_graph_width: Option<usize>,
 }
 
 pub fn delta<I>(lines: ByteLines<I>, writer: &mut dyn Write, config: &Config) -> std::io::Result<()>
 where
     I: BufRead,
 {
-    StateMachine::new(writer, config).consume(lines)
+    // The threads highlighting hunks (--threads) end when the state machine is dropped.
+    std::thread::scope(|scope| {
+        let log_graph = LogGraph::default();
+        let mut writer = LogGraphWriter::new(writer, log_graph.clone(), config);
+        let mut state_machine = StateMachine::new(&mut writer, config);
+        state_machine.log_graph = Some(log_graph);
+        state_machine.highlight_pool = HighlightPool::start(scope, config);
+        state_machine.consume(lines)
+    })
 }
 
 impl<'a> StateMachine<'a> {
@@ -136,19 +236,67 @@ impl<'a> StateMachine<'a> {
             mode_info: "".to_string(),
             current_file_pair: None,
             handled_diff_header_header_line_file_pair: None,
+            pure_renames: Vec::new(),
+            is_pure_rename: false,
             painter: Painter::new(writer, config),
             config,
+            root_config: config,
+            syntax_filename: None,
             blame_key_colors: HashMap::new(),
+            blame_ignore_revs: None,
+            blame_time_range: None,
+            blame_commit_subjects: HashMap::new(),
             minus_line_counter: AmbiguousDiffMinusCounter::not_needed(),
+            in_private_key_block: false,
+            line_had_cr: false,
+            hunk_index: None,
+            commit_block: None,
+            commit_columns: None,
+            commit_subject_pending: false,
+            in_commit_message_code_block: false,
+            current_commit: None,
+            file_symbols: None,
+            hunk_lookahead: VecDeque::new(),
+            change_density: None,
+            log_group_open: false,
+            color_scheme_follower: None,
+            terminal_resize_follower: None,
+            hunks_filtered: false,
+            blob_ids: None,
+            hunks_canonicalized: false,
+            hunk_splitter: None,
+            range_diff_lines: MinusPlus::default(),
+            commit_versions: MinusPlus::default(),
+            line_map: None,
+            highlight_pool: None,
+            hunk_patch: config.hunk_apply_commands.then(HunkPatch::default),
+            log_graph: None,
+            log_graph_width: None,
         }
     }
 
-    fn consume<I>(&mut self, mut lines: ByteLines<I>) -> std::io::Result<()>
+    pub fn consume<I>(&mut self, mut lines: ByteLines<I>) -> std::io::Result<()>
     where
         I: BufRead,
     {
-        while let Some(Ok(raw_line_bytes)) = lines.next() {
-            self.ingest_line(raw_line_bytes);
+        if self.config.color_moved && self.painter.moved_lines.is_none() {
+            return self.consume_with_moved_lines(lines);
+        }
+        // The line map file is created when the input is processed, so that failing to create it
+        // is returned as an error.
+        if let (Some(path), None) = (&self.config.line_map, &self.line_map) {
+            self.line_map = Some(LineMap::create(path)?);
+        }
+        loop {
+            self.read_ahead_for_highlighting(&mut lines);
+            match self.hunk_lookahead.pop_front() {
+                Some(raw_line_bytes) => self.ingest_line(&raw_line_bytes),
+                None => match lines.next() {
+                    Some(Ok(raw_line_bytes)) => self.ingest_line(raw_line_bytes),
+                    _ => break,
+                },
+            }
+            self.remove_log_graph()?;
 
             if self.source == Source::Unknown {
                 self.source = detect_source(&self.line);
@@ -158,35 +306,71 @@ impl<'a> StateMachine<'a> {
                     self.minus_line_counter = AmbiguousDiffMinusCounter::prepare_to_count();
                 }
             }
+            if self.canonicalize_json(&mut lines) || self.filter_hunks(&mut lines) {
+                continue;
+            }
+            if self.config.hunk_change_density {
+                self.read_hunk_ahead(&mut lines);
+            }
+            if self.config.blame_heatmap {
+                self.read_blame_ahead(&mut lines);
+            }
+            self.copy_hunk_patch_line();
+            self.end_pure_renames_before_line()?;
 
             // Every method named handle_* must return std::io::Result<bool>.
             // The bool indicates whether the line has been handled by that
             // method (in which case no subsequent handlers are permitted to
-            // handle it).
-            let _ = self.handle_commit_meta_header_line()?
-                || self.handle_diff_stat_line()?
-                || self.handle_diff_header_diff_line()?
-                || self.handle_diff_header_file_operation_line()?
-                || self.handle_diff_header_minus_line()?
-                || self.handle_diff_header_plus_line()?
-                || self.handle_hunk_header_line()?
-                || self.handle_diff_header_mode_line()?
-                || self.handle_diff_header_misc_line()?
-                || self.handle_submodule_log_line()?
-                || self.handle_submodule_short_line()?
-                || self.handle_merge_conflict_line()?
-                || self.handle_hunk_line()?
-                || self.handle_git_show_file_line()?
-                || self.handle_blame_line()?
-                || self.handle_grep_line()?
+            // handle it). With --input-type, only the handlers of that type
+            // of input are called.
+            let input_type = self.config.input_type;
+            let is_input = |type_| input_type == InputType::Auto || input_type == type_;
+            let _ = (input_type != InputType::Raw
+                && (self.handle_hyperlinks_only_line()? || self.handle_graphics_line()?))
+                || (is_input(InputType::Diff)
+                    && (self.handle_range_diff_line()?
+                        || self.handle_jj_diff_line()?
+                        || self.handle_commit_meta_header_line()?
+                        || self.handle_commit_block_line()?
+                        || self.handle_commit_columns_line()?
+                        || self.handle_commit_merge_line()?
+                        || self.handle_amended_commit_message_line()?
+                        || self.handle_commit_message_line()?
+                        || self.handle_diff_stat_line()?
+                        || self.handle_diff_header_diff_line()?
+                        || self.handle_diff_header_file_operation_line()?
+                        || self.handle_diff_header_minus_line()?
+                        || self.handle_diff_header_plus_line()?
+                        || self.handle_hunk_header_line()?
+                        || self.handle_diff_header_mode_line()?
+                        || self.handle_diff_header_misc_line()?
+                        || self.handle_submodule_log_line()?
+                        || self.handle_submodule_short_line()?
+                        || self.handle_merge_conflict_line()?
+                        || self.handle_patch_apply_line()?
+                        || self.handle_hunk_line()?
+                        || self.handle_git_show_file_line()?))
+                || (is_input(InputType::Blame) && self.handle_blame_line()?)
+                || (is_input(InputType::Grep) && self.handle_grep_line()?)
                 || self.should_skip_line()
                 || self.emit_line_unchanged()?;
+            self.record_line_map()?;
+            warnings::check_error()?;
         }
 
+        self.flush_commit_block()?;
+        self.flush_commit_columns()?;
+        self.flush_pure_renames()?;
         self.handle_pending_line_with_diff_name()?;
+        self.finish_hunk_patch();
         self.painter.paint_buffered_minus_and_plus_lines();
+        self.paint_buffered_range_diff_lines();
         self.painter.emit()?;
-        Ok(())
+        self.end_log_group()?;
+        if let Some(line_map) = &mut self.line_map {
+            line_map.flush()?;
+        }
+        warnings::check_error()
     }
 
     fn ingest_line(&mut self, raw_line_bytes: &[u8]) {
@@ -206,20 +390,29 @@ impl<'a> StateMachine<'a> {
 
     fn ingest_line_utf8(&mut self, raw_line: String) {
         self.raw_line = raw_line;
-        // When a file has \r\n line endings, git sometimes adds ANSI escape sequences between the
-        // \r and \n, in which case byte_lines does not remove the \r. Remove it now.
+        self.line_had_cr = false;
+        // The \r of \r\n line endings is kept by utils::lines::byte_lines, and git sometimes adds
+        // ANSI escape sequences between the \r and \n. Remove it now.
         // TODO: Limit the number of characters we examine when looking for the \r?
-        if let Some(cr_index) = self.raw_line.rfind('\r') {
-            if ansi::measure_text_width(&self.raw_line[cr_index + 1..]) == 0 {
-                self.raw_line = format!(
-                    "{}{}",
-                    &self.raw_line[..cr_index],
-                    &self.raw_line[cr_index + 1..]
-                );
+        if self.config.strip_cr != StripCr::Never {
+            if let Some(cr_index) = self.raw_line.rfind('\r') {
+                if ansi::measure_text_width(&self.raw_line[cr_index + 1..]) == 0 {
+                    self.raw_line = format!(
+                        "{}{}",
+                        &self.raw_line[..cr_index],
+                        &self.raw_line[cr_index + 1..]
+                    );
+                    self.line_had_cr = true;
+                }
             }
         }
+        if self.config.strip_cr == StripCr::Always && self.raw_line.contains('\r') {
+            self.raw_line = self.raw_line.replace('\r', "");
+        }
         if self.config.max_line_length > 0
             && self.raw_line.len() > self.config.max_line_length
+            // Do not truncate inline images
+            && !ansi::contains_graphics_sequence(&self.raw_line)
             // Do not truncate long hunk headers
             && !self.raw_line.starts_with("@@")
             // Do not truncate ripgrep --json output
@@ -267,7 +460,15 @@ impl<'a> StateMachine<'a> {
 // Although raw output should basically be emitted unaltered, we do this.
 pub fn format_raw_line<'a>(line: &'a str, config: &Config) -> Cow<'a, str> {
     if config.hyperlinks && io::stdout().is_terminal() {
-        features::hyperlinks::format_commit_line_with_osc8_commit_hyperlink(line, config)
+        match features::hyperlinks::format_commit_line_with_osc8_commit_hyperlink(line, config) {
+            Cow::Borrowed(line) => {
+                features::hyperlinks::format_issue_references_with_osc8_hyperlinks(line, config)
+            }
+            Cow::Owned(line) => Cow::from(
+                features::hyperlinks::format_issue_references_with_osc8_hyperlinks(&line, config)
+                    .into_owned(),
+            ),
+        }
     } else {
         Cow::from(line)
     }
@@ -278,6 +479,9 @@ pub fn format_raw_line<'a>(line: &'a str, config: &Config) -> Cow<'a, str> {
 /// Currently can detect:
 /// * git diff
 /// * diff -u
+/// * jj diff
+/// * hg diff
+/// * svn diff
 fn detect_source(line: &str) -> Source {
     if line.starts_with("commit ")
         || line.starts_with("diff --git ")
@@ -285,6 +489,10 @@ fn detect_source(line: &str) -> Source {
         || line.starts_with("diff --combined ")
     {
         Source::GitDiff
+    } else if handlers::diff_header::is_hg_diff_line(line) {
+        Source::Mercurial
+    } else if line.starts_with("Index: ") {
+        Source::Subversion
     } else if line.starts_with("diff -u")
         || line.starts_with("diff -ru")
         || line.starts_with("diff -r -u")
@@ -293,6 +501,8 @@ fn detect_source(line: &str) -> Source {
         || line.starts_with("Only in ")
     {
         Source::DiffUnified
+    } else if handlers::jj_diff::is_jj_file_header_line(line) {
+        Source::Jujutsu
     } else {
         Source::Unknown
     }
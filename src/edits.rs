@@ -4,8 +4,55 @@ use unicode_segmentation::UnicodeSegmentation;
 use unicode_width::UnicodeWidthStr;
 
 use crate::align;
+use crate::config::Config;
 use crate::minusplus::MinusPlus;
 
+/// How lines are split into tokens for alignment.
+pub struct Tokenizer<'a> {
+    regex: &'a Regex,
+    // The grammar of the language of the lines (--tree-sitter-tokenization).
+    #[cfg(feature = "tree-sitter")]
+    language: Option<tree_sitter::Language>,
+}
+
+impl<'a> Tokenizer<'a> {
+    /// Split lines into the matches of the regex, and the characters between them.
+    pub fn new(regex: &'a Regex) -> Self {
+        Self {
+            regex,
+            #[cfg(feature = "tree-sitter")]
+            language: None,
+        }
+    }
+
+    /// Split lines of the language of the syntax into its tokens if --tree-sitter-tokenization is
+    /// given and there is a grammar for it, and otherwise into the matches of
+    /// --word-diff-regex.
+    #[cfg_attr(not(feature = "tree-sitter"), allow(unused_variables))]
+    pub fn for_syntax(syntax_name: &str, config: &'a Config) -> Self {
+        #[cfg(feature = "tree-sitter")]
+        if config.tree_sitter_tokenization {
+            return Self {
+                language: crate::utils::tree_sitter::language(syntax_name),
+                ..Self::new(&config.tokenization_regex)
+            };
+        }
+        Self::new(&config.tokenization_regex)
+    }
+
+    fn tokenize<'b>(&self, line: &'b str) -> Vec<&'b str> {
+        #[cfg(feature = "tree-sitter")]
+        if let Some(tokens) = self
+            .language
+            .as_ref()
+            .and_then(|language| crate::utils::tree_sitter::tokenize(line, language, self.regex))
+        {
+            return tokens;
+        }
+        tokenize(line, self.regex)
+    }
+}
+
 /// Infer the edit operations responsible for the differences between a collection of old and new
 /// lines. A "line" is a string. An annotated line is a Vec of (op, &str) pairs, where the &str
 /// slices are slices of the line, and their concatenation equals the line. Return the input minus
@@ -28,9 +75,10 @@ pub fn infer_edits<'a, EditOperation>(
     deletion: EditOperation,
     noop_insertions: Vec<EditOperation>,
     insertion: EditOperation,
-    tokenization_regex: &Regex,
+    tokenizer: &Tokenizer,
     max_line_distance: f64,
     max_line_distance_for_naively_paired_lines: f64,
+    max_line_length: usize,
 ) -> (
     Vec<Vec<(EditOperation, &'a str)>>,  // annotated minus lines
     Vec<Vec<(EditOperation, &'a str)>>,  // annotated plus lines
@@ -48,9 +96,16 @@ where
     'minus_lines_loop: for (minus_index, minus_line) in minus_lines.iter().enumerate() {
         let mut considered = 0; // plus lines considered so far as match for minus_line
         for plus_line in &plus_lines[plus_index..] {
+            // Aligning the tokens of very long lines is too slow: leave them unpaired.
+            if is_longer_than(minus_line, max_line_length)
+                || is_longer_than(plus_line, max_line_length)
+            {
+                considered += 1;
+                continue;
+            }
             let alignment = align::Alignment::new(
-                tokenize(minus_line, tokenization_regex),
-                tokenize(plus_line, tokenization_regex),
+                tokenizer.tokenize(minus_line),
+                tokenizer.tokenize(plus_line),
             );
             let (annotated_minus_line, annotated_plus_line, distance) = annotate(
                 alignment,
@@ -105,6 +160,12 @@ where
     (annotated_minus_lines, annotated_plus_lines, line_alignment)
 }
 
+/// Whether `line` has more than `max_length` characters. A `max_length` of zero means no limit.
+pub fn is_longer_than(line: &str, max_length: usize) -> bool {
+    // Counting characters is only needed if there are more bytes than that.
+    max_length > 0 && line.len() > max_length && line.chars().count() > max_length
+}
+
 // Return `None` if there is no trailing whitespace.
 // Return `Some(content)` where content is trimmed if there was some trailing whitespace
 fn get_contents_before_trailing_whitespace(line: &str) -> Option<&str> {
@@ -137,7 +198,7 @@ pub fn make_lines_have_homolog(
 
 /// Split line into tokens for alignment. The alignment algorithm aligns sequences of substrings;
 /// not individual characters.
-fn tokenize<'a>(line: &'a str, regex: &Regex) -> Vec<&'a str> {
+pub fn tokenize<'a>(line: &'a str, regex: &Regex) -> Vec<&'a str> {
     // Starting with "", see comment in Alignment::new(). Historical note: Replacing the '+/-'
     // prefix with a space implicitly generated this.
     let mut tokens = vec![""];
@@ -912,9 +973,10 @@ mod tests {
             Deletion,
             noop_insertions,
             Insertion,
-            &DEFAULT_TOKENIZATION_REGEXP,
+            &Tokenizer::new(&DEFAULT_TOKENIZATION_REGEXP),
             max_line_distance,
             0.0,
+            0,
         );
         // compare_annotated_lines(actual_edits, expected_edits);
         // TODO: test line alignment
@@ -1023,4 +1085,28 @@ mod tests {
     fn is_edit(edit: &EditOperation) -> bool {
         *edit == Deletion || *edit == Insertion
     }
+
+    #[test]
+    fn test_infer_edits_leaves_long_lines_unpaired() {
+        let (minus_lines, plus_lines, _) = infer_edits(
+            vec!["a b c d e f", "x"],
+            vec!["a b c d e g", "y"],
+            vec![MinusNoop, MinusNoop],
+            Deletion,
+            vec![PlusNoop, PlusNoop],
+            Insertion,
+            &Tokenizer::new(&DEFAULT_TOKENIZATION_REGEXP),
+            1.0,
+            0.0,
+            10,
+        );
+        // The long lines are not paired, while the short ones still are.
+        assert_eq!(minus_lines[0], vec![(MinusNoop, "a b c d e f")]);
+        assert_eq!(plus_lines[0], vec![(PlusNoop, "a b c d e g")]);
+        assert!(minus_lines[1].contains(&(Deletion, "x")));
+        assert!(plus_lines[1].contains(&(Insertion, "y")));
+        assert!(is_longer_than("a b c d e f", 10));
+        assert!(!is_longer_than("二æäöø€ÆÄÖ", 10));
+        assert!(!is_longer_than("a b c d e f", 0));
+    }
 }
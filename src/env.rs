@@ -1,26 +1,50 @@
 use std::env;
 
 const COLORTERM: &str = "COLORTERM";
+const COLUMNS: &str = "COLUMNS";
+const CONEMU_ANSI: &str = "ConEmuANSI";
 const BAT_THEME: &str = "BAT_THEME";
 const GIT_CONFIG_PARAMETERS: &str = "GIT_CONFIG_PARAMETERS";
+const GIT_PAGER_IN_USE: &str = "GIT_PAGER_IN_USE";
 const GIT_PREFIX: &str = "GIT_PREFIX";
+const GITHUB_ACTIONS: &str = "GITHUB_ACTIONS";
+const LANG: &str = "LANG";
+const LC_ALL: &str = "LC_ALL";
+const LC_NUMERIC: &str = "LC_NUMERIC";
+const LC_TIME: &str = "LC_TIME";
+const TERM: &str = "TERM";
 const DELTA_FEATURES: &str = "DELTA_FEATURES";
+const DELTA_LINE_MAP: &str = "DELTA_LINE_MAP";
 const DELTA_NAVIGATE: &str = "DELTA_NAVIGATE";
 const DELTA_EXPERIMENTAL_MAX_LINE_DISTANCE_FOR_NAIVELY_PAIRED_LINES: &str =
     "DELTA_EXPERIMENTAL_MAX_LINE_DISTANCE_FOR_NAIVELY_PAIRED_LINES";
 const DELTA_PAGER: &str = "DELTA_PAGER";
+const DELTA_PICKER: &str = "DELTA_PICKER";
+const WT_SESSION: &str = "WT_SESSION";
 
 #[derive(Default, Clone)]
 pub struct DeltaEnv {
     pub bat_theme: Option<String>,
     pub colorterm: Option<String>,
+    pub columns: Option<String>,
+    pub conemu_ansi: Option<String>,
     pub current_dir: Option<std::path::PathBuf>,
     pub experimental_max_line_distance_for_naively_paired_lines: Option<String>,
     pub features: Option<String>,
     pub git_config_parameters: Option<String>,
+    pub git_pager_in_use: Option<String>,
     pub git_prefix: Option<String>,
+    pub github_actions: Option<String>,
+    pub lang: Option<String>,
+    pub lc_all: Option<String>,
+    pub lc_numeric: Option<String>,
+    pub lc_time: Option<String>,
+    pub line_map: Option<String>,
     pub navigate: Option<String>,
     pub pagers: (Option<String>, Option<String>),
+    pub picker: Option<String>,
+    pub term: Option<String>,
+    pub wt_session: Option<String>,
 }
 
 impl DeltaEnv {
@@ -28,12 +52,24 @@ impl DeltaEnv {
     pub fn init() -> Self {
         let bat_theme = env::var(BAT_THEME).ok();
         let colorterm = env::var(COLORTERM).ok();
+        let columns = env::var(COLUMNS).ok();
+        let conemu_ansi = env::var(CONEMU_ANSI).ok();
         let experimental_max_line_distance_for_naively_paired_lines =
             env::var(DELTA_EXPERIMENTAL_MAX_LINE_DISTANCE_FOR_NAIVELY_PAIRED_LINES).ok();
         let features = env::var(DELTA_FEATURES).ok();
         let git_config_parameters = env::var(GIT_CONFIG_PARAMETERS).ok();
+        let git_pager_in_use = env::var(GIT_PAGER_IN_USE).ok();
         let git_prefix = env::var(GIT_PREFIX).ok();
+        let github_actions = env::var(GITHUB_ACTIONS).ok();
+        let lang = env::var(LANG).ok();
+        let lc_all = env::var(LC_ALL).ok();
+        let lc_numeric = env::var(LC_NUMERIC).ok();
+        let lc_time = env::var(LC_TIME).ok();
+        let line_map = env::var(DELTA_LINE_MAP).ok();
         let navigate = env::var(DELTA_NAVIGATE).ok();
+        let picker = env::var(DELTA_PICKER).ok();
+        let term = env::var(TERM).ok();
+        let wt_session = env::var(WT_SESSION).ok();
 
         let current_dir = env::current_dir().ok();
         let pagers = (
@@ -48,13 +84,25 @@ impl DeltaEnv {
         Self {
             bat_theme,
             colorterm,
+            columns,
+            conemu_ansi,
             current_dir,
             experimental_max_line_distance_for_naively_paired_lines,
             features,
             git_config_parameters,
+            git_pager_in_use,
             git_prefix,
+            github_actions,
+            lang,
+            lc_all,
+            lc_numeric,
+            lc_time,
+            line_map,
             navigate,
             pagers,
+            picker,
+            term,
+            wt_session,
         }
     }
 }
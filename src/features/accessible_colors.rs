@@ -0,0 +1,239 @@
+use crate::color::ColorMode::*;
+use crate::features::OptionValueFunction;
+
+/// Removed and added lines in orange and blue, which remain distinct with red-green color
+/// blindness (deuteranopia and protanopia). The colors are those of the Okabe-Ito palette.
+pub fn make_deuteranopia_feature() -> Vec<(String, OptionValueFunction)> {
+    builtin_feature!([
+        (
+            "minus-style",
+            String,
+            None,
+            opt => match opt.computed.color_mode {
+                Light => "syntax #ffedd1",
+                Dark => "syntax #3d2600",
+            }
+        ),
+        (
+            "minus-emph-style",
+            String,
+            None,
+            opt => match opt.computed.color_mode {
+                Light => "syntax #ffcf85",
+                Dark => "syntax #6e4400",
+            }
+        ),
+        (
+            "plus-style",
+            String,
+            None,
+            opt => match opt.computed.color_mode {
+                Light => "syntax #dfefff",
+                Dark => "syntax #0b2a45",
+            }
+        ),
+        (
+            "plus-emph-style",
+            String,
+            None,
+            opt => match opt.computed.color_mode {
+                Light => "syntax #a9d4ff",
+                Dark => "syntax #134a78",
+            }
+        ),
+        (
+            "line-numbers-minus-style",
+            String,
+            None,
+            opt => match opt.computed.color_mode {
+                Light => "#a05a00",
+                Dark => "#e69f00",
+            }
+        ),
+        (
+            "line-numbers-zero-style",
+            String,
+            None,
+            opt => match opt.computed.color_mode {
+                Light => "#6e6e6e",
+                Dark => "#8a8a8a",
+            }
+        ),
+        (
+            "line-numbers-plus-style",
+            String,
+            None,
+            opt => match opt.computed.color_mode {
+                Light => "#005a9e",
+                Dark => "#56b4e9",
+            }
+        )
+    ])
+}
+
+/// Removed and added lines in red and teal, which remain distinct with blue-yellow color
+/// blindness (tritanopia).
+pub fn make_tritanopia_feature() -> Vec<(String, OptionValueFunction)> {
+    builtin_feature!([
+        (
+            "minus-style",
+            String,
+            None,
+            opt => match opt.computed.color_mode {
+                Light => "syntax #ffe3ea",
+                Dark => "syntax #4a1022",
+            }
+        ),
+        (
+            "minus-emph-style",
+            String,
+            None,
+            opt => match opt.computed.color_mode {
+                Light => "syntax #ffb8c8",
+                Dark => "syntax #80203c",
+            }
+        ),
+        (
+            "plus-style",
+            String,
+            None,
+            opt => match opt.computed.color_mode {
+                Light => "syntax #d9f6f6",
+                Dark => "syntax #00393b",
+            }
+        ),
+        (
+            "plus-emph-style",
+            String,
+            None,
+            opt => match opt.computed.color_mode {
+                Light => "syntax #9ee8e8",
+                Dark => "syntax #006166",
+            }
+        ),
+        (
+            "line-numbers-minus-style",
+            String,
+            None,
+            opt => match opt.computed.color_mode {
+                Light => "#b0134a",
+                Dark => "#ff7a9c",
+            }
+        ),
+        (
+            "line-numbers-zero-style",
+            String,
+            None,
+            opt => match opt.computed.color_mode {
+                Light => "#6e6e6e",
+                Dark => "#8a8a8a",
+            }
+        ),
+        (
+            "line-numbers-plus-style",
+            String,
+            None,
+            opt => match opt.computed.color_mode {
+                Light => "#00707a",
+                Dark => "#4fd6d6",
+            }
+        )
+    ])
+}
+
+/// Black or white text on strongly colored backgrounds, without syntax highlighting, for low
+/// vision or displays with poor contrast. Every style it sets passes --check-contrast.
+pub fn make_high_contrast_feature() -> Vec<(String, OptionValueFunction)> {
+    builtin_feature!([
+        (
+            "minus-style",
+            String,
+            None,
+            opt => match opt.computed.color_mode {
+                Light => "#000000 #ffd0d0",
+                Dark => "#ffffff #5f0000",
+            }
+        ),
+        (
+            "minus-emph-style",
+            String,
+            None,
+            opt => match opt.computed.color_mode {
+                Light => "bold #000000 #ff9090",
+                Dark => "bold #ffffff #a00000",
+            }
+        ),
+        (
+            "zero-style",
+            String,
+            None,
+            opt => match opt.computed.color_mode {
+                Light => "#000000",
+                Dark => "#ffffff",
+            }
+        ),
+        (
+            "plus-style",
+            String,
+            None,
+            opt => match opt.computed.color_mode {
+                Light => "#000000 #d0ffd0",
+                Dark => "#ffffff #004d00",
+            }
+        ),
+        (
+            "plus-emph-style",
+            String,
+            None,
+            opt => match opt.computed.color_mode {
+                Light => "bold #000000 #80e080",
+                Dark => "bold #ffffff #007a00",
+            }
+        ),
+        (
+            "file-style",
+            String,
+            None,
+            opt => match opt.computed.color_mode {
+                Light => "bold #000000",
+                Dark => "bold #ffffff",
+            }
+        ),
+        (
+            "hunk-header-style",
+            String,
+            None,
+            opt => match opt.computed.color_mode {
+                Light => "line-number bold #000000",
+                Dark => "line-number bold #ffffff",
+            }
+        ),
+        (
+            "line-numbers-minus-style",
+            String,
+            None,
+            opt => match opt.computed.color_mode {
+                Light => "#a00000",
+                Dark => "#ff8080",
+            }
+        ),
+        (
+            "line-numbers-zero-style",
+            String,
+            None,
+            opt => match opt.computed.color_mode {
+                Light => "#303030",
+                Dark => "#d0d0d0",
+            }
+        ),
+        (
+            "line-numbers-plus-style",
+            String,
+            None,
+            opt => match opt.computed.color_mode {
+                Light => "#006000",
+                Dark => "#80ff80",
+            }
+        )
+    ])
+}
@@ -0,0 +1,177 @@
+use std::fs;
+use std::io::BufRead;
+use std::path::Path;
+
+use bytelines::ByteLines;
+
+use crate::config::Config;
+use crate::delta::{DiffType, State, StateMachine};
+use crate::handlers::hunk_header;
+
+impl<'a> StateMachine<'a> {
+    /// If the current line is the first hunk header of a file matching the globs, replace its
+    /// hunks by those of the diff of the canonical forms of its versions (--canonicalize-json).
+    /// Return true if the current line should be skipped, since it has been replaced.
+    pub fn canonicalize_json<I: BufRead>(&mut self, lines: &mut ByteLines<I>) -> bool {
+        if self.config.canonicalize_json.is_empty() {
+            return false;
+        }
+        let lengths = match self.state {
+            State::DiffHeader(DiffType::Unified) => {
+                hunk_header::parse_unified_hunk_lengths(&self.line)
+            }
+            _ => None,
+        };
+        // The hunks of the canonical forms are not canonicalized again.
+        let (Some(lengths), Some((minus_id, plus_id)), false) =
+            (lengths, self.blob_ids.clone(), self.hunks_canonicalized)
+        else {
+            self.hunks_canonicalized = false;
+            return false;
+        };
+        let path = self.plus_file.clone();
+        if !self
+            .config
+            .canonicalize_json
+            .iter()
+            .any(|regex| regex.is_match(&path))
+        {
+            return false;
+        }
+        let hunks = match read_versions(&minus_id, &plus_id, &path, self.config) {
+            Some((minus, plus)) => canonical_hunks(&minus, &plus, &path, self.config),
+            None => None,
+        };
+        let Some(hunks) = hunks else {
+            return false;
+        };
+        let (_, next_line) = self.read_hunks(lengths, lines);
+        self.replace_hunks(&hunks, next_line);
+        self.hunks_canonicalized = true;
+        true
+    }
+}
+
+/// The hunks of the diff of the canonical forms of two versions of a JSON file, in which object
+/// keys are sorted, or None if either version is not valid JSON. Changes only reordering keys or
+/// reformatting the file yield no hunks.
+pub fn canonical_hunks(minus: &[u8], plus: &[u8], path: &str, config: &Config) -> Option<Vec<u8>> {
+    let canonical = |contents: &[u8]| {
+        let value: serde_json::Value = serde_json::from_slice(contents).ok()?;
+        let mut canonical = serde_json::to_vec_pretty(&value).ok()?;
+        canonical.push(b'\n');
+        Some(canonical)
+    };
+    let (minus, plus) = (canonical(minus)?, canonical(plus)?);
+    let mut options = git2::DiffOptions::new();
+    options.context_lines(config.context.unwrap_or(3) as u32);
+    let path = Path::new(path);
+    let mut patch =
+        git2::Patch::from_buffers(&minus, Some(path), &plus, Some(path), Some(&mut options))
+            .ok()?;
+    let patch = patch.to_buf().ok()?;
+    // The hunks follow the diff header of the patch.
+    let hunks_start = patch
+        .split_inclusive(|&byte| byte == b'\n')
+        .take_while(|line| !line.starts_with(b"@@"))
+        .map(<[u8]>::len)
+        .sum();
+    Some(patch[hunks_start..].to_vec())
+}
+
+// The old and new versions of a file, from the repository delta is run in. The new version of a
+// file changed in the working tree is not in the repository, and is read from the file.
+fn read_versions(
+    minus_id: &str,
+    plus_id: &str,
+    path: &str,
+    config: &Config,
+) -> Option<(Vec<u8>, Vec<u8>)> {
+    let repo = git2::Repository::discover(config.cwd_of_delta_process.as_ref()?).ok()?;
+    let read_blob = |id: &str| {
+        // An id of zeros stands for an added or removed file.
+        if id.bytes().all(|byte| byte == b'0') {
+            return None;
+        }
+        let blob = repo.revparse_single(id).ok()?.peel_to_blob().ok()?;
+        Some(blob.content().to_vec())
+    };
+    let minus = read_blob(minus_id)?;
+    let plus = match read_blob(plus_id) {
+        Some(plus) => plus,
+        None if !plus_id.bytes().all(|byte| byte == b'0') => {
+            fs::read(repo.workdir()?.join(path)).ok()?
+        }
+        None => return None,
+    };
+    Some((minus, plus))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::integration_test_utils::{self, DeltaTest};
+    use crate::tests::test_utils::temp_path;
+
+    #[test]
+    fn test_canonical_hunks() {
+        let config = integration_test_utils::make_config_from_args(&[]);
+        let hunks = |minus: &str, plus: &str| {
+            canonical_hunks(minus.as_bytes(), plus.as_bytes(), "a.json", &config)
+                .map(|hunks| String::from_utf8(hunks).unwrap())
+        };
+        assert_eq!(
+            hunks(r#"{"a": 1, "b": [1, 2]}"#, r#"{"b":[1,2],"a":1}"#).as_deref(),
+            Some("")
+        );
+        assert_eq!(
+            hunks(r#"{"a": 1, "b": 2}"#, r#"{"b": 3, "a": 1}"#).as_deref(),
+            Some("@@ -1,4 +1,4 @@\n {\n   \"a\": 1,\n-  \"b\": 2\n+  \"b\": 3\n }\n")
+        );
+        assert_eq!(hunks("{", "{}"), None);
+    }
+
+    #[test]
+    fn test_canonicalize_json() {
+        let dir = temp_path("test_canonicalize_json");
+        let _ = fs::remove_dir_all(&dir);
+        let repo = git2::Repository::init(&dir).unwrap();
+        let minus_id = repo.blob(br#"{"a": 1, "b": 2, "c": 3}"#).unwrap();
+        let plus_id = repo
+            .blob(b"{\n  \"c\": 3,\n  \"b\": 2,\n  \"a\": 4\n}\n")
+            .unwrap();
+
+        let mut config =
+            integration_test_utils::make_config_from_args(&["--canonicalize-json", "*.json"]);
+        config.cwd_of_delta_process = Some(dir.clone());
+        let input = DIFF
+            .replace("MINUS_ID", &minus_id.to_string()[..7])
+            .replace("PLUS_ID", &plus_id.to_string()[..7]);
+        DeltaTest::with_config(&config)
+            .with_input(&input)
+            .expect_contains("\n{\n  \"a\": 1,\n  \"a\": 4,\n  \"b\": 2,\n  \"c\": 3\n}\n")
+            // The file not matching the globs is displayed as usual.
+            .expect_contains("\n{\"b\": 3}\n");
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    const DIFF: &str = r#"diff --git a/a.json b/a.json
+index MINUS_ID..PLUS_ID 100644
+--- a/a.json
++++ b/a.json
+@@ -1 +1,5 @@
+-{"a": 1, "b": 2, "c": 3}
++{
++  "c": 3,
++  "b": 2,
++  "a": 4
++}
+diff --git a/b.txt b/b.txt
+index 1111111..2222222 100644
+--- a/b.txt
++++ b/b.txt
+@@ -1 +1 @@
+-{"b": 2}
++{"b": 3}
+"#;
+}
@@ -0,0 +1,140 @@
+use crate::config::Config;
+
+const BAR_WIDTH: usize = 8;
+
+/// The numbers of added, removed and unchanged lines of a hunk, displayed in its header as a bar
+/// and counts, e.g. `▰▰▰▱▱▱▱▱ 12+/31-` (--hunk-change-density).
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct ChangeDensity {
+    plus: usize,
+    minus: usize,
+    zero: usize,
+}
+
+impl ChangeDensity {
+    /// Count a line of the hunk (without ANSI escape sequences). Return false if it is not a hunk
+    /// line, i.e. the hunk has ended.
+    pub fn count_line(&mut self, line: &str) -> bool {
+        match line.chars().next() {
+            Some('+') => self.plus += 1,
+            Some('-') => self.minus += 1,
+            Some(' ') | None => self.zero += 1,
+            // "\ No newline at end of file"
+            Some('\\') => {}
+            _ => return false,
+        }
+        true
+    }
+
+    /// Whether all lines of a hunk with the given lengths of the old and new file have been
+    /// counted.
+    pub fn is_complete(&self, minus_length: usize, plus_length: usize) -> bool {
+        self.minus + self.zero >= minus_length && self.plus + self.zero >= plus_length
+    }
+
+    /// The bar, in which the filled cells are the fraction of changed lines, followed by the
+    /// numbers of added and removed lines.
+    pub fn format(&self, config: &Config) -> String {
+        let changed = self.plus + self.minus;
+        let total = changed + self.zero;
+        let filled = match changed {
+            0 => 0,
+            // A hunk with any change has at least one filled cell.
+            _ => ((BAR_WIDTH * changed + total / 2) / total).max(1),
+        };
+        let plus_cells = match changed {
+            0 => 0,
+            _ => (filled * self.plus + changed / 2) / changed,
+        };
+        let (plus_style, minus_style) = (
+            config.hunk_change_density_plus_style,
+            config.hunk_change_density_minus_style,
+        );
+        format!(
+            "{}{}{} {}/{}",
+            plus_style.paint("▰".repeat(plus_cells)),
+            minus_style.paint("▰".repeat(filled - plus_cells)),
+            "▱".repeat(BAR_WIDTH - filled),
+            plus_style.paint(format!("{}+", config.locale.format_number(self.plus))),
+            minus_style.paint(format!("{}-", config.locale.format_number(self.minus))),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::integration_test_utils::{self, DeltaTest};
+
+    fn change_density(lines: &[&str]) -> ChangeDensity {
+        let mut change_density = ChangeDensity::default();
+        for line in lines {
+            assert!(change_density.count_line(line));
+        }
+        change_density
+    }
+
+    #[test]
+    fn test_format() {
+        let config = integration_test_utils::make_config_from_args(&[
+            "--hunk-change-density-plus-style",
+            "normal",
+            "--hunk-change-density-minus-style",
+            "normal",
+        ]);
+        let format = |lines: &[&str]| change_density(lines).format(&config);
+        assert_eq!(format(&[" a", "-b", "+c", " d"]), "▰▰▰▰▱▱▱▱ 1+/1-");
+        assert_eq!(format(&["+a", "+b", "+c"]), "▰▰▰▰▰▰▰▰ 3+/0-");
+        assert_eq!(format(&[" a"; 20]), "▱▱▱▱▱▱▱▱ 0+/0-");
+        let mut lines = vec![" a"; 99];
+        lines.push("-b");
+        assert_eq!(format(&lines), "▰▱▱▱▱▱▱▱ 0+/1-");
+    }
+
+    #[test]
+    fn test_is_complete() {
+        let mut change_density = change_density(&[" a", "-b"]);
+        assert!(!change_density.is_complete(2, 2));
+        assert!(change_density.count_line("+c"));
+        assert!(change_density.is_complete(2, 2));
+        assert!(change_density.count_line("\\ No newline at end of file"));
+        assert!(!change_density.count_line("diff --git a/a b/a"));
+    }
+
+    #[test]
+    fn test_hunk_change_density() {
+        DeltaTest::with_args(&[
+            "--hunk-change-density",
+            "--hunk-change-density-plus-style",
+            "green",
+            "--hunk-change-density-minus-style",
+            "red",
+        ])
+        .explain_ansi()
+        .with_input(TWO_HUNKS_DIFF)
+        .expect_contains("(blue)1(normal): (green)▰▰(red)▰▰(normal)▱▱▱▱ (green)1+(normal)/(red)1-")
+        .expect_contains("(blue)10(normal): (green)▰▰▰▰▰(normal)▱▱▱ (green)2+(normal)/(red)0-");
+        // The lines read ahead to count them are displayed as usual.
+        DeltaTest::with_args(&["--hunk-change-density"])
+            .with_input(TWO_HUNKS_DIFF)
+            .expect_contains("\n1: ▰▰▰▰▱▱▱▱ 1+/1- │\n")
+            .expect_contains("\na\nb\nc\nd\n")
+            .expect_contains("\ne\nf\ng\n");
+    }
+
+    const TWO_HUNKS_DIFF: &str = "\
+diff --git a/file b/file
+index 1111111..2222222 100644
+--- a/file
++++ b/file
+@@ -1,3 +1,3 @@
+ a
+-b
++c
+ d
+@@ -10,1 +10,3 @@
+ e
++f
++g
+";
+}
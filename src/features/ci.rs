@@ -0,0 +1,208 @@
+use crate::features::OptionValueFunction;
+
+/// Settings making the output reproducible in CI logs: it does not depend on the terminal, the
+/// environment or the process which called delta, and uses only ASCII decorations and the 8
+/// standard colors, which web log viewers render.
+pub fn make_feature() -> Vec<(String, OptionValueFunction)> {
+    builtin_feature!([
+        (
+            "width",
+            Option<String>,
+            None,
+            _opt => Some("120".to_string())
+        ),
+        (
+            "paging",
+            String,
+            None,
+            _opt => "never"
+        ),
+        (
+            "hyperlinks",
+            bool,
+            None,
+            _opt => false
+        ),
+        (
+            "no-process-detection",
+            bool,
+            None,
+            _opt => true
+        ),
+        (
+            "ascii-decorations",
+            bool,
+            None,
+            _opt => true
+        ),
+        (
+            "true-color",
+            String,
+            None,
+            _opt => "never"
+        ),
+        (
+            "minus-style",
+            String,
+            None,
+            _opt => "red"
+        ),
+        (
+            "minus-emph-style",
+            String,
+            None,
+            _opt => "red reverse"
+        ),
+        (
+            "plus-style",
+            String,
+            None,
+            _opt => "green"
+        ),
+        (
+            "plus-emph-style",
+            String,
+            None,
+            _opt => "green reverse"
+        ),
+        (
+            "zero-style",
+            String,
+            None,
+            _opt => "normal"
+        ),
+        (
+            "hunk-header-style",
+            String,
+            None,
+            _opt => "file line-number"
+        ),
+        (
+            "line-numbers-minus-style",
+            String,
+            None,
+            _opt => "red"
+        ),
+        (
+            "line-numbers-plus-style",
+            String,
+            None,
+            _opt => "green"
+        ),
+        (
+            "line-numbers-zero-style",
+            String,
+            None,
+            _opt => "normal"
+        ),
+        (
+            "line-numbers-left-style",
+            String,
+            None,
+            _opt => "blue"
+        ),
+        (
+            "line-numbers-right-style",
+            String,
+            None,
+            _opt => "blue"
+        ),
+        (
+            "line-numbers-left-format",
+            String,
+            None,
+            _opt => "{nm:^4}:"
+        ),
+        (
+            "line-numbers-right-format",
+            String,
+            None,
+            _opt => "{np:^4}|"
+        ),
+        (
+            "right-arrow",
+            String,
+            None,
+            _opt => "->  "
+        ),
+        (
+            "wrap-left-symbol",
+            String,
+            None,
+            _opt => "\\"
+        ),
+        (
+            "wrap-right-symbol",
+            String,
+            None,
+            _opt => "\\"
+        ),
+        (
+            "wrap-right-prefix-symbol",
+            String,
+            None,
+            _opt => "."
+        )
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::remove_file;
+
+    use crate::tests::integration_test_utils::{self, DeltaTest};
+    use crate::utils::bat::output::PagingMode;
+
+    #[test]
+    fn test_ci() {
+        let config = integration_test_utils::make_config_from_args(&["--ci"]);
+        assert_eq!(config.decorations_width, crate::cli::Width::Fixed(120));
+        assert_eq!(config.paging_mode, PagingMode::Never);
+        assert!(!config.hyperlinks);
+        assert!(!config.true_color);
+        assert!(config.ascii_decorations);
+        assert_eq!(config.right_arrow, "->  ");
+        assert!(!config.minus_style.is_syntax_highlighted);
+        assert!(!config.zero_style.is_syntax_highlighted);
+    }
+
+    #[test]
+    fn test_ci_respects_gitconfig() {
+        let git_config_contents = b"
+[delta]
+    ci = true
+    width = 80
+";
+        let git_config_path = "delta__test_ci_respects_gitconfig.gitconfig";
+
+        let opt = integration_test_utils::make_options_from_args_and_git_config(
+            &[],
+            Some(git_config_contents),
+            Some(git_config_path),
+        );
+        assert_eq!(opt.width.as_deref(), Some("80"));
+        assert!(opt.no_process_detection);
+
+        remove_file(git_config_path).unwrap();
+    }
+
+    #[test]
+    fn test_ci_output_is_ascii() {
+        let output = DeltaTest::with_args(&["--ci", "--line-numbers"])
+            .with_input(DIFF)
+            .expect_contains("\n--------+\nfile:1: |\n--------+\n")
+            .expect_contains("\n  2 :    |b\n");
+        assert!(output.raw_output.is_ascii());
+    }
+
+    const DIFF: &str = "\
+diff --git a/file b/file
+index 1111111..2222222 100644
+--- a/file
++++ b/file
+@@ -1,2 +1,2 @@
+ a
+-b
++c
+";
+}
@@ -0,0 +1,309 @@
+use std::collections::HashMap;
+use std::io::{BufRead, Cursor};
+
+use bytelines::ByteLines;
+
+use crate::ansi;
+use crate::config::Config;
+use crate::delta::{State, StateMachine};
+use crate::features::change_density::ChangeDensity;
+use crate::handlers::hunk_header;
+use crate::minusplus::*;
+use crate::paint::{self, LineSections};
+use crate::style::Style;
+use crate::utils::lines;
+
+// As in git, a moved block must contain at least this many alphanumeric characters, so that e.g.
+// closing braces are not considered moved.
+const MIN_ALNUM_COUNT: usize = 20;
+
+/// The runs of consecutive removed and added lines of the whole input, in which moved blocks are
+/// looked up (--color-moved).
+#[derive(Debug, Default)]
+pub struct MovedLines {
+    runs: MinusPlus<Vec<Vec<String>>>,
+    // The positions (run, index in run) at which each line occurs.
+    positions: MinusPlus<HashMap<String, Vec<(usize, usize)>>>,
+}
+
+impl MovedLines {
+    /// Collect the removed and added lines of the unified diff hunks among the raw input lines.
+    pub fn from_raw_lines(raw_lines: &[Vec<u8>], config: &Config) -> Self {
+        let mut moved_lines = Self::default();
+        // The lines of the current hunk counted so far, and its numbers of removed and added lines.
+        let mut hunk: Option<(ChangeDensity, usize, usize)> = None;
+        let mut previous_side = None;
+        for raw_line in raw_lines {
+            let line = ansi::strip_ansi_codes(&String::from_utf8_lossy(raw_line));
+            let line = line.trim_end_matches('\r');
+            let in_hunk = match &mut hunk {
+                Some((change_density, minus_length, plus_length)) => {
+                    !change_density.is_complete(*minus_length, *plus_length)
+                        && change_density.count_line(line)
+                }
+                None => false,
+            };
+            if !in_hunk {
+                hunk = hunk_header::parse_unified_hunk_lengths(line).map(
+                    |(minus_length, plus_length)| {
+                        (ChangeDensity::default(), minus_length, plus_length)
+                    },
+                );
+                previous_side = None;
+                continue;
+            }
+            let side = match line.chars().next() {
+                Some('-') => Minus,
+                Some('+') => Plus,
+                _ => {
+                    previous_side = None;
+                    continue;
+                }
+            };
+            if previous_side != Some(side) {
+                moved_lines.runs[side].push(Vec::new());
+            }
+            previous_side = Some(side);
+            moved_lines.push(side, key(&paint::prepare(line, 1, config)).to_string());
+        }
+        moved_lines
+    }
+
+    fn push(&mut self, side: MinusPlusIndex, line: String) {
+        let run = self.runs[side].len() - 1;
+        let index = self.runs[side][run].len();
+        self.positions[side]
+            .entry(line.clone())
+            .or_default()
+            .push((run, index));
+        self.runs[side][run].push(line);
+    }
+
+    /// Replace the style sections of the lines of moved blocks among the removed and added lines
+    /// of a hunk with the moved styles.
+    pub fn apply_moved_styles<'a>(
+        &self,
+        lines: &MinusPlus<&'a Vec<(String, State)>>,
+        diff_style_sections: &mut MinusPlus<Vec<LineSections<'a, Style>>>,
+        config: &Config,
+    ) {
+        for side in [Minus, Plus] {
+            let moved_styles = self.moved_styles(lines[side], side, config);
+            for (((line, _), style_sections), moved_style) in lines[side]
+                .iter()
+                .zip(diff_style_sections[side].iter_mut())
+                .zip(moved_styles)
+            {
+                if let Some(moved_style) = moved_style {
+                    *style_sections = vec![(moved_style, line.as_str())];
+                }
+            }
+        }
+    }
+
+    /// The style of each line of a run of removed (or added) lines: the lines of a block which is
+    /// also a run of added (or removed) lines elsewhere are moved. Longer blocks are preferred,
+    /// and adjacent blocks are styled alternately.
+    fn moved_styles(
+        &self,
+        lines: &[(String, State)],
+        side: MinusPlusIndex,
+        config: &Config,
+    ) -> Vec<Option<Style>> {
+        let other_side = match side {
+            Minus => Plus,
+            Plus => Minus,
+        };
+        let keys: Vec<&str> = lines.iter().map(|(line, _)| key(line)).collect();
+        let mut styles = vec![None; keys.len()];
+        let (mut i, mut previous_block_end, mut alt) = (0, None, false);
+        while i < keys.len() {
+            let block_length = self.positions[other_side]
+                .get(keys[i])
+                .into_iter()
+                .flatten()
+                .map(|&(run, index)| {
+                    keys[i..]
+                        .iter()
+                        .zip(&self.runs[other_side][run][index..])
+                        .take_while(|(line, other_line)| *line == other_line)
+                        .count()
+                })
+                .max()
+                .unwrap_or(0);
+            let block = &keys[i..i + block_length];
+            if block_length == 0 || alnum_count(block) < MIN_ALNUM_COUNT {
+                i += 1;
+                continue;
+            }
+            alt = previous_block_end == Some(i) && !alt;
+            let style = if alt {
+                config.moved_alt_style_minusplus[side]
+            } else {
+                config.moved_style_minusplus[side]
+            };
+            styles[i..i + block_length].fill(Some(style));
+            i += block_length;
+            previous_block_end = Some(i);
+        }
+        styles
+    }
+}
+
+impl<'a> StateMachine<'a> {
+    /// Read the whole input, in which moved lines are then looked up, and process it
+    /// (--color-moved).
+    pub fn consume_with_moved_lines<I: BufRead>(
+        &mut self,
+        mut lines: ByteLines<I>,
+    ) -> std::io::Result<()> {
+        let mut raw_lines = Vec::new();
+        while let Some(raw_line) = lines.next() {
+            raw_lines.push(raw_line?.to_vec());
+        }
+        self.painter.moved_lines = Some(MovedLines::from_raw_lines(&raw_lines, self.config));
+        self.consume(lines::byte_lines(Cursor::new(raw_lines.join(&b'\n'))))
+    }
+}
+
+/// Whether the style sections are those of a line of a moved block, which is then also filled with
+/// its moved style.
+pub fn is_moved_line(
+    style_sections: &[(Style, &str)],
+    side: MinusPlusIndex,
+    config: &Config,
+) -> bool {
+    config.color_moved
+        && matches!(style_sections, [(style, _)]
+            if *style == config.moved_style_minusplus[side]
+                || *style == config.moved_alt_style_minusplus[side])
+}
+
+// The line, as prepared for painting, without its terminating newline.
+fn key(line: &str) -> &str {
+    line.strip_suffix('\n').unwrap_or(line)
+}
+
+fn alnum_count(lines: &[&str]) -> usize {
+    lines
+        .iter()
+        .flat_map(|line| line.chars())
+        .filter(|c| c.is_alphanumeric())
+        .count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::delta::DiffType;
+    use crate::tests::integration_test_utils::{self, DeltaTest};
+
+    #[test]
+    fn test_from_raw_lines() {
+        let config = integration_test_utils::make_config_from_args(&[]);
+        let raw_lines: Vec<Vec<u8>> = MOVED_DIFF.lines().map(|line| line.into()).collect();
+        let moved_lines = MovedLines::from_raw_lines(&raw_lines, &config);
+        assert_eq!(
+            moved_lines.runs[Minus],
+            vec![
+                vec!["fn moved_function() {", "    println!(\"moved\");", "}"],
+                vec!["removed_line();"]
+            ]
+        );
+        assert_eq!(
+            moved_lines.runs[Plus],
+            vec![vec![
+                "fn moved_function() {",
+                "    println!(\"moved\");",
+                "}"
+            ]]
+        );
+        assert_eq!(moved_lines.positions[Plus]["}"], vec![(0, 2)]);
+    }
+
+    #[test]
+    fn test_moved_styles() {
+        let config = integration_test_utils::make_config_from_args(&[]);
+        let mut moved_lines = MovedLines::default();
+        for line in ["aaaaaaaaaaaaaaaaaaaa", "bbbbbbbbbbbbbbbbbbbb", "short"] {
+            moved_lines.runs[Plus].push(Vec::new());
+            moved_lines.push(Plus, line.to_string());
+        }
+
+        let lines = |lines: &[&str]| -> Vec<(String, State)> {
+            lines
+                .iter()
+                .map(|line| {
+                    (
+                        format!("{line}\n"),
+                        State::HunkMinus(DiffType::Unified, None),
+                    )
+                })
+                .collect()
+        };
+        let (moved, alt) = (
+            Some(config.moved_style_minusplus[Minus]),
+            Some(config.moved_alt_style_minusplus[Minus]),
+        );
+        assert_eq!(
+            moved_lines.moved_styles(
+                &lines(&[
+                    "aaaaaaaaaaaaaaaaaaaa",
+                    "bbbbbbbbbbbbbbbbbbbb",
+                    "short",
+                    "other",
+                    "aaaaaaaaaaaaaaaaaaaa"
+                ]),
+                Minus,
+                &config
+            ),
+            // "short" has too few alphanumeric characters to be a moved block.
+            vec![moved, alt, None, None, moved]
+        );
+    }
+
+    #[test]
+    fn test_color_moved() {
+        DeltaTest::with_args(&[
+            "--color-moved",
+            "--moved-minus-style",
+            "magenta",
+            "--moved-plus-style",
+            "cyan",
+            "--minus-style",
+            "red",
+            "--plus-style",
+            "green",
+            "--syntax-theme",
+            "none",
+        ])
+        .explain_ansi()
+        .with_input(MOVED_DIFF)
+        .expect_contains("(purple)fn moved_function() {")
+        .expect_contains("(purple)    println!(\"moved\");")
+        .expect_contains("(red)removed_line();")
+        .expect_contains("(cyan)fn moved_function() {");
+    }
+
+    const MOVED_DIFF: &str = "\
+diff --git a/a.rs b/a.rs
+index 1111111..2222222 100644
+--- a/a.rs
++++ b/a.rs
+@@ -1,5 +1,1 @@
+-fn moved_function() {
+-    println!(\"moved\");
+-}
+ fn main() {}
+-removed_line();
+diff --git a/b.rs b/b.rs
+index 3333333..4444444 100644
+--- a/b.rs
++++ b/b.rs
+@@ -1,1 +1,4 @@
+ fn other() {}
++fn moved_function() {
++    println!(\"moved\");
++}
+";
+}
@@ -0,0 +1,178 @@
+use std::ffi::OsString;
+use std::time::{Duration, Instant};
+
+use crate::cli::{self, Call};
+use crate::color::ColorMode;
+use crate::config::{delta_unreachable, Config};
+use crate::delta::StateMachine;
+use crate::env::DeltaEnv;
+use crate::options::theme;
+use crate::utils;
+
+// The terminal is queried for its color scheme at most this often.
+const QUERY_INTERVAL: Duration = Duration::from_secs(2);
+
+/// The configs for a light and a dark terminal, between which delta switches when the terminal's
+/// color scheme changes (--follow-color-scheme).
+pub struct ColorSchemeFollower<'a> {
+    light: &'a Config,
+    dark: &'a Config,
+    detect_color_mode: fn(Duration) -> Option<ColorMode>,
+    timeout: Duration,
+    last_query: Option<Instant>,
+}
+
+impl<'a> ColorSchemeFollower<'a> {
+    pub fn new(config: &'a Config, other_config: &'a Config) -> Self {
+        let (light, dark) = match config.color_mode {
+            ColorMode::Light => (config, other_config),
+            ColorMode::Dark => (other_config, config),
+        };
+        Self {
+            light,
+            dark,
+            detect_color_mode: theme::detect_color_mode,
+            timeout: config.detect_dark_light_timeout,
+            last_query: None,
+        }
+    }
+
+    /// The config for the terminal's current color scheme, if it is not `current`. Return None
+    /// without querying the terminal if it has been queried recently.
+    fn changed_config(&mut self, current: &Config) -> Option<&'a Config> {
+        if self
+            .last_query
+            .is_some_and(|last_query| last_query.elapsed() < QUERY_INTERVAL)
+        {
+            return None;
+        }
+        self.last_query = Some(Instant::now());
+        let config = match (self.detect_color_mode)(self.timeout)? {
+            ColorMode::Light => self.light,
+            ColorMode::Dark => self.dark,
+        };
+        (!std::ptr::eq(config, current)).then_some(config)
+    }
+
+    /// Use `config` for the terminal's color scheme, e.g. when the terminal has been resized.
+    pub fn set_config(&mut self, config: &'a Config) {
+        match config.color_mode {
+            ColorMode::Light => self.light = config,
+            ColorMode::Dark => self.dark = config,
+        }
+    }
+}
+
+/// Make the config for the other color scheme than that of `config`, from delta's command line
+/// arguments.
+pub fn make_other_config(args: &[OsString], config: &Config, env: &DeltaEnv) -> Config {
+    let mut args = args.to_vec();
+    args.push(match config.color_mode {
+        ColorMode::Light => "--dark".into(),
+        ColorMode::Dark => "--light".into(),
+    });
+    let assets = utils::bat::assets::load_highlighting_assets();
+    match cli::Opt::from_args_and_git_config(args, env, assets) {
+        Call::Delta(opt) => Config::from(opt),
+        _ => delta_unreachable("The arguments were valid without a color mode."),
+    }
+}
+
+impl<'a> StateMachine<'a> {
+    /// Switch to the config for the terminal's current color scheme before displaying a file, if
+    /// the color scheme has changed (--follow-color-scheme).
+    pub fn follow_color_scheme(&mut self) {
+        let config = match self.color_scheme_follower.as_mut() {
+            Some(follower) => follower.changed_config(self.config),
+            None => None,
+        };
+        if let Some(config) = config {
+            self.config = config;
+            self.painter.config = config;
+            // The highlighter is made with the syntax theme of the config for the next hunk.
+            self.painter.highlighter = None;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytelines::ByteLinesReader;
+
+    use super::*;
+    use crate::tests::integration_test_utils;
+
+    #[test]
+    fn test_changed_config() {
+        let dark = integration_test_utils::make_config_from_args(&["--dark"]);
+        let light = integration_test_utils::make_config_from_args(&["--light"]);
+        let mut follower = ColorSchemeFollower::new(&dark, &light);
+        follower.detect_color_mode = |_| Some(ColorMode::Dark);
+        assert!(follower.changed_config(&dark).is_none());
+
+        follower.last_query = None;
+        follower.detect_color_mode = |_| Some(ColorMode::Light);
+        assert!(std::ptr::eq(
+            follower.changed_config(&dark).unwrap(),
+            &light
+        ));
+        // The terminal is not queried again immediately.
+        assert!(follower.changed_config(&dark).is_none());
+
+        follower.last_query = None;
+        follower.detect_color_mode = |_| None;
+        assert!(follower.changed_config(&dark).is_none());
+    }
+
+    #[test]
+    fn test_changed_config_timeout() {
+        let args = ["--dark", "--detect-dark-light-timeout=50"];
+        let dark = integration_test_utils::make_config_from_args(&args);
+        let light = integration_test_utils::make_config_from_args(&["--light"]);
+        let mut follower = ColorSchemeFollower::new(&dark, &light);
+        follower.detect_color_mode =
+            |timeout| (timeout == Duration::from_millis(50)).then_some(ColorMode::Light);
+        assert!(std::ptr::eq(
+            follower.changed_config(&dark).unwrap(),
+            &light
+        ));
+    }
+
+    #[test]
+    fn test_follow_color_scheme() {
+        let dark = integration_test_utils::make_config_from_args(&["--dark"]);
+        let light = integration_test_utils::make_config_from_args(&["--light"]);
+        let mut follower = ColorSchemeFollower::new(&dark, &light);
+        follower.detect_color_mode = |_| Some(ColorMode::Light);
+
+        let mut output = Vec::new();
+        let mut state_machine = StateMachine::new(&mut output, &dark);
+        state_machine.color_scheme_follower = Some(follower);
+        state_machine.consume(DIFF.as_bytes().byte_lines()).unwrap();
+        assert!(std::ptr::eq(state_machine.config, &light));
+        let light_output = integration_test_utils::run_delta(DIFF, &light);
+        assert_eq!(String::from_utf8(output).unwrap(), light_output);
+        assert_ne!(integration_test_utils::run_delta(DIFF, &dark), light_output);
+    }
+
+    #[test]
+    fn test_make_other_config() {
+        let env = DeltaEnv::default();
+        let args = ["delta", "--no-gitconfig"].map(OsString::from);
+        let config = integration_test_utils::make_config_from_args(&["--dark"]);
+        let other_config = make_other_config(&args, &config, &env);
+        assert_eq!(other_config.color_mode, ColorMode::Light);
+        assert_ne!(other_config.minus_style, config.minus_style);
+    }
+
+    const DIFF: &str = "\
+diff --git a/file.rs b/file.rs
+index 1111111..2222222 100644
+--- a/file.rs
++++ b/file.rs
+@@ -1,2 +1,2 @@
+ fn a() {}
+-fn b() {}
++fn c() {}
+";
+}
@@ -0,0 +1,235 @@
+use std::ops::Range;
+
+use lazy_static::lazy_static;
+use regex::Regex;
+
+use crate::delta::State;
+use crate::features::filter;
+use crate::paint::LineSections;
+use crate::style::Style;
+use crate::utils::warnings::{self, WarningKind};
+
+// The text with which a line of code starts if it is a comment line.
+const COMMENT_MARKERS: &[&str] = &[
+    "//", "/*", "*", "#", "--", ";", "%", "<!--", "\"\"\"", "'''",
+];
+
+lazy_static! {
+    // A problem reported by the check command, e.g. "3:12: `teh` -> `the`" or, as reported by
+    // `typos --format brief -`, "-:3:11: `teh` -> `the`": the number of the checked line, and
+    // the column at which the problem is found, or the text in backquotes, or both.
+    static ref REPORT_REGEX: Regex = Regex::new(
+        r"^(?:[^:]*[^:\d][^:]*:)?(?P<line>\d+):(?:(?P<column>\d+):)?(?:.*?`(?P<text>[^`]+)`)?"
+    )
+    .unwrap();
+}
+
+/// The command checking the spelling or style of added comment lines (--comment-check-command).
+pub struct CommentChecker {
+    command: String,
+    // Whether the command has failed, after which it is not run again.
+    failed: bool,
+}
+
+impl CommentChecker {
+    pub fn new(command: &str) -> Self {
+        Self {
+            command: command.to_string(),
+            failed: false,
+        }
+    }
+
+    /// The spans of the added lines in which the command reports problems. The comment lines
+    /// among them are written to the standard input of the command, one per line, and each line
+    /// of its output reports a problem in one of them, e.g. "3:12: `teh` -> `the`". The text in
+    /// backquotes is then marked wherever it appears as a word in the third comment line, or, if
+    /// there is none, the word at column 12.
+    pub fn check(&mut self, plus_lines: &[(String, State)]) -> Vec<Vec<Range<usize>>> {
+        let mut spans = vec![Vec::new(); plus_lines.len()];
+        let comment_lines: Vec<(usize, &str)> = plus_lines
+            .iter()
+            .map(|(line, _)| line.trim_end_matches('\n'))
+            .enumerate()
+            .filter(|(_, line)| is_comment_line(line))
+            .collect();
+        if self.failed || comment_lines.is_empty() {
+            return spans;
+        }
+        let input: String = comment_lines
+            .iter()
+            .map(|(_, line)| format!("{line}\n"))
+            .collect();
+        let output = match filter::run_command_with_input(&self.command, &[], input.as_bytes()) {
+            Ok(output) => output,
+            Err(err) => {
+                warnings::warn(
+                    WarningKind::Command,
+                    format!("Failed to check comments with '{}': {err}", self.command),
+                );
+                self.failed = true;
+                return spans;
+            }
+        };
+        for report in String::from_utf8_lossy(&output).lines() {
+            let Some(caps) = REPORT_REGEX.captures(report) else {
+                continue;
+            };
+            let Some((index, line)) = caps["line"]
+                .parse::<usize>()
+                .ok()
+                .and_then(|n| comment_lines.get(n.checked_sub(1)?))
+            else {
+                continue;
+            };
+            let column = caps
+                .name("column")
+                .and_then(|column| column.as_str().parse::<usize>().ok());
+            spans[*index].extend(match (caps.name("text"), column) {
+                (Some(text), _) => find_word(line, text.as_str()),
+                (None, Some(column)) => word_at(line, column.saturating_sub(1))
+                    .into_iter()
+                    .collect(),
+                (None, None) => Vec::new(),
+            });
+        }
+        spans
+    }
+}
+
+fn is_comment_line(line: &str) -> bool {
+    let text = line.trim_start();
+    COMMENT_MARKERS
+        .iter()
+        .any(|marker| text.starts_with(marker))
+}
+
+// The spans of the occurrences of the text as a word in the line.
+fn find_word(line: &str, text: &str) -> Vec<Range<usize>> {
+    let is_word_char = |c: Option<char>| c.is_some_and(|c| c.is_alphanumeric() || c == '_');
+    line.match_indices(text)
+        .map(|(start, text)| start..start + text.len())
+        .filter(|span| {
+            !is_word_char(line[..span.start].chars().next_back())
+                && !is_word_char(line[span.end..].chars().next())
+        })
+        .collect()
+}
+
+// The span of the word starting at the byte offset in the line, if any.
+fn word_at(line: &str, start: usize) -> Option<Range<usize>> {
+    let rest = line.get(start..)?;
+    let length = rest
+        .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .unwrap_or(rest.len());
+    (length > 0).then_some(start..start + length)
+}
+
+/// Split the style sections of a line at the spans, and style the text within them.
+pub fn apply_comment_check_style(
+    style_sections: &mut LineSections<'_, Style>,
+    spans: &[Range<usize>],
+    check_style: Style,
+) {
+    if spans.is_empty() {
+        return;
+    }
+    let mut new_sections = Vec::with_capacity(style_sections.len() + 2 * spans.len());
+    let mut offset = 0;
+    for (style, text) in style_sections.drain(..) {
+        let section = offset..offset + text.len();
+        // The boundaries of the spans within the section, at which it is split.
+        let mut boundaries: Vec<usize> = spans
+            .iter()
+            .flat_map(|span| [span.start, span.end])
+            .filter(|i| section.contains(i) && text.is_char_boundary(i - offset))
+            .collect();
+        boundaries.extend([section.start, section.end]);
+        boundaries.sort_unstable();
+        boundaries.dedup();
+        for (start, end) in boundaries.iter().zip(boundaries.iter().skip(1)) {
+            let in_span = spans
+                .iter()
+                .any(|span| span.start <= *start && *end <= span.end);
+            let style = if in_span {
+                check_style.on_top_of(style)
+            } else {
+                style
+            };
+            new_sections.push((style, &text[start - offset..end - offset]));
+        }
+        offset = section.end;
+    }
+    *style_sections = new_sections;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::delta::DiffType;
+    use crate::tests::integration_test_utils::DeltaTest;
+
+    #[test]
+    fn test_is_comment_line() {
+        assert!(is_comment_line("    // Teh value"));
+        assert!(is_comment_line("# Teh value"));
+        assert!(is_comment_line(" * Teh value"));
+        assert!(!is_comment_line("let teh = 1; // value"));
+    }
+
+    #[test]
+    fn test_find_word() {
+        assert_eq!(find_word("// teh, tehx teh", "teh"), vec![3..6, 13..16]);
+        assert_eq!(word_at("// teh value", 3), Some(3..6));
+        assert_eq!(word_at("// teh value", 6), None);
+    }
+
+    #[test]
+    fn test_check() {
+        let plus_lines: Vec<(String, State)> = ["let a = 1;\n", "// Teh valeu\n", "# teh\n"]
+            .iter()
+            .map(|line| (line.to_string(), State::HunkPlus(DiffType::Unified, None)))
+            .collect();
+        let mut checker = CommentChecker::new(
+            r#"sh -c "echo '-:1:0: \`Teh\` -> \`The\`'; echo 1:8:; echo 2:9:""#,
+        );
+        assert_eq!(
+            checker.check(&plus_lines),
+            vec![vec![], vec![3..6, 7..12], vec![]]
+        );
+        let mut checker = CommentChecker::new("false");
+        assert_eq!(
+            checker.check(&plus_lines),
+            vec![Vec::<Range<usize>>::new(); 3]
+        );
+        assert!(checker.failed);
+    }
+
+    #[test]
+    fn test_comment_check_command() {
+        DeltaTest::with_args(&[
+            "--comment-check-command",
+            r#"sh -c "echo '-:1:3: \`teh\` -> \`the\`'""#,
+            "--comment-check-style",
+            "red",
+            "--plus-style",
+            "green",
+        ])
+        .explain_ansi()
+        .with_input(
+            "\
+--- a/a.py
++++ b/a.py
+@@ -1 +1,3 @@
+ a = 1
++teh = 2
++# teh value
+",
+        )
+        .expect_after_header(
+            r#"
+            (231)a (203)=(231) (141)1(normal)
+            (green)teh = 2(normal)
+            (green)# (red)teh(green) value(normal)"#,
+        );
+    }
+}
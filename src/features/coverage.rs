@@ -0,0 +1,204 @@
+use std::collections::HashMap;
+
+use lazy_static::lazy_static;
+use regex::Regex;
+
+/// The number of times each line of each file was run by tests, by path as given in the coverage
+/// file and line number. Lines which cannot run, such as comments, are not included.
+pub type Coverage = HashMap<String, HashMap<usize, u64>>;
+
+/// Parse a coverage file (--coverage-file) in the lcov tracefile format, e.g. written by
+/// cargo-llvm-cov or gcov, or the cobertura XML format, e.g. written by coverage.py or
+/// cargo-tarpaulin.
+pub fn parse_coverage(text: &str) -> Coverage {
+    if text.trim_start().starts_with('<') {
+        parse_cobertura(text)
+    } else {
+        parse_lcov(text)
+    }
+}
+
+// E.g. "SF:/src/lib.rs", followed by "DA:12,3" for each line, and "end_of_record".
+fn parse_lcov(text: &str) -> Coverage {
+    let mut coverage = Coverage::new();
+    let mut file = None;
+    for line in text.lines() {
+        if let Some(path) = line.strip_prefix("SF:") {
+            file = Some(coverage.entry(path.trim().to_string()).or_default());
+        } else if line == "end_of_record" {
+            file = None;
+        } else if let (Some(file), Some(data)) = (&mut file, line.strip_prefix("DA:")) {
+            let mut fields = data.split(',');
+            if let (Some(Ok(line_number)), Some(Ok(hits))) = (
+                fields.next().map(|field| field.trim().parse::<usize>()),
+                fields.next().map(|field| field.trim().parse::<u64>()),
+            ) {
+                *file.entry(line_number).or_default() += hits;
+            }
+        }
+    }
+    coverage
+}
+
+lazy_static! {
+    static ref COBERTURA_ELEMENT_REGEX: Regex = Regex::new(r"<(class|line)\s[^>]*>").unwrap();
+    static ref XML_ATTRIBUTE_REGEX: Regex = Regex::new(r#"([\w-]+)\s*=\s*"([^"]*)""#).unwrap();
+}
+
+// E.g. <class filename="src/lib.rs" ...>, followed by <line number="12" hits="3" .../> for each
+// line. The lines of the methods of a class repeat some of those of the class.
+fn parse_cobertura(text: &str) -> Coverage {
+    let mut coverage = Coverage::new();
+    let mut file = None;
+    for element in COBERTURA_ELEMENT_REGEX.captures_iter(text) {
+        let attributes: HashMap<&str, &str> = XML_ATTRIBUTE_REGEX
+            .captures_iter(&element[0])
+            .map(|c| (c.get(1).unwrap().as_str(), c.get(2).unwrap().as_str()))
+            .collect();
+        if &element[1] == "class" {
+            file = attributes
+                .get("filename")
+                .map(|path| coverage.entry(path.to_string()).or_default());
+        } else if let (Some(file), Some(Ok(line_number)), Some(Ok(hits))) = (
+            &mut file,
+            attributes.get("number").map(|n| n.parse::<usize>()),
+            attributes.get("hits").map(|n| n.parse::<u64>()),
+        ) {
+            file.insert(line_number, hits);
+        }
+    }
+    coverage
+}
+
+/// The path in the coverage of the file of the diff `path`, which is relative to the root of the
+/// repository, while the paths of coverage files are often absolute, or relative to a source
+/// directory: the path itself, else the shortest one ending with it, or which it ends with.
+pub fn find_file<'a, V>(coverage: &'a HashMap<String, V>, path: &str) -> Option<&'a str> {
+    if let Some((path, _)) = coverage.get_key_value(path) {
+        return Some(path);
+    }
+    let suffix = format!("/{path}");
+    coverage
+        .keys()
+        .filter(|key| key.ends_with(&suffix) || path.ends_with(&format!("/{key}")))
+        .min_by_key(|key| (key.len(), key.as_str()))
+        .map(String::as_str)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::integration_test_utils::DeltaTest;
+    use crate::tests::test_utils::temp_path;
+
+    #[test]
+    fn test_parse_lcov() {
+        let coverage = parse_coverage(
+            "\
+TN:
+SF:/home/ada/delta/src/a.rs
+FN:1,a
+DA:1,3
+DA:2,0
+DA:3,1,checksum
+end_of_record
+SF:src/b.rs
+DA:1,0
+end_of_record
+",
+        );
+        assert_eq!(coverage.len(), 2);
+        assert_eq!(
+            coverage["/home/ada/delta/src/a.rs"],
+            HashMap::from([(1, 3), (2, 0), (3, 1)])
+        );
+        assert_eq!(coverage["src/b.rs"], HashMap::from([(1, 0)]));
+    }
+
+    #[test]
+    fn test_parse_cobertura() {
+        let coverage = parse_coverage(
+            r#"<?xml version="1.0" ?>
+<coverage line-rate="0.5" version="7.4">
+  <sources><source>/home/ada/delta</source></sources>
+  <packages><package name="src"><classes>
+    <class name="a.py" filename="src/a.py" line-rate="0.5">
+      <methods/>
+      <lines>
+        <line number="1" hits="2"/>
+        <line number="4" hits="0" branch="true" condition-coverage="50% (1/2)"/>
+      </lines>
+    </class>
+  </classes></package></packages>
+</coverage>
+"#,
+        );
+        assert_eq!(coverage.len(), 1);
+        assert_eq!(coverage["src/a.py"], HashMap::from([(1, 2), (4, 0)]));
+    }
+
+    #[test]
+    fn test_find_file() {
+        let coverage = HashMap::from([
+            ("/home/ada/delta/src/a.rs".to_string(), ()),
+            ("/home/ada/other/delta/src/a.rs".to_string(), ()),
+            ("b.py".to_string(), ()),
+            ("src/c.rs".to_string(), ()),
+        ]);
+        assert_eq!(
+            find_file(&coverage, "src/a.rs"),
+            Some("/home/ada/delta/src/a.rs")
+        );
+        assert_eq!(find_file(&coverage, "src/c.rs"), Some("src/c.rs"));
+        assert_eq!(find_file(&coverage, "python/b.py"), Some("b.py"));
+        assert_eq!(find_file(&coverage, "src/d.rs"), None);
+    }
+
+    #[test]
+    fn test_coverage_file() {
+        let path = temp_path("test_coverage_file.info");
+        std::fs::write(
+            &path,
+            "SF:/home/ada/delta/src/a.rs\nDA:1,1\nDA:2,1\nDA:3,0\nDA:4,1\nend_of_record\n",
+        )
+        .unwrap();
+        DeltaTest::with_args(&[
+            "--coverage-file",
+            path.to_str().unwrap(),
+            "--minus-style",
+            "normal",
+            "--minus-emph-style",
+            "normal",
+            "--plus-style",
+            "normal",
+            "--plus-emph-style",
+            "normal",
+            "--syntax-theme",
+            "none",
+        ])
+        .explain_ansi()
+        .with_input(
+            "\
+diff --git a/src/a.rs b/src/a.rs
+--- a/src/a.rs
++++ b/src/a.rs
+@@ -1,3 +1,4 @@
+ fn a() {
+-    b();
++    c();
++    d();
+ }
+",
+        )
+        .expect_after_header(
+            r#"
+#indent_mark
+  fn a() {
+      b();
+(green)▌(normal)     c();
+(red)▌(normal)     d();
+  }"#,
+        );
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,362 @@
+use std::collections::HashMap;
+use std::fs;
+
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde_json::Value;
+
+use crate::ansi::{measure_text_width, strip_ansi_codes};
+use crate::config::Config;
+use crate::delta::State;
+use crate::features::coverage;
+use crate::utils::warnings::{self, WarningKind};
+
+/// A problem reported in a line by a linter or compiler.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Diagnostic {
+    // The column of the problem, counted in characters from 1, if it is known.
+    column: Option<usize>,
+    message: String,
+}
+
+/// The diagnostics of each file, by path as given in the diagnostics file and line number.
+pub type Diagnostics = HashMap<String, HashMap<usize, Vec<Diagnostic>>>;
+
+/// Parse a diagnostics file (--diagnostics-file) in the SARIF format, e.g. written by clippy via
+/// clippy-sarif, semgrep or CodeQL, or made of lines "file:line:col: message", as written by gcc,
+/// clang or most linters. The column is optional.
+pub fn parse_diagnostics(text: &str) -> Diagnostics {
+    match serde_json::from_str::<Value>(text) {
+        Ok(sarif) => parse_sarif(&sarif),
+        Err(_) => parse_compiler_output(text),
+    }
+}
+
+lazy_static! {
+    static ref COMPILER_OUTPUT_REGEX: Regex =
+        Regex::new(r"^([^:\s][^:]*):(\d+):(?:(\d+):)?\s*(.+)$").unwrap();
+}
+
+fn parse_compiler_output(text: &str) -> Diagnostics {
+    let mut diagnostics = Diagnostics::new();
+    for caps in text
+        .lines()
+        .filter_map(|line| COMPILER_OUTPUT_REGEX.captures(line))
+    {
+        let (Ok(line_number), column) = (caps[2].parse::<usize>(), caps.get(3)) else {
+            continue;
+        };
+        diagnostics
+            .entry(caps[1].to_string())
+            .or_default()
+            .entry(line_number)
+            .or_default()
+            .push(Diagnostic {
+                column: column.and_then(|column| column.as_str().parse().ok()),
+                message: caps[4].trim_end().to_string(),
+            });
+    }
+    diagnostics
+}
+
+// The results of each run, with the location of their first line, e.g.
+// {"runs": [{"results": [{"level": "error", "message": {"text": "..."}, "locations":
+// [{"physicalLocation": {"artifactLocation": {"uri": "src/lib.rs"}, "region": {"startLine": 12,
+// "startColumn": 5}}}]}]}]}
+fn parse_sarif(sarif: &Value) -> Diagnostics {
+    let mut diagnostics = Diagnostics::new();
+    let results = sarif["runs"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .flat_map(|run| run["results"].as_array().into_iter().flatten());
+    for result in results {
+        let location = &result["locations"][0]["physicalLocation"];
+        let (Some(text), Some(uri), Some(line_number)) = (
+            result["message"]["text"].as_str(),
+            location["artifactLocation"]["uri"].as_str(),
+            location["region"]["startLine"].as_u64(),
+        ) else {
+            continue;
+        };
+        // A result without a level is a warning.
+        let message = match result["level"].as_str().unwrap_or("warning") {
+            "none" => text.to_string(),
+            level => format!("{level}: {text}"),
+        };
+        diagnostics
+            .entry(uri.trim_start_matches("file://").to_string())
+            .or_default()
+            .entry(line_number as usize)
+            .or_default()
+            .push(Diagnostic {
+                column: location["region"]["startColumn"]
+                    .as_u64()
+                    .map(|column| column as usize),
+                message,
+            });
+    }
+    diagnostics
+}
+
+/// The messages of a diagnostics file (--diagnostics-file), displayed beneath the added lines
+/// which they are about.
+#[derive(Debug)]
+pub struct DiagnosticsOverlay {
+    diagnostics_file: String,
+    // The diagnostics of each file, once the diagnostics file has been read.
+    diagnostics: Option<Diagnostics>,
+    // The path of the current file, and its key in the diagnostics, if it has any.
+    path: String,
+    file: Option<String>,
+    // Line number in the new file of the next unchanged or added line.
+    next_line_number: usize,
+}
+
+impl DiagnosticsOverlay {
+    pub fn new(diagnostics_file: &str) -> Self {
+        Self {
+            diagnostics_file: diagnostics_file.to_string(),
+            diagnostics: None,
+            path: String::new(),
+            file: None,
+            next_line_number: 0,
+        }
+    }
+
+    /// Start a hunk of the file `path`, at the line number `start` of the new file. The
+    /// diagnostics file is read at the first hunk of the input.
+    pub fn start_hunk(&mut self, path: &str, start: usize) {
+        let diagnostics_file = &self.diagnostics_file;
+        let diagnostics = self
+            .diagnostics
+            .get_or_insert_with(|| read_diagnostics(diagnostics_file));
+        if path != self.path {
+            self.path = path.to_string();
+            self.file =
+                coverage::find_file(diagnostics, path.trim_start_matches("./")).map(String::from);
+        }
+        self.next_line_number = start;
+    }
+
+    /// Skip unchanged lines, whether they are displayed or not.
+    pub fn skip_lines(&mut self, n: usize) {
+        self.next_line_number += n;
+    }
+
+    /// Insert the messages about the added lines `plus_lines` beneath them, among the lines
+    /// painted in `output_buffer` from `start`: first `n_removed` removed lines, followed by the
+    /// added lines. Each message is indented to the column of the problem, which is marked by a
+    /// '^'.
+    pub fn annotate(
+        &mut self,
+        output_buffer: &mut String,
+        start: usize,
+        n_removed: usize,
+        plus_lines: &[(String, State)],
+        config: &Config,
+    ) {
+        let file_diagnostics = self
+            .diagnostics
+            .as_ref()
+            .zip(self.file.as_ref())
+            .and_then(|(diagnostics, file)| diagnostics.get(file));
+        let Some(file_diagnostics) = file_diagnostics else {
+            self.next_line_number += plus_lines.len();
+            return;
+        };
+        let painted = output_buffer.split_off(start);
+        for (i, line) in painted.split_inclusive('\n').enumerate() {
+            output_buffer.push_str(line);
+            if i < n_removed {
+                continue;
+            }
+            self.next_line_number += 1;
+            let (Some(diagnostics), Some((code, _))) = (
+                file_diagnostics.get(&(self.next_line_number - 1)),
+                plus_lines.get(i - n_removed),
+            ) else {
+                continue;
+            };
+            // The width of what precedes the code in the painted line, e.g. line numbers.
+            let code = code.trim_end();
+            let prefix_width = measure_text_width(strip_ansi_codes(line).trim_end())
+                .saturating_sub(measure_text_width(code));
+            for diagnostic in diagnostics {
+                let column_width = match diagnostic.column {
+                    Some(column) => measure_text_width(
+                        &code
+                            .chars()
+                            .take(column.saturating_sub(1))
+                            .collect::<String>(),
+                    ),
+                    None => code.len() - code.trim_start().len(),
+                };
+                let indent = " ".repeat(prefix_width + column_width);
+                for (j, message_line) in diagnostic.message.lines().enumerate() {
+                    let marker = if j == 0 { "^" } else { " " };
+                    output_buffer.push_str(&indent);
+                    output_buffer.push_str(
+                        &config
+                            .diagnostics_style
+                            .paint(format!("{marker} {message_line}"))
+                            .to_string(),
+                    );
+                    output_buffer.push('\n');
+                }
+            }
+        }
+    }
+}
+
+fn read_diagnostics(path: &str) -> Diagnostics {
+    match fs::read_to_string(path) {
+        Ok(text) => parse_diagnostics(&text),
+        Err(err) => {
+            warnings::warn(
+                WarningKind::System,
+                format!("Failed to read the diagnostics file '{path}': {err}"),
+            );
+            Diagnostics::new()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::integration_test_utils::DeltaTest;
+    use crate::tests::test_utils::temp_path;
+
+    #[test]
+    fn test_parse_compiler_output() {
+        let diagnostics = parse_diagnostics(
+            "\
+src/a.c:3:5: error: expected ';' before '}' token
+src/a.c:3:12: warning: unused variable 'x'
+src/b.py:10: E501 line too long
+In file included from src/a.c:1:
+",
+        );
+        assert_eq!(diagnostics.len(), 2);
+        assert_eq!(
+            diagnostics["src/a.c"][&3],
+            vec![
+                Diagnostic {
+                    column: Some(5),
+                    message: "error: expected ';' before '}' token".to_string(),
+                },
+                Diagnostic {
+                    column: Some(12),
+                    message: "warning: unused variable 'x'".to_string(),
+                },
+            ]
+        );
+        assert_eq!(
+            diagnostics["src/b.py"][&10],
+            vec![Diagnostic {
+                column: None,
+                message: "E501 line too long".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_parse_sarif() {
+        let diagnostics = parse_diagnostics(
+            r#"{
+  "version": "2.1.0",
+  "runs": [{
+    "tool": {"driver": {"name": "clippy"}},
+    "results": [
+      {
+        "level": "error",
+        "message": {"text": "this looks like a bug"},
+        "locations": [{"physicalLocation": {
+          "artifactLocation": {"uri": "file:///home/ada/delta/src/a.rs"},
+          "region": {"startLine": 2, "startColumn": 9}
+        }}]
+      },
+      {
+        "message": {"text": "unused import"},
+        "locations": [{"physicalLocation": {
+          "artifactLocation": {"uri": "src/b.rs"},
+          "region": {"startLine": 1}
+        }}]
+      },
+      {"message": {"text": "no location"}}
+    ]
+  }]
+}"#,
+        );
+        assert_eq!(diagnostics.len(), 2);
+        assert_eq!(
+            diagnostics["/home/ada/delta/src/a.rs"][&2],
+            vec![Diagnostic {
+                column: Some(9),
+                message: "error: this looks like a bug".to_string(),
+            }]
+        );
+        assert_eq!(
+            diagnostics["src/b.rs"][&1],
+            vec![Diagnostic {
+                column: None,
+                message: "warning: unused import".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_diagnostics_file() {
+        let path = temp_path("test_diagnostics_file.txt");
+        std::fs::write(
+            &path,
+            "src/a.rs:3:5: error: cannot find function `d`\n\
+             src/a.rs:2: warning: unused result\n\
+             src/a.rs:4:1: unchanged line\n",
+        )
+        .unwrap();
+        DeltaTest::with_args(&[
+            "--diagnostics-file",
+            path.to_str().unwrap(),
+            "--diagnostics-style",
+            "yellow",
+            "--minus-style",
+            "normal",
+            "--minus-emph-style",
+            "normal",
+            "--plus-style",
+            "normal",
+            "--plus-emph-style",
+            "normal",
+            "--syntax-theme",
+            "none",
+        ])
+        .explain_ansi()
+        .with_input(
+            "\
+diff --git a/src/a.rs b/src/a.rs
+--- a/src/a.rs
++++ b/src/a.rs
+@@ -1,3 +1,4 @@
+ fn a() {
+-    b();
++    c();
++    d();
+ }
+",
+        )
+        .expect_after_header(
+            r#"
+            #indent_mark
+            fn a() {
+                b();
+                c();
+                (yellow)^ warning: unused result(normal)
+                d();
+                (yellow)^ error: cannot find function `d`(normal)
+            }"#,
+        );
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -44,11 +44,39 @@ pub fn make_feature() -> Vec<(String, OptionValueFunction)> {
     feature
 }
 
+/// Follow the layout of diff-so-fancy more closely than the diff-so-fancy feature, for users
+/// comparing the output of the two tools: file headers are labeled "modified:", "deleted:", and
+/// "renamed: OLD to NEW" as by diff-so-fancy.
+pub fn make_strict_feature() -> Vec<(String, OptionValueFunction)> {
+    let mut feature = make_feature();
+    feature.extend(builtin_feature!([
+        (
+            "file-modified-label",
+            String,
+            None,
+            _opt => "modified:"
+        ),
+        (
+            "file-removed-label",
+            String,
+            None,
+            _opt => "deleted:"
+        ),
+        (
+            "right-arrow",
+            String,
+            None,
+            _opt => "to"
+        )
+    ]));
+    feature
+}
+
 #[cfg(test)]
 pub mod tests {
     use std::fs::remove_file;
 
-    use crate::tests::integration_test_utils;
+    use crate::tests::integration_test_utils::{self, DeltaTest};
 
     #[test]
     fn test_diff_so_fancy_defaults() {
@@ -137,4 +165,34 @@ pub mod tests {
 
         remove_file(git_config_path).unwrap();
     }
+
+    #[test]
+    fn test_diff_so_fancy_strict_file_headers() {
+        DeltaTest::with_args(&["--features", "diff-so-fancy-strict"])
+            .with_input(
+                "\
+diff --git a/src/a.rs b/src/a.rs
+index 1111111..2222222 100644
+--- a/src/a.rs
++++ b/src/a.rs
+@@ -1 +1 @@
+-a
++b
+diff --git a/b.txt b/b.txt
+deleted file mode 100644
+index 3333333..0000000
+--- a/b.txt
++++ /dev/null
+@@ -1 +0,0 @@
+-gone
+diff --git a/old.txt b/new.txt
+similarity index 100%
+rename from old.txt
+rename to new.txt
+",
+            )
+            .expect_contains("modified: src/a.rs")
+            .expect_contains("deleted: b.txt")
+            .expect_contains("renamed: old.txt to new.txt");
+    }
 }
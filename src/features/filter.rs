@@ -0,0 +1,193 @@
+use std::io::{BufRead, Write};
+use std::process::{Command, Stdio};
+use std::thread;
+
+use bytelines::ByteLines;
+
+use crate::ansi;
+use crate::delta::{State, StateMachine};
+use crate::features::change_density::ChangeDensity;
+use crate::handlers::hunk_header;
+use crate::utils::warnings::{self, WarningKind};
+
+// The environment variable holding the path of the file whose hunks are filtered.
+const DELTA_FILTER_PATH: &str = "DELTA_FILTER_PATH";
+
+impl<'a> StateMachine<'a> {
+    /// If the current line is the first hunk header of a file, read the hunks of the file, and
+    /// put the output of the filter command for them into the lookahead buffer, to be processed
+    /// instead of them (--filter). Return true if the current line should be skipped, since it
+    /// has been replaced.
+    pub fn filter_hunks<I: BufRead>(&mut self, lines: &mut ByteLines<I>) -> bool {
+        let command = match &self.config.filter {
+            Some(command) => command,
+            None => return false,
+        };
+        let lengths = match self.state {
+            State::DiffHeader(_) => hunk_header::parse_unified_hunk_lengths(&self.line),
+            _ => None,
+        };
+        // The first line of the output of the filter command is processed as usual.
+        let (Some(lengths), false) = (lengths, self.hunks_filtered) else {
+            self.hunks_filtered = false;
+            return false;
+        };
+        let (hunks, next_line) = self.read_hunks(lengths, lines);
+        let path = match self.plus_file.as_str() {
+            "/dev/null" => &self.minus_file,
+            path => path,
+        };
+        let filtered = match run_command_with_input(command, &[(DELTA_FILTER_PATH, path)], &hunks) {
+            Ok(filtered) => filtered,
+            Err(err) => {
+                warnings::warn(
+                    WarningKind::Command,
+                    format!("Failed to filter the hunks of {path} with '{command}': {err}"),
+                );
+                hunks
+            }
+        };
+        self.replace_hunks(&filtered, next_line);
+        self.hunks_filtered = true;
+        true
+    }
+
+    /// Put the lines replacing hunks, followed by the line after the hunks, into the lookahead
+    /// buffer, to be processed next.
+    pub fn replace_hunks(&mut self, hunks: &[u8], next_line: Option<Vec<u8>>) {
+        let mut lines: Vec<Vec<u8>> = hunks
+            .strip_suffix(b"\n")
+            .unwrap_or(hunks)
+            .split(|&byte| byte == b'\n')
+            .map(|line| line.to_vec())
+            .collect();
+        if hunks.is_empty() {
+            lines.clear();
+        }
+        lines.extend(next_line);
+        for line in lines.into_iter().rev() {
+            self.hunk_lookahead.push_front(line);
+        }
+    }
+
+    /// The current hunk header and the following hunks, without ANSI escape sequences, and the
+    /// line after them, if any.
+    pub fn read_hunks<I: BufRead>(
+        &mut self,
+        (mut minus_length, mut plus_length): (usize, usize),
+        lines: &mut ByteLines<I>,
+    ) -> (Vec<u8>, Option<Vec<u8>>) {
+        let mut hunks = format!("{}\n", self.line).into_bytes();
+        let mut change_density = ChangeDensity::default();
+        loop {
+            let raw_line = match self.hunk_lookahead.pop_front() {
+                Some(raw_line) => raw_line,
+                None => match lines.next() {
+                    Some(Ok(raw_line)) => raw_line.to_vec(),
+                    _ => return (hunks, None),
+                },
+            };
+            let line = ansi::strip_ansi_codes(&String::from_utf8_lossy(&raw_line));
+            if change_density.is_complete(minus_length, plus_length) {
+                match hunk_header::parse_unified_hunk_lengths(&line) {
+                    Some(lengths) => {
+                        (minus_length, plus_length) = lengths;
+                        change_density = ChangeDensity::default();
+                    }
+                    None => return (hunks, Some(raw_line)),
+                }
+            } else if !change_density.count_line(&line) {
+                return (hunks, Some(raw_line));
+            }
+            hunks.extend(line.as_bytes());
+            hunks.push(b'\n');
+        }
+    }
+}
+
+/// The output of `command` with `input` as its standard input and the variables `envs` in its
+/// environment.
+pub fn run_command_with_input(
+    command: &str,
+    envs: &[(&str, &str)],
+    input: &[u8],
+) -> std::io::Result<Vec<u8>> {
+    let words = shell_words::split(command)
+        .map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidInput, err))?;
+    let (program, args) = words.split_first().ok_or_else(|| {
+        std::io::Error::new(std::io::ErrorKind::InvalidInput, "the command is empty")
+    })?;
+    let mut child = Command::new(program)
+        .args(args)
+        .envs(envs.iter().copied())
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::inherit())
+        .spawn()?;
+    // Write in another thread, so that the command does not block on a full stdout pipe.
+    let mut stdin = child.stdin.take().unwrap();
+    let input = input.to_vec();
+    let writer = thread::spawn(move || stdin.write_all(&input));
+    let output = child.wait_with_output()?;
+    // The command may not read all of its input.
+    let _ = writer.join();
+    if !output.status.success() {
+        return Err(std::io::Error::other(format!(
+            "it failed with {}",
+            output.status
+        )));
+    }
+    Ok(output.stdout)
+}
+
+#[cfg(not(target_os = "windows"))]
+#[cfg(test)]
+mod tests {
+    use crate::tests::integration_test_utils::DeltaTest;
+
+    #[test]
+    fn test_filter() {
+        let output = DeltaTest::with_args(&["--filter", "sed s/secret/public/"])
+            .with_input(DIFF)
+            .expect_contains("\npublic a\na\n")
+            .expect_contains("\nb\npublic a\n")
+            .expect_contains("\npublic b\n");
+        assert!(!output.raw_output.contains("secret"));
+    }
+
+    #[test]
+    fn test_filter_path() {
+        // The line added by the command is displayed after the hunks.
+        DeltaTest::with_args(&["--filter", "sh -c 'cat; echo \" $DELTA_FILTER_PATH\"'"])
+            .with_input(DIFF)
+            .expect_contains("\nb\nsecret a\nfile_a\n")
+            .expect_contains("\nsecret b\nfile_b\n");
+    }
+
+    #[test]
+    fn test_filter_failure() {
+        DeltaTest::with_args(&["--filter", "false"])
+            .with_input(DIFF)
+            .expect_contains("\nsecret a\na\n")
+            .expect_contains("\nsecret b\n");
+    }
+
+    const DIFF: &str = "\
+diff --git a/file_a b/file_a
+index 1111111..2222222 100644
+--- a/file_a
++++ b/file_a
+@@ -1,1 +1,1 @@
+-secret a
++a
+@@ -10,1 +10,1 @@
+-b
++secret a
+diff --git a/file_b b/file_b
+index 3333333..4444444 100644
+--- a/file_b
++++ b/file_b
+@@ -1,1 +1,1 @@
+ secret b
+";
+}
@@ -0,0 +1,161 @@
+use std::process::{Command, Stdio};
+
+use serde::Deserialize;
+
+use crate::ansi::measure_text_width;
+use crate::cli;
+use crate::config::Config;
+use crate::utils;
+
+/// The symbols (functions, classes, etc.) of the new version of a file, used to display a
+/// separator above a hunk which is in a different symbol than the previous hunk of the file
+/// (--function-separators).
+#[derive(Debug, Default)]
+pub struct FileSymbols {
+    symbols: Vec<Symbol>,
+    // The label of the symbol enclosing the previous hunk, if there was a previous hunk.
+    previous: Option<Option<String>>,
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+struct Symbol {
+    name: String,
+    kind: Option<String>,
+    line: usize,
+    end: usize,
+}
+
+impl FileSymbols {
+    /// Read the symbols of `path` with ctags. If ctags fails (e.g. it is not installed, or the file
+    /// does not exist in the working tree) there are no symbols and no separators are displayed.
+    pub fn for_file(path: &str, config: &Config) -> Self {
+        Self {
+            symbols: run_ctags(path, config).unwrap_or_default(),
+            previous: None,
+        }
+    }
+
+    /// Return the label of the separator to display above a hunk starting at `line_number` of the
+    /// new file, if the hunk is in a different symbol than the previous hunk.
+    pub fn separator_label(&mut self, line_number: usize) -> Option<String> {
+        let label = enclosing_symbol(&self.symbols, line_number).map(|symbol| match &symbol.kind {
+            Some(kind) => format!("{kind} {}", symbol.name),
+            None => symbol.name.clone(),
+        });
+        match self.previous.replace(label.clone()) {
+            Some(previous) if previous != label => label,
+            _ => None,
+        }
+    }
+}
+
+/// The separator line, labeled with the symbol, spanning the width of the decorations.
+pub fn format_separator(label: &str, config: &Config) -> String {
+    let width = match config.decorations_width {
+        cli::Width::Fixed(width) => width,
+        cli::Width::Variable => config.available_terminal_width,
+    };
+    let text = format!("── {label} ");
+    let fill = width.saturating_sub(measure_text_width(&text));
+    config
+        .function_separator_style
+        .paint(format!("{text}{}", "─".repeat(fill)))
+        .to_string()
+}
+
+// The innermost symbol containing the line.
+fn enclosing_symbol(symbols: &[Symbol], line_number: usize) -> Option<&Symbol> {
+    symbols
+        .iter()
+        .filter(|symbol| symbol.line <= line_number && line_number <= symbol.end)
+        .min_by_key(|symbol| symbol.end - symbol.line)
+}
+
+fn run_ctags(path: &str, config: &Config) -> Option<Vec<Symbol>> {
+    let path = utils::path::absolute_path(path, config)?;
+    let output = Command::new("ctags")
+        .args(["--output-format=json", "--fields=+ne", "-o", "-"])
+        .arg(path)
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(parse_ctags_json(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Parse the JSON lines output of universal-ctags, keeping the symbols which span a range of lines.
+fn parse_ctags_json(output: &str) -> Vec<Symbol> {
+    output
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::integration_test_utils;
+
+    const CTAGS_JSON: &str = r#"{"_type": "ptag", "name": "JSON_OUTPUT_VERSION", "path": "0.0", "pattern": "in development"}
+{"_type": "tag", "name": "Painter", "path": "src/paint.rs", "pattern": "/^impl<'p> Painter<'p> {$/", "line": 80, "kind": "implementation", "end": 300}
+{"_type": "tag", "name": "new", "path": "src/paint.rs", "pattern": "/^    pub fn new($/", "line": 81, "kind": "method", "end": 114, "scope": "Painter", "scopeKind": "implementation"}
+{"_type": "tag", "name": "emit", "path": "src/paint.rs", "pattern": "/^    pub fn emit($/", "line": 200, "kind": "method", "end": 210, "scope": "Painter", "scopeKind": "implementation"}
+{"_type": "tag", "name": "CARRIAGE_RETURN_MARKER", "path": "src/paint.rs", "pattern": "/^const CARRIAGE_RETURN_MARKER$/", "line": 27, "kind": "constant"}
+"#;
+
+    #[test]
+    fn test_parse_ctags_json() {
+        let symbols = parse_ctags_json(CTAGS_JSON);
+        assert_eq!(symbols.len(), 3);
+        assert_eq!(
+            symbols[1],
+            Symbol {
+                name: "new".to_string(),
+                kind: Some("method".to_string()),
+                line: 81,
+                end: 114,
+            }
+        );
+    }
+
+    #[test]
+    fn test_separator_label() {
+        let mut file_symbols = FileSymbols {
+            symbols: parse_ctags_json(CTAGS_JSON),
+            previous: None,
+        };
+        // No separator above the first hunk of a file.
+        assert_eq!(file_symbols.separator_label(90), None);
+        assert_eq!(file_symbols.separator_label(100), None);
+        assert_eq!(
+            file_symbols.separator_label(150),
+            Some("implementation Painter".to_string())
+        );
+        assert_eq!(
+            file_symbols.separator_label(205),
+            Some("method emit".to_string())
+        );
+        assert_eq!(file_symbols.separator_label(400), None);
+        assert_eq!(
+            file_symbols.separator_label(81),
+            Some("method new".to_string())
+        );
+    }
+
+    #[test]
+    fn test_format_separator() {
+        let config = integration_test_utils::make_config_from_args(&[
+            "--function-separator-style",
+            "normal",
+            "--width",
+            "20",
+        ]);
+        assert_eq!(
+            format_separator("method emit", &config),
+            "── method emit ─────"
+        );
+    }
+}
@@ -0,0 +1,175 @@
+use crate::color::ColorMode::*;
+use crate::features::OptionValueFunction;
+
+/// The colors of GitHub's pull request diff view, in its light and dark default themes. The
+/// translucent background colors used on the web have been blended with the page background.
+pub fn make_feature() -> Vec<(String, OptionValueFunction)> {
+    builtin_feature!([
+        (
+            "minus-style",
+            String,
+            None,
+            opt => match opt.computed.color_mode {
+                Light => "syntax #ffebe9",
+                Dark => "syntax #25171c",
+            }
+        ),
+        (
+            "minus-emph-style",
+            String,
+            None,
+            opt => match opt.computed.color_mode {
+                Light => "syntax #ffcecb",
+                Dark => "syntax #6b2b2b",
+            }
+        ),
+        (
+            "plus-style",
+            String,
+            None,
+            opt => match opt.computed.color_mode {
+                Light => "syntax #e6ffec",
+                Dark => "syntax #12261e",
+            }
+        ),
+        (
+            "plus-emph-style",
+            String,
+            None,
+            opt => match opt.computed.color_mode {
+                Light => "syntax #abf2bc",
+                Dark => "syntax #1a4a29",
+            }
+        ),
+        (
+            "zero-style",
+            String,
+            None,
+            _opt => "syntax"
+        ),
+        (
+            "file-style",
+            String,
+            None,
+            opt => match opt.computed.color_mode {
+                Light => "bold #1f2328 #f6f8fa",
+                Dark => "bold #e6edf3 #161b22",
+            }
+        ),
+        (
+            "file-decoration-style",
+            String,
+            None,
+            opt => match opt.computed.color_mode {
+                Light => "#d0d7de box",
+                Dark => "#30363d box",
+            }
+        ),
+        (
+            "hunk-header-style",
+            String,
+            None,
+            opt => match opt.computed.color_mode {
+                Light => "file line-number #656d76 #ddf4ff",
+                Dark => "file line-number #7d8590 #111d2e",
+            }
+        ),
+        (
+            "hunk-header-decoration-style",
+            String,
+            None,
+            opt => match opt.computed.color_mode {
+                Light => "#d0d7de ol",
+                Dark => "#30363d ol",
+            }
+        ),
+        (
+            "line-numbers-minus-style",
+            String,
+            None,
+            opt => match opt.computed.color_mode {
+                Light => "#656d76 #ffd7d5",
+                Dark => "#7d8590 #482124",
+            }
+        ),
+        (
+            "line-numbers-zero-style",
+            String,
+            None,
+            opt => match opt.computed.color_mode {
+                Light => "#656d76",
+                Dark => "#7d8590",
+            }
+        ),
+        (
+            "line-numbers-plus-style",
+            String,
+            None,
+            opt => match opt.computed.color_mode {
+                Light => "#656d76 #ccffd8",
+                Dark => "#7d8590 #1c4328",
+            }
+        )
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::remove_file;
+
+    use crate::tests::integration_test_utils;
+
+    #[test]
+    fn test_github_light() {
+        let opt = integration_test_utils::make_options_from_args_and_git_config(
+            &["--features", "github", "--light"],
+            None,
+            None,
+        );
+        assert_eq!(opt.minus_style, "syntax #ffebe9");
+        assert_eq!(opt.minus_emph_style, "syntax #ffcecb");
+        assert_eq!(opt.plus_style, "syntax #e6ffec");
+        assert_eq!(opt.plus_emph_style, "syntax #abf2bc");
+        assert_eq!(opt.hunk_header_decoration_style, "#d0d7de ol");
+    }
+
+    #[test]
+    fn test_github_dark() {
+        let opt = integration_test_utils::make_options_from_args_and_git_config(
+            &["--github", "--dark"],
+            None,
+            None,
+        );
+        assert_eq!(opt.minus_style, "syntax #25171c");
+        assert_eq!(opt.plus_style, "syntax #12261e");
+        assert_eq!(opt.file_style, "bold #e6edf3 #161b22");
+        assert_eq!(opt.file_decoration_style, "#30363d box");
+    }
+
+    #[test]
+    fn test_github_respects_gitconfig() {
+        let git_config_contents = b"
+[delta]
+    features = github
+    plus-emph-style = \"syntax bold #abf2bc\"
+";
+        let git_config_path = "delta__test_github_respects_gitconfig.gitconfig";
+
+        let opt = integration_test_utils::make_options_from_args_and_git_config(
+            &["--light"],
+            Some(git_config_contents),
+            Some(git_config_path),
+        );
+        assert_eq!(opt.plus_style, "syntax #e6ffec");
+        assert_eq!(opt.plus_emph_style, "syntax bold #abf2bc");
+
+        remove_file(git_config_path).unwrap();
+    }
+
+    #[test]
+    fn test_github_styles_parse() {
+        let config = integration_test_utils::make_config_from_args(&["--features", "github"]);
+        assert!(config.minus_style.is_syntax_highlighted);
+        assert!(config.plus_emph_style.ansi_term_style.background.is_some());
+    }
+}
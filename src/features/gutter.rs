@@ -0,0 +1,283 @@
+use std::collections::HashMap;
+use std::fs;
+
+use crate::ansi::measure_text_width;
+use crate::config::Config;
+use crate::features::coverage;
+use crate::features::filter;
+use crate::format::{self, Align};
+use crate::style::Style;
+use crate::utils::warnings::{self, WarningKind};
+
+/// A cell of the gutter column: its text, and the style in which the command asked for it to be
+/// painted, if any.
+#[derive(Clone, Debug, PartialEq)]
+struct Cell {
+    text: String,
+    style: Option<Style>,
+}
+
+/// Where the cells of a gutter come from.
+#[derive(Debug)]
+pub enum GutterSource {
+    /// The output of a command (--gutter-command).
+    Command(String),
+    /// A coverage file (--coverage-file): the added lines are marked as covered by tests or not.
+    Coverage(String),
+}
+
+/// A column before the lines of the diff, whose cells are computed by an external command
+/// (--gutter-command), e.g. the problems found in the lines by a linter, or read from a coverage
+/// file (--coverage-file).
+#[derive(Debug)]
+pub struct Gutter {
+    source: GutterSource,
+    // The cells of the lines of each file, by path and line number in the new file, once they
+    // have been computed.
+    cells: Option<HashMap<String, HashMap<usize, Cell>>>,
+    // The path of the current file, and its key in the cells, if it has any.
+    path: String,
+    file: Option<String>,
+    // The width of the column in the current file, or zero if it has no cells.
+    width: usize,
+    // Line number in the new file of the next unchanged or added line.
+    next_line_number: usize,
+}
+
+/// The gutters requested by the options, from the innermost to the outermost.
+pub fn make_gutters(config: &Config) -> Vec<Gutter> {
+    let coverage = config.coverage_file.clone().map(GutterSource::Coverage);
+    let command = config.gutter_command.clone().map(GutterSource::Command);
+    coverage
+        .into_iter()
+        .chain(command)
+        .map(Gutter::new)
+        .collect()
+}
+
+impl Gutter {
+    pub fn new(source: GutterSource) -> Self {
+        Self {
+            source,
+            cells: None,
+            path: String::new(),
+            file: None,
+            width: 0,
+            next_line_number: 0,
+        }
+    }
+
+    /// Start a hunk of the file `path`, at the line number `start` of the new file. The cells are
+    /// computed at the first hunk of the input.
+    pub fn start_hunk(&mut self, path: &str, start: usize, config: &Config) {
+        let source = &self.source;
+        let cells = self.cells.get_or_insert_with(|| match source {
+            GutterSource::Command(command) => run_gutter_command(command, config),
+            GutterSource::Coverage(path) => read_coverage_cells(path, config),
+        });
+        if path != self.path {
+            self.path = path.to_string();
+            self.file = coverage::find_file(cells, path.trim_start_matches("./")).map(String::from);
+            self.width = self
+                .file
+                .as_ref()
+                .and_then(|file| cells.get(file))
+                .and_then(|cells| {
+                    cells
+                        .values()
+                        .map(|cell| measure_text_width(&cell.text))
+                        .max()
+                })
+                .unwrap_or(0);
+        }
+        self.next_line_number = start;
+    }
+
+    /// Skip unchanged lines which are not displayed.
+    pub fn skip_lines(&mut self, n: usize) {
+        self.next_line_number += n;
+    }
+
+    /// Insert the column before each of the lines painted in `output_buffer` from `start`: first
+    /// `n_removed` removed lines, whose cells are blank, followed by added lines if `added`, else
+    /// unchanged lines. The unchanged lines have blank cells in the coverage gutter.
+    pub fn annotate(
+        &mut self,
+        output_buffer: &mut String,
+        start: usize,
+        n_removed: usize,
+        added: bool,
+        config: &Config,
+    ) {
+        if self.width == 0 {
+            return;
+        }
+        let painted = output_buffer.split_off(start);
+        for (i, line) in painted.split_inclusive('\n').enumerate() {
+            let cell = if i < n_removed {
+                None
+            } else {
+                self.next_line_number += 1;
+                if !added && matches!(self.source, GutterSource::Coverage(_)) {
+                    None
+                } else {
+                    self.cells
+                        .as_ref()
+                        .zip(self.file.as_ref())
+                        .and_then(|(cells, file)| cells.get(file))
+                        .and_then(|cells| cells.get(&(self.next_line_number - 1)))
+                }
+            };
+            let (text, style) = match cell {
+                Some(cell) => (
+                    cell.text.as_str(),
+                    cell.style.unwrap_or(config.gutter_style),
+                ),
+                None => ("", config.gutter_style),
+            };
+            let text = format::pad(text, self.width, Align::Left, Some(self.width));
+            output_buffer.push_str(&style.paint(text).to_string());
+            output_buffer.push(' ');
+            output_buffer.push_str(line);
+        }
+    }
+}
+
+fn run_gutter_command(command: &str, config: &Config) -> HashMap<String, HashMap<usize, Cell>> {
+    match filter::run_command_with_input(command, &[], &[]) {
+        Ok(output) => parse_gutter_cells(&String::from_utf8_lossy(&output), config),
+        Err(err) => {
+            warnings::warn(
+                WarningKind::Command,
+                format!("Failed to compute the gutter with '{command}': {err}"),
+            );
+            HashMap::new()
+        }
+    }
+}
+
+// The symbol marking the added lines in the coverage gutter.
+const COVERAGE_SYMBOL: &str = "▌";
+
+fn read_coverage_cells(path: &str, config: &Config) -> HashMap<String, HashMap<usize, Cell>> {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(err) => {
+            warnings::warn(
+                WarningKind::System,
+                format!("Failed to read the coverage file '{path}': {err}"),
+            );
+            return HashMap::new();
+        }
+    };
+    let cell = |hits| Cell {
+        text: COVERAGE_SYMBOL.to_string(),
+        style: Some(if hits > 0 {
+            config.coverage_covered_style
+        } else {
+            config.coverage_uncovered_style
+        }),
+    };
+    coverage::parse_coverage(&text)
+        .into_iter()
+        .map(|(path, lines)| {
+            let cells = lines
+                .into_iter()
+                .map(|(line_number, hits)| (line_number, cell(hits)))
+                .collect();
+            (path, cells)
+        })
+        .collect()
+}
+
+/// Parse the output of the gutter command: one line per cell, of the form
+/// "PATH:LINE<tab>TEXT", optionally followed by "<tab>STYLE". Other lines are ignored.
+fn parse_gutter_cells(output: &str, config: &Config) -> HashMap<String, HashMap<usize, Cell>> {
+    let mut cells: HashMap<String, HashMap<usize, Cell>> = HashMap::new();
+    for line in output.lines() {
+        let mut fields = line.splitn(3, '\t');
+        let (Some(key), Some(text)) = (fields.next(), fields.next()) else {
+            continue;
+        };
+        let Some((path, Ok(line_number))) = key
+            .rsplit_once(':')
+            .map(|(path, line_number)| (path, line_number.parse::<usize>()))
+        else {
+            continue;
+        };
+        let style = fields.next().map(|style| {
+            Style::from_str(style, None, None, config.true_color, config.git_config())
+        });
+        let cell = Cell {
+            text: text.to_string(),
+            style,
+        };
+        cells
+            .entry(path.trim_start_matches("./").to_string())
+            .or_default()
+            .insert(line_number, cell);
+    }
+    cells
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::integration_test_utils::{self, DeltaTest};
+
+    #[test]
+    fn test_parse_gutter_cells() {
+        let config = integration_test_utils::make_config_from_args(&[]);
+        let cells = parse_gutter_cells(
+            "./src/a.rs:2\t✓\nsrc/a.rs:3\t✗\tred\nsrc/b:c.rs:1\t1x\nnot a cell\nsrc/a.rs:x\t✓\n",
+            &config,
+        );
+        assert_eq!(cells.len(), 2);
+        assert_eq!(cells["src/a.rs"][&2].text, "✓");
+        assert_eq!(cells["src/a.rs"][&2].style, None);
+        assert!(cells["src/a.rs"][&3].style.is_some());
+        assert_eq!(cells["src/b:c.rs"][&1].text, "1x");
+    }
+
+    #[test]
+    fn test_gutter_command() {
+        DeltaTest::with_args(&[
+            "--gutter-command",
+            r#"printf 'src/a.rs:2\t✓\nsrc/a.rs:3\t✗\tred\nsrc/a.rs:4\t12x\n'"#,
+            "--gutter-style",
+            "green",
+            "--minus-style",
+            "normal",
+            "--plus-style",
+            "normal",
+            "--minus-emph-style",
+            "normal",
+            "--plus-emph-style",
+            "normal",
+            "--syntax-theme",
+            "none",
+        ])
+        .explain_ansi()
+        .with_input(
+            "\
+diff --git a/src/a.rs b/src/a.rs
+--- a/src/a.rs
++++ b/src/a.rs
+@@ -1,3 +1,4 @@
+ fn a() {
+-    b();
++    c();
++    d();
+ }
+",
+        )
+        .expect_after_header(
+            r#"
+(green)   (normal) fn a() {
+(green)   (normal)     b();
+(green)✓  (normal)     c();
+(red)✗  (normal)     d();
+(green)12x(normal) }"#,
+        );
+    }
+}
@@ -0,0 +1,146 @@
+use std::io;
+
+use crate::config::Config;
+use crate::delta::StateMachine;
+use crate::utils;
+
+// The start of the escape sequence marking an anchor in delta's output, which is only written
+// when the output is rendered as an HTML page: the sequence is an OSC with a private command.
+const ANCHOR_OSC: &str = "\x1b]delta-anchor;";
+
+/// The anchors of the files and lines of the HTML page (--html): the header of the nth file has
+/// the id "F<n>", and its unchanged and added lines the ids "F<n>-L<line>", by line number in the
+/// new file, e.g. "F3-L120".
+#[derive(Debug, Default)]
+pub struct HtmlAnchors {
+    // The number of the current file, counted from 1.
+    file: usize,
+    // Line number in the new file of the next unchanged or added line.
+    next_line_number: usize,
+}
+
+impl HtmlAnchors {
+    /// Start a hunk of the current file, at the line number `start` of the new file.
+    pub fn start_hunk(&mut self, start: usize) {
+        self.next_line_number = start;
+    }
+
+    /// Skip unchanged lines which are not displayed.
+    pub fn skip_lines(&mut self, n: usize) {
+        self.next_line_number += n;
+    }
+
+    /// Mark each of the lines painted in `output_buffer` from `start`, other than the first
+    /// `n_removed` removed lines, with its anchor. The rows of side-by-side output do not
+    /// correspond one to one to the lines, so only the unchanged lines are marked in it.
+    pub fn annotate(
+        &mut self,
+        output_buffer: &mut String,
+        start: usize,
+        n_removed: usize,
+        n_added: usize,
+        config: &Config,
+    ) {
+        if config.side_by_side {
+            self.skip_lines(n_added);
+            return;
+        }
+        let painted = output_buffer.split_off(start);
+        for (i, line) in painted.split_inclusive('\n').enumerate() {
+            if i >= n_removed {
+                output_buffer.push_str(&self.next_line_anchor());
+            }
+            output_buffer.push_str(line);
+        }
+    }
+
+    /// Mark the unchanged line painted in `output_buffer` from `start`, which may be wrapped over
+    /// several rows, with its anchor.
+    pub fn annotate_zero_line(&mut self, output_buffer: &mut String, start: usize) {
+        output_buffer.insert_str(start, &self.next_line_anchor());
+    }
+
+    fn next_line_anchor(&mut self) -> String {
+        self.next_line_number += 1;
+        anchor_sequence(
+            &format!("F{}-L{}", self.file, self.next_line_number - 1),
+            None,
+        )
+    }
+}
+
+/// The escape sequence marking the anchor `id`, and the label of its entry in the index of the
+/// page, if it has one.
+fn anchor_sequence(id: &str, label: Option<&str>) -> String {
+    match label {
+        Some(label) => format!("{ANCHOR_OSC}{id};{label}\x1b\\"),
+        None => format!("{ANCHOR_OSC}{id}\x1b\\"),
+    }
+}
+
+/// If `s` is the escape sequence of an anchor, return its id and the label of its entry in the
+/// index of the page, if it has one.
+pub fn parse_anchor_sequence(s: &str) -> Option<(&str, Option<&str>)> {
+    let rest = s.strip_prefix(ANCHOR_OSC)?;
+    let end = rest.find(['\x1b', '\x07']).unwrap_or(rest.len());
+    match rest[..end].split_once(';') {
+        Some((id, label)) => Some((id, Some(label))),
+        None => Some((&rest[..end], None)),
+    }
+}
+
+impl<'a> StateMachine<'a> {
+    /// Mark the header of the file `path`, which is about to be written, with the anchor of the
+    /// next file, labeled with its path.
+    pub fn mark_file_anchor(&mut self, path: &str) -> io::Result<()> {
+        let Some(anchors) = &mut self.painter.html_anchors else {
+            return Ok(());
+        };
+        anchors.file += 1;
+        let sequence = anchor_sequence(
+            &format!("F{}", anchors.file),
+            Some(&utils::path::display_path(path, self.config)),
+        );
+        self.painter.emit()?;
+        write!(self.painter.writer, "{sequence}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::integration_test_utils;
+
+    #[test]
+    fn test_parse_anchor_sequence() {
+        assert_eq!(
+            parse_anchor_sequence(&anchor_sequence("F1-L2", None)),
+            Some(("F1-L2", None))
+        );
+        assert_eq!(
+            parse_anchor_sequence(&anchor_sequence("F1", Some("src/a;b.rs"))),
+            Some(("F1", Some("src/a;b.rs")))
+        );
+        assert_eq!(parse_anchor_sequence("\x1b]8;;file:///a\x1b\\"), None);
+    }
+
+    #[test]
+    fn test_annotate() {
+        let config = integration_test_utils::make_config_from_args(&[]);
+        let mut anchors = HtmlAnchors {
+            file: 2,
+            next_line_number: 0,
+        };
+        anchors.start_hunk(10);
+        let mut output_buffer = "header\n-a\n+b\n+c\n".to_string();
+        anchors.annotate(&mut output_buffer, 7, 1, 2, &config);
+        assert_eq!(
+            output_buffer,
+            "header\n-a\n\x1b]delta-anchor;F2-L10\x1b\\+b\n\x1b]delta-anchor;F2-L11\x1b\\+c\n"
+        );
+        anchors.skip_lines(3);
+        let mut output_buffer = " d\n".to_string();
+        anchors.annotate_zero_line(&mut output_buffer, 0);
+        assert_eq!(output_buffer, "\x1b]delta-anchor;F2-L15\x1b\\ d\n");
+    }
+}
@@ -0,0 +1,193 @@
+use std::collections::hash_map::DefaultHasher;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::io;
+use std::path::PathBuf;
+
+use crate::delta::StateMachine;
+use crate::handlers::hunk_header::parse_unified_hunk_lengths;
+use crate::utils::cache::cache_dir;
+use crate::utils::private_dir::open_private_dir;
+
+/// The current hunk, copied into a patch of its own which `git apply` applies without the other
+/// hunks of the diff (--hunk-apply-commands).
+#[derive(Debug, Default)]
+pub struct HunkPatch {
+    // The "---" and "+++" lines of the current file.
+    file_header: String,
+    // The lines of the current hunk, if in a hunk, and the numbers of lines of the old and new
+    // file which it has yet to be followed by.
+    hunk: Option<String>,
+    remaining: (usize, usize),
+}
+
+impl<'a> StateMachine<'a> {
+    /// Copy the current line into the patch of the current hunk, after displaying the command
+    /// applying the previous hunk if this line follows it.
+    pub fn copy_hunk_patch_line(&mut self) {
+        let Some(patch) = &mut self.hunk_patch else {
+            return;
+        };
+        if let Some(hunk) = &mut patch.hunk {
+            let (minus, plus) = &mut patch.remaining;
+            let is_copied = match self.line.chars().next() {
+                // "\ No newline at end of file"
+                Some('\\') => true,
+                Some('-') if *minus > 0 => {
+                    *minus -= 1;
+                    true
+                }
+                Some('+') if *plus > 0 => {
+                    *plus -= 1;
+                    true
+                }
+                Some(' ') | None if *minus > 0 && *plus > 0 => {
+                    *minus -= 1;
+                    *plus -= 1;
+                    true
+                }
+                _ => false,
+            };
+            if is_copied {
+                hunk.push_str(&self.line);
+                if self.line_had_cr {
+                    hunk.push('\r');
+                }
+                hunk.push('\n');
+                return;
+            }
+            self.finish_hunk_patch();
+        }
+        let Some(patch) = &mut self.hunk_patch else {
+            return;
+        };
+        if self.line.starts_with("--- ") {
+            patch.file_header = format!("{}\n", self.line);
+        } else if self.line.starts_with("+++ ") {
+            patch.file_header.push_str(&format!("{}\n", self.line));
+        } else if let (true, Some(lengths)) = (
+            self.line.starts_with("@@ "),
+            parse_unified_hunk_lengths(&self.line),
+        ) {
+            patch.hunk = Some(format!("{}\n", self.line));
+            patch.remaining = lengths;
+        }
+    }
+
+    /// If the current hunk is complete, write its patch to a file and display the command
+    /// applying it below the hunk. A hunk which has ended early, e.g. in a truncated diff, is
+    /// dropped, as is one whose patch cannot be written.
+    pub fn finish_hunk_patch(&mut self) {
+        let Some(patch) = &mut self.hunk_patch else {
+            return;
+        };
+        let Some(hunk) = patch.hunk.take() else {
+            return;
+        };
+        if patch.remaining != (0, 0) {
+            return;
+        }
+        let Ok(path) = write_patch(&format!("{}{hunk}", patch.file_header)) else {
+            return;
+        };
+        let command = format!("git apply {}", shell_words::quote(&path.to_string_lossy()));
+        if let Some(record) = self
+            .hunk_index
+            .as_mut()
+            .and_then(|index| index.records.last_mut())
+        {
+            record.apply_command = Some(command.clone());
+        }
+        self.painter.paint_buffered_minus_and_plus_lines();
+        self.painter.output_buffer.push_str(
+            &self
+                .config
+                .hunk_apply_command_style
+                .paint(command)
+                .to_string(),
+        );
+        self.painter.output_buffer.push('\n');
+    }
+}
+
+// Write the patch to a directory in delta's cache directory, private to the user, since the
+// patch is applied later. Patches are named by a hash of their contents, so that displaying a diff
+// again does not write more of them.
+fn write_patch(patch: &str) -> io::Result<PathBuf> {
+    let mut hasher = DefaultHasher::new();
+    patch.hash(&mut hasher);
+    let dir = cache_dir()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "No cache directory."))?
+        .join("hunks");
+    let dir = open_private_dir(&dir)?;
+    let path = dir.join(format!("{:016x}.patch", hasher.finish()));
+    fs::write(&path, patch)?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ansi::strip_ansi_codes;
+    use crate::tests::integration_test_utils::DeltaTest;
+
+    #[test]
+    fn test_hunk_apply_commands() {
+        let output = DeltaTest::with_args(&["--hunk-apply-commands"]).with_input(DIFF);
+        let output = strip_ansi_codes(&output.raw_output);
+        let commands: Vec<&str> = output
+            .lines()
+            .filter_map(|line| line.strip_prefix("git apply "))
+            .collect();
+        assert_eq!(commands.len(), 2);
+        // The command of the first hunk is below its last line.
+        assert!(output.contains(&format!("    println!();\ngit apply {}\n", commands[0])));
+
+        let patch =
+            |command: &str| fs::read_to_string(shell_words::split(command).unwrap()[0].clone());
+        assert_eq!(
+            patch(commands[0]).unwrap(),
+            "\
+--- a/src/a.rs
++++ b/src/a.rs
+@@ -1,2 +1,2 @@ fn main() {
+-    let a = 1;
++    let a = 2;
+     println!();
+"
+        );
+        assert_eq!(
+            patch(commands[1]).unwrap(),
+            "\
+--- a/src/a.rs
++++ b/src/a.rs
+@@ -10 +10,2 @@
+ x
++y
+\\ No newline at end of file
+"
+        );
+    }
+
+    #[test]
+    fn test_hunk_apply_commands_not_for_truncated_hunk() {
+        let output = DeltaTest::with_args(&["--hunk-apply-commands"])
+            .with_input(&DIFF[..DIFF.find("+y").unwrap()]);
+        assert_eq!(output.raw_output.matches("git apply").count(), 1);
+    }
+
+    const DIFF: &str = "\
+diff --git a/src/a.rs b/src/a.rs
+index 1111111..2222222 100644
+--- a/src/a.rs
++++ b/src/a.rs
+@@ -1,2 +1,2 @@ fn main() {
+-    let a = 1;
++    let a = 2;
+     println!();
+@@ -10 +10,2 @@
+ x
++y
+\\ No newline at end of file
+";
+}
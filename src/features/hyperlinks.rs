@@ -1,12 +1,15 @@
 use std::borrow::Cow;
+use std::ops::Range;
 use std::path::Path;
 
 use lazy_static::lazy_static;
 use regex::{Captures, Regex};
 
+use crate::ansi::{self, iterator::AnsiElementIterator, iterator::Element};
 use crate::config::Config;
 use crate::features::OptionValueFunction;
 use crate::git_config::{GitConfig, GitRemoteRepo};
+use crate::utils::regex_replacement::RegexReplacement;
 
 pub fn make_feature() -> Vec<(String, OptionValueFunction)> {
     builtin_feature!([
@@ -41,6 +44,93 @@ pub fn format_commit_line_with_osc8_commit_hyperlink<'a>(
     }
 }
 
+/// Create a hyperlink to `commit`, displaying `text`.
+pub fn format_osc8_commit_hyperlink<'a>(
+    commit: &str,
+    text: &'a str,
+    config: &Config,
+) -> Cow<'a, str> {
+    if let Some(commit_link_format) = &config.hyperlinks_commit_link_format {
+        Cow::from(format_osc8_hyperlink(
+            &commit_link_format.replace("{commit}", commit),
+            text,
+        ))
+    } else if let Some(repo) = config.git_config().and_then(GitConfig::get_remote_url) {
+        Cow::from(format_osc8_hyperlink(&repo.format_commit_url(commit), text))
+    } else {
+        Cow::from(text)
+    }
+}
+
+/// The rules by which references to issues are hyperlinked: those of --hyperlinks-issue-rules,
+/// e.g. `JIRA-\d+ => https://jira.example.com/browse/$0`, then one hyperlinking e.g. "#1234" to
+/// the issue of --hyperlinks-issue-link-format, or else to that of the forge of the repository.
+pub fn make_issue_link_rules(
+    rules: Option<&str>,
+    issue_link_format: Option<&str>,
+    repo: Option<GitRemoteRepo>,
+) -> Vec<RegexReplacement> {
+    let mut rules = rules.map(RegexReplacement::from_rules).unwrap_or_default();
+    let url = match issue_link_format {
+        Some(format) => Some(format.replace('$', "$$").replace("{issue}", "${1}")),
+        None => repo.and_then(|repo| repo.format_issue_url("${1}")),
+    };
+    if let Some(url) = url {
+        rules.push(RegexReplacement::new(ISSUE_NUMBER_REGEX.clone(), url));
+    }
+    rules
+}
+
+/// Hyperlink the references to issues in the line, by the rules of config.hyperlinks_issue_rules.
+/// Text which is hyperlinked already is not, nor is a reference split by escape sequences.
+pub fn format_issue_references_with_osc8_hyperlinks<'a>(
+    line: &'a str,
+    config: &Config,
+) -> Cow<'a, str> {
+    if config.hyperlinks_issue_rules.is_empty() {
+        return Cow::from(line);
+    }
+    let mut links: Vec<(Range<usize>, String)> = Vec::new();
+    let mut in_hyperlink = false;
+    for element in AnsiElementIterator::new(line) {
+        match element {
+            Element::Osc(i, j) => {
+                if let Some(url) = ansi::osc8_hyperlink_url(&line[i..j]) {
+                    in_hyperlink = !url.is_empty();
+                }
+            }
+            Element::Text(i, j) if !in_hyperlink => {
+                // The rules are applied in order: a reference is hyperlinked by the first.
+                for rule in &config.hyperlinks_issue_rules {
+                    for (range, url) in rule.expanded_matches(&line[i..j]) {
+                        let range = i + range.start..i + range.end;
+                        if !links
+                            .iter()
+                            .any(|(link, _)| link.start < range.end && range.start < link.end)
+                        {
+                            links.push((range, url));
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    if links.is_empty() {
+        return Cow::from(line);
+    }
+    links.sort_by_key(|(range, _)| range.start);
+    let mut formatted = String::with_capacity(line.len());
+    let mut end = 0;
+    for (range, url) in links {
+        formatted.push_str(&line[end..range.start]);
+        formatted.push_str(&format_osc8_hyperlink(&url, &line[range.clone()]));
+        end = range.end;
+    }
+    formatted.push_str(&line[end..]);
+    Cow::from(formatted)
+}
+
 /// Create a file hyperlink, displaying `text`.
 pub fn format_osc8_file_hyperlink<'a, P>(
     absolute_path: P,
@@ -64,6 +154,27 @@ where
     Cow::from(format_osc8_hyperlink(&url, text))
 }
 
+/// Create a hyperlink to a line of the old version of a file, whose object id is `blob`,
+/// displaying `text`, if there is a format for such hyperlinks.
+pub fn format_osc8_minus_file_hyperlink<'a>(
+    blob: &str,
+    path: &str,
+    line_number: usize,
+    text: &'a str,
+    config: &Config,
+) -> Cow<'a, str> {
+    match &config.hyperlinks_minus_file_link_format {
+        Some(format) => Cow::from(format_osc8_hyperlink(
+            &format
+                .replace("{blob}", blob)
+                .replace("{path}", path)
+                .replace("{line}", &line_number.to_string()),
+            text,
+        )),
+        None => Cow::from(text),
+    }
+}
+
 fn format_osc8_hyperlink(url: &str, text: &str) -> String {
     format!(
         "{osc}8;;{url}{st}{text}{osc}8;;{st}",
@@ -76,6 +187,8 @@ fn format_osc8_hyperlink(url: &str, text: &str) -> String {
 
 lazy_static! {
     static ref COMMIT_LINE_REGEX: Regex = Regex::new("(.* )?([0-9a-f]{8,40})(.*)").unwrap();
+    // E.g. "#1234", but not "a#1234".
+    static ref ISSUE_NUMBER_REGEX: Regex = Regex::new(r"\B#(\d+)\b").unwrap();
 }
 
 fn format_commit_line_captures_with_osc8_commit_hyperlink(
@@ -271,6 +384,80 @@ pub mod tests {
         });
     }
 
+    #[test]
+    fn test_format_issue_references_with_osc8_hyperlinks() {
+        let config = integration_test_utils::make_config_from_args(&[
+            "--hyperlinks",
+            "--hyperlinks-issue-link-format",
+            "https://bugs.example.com/{issue}",
+            "--hyperlinks-issue-rules",
+            r"PROJ-(\d+) => https://jira.example.com/browse/PROJ-$1",
+        ]);
+        assert_eq!(
+            format_issue_references_with_osc8_hyperlinks("Fix #12 and PROJ-7, not a#3", &config),
+            format!(
+                "Fix {} and {}, not a#3",
+                format_osc8_hyperlink("https://bugs.example.com/12", "#12"),
+                format_osc8_hyperlink("https://jira.example.com/browse/PROJ-7", "PROJ-7")
+            )
+        );
+        // Text which is hyperlinked already is left as it is.
+        let line = format!(
+            "See {}",
+            format_osc8_hyperlink("https://example.com", "#12")
+        );
+        assert_eq!(
+            format_issue_references_with_osc8_hyperlinks(&line, &config),
+            line
+        );
+        let config = integration_test_utils::make_config_from_args(&[]);
+        assert_eq!(
+            format_issue_references_with_osc8_hyperlinks("Fix #12", &config),
+            "Fix #12"
+        );
+    }
+
+    #[test]
+    fn test_make_issue_link_rules() {
+        let repo = GitRemoteRepo::GitLab {
+            slug: "proj/repo".to_string(),
+        };
+        let rules = make_issue_link_rules(None, None, Some(repo));
+        assert_eq!(
+            rules[0].expanded_matches("(#42)").collect::<Vec<_>>(),
+            vec![(1..4, "https://gitlab.com/proj/repo/-/issues/42".to_string())]
+        );
+        let repo = GitRemoteRepo::SourceHut {
+            slug: "~user/repo".to_string(),
+        };
+        assert!(make_issue_link_rules(None, None, Some(repo)).is_empty());
+    }
+
+    #[test]
+    fn test_issue_references_in_unchanged_lines_hyperlinks() {
+        DeltaTest::with_args(&[
+            "--hyperlinks",
+            "--hyperlinks-issue-rules",
+            r"PROJ-\d+ => https://jira.example.com/browse/$0",
+        ])
+        .with_input(
+            "\
+diff --git a/a.rs b/a.rs
+index 587be6b..975fbec 100644
+--- a/a.rs
++++ b/a.rs
+@@ -1,2 +1,2 @@
+ // See PROJ-7.
+-x
++y
+",
+        )
+        .expect_raw_contains(&format_osc8_hyperlink(
+            "https://jira.example.com/browse/PROJ-7",
+            "PROJ-7",
+        ));
+    }
+
     const GIT_DIFF_OUTPUT: &str = r#"
 diff --git a/__path__ b/__path__
 index 587be6b..975fbec 100644
@@ -0,0 +1,258 @@
+use std::collections::HashMap;
+use std::process::{Command, Stdio};
+
+use chrono_humanize::{Accuracy, HumanTime, Tense};
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::ansi::measure_text_width;
+use crate::config::Config;
+use crate::format::{self, Align};
+use crate::utils;
+
+const AUTHOR_WIDTH: usize = 12;
+const AGE_WIDTH: usize = 9;
+
+/// The author and age of the lines of the old file in a hunk, displayed in a column before
+/// removed and unchanged lines (--inline-blame).
+#[derive(Debug, Default)]
+pub struct InlineBlame {
+    authors: HashMap<usize, (String, i64)>,
+    // Line number in the old file of the next removed or unchanged line.
+    next_line_number: usize,
+    now: i64,
+}
+
+impl InlineBlame {
+    /// Run git blame on the lines of the old file `path` covered by a hunk. If git blame fails
+    /// (e.g. the input is not from a repository) the column is left blank.
+    pub fn for_hunk(
+        path: &str,
+        revision: &str,
+        start: usize,
+        count: usize,
+        config: &Config,
+    ) -> Self {
+        let authors = if count > 0 {
+            run_git_blame(path, revision, start, count, config).unwrap_or_default()
+        } else {
+            HashMap::new()
+        };
+        Self {
+            authors,
+            next_line_number: start,
+            now: chrono::Utc::now().timestamp(),
+        }
+    }
+
+    /// The column for the next removed or unchanged line.
+    fn next_column(&mut self) -> String {
+        let column = match self.authors.get(&self.next_line_number) {
+            Some((author, time)) => format_column(author, *time, self.now),
+            None => blank_column(),
+        };
+        self.next_line_number += 1;
+        column
+    }
+
+    /// Skip removed or unchanged lines which are not displayed.
+    pub fn skip_lines(&mut self, n: usize) {
+        self.next_line_number += n;
+    }
+
+    /// Insert the column before each of the lines painted in `output_buffer` from `start`: first
+    /// `n_old` removed or unchanged lines, followed by added lines.
+    pub fn annotate(
+        &mut self,
+        output_buffer: &mut String,
+        start: usize,
+        n_old: usize,
+        config: &Config,
+    ) {
+        let painted = output_buffer.split_off(start);
+        for (i, line) in painted.split_inclusive('\n').enumerate() {
+            let column = if i < n_old {
+                self.next_column()
+            } else {
+                blank_column()
+            };
+            output_buffer.push_str(&config.inline_blame_style.paint(column).to_string());
+            output_buffer.push_str(line);
+        }
+    }
+}
+
+fn format_column(author: &str, time: i64, now: i64) -> String {
+    let age = HumanTime::from(chrono::Duration::seconds(time - now))
+        .to_text_en(Accuracy::Rough, Tense::Present)
+        .replace("a few seconds", "now");
+    let author = if measure_text_width(author) > AUTHOR_WIDTH {
+        author
+            .graphemes(true)
+            .take(AUTHOR_WIDTH - 1)
+            .collect::<String>()
+            + "…"
+    } else {
+        author.to_string()
+    };
+    format!(
+        "{} {} ",
+        format::pad(author, AUTHOR_WIDTH, Align::Left, None),
+        format::pad(age, AGE_WIDTH, Align::Right, Some(AGE_WIDTH)),
+    )
+}
+
+fn blank_column() -> String {
+    " ".repeat(AUTHOR_WIDTH + AGE_WIDTH + 2)
+}
+
+fn run_git_blame(
+    path: &str,
+    revision: &str,
+    start: usize,
+    count: usize,
+    config: &Config,
+) -> Option<HashMap<usize, (String, i64)>> {
+    let path = utils::path::absolute_path(path, config)?;
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(path.parent()?)
+        .args(["blame", "--line-porcelain", "-L"])
+        .arg(format!("{start},+{count}"))
+        .arg(revision)
+        .arg("--")
+        .arg(path.file_name()?)
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(parse_line_porcelain(&String::from_utf8_lossy(
+        &output.stdout,
+    )))
+}
+
+/// Parse the output of `git blame --line-porcelain` into the author and author time of each line.
+fn parse_line_porcelain(output: &str) -> HashMap<usize, (String, i64)> {
+    let mut authors = HashMap::new();
+    let (mut line_number, mut author, mut time) = (None, String::new(), 0);
+    for line in output.lines() {
+        if line.starts_with('\t') {
+            if let Some(line_number) = line_number.take() {
+                authors.insert(line_number, (std::mem::take(&mut author), time));
+            }
+        } else if let Some(name) = line.strip_prefix("author ") {
+            author = name.to_string();
+        } else if let Some(t) = line.strip_prefix("author-time ") {
+            time = t.parse().unwrap_or(0);
+        } else if line_number.is_none() {
+            // The header of a line: <commit> <original line number> <final line number> [<count>]
+            line_number = line.split(' ').nth(2).and_then(|n| n.parse().ok());
+        }
+    }
+    authors
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::integration_test_utils::{self, DeltaTest};
+
+    const LINE_PORCELAIN: &str = "\
+94907c0f136f46dc46ffae2dc92dca9af7eb7c2e 3 10 2
+author Jane Doe
+author-mail <jane@example.com>
+author-time 1704373630
+author-tz +0100
+committer Jane Doe
+summary Change the answer
+filename a.py
+\tx = 41
+94907c0f136f46dc46ffae2dc92dca9af7eb7c2e 4 11
+author Jane Doe
+author-mail <jane@example.com>
+author-time 1704373630
+author-tz +0100
+summary Change the answer
+filename a.py
+\ty = 1
+2e4ec4b73b0b1a2a1b6e8a3d3fd1f6d3a4e2c1a9 12 12 1
+author Maximilian Mustermann
+author-time 1600000000
+filename a.py
+\tz = 2
+";
+
+    #[test]
+    fn test_parse_line_porcelain() {
+        let authors = parse_line_porcelain(LINE_PORCELAIN);
+        assert_eq!(authors.len(), 3);
+        assert_eq!(authors[&10], ("Jane Doe".to_string(), 1704373630));
+        assert_eq!(authors[&11], ("Jane Doe".to_string(), 1704373630));
+        assert_eq!(
+            authors[&12],
+            ("Maximilian Mustermann".to_string(), 1600000000)
+        );
+    }
+
+    #[test]
+    fn test_format_column() {
+        let now = 1704373630;
+        assert_eq!(
+            format_column("Jane Doe", now - 3 * 86400, now),
+            "Jane Doe        3 days "
+        );
+        assert_eq!(
+            format_column("Maximilian Mustermann", now - 400 * 86400, now),
+            "Maximilian …    a year "
+        );
+        assert_eq!(
+            format_column("Jane Doe", now, now),
+            "Jane Doe           now "
+        );
+        assert_eq!(measure_text_width(&blank_column()), 23);
+    }
+
+    #[test]
+    fn test_annotate() {
+        let config =
+            integration_test_utils::make_config_from_args(&["--inline-blame-style", "normal"]);
+        let now = 1704373630;
+        let mut inline_blame = InlineBlame {
+            authors: parse_line_porcelain(LINE_PORCELAIN),
+            next_line_number: 11,
+            now,
+        };
+        let mut output_buffer = "header\n".to_string();
+        inline_blame.annotate(&mut output_buffer, 7, 2, &config);
+        assert_eq!(output_buffer, "header\n");
+
+        output_buffer.push_str("-y = 1\n-z = 2\n+z = 3\n");
+        inline_blame.next_line_number = 11;
+        inline_blame.annotate(&mut output_buffer, 7, 2, &config);
+        let lines: Vec<_> = output_buffer.lines().collect();
+        assert_eq!(
+            lines[1],
+            format!("{}-y = 1", format_column("Jane Doe", 1704373630, now))
+        );
+        assert!(lines[2].starts_with("Maximilian …"));
+        assert_eq!(lines[3], format!("{}+z = 3", blank_column()));
+        assert_eq!(inline_blame.next_line_number, 13);
+    }
+
+    #[test]
+    fn test_inline_blame_without_repository() {
+        let diff = "\
+--- a/delta__test_inline_blame_no_such_file.py
++++ b/delta__test_inline_blame_no_such_file.py
+@@ -1,2 +1,2 @@
+ x = 1
+-y = 1
++y = 2
+";
+        let result = DeltaTest::with_args(&["--inline-blame"]).with_input(diff);
+        let blank = blank_column();
+        result.expect_contains(&format!("{blank}x = 1\n{blank}y = 1\n{blank}y = 2"));
+    }
+}
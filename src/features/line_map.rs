@@ -0,0 +1,192 @@
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use serde::Serialize;
+
+use crate::delta::{State, StateMachine};
+use crate::minusplus::MinusPlus;
+use crate::minusplus::MinusPlusIndex::{Minus, Plus};
+
+/// The record of a line of the input in the line map (--line-map).
+#[derive(Debug, Serialize)]
+struct LineRecord<'a> {
+    line: usize,
+    kind: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    path: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    old_line: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    new_line: Option<usize>,
+}
+
+/// The file to which the line map is written, and the position in the input.
+pub struct LineMap {
+    writer: BufWriter<File>,
+    // The number of lines of the input read so far.
+    line: usize,
+    // The line numbers of the next lines of the old and new versions of the current file.
+    line_numbers: MinusPlus<usize>,
+}
+
+impl LineMap {
+    /// Create the line map file.
+    pub fn create(path: &Path) -> io::Result<Self> {
+        let file = File::create(path).map_err(|err| {
+            io::Error::new(
+                err.kind(),
+                format!(
+                    "Could not create the line map file {}: {err}",
+                    path.display()
+                ),
+            )
+        })?;
+        Ok(Self {
+            writer: BufWriter::new(file),
+            line: 0,
+            line_numbers: MinusPlus::default(),
+        })
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+impl<'a> StateMachine<'a> {
+    /// Write the record of the current line to the line map (--line-map). The kind of the line
+    /// is that of the state in which it has been handled.
+    pub fn record_line_map(&mut self) -> io::Result<()> {
+        let Some(line_map) = &mut self.line_map else {
+            return Ok(());
+        };
+        line_map.line += 1;
+        let path = if self.plus_file == "/dev/null" {
+            &self.minus_file
+        } else {
+            &self.plus_file
+        };
+        let mut record = LineRecord {
+            line: line_map.line,
+            kind: "other",
+            path: Some(path),
+            old_line: None,
+            new_line: None,
+        };
+        let mut take_line_number = |index| {
+            let line_number = line_map.line_numbers[index];
+            line_map.line_numbers[index] += 1;
+            Some(line_number)
+        };
+        match &self.state {
+            // E.g. "\ No newline at end of file"
+            State::HunkMinus(..) | State::HunkZero(..) | State::HunkPlus(..)
+                if self.line.starts_with('\\') => {}
+            State::HunkMinus(..) => {
+                record.kind = "minus";
+                record.old_line = take_line_number(Minus);
+            }
+            State::HunkZero(..) => {
+                record.kind = "zero";
+                record.old_line = take_line_number(Minus);
+                record.new_line = take_line_number(Plus);
+            }
+            State::HunkPlus(..) => {
+                record.kind = "plus";
+                record.new_line = take_line_number(Plus);
+            }
+            State::HunkHeader(_, parsed_hunk_header, _, _) => {
+                record.kind = "hunk-header";
+                // In a combined diff, the line numbers of the old file are those of the first
+                // parent.
+                let ranges = parsed_hunk_header.line_numbers_and_hunk_lengths();
+                let start = |range: Option<&(usize, usize)>| range.map_or(0, |&(start, _)| start);
+                line_map.line_numbers = MinusPlus::new(start(ranges.first()), start(ranges.last()));
+            }
+            State::DiffHeader(_) => record.kind = "file-header",
+            State::CommitMeta => record.kind = "commit",
+            _ => {}
+        }
+        if !matches!(
+            record.kind,
+            "file-header" | "hunk-header" | "minus" | "zero" | "plus"
+        ) || path.is_empty()
+        {
+            record.path = None;
+        }
+        let json = serde_json::to_string(&record).map_err(io::Error::other)?;
+        writeln!(line_map.writer, "{json}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use crate::tests::integration_test_utils::DeltaTest;
+    use crate::tests::test_utils::temp_path;
+
+    fn line_map(args: &[&str], name: &str) -> Option<Vec<String>> {
+        let path = temp_path(name);
+        let _ = fs::remove_file(&path);
+        let mut args = args.to_vec();
+        args.extend(["--line-map", path.to_str().unwrap()]);
+        DeltaTest::with_args(&args).with_input(DIFF);
+        let lines = fs::read_to_string(&path)
+            .ok()
+            .map(|map| map.lines().map(str::to_string).collect());
+        let _ = fs::remove_file(&path);
+        lines
+    }
+
+    #[test]
+    fn test_line_map() {
+        let lines = line_map(&["--color-only"], "test_line_map").unwrap();
+        assert_eq!(
+            lines,
+            [
+                r#"{"line":1,"kind":"commit"}"#,
+                r#"{"line":2,"kind":"commit"}"#,
+                r#"{"line":3,"kind":"commit"}"#,
+                r#"{"line":4,"kind":"commit"}"#,
+                r#"{"line":5,"kind":"commit"}"#,
+                r#"{"line":6,"kind":"file-header","path":"file.rs"}"#,
+                r#"{"line":7,"kind":"file-header","path":"file.rs"}"#,
+                r#"{"line":8,"kind":"file-header","path":"file.rs"}"#,
+                r#"{"line":9,"kind":"file-header","path":"file.rs"}"#,
+                r#"{"line":10,"kind":"hunk-header","path":"file.rs"}"#,
+                r#"{"line":11,"kind":"zero","path":"file.rs","old_line":10,"new_line":20}"#,
+                r#"{"line":12,"kind":"minus","path":"file.rs","old_line":11}"#,
+                r#"{"line":13,"kind":"zero","path":"file.rs","old_line":12,"new_line":21}"#,
+                r#"{"line":14,"kind":"plus","path":"file.rs","new_line":22}"#,
+                r#"{"line":15,"kind":"plus","path":"file.rs","new_line":23}"#,
+                r#"{"line":16,"kind":"other"}"#,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_line_map_requires_color_only() {
+        assert_eq!(line_map(&[], "test_line_map_requires_color_only"), None);
+    }
+
+    const DIFF: &str = "\
+commit 1111111111111111111111111111111111111111
+Author: A <a@example.com>
+
+    Subject
+
+diff --git a/file.rs b/file.rs
+index 1111111..2222222 100644
+--- a/file.rs
++++ b/file.rs
+@@ -10,3 +20,4 @@ fn a() {
+ a
+-b
+ e
++c
++d
+\\ No newline at end of file
+";
+}
@@ -1,3 +1,4 @@
+use std::borrow::Cow;
 use std::cmp::max;
 
 use lazy_static::lazy_static;
@@ -5,10 +6,10 @@ use regex::Regex;
 
 use crate::color::ColorMode::*;
 use crate::config;
-use crate::delta::State;
+use crate::delta::{DiffType, MergeParents, State};
 use crate::features::hyperlinks;
 use crate::features::side_by_side::ansifill::{self, ODD_PAD_CHAR};
-use crate::features::side_by_side::{Left, PanelSide, Right};
+use crate::features::side_by_side::{Left, PanelOrder, PanelSide, Right};
 use crate::features::OptionValueFunction;
 use crate::format::{self, Align, Placeholder};
 use crate::minusplus::*;
@@ -79,6 +80,11 @@ pub fn linenumbers_and_styles<'a>(
         config.line_numbers_style_minusplus[Plus],
     );
     let ((minus_number, plus_number), (minus_style, plus_style)) = match state {
+        // The line numbers of a removed line of a combined diff are those of the first parent, if
+        // the line is in it.
+        State::HunkMinus(diff_type, _) if !is_in_first_parent(diff_type, '-') => {
+            ((None, None), (minus_style, plus_style))
+        }
         State::HunkMinus(_, _) => {
             line_numbers_data.line_number[Left] += increment as usize;
             ((Some(nr_left), None), (minus_style, plus_style))
@@ -90,6 +96,13 @@ pub fn linenumbers_and_styles<'a>(
             ((Some(nr_left), Some(nr_right)), (zero_style, zero_style))
         }
         State::HunkZeroWrapped => ((None, None), (zero_style, zero_style)),
+        // An added line of a combined diff may be in the first parent, e.g. " +x" is added
+        // relative to the second parent only.
+        State::HunkPlus(diff_type, _) if is_in_first_parent(diff_type, ' ') => {
+            line_numbers_data.line_number[Left] += increment as usize;
+            line_numbers_data.line_number[Right] += increment as usize;
+            ((Some(nr_left), Some(nr_right)), (minus_style, plus_style))
+        }
         State::HunkPlus(_, _) => {
             line_numbers_data.line_number[Right] += increment as usize;
             ((None, Some(nr_right)), (minus_style, plus_style))
@@ -103,6 +116,16 @@ pub fn linenumbers_and_styles<'a>(
     ))
 }
 
+// Whether a changed line is in the first parent: for a combined diff, whether the marker of the line
+// for the first parent is `marker` ('-' for removed lines, ' ' for added lines). The removed lines
+// of a unified diff are in the old file, and its added lines are not.
+fn is_in_first_parent(diff_type: &DiffType, marker: char) -> bool {
+    match diff_type {
+        DiffType::Combined(MergeParents::Prefix(prefix), _) => prefix.starts_with(marker),
+        _ => marker == '-',
+    }
+}
+
 /// Return a vec of `ansi_term::ANSIGenericString`s representing the left and right fields of the
 /// two-column line number display.
 pub fn format_and_paint_line_numbers<'a>(
@@ -143,6 +166,78 @@ pub fn format_and_paint_line_numbers<'a>(
     formatted_numbers
 }
 
+/// The width of each line number of the column between side-by-side panels
+/// (--side-by-side-center-line-numbers).
+const CENTER_LINE_NUMBER_WIDTH: usize = 4;
+
+/// The width of the column of line numbers between side-by-side panels, whose numbers are
+/// separated by `separator_width` columns.
+pub fn center_line_numbers_width(separator_width: usize) -> usize {
+    2 * (CENTER_LINE_NUMBER_WIDTH + 1) + separator_width
+}
+
+/// Format the line numbers of a row of side-by-side panels as a column between them, e.g.
+/// "   12│13   ". Both numbers link to the plus file, the minus number to the current line of the
+/// plus file, which is where a removed line was removed, unless it links to the old version of
+/// the file (--hyperlinks-minus-file-link-format).
+pub fn format_and_paint_center_line_numbers(
+    line_numbers_data: &LineNumbersData,
+    line_numbers: MinusPlus<Option<usize>>,
+    styles: MinusPlus<Style>,
+    separator: ansi_term::ANSIString,
+    config: &config::Config,
+) -> String {
+    let plus_file = line_numbers_data.plus_file.as_str();
+    // The number of the left panel is aligned to the separator, as is that of the right one.
+    let (minus_align, plus_align) = match config.side_by_side_order {
+        PanelOrder::MinusFirst => (Align::Right, Align::Left),
+        PanelOrder::PlusFirst => (Align::Left, Align::Right),
+    };
+    let mut minus_number = format_line_number(
+        line_numbers[Minus],
+        minus_align,
+        CENTER_LINE_NUMBER_WIDTH,
+        None,
+        None,
+        config,
+    );
+    if line_numbers_data.minus_version.is_some() {
+        minus_number = link_minus_line_number(
+            line_numbers_data,
+            line_numbers[Minus],
+            &minus_number,
+            config,
+        )
+        .into_owned();
+    } else if let (Some(_), true, Some(absolute_path)) = (
+        line_numbers[Minus],
+        config.hyperlinks,
+        utils::path::hyperlink_target_path(plus_file, config),
+    ) {
+        minus_number = hyperlinks::format_osc8_file_hyperlink(
+            absolute_path,
+            Some(line_numbers_data.line_number[Right]),
+            &minus_number,
+            config,
+        )
+        .into_owned();
+    }
+    let plus_number = format_line_number(
+        line_numbers[Plus],
+        plus_align,
+        CENTER_LINE_NUMBER_WIDTH,
+        None,
+        Some(plus_file),
+        config,
+    );
+    let numbers = MinusPlus::new(
+        styles[Minus].paint(minus_number),
+        styles[Plus].paint(plus_number),
+    );
+    let [left, right] = config.side_by_side_order.panels();
+    format!(" {}{separator}{} ", numbers[left], numbers[right])
+}
+
 lazy_static! {
     static ref LINE_NUMBERS_PLACEHOLDER_REGEX: Regex =
         format::make_placeholder_regex(&["nm", "np"]);
@@ -154,6 +249,9 @@ pub struct LineNumbersData<'a> {
     pub line_number: MinusPlus<usize>,
     pub hunk_max_line_number_width: usize,
     pub plus_file: String,
+    // The object id and path of the old version of the file, to which minus line numbers link
+    // (--hyperlinks-minus-file-link-format).
+    pub minus_version: Option<(String, String)>,
 }
 
 pub type SideBySideLineWidth = MinusPlus<usize>;
@@ -268,14 +366,25 @@ fn format_and_paint_line_number_field<'a>(
         let alignment_spec = placeholder.alignment_spec.unwrap_or(Align::Center);
         match placeholder.placeholder {
             Some(Placeholder::NumberMinus) => {
-                ansi_strings.push(styles[Minus].paint(format_line_number(
+                let number = format_line_number(
                     line_numbers[Minus],
                     alignment_spec,
                     width,
                     placeholder.precision,
                     None,
                     config,
-                )))
+                );
+                ansi_strings.push(
+                    styles[Minus].paint(
+                        link_minus_line_number(
+                            line_numbers_data,
+                            line_numbers[Minus],
+                            &number,
+                            config,
+                        )
+                        .into_owned(),
+                    ),
+                )
             }
             Some(Placeholder::NumberPlus) => {
                 ansi_strings.push(styles[Plus].paint(format_line_number(
@@ -296,6 +405,26 @@ fn format_and_paint_line_number_field<'a>(
     ansi_strings
 }
 
+/// Link a formatted minus line number to the line of the old version of the file, if its object
+/// id is known and there is a format for such links (--hyperlinks-minus-file-link-format).
+fn link_minus_line_number<'a>(
+    line_numbers_data: &LineNumbersData,
+    line_number: Option<usize>,
+    text: &'a str,
+    config: &config::Config,
+) -> Cow<'a, str> {
+    match (
+        line_number,
+        config.hyperlinks,
+        &line_numbers_data.minus_version,
+    ) {
+        (Some(n), true, Some((blob, path))) => {
+            hyperlinks::format_osc8_minus_file_hyperlink(blob, path, n, text, config)
+        }
+        _ => Cow::from(text),
+    }
+}
+
 /// Return line number formatted according to `alignment` and `width`.
 fn format_line_number(
     line_number: Option<usize>,
@@ -308,7 +437,7 @@ fn format_line_number(
     let pad = |n| format::pad(n, width, alignment, precision);
     match (line_number, config.hyperlinks, plus_file) {
         (None, _, _) => " ".repeat(width),
-        (Some(n), true, Some(file)) => match utils::path::absolute_path(file, config) {
+        (Some(n), true, Some(file)) => match utils::path::hyperlink_target_path(file, config) {
             Some(absolute_path) => {
                 hyperlinks::format_osc8_file_hyperlink(absolute_path, line_number, &pad(n), config)
                     .to_string()
@@ -702,6 +831,48 @@ pub mod tests {
         assert_eq!(lines.next().unwrap(), "    ⋮  2 │bb = 2");
     }
 
+    #[test]
+    fn test_minus_line_number_hyperlinks() {
+        let link_format = [
+            "--hyperlinks-minus-file-link-format",
+            "git-blob://{blob}/{path}#{line}",
+        ];
+        for args in [&["-n"][..], &["-s", "--side-by-side-center-line-numbers"]] {
+            let args = [args, &["--hyperlinks"], &link_format].concat();
+            let output = DeltaTest::with_args(&args).with_input(ONE_MINUS_ONE_PLUS_LINE_DIFF);
+            assert!(output
+                .raw_output
+                .contains("\x1b]8;;git-blob://223ca50/a.py#2\x1b\\"));
+            assert!(output
+                .raw_output
+                .contains("\x1b]8;;git-blob://223ca50/a.py#1\x1b\\"));
+        }
+        // The removed line numbers of an added file, or without --hyperlinks, are not links.
+        let output = DeltaTest::with_args(&[&["-n", "--hyperlinks"][..], &link_format].concat())
+            .with_input(&ONE_MINUS_ONE_PLUS_LINE_DIFF.replace("223ca50", "0000000"));
+        assert!(!output.raw_output.contains("git-blob://"));
+        let output = DeltaTest::with_args(&[&["-n"][..], &link_format].concat())
+            .with_input(ONE_MINUS_ONE_PLUS_LINE_DIFF);
+        assert!(!output.raw_output.contains("git-blob://"));
+    }
+
+    #[test]
+    fn test_combined_diff_line_numbers() {
+        DeltaTest::with_args(&["--line-numbers"])
+            .with_input(COMBINED_DIFF)
+            .expect_after_header(
+                r#"
+                #indent_mark
+                  1 ⋮  1 │  a
+                  2 ⋮    │- b2
+                    ⋮    │ -b1
+                    ⋮  2 │++b3
+                  3 ⋮  3 │ +x
+                    ⋮  4 │+ y
+                  4 ⋮  5 │  c"#,
+            );
+    }
+
     #[test]
     fn test_repeated_placeholder() {
         let config = make_config_from_args(&[
@@ -898,6 +1069,21 @@ index 223ca50..367a6f6 100644
 +bb = 2
 ";
 
+    const COMBINED_DIFF: &str = "\
+diff --cc f
+index 1111111,2222222..3333333
+--- a/f
++++ b/f
+@@@ -1,4 -1,4 +1,5 @@@
+  a
+- b2
+ -b1
+++b3
+ +x
++ y
+  c
+";
+
     const TWO_LINE_DIFFS: &str = "\
 diff --git i/a.py w/a.py
 index 223ca50..367a6f6 100644
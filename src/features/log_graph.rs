@@ -0,0 +1,263 @@
+use std::cell::RefCell;
+use std::io::{self, Write};
+use std::rc::Rc;
+
+use lazy_static::lazy_static;
+use regex::Regex;
+
+use crate::ansi;
+use crate::cli;
+use crate::config::Config;
+use crate::delta::{Source, StateMachine};
+
+// The characters of which git draws the graph of `git log --graph`.
+const GRAPH_CHARS: &[char] = &['|', '/', '\\', '_', '.', '*', '-', ' '];
+
+lazy_static! {
+    // E.g. "| * commit 0123abc" or, with --oneline, "*   0123abc (HEAD -> main) Merge": the
+    // graph, with a '*' in the column of the commit, followed by the first line of the commit.
+    static ref COMMIT_LINE_REGEX: Regex =
+        Regex::new(r"^([|/\\_.*\- ]*\*[|/\\_.*\- ]*)(?:commit )?[0-9a-f]{7,40}\b").unwrap();
+}
+
+/// The graph drawn by `git log --graph` to the left of the current line, as displayed, and its
+/// width. It is shared by the state machine, which removes it from the input lines, and the
+/// writer, which adds it to the output lines.
+#[derive(Clone, Debug, Default)]
+pub struct LogGraph(Rc<RefCell<(String, usize)>>);
+
+impl<'a> StateMachine<'a> {
+    /// If the input is the output of `git log --graph`, remove the graph from the current line,
+    /// so that the commit or diff line to its right is handled as usual, and make it the prefix
+    /// of the output lines (see LogGraphWriter). The lines held back are displayed first, with
+    /// the previous graph. The width of the graph is that of the last commit line, since git
+    /// draws a graph of that width to the left of the lines of the commit and its diff.
+    pub fn remove_log_graph(&mut self) -> io::Result<()> {
+        let Some(log_graph) = self.log_graph.clone() else {
+            return Ok(());
+        };
+        let width = match COMMIT_LINE_REGEX.captures(&self.line) {
+            // The input is that of `git log --graph` if it starts with a commit line.
+            Some(caps) if self.log_graph_width.is_some() || self.source == Source::Unknown => {
+                caps[1].len()
+            }
+            _ => match self.log_graph_width {
+                Some(width) => width,
+                None => return Ok(()),
+            },
+        };
+        self.log_graph_width = Some(width);
+        let width = self
+            .line
+            .chars()
+            .take(width)
+            .take_while(|c| GRAPH_CHARS.contains(c))
+            .count();
+        // The graph characters are ASCII, so their width is their length in bytes.
+        let raw_length = raw_length_of_width(&self.raw_line, width);
+        let graph = if self.config.log_graph_style.is_raw {
+            let raw_graph = &self.raw_line[..raw_length];
+            if raw_graph.contains('\x1b') {
+                format!("{raw_graph}\x1b[0m")
+            } else {
+                raw_graph.to_string()
+            }
+        } else {
+            self.config
+                .log_graph_style
+                .paint(&self.line[..width])
+                .to_string()
+        };
+        if log_graph.0.borrow().0 != graph {
+            self.painter.paint_buffered_minus_and_plus_lines();
+            self.painter.emit()?;
+            *log_graph.0.borrow_mut() = (graph, width);
+        }
+        self.line.replace_range(..width, "");
+        self.raw_line.replace_range(..raw_length, "");
+        Ok(())
+    }
+}
+
+// The length in bytes of the start of `s` which is `width` columns wide, not including the ANSI
+// escape sequences following it.
+fn raw_length_of_width(s: &str, width: usize) -> usize {
+    let mut length = 0;
+    let mut remaining = width;
+    for (grapheme_length, grapheme_width) in ansi::grapheme_lengths_and_widths(s) {
+        if remaining == 0 {
+            break;
+        }
+        length += grapheme_length;
+        remaining = remaining.saturating_sub(grapheme_width);
+    }
+    length
+}
+
+/// A writer which starts each line with the graph of `git log --graph` to the left of the input
+/// line from which it was made. In side-by-side mode, the lines are truncated to the width of the
+/// terminal (or --width), which the graph would otherwise make them exceed.
+pub struct LogGraphWriter<'a> {
+    writer: &'a mut dyn Write,
+    log_graph: LogGraph,
+    max_width: Option<usize>,
+    line: Vec<u8>,
+}
+
+impl<'a> LogGraphWriter<'a> {
+    pub fn new(writer: &'a mut dyn Write, log_graph: LogGraph, config: &Config) -> Self {
+        Self {
+            writer,
+            log_graph,
+            max_width: config
+                .side_by_side
+                .then_some(match config.decorations_width {
+                    cli::Width::Fixed(width) => width,
+                    cli::Width::Variable => config.available_terminal_width,
+                }),
+            line: Vec::new(),
+        }
+    }
+
+    fn write_line(&mut self) -> io::Result<()> {
+        let (graph, graph_width) = &*self.log_graph.0.borrow();
+        if graph.is_empty() {
+            self.writer.write_all(&self.line)?;
+        } else {
+            let line = String::from_utf8_lossy(&self.line);
+            let (line, newline) = match line.strip_suffix('\n') {
+                Some(line) => (line, "\n"),
+                None => (&*line, ""),
+            };
+            let line = match self.max_width {
+                Some(max_width) => {
+                    ansi::truncate_str(line, max_width.saturating_sub(*graph_width), "")
+                }
+                None => line.into(),
+            };
+            write!(self.writer, "{graph}{line}{newline}")?;
+        }
+        self.line.clear();
+        Ok(())
+    }
+}
+
+impl<'a> Write for LogGraphWriter<'a> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        for line in buf.split_inclusive(|&byte| byte == b'\n') {
+            self.line.extend_from_slice(line);
+            if line.ends_with(b"\n") {
+                self.write_line()?;
+            }
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+impl<'a> Drop for LogGraphWriter<'a> {
+    fn drop(&mut self) {
+        if !self.line.is_empty() {
+            let _ = self.write_line();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::integration_test_utils::{self, DeltaTest};
+
+    #[test]
+    fn test_raw_length_of_width() {
+        assert_eq!(raw_length_of_width("| * commit", 4), 4);
+        assert_eq!(
+            raw_length_of_width("\x1b[31m|\x1b[m \x1b[33mcommit", 2),
+            "\x1b[31m|\x1b[m ".len()
+        );
+        assert_eq!(raw_length_of_width("|", 2), 1);
+        assert_eq!(raw_length_of_width("abc", 0), 0);
+    }
+
+    #[test]
+    fn test_log_graph() {
+        let output =
+            DeltaTest::with_args(&["--file-modified-label", "modified:"]).with_input(LOG_GRAPH);
+        let output = &output.output;
+        assert!(output.contains("* commit 1111111111111111111111111111111111111111\n"));
+        assert!(output.contains("| Author: Dan Davison"));
+        assert!(output.contains("| modified: src/a.rs\n"));
+        // The diff lines are handled as those of any diff.
+        assert!(output.contains("| fn main() {\n|     let a = 1;\n|     let a = 2;\n|/\n"));
+        assert!(!output.contains("| -"));
+        assert!(output.contains("| * commit 2222222222222222222222222222222222222222\n"));
+        assert!(output.contains("| | added: src/b.rs\n"));
+    }
+
+    #[test]
+    fn test_log_graph_style() {
+        DeltaTest::with_args(&["--log-graph-style", "blue"])
+            .with_input(LOG_GRAPH)
+            .expect_raw_contains("\x1b[34m| \x1b[0m");
+    }
+
+    #[test]
+    fn test_log_graph_side_by_side() {
+        let config =
+            integration_test_utils::make_config_from_args(&["--side-by-side", "--width", "40"]);
+        let output = integration_test_utils::run_delta(LOG_GRAPH, &config);
+        let output = ansi::strip_ansi_codes(&output);
+        // The lines of the diff are no wider than the terminal, as without the graph.
+        for line in output.lines().filter(|line| line.contains('│')) {
+            assert!(ansi::measure_text_width(line) <= 40, "{}", line);
+        }
+        assert!(output.contains("| │  1 │fn main() {"), "{}", output);
+    }
+
+    #[test]
+    fn test_no_log_graph_without_graph_commit_line() {
+        let input = "\
+commit 1111111111111111111111111111111111111111
+Author: Dan Davison <dandavison7@gmail.com>
+
+    * 1234567 is mentioned in a list
+";
+        DeltaTest::with_args(&[])
+            .with_input(input)
+            .expect_contains("    * 1234567 is mentioned in a list");
+    }
+
+    const LOG_GRAPH: &str = "\
+* commit 1111111111111111111111111111111111111111
+| Author: Dan Davison <dandavison7@gmail.com>
+| Date:   Sat Oct 3 17:09:08 2026 -0400
+|
+|     Change a
+|
+| diff --git a/src/a.rs b/src/a.rs
+| index 1111111..2222222 100644
+| --- a/src/a.rs
+| +++ b/src/a.rs
+| @@ -1,2 +1,2 @@
+|  fn main() {
+| -    let a = 1;
+| +    let a = 2;
+|/
+| * commit 2222222222222222222222222222222222222222
+| | Author: Dan Davison <dandavison7@gmail.com>
+| | Date:   Fri Oct 2 17:09:08 2026 -0400
+| |
+| |     Add b
+| |
+| | diff --git a/src/b.rs b/src/b.rs
+| | new file mode 100644
+| | index 0000000..3333333
+| | --- /dev/null
+| | +++ b/src/b.rs
+| | @@ -0,0 +1 @@
+| | +fn b() {}
+";
+}
@@ -26,10 +26,17 @@ type OptionValueFunction = Box<dyn Fn(&cli::Opt, &Option<GitConfig>) -> Provenan
 // for the option.
 pub fn make_builtin_features() -> HashMap<String, BuiltinFeature> {
     vec![
+        ("ci".to_string(), ci::make_feature().into_iter().collect()),
         (
             "color-only".to_string(),
             color_only::make_feature().into_iter().collect(),
         ),
+        (
+            "deuteranopia".to_string(),
+            accessible_colors::make_deuteranopia_feature()
+                .into_iter()
+                .collect(),
+        ),
         (
             "diff-highlight".to_string(),
             diff_highlight::make_feature().into_iter().collect(),
@@ -38,6 +45,20 @@ pub fn make_builtin_features() -> HashMap<String, BuiltinFeature> {
             "diff-so-fancy".to_string(),
             diff_so_fancy::make_feature().into_iter().collect(),
         ),
+        (
+            "diff-so-fancy-strict".to_string(),
+            diff_so_fancy::make_strict_feature().into_iter().collect(),
+        ),
+        (
+            "github".to_string(),
+            github::make_feature().into_iter().collect(),
+        ),
+        (
+            "high-contrast".to_string(),
+            accessible_colors::make_high_contrast_feature()
+                .into_iter()
+                .collect(),
+        ),
         (
             "hyperlinks".to_string(),
             hyperlinks::make_feature().into_iter().collect(),
@@ -55,6 +76,12 @@ pub fn make_builtin_features() -> HashMap<String, BuiltinFeature> {
             "side-by-side".to_string(),
             side_by_side::make_feature().into_iter().collect(),
         ),
+        (
+            "tritanopia".to_string(),
+            accessible_colors::make_tritanopia_feature()
+                .into_iter()
+                .collect(),
+        ),
     ]
     .into_iter()
     .collect()
@@ -79,14 +106,39 @@ macro_rules! builtin_feature {
     }
 }
 
+pub mod accessible_colors;
+pub mod canonical_json;
+pub mod change_density;
+pub mod ci;
+pub mod color_moved;
 pub mod color_only;
+pub mod color_scheme;
+pub mod comment_check;
+pub mod coverage;
+pub mod diagnostics;
 pub mod diff_highlight;
 pub mod diff_so_fancy;
+pub mod filter;
+pub mod function_separators;
+pub mod github;
+pub mod gutter;
+pub mod html_anchors;
+pub mod hunk_apply;
 pub mod hyperlinks;
+pub mod inline_blame;
+pub mod line_map;
 pub mod line_numbers;
+pub mod log_graph;
 pub mod navigate;
+pub mod no_style;
+pub mod parallel_highlighting;
+pub mod pull_request_titles;
 pub mod raw;
+pub mod review_bookmarks;
+pub mod secrets;
 pub mod side_by_side;
+pub mod split_hunks;
+pub mod terminal_resize;
 
 #[cfg(test)]
 pub mod tests {
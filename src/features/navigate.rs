@@ -4,6 +4,7 @@ use std::io::{Error, ErrorKind};
 use std::path::PathBuf;
 
 use crate::features::OptionValueFunction;
+use crate::handlers::hunk_header::HunkLabel;
 use crate::utils::bat::output::PagerCfg;
 
 pub fn make_feature() -> Vec<(String, OptionValueFunction)> {
@@ -36,7 +37,7 @@ pub fn make_navigate_regex(
     file_added_label: &str,
     file_removed_label: &str,
     file_renamed_label: &str,
-    hunk_label: &str,
+    hunk_label: &HunkLabel,
 ) -> String {
     if show_themes {
         "^Theme:".to_string()
@@ -54,11 +55,108 @@ pub fn make_navigate_regex(
             optional_regexp(file_removed_label),
             optional_regexp(file_renamed_label),
             optional_regexp(file_modified_label),
-            optional_regexp(hunk_label),
+            match hunk_label.to_regex() {
+                regexp if regexp.is_empty() => regexp,
+                regexp => format!("|{regexp}"),
+            },
         )
     }
 }
 
+/// A stop point of diff navigation to which a key of the navigate keymap jumps.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NavigateAction {
+    // The next or previous match of the navigate regex: a commit, file or hunk.
+    NextHunk,
+    PreviousHunk,
+    // The next or previous commit or file.
+    NextFile,
+    PreviousFile,
+}
+
+/// Parse the bindings of --navigate-keymap, e.g. "n=next-hunk }=next-file". A key is one or more
+/// characters typed in less, and the last '=' of a binding separates it from the action.
+pub fn parse_navigate_keymap(keymap: &str) -> Result<Vec<(String, NavigateAction)>, String> {
+    keymap
+        .split_whitespace()
+        .map(|binding| {
+            let (key, action) = binding
+                .rsplit_once('=')
+                .filter(|(key, _)| !key.is_empty())
+                .ok_or_else(|| format!("Invalid key binding '{binding}': expected KEY=ACTION."))?;
+            let action = match action {
+                "next-hunk" => NavigateAction::NextHunk,
+                "previous-hunk" => NavigateAction::PreviousHunk,
+                "next-file" => NavigateAction::NextFile,
+                "previous-file" => NavigateAction::PreviousFile,
+                _ => {
+                    return Err(format!(
+                        "Invalid action '{action}': expected next-hunk, previous-hunk, next-file \
+                         or previous-file."
+                    ))
+                }
+            };
+            Ok((key.to_string(), action))
+        })
+        .collect()
+}
+
+// The lesskey source binding the keys of the navigate keymap to searches for the navigate regex
+// and for the file regex. A bound key types a search command followed by the regex and Enter.
+fn make_lesskey_src(config: &PagerCfg) -> Option<String> {
+    let hunk_regex = config.navigate_regex.as_deref()?;
+    let file_regex = config.navigate_file_regex.as_deref().unwrap_or(hunk_regex);
+    let mut src = "#command\n".to_string();
+    for (key, action) in &config.navigate_keymap {
+        let (command, regex) = match action {
+            NavigateAction::NextHunk => ("forw-search", hunk_regex),
+            NavigateAction::PreviousHunk => ("back-search", hunk_regex),
+            NavigateAction::NextFile => ("forw-search", file_regex),
+            NavigateAction::PreviousFile => ("back-search", file_regex),
+        };
+        src.push_str(&format!(
+            "{} {command} {}\\n\n",
+            escape_lesskey(key),
+            escape_lesskey(regex)
+        ));
+    }
+    Some(src)
+}
+
+// Escape the characters which lesskey interprets: '\' introduces escape sequences, '^' control
+// characters, and '#' comments at the start of a line.
+fn escape_lesskey(s: &str) -> String {
+    s.replace('\\', "\\\\")
+        .replace('^', "\\^")
+        .replace('#', "\\#")
+}
+
+/// Write the lesskey source of the navigate keymap (--navigate-keymap) to a file in delta's data
+/// directory, to be passed to less with --lesskey-src.
+pub fn write_lesskey_src_file(config: &PagerCfg) -> std::io::Result<Option<PathBuf>> {
+    if !config.navigate || config.show_themes || config.navigate_keymap.is_empty() {
+        return Ok(None);
+    }
+    let Some(src) = make_lesskey_src(config) else {
+        return Ok(None);
+    };
+    let path = get_delta_data_file("lesskey")?;
+    std::fs::write(&path, src)?;
+    Ok(Some(path))
+}
+
+// The less initial command which sets the search pattern to the navigate regex, so that 'n' and
+// 'N' work immediately. The ^K search modifier keeps the current position instead of jumping to
+// the first match.
+pub fn less_prefill_search_arg(config: &PagerCfg) -> Option<String> {
+    match &config.navigate_regex {
+        Some(regex) if config.navigate && config.navigate_prefill_search => {
+            Some(format!("+/\x0b{regex}"))
+        }
+        _ => None,
+    }
+}
+
 // Create a less history file to be used by delta's child less process. This file is initialized
 // with the contents of user's real less hist file, to which the navigate regex is appended. This
 // has the effect that 'n' or 'N' in delta's less process will search for the navigate regex,
@@ -71,7 +169,11 @@ pub fn make_navigate_regex(
 pub fn copy_less_hist_file_and_append_navigate_regex(
     config: &PagerCfg,
 ) -> std::io::Result<PathBuf> {
-    let delta_less_hist_file = get_delta_less_hist_file()?;
+    let delta_less_hist_file = get_delta_data_file(if cfg!(windows) {
+        "delta.lesshst"
+    } else {
+        "lesshst"
+    })?;
     let initial_contents = ".less-history-file:\n".to_string();
     let mut contents = if let Some(hist_file) = get_less_hist_file() {
         std::fs::read_to_string(hist_file).unwrap_or(initial_contents)
@@ -91,19 +193,19 @@ pub fn copy_less_hist_file_and_append_navigate_regex(
 }
 
 #[cfg(target_os = "windows")]
-fn get_delta_less_hist_file() -> std::io::Result<PathBuf> {
+fn get_delta_data_file(name: &str) -> std::io::Result<PathBuf> {
     let mut path = dirs::data_local_dir()
         .ok_or_else(|| Error::new(ErrorKind::NotFound, "Can't find AppData\\Local folder"))?;
     path.push("delta");
     std::fs::create_dir_all(&path)?;
-    path.push("delta.lesshst");
+    path.push(name);
     Ok(path)
 }
 
 #[cfg(not(target_os = "windows"))]
-fn get_delta_less_hist_file() -> std::io::Result<PathBuf> {
+fn get_delta_data_file(name: &str) -> std::io::Result<PathBuf> {
     let dir = xdg::BaseDirectories::with_prefix("delta")?;
-    dir.place_data_file("lesshst")
+    dir.place_data_file(name)
 }
 
 // LESSHISTFILE
@@ -145,6 +247,7 @@ fn get_less_hist_file() -> Option<PathBuf> {
 mod tests {
     use std::fs::remove_file;
 
+    use super::*;
     use crate::tests::integration_test_utils;
 
     #[test]
@@ -261,4 +364,93 @@ mod tests {
 
         remove_file(git_config_path).unwrap();
     }
+
+    #[test]
+    fn test_navigate_prefill_search() {
+        let git_config_contents = b"
+[delta]
+    navigate = true
+    navigate-prefill-search = true
+";
+        let git_config_path = "delta__test_navigate_prefill_search.gitconfig";
+
+        let pager_cfg = |config: &crate::config::Config| -> PagerCfg { config.into() };
+
+        let config = integration_test_utils::make_config_from_args(&[]);
+        assert_eq!(less_prefill_search_arg(&pager_cfg(&config)), None);
+
+        // The search pattern is only set when requested.
+        let config = integration_test_utils::make_config_from_args(&["--navigate"]);
+        assert_eq!(less_prefill_search_arg(&pager_cfg(&config)), None);
+
+        let config = integration_test_utils::make_config_from_args(&[
+            "--navigate",
+            "--navigate-prefill-search",
+        ]);
+        assert_eq!(
+            less_prefill_search_arg(&pager_cfg(&config)),
+            Some(format!(
+                "+/\x0b{}",
+                "^(commit|added:|removed:|renamed:|Δ|•)"
+            ))
+        );
+
+        let config = integration_test_utils::make_config_from_args_and_git_config(
+            &[],
+            Some(git_config_contents),
+            Some(git_config_path),
+        );
+        assert!(config.navigate);
+        assert!(less_prefill_search_arg(&pager_cfg(&config)).is_some());
+
+        remove_file(git_config_path).unwrap();
+    }
+
+    #[test]
+    fn test_parse_navigate_keymap() {
+        assert_eq!(
+            parse_navigate_keymap("n=next-hunk  ]f=next-file ==previous-file"),
+            Ok(vec![
+                ("n".to_string(), NavigateAction::NextHunk),
+                ("]f".to_string(), NavigateAction::NextFile),
+                ("=".to_string(), NavigateAction::PreviousFile),
+            ])
+        );
+        assert_eq!(parse_navigate_keymap(""), Ok(vec![]));
+        assert!(parse_navigate_keymap("n").is_err());
+        assert!(parse_navigate_keymap("=next-hunk").is_err());
+        assert!(parse_navigate_keymap("n=next-commit").is_err());
+    }
+
+    #[test]
+    fn test_make_lesskey_src() {
+        let pager_cfg = |config: &crate::config::Config| -> PagerCfg { config.into() };
+
+        let config = integration_test_utils::make_config_from_args(&[]);
+        assert_eq!(write_lesskey_src_file(&pager_cfg(&config)).unwrap(), None);
+
+        let config = integration_test_utils::make_config_from_args(&["--navigate"]);
+        assert_eq!(
+            make_lesskey_src(&pager_cfg(&config)).unwrap(),
+            "#command\n\
+             n forw-search \\^(commit|added:|removed:|renamed:|Δ|•)\\n\n\
+             N back-search \\^(commit|added:|removed:|renamed:|Δ|•)\\n\n\
+             } forw-search \\^(commit|added:|removed:|renamed:|Δ)\\n\n\
+             { back-search \\^(commit|added:|removed:|renamed:|Δ)\\n\n"
+        );
+
+        let config = integration_test_utils::make_config_from_args(&[
+            "--navigate",
+            "--navigate-keymap",
+            "#=next-file",
+        ]);
+        assert_eq!(
+            make_lesskey_src(&pager_cfg(&config)).unwrap(),
+            "#command\n\\# forw-search \\^(commit|added:|removed:|renamed:|Δ)\\n\n"
+        );
+
+        let config =
+            integration_test_utils::make_config_from_args(&["--navigate", "--navigate-keymap", ""]);
+        assert_eq!(write_lesskey_src_file(&pager_cfg(&config)).unwrap(), None);
+    }
 }
@@ -0,0 +1,257 @@
+use std::io::BufRead;
+
+use lazy_static::lazy_static;
+use regex::Regex;
+
+use crate::ansi;
+use crate::delta::StateMachine;
+use crate::handlers::hunk_header;
+use crate::utils::warnings::{self, WarningKind};
+
+lazy_static! {
+    // An OSC 8 hyperlink escape sequence, opening or closing a hyperlink.
+    static ref OSC8_REGEX: Regex = Regex::new(r"\x1b]8;[^\x1b\x07]*(?:\x1b\\|\x07)").unwrap();
+}
+
+// The line numbers of the next removed and added lines of a hunk, and the numbers of those lines
+// which remain in the hunk.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct HunkLineNumbers {
+    minus: usize,
+    plus: usize,
+    minus_remaining: usize,
+    plus_remaining: usize,
+}
+
+impl HunkLineNumbers {
+    fn is_complete(&self) -> bool {
+        self.minus_remaining == 0 && self.plus_remaining == 0
+    }
+
+    // The prefix of a line of the hunk, e.g. "  12   13 │", if it is one.
+    fn prefix(&mut self, line: &str) -> Option<String> {
+        let (minus, plus) = match line.chars().next() {
+            Some(' ') => (Some(self.minus), Some(self.plus)),
+            Some('-') => (Some(self.minus), None),
+            Some('+') => (None, Some(self.plus)),
+            _ => return None,
+        };
+        if minus.is_some() {
+            self.minus += 1;
+            self.minus_remaining = self.minus_remaining.checked_sub(1)?;
+        }
+        if plus.is_some() {
+            self.plus += 1;
+            self.plus_remaining = self.plus_remaining.checked_sub(1)?;
+        }
+        let format = |n: Option<usize>| n.map(|n| n.to_string()).unwrap_or_default();
+        Some(format!("{:>4} {:>4} │", format(minus), format(plus)))
+    }
+}
+
+impl<'a> StateMachine<'a> {
+    /// Write the input unchanged, byte for byte, except for the hyperlinks and line numbers
+    /// requested (--no-style).
+    pub fn consume_without_style<I: BufRead>(&mut self, mut reader: I) -> std::io::Result<()> {
+        let mut hunk = None;
+        let mut bytes = Vec::new();
+        for line_number in 1.. {
+            bytes.clear();
+            if reader.read_until(b'\n', &mut bytes)? == 0 {
+                break;
+            }
+            let (content, ending) = split_line_ending(&bytes);
+            let line = std::str::from_utf8(content)
+                .ok()
+                .and_then(|text| self.transform_line_without_style(text, &mut hunk));
+            match line {
+                Some(line) if self.config.no_style_verify && !is_reversible(&line, content) => {
+                    warnings::warn(
+                        WarningKind::Output,
+                        format!(
+                            "Line {line_number} of the input cannot be recovered from its \
+                             output with --no-style; it is written unchanged."
+                        ),
+                    );
+                    self.painter.writer.write_all(&bytes)?;
+                }
+                Some(line) => {
+                    self.painter.writer.write_all(line.as_bytes())?;
+                    self.painter.writer.write_all(ending)?;
+                }
+                None => self.painter.writer.write_all(&bytes)?,
+            }
+            warnings::check_error()?;
+        }
+        self.painter.writer.flush()
+    }
+
+    // The line with the hyperlinks and line numbers requested, if it has any.
+    fn transform_line_without_style(
+        &mut self,
+        text: &str,
+        hunk: &mut Option<HunkLineNumbers>,
+    ) -> Option<String> {
+        self.raw_line = text.to_string();
+        self.line = ansi::strip_ansi_codes(text);
+        let hyperlinked = if self.config.hyperlinks {
+            self.hyperlink_raw_line()
+        } else {
+            None
+        };
+        let prefix = if self.config.line_numbers {
+            self.hunk_line_prefix(hunk)
+        } else {
+            None
+        };
+        match (prefix, hyperlinked) {
+            (None, None) => None,
+            (prefix, hyperlinked) => Some(format!(
+                "{}{}",
+                prefix.unwrap_or_default(),
+                hyperlinked.as_deref().unwrap_or(text)
+            )),
+        }
+    }
+
+    // The line numbers prefixed to the line, if it is a line of a hunk.
+    fn hunk_line_prefix(&self, hunk: &mut Option<HunkLineNumbers>) -> Option<String> {
+        if let Some(numbers) = hunk {
+            if let Some(prefix) = numbers.prefix(&self.line) {
+                if numbers.is_complete() {
+                    *hunk = None;
+                }
+                return Some(prefix);
+            }
+            if self.line.starts_with('\\') {
+                // "\ No newline at end of file"
+                return None;
+            }
+        }
+        *hunk = None;
+        if self.line.starts_with("@@ ") {
+            if let [(minus, minus_length), (plus, plus_length)] =
+                hunk_header::parse_hunk_header(&self.line)?.line_numbers_and_hunk_lengths()
+            {
+                *hunk = Some(HunkLineNumbers {
+                    minus: *minus,
+                    plus: *plus,
+                    minus_remaining: *minus_length,
+                    plus_remaining: *plus_length,
+                })
+                .filter(|numbers| !numbers.is_complete());
+            }
+        }
+        None
+    }
+}
+
+// The line without its line ending, and the line ending: "\n", "\r\n" or none.
+fn split_line_ending(bytes: &[u8]) -> (&[u8], &[u8]) {
+    let n = match bytes {
+        [.., b'\r', b'\n'] => 2,
+        [.., b'\n'] => 1,
+        _ => 0,
+    };
+    bytes.split_at(bytes.len() - n)
+}
+
+// Whether the line of input is recovered from the line of output by removing the hyperlinks and
+// line numbers (--no-style-verify).
+fn is_reversible(output: &str, input: &[u8]) -> bool {
+    let output = output
+        .split_once(" │")
+        .filter(|(prefix, _)| prefix.chars().all(|c| c.is_ascii_digit() || c == ' '))
+        .map_or(output, |(_, line)| line);
+    OSC8_REGEX.replace_all(output, "").as_bytes() == input
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::integration_test_utils::make_config_from_args;
+
+    fn consume_without_style(args: &[&str], input: &str) -> String {
+        let config = make_config_from_args(&[&["--no-style"], args].concat());
+        let mut output = Vec::new();
+        StateMachine::new(&mut output, &config)
+            .consume_without_style(input.as_bytes())
+            .unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    const DIFF: &str = "\
+commit 7dc2b2d3a2c0ad7bbd1bfa6ee1b74e0e6d2a7a1c\r
+diff --git a/src/a.rs b/src/a.rs
+--- a/src/a.rs
++++ b/src/a.rs
+@@ -9,3 +9,3 @@ fn main() {
+ \x1b[31m    let a = 1;\x1b[m
+-    let b = 2;
++    let b = 3;
+\\ No newline at end of file
+@@ -99 +99 @@
+-x
++y";
+
+    #[test]
+    fn test_no_style_is_byte_identical() {
+        assert_eq!(consume_without_style(&[], DIFF), DIFF);
+    }
+
+    #[test]
+    fn test_no_style_line_numbers() {
+        let output = consume_without_style(&["--line-numbers"], DIFF);
+        assert_eq!(
+            output,
+            "\
+commit 7dc2b2d3a2c0ad7bbd1bfa6ee1b74e0e6d2a7a1c\r
+diff --git a/src/a.rs b/src/a.rs
+--- a/src/a.rs
++++ b/src/a.rs
+@@ -9,3 +9,3 @@ fn main() {
+   9    9 │ \x1b[31m    let a = 1;\x1b[m
+  10      │-    let b = 2;
+       10 │+    let b = 3;
+\\ No newline at end of file
+@@ -99 +99 @@
+  99      │-x
+       99 │+y"
+        );
+        for (output, input) in output.lines().zip(DIFF.lines()) {
+            assert!(is_reversible(output, input.as_bytes()), "{}", output);
+        }
+    }
+
+    #[test]
+    fn test_no_style_hyperlinks() {
+        let output = consume_without_style(
+            &[
+                "--hyperlinks",
+                "--hyperlinks-commit-link-format",
+                "https://example.com/{commit}",
+            ],
+            DIFF,
+        );
+        assert!(output.starts_with(
+            "commit \x1b]8;;https://example.com/7dc2b2d3a2c0ad7bbd1bfa6ee1b74e0e6d2a7a1c\x1b\\"
+        ));
+        assert!(output.contains("\r\ndiff --git"));
+        assert_eq!(OSC8_REGEX.replace_all(&output, ""), DIFF);
+    }
+
+    #[test]
+    fn test_is_reversible() {
+        assert!(is_reversible("  12      │-x", b"-x"));
+        assert!(is_reversible(
+            "\x1b]8;;file:///a\x1b\\a\x1b]8;;\x1b\\",
+            b"a"
+        ));
+        assert!(!is_reversible("  12      │-x", b"-y"));
+        // Hyperlinks of the input are not recovered.
+        assert!(!is_reversible(
+            "\x1b]8;;file:///a\x1b\\a\x1b]8;;\x1b\\",
+            b"\x1b]8;;x\x1b\\a"
+        ));
+    }
+}
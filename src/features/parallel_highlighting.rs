@@ -0,0 +1,526 @@
+use std::collections::{HashMap, VecDeque};
+use std::io::BufRead;
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+
+use bytelines::ByteLines;
+use syntect::easy::HighlightLines;
+use syntect::highlighting::{Style as SyntectStyle, Theme};
+use syntect::parsing::{SyntaxReference, SyntaxSet};
+
+use crate::ansi;
+use crate::config::{Config, StripCr};
+use crate::delta::{DiffType, State, StateMachine};
+use crate::edits;
+use crate::handlers::diff_header::get_filename_from_diff_header_line_file_path;
+use crate::handlers::hunk_header::parse_unified_hunk_lengths;
+use crate::paint::{self, Painter};
+
+// The number of lines read ahead of the current line, whose hunks are highlighted in parallel,
+// unless --max-buffer-lines is lower.
+const READ_AHEAD_LINES: usize = 4096;
+
+/// The greatest number of threads highlighting hunks (--threads).
+pub const MAX_THREADS: usize = 64;
+
+// The style and length of each section of a highlighted line.
+type Sections = Vec<(SyntectStyle, usize)>;
+
+/// The syntax highlighter of a hunk. As with syntect's `HighlightLines`, the highlighting of a
+/// line depends on the lines highlighted before it. The lines of the hunk may have been
+/// highlighted in advance by a `HighlightPool` (--threads).
+pub struct Highlighter<'a> {
+    highlighter: HighlightLines<'a>,
+    // The lines highlighted in advance which are expected to be highlighted next, with their
+    // sections.
+    highlighted: VecDeque<(String, Sections)>,
+    // The lines highlighted in advance which have been highlighted, and have not been passed to
+    // `highlighter`.
+    skipped: Vec<String>,
+}
+
+impl<'a> Highlighter<'a> {
+    pub fn new(syntax: &'a SyntaxReference, theme: &'a Theme) -> Self {
+        Self {
+            highlighter: HighlightLines::new(syntax, theme),
+            highlighted: VecDeque::new(),
+            skipped: Vec::new(),
+        }
+    }
+
+    pub fn highlight_line<'b>(
+        &mut self,
+        line: &'b str,
+        syntax_set: &SyntaxSet,
+    ) -> Result<Vec<(SyntectStyle, &'b str)>, syntect::Error> {
+        match self.highlighted.pop_front() {
+            Some((highlighted_line, sections)) if highlighted_line == line => {
+                self.skipped.push(highlighted_line);
+                let mut offset = 0;
+                Ok(sections
+                    .into_iter()
+                    .map(|(style, length)| {
+                        offset += length;
+                        (style, &line[offset - length..offset])
+                    })
+                    .collect())
+            }
+            _ => {
+                // The lines differ from those highlighted in advance, e.g. because a line of the
+                // hunk was not read ahead: bring the state of the highlighter up to date.
+                self.highlighted.clear();
+                for skipped in std::mem::take(&mut self.skipped) {
+                    self.highlighter.highlight_line(&skipped, syntax_set)?;
+                }
+                self.highlighter.highlight_line(line, syntax_set)
+            }
+        }
+    }
+}
+
+// The lines of a hunk to highlight, as they will be passed to the highlighter of the hunk. The
+// syntax is its index in the syntax set.
+struct Hunk {
+    header: String,
+    syntax: usize,
+    lines: Vec<String>,
+}
+
+// The lines of a hunk, with their sections in the theme of the pool.
+struct HighlightedHunk {
+    syntax: usize,
+    lines: Vec<(String, Sections)>,
+}
+
+/// Threads highlighting the hunks of the lines read ahead of the current line (--threads).
+pub struct HighlightPool {
+    hunks: mpsc::Sender<(usize, Hunk)>,
+    highlighted_hunks: mpsc::Receiver<(usize, HighlightedHunk)>,
+    // The theme of the root config, with which the threads highlight hunks: that of a language
+    // config (see get_language_config) or of another color scheme may differ.
+    theme: Theme,
+    // The id and header of the hunks sent to the threads and not yet used, in order.
+    sent: VecDeque<(usize, String)>,
+    // The highlighted hunks received before they are used.
+    received: HashMap<usize, HighlightedHunk>,
+    scanner: HunkScanner,
+}
+
+impl HighlightPool {
+    /// Start the threads in the scope, unless --threads is 1 or syntax highlighting is disabled, or
+    /// no thread can be started.
+    pub fn start<'scope, 'env>(
+        scope: &'scope thread::Scope<'scope, 'env>,
+        config: &'env Config,
+    ) -> Option<Self> {
+        let theme = config
+            .syntax_theme
+            .as_ref()
+            .filter(|_| config.threads > 1)?;
+        let syntax_set = &config.syntax_set;
+        let (hunks, hunks_receiver) = mpsc::channel::<(usize, Hunk)>();
+        let (highlighted_hunks_sender, highlighted_hunks) = mpsc::channel();
+        let hunks_receiver = Arc::new(Mutex::new(hunks_receiver));
+        let mut n_started = 0;
+        for _ in 0..config.threads {
+            let hunks_receiver = Arc::clone(&hunks_receiver);
+            let highlighted_hunks_sender = highlighted_hunks_sender.clone();
+            let started = thread::Builder::new().spawn_scoped(scope, move || loop {
+                let received = hunks_receiver.lock().unwrap().recv();
+                let Ok((id, hunk)) = received else {
+                    break;
+                };
+                let syntax = &syntax_set.syntaxes()[hunk.syntax];
+                let mut highlighter = HighlightLines::new(syntax, theme);
+                // A line which cannot be highlighted ends the highlighted lines.
+                let lines = hunk
+                    .lines
+                    .into_iter()
+                    .map_while(|line| {
+                        let sections = highlighter.highlight_line(&line, syntax_set).ok()?;
+                        let sections = sections
+                            .into_iter()
+                            .map(|(style, text)| (style, text.len()))
+                            .collect();
+                        Some((line, sections))
+                    })
+                    .collect();
+                let highlighted_hunk = HighlightedHunk {
+                    syntax: hunk.syntax,
+                    lines,
+                };
+                if highlighted_hunks_sender
+                    .send((id, highlighted_hunk))
+                    .is_err()
+                {
+                    break;
+                }
+            });
+            // E.g. the stack of another thread cannot be allocated.
+            if started.is_err() {
+                break;
+            }
+            n_started += 1;
+        }
+        if n_started == 0 {
+            return None;
+        }
+        Some(Self {
+            hunks,
+            highlighted_hunks,
+            theme: theme.clone(),
+            sent: VecDeque::new(),
+            received: HashMap::new(),
+            scanner: HunkScanner {
+                syntax: syntax_index(
+                    Painter::get_syntax(syntax_set, None, &config.default_language),
+                    syntax_set,
+                ),
+                hunk: None,
+                next_id: 0,
+            },
+        })
+    }
+
+    // Send the hunk to the threads.
+    fn send(&mut self, (id, hunk): (usize, Hunk)) {
+        self.sent.push_back((id, hunk.header.clone()));
+        // The threads only stop when the pool is dropped.
+        let _ = self.hunks.send((id, hunk));
+    }
+
+    // The highlighted lines of the next hunk with the header and syntax, if it has been read
+    // ahead, and highlighted in the theme.
+    fn take(
+        &mut self,
+        header: &str,
+        syntax: usize,
+        theme: &Theme,
+    ) -> Option<Vec<(String, Sections)>> {
+        let index = self.sent.iter().position(|(_, sent)| sent == header)?;
+        // The hunks before it have not been highlighted as expected.
+        let (id, _) = self.sent.drain(..=index).next_back()?;
+        self.received.retain(|&received_id, _| received_id >= id);
+        let highlighted_hunk = loop {
+            match self.received.remove(&id) {
+                Some(highlighted_hunk) => break highlighted_hunk,
+                None => {
+                    let (received_id, highlighted_hunk) = self.highlighted_hunks.recv().ok()?;
+                    if received_id >= id {
+                        self.received.insert(received_id, highlighted_hunk);
+                    }
+                }
+            }
+        };
+        (highlighted_hunk.syntax == syntax && *theme == self.theme)
+            .then_some(highlighted_hunk.lines)
+    }
+}
+
+// Finds the hunks of unified diffs in the lines read ahead.
+struct HunkScanner {
+    // The syntax of the current file.
+    syntax: usize,
+    hunk: Option<ScannedHunk>,
+    next_id: usize,
+}
+
+// The hunk being scanned.
+struct ScannedHunk {
+    // The lines of the hunk, unless its header has been displayed before it was read to its end: a
+    // hunk longer than the lines read ahead is highlighted as it is displayed, and only then.
+    hunk: Option<Hunk>,
+    // The numbers of its minus and plus lines not yet read.
+    remaining: (usize, usize),
+    // The number of its lines read after its header.
+    n_read: usize,
+}
+
+impl HunkScanner {
+    // Scan a line, given the number of lines read ahead before it which the state machine has not
+    // yet read, returning the hunk it ends, if any.
+    fn scan_line(
+        &mut self,
+        line: &str,
+        n_read_ahead: usize,
+        config: &Config,
+    ) -> Option<(usize, Hunk)> {
+        let mut ended_hunk = None;
+        if let Some(ScannedHunk {
+            hunk,
+            remaining,
+            n_read,
+        }) = &mut self.hunk
+        {
+            if *n_read >= n_read_ahead {
+                // The state machine has read the header of the hunk.
+                *hunk = None;
+            }
+            let (in_hunk, state) = match line.chars().next() {
+                Some('-') if remaining.0 > 0 => {
+                    remaining.0 -= 1;
+                    (true, Some(State::HunkMinus(DiffType::Unified, None)))
+                }
+                Some('+') if remaining.1 > 0 => {
+                    remaining.1 -= 1;
+                    (true, Some(State::HunkPlus(DiffType::Unified, None)))
+                }
+                Some(' ') if remaining.0 > 0 && remaining.1 > 0 => {
+                    remaining.0 -= 1;
+                    remaining.1 -= 1;
+                    (true, Some(State::HunkZero(DiffType::Unified, None)))
+                }
+                // E.g. "\ No newline at end of file"
+                Some('\\') => (true, None),
+                _ => (false, None),
+            };
+            if in_hunk {
+                *n_read += 1;
+                let is_highlighted = state.is_some_and(|state| {
+                    Painter::should_compute_syntax_highlighting(&state, config)
+                });
+                if let Some(hunk) = hunk.as_mut().filter(|_| is_highlighted) {
+                    if let Some(line) = line_to_highlight(paint::prepare(line, 1, config), config) {
+                        hunk.lines.push(line);
+                    }
+                }
+                if *remaining == (0, 0) {
+                    return self.end_hunk(n_read_ahead + 1);
+                }
+                return None;
+            }
+            ended_hunk = self.end_hunk(n_read_ahead);
+        }
+        if let Some(path) = line.strip_prefix("+++ ") {
+            let path = path.split('\t').next().unwrap_or(path);
+            let syntax = Painter::get_syntax(
+                &config.syntax_set,
+                get_filename_from_diff_header_line_file_path(path),
+                &config.default_language,
+            );
+            self.syntax = syntax_index(syntax, &config.syntax_set);
+        } else if let Some(lengths) = line
+            .strip_prefix("@@ ")
+            .and_then(|_| parse_unified_hunk_lengths(line))
+        {
+            let hunk = Hunk {
+                header: line.to_string(),
+                syntax: self.syntax,
+                lines: Vec::new(),
+            };
+            self.hunk = Some(ScannedHunk {
+                hunk: Some(hunk),
+                remaining: lengths,
+                n_read: 0,
+            });
+        }
+        ended_hunk
+    }
+
+    // End the current hunk, returning it unless the state machine has read its header, given the
+    // number of lines read ahead which it has not yet read.
+    fn end_hunk(&mut self, n_read_ahead: usize) -> Option<(usize, Hunk)> {
+        let scanned = self.hunk.take()?;
+        if scanned.n_read >= n_read_ahead {
+            return None;
+        }
+        let hunk = scanned.hunk?;
+        self.next_id += 1;
+        Some((self.next_id, hunk))
+    }
+}
+
+// The index of the syntax in the syntax set.
+fn syntax_index(syntax: &SyntaxReference, syntax_set: &SyntaxSet) -> usize {
+    syntax_set
+        .syntaxes()
+        .iter()
+        .position(|candidate| std::ptr::eq(candidate, syntax))
+        .unwrap_or(usize::MAX)
+}
+
+// The line as it is passed to the highlighter by `paint::get_syntax_style_sections_for_lines`.
+fn line_to_highlight(line: String, config: &Config) -> Option<String> {
+    if edits::is_longer_than(&line, config.max_highlight_length) {
+        None
+    } else if line.len() < config.max_syntax_length || config.max_syntax_length == 0 {
+        Some(line)
+    } else {
+        Some(ansi::truncate_str_short(&line, config.max_syntax_length).to_string())
+    }
+}
+
+impl<'a> StateMachine<'a> {
+    /// Read lines ahead of the current line, and send the hunks in them to the threads of the
+    /// highlight pool (--threads).
+    pub fn read_ahead_for_highlighting<I: BufRead>(&mut self, lines: &mut ByteLines<I>) {
+        let Some(pool) = &mut self.highlight_pool else {
+            return;
+        };
+        let max_lines = match self.config.max_buffer_lines {
+            0 => READ_AHEAD_LINES,
+            max_lines => max_lines.min(READ_AHEAD_LINES),
+        };
+        // Lines are read in batches.
+        if self.hunk_lookahead.len() >= max_lines.div_ceil(2) {
+            return;
+        }
+        while self.hunk_lookahead.len() < max_lines {
+            let Some(Ok(raw_line)) = lines.next() else {
+                if let Some(hunk) = pool.scanner.end_hunk(self.hunk_lookahead.len()) {
+                    pool.send(hunk);
+                }
+                break;
+            };
+            let mut line = ansi::strip_ansi_codes(&String::from_utf8_lossy(raw_line));
+            // As in `StateMachine::ingest_line_utf8`.
+            if self.config.strip_cr != StripCr::Never && line.ends_with('\r') {
+                line.pop();
+            }
+            let n_read_ahead = self.hunk_lookahead.len();
+            if let Some(hunk) = pool.scanner.scan_line(&line, n_read_ahead, self.config) {
+                pool.send(hunk);
+            }
+            self.hunk_lookahead.push_back(raw_line.to_vec());
+        }
+    }
+
+    /// Use the lines of the current hunk highlighted by the highlight pool, if it has read them.
+    pub fn use_highlighted_hunk(&mut self, header: &str) {
+        if let (Some(pool), Some(highlighter), Some(theme)) = (
+            &mut self.highlight_pool,
+            &mut self.painter.highlighter,
+            &self.config.syntax_theme,
+        ) {
+            let syntax = syntax_index(self.painter.syntax, &self.config.syntax_set);
+            if let Some(lines) = pool.take(header, syntax, theme) {
+                highlighter.highlighted = lines.into();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytelines::ByteLinesReader;
+
+    use super::*;
+    use crate::tests::integration_test_utils::{self, DeltaTest};
+
+    #[test]
+    fn test_parallel_highlighting() {
+        let mut input = String::new();
+        for i in 0..200 {
+            input.push_str(&format!(
+                "diff --git a/f{i}.rs b/f{i}.rs\n\
+                 index 1111111..2222222 100644\n\
+                 --- a/f{i}.rs\n\
+                 +++ b/f{i}.rs\n\
+                 @@ -1,4 +1,4 @@\n \
+                 /* a comment\n\
+                 -   continued {i} */ let a = 1;\n\
+                 +   continued */ let b = \"{i}\";\n \
+                 fn f() {{}}\n\
+                 \\ No newline at end of file\n"
+            ));
+        }
+        // A hunk longer than its header says, and one with lines missing.
+        input.push_str(&input.replacen("@@ -1,4 +1,4 @@", "@@ -1,3 +1,3 @@", 1));
+        input.push_str(&input.replacen("@@ -1,4 +1,4 @@", "@@ -1,5 +1,5 @@", 1));
+        let args = ["--syntax-theme", "Monokai Extended", "--line-numbers"];
+        let expected = DeltaTest::with_args(&args).with_input(&input).raw_output;
+        for threads in ["2", "4"] {
+            let config = integration_test_utils::make_config_from_args(
+                &[&args[..], &["--threads", threads]].concat(),
+            );
+            assert_eq!(config.threads, threads.parse::<usize>().unwrap());
+            let output = DeltaTest::with_config(&config)
+                .with_input(&input)
+                .raw_output;
+            assert!(output == expected);
+        }
+    }
+
+    #[test]
+    fn test_parallel_highlighting_of_hunk_longer_than_read_ahead() {
+        let mut input = "\
+diff --git a/f.rs b/f.rs
+index 1111111..2222222 100644
+--- a/f.rs
++++ b/f.rs
+@@ -1,101 +1,101 @@
+-let a = 0;
++let a = 1;
+"
+        .to_string();
+        input.push_str(&" let b = 1;\n".repeat(100));
+        input.push_str("@@ -200 +200 @@\n-let c = 0;\n+let c = 1;\n");
+        let args = ["--max-buffer-lines", "20"];
+        let expected = DeltaTest::with_args(&args).with_input(&input).raw_output;
+        let config = integration_test_utils::make_config_from_args(
+            &[&args[..], &["--threads", "2"]].concat(),
+        );
+        let mut output = Vec::new();
+        thread::scope(|scope| {
+            let mut state_machine = StateMachine::new(&mut output, &config);
+            state_machine.highlight_pool = HighlightPool::start(scope, &config);
+            state_machine
+                .consume(input.as_bytes().byte_lines())
+                .unwrap();
+            // Only the second hunk is highlighted in advance: the first is longer than the lines
+            // read ahead.
+            let pool = state_machine.highlight_pool.as_ref().unwrap();
+            assert_eq!(pool.scanner.next_id, 1);
+        });
+        assert!(String::from_utf8(output).unwrap() == expected);
+    }
+
+    #[test]
+    fn test_parallel_highlighting_with_language_theme() {
+        // Options of the command line override those of language sections.
+        let git_config_contents = b"
+[delta]
+    syntax-theme = Monokai Extended
+[delta \"lang:rs\"]
+    syntax-theme = GitHub
+";
+        let git_config_path = "delta__test_parallel_highlighting_with_language_theme.gitconfig";
+        let input = "\
+diff --git a/f.py b/f.py
+index 1111111..2222222 100644
+--- a/f.py
++++ b/f.py
+@@ -1 +1 @@
+-a = 0
++a = \"1\"
+diff --git a/f.rs b/f.rs
+index 1111111..2222222 100644
+--- a/f.rs
++++ b/f.rs
+@@ -1 +1 @@
+-let a = 0;
++let a = \"1\";
+";
+        let make_config = |args: &[&str]| {
+            integration_test_utils::make_config_from_args_and_git_config(
+                args,
+                Some(git_config_contents),
+                Some(git_config_path),
+            )
+        };
+        let expected = DeltaTest::with_config(&make_config(&[]))
+            .with_input(input)
+            .raw_output;
+        let output = DeltaTest::with_config(&make_config(&["--threads", "2"]))
+            .with_input(input)
+            .raw_output;
+        std::fs::remove_file(git_config_path).unwrap();
+        assert!(output == expected);
+    }
+
+    #[test]
+    fn test_threads_are_capped() {
+        let config = integration_test_utils::make_config_from_args(&["--threads", "100000"]);
+        assert_eq!(config.threads, MAX_THREADS);
+    }
+}
@@ -0,0 +1,140 @@
+use std::borrow::Cow;
+use std::process::{Command, Stdio};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use lazy_static::lazy_static;
+use regex::{Captures, Regex};
+
+use crate::config::Config;
+use crate::git_config::{GitConfig, GitRemoteRepo};
+use crate::utils::cache::{self, Cache};
+
+lazy_static! {
+    // E.g. "#123" in "Fix the parser (#123)", but not in "a#123" or "#123a".
+    static ref REFERENCE_REGEX: Regex = Regex::new(r"(^|[\s(\[])#([0-9]+)\b").unwrap();
+    static ref FETCH_BUDGET: Mutex<FetchBudget> = Mutex::new(FetchBudget::default());
+}
+
+// The time which all the fetches of a run of delta may take together, and each of them.
+const FETCH_TIME_BUDGET: Duration = Duration::from_secs(10);
+const FETCH_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Follow the references to issues and pull requests in a line of a commit message with their
+/// titles (--pull-request-titles), e.g. "Fix the parser (#123: Parser fails on tabs)".
+pub fn format_pull_request_titles<'a>(text: &'a str, config: &Config) -> Cow<'a, str> {
+    let Some(repo) = config.git_config().and_then(GitConfig::get_remote_url) else {
+        return Cow::from(text);
+    };
+    let cache = Cache::new(cache::cache_dir(), cache::TIME_TO_LIVE, config.offline);
+    format_titles(text, config, |number| title(&repo, number, &cache))
+}
+
+fn format_titles<'a>(
+    text: &'a str,
+    config: &Config,
+    mut title: impl FnMut(u64) -> Option<String>,
+) -> Cow<'a, str> {
+    REFERENCE_REGEX.replace_all(text, |captures: &Captures| {
+        let reference = &captures[0];
+        match captures[2].parse().ok().and_then(&mut title) {
+            Some(title) => format!(
+                "{reference}{}",
+                config.pull_request_title_style.paint(format!(": {title}"))
+            ),
+            None => reference.to_string(),
+        }
+    })
+}
+
+// The title of the issue or pull request, fetched from the API of the forge unless it is cached.
+fn title(repo: &GitRemoteRepo, number: u64, cache: &Cache) -> Option<String> {
+    let url = repo.format_issue_api_url(number)?;
+    cache.get_or_fetch(url.trim_start_matches("https://"), || fetch_title(&url))
+}
+
+// Fetch the title with curl, unless the time budget of the fetches is spent or a fetch has
+// failed, e.g. because the network is unavailable or curl is not installed, so that the output is
+// not held up by one slow fetch after another.
+fn fetch_title(url: &str) -> Option<String> {
+    let max_time = lock_fetch_budget().max_time(Instant::now())?;
+    let output = Command::new("curl")
+        .args(["--fail", "--silent", "--location", "--max-time"])
+        .arg(format!("{:.1}", max_time.as_secs_f64()))
+        .arg(url)
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .ok()
+        .filter(|output| output.status.success());
+    let Some(output) = output else {
+        lock_fetch_budget().failed = true;
+        return None;
+    };
+    let response: serde_json::Value = serde_json::from_slice(&output.stdout).ok()?;
+    let title = response.get("title")?.as_str()?.trim();
+    (!title.is_empty()).then(|| title.to_string())
+}
+
+fn lock_fetch_budget() -> std::sync::MutexGuard<'static, FetchBudget> {
+    FETCH_BUDGET
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+}
+
+// The time left for fetching, from the first fetch of the run, and whether a fetch has failed.
+#[derive(Debug, Default)]
+struct FetchBudget {
+    deadline: Option<Instant>,
+    failed: bool,
+}
+
+impl FetchBudget {
+    // The time which a fetch starting now may take, or None if nothing more is fetched.
+    fn max_time(&mut self, now: Instant) -> Option<Duration> {
+        if self.failed {
+            return None;
+        }
+        let deadline = *self.deadline.get_or_insert(now + FETCH_TIME_BUDGET);
+        let remaining = deadline.saturating_duration_since(now);
+        // curl does not take a time below a tenth of a second.
+        (remaining >= Duration::from_millis(100)).then(|| remaining.min(FETCH_TIMEOUT))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::integration_test_utils::make_config_from_args;
+
+    #[test]
+    fn test_format_titles() {
+        let config = make_config_from_args(&["--pull-request-title-style", "raw"]);
+        let title = |number| (number == 123).then(|| "Parser fails on tabs".to_string());
+        assert_eq!(
+            format_titles("Fix the parser (#123)", &config, title),
+            "Fix the parser (#123: Parser fails on tabs)"
+        );
+        assert_eq!(
+            format_titles("#123, #45 and a#123", &config, title),
+            "#123: Parser fails on tabs, #45 and a#123"
+        );
+    }
+
+    #[test]
+    fn test_fetch_budget() {
+        let start = Instant::now();
+        let mut budget = FetchBudget::default();
+        assert_eq!(budget.max_time(start), Some(FETCH_TIMEOUT));
+        let later = start + FETCH_TIME_BUDGET - Duration::from_secs(2);
+        assert_eq!(budget.max_time(later), Some(Duration::from_secs(2)));
+        assert_eq!(budget.max_time(start + FETCH_TIME_BUDGET), None);
+
+        // Nothing is fetched after a failure.
+        let mut budget = FetchBudget {
+            deadline: None,
+            failed: true,
+        };
+        assert_eq!(budget.max_time(start), None);
+    }
+}
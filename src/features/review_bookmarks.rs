@@ -0,0 +1,153 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::style::Style;
+
+/// The files marked as reviewed with --mark-reviewed, kept in .git/delta/reviewed. Each line of
+/// the file records one: the id of its blob when it was marked, the commit checked out then, and
+/// its path in the repository, separated by tabs.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ReviewBookmarks {
+    files: HashMap<String, Bookmark>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Bookmark {
+    blob: String,
+    commit: String,
+}
+
+/// The path of the file keeping the review bookmarks of the repository.
+pub fn state_file(repo: &git2::Repository) -> PathBuf {
+    repo.path().join("delta").join("reviewed")
+}
+
+impl ReviewBookmarks {
+    /// Read the bookmarks from the file, if it exists.
+    pub fn read(path: &Path) -> Self {
+        fs::read_to_string(path)
+            .map(|text| Self::parse(&text))
+            .unwrap_or_default()
+    }
+
+    fn parse(text: &str) -> Self {
+        let files = text
+            .lines()
+            .filter_map(|line| {
+                let mut fields = line.splitn(3, '\t');
+                let (blob, commit, path) = (fields.next()?, fields.next()?, fields.next()?);
+                let bookmark = Bookmark {
+                    blob: blob.to_string(),
+                    commit: commit.to_string(),
+                };
+                Some((path.to_string(), bookmark))
+            })
+            .collect();
+        Self { files }
+    }
+
+    pub fn write(&self, path: &Path) -> io::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let mut lines: Vec<String> = self
+            .files
+            .iter()
+            .map(|(path, bookmark)| format!("{}\t{}\t{}\n", bookmark.blob, bookmark.commit, path))
+            .collect();
+        lines.sort_unstable();
+        fs::write(path, lines.concat())
+    }
+
+    pub fn mark(&mut self, path: &str, blob: &str, commit: &str) {
+        let bookmark = Bookmark {
+            blob: blob.to_string(),
+            commit: commit.to_string(),
+        };
+        self.files.insert(path.to_string(), bookmark);
+    }
+
+    /// The commit at which the file was marked as reviewed, if it has not changed since: if its
+    /// blob in the diff, which git abbreviates, is the one recorded.
+    pub fn reviewed_at(&self, path: &str, blob: &str) -> Option<&str> {
+        let bookmark = self.files.get(path)?;
+        (!blob.is_empty() && bookmark.blob.starts_with(blob)).then_some(bookmark.commit.as_str())
+    }
+}
+
+/// The file header of a file which is reviewed, e.g. "src/lib.rs ✓ reviewed at 1a2b3c4", and its
+/// style: the file style, dimmed.
+pub fn format_reviewed_file_header(line: &str, commit: &str, file_style: Style) -> (String, Style) {
+    let mut style = file_style;
+    style.ansi_term_style.is_dimmed = true;
+    let commit = commit.get(..7).unwrap_or(commit);
+    (format!("{line} ✓ reviewed at {commit}"), style)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::integration_test_utils::{self, DeltaTest};
+    use crate::tests::test_utils::temp_path;
+
+    #[test]
+    fn test_parse_and_write() {
+        let mut bookmarks = ReviewBookmarks::parse(
+            "e69de29bb2d1d6434b8b29ae775ad8c2e48c5391\t1a2b3c4d5e6f\tsrc/a b.rs\nbad line\n",
+        );
+        assert_eq!(
+            bookmarks.reviewed_at("src/a b.rs", "e69de29"),
+            Some("1a2b3c4d5e6f")
+        );
+        assert_eq!(bookmarks.reviewed_at("src/a b.rs", "587be6b"), None);
+        assert_eq!(bookmarks.reviewed_at("src/a b.rs", ""), None);
+        assert_eq!(bookmarks.reviewed_at("src/c.rs", "e69de29"), None);
+
+        bookmarks.mark("src/c.rs", "587be6b0c1", "9f8e7d6c5b4a");
+        let path = temp_path("test_review_bookmarks").join("reviewed");
+        bookmarks.write(&path).unwrap();
+        assert_eq!(ReviewBookmarks::read(&path), bookmarks);
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_reviewed_file_header() {
+        let mut config = integration_test_utils::make_config_from_args(&[
+            "--file-style",
+            "blue",
+            "--file-decoration-style",
+            "none",
+        ]);
+        config.review_bookmarks.mark(
+            "src/a.rs",
+            "e69de29bb2d1d6434b8b29ae775ad8c2e48c5391",
+            "1a2b3c4d5e6f",
+        );
+        let output = DeltaTest::with_config(&config)
+            .explain_ansi()
+            .with_input(REVIEWED_DIFF);
+        assert!(output
+            .output
+            .contains("(dim blue)src/a.rs ✓ reviewed at 1a2b3c4(normal)"));
+        assert!(output.output.contains("(blue)src/b.rs(normal)"));
+    }
+
+    const REVIEWED_DIFF: &str = "\
+diff --git a/src/a.rs b/src/a.rs
+index 587be6b..e69de29 100644
+--- a/src/a.rs
++++ b/src/a.rs
+@@ -1 +1 @@
+-fn a() {}
++fn a() -> u8 {}
+diff --git a/src/b.rs b/src/b.rs
+index 587be6b..e69de29 100644
+--- a/src/b.rs
++++ b/src/b.rs
+@@ -1 +1 @@
+-fn b() {}
++fn b() -> u8 {}
+";
+}
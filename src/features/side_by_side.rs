@@ -8,6 +8,7 @@ use crate::config::{self, delta_unreachable, Config};
 use crate::delta::DiffType;
 use crate::delta::State;
 use crate::edits;
+use crate::fatal;
 use crate::features::{line_numbers, OptionValueFunction};
 use crate::minusplus::*;
 use crate::paint::{BgFillMethod, BgShouldFill, LineSections, Painter};
@@ -45,14 +46,89 @@ pub type LeftRight<T> = MinusPlus<T>;
 
 pub type SideBySideData = LeftRight<Panel>;
 
+/// The width of the column between the panels showing change markers (--side-by-side-center-gutter).
+pub const CENTER_GUTTER_WIDTH: usize = 3;
+
+/// The width of the column between the panels, which shows change markers
+/// (--side-by-side-center-gutter) and/or line numbers (--side-by-side-center-line-numbers).
+pub fn center_gutter_width(center_gutter: bool, center_line_numbers: bool) -> usize {
+    match (center_gutter, center_line_numbers) {
+        (true, true) => line_numbers::center_line_numbers_width(CENTER_GUTTER_WIDTH),
+        // The numbers are separated by a "│".
+        (false, true) => line_numbers::center_line_numbers_width(1),
+        (true, false) => CENTER_GUTTER_WIDTH,
+        (false, false) => 0,
+    }
+}
+
+/// Which panel is on the left (--side-by-side-order).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PanelOrder {
+    MinusFirst,
+    PlusFirst,
+}
+
+impl PanelOrder {
+    pub fn parse(order: &str) -> Self {
+        match order {
+            "minus-first" => PanelOrder::MinusFirst,
+            "plus-first" => PanelOrder::PlusFirst,
+            _ => fatal(
+                "Invalid option for side-by-side-order: Expected \"minus-first\" or \"plus-first\".",
+            ),
+        }
+    }
+
+    /// The panels from left to right.
+    pub fn panels(self) -> [PanelSide; 2] {
+        match self {
+            PanelOrder::MinusFirst => [Minus, Plus],
+            PanelOrder::PlusFirst => [Plus, Minus],
+        }
+    }
+}
+
+/// Parse the proportions of the widths of the left and right panels (--side-by-side-split), e.g.
+/// "30:70".
+pub fn parse_split(split: &str) -> (usize, usize) {
+    match split
+        .split_once(':')
+        .map(|(left, right)| (left.trim().parse::<usize>(), right.trim().parse::<usize>()))
+    {
+        Some((Ok(left), Ok(right))) if left > 0 && right > 0 => (left, right),
+        _ => fatal(format!(
+            "Invalid value for side-by-side-split: '{split}'. \
+             Expected two positive numbers separated by ':', e.g. \"30:70\"."
+        )),
+    }
+}
+
 impl SideBySideData {
     /// Create a [`LeftRight<Panel>`](LeftRight<Panel>) named [`SideBySideData`].
-    pub fn new_sbs(decorations_width: &cli::Width, available_terminal_width: &usize) -> Self {
-        let panel_width = match decorations_width {
-            cli::Width::Fixed(w) => w / 2,
-            _ => available_terminal_width / 2,
+    pub fn new_sbs(
+        decorations_width: &cli::Width,
+        available_terminal_width: &usize,
+        center_gutter_width: usize,
+        split: (usize, usize),
+        order: PanelOrder,
+    ) -> Self {
+        let width = match decorations_width {
+            cli::Width::Fixed(w) => w.saturating_sub(center_gutter_width),
+            _ => available_terminal_width.saturating_sub(center_gutter_width),
         };
-        SideBySideData::new(Panel { width: panel_width }, Panel { width: panel_width })
+        if split.0 == split.1 {
+            let panel_width = width / 2;
+            return SideBySideData::new(Panel { width: panel_width }, Panel { width: panel_width });
+        }
+        // Unequal panels take up the whole width, rounding the width of the left one.
+        let left_width = (width * split.0 + (split.0 + split.1) / 2) / (split.0 + split.1);
+        let [left, _] = order.panels();
+        let (minus_width, plus_width) = if left == Minus {
+            (left_width, width - left_width)
+        } else {
+            (width - left_width, left_width)
+        };
+        SideBySideData::new(Panel { width: minus_width }, Panel { width: plus_width })
     }
 }
 
@@ -129,12 +205,12 @@ pub fn paint_minus_and_plus_lines_side_by_side(
         .as_mut()
         .unwrap_or_else(|| delta_unreachable("side-by-side requires Some(line_numbers_data)"));
 
-    let bg_should_fill = LeftRight::new(
-        // Using an ANSI sequence to fill the left panel would not work.
-        BgShouldFill::With(BgFillMethod::Spaces),
-        // Use what is configured for the right side.
-        BgShouldFill::With(config.line_fill_method),
-    );
+    let [left, right] = config.side_by_side_order.panels();
+    let mut bg_should_fill = LeftRight::default();
+    // Using an ANSI sequence to fill the left panel would not work.
+    bg_should_fill[left] = BgShouldFill::With(BgFillMethod::Spaces);
+    // Use what is configured for the right side.
+    bg_should_fill[right] = BgShouldFill::With(config.line_fill_method);
 
     // Only set `should_wrap` to true if wrapping is wanted and lines which are
     // too long are found.
@@ -171,12 +247,36 @@ pub fn paint_minus_and_plus_lines_side_by_side(
         lines_have_homolog
     };
 
+    let mut marker = ' ';
     for (minus_line_index, plus_line_index) in line_alignment {
         let left_state = match minus_line_index {
             Some(i) => &line_states[Left][i],
             None => &State::HunkMinus(DiffType::Unified, None),
         };
-        output_buffer.push_str(&paint_left_panel_minus_line(
+        let right_state = match plus_line_index {
+            Some(i) => &line_states[Right][i],
+            None => &State::HunkPlus(DiffType::Unified, None),
+        };
+        // Lines continuing wrapped lines keep the marker of the line they belong to.
+        let is_wrapped_continuation = (minus_line_index.is_none()
+            || left_state == &State::HunkMinusWrapped)
+            && (plus_line_index.is_none() || right_state == &State::HunkPlusWrapped);
+        if !is_wrapped_continuation {
+            marker = center_gutter_marker(minus_line_index, plus_line_index);
+        }
+        // Read before the panels are painted, which may increment them.
+        let center_line_numbers = MinusPlus::new(
+            (minus_line_index.is_some() && left_state != &State::HunkMinusWrapped)
+                .then_some(line_numbers_data.line_number[Left]),
+            (plus_line_index.is_some() && right_state != &State::HunkPlusWrapped)
+                .then_some(line_numbers_data.line_number[Right]),
+        );
+        let center_line_number_styles = MinusPlus::new(
+            config.line_numbers_style_minusplus[Minus],
+            config.line_numbers_style_minusplus[Plus],
+        );
+        // The minus panel is painted first, which does not increment the line numbers.
+        let minus_panel_line = paint_left_panel_minus_line(
             minus_line_index,
             &syntax_sections[Left],
             &diff_sections[Left],
@@ -185,13 +285,8 @@ pub fn paint_minus_and_plus_lines_side_by_side(
             &mut Some(line_numbers_data),
             bg_should_fill[Left],
             config,
-        ));
-
-        let right_state = match plus_line_index {
-            Some(i) => &line_states[Right][i],
-            None => &State::HunkPlus(DiffType::Unified, None),
-        };
-        output_buffer.push_str(&paint_right_panel_plus_line(
+        );
+        let plus_panel_line = paint_right_panel_plus_line(
             plus_line_index,
             &syntax_sections[Right],
             &diff_sections[Right],
@@ -200,7 +295,18 @@ pub fn paint_minus_and_plus_lines_side_by_side(
             &mut Some(line_numbers_data),
             bg_should_fill[Right],
             config,
-        ));
+        );
+        let panel_lines = LeftRight::new(minus_panel_line, plus_panel_line);
+        output_buffer.push_str(&panel_lines[left]);
+        paint_center_gutter(
+            marker,
+            center_line_numbers,
+            center_line_number_styles,
+            Some(line_numbers_data),
+            output_buffer,
+            config,
+        );
+        output_buffer.push_str(&panel_lines[right]);
         output_buffer.push('\n');
 
         // HACK: The left line number is not getting incremented in `linenumbers_and_styles()`
@@ -246,9 +352,18 @@ pub fn paint_zero_lines_side_by_side<'a>(
     for (line_index, ((syntax_sections, diff_sections), state)) in syntax_style_sections
         .into_iter()
         .zip_eq(diff_style_sections.iter())
-        .zip_eq(states.into_iter())
+        .zip_eq(states)
         .enumerate()
     {
+        // Read before the panels are painted, which may increment them.
+        let center_line_numbers = match (&state, line_numbers_data.as_ref()) {
+            (State::HunkZero(_, _), Some(data)) => {
+                MinusPlus::new(Some(data.line_number[Left]), Some(data.line_number[Right]))
+            }
+            _ => MinusPlus::default(),
+        };
+        // The minus panel is painted first, which does not increment the line numbers.
+        let mut panel_lines = LeftRight::<String>::default();
         for panel_side in &[Left, Right] {
             let (mut panel_line, panel_line_is_empty) = Painter::paint_line(
                 &syntax_sections,
@@ -270,12 +385,116 @@ pub fn paint_zero_lines_side_by_side<'a>(
                 background_color_extends_to_terminal_width,
                 config,
             );
-            output_buffer.push_str(&panel_line);
+            panel_lines[*panel_side] = panel_line;
         }
+        let [left, right] = config.side_by_side_order.panels();
+        output_buffer.push_str(&panel_lines[left]);
+        paint_center_gutter(
+            ' ',
+            center_line_numbers,
+            MinusPlus::new(
+                config.line_numbers_zero_style,
+                config.line_numbers_zero_style,
+            ),
+            line_numbers_data.as_deref(),
+            output_buffer,
+            config,
+        );
+        output_buffer.push_str(&panel_lines[right]);
         output_buffer.push('\n');
     }
 }
 
+/// Paint a row of panels which are not the two sides of a hunk, e.g. the old and new patches of a
+/// pair of commits of `git range-diff` output. The painted lines are truncated to the width of
+/// their panels, and a panel without a line is left blank.
+pub fn paint_panel_row(
+    panel_lines: LeftRight<Option<String>>,
+    marker: char,
+    output_buffer: &mut String,
+    config: &Config,
+) {
+    let [left, _] = config.side_by_side_order.panels();
+    for panel_side in config.side_by_side_order.panels() {
+        let panel_width = config.side_by_side_data[panel_side].width;
+        let mut panel_line = panel_lines[panel_side].clone().unwrap_or_default();
+        if ansi::measure_text_width(&panel_line) > panel_width {
+            panel_line =
+                ansi::truncate_str(&panel_line, panel_width, &config.truncation_symbol).to_string();
+        }
+        output_buffer.push_str(&panel_line);
+        if panel_side == left {
+            let text_width = ansi::measure_text_width(&panel_line);
+            output_buffer.push_str(&" ".repeat(panel_width.saturating_sub(text_width)));
+            paint_center_gutter(
+                marker,
+                MinusPlus::default(),
+                MinusPlus::new(
+                    config.line_numbers_zero_style,
+                    config.line_numbers_zero_style,
+                ),
+                None,
+                output_buffer,
+                config,
+            );
+        }
+    }
+    output_buffer.push('\n');
+}
+
+/// The marker of classic sdiff for a line: '|' for a changed line, '<' for a removed line and '>'
+/// for an added line.
+pub fn center_gutter_marker(
+    minus_line_index: Option<usize>,
+    plus_line_index: Option<usize>,
+) -> char {
+    match (minus_line_index, plus_line_index) {
+        (Some(_), Some(_)) => '|',
+        (Some(_), None) => '<',
+        (None, Some(_)) => '>',
+        (None, None) => ' ',
+    }
+}
+
+/// Paint the column between the panels: the change marker (--side-by-side-center-gutter) and/or
+/// the line numbers of the row (--side-by-side-center-line-numbers).
+fn paint_center_gutter(
+    marker: char,
+    line_numbers: MinusPlus<Option<usize>>,
+    line_number_styles: MinusPlus<Style>,
+    line_numbers_data: Option<&LineNumbersData>,
+    output_buffer: &mut String,
+    config: &Config,
+) {
+    // The markers point to the panel of a removed or added line.
+    let marker = match (config.side_by_side_order, marker) {
+        (PanelOrder::PlusFirst, '<') => '>',
+        (PanelOrder::PlusFirst, '>') => '<',
+        _ => marker,
+    };
+    let marker = config
+        .side_by_side_center_gutter_style
+        .paint(format!(" {marker} "));
+    if let (true, Some(line_numbers_data)) =
+        (config.side_by_side_center_line_numbers, line_numbers_data)
+    {
+        let separator = if config.side_by_side_center_gutter {
+            marker
+        } else {
+            config.line_numbers_style_leftright[Left].paint("│".to_string())
+        };
+        output_buffer.push_str(&line_numbers::format_and_paint_center_line_numbers(
+            line_numbers_data,
+            line_numbers,
+            line_number_styles,
+            separator,
+            config,
+        ));
+    } else if config.side_by_side_center_gutter {
+        output_buffer.push_str(&marker.to_string());
+    }
+}
+
 #[allow(clippy::too_many_arguments)]
 fn paint_left_panel_minus_line<'a>(
     line_index: Option<usize>,
@@ -359,7 +578,8 @@ fn get_right_fill_style_for_panel(
     config: &Config,
 ) -> (Option<BgFillMethod>, Style) {
     // If in the left panel then it must be filled with spaces.
-    let none_or_override = if panel_side == Left {
+    let is_left_panel = panel_side == config.side_by_side_order.panels()[0];
+    let none_or_override = if is_left_panel {
         Some(BgFillMethod::Spaces)
     } else {
         None
@@ -380,7 +600,7 @@ fn get_right_fill_style_for_panel(
 
             match bg_fill_mode {
                 None => (none_or_override, config.null_style),
-                _ if panel_side == Left => (Some(BgFillMethod::Spaces), fill_style),
+                _ if is_left_panel => (Some(BgFillMethod::Spaces), fill_style),
                 _ => (bg_fill_mode, fill_style),
             }
         }
@@ -560,15 +780,25 @@ pub mod ansifill {
         pub fn new(config: &Config) -> Self {
             Self(
                 config.side_by_side
-                    && Self::is_odd_with_ansi(&config.decorations_width, &config.line_fill_method),
+                    && Self::is_odd_with_ansi(
+                        &config.decorations_width,
+                        &config.line_fill_method,
+                        super::center_gutter_width(
+                            config.side_by_side_center_gutter,
+                            config.side_by_side_center_line_numbers,
+                        ),
+                        config.side_by_side_split,
+                    ),
             )
         }
         pub fn sbs_odd_fix(
             width: &crate::cli::Width,
             method: &BgFillMethod,
+            center_gutter_width: usize,
+            split: (usize, usize),
             sbs_data: SideBySideData,
         ) -> SideBySideData {
-            if Self::is_odd_with_ansi(width, method) {
+            if Self::is_odd_with_ansi(width, method, center_gutter_width, split) {
                 Self::adapt_sbs_data(sbs_data)
             } else {
                 sbs_data
@@ -577,9 +807,20 @@ pub mod ansifill {
         pub fn pad_width(&self) -> bool {
             self.0
         }
-        fn is_odd_with_ansi(width: &crate::cli::Width, method: &BgFillMethod) -> bool {
-            method == &BgFillMethod::TryAnsiSequence
-                && matches!(&width, crate::cli::Width::Fixed(width) if width % 2 == 1)
+        fn is_odd_with_ansi(
+            width: &crate::cli::Width,
+            method: &BgFillMethod,
+            center_gutter_width: usize,
+            split: (usize, usize),
+        ) -> bool {
+            // Unequal panels take up the whole width (--side-by-side-split).
+            split.0 == split.1
+                && method == &BgFillMethod::TryAnsiSequence
+                && matches!(
+                    &width,
+                    crate::cli::Width::Fixed(width)
+                        if width.saturating_sub(center_gutter_width) % 2 == 1
+                )
         }
         fn adapt_sbs_data(mut sbs_data: SideBySideData) -> SideBySideData {
             sbs_data[super::Right].width += 1;
@@ -590,6 +831,7 @@ pub mod ansifill {
 
 #[cfg(test)]
 pub mod tests {
+    use super::parse_split;
     use crate::ansi::strip_ansi_codes;
     use crate::features::line_numbers::tests::*;
     use crate::tests::integration_test_utils::{make_config_from_args, run_delta, DeltaTest};
@@ -608,6 +850,146 @@ pub mod tests {
         );
     }
 
+    #[test]
+    fn test_center_gutter() {
+        DeltaTest::with_args(&[
+            "--side-by-side",
+            "--side-by-side-center-gutter",
+            "--width",
+            "43",
+            "--line-fill-method=spaces",
+        ])
+        .with_input(CENTER_GUTTER_DIFF)
+        .expect_after_header(
+            r#"
+            │  1 │a = 1            │  1 │a = 1
+            │  2 │b = 2          | │  2 │bb = 2        
+            │  3 │removed        < │    │
+            │  4 │c = 3            │  3 │c = 3
+            │    │               > │  4 │added         "#,
+        );
+    }
+
+    #[test]
+    fn test_center_line_numbers() {
+        DeltaTest::with_args(&[
+            "--side-by-side",
+            "--side-by-side-center-line-numbers",
+            "--width",
+            "43",
+            "--line-fill-method=spaces",
+        ])
+        .with_input(CENTER_GUTTER_DIFF)
+        .expect_after_header(
+            r#"
+            #indent_mark
+            a = 1               1│1    a = 1
+            b = 2               2│2    bb = 2          
+            removed             3│     
+            c = 3               4│3    c = 3
+                                 │4    added           "#,
+        );
+        DeltaTest::with_args(&[
+            "--side-by-side",
+            "--side-by-side-center-line-numbers",
+            "--side-by-side-center-gutter",
+            "--width",
+            "43",
+            "--line-fill-method=spaces",
+        ])
+        .with_input(CENTER_GUTTER_DIFF)
+        .expect_after_header(
+            r#"
+            #indent_mark
+            a = 1              1   1    a = 1
+            b = 2              2 | 2    bb = 2         
+            removed            3 <      
+            c = 3              4   3    c = 3
+                                 > 4    added          "#,
+        );
+    }
+
+    #[test]
+    fn test_side_by_side_split() {
+        DeltaTest::with_args(&[
+            "--side-by-side",
+            "--side-by-side-split",
+            "30:70",
+            "--width",
+            "40",
+            "--line-fill-method=spaces",
+        ])
+        .with_input(CENTER_GUTTER_DIFF)
+        .expect_after_header(
+            r#"
+            │  1 │a = 1 │  1 │a = 1
+            │  2 │b = 2 │  2 │bb = 2                
+            │  3 │remov↴│    │
+            │    │   …ed│    │
+            │  4 │c = 3 │  3 │c = 3
+            │    │      │  4 │added                 "#,
+        );
+    }
+
+    #[test]
+    fn test_side_by_side_order() {
+        DeltaTest::with_args(&[
+            "--side-by-side",
+            "--side-by-side-order",
+            "plus-first",
+            "--side-by-side-center-gutter",
+            "--width",
+            "43",
+            "--line-fill-method=spaces",
+        ])
+        .with_input(CENTER_GUTTER_DIFF)
+        .expect_after_header(
+            r#"
+            │  1 │a = 1            │  1 │a = 1
+            │  2 │bb = 2         | │  2 │b = 2         
+            │    │               > │  3 │removed       
+            │  3 │c = 3            │  4 │c = 3
+            │  4 │added          < │    │"#,
+        );
+        DeltaTest::with_args(&[
+            "--side-by-side",
+            "--side-by-side-order",
+            "plus-first",
+            "--side-by-side-center-line-numbers",
+            "--width",
+            "43",
+            "--line-fill-method=spaces",
+        ])
+        .with_input(CENTER_GUTTER_DIFF)
+        .expect_after_header(
+            r#"
+            #indent_mark
+            a = 1               1│1    a = 1
+            bb = 2              2│2    b = 2           
+                                 │3    removed         
+            c = 3               3│4    c = 3
+            added               4│     "#,
+        );
+    }
+
+    #[test]
+    fn test_parse_split() {
+        assert_eq!(parse_split("30:70"), (30, 70));
+        assert_eq!(parse_split(" 1 : 2 "), (1, 2));
+    }
+
+    const CENTER_GUTTER_DIFF: &str = "\
+--- a/a.py
++++ b/a.py
+@@ -1,4 +1,4 @@
+ a = 1
+-b = 2
+-removed
++bb = 2
+ c = 3
++added
+";
+
     #[test]
     fn test_two_minus_lines_truncated() {
         DeltaTest::with_args(&[
@@ -0,0 +1,174 @@
+use crate::ansi::measure_text_width;
+use crate::cli;
+use crate::config::Config;
+use crate::delta::{State, StateMachine};
+use crate::minusplus::MinusPlus;
+use crate::minusplus::MinusPlusIndex::{self, Minus, Plus};
+
+/// The position in a hunk, used to split a long hunk at blank lines (--split-hunks).
+#[derive(Debug)]
+pub struct HunkSplitter {
+    // The number of lines since the start of the hunk or the last separator.
+    n_lines: usize,
+    // The line numbers of the next minus and plus lines.
+    line_numbers: MinusPlus<usize>,
+    // The line numbers after the hunk.
+    ends: MinusPlus<usize>,
+}
+
+impl HunkSplitter {
+    /// A splitter for a hunk with the ranges of its header.
+    pub fn new(
+        (minus_start, minus_length): (usize, usize),
+        (plus_start, plus_length): (usize, usize),
+    ) -> Self {
+        Self {
+            n_lines: 0,
+            line_numbers: MinusPlus::new(minus_start, plus_start),
+            ends: MinusPlus::new(minus_start + minus_length, plus_start + plus_length),
+        }
+    }
+
+    // Count a line, returning its side, if it is a minus or plus line.
+    fn count_line(&mut self, state: &State) -> Option<MinusPlusIndex> {
+        self.n_lines += 1;
+        match state {
+            State::HunkMinus(_, _) => {
+                self.line_numbers[Minus] += 1;
+                Some(Minus)
+            }
+            State::HunkPlus(_, _) => {
+                self.line_numbers[Plus] += 1;
+                Some(Plus)
+            }
+            _ => {
+                self.line_numbers[Minus] += 1;
+                self.line_numbers[Plus] += 1;
+                None
+            }
+        }
+    }
+}
+
+impl<'a> StateMachine<'a> {
+    /// Count the current hunk line and, if it is a blank line following at least --split-hunks
+    /// lines of the hunk, paint the buffered lines and a separator below it. Only unchanged lines,
+    /// and changed lines which are not paired with lines of the other side, split the hunk, so
+    /// that the highlighting of changes within lines is unaffected.
+    pub fn split_hunk(&mut self) {
+        let (Some(splitter), false) = (&mut self.hunk_splitter, self.line.starts_with('\\')) else {
+            return;
+        };
+        let side = splitter.count_line(&self.state);
+        let blank = self.line.get(1..).is_none_or(|line| line.trim().is_empty());
+        if !blank || splitter.n_lines < self.config.split_hunks {
+            return;
+        }
+        let can_split = match side {
+            // Folded unchanged lines are not split (--max-context-lines).
+            None => self.config.max_context_lines == 0,
+            Some(Plus) => self.painter.minus_lines.is_empty(),
+            Some(Minus) => splitter.ends[Plus] == splitter.line_numbers[Plus],
+        };
+        // The separator is labeled with the line number of the next line, of the new file unless
+        // the hunk only removes lines.
+        let label_side = match side {
+            Some(Minus) => Minus,
+            _ => Plus,
+        };
+        let line_number = splitter.line_numbers[label_side];
+        if !can_split || line_number >= splitter.ends[label_side] {
+            return;
+        }
+        splitter.n_lines = 0;
+        self.painter.paint_buffered_minus_and_plus_lines();
+        self.painter
+            .output_buffer
+            .push_str(&format_separator(line_number, self.config));
+        self.painter.output_buffer.push('\n');
+    }
+}
+
+/// The thin separator line, labeled with a line number, spanning the width of the decorations.
+fn format_separator(line_number: usize, config: &Config) -> String {
+    let width = match config.decorations_width {
+        cli::Width::Fixed(width) => width,
+        cli::Width::Variable => config.available_terminal_width,
+    };
+    let text = format!("┄┄ {line_number} ");
+    let fill = width.saturating_sub(measure_text_width(&text));
+    config
+        .split_hunks_style
+        .paint(format!("{text}{}", "┄".repeat(fill)))
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ansi::strip_ansi_codes;
+    use crate::tests::integration_test_utils::DeltaTest;
+
+    #[test]
+    fn test_split_hunks() {
+        let output = DeltaTest::with_args(&["--split-hunks", "3", "--width", "20"])
+            .with_input(DIFF)
+            .expect_contains("\na\n\n\n┄┄ 13 ┄┄┄┄┄┄┄┄┄┄┄┄┄┄\nb\nc\n\n┄┄ 16 ┄┄┄┄┄┄┄┄┄┄┄┄┄┄\nd\n")
+            // The blank line paired with a removed line does not split the hunk.
+            .expect_contains("\nx\n\ny\n");
+        assert_eq!(
+            strip_ansi_codes(&output.raw_output).matches('┄').count(),
+            32
+        );
+    }
+
+    #[test]
+    fn test_split_hunks_deletion() {
+        DeltaTest::with_args(&["--split-hunks", "2", "--width", "10"])
+            .with_input(DELETION_DIFF)
+            .expect_contains("\na\n\n┄┄ 3 ┄┄┄┄┄\nb\n");
+    }
+
+    #[test]
+    fn test_split_hunks_disabled() {
+        for args in [
+            &["--split-hunks", "0"][..],
+            &["--split-hunks", "3", "--color-only"],
+        ] {
+            let output = DeltaTest::with_args(args).with_input(DIFF);
+            assert!(!output.raw_output.contains('┄'));
+        }
+    }
+
+    const DIFF: &str = "\
+diff --git a/file b/file
+index 1111111..2222222 100644
+--- a/file
++++ b/file
+@@ -10,8 +10,10 @@
+ a
++
+ 
+ b
+ c
++
+ d
+-z
++
+ x
+-
++y
+";
+
+    const DELETION_DIFF: &str = "\
+diff --git a/file b/file
+deleted file mode 100644
+index 1111111..0000000
+--- a/file
++++ /dev/null
+@@ -1,4 +0,0 @@
+-a
+-
+-b
+-
+";
+}
@@ -0,0 +1,313 @@
+use std::cell::OnceCell;
+use std::io::{self, IsTerminal};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use console::Term;
+
+use crate::cli;
+use crate::config::Config;
+use crate::delta::StateMachine;
+use crate::features::side_by_side::{self, ansifill};
+use crate::handlers::diff_header::get_language_config;
+use crate::utils::bat::output::PagingMode;
+
+/// The number of configs for other sizes of the terminal which delta makes at most, since the
+/// configs in use cannot be replaced: a size to which the terminal was already resized reuses its
+/// config, but after as many different sizes, the layout no longer adapts to new ones (see --width).
+pub const MAX_RESIZED_CONFIGS: usize = 8;
+
+// Set when the terminal is resized, until the layout is adapted to its new size.
+static RESIZED: AtomicBool = AtomicBool::new(false);
+
+/// Whether the layout follows the width of the terminal when it is resized: only when delta writes
+/// to the terminal without a pager, which would otherwise handle the resizing, and the width is not
+/// fixed by --width. If so, record when the terminal is resized (SIGWINCH), to adapt the layout of
+/// the following files to its new width.
+pub fn watch_terminal_size(paging_mode: PagingMode, fixed_width: bool) -> bool {
+    let follow = !fixed_width && paging_mode == PagingMode::Never && io::stdout().is_terminal();
+    follow && handle_resize()
+}
+
+#[cfg(unix)]
+fn handle_resize() -> bool {
+    extern "C" fn on_resize(_signal: libc::c_int) {
+        RESIZED.store(true, Ordering::Relaxed);
+    }
+    // SAFETY: The handler only stores to an atomic, which is async-signal-safe. The action is
+    // zero-initialized, which is valid for the C struct, before its fields are set. SA_RESTART
+    // resumes the reads from stdin interrupted by the signal.
+    unsafe {
+        let mut action: libc::sigaction = std::mem::zeroed();
+        action.sa_sigaction = on_resize as extern "C" fn(libc::c_int) as libc::sighandler_t;
+        action.sa_flags = libc::SA_RESTART;
+        libc::sigemptyset(&mut action.sa_mask);
+        libc::sigaction(libc::SIGWINCH, &action, std::ptr::null_mut()) == 0
+    }
+}
+
+#[cfg(not(unix))]
+fn handle_resize() -> bool {
+    false
+}
+
+/// The configs for the sizes to which the terminal is resized, made from the root config (see
+/// `StateMachine::root_config`) when delta writes to the terminal without a pager and the width is
+/// not fixed by --width.
+pub struct TerminalResizeFollower<'a> {
+    configs: &'a [OnceCell<Config>],
+    resized: fn() -> bool,
+    terminal_width: fn() -> usize,
+}
+
+impl<'a> TerminalResizeFollower<'a> {
+    pub fn new(configs: &'a [OnceCell<Config>]) -> Self {
+        Self {
+            configs,
+            resized: || RESIZED.swap(false, Ordering::Relaxed),
+            terminal_width: || Term::stdout().size().1 as usize,
+        }
+    }
+
+    /// The root config for the current width of the terminal, if it has been resized to another
+    /// width than that of the root config `current`.
+    fn resized_config(&mut self, current: &Config) -> Option<&'a Config> {
+        if !(self.resized)() {
+            return None;
+        }
+        let width = (self.terminal_width)();
+        if width == current.available_terminal_width {
+            return None;
+        }
+        let is_resized = |config: &Config| {
+            config.available_terminal_width == width && config.color_mode == current.color_mode
+        };
+        let configs = self.configs;
+        if let Some(config) = configs
+            .iter()
+            .filter_map(OnceCell::get)
+            .find(|c| is_resized(c))
+        {
+            return Some(config);
+        }
+        let slot = configs.iter().find(|slot| slot.get().is_none())?;
+        Some(slot.get_or_init(|| make_config(current, width)))
+    }
+}
+
+// The root config for a terminal of the given width: only the widths of the layout differ from
+// those of the current root config, and of its language configs.
+fn make_config(current: &Config, width: usize) -> Config {
+    let mut config = current.clone();
+    set_width(&mut config, width);
+    for language_config in config.language_configs.values_mut() {
+        set_width(language_config, width);
+    }
+    config
+}
+
+fn set_width(config: &mut Config, width: usize) {
+    config.available_terminal_width = width;
+    if let cli::Width::Fixed(_) = config.decorations_width {
+        config.decorations_width = cli::Width::Fixed(width);
+    }
+    let center_gutter_width = side_by_side::center_gutter_width(
+        config.side_by_side_center_gutter,
+        config.side_by_side_center_line_numbers,
+    );
+    let side_by_side_data = side_by_side::SideBySideData::new_sbs(
+        &config.decorations_width,
+        &config.available_terminal_width,
+        center_gutter_width,
+        config.side_by_side_split,
+        config.side_by_side_order,
+    );
+    config.side_by_side_data = ansifill::UseFullPanelWidth::sbs_odd_fix(
+        &config.decorations_width,
+        &config.line_fill_method,
+        center_gutter_width,
+        config.side_by_side_split,
+        side_by_side_data,
+    );
+}
+
+impl<'a> StateMachine<'a> {
+    /// Switch to the config for the terminal's current width before displaying a file or a hunk,
+    /// if the terminal has been resized.
+    pub fn follow_terminal_size(&mut self) {
+        let root_config = match self.terminal_resize_follower.as_mut() {
+            Some(follower) => follower.resized_config(self.root_config),
+            None => None,
+        };
+        if let Some(root_config) = root_config {
+            if let Some(follower) = self.color_scheme_follower.as_mut() {
+                follower.set_config(root_config);
+            }
+            let config = get_language_config(root_config, self.syntax_filename.as_deref());
+            self.root_config = root_config;
+            self.config = config;
+            self.painter.set_config(config);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicUsize;
+
+    use bytelines::ByteLinesReader;
+
+    use super::*;
+    use crate::features::side_by_side::{Left, Right};
+    use crate::tests::integration_test_utils;
+
+    #[test]
+    fn test_resized_config() {
+        let configs: [OnceCell<Config>; 2] = Default::default();
+        let mut follower = TerminalResizeFollower::new(&configs);
+        follower.terminal_width = || 60;
+        let mut config = integration_test_utils::make_config_from_args(&["--side-by-side"]);
+        config.available_terminal_width = 80;
+
+        follower.resized = || false;
+        assert!(follower.resized_config(&config).is_none());
+
+        follower.resized = || true;
+        let resized = follower.resized_config(&config).unwrap();
+        assert!(std::ptr::eq(resized, configs[0].get().unwrap()));
+        assert_eq!(resized.available_terminal_width, 60);
+        assert_eq!(resized.decorations_width, cli::Width::Fixed(60));
+        assert_eq!(resized.side_by_side_data[Left].width, 30);
+        assert_eq!(resized.side_by_side_data[Right].width, 30);
+        // The config is made once for each width.
+        assert!(std::ptr::eq(
+            follower.resized_config(&config).unwrap(),
+            resized
+        ));
+        assert!(configs[1].get().is_none());
+        // The terminal has the width of the current config.
+        assert!(follower.resized_config(resized).is_none());
+    }
+
+    #[test]
+    fn test_resized_config_with_variable_width() {
+        let configs: [OnceCell<Config>; 1] = Default::default();
+        let mut follower = TerminalResizeFollower::new(&configs);
+        follower.resized = || true;
+        follower.terminal_width = || 60;
+        let config = integration_test_utils::make_config_from_args(&["--width", "variable"]);
+
+        let resized = follower.resized_config(&config).unwrap();
+        assert_eq!(resized.available_terminal_width, 60);
+        assert_eq!(resized.decorations_width, cli::Width::Variable);
+    }
+
+    #[test]
+    fn test_follow_terminal_size() {
+        let configs: [OnceCell<Config>; 1] = Default::default();
+        let mut follower = TerminalResizeFollower::new(&configs);
+        follower.resized = || true;
+        follower.terminal_width = || 60;
+        let config = integration_test_utils::make_config_from_args(&["--side-by-side"]);
+
+        let mut output = Vec::new();
+        let mut state_machine = StateMachine::new(&mut output, &config);
+        state_machine.terminal_resize_follower = Some(follower);
+        state_machine.consume(DIFF.as_bytes().byte_lines()).unwrap();
+        let resized = configs[0].get().unwrap();
+        assert!(std::ptr::eq(state_machine.config, resized));
+        let resized_output = integration_test_utils::run_delta(
+            DIFF,
+            &integration_test_utils::make_config_from_args(&["--side-by-side", "--width", "60"]),
+        );
+        assert_eq!(String::from_utf8(output).unwrap(), resized_output);
+        assert_ne!(
+            integration_test_utils::run_delta(DIFF, &config),
+            resized_output
+        );
+    }
+
+    #[test]
+    fn test_follow_terminal_size_with_language_config() {
+        let git_config_contents = b"
+[delta \"lang:rs\"]
+    plus-style = blue
+";
+        let git_config_path = "delta__test_follow_terminal_size_with_language_config.gitconfig";
+        let make_config = |args: &[&str]| {
+            integration_test_utils::make_config_from_args_and_git_config(
+                args,
+                Some(git_config_contents),
+                Some(git_config_path),
+            )
+        };
+        let config = make_config(&["--side-by-side"]);
+        let resized_output = integration_test_utils::run_delta(
+            DIFF,
+            &make_config(&["--side-by-side", "--width", "60"]),
+        );
+        std::fs::remove_file(git_config_path).unwrap();
+        let configs: [OnceCell<Config>; 1] = Default::default();
+        let mut follower = TerminalResizeFollower::new(&configs);
+        follower.resized = || true;
+        follower.terminal_width = || 60;
+
+        let mut output = Vec::new();
+        let mut state_machine = StateMachine::new(&mut output, &config);
+        state_machine.terminal_resize_follower = Some(follower);
+        state_machine.consume(DIFF.as_bytes().byte_lines()).unwrap();
+        let resized = configs[0].get().unwrap();
+        assert!(std::ptr::eq(state_machine.root_config, resized));
+        assert!(std::ptr::eq(
+            state_machine.config,
+            &resized.language_configs["rs"]
+        ));
+        assert_eq!(resized.language_configs["rs"].available_terminal_width, 60);
+        assert_eq!(
+            resized.language_configs["rs"].side_by_side_data[Left].width,
+            30
+        );
+        assert_eq!(String::from_utf8(output).unwrap(), resized_output);
+    }
+
+    #[test]
+    fn test_follow_terminal_size_at_hunk_header() {
+        // The terminal is resized after the header of the file has been displayed.
+        static CALLS: AtomicUsize = AtomicUsize::new(0);
+        let configs: [OnceCell<Config>; 1] = Default::default();
+        let mut follower = TerminalResizeFollower::new(&configs);
+        follower.resized = || CALLS.fetch_add(1, Ordering::Relaxed) == 1;
+        follower.terminal_width = || 60;
+        let config = integration_test_utils::make_config_from_args(&["--side-by-side"]);
+
+        let mut output = Vec::new();
+        let mut state_machine = StateMachine::new(&mut output, &config);
+        state_machine.terminal_resize_follower = Some(follower);
+        state_machine.consume(DIFF.as_bytes().byte_lines()).unwrap();
+        assert!(std::ptr::eq(
+            state_machine.config,
+            configs[0].get().unwrap()
+        ));
+        let output = String::from_utf8(output).unwrap();
+        let hunk_output = integration_test_utils::run_delta(
+            DIFF,
+            &integration_test_utils::make_config_from_args(&["--side-by-side", "--width", "60"]),
+        );
+        let hunk = hunk_output
+            .lines()
+            .skip_while(|line| !line.contains("fn a"))
+            .collect::<Vec<_>>();
+        assert!(output.lines().collect::<Vec<_>>().ends_with(&hunk));
+        assert_ne!(output, hunk_output);
+    }
+
+    const DIFF: &str = "\
+diff --git a/file.rs b/file.rs
+index 1111111..2222222 100644
+--- a/file.rs
++++ b/file.rs
+@@ -1,2 +1,2 @@
+ fn a() {}
+-fn b() {}
++fn c() {}
+";
+}
@@ -1,39 +1,34 @@
 mod remote;
 
-pub use remote::GitRemoteRepo;
+pub use remote::{Forge, GitRemoteRepo};
 
 use crate::env::DeltaEnv;
 use regex::Regex;
 use std::collections::HashMap;
 use std::path::Path;
 use std::str::FromStr;
+use std::sync::{Mutex, MutexGuard, PoisonError};
 
 use lazy_static::lazy_static;
 
+// The libgit2 objects are locked while they are used, so that a config can be shared by threads.
 pub struct GitConfig {
-    config: git2::Config,
+    config: Mutex<SendConfig>,
     config_from_env_var: HashMap<String, String>,
     pub enabled: bool,
-    repo: Option<git2::Repository>,
-    // To make GitConfig cloneable when testing (in turn to make Config cloneable):
-    #[cfg(test)]
-    path: std::path::PathBuf,
+    repo: Option<Mutex<git2::Repository>>,
 }
 
-#[cfg(test)]
-impl Clone for GitConfig {
-    fn clone(&self) -> Self {
-        assert!(self.repo.is_none());
-        GitConfig {
-            // Assumes no test modifies the file pointed to by `path`
-            config: git2::Config::open(&self.path).unwrap(),
-            config_from_env_var: self.config_from_env_var.clone(),
-            enabled: self.enabled,
-            repo: None,
-            path: self.path.clone(),
-        }
-    }
-}
+struct SendConfig(git2::Config);
+
+// git2::Config is not Send only because it holds a raw pointer to the libgit2 object.
+//
+// SAFETY: libgit2 is built thread-safe (libgit2-sys always defines GIT_THREADS, and git2 calls
+// git_libgit2_init before the config is opened), and a libgit2 object is not bound to the thread
+// which created it, but may not be used by several threads at once. The config is only reached
+// through the mutex of `GitConfig::config`, which gives a thread exclusive access to it, and it
+// is a snapshot, which libgit2 does not change when the config files change.
+unsafe impl Send for SendConfig {}
 
 impl GitConfig {
     #[cfg(not(test))]
@@ -54,9 +49,9 @@ impl GitConfig {
                     fatal(format!("Failed to read git config: {err}"));
                 });
                 Some(Self {
-                    config,
+                    config: Mutex::new(SendConfig(config)),
                     config_from_env_var: parse_config_from_env_var(env),
-                    repo,
+                    repo: repo.map(Mutex::new),
                     enabled: true,
                 })
             }
@@ -80,7 +75,7 @@ impl GitConfig {
                 });
 
                 Self {
-                    config,
+                    config: Mutex::new(SendConfig(config)),
                     config_from_env_var: if honor_env_var {
                         parse_config_from_env_var(env)
                     } else {
@@ -88,8 +83,6 @@ impl GitConfig {
                     },
                     repo: None,
                     enabled: true,
-                    #[cfg(test)]
-                    path: path.into(),
                 }
             }
             Err(e) => {
@@ -109,20 +102,55 @@ impl GitConfig {
         }
     }
 
+    /// The repository of the remote "origin". A repository which is not hosted by one of the
+    /// sites known to delta is found by the forge named by the remote in git config, e.g. by
+    /// `git config remote.origin.delta-forge gitlab`.
     pub fn get_remote_url(&self) -> Option<GitRemoteRepo> {
-        self.repo
-            .as_ref()?
-            .find_remote("origin")
-            .ok()?
-            .url()
-            .and_then(|url| GitRemoteRepo::from_str(url).ok())
+        let repo = self.repo()?;
+        let remote = repo.find_remote("origin").ok()?;
+        let url = remote.url()?;
+        match self
+            .get::<String>("remote.origin.delta-forge")
+            .and_then(|forge| Forge::from_str(&forge).ok())
+        {
+            Some(forge) => GitRemoteRepo::self_hosted(url, forge),
+            None => GitRemoteRepo::from_str(url).ok(),
+        }
+    }
+
+    /// The repository in which delta runs, if any.
+    pub fn repo(&self) -> Option<MutexGuard<'_, git2::Repository>> {
+        let repo = self.repo.as_ref()?;
+        Some(repo.lock().unwrap_or_else(PoisonError::into_inner))
+    }
+
+    fn config(&self) -> MutexGuard<'_, SendConfig> {
+        self.config.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Whether git config has a key starting with the prefix, e.g. "delta.my-feature.".
+    pub fn has_key_with_prefix(&self, prefix: &str) -> bool {
+        if !self.enabled {
+            return false;
+        }
+        if self
+            .config_from_env_var
+            .keys()
+            .any(|key| key.starts_with(prefix))
+        {
+            return true;
+        }
+        let mut found = false;
+        self.for_each(".*", |name, _| found |= name.starts_with(prefix));
+        found
     }
 
     pub fn for_each<F>(&self, regex: &str, mut f: F)
     where
         F: FnMut(&str, Option<&str>),
     {
-        let mut entries = self.config.entries(Some(regex)).unwrap();
+        let config = self.config();
+        let mut entries = config.0.entries(Some(regex)).unwrap();
         while let Some(entry) = entries.next() {
             let entry = entry.unwrap();
             let name = entry.name().unwrap();
@@ -185,7 +213,7 @@ impl GitConfigGet for String {
     fn git_config_get(key: &str, git_config: &GitConfig) -> Option<Self> {
         match git_config.config_from_env_var.get(key) {
             Some(val) => Some(val.to_string()),
-            None => git_config.config.get_string(key).ok(),
+            None => git_config.config().0.get_string(key).ok(),
         }
     }
 }
@@ -194,7 +222,7 @@ impl GitConfigGet for Option<String> {
     fn git_config_get(key: &str, git_config: &GitConfig) -> Option<Self> {
         match git_config.config_from_env_var.get(key) {
             Some(val) => Some(Some(val.to_string())),
-            None => match git_config.config.get_string(key) {
+            None => match git_config.config().0.get_string(key) {
                 Ok(val) => Some(Some(val)),
                 _ => None,
             },
@@ -207,7 +235,7 @@ impl GitConfigGet for bool {
         match git_config.config_from_env_var.get(key).map(|s| s.as_str()) {
             Some("true") => Some(true),
             Some("false") => Some(false),
-            _ => git_config.config.get_bool(key).ok(),
+            _ => git_config.config().0.get_bool(key).ok(),
         }
     }
 }
@@ -219,7 +247,7 @@ impl GitConfigGet for usize {
                 return Some(n);
             }
         }
-        match git_config.config.get_i64(key) {
+        match git_config.config().0.get_i64(key) {
             Ok(value) => Some(value as usize),
             _ => None,
         }
@@ -233,7 +261,7 @@ impl GitConfigGet for f64 {
                 return Some(n);
             }
         }
-        match git_config.config.get_string(key) {
+        match git_config.config().0.get_string(key) {
             Ok(value) => value.parse::<f64>().ok(),
             _ => None,
         }
@@ -298,4 +326,4 @@ mod tests {
             );
         }
     }
-}
\ No newline at end of file
+}
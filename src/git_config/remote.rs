@@ -8,13 +8,72 @@ use crate::errors::*;
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum GitRemoteRepo {
-    GitHub { slug: String },
-    GitLab { slug: String },
-    SourceHut { slug: String },
-    Codeberg { slug: String },
+    GitHub {
+        slug: String,
+    },
+    GitLab {
+        slug: String,
+    },
+    SourceHut {
+        slug: String,
+    },
+    Codeberg {
+        slug: String,
+    },
+    Bitbucket {
+        slug: String,
+    },
+    /// A repository hosted elsewhere than on the sites above, by the software of a forge which
+    /// is named in git config by the remote, e.g. `git config remote.origin.delta-forge gitlab`.
+    /// The path is that of the repository on the host, e.g. "group/repo".
+    SelfHosted {
+        forge: Forge,
+        host: String,
+        path: String,
+    },
+}
+
+/// The software of a forge, which decides the URLs of the commits and issues of its repositories.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Forge {
+    GitHub,
+    GitLab,
+    Bitbucket,
+    Gerrit,
+    /// Gitea and Forgejo, e.g. Codeberg.
+    Gitea,
+    SourceHut,
+}
+
+impl FromStr for Forge {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "github" => Ok(Self::GitHub),
+            "gitlab" => Ok(Self::GitLab),
+            "bitbucket" => Ok(Self::Bitbucket),
+            "gerrit" => Ok(Self::Gerrit),
+            "gitea" | "forgejo" | "codeberg" => Ok(Self::Gitea),
+            "sourcehut" => Ok(Self::SourceHut),
+            _ => Err(anyhow!(
+                "Invalid forge {s:?}: expected github, gitlab, bitbucket, gerrit, gitea or sourcehut."
+            )),
+        }
+    }
 }
 
 impl GitRemoteRepo {
+    /// The repository of the remote URL, hosted by the forge, e.g. "https://git.example.com/a/b"
+    /// or "git@git.example.com:a/b.git".
+    pub fn self_hosted(url: &str, forge: Forge) -> Option<Self> {
+        let caps = REMOTE_URL.captures(url)?;
+        Some(Self::SelfHosted {
+            forge,
+            host: caps[1].to_string(),
+            path: caps[2].to_string(),
+        })
+    }
+
     pub fn format_commit_url(&self, commit: &str) -> String {
         match self {
             Self::GitHub { slug } => {
@@ -29,11 +88,122 @@ impl GitRemoteRepo {
             Self::Codeberg { slug } => {
                 format!("https://codeberg.org/{slug}/commit/{commit}")
             }
+            Self::Bitbucket { slug } => {
+                format!("https://bitbucket.org/{slug}/commits/{commit}")
+            }
+            Self::SelfHosted { forge, host, path } => match forge {
+                Forge::GitHub | Forge::Gitea | Forge::SourceHut => {
+                    format!("https://{host}/{path}/commit/{commit}")
+                }
+                Forge::GitLab => format!("https://{host}/{path}/-/commit/{commit}"),
+                Forge::Bitbucket => match bitbucket_server_project_and_repo(path) {
+                    Some((project, repo)) => {
+                        format!("https://{host}/projects/{project}/repos/{repo}/commits/{commit}")
+                    }
+                    None => format!("https://{host}/{path}/commits/{commit}"),
+                },
+                // A change of Gerrit is found by any of its commits.
+                Forge::Gerrit => format!("https://{host}/q/{commit}"),
+            },
+        }
+    }
+
+    /// The URL of the issue with the number, if the forge has issues.
+    pub fn format_issue_url(&self, number: &str) -> Option<String> {
+        match self {
+            Self::GitHub { slug } => Some(format!("https://github.com/{slug}/issues/{number}")),
+            Self::GitLab { slug } => Some(format!("https://gitlab.com/{slug}/-/issues/{number}")),
+            // The trackers of SourceHut are not named after repositories.
+            Self::SourceHut { .. } => None,
+            Self::Codeberg { slug } => Some(format!("https://codeberg.org/{slug}/issues/{number}")),
+            Self::Bitbucket { slug } => {
+                Some(format!("https://bitbucket.org/{slug}/issues/{number}"))
+            }
+            Self::SelfHosted { forge, host, path } => match forge {
+                Forge::GitHub | Forge::Gitea => {
+                    Some(format!("https://{host}/{path}/issues/{number}"))
+                }
+                Forge::GitLab => Some(format!("https://{host}/{path}/-/issues/{number}")),
+                Forge::Bitbucket | Forge::Gerrit | Forge::SourceHut => None,
+            },
+        }
+    }
+
+    /// The URL of the issue or pull request in the API of the forge, whose response is JSON with
+    /// a "title" field. On GitHub and Codeberg, issues and pull requests are numbered together.
+    pub fn format_issue_api_url(&self, number: u64) -> Option<String> {
+        match self {
+            Self::GitHub { slug } => Some(format!(
+                "https://api.github.com/repos/{slug}/issues/{number}"
+            )),
+            Self::GitLab { slug } => Some(format!(
+                "https://gitlab.com/api/v4/projects/{}/issues/{number}",
+                slug.replace('/', "%2F")
+            )),
+            Self::SourceHut { .. } => None,
+            Self::Codeberg { slug } => Some(format!(
+                "https://codeberg.org/api/v1/repos/{slug}/issues/{number}"
+            )),
+            Self::Bitbucket { slug } => Some(format!(
+                "https://api.bitbucket.org/2.0/repositories/{slug}/issues/{number}"
+            )),
+            Self::SelfHosted { forge, host, path } => match forge {
+                Forge::GitHub => Some(format!(
+                    "https://{host}/api/v3/repos/{path}/issues/{number}"
+                )),
+                Forge::GitLab => Some(format!(
+                    "https://{host}/api/v4/projects/{}/issues/{number}",
+                    path.replace('/', "%2F")
+                )),
+                Forge::Gitea => Some(format!(
+                    "https://{host}/api/v1/repos/{path}/issues/{number}"
+                )),
+                Forge::Bitbucket | Forge::Gerrit | Forge::SourceHut => None,
+            },
         }
     }
 }
 
+// The project and repository of the path of a repository of Bitbucket Server, which is cloned
+// from e.g. "https://host/scm/project/repo.git" or "ssh://git@host:7999/project/repo.git".
+fn bitbucket_server_project_and_repo(path: &str) -> Option<(&str, &str)> {
+    let path = path.strip_prefix("scm/").unwrap_or(path);
+    match path.split_once('/') {
+        Some((project, repo)) if !repo.contains('/') => Some((project, repo)),
+        _ => None,
+    }
+}
+
 lazy_static! {
+    static ref REMOTE_URL: Regex = Regex::new(
+        r"(?x)
+        ^
+        (?:[a-z+]+://)?   # The scheme of the URL, if any: SSH URLs may be like scp's
+        (?:[^@/]+@)?      # The user, if any
+        ([^:/]+)          # Capture the host
+        (?::\d+)?         # The port, if any
+        [:/]              # This separator differs between SSH and HTTPS URLs
+        (.+?)             # Capture the path (lazy to avoid consuming '.git' if present)
+        (?:\.git)?        # Non-capturing group to consume '.git' if present
+        /?
+        $
+        "
+    )
+    .unwrap();
+    static ref BITBUCKET_REMOTE_URL: Regex = Regex::new(
+        r"(?x)
+        ^
+        (?:https://(?:[^@/]+@)?|git@)? # Support both HTTPS and SSH URLs, HTTPS URLs with a user
+        bitbucket\.org
+        [:/]              # This separator differs between SSH and HTTPS URLs
+        ([^/]+)           # Capture the workspace
+        /
+        (.+?)             # Capture the repo name (lazy to avoid consuming '.git' if present)
+        (?:\.git)?        # Non-capturing group to consume '.git' if present
+        $
+        "
+    )
+    .unwrap();
     static ref GITHUB_REMOTE_URL: Regex = Regex::new(
         r"(?x)
         ^
@@ -128,8 +298,18 @@ impl FromStr for GitRemoteRepo {
                     repo = caps.get(2).unwrap().as_str()
                 ),
             })
+        } else if let Some(caps) = BITBUCKET_REMOTE_URL.captures(s) {
+            Ok(Self::Bitbucket {
+                slug: format!(
+                    "{workspace}/{repo}",
+                    workspace = caps.get(1).unwrap().as_str(),
+                    repo = caps.get(2).unwrap().as_str()
+                ),
+            })
         } else {
-            Err(anyhow!("Not a GitHub, GitLab, SourceHut or Codeberg repo."))
+            Err(anyhow!(
+                "Not a GitHub, GitLab, SourceHut, Codeberg or Bitbucket repo."
+            ))
         }
     }
 }
@@ -281,4 +461,75 @@ mod tests {
             format!("https://codeberg.org/dnkl/foot/commit/{commit_hash}")
         )
     }
+
+    #[test]
+    fn test_parse_bitbucket_urls() {
+        let urls = &[
+            "https://someuser@bitbucket.org/someworkspace/somerepo.git",
+            "https://bitbucket.org/someworkspace/somerepo",
+            "git@bitbucket.org:someworkspace/somerepo.git",
+        ];
+        for url in urls {
+            assert_eq!(
+                GitRemoteRepo::from_str(url).unwrap(),
+                GitRemoteRepo::Bitbucket {
+                    slug: "someworkspace/somerepo".to_string()
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_self_hosted_urls() {
+        let urls = &[
+            "https://git.example.com/group/repo.git",
+            "https://git.example.com:8443/group/repo/",
+            "ssh://git@git.example.com:2222/group/repo.git",
+            "git@git.example.com:group/repo.git",
+        ];
+        for url in urls {
+            assert_eq!(
+                GitRemoteRepo::self_hosted(url, Forge::GitLab),
+                Some(GitRemoteRepo::SelfHosted {
+                    forge: Forge::GitLab,
+                    host: "git.example.com".to_string(),
+                    path: "group/repo".to_string(),
+                })
+            );
+        }
+    }
+
+    #[test]
+    fn test_format_self_hosted_links() {
+        let repo = |forge: &str, url| {
+            GitRemoteRepo::self_hosted(url, Forge::from_str(forge).unwrap()).unwrap()
+        };
+        let commit_hash = "1c072856ebf12419378c5098ad543c497197c6da";
+        let gitlab = repo("gitlab", "git@git.example.com:group/repo.git");
+        assert_eq!(
+            gitlab.format_commit_url(commit_hash),
+            format!("https://git.example.com/group/repo/-/commit/{commit_hash}")
+        );
+        assert_eq!(
+            gitlab.format_issue_url("12").as_deref(),
+            Some("https://git.example.com/group/repo/-/issues/12")
+        );
+        let gitea = repo("Forgejo", "https://git.example.com/user/repo");
+        assert_eq!(
+            gitea.format_issue_url("12").as_deref(),
+            Some("https://git.example.com/user/repo/issues/12")
+        );
+        assert_eq!(
+            repo("bitbucket", "ssh://git@git.example.com:7999/proj/repo.git")
+                .format_commit_url(commit_hash),
+            format!("https://git.example.com/projects/proj/repos/repo/commits/{commit_hash}")
+        );
+        let gerrit = repo("gerrit", "ssh://user@review.example.com:29418/project");
+        assert_eq!(
+            gerrit.format_commit_url(commit_hash),
+            format!("https://review.example.com/q/{commit_hash}")
+        );
+        assert_eq!(gerrit.format_issue_url("12"), None);
+        assert!(Forge::from_str("svn").is_err());
+    }
 }
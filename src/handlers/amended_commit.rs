@@ -0,0 +1,223 @@
+use crate::delta::{State, StateMachine};
+use crate::edits;
+use crate::git_config::GitConfig;
+use crate::minusplus::*;
+use crate::utils::process::{self, CallingProcess};
+
+/// The author lines and the message of a commit of `git show` or `git log` output. A commit
+/// whose author and author date are those of the commit before it is taken to be another version
+/// of it, as left by `git commit --amend` or a rebase, e.g. in `git show HEAD@{1} HEAD`, unless
+/// one of them is the parent of the other (see --amended-commit-messages).
+#[derive(Debug, Default)]
+pub struct CommitVersion {
+    commit: Option<String>,
+    author: Vec<String>,
+    message: Vec<String>,
+    // Whether the commit is another version of the previous one, known at its first message line.
+    amends_previous: Option<bool>,
+}
+
+impl<'a> StateMachine<'a> {
+    /// Keep the previous commit, to compare the one starting at the current line with it.
+    pub fn start_commit_version(&mut self) {
+        self.commit_versions[Minus] = std::mem::take(&mut self.commit_versions[Plus]);
+        self.commit_versions[Plus].commit = self.current_commit.clone();
+    }
+
+    /// Highlight the words of a line of a commit message which differ from those of the message
+    /// of the previous version of the commit, like the changes within lines of a diff: a line
+    /// which is not in the previous message is painted in plus-style, with the words which
+    /// changed in plus-emph-style.
+    pub fn handle_amended_commit_message_line(&mut self) -> std::io::Result<bool> {
+        if self.state != State::CommitMeta || !self.config.amended_commit_messages {
+            return Ok(false);
+        }
+        if ["Author:", "Date:", "AuthorDate:"]
+            .iter()
+            .any(|field| self.line.starts_with(field))
+        {
+            self.commit_versions[Plus].author.push(self.line.clone());
+            return Ok(false);
+        }
+        let Some(text) = self.line.strip_prefix("    ") else {
+            return Ok(false);
+        };
+        let amends_previous = match self.commit_versions[Plus].amends_previous {
+            Some(amends_previous) => amends_previous,
+            None => {
+                let amends_previous = self.amends_previous_commit();
+                self.commit_versions[Plus].amends_previous = Some(amends_previous);
+                amends_previous
+            }
+        };
+        self.commit_versions[Plus].message.push(text.to_string());
+        let previous = &self.commit_versions[Minus];
+        if !amends_previous || previous.message.iter().any(|line| line == text) {
+            return Ok(false);
+        }
+        let config = self.config;
+        let (_, plus_sections, _) = edits::infer_edits(
+            previous.message.iter().map(String::as_str).collect(),
+            vec![text],
+            vec![config.minus_style; previous.message.len()],
+            config.minus_emph_style,
+            vec![config.plus_style],
+            config.plus_emph_style,
+            &edits::Tokenizer::for_syntax("Plain Text", config),
+            config.max_line_distance,
+            config.max_line_distance_for_naively_paired_lines,
+            config.max_highlight_length,
+        );
+        let line: String = plus_sections
+            .iter()
+            .flatten()
+            .map(|(style, text)| style.paint(*text).to_string())
+            .collect();
+        self.painter.emit()?;
+        writeln!(self.painter.writer, "    {line}")?;
+        Ok(true)
+    }
+
+    // Whether the current commit is another version of the previous one: they have the same
+    // author lines, and neither follows the other in history. Consecutive commits of `git log`
+    // output are a commit and its parent, unless it walks the reflog.
+    fn amends_previous_commit(&self) -> bool {
+        let (previous, current) = (&self.commit_versions[Minus], &self.commit_versions[Plus]);
+        if previous.author.len() < 2
+            || previous.author != current.author
+            || previous.commit == current.commit
+        {
+            return false;
+        }
+        let lists_versions = match &*process::calling_process() {
+            CallingProcess::GitLog(cmd) => {
+                cmd.long_options.contains("--walk-reflogs") || cmd.short_options.contains("-g")
+            }
+            _ => true,
+        };
+        lists_versions
+            && match (&previous.commit, &current.commit) {
+                (Some(previous), Some(current)) => !self.are_parent_and_child(previous, current),
+                _ => false,
+            }
+    }
+
+    // Whether either commit is a parent of the other, if they are found in the repository.
+    fn are_parent_and_child(&self, commit_1: &str, commit_2: &str) -> bool {
+        let Some(repo) = self.config.git_config().and_then(GitConfig::repo) else {
+            return false;
+        };
+        let find = |rev: &str| {
+            repo.revparse_single(rev)
+                .and_then(|object| object.peel_to_commit())
+        };
+        let are_parent_and_child = match (find(commit_1), find(commit_2)) {
+            (Ok(commit_1), Ok(commit_2)) => {
+                commit_1.parent_ids().any(|id| id == commit_2.id())
+                    || commit_2.parent_ids().any(|id| id == commit_1.id())
+            }
+            _ => false,
+        };
+        are_parent_and_child
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::integration_test_utils::{self, DeltaTest};
+    use crate::utils::process::tests::FakeParentArgs;
+
+    #[test]
+    fn test_amended_commit_message() {
+        DeltaTest::with_args(&[
+            "--amended-commit-messages",
+            "--plus-style",
+            "green",
+            "--plus-emph-style",
+            "bold green",
+            "--commit-style",
+            "raw",
+            "--commit-decoration-style",
+            "none",
+        ])
+        .explain_ansi()
+        .with_input(AMENDED_COMMITS)
+        .expect_contains(
+            "
+commit 2222222222222222222222222222222222222222
+Author: Ada <ada@example.com>
+Date:   Mon Oct 12 10:00:00 2026 +0200
+
+    (green)Fix the(green) (bold green)tokenizer(green) for tabs(normal)
+
+    The tokenizer no longer splits words at tabs.
+    (green)It is faster, too.(normal)
+
+commit 3333333333333333333333333333333333333333
+Author: Ada <ada@example.com>
+Date:   Tue Oct 13 10:00:00 2026 +0200
+
+    Fix the tokenizer for tabs
+",
+        );
+    }
+
+    #[test]
+    fn test_amended_commit_message_is_opt_in() {
+        let output = DeltaTest::with_args(&["--commit-style", "raw"])
+            .explain_ansi()
+            .with_input(AMENDED_COMMITS);
+        // The commit lines are raw, so that only the message lines could be styled.
+        assert_eq!(
+            output.output.trim_start_matches("(normal)").trim_end(),
+            AMENDED_COMMITS.trim_end()
+        );
+    }
+
+    #[test]
+    fn test_git_log_output_is_unchanged() {
+        // The consecutive commits of `git log` are a commit and its parent, even when they have
+        // the same author and date.
+        let _args = FakeParentArgs::for_scope("git log -p");
+        let run = |args: &[&str]| {
+            let config = integration_test_utils::make_config_from_args(args);
+            integration_test_utils::run_delta(AMENDED_COMMITS, &config)
+        };
+        assert_eq!(run(&["--amended-commit-messages"]), run(&[]));
+    }
+
+    #[test]
+    fn test_git_log_walking_reflogs_compares_versions() {
+        let _args = FakeParentArgs::for_scope("git log -g -p");
+        let run = |args: &[&str]| {
+            let config = integration_test_utils::make_config_from_args(args);
+            integration_test_utils::run_delta(AMENDED_COMMITS, &config)
+        };
+        assert_ne!(run(&["--amended-commit-messages"]), run(&[]));
+    }
+
+    const AMENDED_COMMITS: &str = "\
+commit 1111111111111111111111111111111111111111
+Author: Ada <ada@example.com>
+Date:   Mon Oct 12 10:00:00 2026 +0200
+
+    Fix the parser for tabs
+
+    The tokenizer no longer splits words at tabs.
+
+commit 2222222222222222222222222222222222222222
+Author: Ada <ada@example.com>
+Date:   Mon Oct 12 10:00:00 2026 +0200
+
+    Fix the tokenizer for tabs
+
+    The tokenizer no longer splits words at tabs.
+    It is faster, too.
+
+commit 3333333333333333333333333333333333333333
+Author: Ada <ada@example.com>
+Date:   Tue Oct 13 10:00:00 2026 +0200
+
+    Fix the tokenizer for tabs
+";
+}
@@ -1,20 +1,27 @@
+use ansi_term::Color;
+use bytelines::ByteLines;
 use chrono::{DateTime, FixedOffset};
 use lazy_static::lazy_static;
 use regex::Regex;
 use std::borrow::Cow;
+use std::fs;
+use std::io::BufRead;
 use unicode_width::UnicodeWidthStr;
 
-use crate::ansi::measure_text_width;
+use crate::ansi::{self, measure_text_width};
 use crate::color;
 use crate::config;
 use crate::config::delta_unreachable;
 use crate::delta::{self, State, StateMachine};
 use crate::fatal;
+use crate::features;
+use crate::features::side_by_side::{self, Left, LeftRight, Right};
 use crate::format::{self, FormatStringSimple, Placeholder};
 use crate::format::{make_placeholder_regex, parse_line_number_format};
+use crate::handlers::commit_meta;
 use crate::paint::{self, BgShouldFill, StyleSectionSpecifier};
 use crate::style::Style;
-use crate::utils::process;
+use crate::utils::{self, process};
 
 #[derive(Clone, Debug)]
 pub enum BlameLineNumbers {
@@ -47,8 +54,21 @@ impl<'a> StateMachine<'a> {
                     &BLAME_PLACEHOLDER_REGEX,
                     false,
                 );
-                let mut formatted_blame_metadata =
-                    format_blame_metadata(&format_data, &blame, self.config);
+                let config = self.config;
+                let ignored_note = self
+                    .blame_ignore_revs
+                    .get_or_insert_with(|| BlameIgnoreRevs::for_repo(config))
+                    .ignored(&blame)
+                    .map(|comment| match comment {
+                        Some(comment) => format!("(ignored: {comment})"),
+                        None => "(ignored)".to_string(),
+                    });
+                let mut formatted_blame_metadata = format_blame_metadata(
+                    &format_data,
+                    &blame,
+                    ignored_note.as_deref(),
+                    self.config,
+                );
                 let key = formatted_blame_metadata.clone();
                 let is_repeat = previous_key.as_deref() == Some(&key);
                 if is_repeat {
@@ -59,40 +79,206 @@ impl<'a> StateMachine<'a> {
                     self.blame_metadata_style(&key, previous_key.as_deref(), is_repeat);
                 let code_style = self.config.blame_code_style.unwrap_or(metadata_style);
                 let separator_style = self.config.blame_separator_style.unwrap_or(code_style);
+                // Boundary and ignored commits keep their place in the palette, but their metadata
+                // is de-emphasized, since they are unlikely to be the real author of the line: their
+                // style is applied on top of that of the palette, whose colors it does not set.
+                let metadata_style = self.blame_heatmap_style(&blame, metadata_style);
+                let metadata_style = if ignored_note.is_some() {
+                    self.config.blame_ignored_style.on_top_of(metadata_style)
+                } else if blame.commit.starts_with('^') {
+                    self.config.blame_boundary_style.on_top_of(metadata_style)
+                } else {
+                    metadata_style
+                };
 
                 let (nr_prefix, line_number, nr_suffix) = format_blame_line_number(
                     &self.config.blame_separator_format,
                     blame.line_number,
                     is_repeat,
                 );
-
-                write!(
-                    self.painter.writer,
-                    "{}{}{}{}",
-                    metadata_style.paint(&formatted_blame_metadata),
+                let painted_line_number = format!(
+                    "{}{}{}",
                     separator_style.paint(nr_prefix),
                     metadata_style.paint(&line_number),
                     separator_style.paint(nr_suffix),
-                )?;
+                );
 
-                // Emit syntax-highlighted code
                 if self.state == State::Unknown {
                     self.painter.set_syntax(self.get_filename().as_deref());
                     self.painter.set_highlighter();
                 }
                 self.state = State::Blame(key);
-                self.painter.syntax_highlight_and_paint_line(
-                    &format!("{}\n", blame.code),
-                    StyleSectionSpecifier::Style(code_style),
-                    self.state.clone(),
-                    BgShouldFill::default(),
-                );
+                let code = format!("{}\n", blame.code);
+                if self.config.blame_side_by_side {
+                    let subject = if is_repeat {
+                        None
+                    } else {
+                        self.blame_commit_subject(blame.commit)
+                    };
+                    let history = match subject {
+                        Some(subject) => format!("{formatted_blame_metadata} {subject}"),
+                        None => formatted_blame_metadata,
+                    };
+                    // The padding of the metadata is not truncated with the panel.
+                    self.paint_blame_panels(
+                        &code,
+                        painted_line_number,
+                        code_style,
+                        history.trim_end(),
+                        metadata_style,
+                    );
+                } else {
+                    write!(
+                        self.painter.writer,
+                        "{}{}",
+                        metadata_style.paint(&formatted_blame_metadata),
+                        painted_line_number,
+                    )?;
+                    // Emit syntax-highlighted code
+                    self.painter.syntax_highlight_and_paint_line(
+                        &code,
+                        StyleSectionSpecifier::Style(code_style),
+                        self.state.clone(),
+                        BgShouldFill::default(),
+                    );
+                }
                 handled_line = true
             }
         }
         Ok(handled_line)
     }
 
+    // Paint a blame line as a row of two panels (--blame-side-by-side): the line number and the
+    // syntax-highlighted code on the left, and the history of the line on the right. Each panel is
+    // filled with the background color of its style.
+    fn paint_blame_panels(
+        &mut self,
+        code: &str,
+        painted_line_number: String,
+        code_style: Style,
+        history: &str,
+        metadata_style: Style,
+    ) {
+        let painted_from = self.painter.output_buffer.len();
+        self.painter.syntax_highlight_and_paint_line(
+            code,
+            StyleSectionSpecifier::Style(code_style),
+            self.state.clone(),
+            BgShouldFill::No,
+        );
+        let painted_code = self.painter.output_buffer.split_off(painted_from);
+        let config = self.config;
+        let fill_panel = |mut panel: String, side, style: Style| {
+            let width = config.side_by_side_data[side].width;
+            let text_width = measure_text_width(&panel);
+            if text_width < width {
+                panel.push_str(&style.paint(" ".repeat(width - text_width)).to_string());
+            }
+            panel
+        };
+        let panels = LeftRight::new(
+            Some(fill_panel(
+                painted_line_number + painted_code.trim_end_matches('\n'),
+                Left,
+                code_style,
+            )),
+            Some(fill_panel(
+                metadata_style.paint(history).to_string(),
+                Right,
+                metadata_style,
+            )),
+        );
+        side_by_side::paint_panel_row(panels, ' ', &mut self.painter.output_buffer, config);
+    }
+
+    // The subject of the commit of a blame line, whose hash may be marked as that of a boundary
+    // or ignored commit.
+    fn blame_commit_subject(&mut self, commit: &str) -> Option<String> {
+        let commit = commit.trim_start_matches(['^', '?', '*']);
+        let config = self.config;
+        self.blame_commit_subjects
+            .entry(commit.to_string())
+            .or_insert_with(|| commit_meta::read_commit_subject(commit, config))
+            .clone()
+    }
+
+    /// If the current line is the first of git blame output, read the following blame lines from
+    /// the input into the lookahead buffer, so that the range of the times of their commits is
+    /// known (--blame-heatmap). At most --max-buffer-lines are read: the times of later lines are
+    /// clamped to the range.
+    pub fn read_blame_ahead<I: BufRead>(&mut self, lines: &mut ByteLines<I>) {
+        if self.state != State::Unknown {
+            return;
+        }
+        let timestamp_format = &self.config.blame_timestamp_format;
+        let Some(blame) = parse_git_blame_line(&self.line, timestamp_format) else {
+            return;
+        };
+        let (mut oldest, mut newest) = (blame.time.timestamp(), blame.time.timestamp());
+        let max_lines = self.config.max_buffer_lines;
+        let mut n_read = 0;
+        loop {
+            if n_read == self.hunk_lookahead.len() {
+                if max_lines > 0 && n_read >= max_lines {
+                    break;
+                }
+                match lines.next() {
+                    Some(Ok(raw_line)) => self.hunk_lookahead.push_back(raw_line.to_vec()),
+                    _ => break,
+                }
+            }
+            let line =
+                ansi::strip_ansi_codes(&String::from_utf8_lossy(&self.hunk_lookahead[n_read]));
+            n_read += 1;
+            match parse_git_blame_line(&line, timestamp_format) {
+                Some(blame) => {
+                    oldest = oldest.min(blame.time.timestamp());
+                    newest = newest.max(blame.time.timestamp());
+                }
+                None => break,
+            }
+        }
+        self.blame_time_range = Some((oldest, newest));
+    }
+
+    // The style of the metadata of the blame line, on the gradient from blame-heatmap-oldest to
+    // blame-heatmap-newest according to the time of its commit. A color which is not set by those
+    // styles is that of the palette.
+    fn blame_heatmap_style(&self, blame: &BlameLine, palette_style: Style) -> Style {
+        let Some((oldest, newest)) = self.blame_time_range.filter(|_| self.config.blame_heatmap)
+        else {
+            return palette_style;
+        };
+        let fraction = if newest > oldest {
+            (blame.time.timestamp().clamp(oldest, newest) - oldest) as f64
+                / (newest - oldest) as f64
+        } else {
+            1.0
+        };
+        let (from, to) = (
+            self.config.blame_heatmap_oldest,
+            self.config.blame_heatmap_newest,
+        );
+        let mut style = if fraction < 0.5 { from } else { to };
+        let (from, to) = (from.ansi_term_style, to.ansi_term_style);
+        let palette_style = palette_style.ansi_term_style;
+        let true_color = self.config.true_color;
+        style.ansi_term_style.foreground = heatmap_color(
+            (from.foreground, to.foreground),
+            palette_style.foreground,
+            fraction,
+            true_color,
+        );
+        style.ansi_term_style.background = heatmap_color(
+            (from.background, to.background),
+            palette_style.background,
+            fraction,
+            true_color,
+        );
+        style.is_syntax_highlighted = true;
+        style
+    }
+
     fn get_filename(&self) -> Option<String> {
         match &*process::calling_process() {
             process::CallingProcess::GitBlame(command_line) => command_line.last_arg.clone(),
@@ -187,9 +373,25 @@ impl<'a> StateMachine<'a> {
     }
 }
 
+// The color at `fraction` of the way between the colors of the oldest and newest commits, or if
+// either is not set, the nearer one, or else the color of the palette.
+fn heatmap_color(
+    colors: (Option<Color>, Option<Color>),
+    palette_color: Option<Color>,
+    fraction: f64,
+    true_color: bool,
+) -> Option<Color> {
+    match colors {
+        (Some(from), Some(to)) => Some(color::blend_colors(from, to, fraction, true_color)),
+        (from, to) => (if fraction < 0.5 { from } else { to }).or(palette_color),
+    }
+}
+
 #[derive(Debug)]
 pub struct BlameLine<'a> {
     pub commit: &'a str,
+    // The path of the file relative to the repository root, if displayed by git.
+    pub path: Option<&'a str>,
     pub author: &'a str,
     pub time: DateTime<FixedOffset>,
     pub line_number: usize,
@@ -204,9 +406,10 @@ lazy_static! {
         r"(?x)
 ^
 (
-    \^?[0-9a-f]{4,40} # commit hash (^ is 'boundary commit' marker)
+    [\^?*]?[0-9a-f]{4,40} # commit hash (^ is 'boundary commit' marker, ? and * mark lines
+                           # affected by ignored commits: see blame.markIgnoredLines)
 )
-(?: [^(]+)?        # optional file name (unused; present if file has been renamed; TODO: inefficient?)
+(?:\ ([^(]+))?     # optional file name (present with -f, or if file has been renamed)
 [\ ]
 \(                 # open ( which the previous file name may not contain in case a name does (which is more likely)
 (
@@ -234,17 +437,19 @@ pub fn parse_git_blame_line<'a>(line: &'a str, timestamp_format: &str) -> Option
     let caps = BLAME_LINE_REGEX.captures(line)?;
 
     let commit = caps.get(1).unwrap().as_str();
-    let author = caps.get(2).unwrap().as_str();
-    let timestamp = caps.get(3).unwrap().as_str();
+    let path = caps.get(2).map(|m| m.as_str().trim_end());
+    let author = caps.get(3).unwrap().as_str();
+    let timestamp = caps.get(4).unwrap().as_str();
 
     let time = DateTime::parse_from_str(timestamp, timestamp_format).ok()?;
 
-    let line_number = caps.get(4).unwrap().as_str().parse::<usize>().ok()?;
+    let line_number = caps.get(5).unwrap().as_str().parse::<usize>().ok()?;
 
-    let code = caps.get(5).unwrap().as_str();
+    let code = caps.get(6).unwrap().as_str();
 
     Some(BlameLine {
         commit,
+        path,
         author,
         time,
         line_number,
@@ -255,12 +460,67 @@ pub fn parse_git_blame_line<'a>(line: &'a str, timestamp_format: &str) -> Option
 lazy_static! {
     // line numbers (`{n}`) change with every line and are set separately via `blame-separator-format`
     pub static ref BLAME_PLACEHOLDER_REGEX: Regex =
-        format::make_placeholder_regex(&["timestamp", "author", "commit"]);
+        format::make_placeholder_regex(&["timestamp", "author", "commit", "ignored", "path"]);
+}
+
+/// The commits listed in the file named by git config blame.ignoreRevsFile, or else in
+/// .git-blame-ignore-revs, together with the comment preceding them in that file, if any.
+#[derive(Debug, Default)]
+pub struct BlameIgnoreRevs(Vec<(String, Option<String>)>);
+
+impl BlameIgnoreRevs {
+    pub fn for_repo(config: &config::Config) -> Self {
+        let path = config
+            .git_config()
+            .and_then(|git_config| git_config.get::<String>("blame.ignoreRevsFile"))
+            .filter(|path| !path.is_empty())
+            .unwrap_or_else(|| ".git-blame-ignore-revs".to_string());
+        config
+            .cwd_of_delta_process
+            .as_ref()
+            .and_then(|cwd| fs::read_to_string(cwd.join(path)).ok())
+            .map(|contents| Self::parse(&contents))
+            .unwrap_or_default()
+    }
+
+    /// Parse a file in the format of git blame --ignore-revs-file. A comment applies to the
+    /// commits on the following lines, up to the next blank line.
+    fn parse(contents: &str) -> Self {
+        let mut revs = Vec::new();
+        let mut comment = None;
+        for line in contents.lines().map(str::trim) {
+            if line.is_empty() {
+                comment = None;
+            } else if let Some(text) = line.strip_prefix('#') {
+                comment = Some(text.trim().to_string()).filter(|text| !text.is_empty());
+            } else {
+                let (rev, inline_comment) = match line.split_once('#') {
+                    Some((rev, text)) => (rev.trim(), Some(text.trim().to_string())),
+                    None => (line, None),
+                };
+                revs.push((rev.to_string(), inline_comment.or_else(|| comment.clone())));
+            }
+        }
+        Self(revs)
+    }
+
+    /// If the commit of the blame line is ignored, return the comment of the ignored commit.
+    pub fn ignored(&self, blame: &BlameLine) -> Option<Option<&str>> {
+        if blame.commit.starts_with('*') {
+            return Some(None);
+        }
+        let hash = blame.commit.trim_start_matches(['^', '?']);
+        self.0
+            .iter()
+            .find(|(rev, _)| rev.starts_with(hash))
+            .map(|(_, comment)| comment.as_deref())
+    }
 }
 
 pub fn format_blame_metadata(
     format_data: &[format::FormatStringPlaceholderData],
     blame: &BlameLine,
+    ignored_note: Option<&str>,
     config: &config::Config,
 ) -> String {
     let mut s = String::new();
@@ -274,12 +534,24 @@ pub fn format_blame_metadata(
         let field = match placeholder.placeholder {
             Some(Placeholder::Str("timestamp")) => {
                 Some(Cow::from(match &config.blame_timestamp_output_format {
-                    Some(time_format) => blame.time.format(time_format).to_string(),
+                    Some(time_format) => blame
+                        .time
+                        .format(&config.locale.localize_time_format(time_format))
+                        .to_string(),
                     None => chrono_humanize::HumanTime::from(blame.time).to_string(),
                 }))
             }
             Some(Placeholder::Str("author")) => Some(Cow::from(blame.author)),
             Some(Placeholder::Str("commit")) => Some(delta::format_raw_line(blame.commit, config)),
+            Some(Placeholder::Str("ignored")) => Some(Cow::from(ignored_note.unwrap_or_default())),
+            Some(Placeholder::Str("path")) => Some(Cow::from(match blame.path {
+                Some(path) => {
+                    let mut path = path.to_string();
+                    utils::path::relativize_repo_path_maybe(&mut path, config);
+                    path
+                }
+                None => String::new(),
+            })),
             None => None,
             _ => unreachable!("Unexpected `git blame` input"),
         };
@@ -287,12 +559,18 @@ pub fn format_blame_metadata(
             // Unicode modifier should not be counted as character to allow a consistent padding
             let unicode_modifier_width =
                 field.as_ref().chars().count() - UnicodeWidthStr::width(field.as_ref());
-            s.push_str(&format::pad(
+            let field = format::pad(
                 &field,
                 width + unicode_modifier_width,
                 alignment_spec,
                 placeholder.precision,
-            ))
+            );
+            match (&placeholder.placeholder, blame.path) {
+                (Some(Placeholder::Str("path")), Some(path)) if config.hyperlinks => s.push_str(
+                    &format_path_hyperlink(path, blame.line_number, &field, config),
+                ),
+                _ => s.push_str(&field),
+            }
         }
         suffix = placeholder.suffix.as_str();
     }
@@ -300,6 +578,25 @@ pub fn format_blame_metadata(
     s
 }
 
+// Hyperlink `text` to the line of the file at `path`, which is relative to the repository root.
+fn format_path_hyperlink<'a>(
+    path: &str,
+    line_number: usize,
+    text: &'a str,
+    config: &config::Config,
+) -> Cow<'a, str> {
+    // If delta was invoked by git then its current directory is the repository root.
+    match &config.cwd_of_delta_process {
+        Some(repo_root) => features::hyperlinks::format_osc8_file_hyperlink(
+            repo_root.join(path),
+            Some(line_number),
+            text,
+            config,
+        ),
+        None => Cow::from(text),
+    }
+}
+
 pub fn format_blame_line_number(
     format: &BlameLineNumbers,
     line_number: usize,
@@ -307,19 +604,16 @@ pub fn format_blame_line_number(
 ) -> (&str, String, &str) {
     let (format, empty) = match &format {
         BlameLineNumbers::PerBlock(format) => (format, is_repeat),
-        BlameLineNumbers::Every(n, format) => (format, is_repeat && line_number % n != 0),
+        BlameLineNumbers::Every(n, format) => {
+            (format, is_repeat && !line_number.is_multiple_of(*n))
+        }
         BlameLineNumbers::On(format) => (format, false),
     };
     let mut result = String::new();
 
     // depends on defaults being set when parsing arguments
-    let line_number = if format.width.is_some() {
-        format::pad(
-            line_number,
-            format.width.unwrap(),
-            format.alignment_spec.unwrap(),
-            None,
-        )
+    let line_number = if let Some(width) = format.width {
+        format::pad(line_number, width, format.alignment_spec.unwrap(), None)
     } else {
         String::new()
     };
@@ -412,7 +706,7 @@ mod tests {
         let line =
             "61f180c8 (Kangwook Lee (이강욱) 2021-06-09 23:33:59 +0900 130)     let mut output_type =";
         let caps = BLAME_LINE_REGEX.captures(line).unwrap();
-        assert_eq!(caps.get(2).unwrap().as_str(), "Kangwook Lee (이강욱)");
+        assert_eq!(caps.get(3).unwrap().as_str(), "Kangwook Lee (이강욱)");
     }
 
     #[test]
@@ -421,7 +715,7 @@ mod tests {
         let blame = make_blame_line_with_time("1996-12-19T16:39:57-08:00");
         let config = integration_test_utils::make_config_from_args(&[]);
         let regex = Regex::new(r"^\d+ years ago$").unwrap();
-        let result = format_blame_metadata(&[format_data], &blame, &config);
+        let result = format_blame_metadata(&[format_data], &blame, None, &config);
         assert!(regex.is_match(result.trim()));
     }
 
@@ -432,7 +726,7 @@ mod tests {
         let config = integration_test_utils::make_config_from_args(&[
             "--blame-timestamp-output-format=%Y-%m-%d %H:%M",
         ]);
-        let result = format_blame_metadata(&[format_data], &blame, &config);
+        let result = format_blame_metadata(&[format_data], &blame, None, &config);
         assert_eq!(result.trim(), "1996-12-19 16:39");
     }
 
@@ -444,11 +738,11 @@ mod tests {
 
         let format_data1 = make_format_data_with_placeholder("author");
         let blame1 = make_blame_line_with_author("E\u{301}dith Piaf");
-        let result1 = format_blame_metadata(&[format_data1], &blame1, &config);
+        let result1 = format_blame_metadata(&[format_data1], &blame1, None, &config);
 
         let format_data2 = make_format_data_with_placeholder("author");
         let blame2 = make_blame_line_with_author("Edith Piaf");
-        let result2 = format_blame_metadata(&[format_data2], &blame2, &config);
+        let result2 = format_blame_metadata(&[format_data2], &blame2, None, &config);
 
         assert_eq!(
             count_trailing_spaces(result1),
@@ -456,6 +750,33 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_format_blame_metadata_with_path() {
+        let format_data = make_format_data_with_placeholder("path");
+        let mut blame = make_blame_line_with_author("Edith Piaf");
+        let mut config = integration_test_utils::make_config_from_args(&["--relative-paths"]);
+        config.cwd_relative_to_repo_root = Some("src/utils/".into());
+        let result = format_blame_metadata(&[format_data], &blame, None, &config);
+        assert_eq!(result.trim(), "");
+
+        blame.path = Some("src/cli.rs");
+        let format_data = make_format_data_with_placeholder("path");
+        let result = format_blame_metadata(&[format_data], &blame, None, &config);
+        assert_eq!(
+            result.trim(),
+            format!("..{0}cli.rs", std::path::MAIN_SEPARATOR)
+        );
+    }
+
+    #[test]
+    fn test_blame_line_regex_with_path() {
+        let line = "ea82f2d0 src/cli.rs (Dan Davison 2021-08-22 18:20:19 -0700 120) fn main() {";
+        let blame = parse_git_blame_line(line, "%Y-%m-%d %H:%M:%S %z").unwrap();
+        assert_eq!(blame.path, Some("src/cli.rs"));
+        assert_eq!(blame.author, "Dan Davison");
+        assert_eq!(blame.line_number, 120);
+    }
+
     #[test]
     fn test_color_assignment() {
         let mut writer = Cursor::new(vec![0; 512]);
@@ -538,6 +859,202 @@ mod tests {
         );
     }
 
+    const IGNORE_REVS: &str = "\
+# Reformat with rustfmt
+aaaaaaa1111111111111111111111111111111111
+aaaaaaa2222222222222222222222222222222222
+
+bbbbbbb1111111111111111111111111111111111 # Rename variables
+ccccccc1111111111111111111111111111111111
+";
+
+    #[test]
+    fn test_parse_blame_ignore_revs() {
+        let revs = BlameIgnoreRevs::parse(IGNORE_REVS);
+        assert_eq!(
+            revs.0
+                .iter()
+                .map(|(rev, comment)| (&rev[..8], comment.as_deref()))
+                .collect::<Vec<_>>(),
+            vec![
+                ("aaaaaaa1", Some("Reformat with rustfmt")),
+                ("aaaaaaa2", Some("Reformat with rustfmt")),
+                ("bbbbbbb1", Some("Rename variables")),
+                ("ccccccc1", None),
+            ]
+        );
+        let ignored = |commit| {
+            let mut blame = make_blame_line_with_author("");
+            blame.commit = commit;
+            revs.ignored(&blame)
+                .map(|comment| comment.map(str::to_string))
+        };
+        assert_eq!(
+            ignored("aaaaaaa2"),
+            Some(Some("Reformat with rustfmt".to_string()))
+        );
+        assert_eq!(ignored("^ccccccc1"), Some(None));
+        assert_eq!(ignored("*1234567"), Some(None));
+        assert_eq!(ignored("?1234567"), None);
+        assert_eq!(ignored("aaaaaaa3"), None);
+    }
+
+    #[test]
+    fn test_boundary_and_ignored_commit_styles() {
+        let mut writer = Cursor::new(Vec::new());
+        let config = integration_test_utils::make_config_from_args(&[
+            "--blame-format",
+            "{commit:<9} {ignored:<25}",
+            "--blame-palette",
+            "green",
+            "--blame-code-style",
+            "normal",
+            "--blame-separator-format",
+            "│",
+            "--blame-boundary-style",
+            "blue",
+            "--blame-ignored-style",
+            "red",
+        ]);
+        let mut machine = StateMachine::new(&mut writer, &config);
+        machine.blame_ignore_revs = Some(BlameIgnoreRevs::parse(IGNORE_REVS));
+        for line in [
+            "aaaaaaa2 (Dan Davison 2021-08-22 18:20:19 -0700 1) A",
+            "^0123456 (Dan Davison 2020-07-18 15:34:43 -0400 2) B",
+            "ddddddd1 (Dan Davison 2020-07-18 15:34:43 -0400 3) C",
+        ] {
+            machine.line = line.into();
+            machine.raw_line = line.into();
+            machine.handle_blame_line().unwrap();
+        }
+        machine.painter.emit().unwrap();
+        let output = String::from_utf8(writer.into_inner()).unwrap();
+        assert_eq!(
+            crate::ansi::explain_ansi(&output, false),
+            "\
+(red green)aaaaaaa2  (ignored: Reformat with rustfmt)(normal)│ A
+(blue green)^0123456                           (normal)│ B
+(normal green)ddddddd1                           (normal)│ C
+"
+        );
+    }
+
+    #[test]
+    fn test_default_boundary_commit_style_keeps_palette_color() {
+        let output = integration_test_utils::DeltaTest::with_args(&[
+            "--blame-format",
+            "{commit:<8}",
+            "--blame-palette",
+            "green",
+            "--blame-code-style",
+            "normal",
+            "--blame-separator-format",
+            "│",
+        ])
+        .explain_ansi()
+        .with_input(
+            "\
+aaaaaaa2 (Dan Davison 2021-08-22 18:20:19 -0700 1) A
+^0123456 (Dan Davison 2020-07-18 15:34:43 -0400 2) B
+",
+        );
+        assert_eq!(
+            output.output,
+            "\
+(normal green)aaaaaaa2(normal)│ A
+(dim normal green)^0123456(normal)│ B
+"
+        );
+    }
+
+    #[test]
+    fn test_blame_heatmap() {
+        let output = integration_test_utils::DeltaTest::with_args(&[
+            "--true-color",
+            "always",
+            "--blame-heatmap",
+            "--blame-heatmap-oldest",
+            "#000000",
+            "--blame-heatmap-newest",
+            "#c8c864",
+            "--blame-format",
+            "{commit:<8}",
+            "--blame-separator-format",
+            "│",
+            "--blame-code-style",
+            "normal",
+        ])
+        .with_input(
+            "\
+aaaaaaa1 (Dan Davison 2020-01-01 00:00:00 +0000 1) A
+bbbbbbb1 (Dan Davison 2022-01-01 00:00:00 +0000 2) B
+ccccccc1 (Dan Davison 2021-01-01 00:00:00 +0000 3) C
+ccccccc1 (Dan Davison 2021-01-01 00:00:00 +0000 4) D
+",
+        );
+        let output = crate::ansi::explain_ansi(&output.raw_output, false);
+        assert!(
+            output.contains("(\"#000000\" \"#000000\")aaaaaaa1(normal)│ A"),
+            "{}",
+            output
+        );
+        assert!(
+            output.contains("(\"#c8c864\" \"#222222\")bbbbbbb1(normal)│ B"),
+            "{}",
+            output
+        );
+        // The foreground is on the gradient, and the background is still that of the palette.
+        // 2021-01-01 is 366 days after 2020-01-01, in the middle of the range of 731 days.
+        assert!(
+            output.contains("(\"#646432\" \"#444444\")ccccccc1(normal)│ C"),
+            "{}",
+            output
+        );
+    }
+
+    #[test]
+    fn test_blame_side_by_side() {
+        let config = integration_test_utils::make_config_from_args(&[
+            "--blame-side-by-side",
+            "--width",
+            "40",
+            "--blame-format",
+            "{author:<6}",
+            "--blame-code-style",
+            "normal",
+        ]);
+        let mut writer = Cursor::new(Vec::new());
+        let mut machine = StateMachine::new(&mut writer, &config);
+        machine
+            .blame_commit_subjects
+            .insert("aaaaaaa1".to_string(), Some("Add a and b".to_string()));
+        machine
+            .blame_commit_subjects
+            .insert("bbbbbbb1".to_string(), None);
+        for line in [
+            "aaaaaaa1 (Ada 2020-01-01 00:00:00 +0000 1) fn a() {}",
+            "aaaaaaa1 (Ada 2020-01-01 00:00:00 +0000 2) fn b() {}",
+            "^bbbbbbb1 (Bob 2021-01-01 00:00:00 +0000 3) fn a_much_longer_name() {}",
+        ] {
+            machine.line = line.to_string();
+            assert!(machine.handle_blame_line().unwrap());
+        }
+        machine.painter.emit().unwrap();
+        drop(machine);
+        let output = String::from_utf8(writer.into_inner()).unwrap();
+        // Both panels are 20 wide, and the metadata of a repeated commit is blank.
+        assert_eq!(
+            crate::ansi::strip_ansi_codes(&output)
+                .lines()
+                .collect::<Vec<_>>(),
+            [
+                "│  1 │ fn a() {}    Ada    Add a and b  ",
+                &format!("│  2 │ fn b() {{}}{}", " ".repeat(24)),
+                "│  3 │ fn a_much_lo→Bob                 ",
+            ]
+        );
+    }
+
     fn hashmap_items(hashmap: &HashMap<String, String>) -> Vec<(&str, &str)> {
         hashmap
             .iter()
@@ -546,10 +1063,11 @@ mod tests {
             .collect()
     }
 
-    fn make_blame_line_with_time(timestamp: &str) -> BlameLine {
+    fn make_blame_line_with_time(timestamp: &str) -> BlameLine<'_> {
         let time = chrono::DateTime::parse_from_rfc3339(timestamp).unwrap();
         BlameLine {
             commit: "",
+            path: None,
             author: "",
             time,
             line_number: 0,
@@ -557,16 +1075,19 @@ mod tests {
         }
     }
 
-    fn make_format_data_with_placeholder(placeholder: &str) -> format::FormatStringPlaceholderData {
+    fn make_format_data_with_placeholder(
+        placeholder: &str,
+    ) -> format::FormatStringPlaceholderData<'_> {
         format::FormatStringPlaceholderData {
             placeholder: Some(Placeholder::Str(placeholder)),
             ..Default::default()
         }
     }
 
-    fn make_blame_line_with_author(author: &str) -> BlameLine {
+    fn make_blame_line_with_author(author: &str) -> BlameLine<'_> {
         BlameLine {
             commit: "",
+            path: None,
             author,
             time: chrono::DateTime::default(),
             line_number: 0,
@@ -1,8 +1,40 @@
 use std::borrow::Cow;
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+use chrono::{DateTime, FixedOffset};
+use itertools::Itertools;
+use lazy_static::lazy_static;
+use regex::Regex;
 
 use super::draw;
+use crate::ansi;
+use crate::cli;
+use crate::config::Config;
 use crate::delta::{State, StateMachine};
 use crate::features;
+use crate::format::{self, Placeholder};
+use crate::paint::{BgShouldFill, Painter, StyleSectionSpecifier};
+
+/// The fields of a commit metadata block which is being condensed into one line by
+/// commit-block-format.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct CommitBlock {
+    pub hash: String,
+    pub refs: String,
+    pub author: String,
+    pub email: String,
+    pub date: String,
+    pub subject: Option<String>,
+}
+
+/// The lines of a commit metadata block which is being laid out in two columns by
+/// --commit-columns: the commit line and the header fields, and the lines of the message.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct CommitColumns {
+    header: Vec<String>,
+    message: Vec<String>,
+}
 
 impl<'a> StateMachine<'a> {
     #[inline]
@@ -17,8 +49,18 @@ impl<'a> StateMachine<'a> {
         let mut handled_line = false;
         self.painter.paint_buffered_minus_and_plus_lines();
         self.handle_pending_line_with_diff_name()?;
+        self.end_log_group()?;
+        self.flush_commit_block()?;
+        self.flush_commit_columns()?;
         self.state = State::CommitMeta;
-        if self.should_handle() {
+        self.commit_subject_pending = true;
+        self.in_commit_message_code_block = false;
+        self.current_commit = self.line.split_whitespace().nth(1).map(String::from);
+        self.start_commit_version();
+        if self.should_handle()
+            || self.config.commit_block_format.is_some()
+            || self.config.commit_columns
+        {
             self.painter.emit()?;
             self._handle_commit_meta_header_line()?;
             handled_line = true
@@ -30,8 +72,10 @@ impl<'a> StateMachine<'a> {
         if self.config.commit_style.is_omitted {
             return Ok(());
         }
-        let (mut draw_fn, pad, decoration_ansi_term_style) =
-            draw::get_draw_function(self.config.commit_style.decoration_style);
+        if self.config.commit_block_format.is_some() {
+            self.commit_block = Some(parse_commit_line(&self.line));
+            return Ok(());
+        }
         let (formatted_line, formatted_raw_line) = if self.config.hyperlinks {
             (
                 features::hyperlinks::format_commit_line_with_osc8_commit_hyperlink(
@@ -46,16 +90,874 @@ impl<'a> StateMachine<'a> {
         } else {
             (Cow::from(&self.line), Cow::from(&self.raw_line))
         };
+        if self.config.commit_columns {
+            let line = if self.config.commit_style.is_raw {
+                formatted_raw_line.into_owned()
+            } else {
+                self.config.commit_style.paint(formatted_line).to_string()
+            };
+            self.commit_columns = Some(CommitColumns {
+                header: vec![line],
+                message: Vec::new(),
+            });
+            return Ok(());
+        }
+        draw_commit_line(
+            self.painter.writer,
+            &formatted_line,
+            &formatted_raw_line,
+            self.config,
+        )
+    }
+
+    /// Collect the fields of a commit metadata block which is being condensed by
+    /// commit-block-format, and emit the condensed line once the subject is known.
+    pub fn handle_commit_block_line(&mut self) -> std::io::Result<bool> {
+        if self.state != State::CommitMeta {
+            return Ok(false);
+        }
+        let line = &self.line;
+        let block = match &mut self.commit_block {
+            Some(block) => block,
+            None => return Ok(false),
+        };
+        if block.subject.is_none() {
+            if let Some(author) = line.strip_prefix("Author:") {
+                let (author, email) = parse_author(author);
+                block.author = author;
+                block.email = email;
+                return Ok(true);
+            } else if let Some(date) = self
+                .line
+                .strip_prefix("Date:")
+                .or_else(|| line.strip_prefix("AuthorDate:"))
+            {
+                block.date = date.trim().to_string();
+                return Ok(true);
+            } else if COMMIT_HEADER_FIELD_REGEX.is_match(line) {
+                // E.g. Merge:, Commit:, CommitDate:
+                return Ok(true);
+            }
+        }
+        if line.is_empty() {
+            return Ok(true);
+        }
+        if let Some(message_line) = line.strip_prefix("    ") {
+            if block.subject.is_none() {
+                block.subject = Some(message_line.to_string());
+                self.emit_commit_block()?;
+            }
+            // The rest of the commit message is omitted.
+            return Ok(true);
+        }
+        // The end of the block: e.g. a diff stat or a diff.
+        self.flush_commit_block()?;
+        Ok(false)
+    }
+
+    /// Collect the lines of a commit metadata block which is being laid out in two columns, and
+    /// emit the block at its end.
+    pub fn handle_commit_columns_line(&mut self) -> std::io::Result<bool> {
+        if self.state != State::CommitMeta {
+            return Ok(false);
+        }
+        let Some(columns) = &mut self.commit_columns else {
+            return Ok(false);
+        };
+        if columns.message.is_empty() && COMMIT_HEADER_FIELD_REGEX.is_match(&self.line) {
+            columns.header.push(self.raw_line.clone());
+        } else if self.line.is_empty() {
+            // The line separating the header from the message, or a blank line of the message.
+            if !columns.message.is_empty() {
+                columns.message.push(String::new());
+            }
+        } else if let Some(text) = self.line.strip_prefix("    ") {
+            let text = match (columns.message.is_empty() && self.config.conventional_commits)
+                .then(|| split_conventional_commit_prefix(text))
+                .flatten()
+            {
+                Some((prefix, rest)) => format!(
+                    "{}{}",
+                    format_conventional_commit_prefix(&prefix, self.config),
+                    format_message_text(rest, self.config)
+                ),
+                None => format_message_text(text, self.config).into_owned(),
+            };
+            columns.message.push(text);
+        } else {
+            // The end of the block: e.g. a diff stat or a diff.
+            self.flush_commit_columns()?;
+            return Ok(false);
+        }
+        Ok(true)
+    }
+
+    /// Emit the commit metadata block being laid out in two columns, if any.
+    pub fn flush_commit_columns(&mut self) -> std::io::Result<()> {
+        if let Some(columns) = self.commit_columns.take() {
+            self.painter.emit()?;
+            write!(
+                self.painter.writer,
+                "{}",
+                format_commit_columns(&columns, self.config)
+            )?;
+        }
+        Ok(())
+    }
 
-        draw_fn(
+    /// Style and hyperlink the parent commits listed by the "Merge:" line of a merge commit, and
+    /// list their subjects (--merge-parent-subjects).
+    pub fn handle_commit_merge_line(&mut self) -> std::io::Result<bool> {
+        if self.state != State::CommitMeta {
+            return Ok(false);
+        }
+        let parents = match self.line.strip_prefix("Merge:") {
+            Some(parents) => parents.split_whitespace().collect::<Vec<_>>(),
+            None => return Ok(false),
+        };
+        if self.config.merge_parent_style.is_raw
+            && !self.config.hyperlinks
+            && !self.config.merge_parent_subjects
+        {
+            return Ok(false);
+        }
+        let subjects = parents
+            .iter()
+            .map(|parent| {
+                self.config
+                    .merge_parent_subjects
+                    .then(|| read_commit_subject(parent, self.config))
+                    .flatten()
+            })
+            .collect::<Vec<_>>();
+        self.painter.emit()?;
+        write!(
             self.painter.writer,
-            &format!("{}{}", formatted_line, if pad { " " } else { "" }),
-            &format!("{}{}", formatted_raw_line, if pad { " " } else { "" }),
-            "",
-            &self.config.decorations_width,
-            self.config.commit_style,
-            decoration_ansi_term_style,
+            "{}",
+            format_merge_line(&parents, &subjects, self.config)
         )?;
+        Ok(true)
+    }
+
+    /// Render a line of a commit message as markdown (--commit-message-markdown), highlight the
+    /// conventional commit prefix of the subject (--conventional-commits), and display the titles
+    /// of the pull requests it references (--pull-request-titles).
+    pub fn handle_commit_message_line(&mut self) -> std::io::Result<bool> {
+        if self.state != State::CommitMeta {
+            return Ok(false);
+        }
+        let text = match self.line.strip_prefix("    ") {
+            Some(text) => text,
+            None => return Ok(false),
+        };
+        let is_subject = std::mem::take(&mut self.commit_subject_pending);
+        let conventional_commit_prefix = if is_subject && self.config.conventional_commits {
+            split_conventional_commit_prefix(text)
+        } else {
+            None
+        };
+        if !self.config.commit_message_markdown
+            && conventional_commit_prefix.is_none()
+            && !self.config.pull_request_titles
+        {
+            return Ok(false);
+        }
+        self.painter.emit()?;
+        let code_block_fence = text
+            .trim_start()
+            .strip_prefix("```")
+            .filter(|_| self.config.commit_message_markdown);
+        if let Some((prefix, rest)) = conventional_commit_prefix {
+            writeln!(
+                self.painter.writer,
+                "    {}{}",
+                format_conventional_commit_prefix(&prefix, self.config),
+                format_message_text(rest, self.config)
+            )?;
+        } else if let Some(info_string) = code_block_fence {
+            if !self.in_commit_message_code_block {
+                self.painter.syntax = code_block_syntax(info_string, self.config);
+                self.painter.set_highlighter();
+            }
+            self.in_commit_message_code_block = !self.in_commit_message_code_block;
+            writeln!(self.painter.writer, "    {text}")?;
+        } else if self.in_commit_message_code_block {
+            self.painter.output_buffer.push_str("    ");
+            // Highlighted like the lines of a file displayed by `git show $revision:$path`.
+            self.painter.syntax_highlight_and_paint_line(
+                text,
+                StyleSectionSpecifier::Style(self.config.zero_style),
+                State::GitShowFile,
+                BgShouldFill::No,
+            );
+            self.painter.emit()?;
+        } else {
+            writeln!(
+                self.painter.writer,
+                "    {}",
+                format_message_text(text, self.config)
+            )?;
+        }
+        Ok(true)
+    }
+
+    /// Emit the commit metadata block being condensed, if it has not been emitted yet.
+    pub fn flush_commit_block(&mut self) -> std::io::Result<()> {
+        if matches!(&self.commit_block, Some(block) if block.subject.is_none()) {
+            self.emit_commit_block()?;
+        }
+        self.commit_block = None;
+        Ok(())
+    }
+
+    fn emit_commit_block(&mut self) -> std::io::Result<()> {
+        if let (Some(format), Some(block)) = (&self.config.commit_block_format, &self.commit_block)
+        {
+            let line = format_commit_block(format, block, self.config);
+            draw_commit_line(self.painter.writer, &line, &line, self.config)?;
+        }
         Ok(())
     }
 }
+
+// The lines of the header of the commit side by side with the first lines of its message,
+// followed by the rest of the message. The block is displayed as git printed it if the terminal is
+// too narrow, or the commit has no message.
+fn format_commit_columns(columns: &CommitColumns, config: &Config) -> String {
+    const SEPARATOR: &str = " │ ";
+    let width = match config.decorations_width {
+        cli::Width::Fixed(width) => width,
+        cli::Width::Variable => config.available_terminal_width,
+    };
+    let message_end = columns
+        .message
+        .iter()
+        .rposition(|line| !line.is_empty())
+        .map_or(0, |i| i + 1);
+    let (message, blank_lines) = columns.message.split_at(message_end);
+    let header_width = columns
+        .header
+        .iter()
+        .map(|line| ansi::measure_text_width(line))
+        .max()
+        .unwrap_or_default();
+    let message_width = width.saturating_sub(header_width + ansi::measure_text_width(SEPARATOR));
+    let mut s = String::new();
+    let rest =
+        if width < config.commit_columns_min_width || message.is_empty() || message_width == 0 {
+            for line in &columns.header {
+                s.push_str(line);
+                s.push('\n');
+            }
+            if !message.is_empty() {
+                s.push('\n');
+            }
+            message
+        } else {
+            for (i, line) in columns.header.iter().enumerate() {
+                s.push_str(line);
+                if let Some(text) = message.get(i) {
+                    s.push_str(&" ".repeat(header_width - ansi::measure_text_width(line)));
+                    if text.is_empty() {
+                        s.push_str(SEPARATOR.trim_end());
+                    } else {
+                        s.push_str(SEPARATOR);
+                    }
+                    s.push_str(&ansi::truncate_str(
+                        text,
+                        message_width,
+                        &config.truncation_symbol,
+                    ));
+                }
+                s.push('\n');
+            }
+            &message[columns.header.len().min(message.len())..]
+        };
+    for text in rest {
+        if !text.is_empty() {
+            s.push_str("    ");
+            s.push_str(text);
+        }
+        s.push('\n');
+    }
+    if !blank_lines.is_empty() {
+        s.push('\n');
+    }
+    s
+}
+
+fn draw_commit_line(
+    writer: &mut dyn Write,
+    line: &str,
+    raw_line: &str,
+    config: &Config,
+) -> std::io::Result<()> {
+    let (mut draw_fn, pad, decoration_ansi_term_style) = draw::get_draw_function(
+        config.commit_style.decoration_style,
+        config.ascii_decorations,
+    );
+    draw_fn(
+        writer,
+        &format!("{}{}", line, if pad { " " } else { "" }),
+        &format!("{}{}", raw_line, if pad { " " } else { "" }),
+        "",
+        &config.decorations_width,
+        config.commit_style,
+        decoration_ansi_term_style,
+    )
+}
+
+lazy_static! {
+    static ref COMMIT_LINE_REGEX: Regex =
+        Regex::new(r"^commit ([0-9a-f]+)(?: \(from [0-9a-f]+\))?( \(.*\))?").unwrap();
+    static ref COMMIT_HEADER_FIELD_REGEX: Regex = Regex::new(r"^[A-Z][A-Za-z]*: ").unwrap();
+    static ref CONVENTIONAL_COMMIT_REGEX: Regex =
+        Regex::new(r"^([A-Za-z]+)(\([^()]*\))?(!)?: ").unwrap();
+    static ref MARKDOWN_LIST_ITEM_REGEX: Regex = Regex::new(r"^(\s*)[-*+] ").unwrap();
+    static ref MARKDOWN_INLINE_REGEX: Regex =
+        Regex::new(r"`([^`]+)`|\*\*([^*]+)\*\*|__([^_]+)__").unwrap();
+    static ref COMMIT_BLOCK_PLACEHOLDER_REGEX: Regex =
+        format::make_placeholder_regex(&["hash", "refs", "author", "email", "date", "subject"]);
+}
+
+/// The "Merge:" line listing the parent commits. If any of their subjects are known, each parent is
+/// listed on its own line, followed by its subject.
+fn format_merge_line(parents: &[&str], subjects: &[Option<String>], config: &Config) -> String {
+    let format_parent = |parent: &str| {
+        let text = if config.merge_parent_style.is_raw {
+            parent.to_string()
+        } else {
+            config.merge_parent_style.paint(parent).to_string()
+        };
+        if config.hyperlinks {
+            features::hyperlinks::format_osc8_commit_hyperlink(parent, &text, config).into_owned()
+        } else {
+            text
+        }
+    };
+    if subjects.iter().all(Option::is_none) {
+        return format!(
+            "Merge: {}\n",
+            parents.iter().map(|parent| format_parent(parent)).join(" ")
+        );
+    }
+    let mut s = String::new();
+    for (i, (parent, subject)) in parents.iter().zip(subjects).enumerate() {
+        s.push_str(if i == 0 { "Merge: " } else { "       " });
+        s.push_str(&format_parent(parent));
+        if let Some(subject) = subject {
+            s.push(' ');
+            s.push_str(subject);
+        }
+        s.push('\n');
+    }
+    s
+}
+
+/// The subject of a commit in the repository delta is run in.
+pub fn read_commit_subject(commit: &str, config: &Config) -> Option<String> {
+    let mut command = Command::new("git");
+    if let Some(cwd) = &config.cwd_of_delta_process {
+        command.current_dir(cwd);
+    }
+    let output = command
+        .args(["show", "-s", "--format=%s", commit, "--"])
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let subject = String::from_utf8_lossy(&output.stdout)
+        .trim_end()
+        .to_string();
+    (!subject.is_empty()).then_some(subject)
+}
+
+/// The type, scope and breaking change marker of a conventional commit subject, e.g.
+/// "feat(parser)!: ".
+#[derive(Debug, PartialEq, Eq)]
+struct ConventionalCommitPrefix<'a> {
+    commit_type: &'a str,
+    scope: Option<&'a str>,
+    breaking: bool,
+}
+
+// Split a subject into its conventional commit prefix and the rest of the subject.
+fn split_conventional_commit_prefix(subject: &str) -> Option<(ConventionalCommitPrefix<'_>, &str)> {
+    let captures = CONVENTIONAL_COMMIT_REGEX.captures(subject)?;
+    let prefix = ConventionalCommitPrefix {
+        commit_type: captures.get(1)?.as_str(),
+        scope: captures.get(2).map(|m| m.as_str()),
+        breaking: captures.get(3).is_some(),
+    };
+    Some((prefix, &subject[captures[0].len()..]))
+}
+
+fn format_conventional_commit_prefix(prefix: &ConventionalCommitPrefix, config: &Config) -> String {
+    let mut s = config
+        .conventional_commit_type_style
+        .paint(prefix.commit_type)
+        .to_string();
+    if let Some(scope) = prefix.scope {
+        s.push_str(
+            &config
+                .conventional_commit_scope_style
+                .paint(scope)
+                .to_string(),
+        );
+    }
+    if prefix.breaking {
+        s.push_str(
+            &config
+                .conventional_commit_breaking_style
+                .paint("!")
+                .to_string(),
+        );
+    }
+    s.push_str(": ");
+    s
+}
+
+// The syntax of a fenced code block, from the language named by its info string, e.g. "rust" or
+// "py".
+fn code_block_syntax<'a>(
+    info_string: &str,
+    config: &'a Config,
+) -> &'a syntect::parsing::SyntaxReference {
+    let language = info_string.split_whitespace().next().unwrap_or("");
+    config
+        .syntax_set
+        .find_syntax_by_token(language)
+        .filter(|_| !language.is_empty())
+        .unwrap_or_else(|| Painter::get_syntax(&config.syntax_set, None, &config.default_language))
+}
+
+// Format the text of a line of a commit message, as markdown and with the titles of the pull
+// requests it references, as requested.
+fn format_message_text<'a>(text: &'a str, config: &Config) -> Cow<'a, str> {
+    let text = if config.commit_message_markdown {
+        Cow::from(format_markdown_line(text, config))
+    } else {
+        Cow::from(text)
+    };
+    if !config.pull_request_titles {
+        return text;
+    }
+    match features::pull_request_titles::format_pull_request_titles(&text, config) {
+        Cow::Borrowed(_) => text,
+        Cow::Owned(formatted) => Cow::from(formatted),
+    }
+}
+
+/// Format the bold text, inline code and list item marker of a line of markdown.
+fn format_markdown_line(text: &str, config: &Config) -> String {
+    let (prefix, text) = match MARKDOWN_LIST_ITEM_REGEX.captures(text) {
+        Some(captures) => (format!("{}• ", &captures[1]), &text[captures[0].len()..]),
+        None => (String::new(), text),
+    };
+    let text = MARKDOWN_INLINE_REGEX.replace_all(text, |captures: &regex::Captures| {
+        match (captures.get(1), captures.get(2).or_else(|| captures.get(3))) {
+            (Some(code), _) => config
+                .commit_message_code_style
+                .paint(code.as_str())
+                .to_string(),
+            (None, Some(bold)) => ansi_term::Style::new()
+                .bold()
+                .paint(bold.as_str())
+                .to_string(),
+            (None, None) => captures[0].to_string(),
+        }
+    });
+    format!("{prefix}{text}")
+}
+
+fn parse_commit_line(line: &str) -> CommitBlock {
+    let captures = COMMIT_LINE_REGEX.captures(line);
+    let capture = |i| {
+        captures
+            .as_ref()
+            .and_then(|c| c.get(i))
+            .map(|m| m.as_str().trim().to_string())
+            .unwrap_or_default()
+    };
+    CommitBlock {
+        hash: capture(1),
+        refs: capture(2),
+        ..CommitBlock::default()
+    }
+}
+
+fn parse_author(s: &str) -> (String, String) {
+    let s = s.trim();
+    match (s.rfind('<'), s.ends_with('>')) {
+        (Some(i), true) => (s[..i].trim().to_string(), s[i + 1..s.len() - 1].to_string()),
+        _ => (s.to_string(), String::new()),
+    }
+}
+
+/// Parse a date as printed by git log with the default, rfc, iso or iso-strict date formats.
+fn parse_git_date(date: &str) -> Option<DateTime<FixedOffset>> {
+    DateTime::parse_from_str(date, "%a %b %e %H:%M:%S %Y %z")
+        .or_else(|_| DateTime::parse_from_rfc2822(date))
+        .or_else(|_| DateTime::parse_from_str(date, "%Y-%m-%d %H:%M:%S %z"))
+        .or_else(|_| DateTime::parse_from_rfc3339(date))
+        .ok()
+}
+
+pub fn format_commit_block(format: &str, block: &CommitBlock, config: &Config) -> String {
+    let format_data =
+        format::parse_line_number_format(format, &COMMIT_BLOCK_PLACEHOLDER_REGEX, false);
+    let mut s = String::new();
+    let mut suffix = "";
+    for placeholder in format_data.iter() {
+        s.push_str(placeholder.prefix.as_str());
+        let field = match placeholder.placeholder {
+            Some(Placeholder::Str("hash")) => Some(Cow::from(&block.hash)),
+            Some(Placeholder::Str("refs")) => Some(Cow::from(&block.refs)),
+            Some(Placeholder::Str("author")) => Some(Cow::from(&block.author)),
+            Some(Placeholder::Str("email")) => Some(Cow::from(&block.email)),
+            Some(Placeholder::Str("date")) => Some(match parse_git_date(&block.date) {
+                Some(date) => Cow::from(chrono_humanize::HumanTime::from(date).to_string()),
+                None => Cow::from(&block.date),
+            }),
+            Some(Placeholder::Str("subject")) => {
+                Some(Cow::from(block.subject.as_deref().unwrap_or("")))
+            }
+            None => None,
+            _ => unreachable!("Unexpected commit-block-format placeholder"),
+        };
+        if let Some(field) = field {
+            let field = format::pad(
+                &field,
+                placeholder.width.unwrap_or(0),
+                placeholder.alignment_spec.unwrap_or(format::Align::Left),
+                placeholder.precision,
+            );
+            if placeholder.placeholder == Some(Placeholder::Str("hash")) && config.hyperlinks {
+                s.push_str(&features::hyperlinks::format_osc8_commit_hyperlink(
+                    &block.hash,
+                    &field,
+                    config,
+                ));
+            } else {
+                s.push_str(&field);
+            }
+        }
+        suffix = placeholder.suffix.as_str();
+    }
+    s.push_str(suffix);
+    s
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::integration_test_utils::{self, DeltaTest};
+
+    const GIT_LOG_P: &str = "\
+commit 94907c0f136f46dc46ffae2dc92dca9af7eb7c2e (HEAD -> main, origin/main)
+Author: Jane Doe <jane@example.com>
+Date:   Thu Jan 4 14:07:10 2024 +0100
+
+    Change the answer
+
+    The question was different.
+
+diff --git a/a.py b/a.py
+index 223ca50..e69de29 100644
+--- a/a.py
++++ b/a.py
+@@ -1 +1 @@
+-x = 41
++x = 42
+commit 2e4ec4b73b0b1a2a1b6e8a3d3fd1f6d3a4e2c1a9
+Merge: 1111111 2222222
+Author: John Roe <john@example.com>
+Date:   Wed Jan 3 09:00:00 2024 +0000
+
+    Merge branch 'topic'
+";
+
+    #[test]
+    fn test_commit_block_format() {
+        let result = DeltaTest::with_args(&[
+            "--commit-block-format",
+            "{hash:.8} {author} <{email}> {subject} {refs}",
+        ])
+        .with_input(GIT_LOG_P);
+        assert_eq!(
+            result.output.lines().next().unwrap(),
+            "94907c0f Jane Doe <jane@example.com> Change the answer (HEAD -> main, origin/main)"
+        );
+        let result = DeltaTest::with_args(&["--commit-block-format", "{hash:.8} {subject}"])
+            .with_input(GIT_LOG_P);
+        let output = result.output;
+        assert!(output.contains("2e4ec4b7 Merge branch 'topic'"));
+        assert!(output.contains("x = 42"));
+        for omitted in ["Author:", "Date:", "Merge:", "The question was different."] {
+            assert!(!output.contains(omitted), "{}", omitted);
+        }
+    }
+
+    #[test]
+    fn test_commit_columns() {
+        DeltaTest::with_args(&[
+            "--commit-columns",
+            "--commit-columns-min-width",
+            "90",
+            "--width",
+            "110",
+        ])
+        .with_input(GIT_LOG_P)
+        .expect_contains(
+            "\
+commit 94907c0f136f46dc46ffae2dc92dca9af7eb7c2e (HEAD -> main, origin/main) │ Change the answer
+Author: Jane Doe <jane@example.com>                                         │
+Date:   Thu Jan 4 14:07:10 2024 +0100                                       │ The question was different.
+
+",
+        )
+        .expect_contains(
+            "\
+commit 2e4ec4b73b0b1a2a1b6e8a3d3fd1f6d3a4e2c1a9 │ Merge branch 'topic'
+Merge: 1111111 2222222
+Author: John Roe <john@example.com>
+Date:   Wed Jan 3 09:00:00 2024 +0000
+",
+        );
+    }
+
+    #[test]
+    fn test_commit_columns_truncated_message() {
+        let result = DeltaTest::with_args(&[
+            "--commit-columns",
+            "--commit-columns-min-width",
+            "90",
+            "--width",
+            "90",
+        ])
+        .with_input(GIT_LOG_P);
+        let output = result.output;
+        assert!(output.contains(
+            "Date:   Thu Jan 4 14:07:10 2024 +0100                                       │ The questio→\n"
+        ));
+        // Narrower than commit-columns-min-width.
+        DeltaTest::with_args(&["--commit-columns", "--width", "100"])
+            .with_input(GIT_LOG_P)
+            .expect_contains(
+                "\
+commit 94907c0f136f46dc46ffae2dc92dca9af7eb7c2e (HEAD -> main, origin/main)
+Author: Jane Doe <jane@example.com>
+Date:   Thu Jan 4 14:07:10 2024 +0100
+
+    Change the answer
+
+    The question was different.
+",
+            );
+    }
+
+    #[test]
+    fn test_commit_block_format_without_message() {
+        let input = "\
+commit 94907c0f136f46dc46ffae2dc92dca9af7eb7c2e
+Author: Jane Doe <jane@example.com>
+Date:   Thu Jan 4 14:07:10 2024 +0100
+ a.py | 2 +-
+";
+        DeltaTest::with_args(&["--commit-block-format", "{hash:.8} by {author}"])
+            .with_input(input)
+            .expect_contains("94907c0f by Jane Doe")
+            .expect_contains("a.py | 2 +-");
+    }
+
+    #[test]
+    fn test_commit_block_format_not_set() {
+        DeltaTest::with_args(&[])
+            .with_input(GIT_LOG_P)
+            .expect_contains("Author: Jane Doe <jane@example.com>")
+            .expect_contains("The question was different.");
+    }
+
+    #[test]
+    fn test_format_commit_block_date() {
+        let config = integration_test_utils::make_config_from_args(&[]);
+        let mut block = CommitBlock {
+            date: "3 days ago".to_string(),
+            ..CommitBlock::default()
+        };
+        assert_eq!(format_commit_block("{date}", &block, &config), "3 days ago");
+        assert_eq!(
+            format_commit_block("[{date:>8.6}]", &block, &config),
+            "[  3 days]"
+        );
+        block.date = "Thu Jan 4 14:07:10 2024 +0100".to_string();
+        assert!(format_commit_block("{date}", &block, &config).ends_with(" ago"));
+    }
+
+    #[test]
+    fn test_parse_git_date() {
+        for date in [
+            "Thu Jan 4 14:07:10 2024 +0100",
+            "Thu, 4 Jan 2024 14:07:10 +0100",
+            "2024-01-04 14:07:10 +0100",
+            "2024-01-04T14:07:10+01:00",
+        ] {
+            assert_eq!(
+                parse_git_date(date).unwrap().to_rfc3339(),
+                "2024-01-04T14:07:10+01:00",
+                "{}",
+                date
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_commit_and_author_lines() {
+        assert_eq!(
+            parse_commit_line("commit abc123 (from def456) (tag: v1.0)"),
+            CommitBlock {
+                hash: "abc123".to_string(),
+                refs: "(tag: v1.0)".to_string(),
+                ..CommitBlock::default()
+            }
+        );
+        assert_eq!(
+            parse_author(" Jane Doe <jane@example.com>"),
+            ("Jane Doe".to_string(), "jane@example.com".to_string())
+        );
+        assert_eq!(parse_author(" Jane"), ("Jane".to_string(), String::new()));
+    }
+
+    #[test]
+    fn test_commit_message_markdown() {
+        let input = "\
+commit 94907c0f136f46dc46ffae2dc92dca9af7eb7c2e
+Author: Jane Doe <jane@example.com>
+Date:   Thu Jan 4 14:07:10 2024 +0100
+
+    Change the answer
+
+    Use **the** `answer` now:
+    - one
+      * two
+    ```rust
+    let x = 42;
+    ```
+";
+        DeltaTest::with_args(&[
+            "--commit-message-markdown",
+            "--commit-message-code-style",
+            "yellow",
+        ])
+        .explain_ansi()
+        .with_input(input)
+        .expect_after_skip(
+            6,
+            r#"
+            #indent_mark
+                Use (bold normal)the(normal) (yellow)answer(normal) now:
+                • one
+                  • two
+                ```rust
+                (81)let(231) x (203)=(231) (141)42(231);(normal)
+                ```"#,
+        );
+    }
+
+    #[test]
+    fn test_commit_message_markdown_not_set() {
+        DeltaTest::with_args(&[])
+            .with_input("commit 94907c0f\n\n    - Use **the** `answer`\n")
+            .expect_contains("    - Use **the** `answer`");
+    }
+
+    #[test]
+    fn test_conventional_commits() {
+        let input = "\
+commit 94907c0f136f46dc46ffae2dc92dca9af7eb7c2e
+Author: Jane Doe <jane@example.com>
+Date:   Thu Jan 4 14:07:10 2024 +0100
+
+    feat(parser)!: drop support for `x`
+
+    fix: this is not the subject
+";
+        DeltaTest::with_args(&["--conventional-commits"])
+            .explain_ansi()
+            .with_input(input)
+            .expect_contains(
+                "\n    (bold blue)feat(blue)(parser)(bold red)!(normal): drop support for `x`\n",
+            )
+            .expect_contains("\n    fix: this is not the subject\n");
+    }
+
+    #[test]
+    fn test_split_conventional_commit_prefix() {
+        assert_eq!(
+            split_conventional_commit_prefix("fix: typo"),
+            Some((
+                ConventionalCommitPrefix {
+                    commit_type: "fix",
+                    scope: None,
+                    breaking: false,
+                },
+                "typo"
+            ))
+        );
+        assert_eq!(
+            split_conventional_commit_prefix("feat(api)!: remove v1"),
+            Some((
+                ConventionalCommitPrefix {
+                    commit_type: "feat",
+                    scope: Some("(api)"),
+                    breaking: true,
+                },
+                "remove v1"
+            ))
+        );
+        for subject in [
+            "Merge branch 'topic'",
+            "fix:typo",
+            "see https://example.com",
+        ] {
+            assert_eq!(
+                split_conventional_commit_prefix(subject),
+                None,
+                "{}",
+                subject
+            );
+        }
+    }
+
+    #[test]
+    fn test_merge_parent_style() {
+        DeltaTest::with_args(&["--merge-parent-style", "yellow"])
+            .explain_ansi()
+            .with_input(GIT_LOG_P)
+            .expect_contains("\nMerge: (yellow)1111111(normal) (yellow)2222222(normal)\n");
+        DeltaTest::with_args(&[])
+            .explain_ansi()
+            .with_input(GIT_LOG_P)
+            .expect_contains("\nMerge: 1111111 2222222\n");
+    }
+
+    #[test]
+    fn test_format_merge_line_with_subjects() {
+        let config = integration_test_utils::make_config_from_args(&[]);
+        assert_eq!(
+            format_merge_line(
+                &["1111111", "2222222"],
+                &[Some("Fix the answer".to_string()), None],
+                &config
+            ),
+            "Merge: 1111111 Fix the answer\n       2222222\n"
+        );
+        assert_eq!(
+            format_merge_line(&["1111111", "2222222"], &[None, None], &config),
+            "Merge: 1111111 2222222\n"
+        );
+    }
+}
@@ -1,17 +1,72 @@
-use std::borrow::Cow;
 use std::path::Path;
 
+use lazy_static::lazy_static;
+use regex::Regex;
 use unicode_segmentation::UnicodeSegmentation;
 
 use super::draw;
 use crate::config::Config;
 use crate::delta::{DiffType, Source, State, StateMachine};
+use crate::git_config::GitConfig;
+use crate::minusplus::MinusPlusIndex;
 use crate::paint::Painter;
+use crate::style::Style;
+use crate::utils::process::{self, CallingProcess};
 use crate::{features, utils};
 
 // https://git-scm.com/docs/git-config#Documentation/git-config.txt-diffmnemonicPrefix
 const DIFF_PREFIXES: [&str; 6] = ["a/", "b/", "c/", "i/", "o/", "w/"];
 
+lazy_static! {
+    // E.g. "diff -r 1234abcd5678 src/main.rs", or "diff -r 1234abcd5678 -r 5678abcd1234 src/main.rs"
+    // comparing two revisions, the first line of the diff of a file of `hg diff`.
+    static ref HG_DIFF_LINE_REGEX: Regex =
+        Regex::new(r"^diff (?:-r [0-9a-f]{12,40} ){1,2}(?P<path>.+)$").unwrap();
+}
+
+/// The prefixes of the file paths in the header lines of git's diffs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DiffPathPrefixes {
+    /// "a/" and "b/", or those of git config diff.mnemonicPrefix.
+    Default,
+    /// Those of git config diff.srcPrefix and diff.dstPrefix, or none with diff.noprefix.
+    Custom { src: String, dst: String },
+}
+
+impl DiffPathPrefixes {
+    pub fn from_git_config(git_config: Option<&GitConfig>) -> Self {
+        let get = |key| git_config.and_then(|git_config| git_config.get::<String>(key));
+        let noprefix = git_config
+            .and_then(|git_config| git_config.get::<bool>("diff.noprefix"))
+            .unwrap_or(false);
+        match (noprefix, get("diff.srcPrefix"), get("diff.dstPrefix")) {
+            (true, _, _) => Self::Custom {
+                src: String::new(),
+                dst: String::new(),
+            },
+            (false, None, None) => Self::Default,
+            (false, src, dst) => Self::Custom {
+                src: src.unwrap_or_else(|| "a/".to_string()),
+                dst: dst.unwrap_or_else(|| "b/".to_string()),
+            },
+        }
+    }
+
+    fn strip<'p>(&self, path: &'p str, side: MinusPlusIndex) -> &'p str {
+        match self {
+            Self::Default if DIFF_PREFIXES.iter().any(|s| path.starts_with(s)) => &path[2..],
+            Self::Default => path,
+            Self::Custom { src, dst } => {
+                let prefix = match side {
+                    MinusPlusIndex::Minus => src,
+                    MinusPlusIndex::Plus => dst,
+                };
+                path.strip_prefix(prefix.as_str()).unwrap_or(path)
+            }
+        }
+    }
+}
+
 #[derive(Debug, PartialEq, Eq)]
 pub enum FileEvent {
     Added,
@@ -76,6 +131,9 @@ impl<'a> StateMachine<'a> {
             && ((self.line.starts_with("--- ") && self.minus_line_counter.three_dashes_expected())
                 || self.line.starts_with("rename from ")
                 || self.line.starts_with("copy from "))
+            // The patches of `git log -L` may lack a "diff --git" line, so that the "--- " line
+            // directly follows the commit metadata (in which message lines are indented).
+            || (self.state == State::CommitMeta && self.line.starts_with("--- "))
     }
 
     /// Check for and handle the "--- filename ..." line.
@@ -84,22 +142,23 @@ impl<'a> StateMachine<'a> {
             return Ok(false);
         }
 
-        let (mut path_or_mode, file_event) =
-            parse_diff_header_line(&self.line, self.source == Source::GitDiff);
-
-        utils::path::relativize_path_maybe(&mut path_or_mode, self.config);
+        let (path_or_mode, file_event) = self.parse_diff_header_file_path();
         self.minus_file = path_or_mode;
         self.minus_file_event = file_event;
 
+        if self.state == State::CommitMeta {
+            self.state = State::DiffHeader(DiffType::Unified);
+            // Without a "diff --git" line (`git log -L`), a commit changing the same file as the
+            // previous one must display the file header again.
+            self.handled_diff_header_header_line_file_pair = None;
+        }
         if self.source == Source::DiffUnified {
             self.state = State::DiffHeader(DiffType::Unified);
-            self.painter
-                .set_syntax(get_filename_from_marker_line(&self.line));
+            self.set_file_syntax(get_filename_from_marker_line(&self.line).map(String::from));
         } else {
-            self.painter
-                .set_syntax(get_filename_from_diff_header_line_file_path(
-                    &self.minus_file,
-                ));
+            self.set_file_syntax(
+                get_filename_from_diff_header_line_file_path(&self.minus_file).map(String::from),
+            );
         }
 
         self.painter.paint_buffered_minus_and_plus_lines();
@@ -120,16 +179,13 @@ impl<'a> StateMachine<'a> {
             return Ok(false);
         }
         let mut handled_line = false;
-        let (mut path_or_mode, file_event) =
-            parse_diff_header_line(&self.line, self.source == Source::GitDiff);
-
-        utils::path::relativize_path_maybe(&mut path_or_mode, self.config);
+        let (path_or_mode, file_event) = self.parse_diff_header_file_path();
         self.plus_file = path_or_mode;
         self.plus_file_event = file_event;
-        self.painter
-            .set_syntax(get_filename_from_diff_header_line_file_path(
-                &self.plus_file,
-            ));
+        self.file_symbols = None;
+        self.set_file_syntax(
+            get_filename_from_diff_header_line_file_path(&self.plus_file).map(String::from),
+        );
         self.current_file_pair = Some((self.minus_file.clone(), self.plus_file.clone()));
 
         self.painter.paint_buffered_minus_and_plus_lines();
@@ -139,7 +195,13 @@ impl<'a> StateMachine<'a> {
             && self.handled_diff_header_header_line_file_pair != self.current_file_pair
         {
             self.painter.emit()?;
-            self._handle_diff_header_header_line(self.source == Source::DiffUnified)?;
+            if self.is_pure_rename && self.plus_file_event == FileEvent::Rename {
+                // Displayed together with the next files renamed without changes, if any.
+                self.pure_renames
+                    .push((self.minus_file.clone(), self.plus_file.clone()));
+            } else {
+                self._handle_diff_header_header_line(self.source == Source::DiffUnified)?;
+            }
             self.handled_diff_header_header_line_file_pair
                 .clone_from(&self.current_file_pair);
         }
@@ -159,9 +221,8 @@ impl<'a> StateMachine<'a> {
             return Ok(false);
         }
         let mut handled_line = false;
-        let (_mode_info, file_event) =
-            parse_diff_header_line(&self.line, self.source == Source::GitDiff);
-        let name = get_repeated_file_path_from_diff_line(&self.diff_line).unwrap_or_default();
+        let (_mode_info, file_event) = parse_diff_header_line(&self.line, self.git_path_prefixes());
+        let name = self.file_path_from_diff_line(&self.diff_line);
         match file_event {
             FileEvent::Removed => {
                 self.minus_file = name;
@@ -189,8 +250,46 @@ impl<'a> StateMachine<'a> {
         Ok(handled_line)
     }
 
+    // The prefixes of the paths in the diff header lines, if the diff is git's, or Mercurial's,
+    // whose paths are always prefixed with "a/" and "b/".
+    fn git_path_prefixes(&self) -> Option<&DiffPathPrefixes> {
+        match self.source {
+            Source::GitDiff => Some(&self.config.diff_path_prefixes),
+            Source::Mercurial => Some(&DiffPathPrefixes::Default),
+            _ => None,
+        }
+    }
+
+    /// Parse the path of the file, or the mode, from the current "---", "+++", "rename" or "copy"
+    /// line, and clean up the path for display.
+    pub fn parse_diff_header_file_path(&self) -> (String, FileEvent) {
+        let (mut path_or_mode, file_event) =
+            parse_diff_header_line(&self.line, self.git_path_prefixes());
+        clean_file_path(&mut path_or_mode, self.config);
+        utils::path::relativize_path_maybe(&mut path_or_mode, self.config);
+        (path_or_mode, file_event)
+    }
+
+    /// The path of the file of a "diff --git" line, if both paths of the line are the same, or of
+    /// the "diff -r" line of `hg diff` or the "Index:" line of `svn diff`.
+    pub fn file_path_from_diff_line(&self, line: &str) -> String {
+        let mut path = get_repeated_file_path_from_diff_line(line, &self.config.diff_path_prefixes)
+            .or_else(|| get_file_path_from_hg_or_svn_diff_line(line))
+            .unwrap_or_default();
+        clean_file_path(&mut path, self.config);
+        path
+    }
+
     /// Construct file change line from minus and plus file and write with DiffHeader styling.
-    fn _handle_diff_header_header_line(&mut self, comparing: bool) -> std::io::Result<()> {
+    pub fn _handle_diff_header_header_line(&mut self, comparing: bool) -> std::io::Result<()> {
+        let path = match self.plus_file.as_str() {
+            "/dev/null" => self.minus_file.clone(),
+            _ => self.plus_file.clone(),
+        };
+        self.start_log_group(&path)?;
+        self.follow_color_scheme();
+        self.mark_file_anchor(&path)?;
+        self.follow_terminal_size();
         let line = get_file_change_description_from_file_paths(
             &self.minus_file,
             &self.plus_file,
@@ -199,6 +298,25 @@ impl<'a> StateMachine<'a> {
             &self.plus_file_event,
             self.config,
         );
+        let reviewed_at = self
+            .blob_ids
+            .as_ref()
+            .and_then(|(_, plus_blob)| self.config.review_bookmarks.reviewed_at(&path, plus_blob));
+        if let Some(commit) = reviewed_at {
+            let (line, style) = features::review_bookmarks::format_reviewed_file_header(
+                &line,
+                commit,
+                self.config.file_style,
+            );
+            return write_diff_header_header_line_with_style(
+                &line,
+                &line,
+                style,
+                &mut self.painter,
+                &mut self.mode_info,
+                self.config,
+            );
+        }
         // FIXME: no support for 'raw'
         write_generic_diff_header_header_line(
             &line,
@@ -227,21 +345,31 @@ impl<'a> StateMachine<'a> {
                     "".to_string()
                 }
             };
-            let format_file = |file| match (
-                self.config.hyperlinks,
-                utils::path::absolute_path(file, self.config),
-            ) {
-                (true, Some(absolute_path)) => features::hyperlinks::format_osc8_file_hyperlink(
-                    absolute_path,
-                    None,
-                    file,
-                    self.config,
-                ),
-                _ => Cow::from(file),
+            let format_file = |file| {
+                let formatted_file = utils::path::display_path(file, self.config);
+                match (
+                    self.config.hyperlinks,
+                    utils::path::hyperlink_target_path(file, self.config),
+                ) {
+                    (true, Some(absolute_path)) => {
+                        features::hyperlinks::format_osc8_file_hyperlink(
+                            absolute_path,
+                            None,
+                            &formatted_file,
+                            self.config,
+                        )
+                        .into_owned()
+                    }
+                    _ => formatted_file.into_owned(),
+                }
             };
             let label = format_label(&self.config.file_modified_label);
-            let name = get_repeated_file_path_from_diff_line(&self.diff_line).unwrap_or_default();
+            let name = self.file_path_from_diff_line(&self.diff_line);
             let line = format!("{}{}", label, format_file(&name));
+            self.start_log_group(&name)?;
+            self.follow_color_scheme();
+            self.mark_file_anchor(&name)?;
+            self.follow_terminal_size();
             write_generic_diff_header_header_line(
                 &line,
                 &line,
@@ -261,6 +389,49 @@ impl<'a> StateMachine<'a> {
             Ok(())
         }
     }
+
+    /// Set the syntax of the file, and use the config of its language if git config has a
+    /// [delta "lang:<language>"] section for it, named after the language, e.g. "python", or the
+    /// extension of the file, e.g. "py".
+    pub fn set_file_syntax(&mut self, filename: Option<String>) {
+        self.syntax_filename = filename;
+        let filename = self.syntax_filename.as_deref();
+        let config = get_language_config(self.root_config, filename);
+        if !std::ptr::eq(config, self.config) {
+            // The lines of the previous file are painted with its config.
+            self.painter.paint_buffered_minus_and_plus_lines();
+            self.painter.highlighter = None;
+            self.config = config;
+            self.painter.config = config;
+        }
+        self.painter.set_syntax(filename);
+    }
+
+    /// Write the GitHub Actions workflow command opening a collapsible group for the output of the
+    /// file, closing the group of the previous file (--github-actions-groups).
+    pub fn start_log_group(&mut self, path: &str) -> std::io::Result<()> {
+        if !self.config.github_actions_groups {
+            return Ok(());
+        }
+        self.end_log_group()?;
+        writeln!(
+            self.painter.writer,
+            "::group::{}",
+            utils::path::display_path(path, self.config)
+        )?;
+        self.log_group_open = true;
+        Ok(())
+    }
+
+    /// Close the group of the current file, if any, after the lines painted so far.
+    pub fn end_log_group(&mut self) -> std::io::Result<()> {
+        if self.log_group_open {
+            self.painter.emit()?;
+            writeln!(self.painter.writer, "::endgroup::")?;
+            self.log_group_open = false;
+        }
+        Ok(())
+    }
 }
 
 /// Write `line` with DiffHeader styling.
@@ -270,15 +441,34 @@ pub fn write_generic_diff_header_header_line(
     painter: &mut Painter,
     mode_info: &mut String,
     config: &Config,
+) -> std::io::Result<()> {
+    write_diff_header_header_line_with_style(
+        line,
+        raw_line,
+        config.file_style,
+        painter,
+        mode_info,
+        config,
+    )
+}
+
+/// Write `line` with DiffHeader styling, in the style instead of file-style.
+fn write_diff_header_header_line_with_style(
+    line: &str,
+    raw_line: &str,
+    style: Style,
+    painter: &mut Painter,
+    mode_info: &mut String,
+    config: &Config,
 ) -> std::io::Result<()> {
     // If file_style is "omit", we'll skip the process and print nothing.
     // However in the case of color_only mode,
     // we won't skip because we can't change raw_line structure.
-    if config.file_style.is_omitted && !config.color_only {
+    if style.is_omitted && !config.color_only {
         return Ok(());
     }
     let (mut draw_fn, pad, decoration_ansi_term_style) =
-        draw::get_draw_function(config.file_style.decoration_style);
+        draw::get_draw_function(style.decoration_style, config.ascii_decorations);
     if !config.color_only {
         // Maintain 1-1 correspondence between input and output lines.
         writeln!(painter.writer)?;
@@ -289,7 +479,7 @@ pub fn write_generic_diff_header_header_line(
         &format!("{}{}", raw_line, if pad { " " } else { "" }),
         mode_info,
         &config.decorations_width,
-        config.file_style,
+        style,
         decoration_ansi_term_style,
     )?;
     if !mode_info.is_empty() {
@@ -298,6 +488,27 @@ pub fn write_generic_diff_header_header_line(
     Ok(())
 }
 
+/// The config of the language of the file: that of its [delta "lang:<language>"] section, if any,
+/// or else the root config.
+pub fn get_language_config<'a>(root_config: &'a Config, filename: Option<&str>) -> &'a Config {
+    if root_config.language_configs.is_empty() {
+        return root_config;
+    }
+    let syntax = Painter::get_syntax(
+        &root_config.syntax_set,
+        filename,
+        &root_config.default_language,
+    );
+    let extension = filename
+        .and_then(|filename| Path::new(filename).extension())
+        .and_then(|extension| extension.to_str());
+    root_config
+        .language_configs
+        .get(&syntax.name.to_lowercase())
+        .or_else(|| extension.and_then(|extension| root_config.language_configs.get(extension)))
+        .unwrap_or(root_config)
+}
+
 #[allow(clippy::tabs_in_doc_comments)]
 /// Given input like
 /// "--- a/zero/one.rs	2019-11-20 06:16:08.000000000 +0100"
@@ -309,7 +520,7 @@ fn get_filename_from_marker_line(line: &str) -> Option<&str> {
         .and_then(get_filename_from_diff_header_line_file_path)
 }
 
-fn get_filename_from_diff_header_line_file_path(path: &str) -> Option<&str> {
+pub fn get_filename_from_diff_header_line_file_path(path: &str) -> Option<&str> {
     Path::new(path).file_name().and_then(|filename| {
         if path != "/dev/null" {
             filename.to_str()
@@ -319,11 +530,19 @@ fn get_filename_from_diff_header_line_file_path(path: &str) -> Option<&str> {
     })
 }
 
-fn parse_diff_header_line(line: &str, git_diff_name: bool) -> (String, FileEvent) {
+fn parse_diff_header_line(
+    line: &str,
+    git_path_prefixes: Option<&DiffPathPrefixes>,
+) -> (String, FileEvent) {
     match line {
         line if line.starts_with("--- ") || line.starts_with("+++ ") => {
             let offset = 4;
-            let file = _parse_file_path(&line[offset..], git_diff_name);
+            let side = if line.starts_with("--- ") {
+                MinusPlusIndex::Minus
+            } else {
+                MinusPlusIndex::Plus
+            };
+            let file = _parse_file_path(&line[offset..], git_path_prefixes, side);
             (file, FileEvent::Change)
         }
         line if line.starts_with("rename from ") => {
@@ -350,13 +569,24 @@ fn parse_diff_header_line(line: &str, git_diff_name: bool) -> (String, FileEvent
 
 /// Given input like "diff --git a/src/my file.rs b/src/my file.rs"
 /// return Some("src/my file.rs")
-pub fn get_repeated_file_path_from_diff_line(line: &str) -> Option<String> {
+pub fn get_repeated_file_path_from_diff_line(
+    line: &str,
+    path_prefixes: &DiffPathPrefixes,
+) -> Option<String> {
     if let Some(line) = line.strip_prefix("diff --git ") {
         let line: Vec<&str> = line.graphemes(true).collect();
         let midpoint = line.len() / 2;
         if line[midpoint] == " " {
-            let first_path = _parse_file_path(&line[..midpoint].join(""), true);
-            let second_path = _parse_file_path(&line[midpoint + 1..].join(""), true);
+            let first_path = _parse_file_path(
+                &line[..midpoint].join(""),
+                Some(path_prefixes),
+                MinusPlusIndex::Minus,
+            );
+            let second_path = _parse_file_path(
+                &line[midpoint + 1..].join(""),
+                Some(path_prefixes),
+                MinusPlusIndex::Plus,
+            );
             if first_path == second_path {
                 return Some(first_path);
             }
@@ -365,6 +595,22 @@ pub fn get_repeated_file_path_from_diff_line(line: &str) -> Option<String> {
     None
 }
 
+/// Given input like "diff -r 1234abcd5678 src/main.rs" (`hg diff`) or "Index: src/main.rs"
+/// (`svn diff`), return Some("src/main.rs").
+pub fn get_file_path_from_hg_or_svn_diff_line(line: &str) -> Option<String> {
+    match line.strip_prefix("Index: ") {
+        Some(path) => Some(path.to_string()),
+        None => HG_DIFF_LINE_REGEX
+            .captures(line)
+            .map(|caps| caps["path"].to_string()),
+    }
+}
+
+/// Whether the line is the first line of the diff of a file of `hg diff`.
+pub fn is_hg_diff_line(line: &str) -> bool {
+    HG_DIFF_LINE_REGEX.is_match(line)
+}
+
 fn remove_surrounding_quotes(path: &str) -> &str {
     if path.starts_with('"') && path.ends_with('"') {
         // Indexing into the UTF-8 string is safe because of the previous test
@@ -374,7 +620,11 @@ fn remove_surrounding_quotes(path: &str) -> &str {
     }
 }
 
-fn _parse_file_path(path: &str, git_diff_name: bool) -> String {
+fn _parse_file_path(
+    path: &str,
+    git_path_prefixes: Option<&DiffPathPrefixes>,
+    side: MinusPlusIndex,
+) -> String {
     // When git config 'core.quotepath = true' (the default), and `path` contains
     // non-ASCII characters, a backslash, or a quote; then it is quoted, so remove
     // these quotes. Characters may also be escaped, but these are left as-is.
@@ -386,15 +636,67 @@ fn _parse_file_path(path: &str, git_diff_name: bool) -> String {
     // index·d00491f..0cfbf08·100644␊
     // ---·a/a·b├──┤␊
     // +++·b/c·d├──┤␊
-    match path.strip_suffix('\t').unwrap_or(path) {
-        "/dev/null" => "/dev/null",
-        path if git_diff_name && DIFF_PREFIXES.iter().any(|s| path.starts_with(s)) => &path[2..],
-        path if git_diff_name => path,
-        path => path.split('\t').next().unwrap_or(""),
+    // Other diffs follow the tab with e.g. the time of the file (diff -u, hg diff), or its
+    // revision (svn diff), which is "(nonexistent)" for an added or removed file.
+    let (path, suffix) = path.split_once('\t').unwrap_or((path, ""));
+    match (path, suffix) {
+        ("/dev/null", _) | (_, "(nonexistent)") => "/dev/null",
+        (path, _) => match git_path_prefixes {
+            Some(prefixes) => prefixes.strip(path, side),
+            None => path,
+        },
     }
     .to_string()
 }
 
+/// Restore the leading "/" which git removes from the absolute paths of files compared by
+/// `git diff --no-index`, and remove --strip-path-prefix and --path-strip leading components.
+fn clean_file_path(path: &mut String, config: &Config) {
+    if path.is_empty() || path == "/dev/null" {
+        return;
+    }
+    if is_no_index_absolute_path(path, config) {
+        path.insert(0, '/');
+    }
+    if let Some(prefix) = &config.strip_path_prefix {
+        match Path::new(path.as_str()).strip_prefix(prefix) {
+            Ok(stripped) if !stripped.as_os_str().is_empty() => {
+                *path = stripped.to_string_lossy().into_owned();
+            }
+            _ => {}
+        }
+    }
+    if config.path_strip > 0 {
+        if let Some(stripped) = strip_path_components(path, config.path_strip) {
+            *path = stripped.to_string();
+        }
+    }
+}
+
+// Remove the shortest prefix of `path` containing `n` slashes, like `patch -pN`.
+fn strip_path_components(path: &str, n: usize) -> Option<&str> {
+    let (i, _) = path.match_indices('/').nth(n.checked_sub(1)?)?;
+    Some(&path[i + 1..]).filter(|stripped| !stripped.is_empty())
+}
+
+// Whether `path` is an absolute path without its leading "/", i.e. does not exist relative to the
+// current directory but does from the root directory, in the output of `git diff --no-index`
+// (which is also what delta runs to compare two files).
+fn is_no_index_absolute_path(path: &str, config: &Config) -> bool {
+    let is_no_index = config.minus_file.is_some()
+        || matches!(
+            &*process::calling_process(),
+            CallingProcess::GitDiff(command_line) if command_line.long_options.contains("--no-index")
+        );
+    is_no_index
+        && Path::new(path).is_relative()
+        && !config
+            .cwd_of_delta_process
+            .as_ref()
+            .is_some_and(|cwd| cwd.join(path).exists())
+        && Path::new("/").join(path).exists()
+}
+
 pub fn get_file_change_description_from_file_paths(
     minus_file: &str,
     plus_file: &str,
@@ -420,12 +722,11 @@ pub fn get_file_change_description_from_file_paths(
         )
     } else {
         let format_file = |file| {
-            let formatted_file = if let Some(regex_replacement) = &config.file_regex_replacement {
-                regex_replacement.execute(file)
-            } else {
-                Cow::from(file)
-            };
-            match (config.hyperlinks, utils::path::absolute_path(file, config)) {
+            let formatted_file = utils::path::display_path(file, config);
+            match (
+                config.hyperlinks,
+                utils::path::hyperlink_target_path(file, config),
+            ) {
                 (true, Some(absolute_path)) => features::hyperlinks::format_osc8_file_hyperlink(
                     absolute_path,
                     None,
@@ -470,7 +771,12 @@ pub fn get_file_change_description_from_file_paths(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::tests::integration_test_utils::{make_config_from_args, DeltaTest};
+    use crate::env::DeltaEnv;
+    use crate::tests::integration_test_utils::{
+        make_config_from_args, make_config_from_args_and_git_config,
+        make_options_from_args_and_git_config_with_custom_env, DeltaTest,
+    };
+    use crate::tests::test_utils::temp_path;
     use insta::assert_snapshot;
 
     #[test]
@@ -538,26 +844,29 @@ mod tests {
     #[test]
     fn test_get_file_path_from_git_diff_header_line() {
         assert_eq!(
-            parse_diff_header_line("--- /dev/null", true),
+            parse_diff_header_line("--- /dev/null", Some(&DiffPathPrefixes::Default)),
             ("/dev/null".to_string(), FileEvent::Change)
         );
         for prefix in &DIFF_PREFIXES {
             assert_eq!(
-                parse_diff_header_line(&format!("--- {prefix}src/delta.rs"), true),
+                parse_diff_header_line(
+                    &format!("--- {prefix}src/delta.rs"),
+                    Some(&DiffPathPrefixes::Default)
+                ),
                 ("src/delta.rs".to_string(), FileEvent::Change)
             );
         }
         assert_eq!(
-            parse_diff_header_line("--- src/delta.rs", true),
+            parse_diff_header_line("--- src/delta.rs", Some(&DiffPathPrefixes::Default)),
             ("src/delta.rs".to_string(), FileEvent::Change)
         );
         assert_eq!(
-            parse_diff_header_line("+++ src/delta.rs", true),
+            parse_diff_header_line("+++ src/delta.rs", Some(&DiffPathPrefixes::Default)),
             ("src/delta.rs".to_string(), FileEvent::Change)
         );
 
         assert_eq!(
-            parse_diff_header_line("+++ \".\\delta.rs\"", true),
+            parse_diff_header_line("+++ \".\\delta.rs\"", Some(&DiffPathPrefixes::Default)),
             (".\\delta.rs".to_string(), FileEvent::Change)
         );
     }
@@ -565,23 +874,26 @@ mod tests {
     #[test]
     fn test_get_file_path_from_git_diff_header_line_containing_spaces() {
         assert_eq!(
-            parse_diff_header_line("+++ a/my src/delta.rs", true),
+            parse_diff_header_line("+++ a/my src/delta.rs", Some(&DiffPathPrefixes::Default)),
             ("my src/delta.rs".to_string(), FileEvent::Change)
         );
         assert_eq!(
-            parse_diff_header_line("+++ my src/delta.rs", true),
+            parse_diff_header_line("+++ my src/delta.rs", Some(&DiffPathPrefixes::Default)),
             ("my src/delta.rs".to_string(), FileEvent::Change)
         );
         assert_eq!(
-            parse_diff_header_line("+++ a/src/my delta.rs", true),
+            parse_diff_header_line("+++ a/src/my delta.rs", Some(&DiffPathPrefixes::Default)),
             ("src/my delta.rs".to_string(), FileEvent::Change)
         );
         assert_eq!(
-            parse_diff_header_line("+++ a/my src/my delta.rs", true),
+            parse_diff_header_line("+++ a/my src/my delta.rs", Some(&DiffPathPrefixes::Default)),
             ("my src/my delta.rs".to_string(), FileEvent::Change)
         );
         assert_eq!(
-            parse_diff_header_line("+++ b/my src/my enough/my delta.rs", true),
+            parse_diff_header_line(
+                "+++ b/my src/my enough/my delta.rs",
+                Some(&DiffPathPrefixes::Default)
+            ),
             (
                 "my src/my enough/my delta.rs".to_string(),
                 FileEvent::Change
@@ -592,7 +904,10 @@ mod tests {
     #[test]
     fn test_get_file_path_from_git_diff_header_line_rename() {
         assert_eq!(
-            parse_diff_header_line("rename from nospace/file2.el", true),
+            parse_diff_header_line(
+                "rename from nospace/file2.el",
+                Some(&DiffPathPrefixes::Default)
+            ),
             ("nospace/file2.el".to_string(), FileEvent::Rename)
         );
     }
@@ -600,7 +915,10 @@ mod tests {
     #[test]
     fn test_get_file_path_from_git_diff_header_line_rename_containing_spaces() {
         assert_eq!(
-            parse_diff_header_line("rename from with space/file1.el", true),
+            parse_diff_header_line(
+                "rename from with space/file1.el",
+                Some(&DiffPathPrefixes::Default)
+            ),
             ("with space/file1.el".to_string(), FileEvent::Rename)
         );
     }
@@ -608,40 +926,245 @@ mod tests {
     #[test]
     fn test_parse_diff_header_line() {
         assert_eq!(
-            parse_diff_header_line("--- src/delta.rs", false),
+            parse_diff_header_line("--- src/delta.rs", None),
+            ("src/delta.rs".to_string(), FileEvent::Change)
+        );
+        assert_eq!(
+            parse_diff_header_line("+++ src/delta.rs", None),
             ("src/delta.rs".to_string(), FileEvent::Change)
         );
+    }
+
+    #[test]
+    fn test_parse_diff_header_line_with_custom_prefixes() {
+        let prefixes = DiffPathPrefixes::Custom {
+            src: "old/".to_string(),
+            dst: "new/".to_string(),
+        };
         assert_eq!(
-            parse_diff_header_line("+++ src/delta.rs", false),
+            parse_diff_header_line("--- old/src/delta.rs", Some(&prefixes)),
             ("src/delta.rs".to_string(), FileEvent::Change)
         );
+        assert_eq!(
+            parse_diff_header_line("+++ new/src/delta.rs", Some(&prefixes)),
+            ("src/delta.rs".to_string(), FileEvent::Change)
+        );
+        assert_eq!(
+            get_repeated_file_path_from_diff_line("diff --git old/a.rs new/a.rs", &prefixes),
+            Some("a.rs".to_string())
+        );
+        let noprefix = DiffPathPrefixes::Custom {
+            src: String::new(),
+            dst: String::new(),
+        };
+        assert_eq!(
+            parse_diff_header_line("--- a/src/delta.rs", Some(&noprefix)),
+            ("a/src/delta.rs".to_string(), FileEvent::Change)
+        );
+    }
+
+    #[test]
+    fn test_diff_path_prefixes_from_git_config() {
+        for (git_config_contents, expected) in [
+            ("", DiffPathPrefixes::Default),
+            (
+                "[diff]\n    noprefix = true\n",
+                DiffPathPrefixes::Custom {
+                    src: String::new(),
+                    dst: String::new(),
+                },
+            ),
+            (
+                "[diff]\n    dstPrefix = new/\n",
+                DiffPathPrefixes::Custom {
+                    src: "a/".to_string(),
+                    dst: "new/".to_string(),
+                },
+            ),
+        ] {
+            let git_config_path = "delta__test_diff_path_prefixes.gitconfig";
+            let config = make_config_from_args_and_git_config(
+                &[],
+                Some(git_config_contents.as_bytes()),
+                Some(git_config_path),
+            );
+            assert_eq!(config.diff_path_prefixes, expected, "{git_config_contents}");
+            std::fs::remove_file(git_config_path).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_language_configs() {
+        let git_config_contents = b"
+[delta]
+    plus-style = green
+[delta \"lang:python\"]
+    plus-style = red
+[delta \"lang:rs\"]
+    plus-style = blue
+";
+        let git_config_path = "delta__test_language_configs.gitconfig";
+        let config = make_config_from_args_and_git_config(
+            &[],
+            Some(git_config_contents),
+            Some(git_config_path),
+        );
+        std::fs::remove_file(git_config_path).unwrap();
+        let mut languages: Vec<&String> = config.language_configs.keys().collect();
+        languages.sort();
+        assert_eq!(languages, ["python", "rs"]);
+        let file = |name| {
+            format!(
+                "\
+--- a/{name}
++++ b/{name}
+@@ -1 +1 @@
+-a
++b
+"
+            )
+        };
+        let input = file("a.py") + &file("a.rs") + &file("a.txt");
+        let output = DeltaTest::with_config(&config)
+            .explain_ansi()
+            .with_input(&input);
+        let plus_lines: Vec<&str> = output
+            .output
+            .lines()
+            .filter(|line| line.ends_with(")b(normal)"))
+            .collect();
+        assert_eq!(
+            plus_lines,
+            ["(red)b(normal)", "(blue)b(normal)", "(green)b(normal)"]
+        );
+    }
+
+    #[test]
+    fn test_strip_path_prefix_and_no_index_absolute_paths() {
+        let dir = temp_path("test_no_index_absolute_paths");
+        std::fs::create_dir_all(dir.join("src")).unwrap();
+        std::fs::write(dir.join("src/a.txt"), "x\n").unwrap();
+        // git removes the leading "/" of absolute paths.
+        let path = dir.join("src/a.txt").to_string_lossy()[1..].to_string();
+        let input = format!(
+            "\
+diff --git a/{path} b/{path}
+index 587be6b..e69de29 100644
+--- a/{path}
++++ b/{path}
+@@ -1 +1 @@
+-x
++y
+"
+        );
+        let calling_process = format!("git diff --no-index /{path} /{path}");
+        DeltaTest::with_args(&[])
+            .with_calling_process(&calling_process)
+            .with_input(&input)
+            .expect_contains(&format!("\n/{path}\n"));
+        DeltaTest::with_args(&["--strip-path-prefix", &dir.to_string_lossy()])
+            .with_calling_process(&calling_process)
+            .with_input(&input)
+            .expect_contains("\nsrc/a.txt\n");
+        // Without --no-index, the path is left as it is. (The test config compares two files, as
+        // `delta a b` does.)
+        DeltaTest::with_args(&[])
+            .set_config(|config| config.minus_file = None)
+            .with_input(&input)
+            .expect_contains(&format!("\n{path}\n"));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_strip_path_components() {
+        assert_eq!(
+            strip_path_components("build/src/delta.rs", 1),
+            Some("src/delta.rs")
+        );
+        assert_eq!(
+            strip_path_components("build/src/delta.rs", 2),
+            Some("delta.rs")
+        );
+        assert_eq!(strip_path_components("build/src/delta.rs", 3), None);
+        assert_eq!(
+            strip_path_components("/build/src/delta.rs", 1),
+            Some("build/src/delta.rs")
+        );
+        assert_eq!(strip_path_components("build/src/", 2), None);
+        assert_eq!(strip_path_components("delta.rs", 0), None);
+    }
+
+    #[test]
+    fn test_path_strip() {
+        DeltaTest::with_args(&["--path-strip", "2"])
+            .with_input(
+                BIN_AND_TXT_FILE_ADDED
+                    .replace("TXT", "one/two/TXT")
+                    .as_str(),
+            )
+            .expect_contains("\nadded: TXT\n")
+            .expect_contains("\nadded: BIN (binary file)\n");
+    }
+
+    #[test]
+    fn test_path_rewrite() {
+        let input = BIN_AND_TXT_FILE_ADDED.replace("TXT", "one/two/TXT");
+        let output =
+            DeltaTest::with_args(&["--path-rewrite", r"^one/(\w+)/ => $1:", "--hyperlinks"])
+                .with_input(&input)
+                .expect_contains("\nadded: two:TXT\n");
+        assert!(output.raw_output.contains("one/two/TXT"));
+
+        let output = DeltaTest::with_args(&[
+            "--path-rewrite",
+            r"^one/(\w+)/ => $1/",
+            "--hyperlinks",
+            "--path-rewrite-hyperlinks",
+        ])
+        .with_input(&input)
+        .expect_contains("\nadded: two/TXT\n");
+        assert!(!output.raw_output.contains("one/two/TXT"));
     }
 
     #[test]
     fn test_get_repeated_file_path_from_diff_line() {
         assert_eq!(
-            get_repeated_file_path_from_diff_line("diff --git a/src/main.rs b/src/main.rs"),
+            get_repeated_file_path_from_diff_line(
+                "diff --git a/src/main.rs b/src/main.rs",
+                &DiffPathPrefixes::Default
+            ),
             Some("src/main.rs".to_string())
         );
         assert_eq!(
-            get_repeated_file_path_from_diff_line("diff --git a/a b/a"),
+            get_repeated_file_path_from_diff_line("diff --git a/a b/a", &DiffPathPrefixes::Default),
             Some("a".to_string())
         );
         assert_eq!(
-            get_repeated_file_path_from_diff_line("diff --git a/a b b/a b"),
+            get_repeated_file_path_from_diff_line(
+                "diff --git a/a b b/a b",
+                &DiffPathPrefixes::Default
+            ),
             Some("a b".to_string())
         );
         assert_eq!(
-            get_repeated_file_path_from_diff_line("diff --git a/a b/aa"),
+            get_repeated_file_path_from_diff_line(
+                "diff --git a/a b/aa",
+                &DiffPathPrefixes::Default
+            ),
             None
         );
         assert_eq!(
             get_repeated_file_path_from_diff_line(
-                "diff --git a/.config/Code - Insiders/User/settings.json b/.config/Code - Insiders/User/settings.json"),
+                "diff --git a/.config/Code - Insiders/User/settings.json b/.config/Code - Insiders/User/settings.json",
+                &DiffPathPrefixes::Default
+            ),
             Some(".config/Code - Insiders/User/settings.json".to_string())
         );
         assert_eq!(
-            get_repeated_file_path_from_diff_line(r#"diff --git "a/quoted" "b/quoted""#),
+            get_repeated_file_path_from_diff_line(
+                r#"diff --git "a/quoted" "b/quoted""#,
+                &DiffPathPrefixes::Default
+            ),
             Some("quoted".to_string())
         );
     }
@@ -685,6 +1208,43 @@ index 0000000..323fae0
         });
     }
 
+    #[test]
+    fn test_github_actions_groups() {
+        let config = make_config_from_args(&["--github-actions-groups"]);
+        assert!(!config.github_actions_groups);
+        let env = DeltaEnv {
+            github_actions: Some("true".into()),
+            ..DeltaEnv::default()
+        };
+        let config = Config::from(make_options_from_args_and_git_config_with_custom_env(
+            env,
+            &["--github-actions-groups"],
+            None,
+            None,
+        ));
+        assert!(config.github_actions_groups);
+        let result = DeltaTest::with_config(&config)
+            .with_input(BIN_AND_TXT_FILE_ADDED)
+            .output;
+        assert_snapshot!(result, @r###"
+        ::group::BIN (binary file)
+
+        added: BIN (binary file)
+        ───────────────────────────────────────────
+        ::endgroup::
+        ::group::TXT
+
+        added: TXT
+        ───────────────────────────────────────────
+
+        ───┐
+        1: │
+        ───┘
+        plain text
+        ::endgroup::
+        "###);
+    }
+
     pub const DIFF_AMBIGUOUS_HEADER_3X_MINUS: &str = r#"--- a.lua
 +++ b.lua
 @@ -1,5 +1,4 @@
@@ -869,4 +1429,79 @@ index 0000000..323fae0
         (normal 52)-- World?(normal)
         "###);
     }
+
+    #[test]
+    fn test_get_file_path_from_hg_or_svn_diff_line() {
+        assert_eq!(
+            get_file_path_from_hg_or_svn_diff_line("diff -r 1234abcd5678 src/a.rs"),
+            Some("src/a.rs".to_string())
+        );
+        assert_eq!(
+            get_file_path_from_hg_or_svn_diff_line(
+                "diff -r 1234abcd5678 -r 5678abcd1234 src/my file.rs"
+            ),
+            Some("src/my file.rs".to_string())
+        );
+        assert_eq!(
+            get_file_path_from_hg_or_svn_diff_line("Index: src/a.rs"),
+            Some("src/a.rs".to_string())
+        );
+        assert_eq!(
+            get_file_path_from_hg_or_svn_diff_line("diff -r -u one two"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_hg_diff_header() {
+        let output = DeltaTest::with_args(&[])
+            .set_config(|config| config.minus_file = None)
+            .with_input(
+                "\
+diff -r 1234abcd5678 src/a.rs
+--- a/src/a.rs\tThu Jan 01 00:00:00 1970 +0000
++++ b/src/a.rs\tThu Jan 01 00:00:00 1970 +0000
+@@ -1,2 +1,2 @@
+-a
++b
+ x
+diff -r 1234abcd5678 src/new.rs
+--- /dev/null\tThu Jan 01 00:00:00 1970 +0000
++++ b/src/new.rs\tThu Jan 01 00:00:00 1970 +0000
+@@ -0,0 +1,1 @@
++n
+",
+            )
+            .expect_contains("\nsrc/a.rs\n")
+            .expect_contains("\nadded: src/new.rs\n");
+        assert!(!output.output.contains("diff -r"));
+    }
+
+    #[test]
+    fn test_svn_diff_header() {
+        let output = DeltaTest::with_args(&[])
+            .set_config(|config| config.minus_file = None)
+            .with_input(
+                "\
+Index: src/a.rs
+===================================================================
+--- src/a.rs\t(revision 123)
++++ src/a.rs\t(working copy)
+@@ -1,2 +1,2 @@
+-a
++b
+ x
+Index: src/old.rs
+===================================================================
+--- src/old.rs\t(revision 123)
++++ src/old.rs\t(nonexistent)
+@@ -1 +0,0 @@
+-o
+",
+            )
+            .expect_contains("\nsrc/a.rs\n")
+            .expect_contains("\nremoved: src/old.rs\n");
+        assert!(!output.output.contains("Index:"));
+        assert!(!output.output.contains("====="));
+    }
 }
@@ -1,10 +1,11 @@
-use crate::delta::{DiffType, InMergeConflict, MergeParents, State, StateMachine};
-use crate::handlers::diff_header::{get_repeated_file_path_from_diff_line, FileEvent};
+use crate::delta::{DiffType, InMergeConflict, MergeParents, Source, State, StateMachine};
+use crate::handlers::diff_header::FileEvent;
 
 impl<'a> StateMachine<'a> {
     #[inline]
     fn test_diff_header_diff_line(&self) -> bool {
         self.line.starts_with("diff ")
+            || (self.source == Source::Subversion && self.line.starts_with("Index: "))
     }
 
     #[allow(clippy::unnecessary_wraps)]
@@ -24,15 +25,17 @@ impl<'a> StateMachine<'a> {
                 State::DiffHeader(DiffType::Unified)
             };
         self.handle_pending_line_with_diff_name()?;
+        self.end_log_group()?;
         self.handled_diff_header_header_line_file_pair = None;
         self.diff_line.clone_from(&self.line);
+        self.blob_ids = None;
 
         // Pre-fill header fields from the diff line. For added, removed or renamed files
         // these are updated precisely on actual header minus and header plus lines.
         // But for modified binary files which are not added, removed or renamed, there
         // are no minus and plus lines. Without the code below, in such cases the file names
         // would remain unchanged from the previous diff, or empty for the very first diff.
-        let name = get_repeated_file_path_from_diff_line(&self.line).unwrap_or_default();
+        let name = self.file_path_from_diff_line(&self.line);
         self.minus_file.clone_from(&name);
         self.plus_file.clone_from(&name);
         self.minus_file_event = FileEvent::Change;
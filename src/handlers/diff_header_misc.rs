@@ -13,6 +13,16 @@ impl<'a> StateMachine<'a> {
     }
 
     pub fn handle_diff_header_misc_line(&mut self) -> std::io::Result<bool> {
+        if let (State::DiffHeader(_), Some(blob_ids)) =
+            (&self.state, self.line.strip_prefix("index "))
+        {
+            // E.g. "index 587be6b..e69de29 100644"
+            self.blob_ids = blob_ids
+                .split(' ')
+                .next()
+                .and_then(|blob_ids| blob_ids.split_once(".."))
+                .map(|(minus, plus)| (minus.to_string(), plus.to_string()));
+        }
         if !self.test_diff_file_missing() && !self.test_diff_is_binary() {
             return Ok(false);
         }
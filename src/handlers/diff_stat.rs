@@ -5,6 +5,7 @@ use std::borrow::Cow;
 use crate::config::Config;
 use crate::delta::{State, StateMachine};
 use crate::features;
+use crate::style::Style;
 use crate::utils;
 
 impl<'a> StateMachine<'a> {
@@ -30,6 +31,15 @@ impl<'a> StateMachine<'a> {
                 }
             }
         }
+        if !handled_line && !self.config.color_only {
+            if let Some(formatted_line) = format_diff_summary_line(&self.line, self.config)
+                .or_else(|| localize_diff_shortstat_line(&self.line, self.config))
+            {
+                self.painter.emit()?;
+                writeln!(self.painter.writer, "{formatted_line}")?;
+                handled_line = true
+            }
+        }
         Ok(handled_line)
     }
 }
@@ -43,6 +53,112 @@ lazy_static! {
         Regex::new(r" ([^\| ][^\|]+[^\| ]) +(\| +[0-9]+ .+)").unwrap();
 }
 
+// A regex to capture the operation, the path and the similarity index in lines like these, which
+// are output by git's --summary option:
+// " create mode 100644 src/features/secrets.rs"
+// " mode change 100644 => 100755 build.sh"
+// " rename src/{parse.rs => parse_styles.rs} (87%)"
+lazy_static! {
+    static ref DIFF_SUMMARY_LINE_REGEX: Regex = Regex::new(
+        r"^ (create mode \d+|delete mode \d+|mode change \d+ => \d+|rename|copy|rewrite) (.+?)(?: \((\d+%)\))?$"
+    )
+    .unwrap();
+    static ref RENAME_WITH_COMMON_PARTS_REGEX: Regex =
+        Regex::new(r"^(.*)\{(.*) => (.*)\}(.*)$").unwrap();
+}
+
+/// Style a line of git's --summary output: created and deleted files are colored, the similarity
+/// index of renamed and copied files is styled, and paths of files which exist after the change
+/// are hyperlinked.
+pub fn format_diff_summary_line(line: &str, config: &Config) -> Option<String> {
+    if DIFF_STAT_LINE_REGEX.is_match(line) {
+        return None;
+    }
+    let caps = DIFF_SUMMARY_LINE_REGEX.captures(line)?;
+    let operation = caps.get(1).unwrap().as_str();
+    let path = caps.get(2).unwrap().as_str();
+    let (style, formatted_path) = match operation.split(' ').next() {
+        Some("create") => (
+            config.summary_create_style,
+            hyperlink_path(path, path, config),
+        ),
+        Some("delete") => (config.summary_delete_style, Cow::from(path)),
+        Some("rename" | "copy") => (
+            Style::default(),
+            hyperlink_path(path, &destination_path(path), config),
+        ),
+        _ => (Style::default(), hyperlink_path(path, path, config)),
+    };
+    let similarity = match caps.get(3) {
+        Some(similarity) => format!(
+            " {}",
+            config
+                .summary_similarity_style
+                .paint(format!("({})", similarity.as_str()))
+        ),
+        None => String::new(),
+    };
+    Some(format!(
+        " {}{}",
+        style.paint(format!("{operation} {formatted_path}")),
+        similarity
+    ))
+}
+
+// A regex to capture the numbers in the last line of a diffstat, like this one:
+// " 3 files changed, 1234 insertions(+), 5 deletions(-)"
+lazy_static! {
+    static ref DIFF_SHORTSTAT_LINE_REGEX: Regex =
+        Regex::new(r"^ \d+ files? changed(?:, \d+ insertions?\(\+\))?(?:, \d+ deletions?\(-\))?$")
+            .unwrap();
+    static ref NUMBER_REGEX: Regex = Regex::new(r"\d+").unwrap();
+}
+
+/// The last line of a diffstat with its numbers grouped by thousands as in the locale. The lines
+/// of the files are left alone, so as not to break the alignment of their bars.
+pub fn localize_diff_shortstat_line(line: &str, config: &Config) -> Option<String> {
+    config.locale.thousands_separator?;
+    if !DIFF_SHORTSTAT_LINE_REGEX.is_match(line) {
+        return None;
+    }
+    let localized = NUMBER_REGEX.replace_all(line, |caps: &regex::Captures| {
+        caps[0]
+            .parse()
+            .map(|n| config.locale.format_number(n))
+            .unwrap_or_else(|_| caps[0].to_string())
+    });
+    Some(localized.into_owned())
+}
+
+// The path after a rename or copy, which git displays like "old => new" or, if the paths have a
+// common prefix or suffix, like "src/{old => new}.rs".
+fn destination_path(path: &str) -> String {
+    if let Some(caps) = RENAME_WITH_COMMON_PARTS_REGEX.captures(path) {
+        // E.g. "{src => }/main.rs" for a file moved to the root of the repository.
+        format!("{}{}{}", &caps[1], &caps[3], &caps[4])
+            .replace("//", "/")
+            .trim_start_matches('/')
+            .to_string()
+    } else {
+        match path.split_once(" => ") {
+            Some((_, destination)) => destination.to_string(),
+            None => path.to_string(),
+        }
+    }
+}
+
+fn hyperlink_path<'a>(text: &'a str, path: &str, config: &Config) -> Cow<'a, str> {
+    match (
+        config.hyperlinks,
+        utils::path::hyperlink_target_path(path, config),
+    ) {
+        (true, Some(absolute_path)) => {
+            features::hyperlinks::format_osc8_file_hyperlink(absolute_path, None, text, config)
+        }
+        _ => Cow::from(text),
+    }
+}
+
 pub fn relativize_path_in_diff_stat_line(
     line: &str,
     cwd_relative_to_repo_root: &str,
@@ -56,7 +172,7 @@ pub fn relativize_path_in_diff_stat_line(
     let relative_path = relative_path.to_str()?;
     let formatted_path = match (
         config.hyperlinks,
-        utils::path::absolute_path(path_relative_to_repo_root, config),
+        utils::path::hyperlink_target_path(path_relative_to_repo_root, config),
     ) {
         (true, Some(absolute_path)) => features::hyperlinks::format_osc8_file_hyperlink(
             absolute_path,
@@ -77,6 +193,7 @@ pub fn relativize_path_in_diff_stat_line(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::tests::integration_test_utils::{make_config_from_args, DeltaTest};
 
     #[test]
     fn test_diff_stat_line_regex_1() {
@@ -96,6 +213,85 @@ mod tests {
         assert_eq!(caps.get(2).unwrap().as_str(), "|  2 ++");
     }
 
+    #[test]
+    fn test_localize_diff_shortstat_line() {
+        let line = " 3 files changed, 1234 insertions(+), 56789 deletions(-)";
+        let config = make_config_from_args(&["--locale", "de_DE.UTF-8"]);
+        assert_eq!(
+            localize_diff_shortstat_line(line, &config).as_deref(),
+            Some(" 3 files changed, 1.234 insertions(+), 56.789 deletions(-)")
+        );
+        assert_eq!(
+            localize_diff_shortstat_line(" 1 file changed, 1000 deletions(-)", &config).as_deref(),
+            Some(" 1 file changed, 1.000 deletions(-)")
+        );
+        assert_eq!(
+            localize_diff_shortstat_line(" src/a.rs | 1234 +++", &config),
+            None
+        );
+        let config = make_config_from_args(&["--locale", "C"]);
+        assert_eq!(localize_diff_shortstat_line(line, &config), None);
+    }
+
+    #[test]
+    fn test_destination_path() {
+        for (path, expected) in [
+            ("f.rs => g.rs", "g.rs"),
+            ("d/{h.txt => i.txt}", "d/i.txt"),
+            ("src/{ => handlers}/blame.rs", "src/handlers/blame.rs"),
+            ("{src => }/main.rs", "main.rs"),
+            ("src/{old => }/lib.rs", "src/lib.rs"),
+        ] {
+            assert_eq!(destination_path(path), expected);
+        }
+    }
+
+    #[test]
+    fn test_diff_summary_lines() {
+        DeltaTest::with_args(&[
+            "--summary-create-style",
+            "green",
+            "--summary-delete-style",
+            "red",
+            "--summary-similarity-style",
+            "blue",
+        ])
+        .explain_ansi()
+        .with_input(
+            "\
+ src/{parse.rs => parse_styles.rs} | 4 ++--
+ 3 files changed, 2 insertions(+), 2 deletions(-)
+ create mode 100644 src/features/secrets.rs
+ delete mode 100755 build.sh
+ rename src/{parse.rs => parse_styles.rs} (87%)
+ mode change 100644 => 100755 run.sh
+",
+        )
+        .expect(
+            r#"
+            #indent_mark
+            (normal)src/{parse.rs => parse_styles.rs} | 4 ++--
+             3 files changed, 2 insertions(+), 2 deletions(-)
+             (green)create mode 100644 src/features/secrets.rs(normal)
+             (red)delete mode 100755 build.sh(normal)
+             rename src/{parse.rs => parse_styles.rs} (blue)(87%)(normal)
+             mode change 100644 => 100755 run.sh"#,
+        );
+    }
+
+    #[test]
+    fn test_diff_summary_line_hyperlink() {
+        DeltaTest::with_args(&[
+            "--hyperlinks",
+            "--hyperlinks-file-link-format",
+            "file-line://{path}",
+        ])
+        .with_input(" rename src/{parse.rs => parse_styles.rs} (87%)\n")
+        .expect_raw_contains(
+            "parse_styles.rs\x1b\\src/{parse.rs => parse_styles.rs}\x1b]8;;\x1b\\",
+        );
+    }
+
     #[test]
     fn test_relative_path() {
         for (path, cwd_relative_to_repo_root, expected) in &[
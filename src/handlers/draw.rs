@@ -25,30 +25,92 @@ pub type DrawFunction = dyn FnMut(
     ansi_term::Style,
 ) -> std::io::Result<()>;
 
+/// The draw function for the decoration style, whether it pads the text, and the style of the
+/// decoration. With `ascii`, boxes and lines are drawn with ASCII characters instead of box-drawing
+/// characters.
 pub fn get_draw_function(
     decoration_style: DecorationStyle,
+    ascii: bool,
 ) -> (Box<DrawFunction>, bool, ansi_term::Style) {
+    let draw = |write: WriteDecorated| -> Box<DrawFunction> {
+        Box::new(
+            move |writer, text, raw_text, addendum, line_width, text_style, decoration_style| {
+                let decoration = Decoration {
+                    style: decoration_style,
+                    chars: BoxChars::new(decoration_style, ascii),
+                };
+                write(
+                    writer, text, raw_text, addendum, line_width, text_style, decoration,
+                )
+            },
+        )
+    };
     match decoration_style {
-        DecorationStyle::Box(style) => (Box::new(write_boxed), true, style),
-        DecorationStyle::BoxWithUnderline(style) => {
-            (Box::new(write_boxed_with_underline), true, style)
-        }
+        DecorationStyle::Box(style) => (draw(write_boxed), true, style),
+        DecorationStyle::BoxWithUnderline(style) => (draw(write_boxed_with_underline), true, style),
         DecorationStyle::BoxWithOverline(style) => {
             // TODO: not implemented
-            (Box::new(write_boxed), true, style)
+            (draw(write_boxed), true, style)
         }
         DecorationStyle::BoxWithUnderOverline(style) => {
             // TODO: not implemented
-            (Box::new(write_boxed), true, style)
+            (draw(write_boxed), true, style)
+        }
+        DecorationStyle::Underline(style) => (draw(write_underlined), false, style),
+        DecorationStyle::Overline(style) => (draw(write_overlined), false, style),
+        DecorationStyle::UnderOverline(style) => (draw(write_underoverlined), false, style),
+        DecorationStyle::NoDecoration => {
+            (draw(write_no_decoration), false, ansi_term::Style::new())
+        }
+    }
+}
+
+type WriteDecorated =
+    fn(&mut dyn Write, &str, &str, &str, &Width, Style, Decoration) -> std::io::Result<()>;
+
+/// The style of a decoration and the characters it is drawn with.
+#[derive(Clone, Copy)]
+struct Decoration {
+    style: ansi_term::Style,
+    chars: BoxChars,
+}
+
+#[derive(Clone, Copy)]
+struct BoxChars {
+    horizontal: &'static str,
+    vertical: &'static str,
+    up_left: &'static str,
+    down_left: &'static str,
+    up_horizontal: &'static str,
+}
+
+impl BoxChars {
+    fn new(decoration_style: ansi_term::Style, ascii: bool) -> Self {
+        if ascii {
+            Self {
+                horizontal: if decoration_style.is_bold { "=" } else { "-" },
+                vertical: "|",
+                up_left: "+",
+                down_left: "+",
+                up_horizontal: "+",
+            }
+        } else if decoration_style.is_bold {
+            Self {
+                horizontal: box_drawing::heavy::HORIZONTAL,
+                vertical: box_drawing::heavy::VERTICAL,
+                up_left: box_drawing::heavy::UP_LEFT,
+                down_left: box_drawing::heavy::DOWN_LEFT,
+                up_horizontal: box_drawing::heavy::UP_HORIZONTAL,
+            }
+        } else {
+            Self {
+                horizontal: box_drawing::light::HORIZONTAL,
+                vertical: box_drawing::light::VERTICAL,
+                up_left: box_drawing::light::UP_LEFT,
+                down_left: box_drawing::light::DOWN_LEFT,
+                up_horizontal: box_drawing::light::UP_HORIZONTAL,
+            }
         }
-        DecorationStyle::Underline(style) => (Box::new(write_underlined), false, style),
-        DecorationStyle::Overline(style) => (Box::new(write_overlined), false, style),
-        DecorationStyle::UnderOverline(style) => (Box::new(write_underoverlined), false, style),
-        DecorationStyle::NoDecoration => (
-            Box::new(write_no_decoration),
-            false,
-            ansi_term::Style::new(),
-        ),
     }
 }
 
@@ -59,7 +121,7 @@ fn write_no_decoration(
     addendum: &str,
     _line_width: &Width, // ignored
     text_style: Style,
-    _decoration_style: ansi_term::Style,
+    _decoration: Decoration,
 ) -> std::io::Result<()> {
     if text_style.is_raw {
         writeln!(writer, "{raw_text}")?;
@@ -71,31 +133,24 @@ fn write_no_decoration(
 
 /// Write text to stream, surrounded by a box, leaving the cursor just
 /// beyond the bottom right corner.
-pub fn write_boxed(
+fn write_boxed(
     writer: &mut dyn Write,
     text: &str,
     raw_text: &str,
     addendum: &str,
     _line_width: &Width, // ignored
     text_style: Style,
-    decoration_style: ansi_term::Style,
+    decoration: Decoration,
 ) -> std::io::Result<()> {
-    let up_left = if decoration_style.is_bold {
-        box_drawing::heavy::UP_LEFT
-    } else {
-        box_drawing::light::UP_LEFT
-    };
     let box_width = ansi::measure_text_width(text);
     write_boxed_partial(
+        writer, text, raw_text, addendum, box_width, text_style, decoration,
+    )?;
+    writeln!(
         writer,
-        text,
-        raw_text,
-        addendum,
-        box_width,
-        text_style,
-        decoration_style,
+        "{}",
+        decoration.style.paint(decoration.chars.up_left)
     )?;
-    writeln!(writer, "{}", decoration_style.paint(up_left))?;
     Ok(())
 }
 
@@ -108,17 +163,11 @@ fn write_boxed_with_underline(
     addendum: &str,
     line_width: &Width,
     text_style: Style,
-    decoration_style: ansi_term::Style,
+    decoration: Decoration,
 ) -> std::io::Result<()> {
     let box_width = ansi::measure_text_width(text);
     write_boxed_with_horizontal_whisker(
-        writer,
-        text,
-        raw_text,
-        addendum,
-        box_width,
-        text_style,
-        decoration_style,
+        writer, text, raw_text, addendum, box_width, text_style, decoration,
     )?;
     let line_width = match *line_width {
         Width::Fixed(n) => n,
@@ -132,7 +181,7 @@ fn write_boxed_with_underline(
             0
         },
         text_style,
-        decoration_style,
+        decoration,
     )?;
     writeln!(writer)?;
     Ok(())
@@ -151,7 +200,7 @@ fn write_underlined(
     addendum: &str,
     line_width: &Width,
     text_style: Style,
-    decoration_style: ansi_term::Style,
+    decoration: Decoration,
 ) -> std::io::Result<()> {
     _write_under_or_over_lined(
         UnderOverline::Under,
@@ -161,7 +210,7 @@ fn write_underlined(
         addendum,
         line_width,
         text_style,
-        decoration_style,
+        decoration,
     )
 }
 
@@ -172,7 +221,7 @@ fn write_overlined(
     addendum: &str,
     line_width: &Width,
     text_style: Style,
-    decoration_style: ansi_term::Style,
+    decoration: Decoration,
 ) -> std::io::Result<()> {
     _write_under_or_over_lined(
         UnderOverline::Over,
@@ -182,7 +231,7 @@ fn write_overlined(
         addendum,
         line_width,
         text_style,
-        decoration_style,
+        decoration,
     )
 }
 
@@ -193,7 +242,7 @@ fn write_underoverlined(
     addendum: &str,
     line_width: &Width,
     text_style: Style,
-    decoration_style: ansi_term::Style,
+    decoration: Decoration,
 ) -> std::io::Result<()> {
     _write_under_or_over_lined(
         UnderOverline::Underover,
@@ -203,7 +252,7 @@ fn write_underoverlined(
         addendum,
         line_width,
         text_style,
-        decoration_style,
+        decoration,
     )
 }
 
@@ -216,7 +265,7 @@ fn _write_under_or_over_lined(
     addendum: &str,
     line_width: &Width,
     text_style: Style,
-    decoration_style: ansi_term::Style,
+    decoration: Decoration,
 ) -> std::io::Result<()> {
     let text_width = ansi::measure_text_width(text);
     let line_width = match *line_width {
@@ -224,7 +273,7 @@ fn _write_under_or_over_lined(
         Width::Variable => text_width,
     };
     let write_line = |writer: &mut dyn Write| -> std::io::Result<()> {
-        write_horizontal_line(writer, line_width, text_style, decoration_style)?;
+        write_horizontal_line(writer, line_width, text_style, decoration)?;
         writeln!(writer)?;
         Ok(())
     };
@@ -248,17 +297,14 @@ fn write_horizontal_line(
     writer: &mut dyn Write,
     width: usize,
     _text_style: Style,
-    decoration_style: ansi_term::Style,
+    decoration: Decoration,
 ) -> std::io::Result<()> {
-    let horizontal = if decoration_style.is_bold {
-        box_drawing::heavy::HORIZONTAL
-    } else {
-        box_drawing::light::HORIZONTAL
-    };
     write!(
         writer,
         "{}",
-        decoration_style.paint(horizontal.repeat(width))
+        decoration
+            .style
+            .paint(decoration.chars.horizontal.repeat(width))
     )
 }
 
@@ -269,23 +315,16 @@ fn write_boxed_with_horizontal_whisker(
     addendum: &str,
     box_width: usize,
     text_style: Style,
-    decoration_style: ansi_term::Style,
+    decoration: Decoration,
 ) -> std::io::Result<()> {
-    let up_horizontal = if decoration_style.is_bold {
-        box_drawing::heavy::UP_HORIZONTAL
-    } else {
-        box_drawing::light::UP_HORIZONTAL
-    };
     write_boxed_partial(
+        writer, text, raw_text, addendum, box_width, text_style, decoration,
+    )?;
+    write!(
         writer,
-        text,
-        raw_text,
-        addendum,
-        box_width,
-        text_style,
-        decoration_style,
+        "{}",
+        decoration.style.paint(decoration.chars.up_horizontal)
     )?;
-    write!(writer, "{}", decoration_style.paint(up_horizontal))?;
     Ok(())
 }
 
@@ -296,27 +335,14 @@ fn write_boxed_partial(
     addendum: &str,
     box_width: usize,
     text_style: Style,
-    decoration_style: ansi_term::Style,
+    decoration: Decoration,
 ) -> std::io::Result<()> {
-    let (horizontal, down_left, vertical) = if decoration_style.is_bold {
-        (
-            box_drawing::heavy::HORIZONTAL,
-            box_drawing::heavy::DOWN_LEFT,
-            box_drawing::heavy::VERTICAL,
-        )
-    } else {
-        (
-            box_drawing::light::HORIZONTAL,
-            box_drawing::light::DOWN_LEFT,
-            box_drawing::light::VERTICAL,
-        )
-    };
-    let horizontal_edge = horizontal.repeat(box_width);
+    let horizontal_edge = decoration.chars.horizontal.repeat(box_width);
     writeln!(
         writer,
         "{}{}",
-        decoration_style.paint(&horizontal_edge),
-        decoration_style.paint(down_left),
+        decoration.style.paint(&horizontal_edge),
+        decoration.style.paint(decoration.chars.down_left),
     )?;
     if text_style.is_raw {
         write!(writer, "{raw_text}")?;
@@ -326,7 +352,7 @@ fn write_boxed_partial(
     write!(
         writer,
         "{}\n{}",
-        decoration_style.paint(vertical),
-        decoration_style.paint(&horizontal_edge),
+        decoration.style.paint(decoration.chars.vertical),
+        decoration.style.paint(&horizontal_edge),
     )
 }
@@ -0,0 +1,57 @@
+use crate::ansi;
+use crate::delta::StateMachine;
+
+impl<'a> StateMachine<'a> {
+    /// Forward lines containing terminal graphics sequences (kitty graphics protocol, iTerm2
+    /// inline images) verbatim. The image payload would be corrupted by delta's usual ANSI
+    /// stripping and re-painting, so such a line is never handled as diff content.
+    pub fn handle_graphics_line(&mut self) -> std::io::Result<bool> {
+        if !ansi::contains_graphics_sequence(&self.raw_line) {
+            return Ok(false);
+        }
+        self.painter.paint_buffered_minus_and_plus_lines();
+        self.painter.emit()?;
+        writeln!(self.painter.writer, "{}", self.raw_line)?;
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::integration_test_utils::DeltaTest;
+
+    const KITTY_IMAGE: &str = "\x1b_Ga=T,f=100;iVBORw0KGgoAAAANSUhEUgAAAAEAAAABCAYAAAAfFcSJ\x1b\\";
+    const ITERM2_IMAGE: &str = "\x1b]1337;File=inline=1:iVBORw0KGgoAAAANSUhEUgAAAAEAAAAB\x07";
+
+    #[test]
+    fn test_graphics_sequences_are_passed_through() {
+        for image in [KITTY_IMAGE, ITERM2_IMAGE] {
+            let input = format!("some output\n{image}\nmore output\n");
+            DeltaTest::with_args(&["--max-line-length", "10"])
+                .with_input(&input)
+                .expect_raw_contains(&format!("\n{image}\n"));
+        }
+    }
+
+    #[test]
+    fn test_graphics_sequence_in_hunk_is_passed_through() {
+        let input = format!(
+            "\
+diff --git a/a.md b/a.md
+index 1111111..2222222 100644
+--- a/a.md
++++ b/a.md
+@@ -1,2 +1,2 @@
+ unchanged
+-old
+{KITTY_IMAGE}
++new
+"
+        );
+        DeltaTest::with_args(&[])
+            .with_input(&input)
+            .expect_raw_contains(&format!("\n{KITTY_IMAGE}\n"))
+            .expect_contains("old")
+            .expect_contains("new");
+    }
+}
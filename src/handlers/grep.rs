@@ -13,7 +13,7 @@ use crate::delta::{State, StateMachine};
 use crate::handlers::{self, ripgrep_json};
 use crate::paint::{self, BgShouldFill, StyleSectionSpecifier};
 use crate::style::Style;
-use crate::utils::{process, tabs};
+use crate::utils::{self, process, tabs};
 
 use super::hunk_header::HunkHeaderIncludeHunkLabel;
 
@@ -98,7 +98,7 @@ impl<'a> StateMachine<'a> {
         // Try parse_raw_grep_line on raw_line, and fall back to parse_grep_line
         let raw_line = self.raw_line.clone(); // TODO: avoid clone
         let line;
-        let grep_line = if let Some(grep_line) = parse_raw_grep_line(&raw_line) {
+        let mut grep_line = if let Some(grep_line) = parse_raw_grep_line(&raw_line) {
             grep_line
         } else {
             line = self.line.clone(); // TODO: avoid clone
@@ -112,6 +112,16 @@ impl<'a> StateMachine<'a> {
         if matches!(grep_line.line_type, LineType::Ignore) {
             return Ok(true);
         }
+        if self.config.relative_paths {
+            let mut path = grep_line.path.to_string();
+            utils::path::relativize_path_maybe(&mut path, self.config);
+            if path != grep_line.path {
+                // The match highlighting is located in the raw line after the path, so rewrite
+                // it there too, as if git had output the relative path.
+                self.raw_line = self.raw_line.replacen(grep_line.path.as_ref(), &path, 1);
+                grep_line.path = Cow::from(path);
+            }
+        }
         let first_path = previous_path.is_none();
         let new_path = first_path || previous_path.as_deref() != Some(&grep_line.path);
         let line_number_jump = previous_line < &grep_line.line_number.as_ref().map(|n| n - 1);
@@ -176,6 +186,7 @@ impl<'a> StateMachine<'a> {
                 &HunkHeaderIncludeHunkLabel::Yes,
                 &HunkHeaderIncludeCodeFragment::Yes,
                 "",
+                None,
                 self.config,
             )?
         }
@@ -239,6 +250,7 @@ impl<'a> StateMachine<'a> {
             &HunkHeaderIncludeHunkLabel::No,
             &HunkHeaderIncludeCodeFragment::Yes,
             grep_line.line_type.file_path_separator(),
+            None,
             self.config,
         )
     }
@@ -265,6 +277,7 @@ impl<'a> StateMachine<'a> {
                     &HunkHeaderIncludeHunkLabel::Yes,
                     &HunkHeaderIncludeCodeFragment::Yes,
                     grep_line.line_type.file_path_separator(),
+                    None,
                     self.config,
                 )?
             }
@@ -664,7 +677,7 @@ $
     .unwrap()
 }
 
-pub fn parse_grep_line(line: &str) -> Option<GrepLine> {
+pub fn parse_grep_line(line: &str) -> Option<GrepLine<'_>> {
     if line.starts_with('{') {
         ripgrep_json::parse_line(line)
     } else {
@@ -682,7 +695,7 @@ pub fn parse_grep_line(line: &str) -> Option<GrepLine> {
     }
 }
 
-pub fn parse_raw_grep_line(raw_line: &str) -> Option<GrepLine> {
+pub fn parse_raw_grep_line(raw_line: &str) -> Option<GrepLine<'_>> {
     // Early exit if we don't have an escape sequence
     if !raw_line.starts_with('\x1b') {
         return None;
@@ -1291,4 +1304,24 @@ mod tests {
             ]))
         );
     }
+
+    #[test]
+    fn test_grep_relative_paths() {
+        use crate::tests::integration_test_utils::{make_config_from_args, DeltaTest};
+
+        let mut config = make_config_from_args(&["--relative-paths"]);
+        config.cwd_relative_to_repo_root = Some("src/utils/".into());
+        // With --full-name the paths output by git grep are relative to the repository root.
+        DeltaTest::with_config(&config)
+            .with_calling_process("git grep --full-name -n pattern")
+            .with_input("src/utils/path.rs:1:pattern\nsrc/cli.rs:2:pattern\n")
+            .expect_contains("path.rs")
+            .expect_contains("../cli.rs");
+        // Without it they are already relative to the current directory.
+        DeltaTest::with_config(&config)
+            .with_calling_process("git grep -n pattern")
+            .with_input("path.rs:1:pattern\n../cli.rs:2:pattern\n")
+            .expect_contains("path.rs")
+            .expect_contains("../cli.rs");
+    }
 }
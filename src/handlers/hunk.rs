@@ -5,6 +5,7 @@ use lazy_static::lazy_static;
 use crate::cli;
 use crate::config::{delta_unreachable, Config};
 use crate::delta::{DiffType, InMergeConflict, MergeParents, State, StateMachine};
+use crate::minusplus::MinusPlusIndex::{Minus, Plus};
 use crate::paint::{prepare, prepare_raw_line};
 use crate::style;
 use crate::utils::process::{self, CallingProcess};
@@ -72,14 +73,17 @@ impl<'a> StateMachine<'a> {
         // would process the entire file before painting anything.
         if self.painter.minus_lines.len() > self.config.line_buffer_size
             || self.painter.plus_lines.len() > self.config.line_buffer_size
+            || self.painter.is_buffer_full()
         {
             self.painter.paint_buffered_minus_and_plus_lines();
         }
         if let State::HunkHeader(_, parsed_hunk_header, line, raw_line) = &self.state.clone() {
             self.emit_hunk_header_line(parsed_hunk_header, line, raw_line)?;
         }
+        self.redact_secrets_in_line();
         self.state = match new_line_state(&self.line, &self.raw_line, &self.state, self.config) {
             Some(HunkMinus(diff_type, raw_line)) => {
+                self.painter.paint_buffered_zero_lines(true);
                 if let HunkPlus(_, _) = self.state {
                     // We have just entered a new subhunk; process the previous one
                     // and flush the line buffers.
@@ -89,21 +93,26 @@ impl<'a> StateMachine<'a> {
                 let line = prepare(&self.line, n_parents, self.config);
                 let state = HunkMinus(diff_type, raw_line);
                 self.painter.minus_lines.push((line, state.clone()));
+                self.painter.lines_had_cr[Minus].push(self.line_had_cr);
                 self.minus_line_counter.count_line();
                 state
             }
             Some(HunkPlus(diff_type, raw_line)) => {
+                self.painter.paint_buffered_zero_lines(true);
                 let n_parents = diff_type.n_parents();
                 let line = prepare(&self.line, n_parents, self.config);
                 let state = HunkPlus(diff_type, raw_line);
                 self.painter.plus_lines.push((line, state.clone()));
+                self.painter.lines_had_cr[Plus].push(self.line_had_cr);
                 state
             }
             Some(HunkZero(diff_type, raw_line)) => {
                 // We are in a zero (unchanged) line, therefore we have just exited a subhunk (a
                 // sequence of consecutive minus (removed) and/or plus (added) lines). Process that
                 // subhunk and flush the line buffers.
-                self.painter.paint_buffered_minus_and_plus_lines();
+                if !matches!(self.state, HunkZero(_, _)) {
+                    self.painter.paint_buffered_minus_and_plus_lines();
+                }
                 let n_parents = if is_word_diff() {
                     0
                 } else {
@@ -111,10 +120,36 @@ impl<'a> StateMachine<'a> {
                 };
                 let line = prepare(&self.line, n_parents, self.config);
                 let state = State::HunkZero(diff_type, raw_line);
-                self.painter.paint_zero_line(&line, state.clone());
+                if self.config.max_context_lines > 0 || self.config.context_fade > 0 {
+                    self.painter.zero_lines.push((line, state.clone()));
+                } else {
+                    self.painter.paint_zero_line(&line, state.clone(), None);
+                }
                 self.minus_line_counter.count_line();
                 state
             }
+            None if self.line.starts_with('\\') && self.config.missing_newline_marker.is_some() => {
+                // '\ No newline at end of file': the marker is attached to the preceding line
+                // when it is painted.
+                match self.state {
+                    HunkMinus(_, _) => self.painter.lines_missing_final_newline[Minus] = true,
+                    HunkPlus(_, _) => self.painter.lines_missing_final_newline[Plus] = true,
+                    _ => {
+                        // The preceding unchanged line has already been emitted.
+                        self.painter.paint_buffered_minus_and_plus_lines();
+                        let marker = self.config.missing_newline_marker.as_deref().unwrap();
+                        let style = self
+                            .config
+                            .missing_newline_marker_style
+                            .on_top_of(self.config.zero_style);
+                        self.painter
+                            .output_buffer
+                            .push_str(&style.paint(marker).to_string());
+                        self.painter.output_buffer.push('\n');
+                    }
+                }
+                self.state.clone()
+            }
             _ => {
                 // The first character here could be e.g. '\' from '\ No newline at end of file'. This
                 // is not a hunk line, but the parser does not have a more accurate state corresponding
@@ -127,6 +162,7 @@ impl<'a> StateMachine<'a> {
                 State::HunkZero(Unified, None)
             }
         };
+        self.split_hunk();
         self.painter.emit()?;
         Ok(true)
     }
@@ -276,6 +312,330 @@ fn new_line_state(
 mod tests {
     use crate::tests::integration_test_utils::DeltaTest;
 
+    mod strip_cr {
+        use super::*;
+
+        #[test]
+        fn test_strip_cr_auto_marks_differing_carriage_return() {
+            DeltaTest::with_args(&[])
+                .with_input(GIT_DIFF_CRLF_TO_LF)
+                .expect_contains("foo␍")
+                .expect_contains("bar\n");
+        }
+
+        #[test]
+        fn test_strip_cr_auto_marks_aligned_lines() {
+            // The added line is aligned with the second removed line, not with the first.
+            let result = DeltaTest::with_args(&[]).with_input(
+                "\
+diff --git a/file b/file
+index 72943a1..f761ec1 100644
+--- a/file
++++ b/file
+@@ -1,2 +1 @@
+\x1b[31m-x\r\x1b[m
+\x1b[31m-foo\r\x1b[m
+\x1b[32m+foo\x1b[m
+",
+            );
+            assert!(result.output.contains("foo␍"));
+            assert!(!result.output.contains("x␍"));
+        }
+
+        #[test]
+        fn test_strip_cr_auto_marks_uncolored_carriage_return() {
+            // E.g. the output of `diff -u` or `git diff --no-color`.
+            let result = DeltaTest::with_args(&[]).with_input(DIFF_CRLF_TO_LF);
+            assert!(result.output.contains("foo␍"));
+            assert!(result.output.contains("bar\n"));
+            assert!(!result.output.contains('\r'));
+        }
+
+        #[test]
+        fn test_strip_cr_never_keeps_uncolored_carriage_return() {
+            let result = DeltaTest::with_args(&["--strip-cr", "never"]).with_input(DIFF_CRLF_TO_LF);
+            assert!(!result.output.contains('␍'));
+            assert!(result.output.contains("foo\r"));
+            assert!(result.output.contains("baz\r"));
+        }
+
+        #[test]
+        fn test_strip_cr_always_does_not_mark() {
+            let result =
+                DeltaTest::with_args(&["--strip-cr", "always"]).with_input(GIT_DIFF_CRLF_TO_LF);
+            assert!(!result.output.contains('␍'));
+            assert!(!result.output.contains('\r'));
+        }
+
+        #[test]
+        fn test_strip_cr_never_keeps_carriage_return() {
+            let result =
+                DeltaTest::with_args(&["--strip-cr", "never"]).with_input(GIT_DIFF_CRLF_TO_LF);
+            assert!(!result.output.contains('␍'));
+            assert!(result.output.contains("foo\r"));
+        }
+
+        // The first line changes from CRLF to LF, the second has CRLF on both sides.
+        const GIT_DIFF_CRLF_TO_LF: &str = "\
+diff --git a/file b/file
+index 72943a1..f761ec1 100644
+--- a/file
++++ b/file
+@@ -1,2 +1,2 @@
+\x1b[31m-foo\r\x1b[m
+\x1b[31m-bar\r\x1b[m
+\x1b[32m+foo\x1b[m
+\x1b[32m+baz\r\x1b[m
+";
+
+        // As GIT_DIFF_CRLF_TO_LF, without colors.
+        const DIFF_CRLF_TO_LF: &str = "\
+--- a/file
++++ b/file
+@@ -1,2 +1,2 @@
+-foo\r
+-bar\r
++foo
++baz\r
+";
+    }
+
+    mod max_context_lines {
+        use super::*;
+
+        #[test]
+        fn test_fold_unchanged_lines() {
+            DeltaTest::with_args(&["--max-context-lines", "2"])
+                .with_input(GIT_DIFF_LONG_CONTEXT)
+                .expect_after_header(
+                    "
+                    ⋯ 2 unchanged lines ⋯
+                    3
+                    4
+                    5
+                    five
+                    6
+                    7
+                    ⋯ 4 unchanged lines ⋯
+                    12
+                    13
+                    14
+                    fourteen
+                    15
+                    16
+                    ⋯ 2 unchanged lines ⋯",
+                );
+        }
+
+        #[test]
+        fn test_single_line_is_not_folded() {
+            DeltaTest::with_args(&["--max-context-lines", "3"])
+                .with_input(GIT_DIFF_LONG_CONTEXT)
+                .expect_after_header(
+                    "
+                    1
+                    2
+                    3
+                    4
+                    5
+                    five
+                    6
+                    7
+                    8
+                    ⋯ 2 unchanged lines ⋯
+                    11
+                    12
+                    13
+                    14
+                    fourteen
+                    15
+                    16
+                    17
+                    18",
+                );
+        }
+
+        #[test]
+        fn test_line_numbers_of_lines_after_fold() {
+            DeltaTest::with_args(&["--max-context-lines", "1", "--line-numbers"])
+                .with_input(GIT_DIFF_LONG_CONTEXT)
+                .expect_contains("⋯ 6 unchanged lines ⋯\n 13 ⋮ 13 │13\n");
+        }
+
+        const GIT_DIFF_LONG_CONTEXT: &str = "\
+diff --git a/file b/file
+index 3b47f8f..8a5e4d1 100644
+--- a/file
++++ b/file
+@@ -1,18 +1,18 @@
+ 1
+ 2
+ 3
+ 4
+-5
++five
+ 6
+ 7
+ 8
+ 9
+ 10
+ 11
+ 12
+ 13
+-14
++fourteen
+ 15
+ 16
+ 17
+ 18
+";
+    }
+
+    mod max_buffer_lines {
+        use super::*;
+
+        #[test]
+        fn test_buffered_lines_are_painted_when_full() {
+            // The removed line is painted before the added line is read, and so is not paired
+            // with it.
+            let args = ["--minus-emph-style", "bold red", "--max-line-distance", "1"];
+            DeltaTest::with_args(&args)
+                .explain_ansi()
+                .with_input(GIT_DIFF)
+                .expect_contains("(bold red)");
+            let output = DeltaTest::with_args(&[&args[..], &["--max-buffer-lines", "1"]].concat())
+                .explain_ansi()
+                .with_input(GIT_DIFF)
+                .output;
+            assert!(!output.contains("(bold red)"));
+        }
+
+        #[test]
+        fn test_change_density_of_long_hunk_is_not_read_ahead() {
+            let args = ["--hunk-change-density", "--max-buffer-lines"];
+            DeltaTest::with_args(&[&args[..], &["3"]].concat())
+                .with_input(GIT_DIFF)
+                .expect_contains("\n1: ▰▰▰▰▰▱▱▱ 1+/1- │\n");
+            DeltaTest::with_args(&[&args[..], &["2"]].concat())
+                .with_input(GIT_DIFF)
+                .expect_contains("\n1: │\n")
+                .expect_contains("\nab\nac\nd\n");
+        }
+
+        const GIT_DIFF: &str = "\
+diff --git a/file b/file
+index 1111111..2222222 100644
+--- a/file
++++ b/file
+@@ -1,2 +1,2 @@
+-ab
++ac
+ d
+";
+    }
+
+    mod missing_newline {
+        use super::*;
+
+        #[test]
+        fn test_newline_added_at_end_of_file() {
+            DeltaTest::with_args(&[])
+                .with_input(&format!(
+                    "{DIFF_HEADER}-b\n\\ No newline at end of file\n+b\n"
+                ))
+                .expect_after_header(
+                    "
+                    a
+                    b  ⏎∅ newline added at end of file ",
+                );
+        }
+
+        #[test]
+        fn test_newline_added_at_end_of_file_with_marker() {
+            DeltaTest::with_args(&["--missing-newline-marker", "<no eol>"])
+                .with_input(&format!(
+                    "{DIFF_HEADER}-b\n\\ No newline at end of file\n+b\n"
+                ))
+                .expect_after_header(
+                    "
+                    a
+                    b  <no eol> newline added at end of file ",
+                );
+        }
+
+        #[test]
+        fn test_newline_removed_at_end_of_file() {
+            DeltaTest::with_args(&["--missing-newline-marker", "<no eol>"])
+                .with_input(&format!(
+                    "{DIFF_HEADER}-b\n+b\n\\ No newline at end of file\n"
+                ))
+                .expect_after_header(
+                    "
+                    a
+                    b  <no eol> newline removed at end of file ",
+                );
+        }
+
+        #[test]
+        fn test_marker_attached_to_changed_lines() {
+            DeltaTest::with_args(&[])
+                .with_input(&format!(
+                    "{DIFF_HEADER}-b\n\\ No newline at end of file\n+c\n\\ No newline at end of file\n"
+                ))
+                .expect_after_header(
+                    "
+                    a
+                    b⏎∅
+                    c⏎∅",
+                );
+        }
+
+        #[test]
+        fn test_marker_style() {
+            let output = DeltaTest::with_args(&[
+                "--minus-style",
+                "blue",
+                "--plus-style",
+                "green",
+                "--missing-newline-marker-style",
+                "red",
+            ])
+            .explain_ansi()
+            .with_input(&format!(
+                "{DIFF_HEADER}-b\n\\ No newline at end of file\n+c\n\\ No newline at end of file\n"
+            ));
+            assert!(output.output.contains("(blue)b(red)⏎∅"));
+            assert!(output.output.contains("(green)c(red)⏎∅"));
+        }
+
+        #[test]
+        fn test_marker_after_unchanged_line() {
+            DeltaTest::with_args(&[])
+                .with_input(&format!(
+                    "{DIFF_HEADER}-a\n+A\n b\n\\ No newline at end of file\n"
+                ))
+                .expect_contains("A\nb\n⏎∅");
+        }
+
+        #[test]
+        fn test_empty_marker_emits_line_unaltered() {
+            DeltaTest::with_args(&["--missing-newline-marker", ""])
+                .with_input(&format!(
+                    "{DIFF_HEADER}-b\n\\ No newline at end of file\n+b\n"
+                ))
+                .expect_contains("b\n\\ No newline at end of file\nb");
+        }
+
+        const DIFF_HEADER: &str = "\
+diff --git a/file b/file
+index 0a207c0..422c2b7 100644
+--- a/file
++++ b/file
+@@ -1,2 +1,2 @@
+ a
+";
+    }
+
     mod word_diff {
         use super::*;
 
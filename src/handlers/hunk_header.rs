@@ -18,14 +18,24 @@
 // src/hunk_header.rs:119: fn write_to_output_buffer( │
 // ───────────────────────────────────────────────────┘
 // ```
+use std::borrow::Cow;
 use std::convert::TryInto;
 use std::fmt::Write as FmtWrite;
+use std::io::BufRead;
+use std::path::Path;
+
+use bytelines::ByteLines;
 
 use super::draw;
+use crate::ansi;
 use crate::config::{
     Config, HunkHeaderIncludeCodeFragment, HunkHeaderIncludeFilePath, HunkHeaderIncludeLineNumber,
 };
 use crate::delta::{self, DiffType, InMergeConflict, MergeParents, State, StateMachine};
+use crate::features::change_density::ChangeDensity;
+use crate::features::function_separators::{self, FileSymbols};
+use crate::features::inline_blame::InlineBlame;
+use crate::features::split_hunks::HunkSplitter;
 use crate::paint::{self, BgShouldFill, Painter, StyleSectionSpecifier};
 use crate::style::{DecorationStyle, Style};
 use lazy_static::lazy_static;
@@ -37,6 +47,14 @@ pub struct ParsedHunkHeader {
     line_numbers_and_hunk_lengths: Vec<(usize, usize)>,
 }
 
+impl ParsedHunkHeader {
+    /// The start and length of each range of the hunk header: those of the parents of a combined
+    /// diff, followed by that of the new file.
+    pub fn line_numbers_and_hunk_lengths(&self) -> &[(usize, usize)] {
+        &self.line_numbers_and_hunk_lengths
+    }
+}
+
 pub enum HunkHeaderIncludeHunkLabel {
     Yes,
     No,
@@ -147,6 +165,45 @@ impl<'a> StateMachine<'a> {
         Ok(handled_line)
     }
 
+    /// If the current line is a hunk header, read the lines of the hunk from the input into the
+    /// lookahead buffer (unless they are already in it) and count them, so that its change density
+    /// can be displayed in the header (--hunk-change-density). The hunk ends after the numbers of
+    /// lines in the header. The change density of a hunk longer than --max-buffer-lines is not
+    /// known.
+    pub fn read_hunk_ahead<I: BufRead>(&mut self, lines: &mut ByteLines<I>) {
+        if !self.test_hunk_header_line() {
+            return;
+        }
+        self.change_density = None;
+        let (minus_length, plus_length) = match parse_unified_hunk_lengths(&self.line) {
+            Some(lengths) => lengths,
+            // Combined diffs are not supported.
+            None => return,
+        };
+        let mut change_density = ChangeDensity::default();
+        let mut n_counted = 0;
+        while !change_density.is_complete(minus_length, plus_length) {
+            if n_counted == self.hunk_lookahead.len() {
+                let max_lines = self.config.max_buffer_lines;
+                if max_lines > 0 && n_counted >= max_lines {
+                    return;
+                }
+                match lines.next() {
+                    Some(Ok(raw_line)) => self.hunk_lookahead.push_back(raw_line.to_vec()),
+                    _ => break,
+                }
+            }
+            let raw_line = &self.hunk_lookahead[n_counted];
+            n_counted += 1;
+            let is_hunk_line = change_density
+                .count_line(&ansi::strip_ansi_codes(&String::from_utf8_lossy(raw_line)));
+            if !is_hunk_line {
+                break;
+            }
+        }
+        self.change_density = Some(change_density);
+    }
+
     /// Emit the hunk header, with any requested decoration.
     pub fn emit_hunk_header_line(
         &mut self,
@@ -155,20 +212,97 @@ impl<'a> StateMachine<'a> {
         raw_line: &str,
     ) -> std::io::Result<bool> {
         self.painter.paint_buffered_minus_and_plus_lines();
+        self.painter.zero_lines_follow_change = false;
         self.painter.set_highlighter();
         self.painter.emit()?;
+        self.follow_terminal_size();
 
         let ParsedHunkHeader {
             code_fragment,
             line_numbers_and_hunk_lengths,
         } = parsed_hunk_header;
 
+        if let Some(hunk_index) = &mut self.hunk_index {
+            let path = if self.plus_file == "/dev/null" {
+                &self.minus_file
+            } else {
+                &self.plus_file
+            };
+            hunk_index.record(path, line_numbers_and_hunk_lengths);
+        }
+
+        // In a combined diff, the last range is that of the new file.
+        if let (Some(html_anchors), Some((plus_start, _))) = (
+            &mut self.painter.html_anchors,
+            line_numbers_and_hunk_lengths.last(),
+        ) {
+            html_anchors.start_hunk(*plus_start);
+        }
+
+        // Combined diffs have more than two ranges; only the lines of the old file are blamed.
+        self.painter.inline_blame = match line_numbers_and_hunk_lengths.as_slice() {
+            &[(start, count), _] if self.config.inline_blame => Some(InlineBlame::for_hunk(
+                &self.minus_file,
+                &self
+                    .current_commit
+                    .as_ref()
+                    .map_or_else(|| "HEAD".to_string(), |commit| format!("{commit}^")),
+                start,
+                count,
+                self.config,
+            )),
+            _ => None,
+        };
+
+        if let &[_, (plus_start, _)] = line_numbers_and_hunk_lengths.as_slice() {
+            let path = if self.plus_file == "/dev/null" {
+                &self.minus_file
+            } else {
+                &self.plus_file
+            };
+            for gutter in &mut self.painter.gutters {
+                gutter.start_hunk(path, plus_start, self.config);
+            }
+            if let Some(diagnostics) = &mut self.painter.diagnostics {
+                diagnostics.start_hunk(path, plus_start);
+            }
+        }
+
+        self.hunk_splitter = match line_numbers_and_hunk_lengths.as_slice() {
+            &[minus_range, plus_range] if self.config.split_hunks > 0 => {
+                Some(HunkSplitter::new(minus_range, plus_range))
+            }
+            _ => None,
+        };
+
+        if let (true, Some(&(start, _))) = (
+            self.config.function_separators && self.plus_file != "/dev/null",
+            line_numbers_and_hunk_lengths.last(),
+        ) {
+            let (plus_file, config) = (&self.plus_file, self.config);
+            let file_symbols = self
+                .file_symbols
+                .get_or_insert_with(|| FileSymbols::for_file(plus_file, config));
+            if let Some(label) = file_symbols.separator_label(start) {
+                writeln!(
+                    self.painter.writer,
+                    "{}",
+                    function_separators::format_separator(&label, self.config)
+                )?;
+            }
+        }
+
         if self.config.line_numbers {
-            self.painter
-                .line_numbers_data
-                .as_mut()
-                .unwrap()
+            let line_numbers_data = self.painter.line_numbers_data.as_mut().unwrap();
+            line_numbers_data
                 .initialize_hunk(line_numbers_and_hunk_lengths, self.plus_file.to_string());
+            // An id of zeros stands for an added file, which has no old version.
+            line_numbers_data.minus_version = match &self.blob_ids {
+                Some((minus_id, _)) if !minus_id.bytes().all(|byte| byte == b'0') => {
+                    Some((minus_id.clone(), self.minus_file.clone()))
+                }
+                _ => None,
+            };
         }
 
         if self.config.hunk_header_style.is_raw {
@@ -182,6 +316,10 @@ impl<'a> StateMachine<'a> {
                 writeln!(self.painter.writer)?;
             }
 
+            let change_density = self
+                .change_density
+                .take()
+                .map(|change_density| change_density.format(self.config));
             write_line_of_code_with_optional_path_and_line_number(
                 code_fragment,
                 line_numbers_and_hunk_lengths,
@@ -201,10 +339,12 @@ impl<'a> StateMachine<'a> {
                 &HunkHeaderIncludeHunkLabel::Yes,
                 &self.config.hunk_header_style_include_code_fragment,
                 ":",
+                change_density.as_deref(),
                 self.config,
             )?;
         };
         self.painter.set_highlighter();
+        self.use_highlighted_hunk(line);
         Ok(true)
     }
 }
@@ -229,10 +369,19 @@ lazy_static! {
     .unwrap();
 }
 
+/// The numbers of lines of the old and new file in a unified diff hunk header, e.g. (15, 14) for
+/// "@@ -74,15 +74,14 @@". None if the line is not the hunk header of a unified diff.
+pub fn parse_unified_hunk_lengths(line: &str) -> Option<(usize, usize)> {
+    match parse_hunk_header(line)?.line_numbers_and_hunk_lengths[..] {
+        [(_, minus_length), (_, plus_length)] => Some((minus_length, plus_length)),
+        _ => None,
+    }
+}
+
 /// Given input like
 /// "@@ -74,15 +74,14 @@ pub fn delta("
 /// Return " pub fn delta(" and a vector of (line_number, hunk_length) tuples.
-fn parse_hunk_header(line: &str) -> Option<ParsedHunkHeader> {
+pub fn parse_hunk_header(line: &str) -> Option<ParsedHunkHeader> {
     if let Some(caps) = HUNK_HEADER_REGEX.captures(line) {
         let file_coordinates = &caps[1];
         let line_numbers_and_hunk_lengths = HUNK_HEADER_FILE_COORDINATE_REGEX
@@ -265,8 +414,10 @@ fn write_hunk_header_raw(
     raw_line: &str,
     config: &Config,
 ) -> std::io::Result<()> {
-    let (mut draw_fn, pad, decoration_ansi_term_style) =
-        draw::get_draw_function(config.hunk_header_style.decoration_style);
+    let (mut draw_fn, pad, decoration_ansi_term_style) = draw::get_draw_function(
+        config.hunk_header_style.decoration_style,
+        config.ascii_decorations,
+    );
     if config.hunk_header_style.decoration_style != DecorationStyle::NoDecoration {
         writeln!(painter.writer)?;
     }
@@ -298,9 +449,11 @@ pub fn write_line_of_code_with_optional_path_and_line_number(
     include_hunk_label: &HunkHeaderIncludeHunkLabel,
     include_code_fragment: &HunkHeaderIncludeCodeFragment,
     file_path_separator: &str,
+    annotation: Option<&str>,
     config: &Config,
 ) -> std::io::Result<()> {
-    let (mut draw_fn, _, decoration_ansi_term_style) = draw::get_draw_function(decoration_style);
+    let (mut draw_fn, _, decoration_ansi_term_style) =
+        draw::get_draw_function(decoration_style, config.ascii_decorations);
     let line = if config.color_only {
         line.to_string()
     } else if matches!(include_code_fragment, HunkHeaderIncludeCodeFragment::Yes)
@@ -323,16 +476,22 @@ pub fn write_line_of_code_with_optional_path_and_line_number(
         config,
     );
 
-    if !line.is_empty() || !file_with_line_number.is_empty() {
+    if !line.is_empty() || !file_with_line_number.is_empty() || annotation.is_some() {
         write_to_output_buffer(
             &file_with_line_number,
             file_path_separator,
             line,
             style_sections,
             include_hunk_label,
+            plus_file,
             painter,
             config,
         );
+        // E.g. the change density of the hunk (--hunk-change-density).
+        if let Some(annotation) = annotation {
+            painter.output_buffer.push_str(annotation);
+            painter.output_buffer.push(' ');
+        }
         draw_fn(
             painter.writer,
             &painter.output_buffer,
@@ -385,23 +544,25 @@ fn paint_file_path_with_line_number(
     )
 }
 
+#[allow(clippy::too_many_arguments)]
 fn write_to_output_buffer(
     file_with_line_number: &str,
     file_path_separator: &str,
     line: String,
     style_sections: Option<StyleSectionSpecifier>,
     include_hunk_label: &HunkHeaderIncludeHunkLabel,
+    file: &str,
     painter: &mut Painter,
     config: &Config,
 ) {
-    if matches!(include_hunk_label, HunkHeaderIncludeHunkLabel::Yes)
-        && !config.hunk_label.is_empty()
-    {
-        let _ = write!(
-            &mut painter.output_buffer,
-            "{} ",
-            config.hunk_header_file_style.paint(&config.hunk_label)
-        );
+    if matches!(include_hunk_label, HunkHeaderIncludeHunkLabel::Yes) {
+        if let Some(hunk_label) = config.hunk_label.get(file, &painter.syntax.name) {
+            let _ = write!(
+                &mut painter.output_buffer,
+                "{} ",
+                config.hunk_header_file_style.paint(hunk_label)
+            );
+        }
     }
     if !file_with_line_number.is_empty() {
         // The code fragment in "line" adds whitespace, but if only a line number is printed
@@ -428,6 +589,80 @@ fn write_to_output_buffer(
     }
 }
 
+/// The text displayed before hunk headers. This is either a single label, or labels for different
+/// file types, keyed by file name, extension, or language, with * as the fallback. A label may
+/// contain the placeholder {language}.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct HunkLabel {
+    labels: Vec<(String, String)>,
+}
+
+lazy_static! {
+    static ref HUNK_LABEL_MAP_REGEX: Regex =
+        Regex::new(r#"^\s*[^\s:,"]+\s*:\s*"[^"]*"\s*(,\s*[^\s:,"]+\s*:\s*"[^"]*"\s*)*$"#).unwrap();
+    static ref HUNK_LABEL_MAP_ENTRY_REGEX: Regex =
+        Regex::new(r#"([^\s:,"]+)\s*:\s*"([^"]*)""#).unwrap();
+}
+
+impl HunkLabel {
+    pub fn parse(s: &str) -> Self {
+        let labels = if HUNK_LABEL_MAP_REGEX.is_match(s) {
+            HUNK_LABEL_MAP_ENTRY_REGEX
+                .captures_iter(s)
+                .map(|c| (c[1].to_lowercase(), c[2].to_string()))
+                .collect()
+        } else if s.is_empty() {
+            Vec::new()
+        } else {
+            vec![("*".to_string(), s.to_string())]
+        };
+        Self { labels }
+    }
+
+    /// The label for hunks of `file`, which is highlighted as `language`.
+    pub fn get(&self, file: &str, language: &str) -> Option<Cow<'_, str>> {
+        let file_name = Path::new(file)
+            .file_name()
+            .map(|s| s.to_string_lossy().to_lowercase());
+        let extension = Path::new(file)
+            .extension()
+            .map(|s| s.to_string_lossy().to_lowercase());
+        let language_key = language.to_lowercase();
+        let label = [
+            file_name,
+            extension,
+            Some(language_key),
+            Some("*".to_string()),
+        ]
+        .iter()
+        .flatten()
+        .find_map(|key| self.labels.iter().find(|(k, _)| k == key))
+        .map(|(_, label)| label)
+        .filter(|label| !label.is_empty())?;
+        Some(if label.contains("{language}") {
+            Cow::from(label.replace("{language}", language))
+        } else {
+            Cow::from(label.as_str())
+        })
+    }
+
+    /// A regular expression (alternation) matching any of the labels.
+    pub fn to_regex(&self) -> String {
+        self.labels
+            .iter()
+            .filter(|(_, label)| !label.is_empty())
+            .map(|(_, label)| {
+                label
+                    .split("{language}")
+                    .map(regex::escape)
+                    .collect::<Vec<_>>()
+                    .join(".+?")
+            })
+            .collect::<Vec<_>>()
+            .join("|")
+    }
+}
+
 #[cfg(test)]
 pub mod tests {
     use super::*;
@@ -664,4 +899,90 @@ src/handlers/submodule.rs      |   4 ++--
 src/paint.rs                   |   2 +-
 7 files changed, 90 insertions(+), 54 deletions(-)
 ";
+
+    #[test]
+    fn test_hunk_label_parse() {
+        assert_eq!(HunkLabel::parse(""), HunkLabel::default());
+        let label = HunkLabel::parse("•");
+        assert_eq!(label.get("src/a.rs", "Rust").as_deref(), Some("•"));
+
+        // Not a map: the whole string is the label.
+        let label = HunkLabel::parse("Note: hunk");
+        assert_eq!(label.get("a.rs", "Rust").as_deref(), Some("Note: hunk"));
+
+        let label = HunkLabel::parse(r#"rs:"fn", py: "def",Makefile:"make", markdown:"§""#);
+        assert_eq!(label.get("src/a.rs", "Rust").as_deref(), Some("fn"));
+        assert_eq!(label.get("A.PY", "Python").as_deref(), Some("def"));
+        assert_eq!(
+            label.get("sub/Makefile", "Makefile").as_deref(),
+            Some("make")
+        );
+        assert_eq!(label.get("README.md", "Markdown").as_deref(), Some("§"));
+        assert_eq!(label.get("a.c", "C"), None);
+
+        let label = HunkLabel::parse(r#"rs:"fn", *:"{language} •""#);
+        assert_eq!(label.get("a.rs", "Rust").as_deref(), Some("fn"));
+        assert_eq!(label.get("a.c", "C").as_deref(), Some("C •"));
+    }
+
+    #[test]
+    fn test_hunk_label_to_regex() {
+        assert_eq!(HunkLabel::parse("").to_regex(), "");
+        assert_eq!(HunkLabel::parse("•").to_regex(), "•");
+        assert_eq!(
+            HunkLabel::parse(r#"rs:"fn(", *:"{language} •""#).to_regex(),
+            r"fn\(|.+? •"
+        );
+    }
+
+    #[test]
+    fn test_hunk_label_per_file_type() {
+        let input = "\
+diff --git a/src/a.rs b/src/a.rs
+index 1111111..2222222 100644
+--- a/src/a.rs
++++ b/src/a.rs
+@@ -1 +1 @@
+-let a = 1;
++let a = 2;
+diff --git a/b.py b/b.py
+index 1111111..2222222 100644
+--- a/b.py
++++ b/b.py
+@@ -1 +1 @@
+-a = 1
++a = 2
+diff --git a/c.txt b/c.txt
+index 1111111..2222222 100644
+--- a/c.txt
++++ b/c.txt
+@@ -1 +1 @@
+-a
++b
+";
+        let result = integration_test_utils::DeltaTest::with_args(&[
+            "--hunk-label",
+            r#"rs:"fn", python:"{language} def""#,
+            "--hunk-header-style",
+            "file line-number",
+        ])
+        .with_input(input);
+        result
+            .expect_contains("fn src/a.rs:1:")
+            .expect_contains("Python def b.py:1:")
+            .expect_contains("\nc.txt:1:");
+    }
+
+    #[test]
+    fn test_navigate_regex_with_hunk_label_map() {
+        let config = integration_test_utils::make_config_from_args(&[
+            "--navigate",
+            "--hunk-label",
+            r#"rs:"fn", py:"def""#,
+        ]);
+        let regex = Regex::new(config.navigate_regex.as_ref().unwrap()).unwrap();
+        assert!(regex.is_match("fn src/a.rs:1:"));
+        assert!(regex.is_match("def b.py:1:"));
+        assert!(!regex.is_match("src/a.rs:1:"));
+    }
 }
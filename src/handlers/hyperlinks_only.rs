@@ -0,0 +1,189 @@
+use lazy_static::lazy_static;
+use regex::Regex;
+
+use crate::ansi;
+use crate::delta::{DiffType, State, StateMachine};
+use crate::features::hyperlinks;
+use crate::handlers::grep;
+use crate::utils;
+
+lazy_static! {
+    // The range of the new file in a hunk header, e.g. "+12,7".
+    static ref HUNK_HEADER_PLUS_RANGE_REGEX: Regex =
+        Regex::new(r"^@@ -\S+ (\+(\d+)(?:,\d+)?) @@").unwrap();
+}
+
+impl<'a> StateMachine<'a> {
+    /// Emit the line unchanged, except for hyperlinks added to commit hashes, file paths and line
+    /// numbers (--hyperlinks-only).
+    pub fn handle_hyperlinks_only_line(&mut self) -> std::io::Result<bool> {
+        if !self.config.hyperlinks_only {
+            return Ok(false);
+        }
+        let line = self
+            .hyperlink_raw_line()
+            .unwrap_or_else(|| self.raw_line.clone());
+        writeln!(
+            self.painter.writer,
+            "{line}{}",
+            if self.line_had_cr { "\r" } else { "" }
+        )?;
+        Ok(true)
+    }
+
+    // The raw line with hyperlinks added, if it contains anything to link. The state only tracks
+    // whether the line is in a diff header or a hunk.
+    pub fn hyperlink_raw_line(&mut self) -> Option<String> {
+        let config = self.config;
+        if self.line.starts_with("commit ") {
+            self.state = State::CommitMeta;
+            return Some(
+                hyperlinks::format_commit_line_with_osc8_commit_hyperlink(&self.raw_line, config)
+                    .into_owned(),
+            );
+        }
+        if self.line.starts_with("diff ") {
+            self.state = State::DiffHeader(DiffType::Unified);
+            return None;
+        }
+        let in_hunk = matches!(self.state, State::HunkZero(_, _));
+        if !in_hunk && (self.line.starts_with("--- ") || self.line.starts_with("+++ ")) {
+            self.state = State::DiffHeader(DiffType::Unified);
+            let (path, _) = self.parse_diff_header_file_path();
+            if self.line.starts_with("+++ ") {
+                self.plus_file.clone_from(&path);
+            }
+            // E.g. "+++ b/src/main.rs" or "+++ main.rs\t2024-01-01 12:00:00"
+            let text = self.line[4..]
+                .split('\t')
+                .next()
+                .unwrap_or_default()
+                .trim_end();
+            let target = match path.as_str() {
+                "/dev/null" => None,
+                path => utils::path::hyperlink_target_path(path, config),
+            }?;
+            return hyperlink_text(&self.raw_line, 4, text, |text| {
+                hyperlinks::format_osc8_file_hyperlink(target, None, text, config).into_owned()
+            });
+        }
+        if self.line.starts_with("@@") && (in_hunk || matches!(self.state, State::DiffHeader(_))) {
+            self.state = State::HunkZero(DiffType::Unified, None);
+            let caps = HUNK_HEADER_PLUS_RANGE_REGEX.captures(&self.line)?;
+            let (range, line_number) = (caps.get(1)?, caps[2].parse().ok()?);
+            let target = utils::path::hyperlink_target_path(&self.plus_file, config)?;
+            return hyperlink_text(&self.raw_line, range.start(), range.as_str(), |text| {
+                hyperlinks::format_osc8_file_hyperlink(target, Some(line_number), text, config)
+                    .into_owned()
+            });
+        }
+        if matches!(self.state, State::Unknown) {
+            let grep_line = grep::parse_grep_line(&self.line)?;
+            let target = utils::path::hyperlink_target_path(&grep_line.path, config)?;
+            return hyperlink_text(&self.raw_line, 0, &grep_line.path, |text| {
+                hyperlinks::format_osc8_file_hyperlink(target, grep_line.line_number, text, config)
+                    .into_owned()
+            });
+        }
+        None
+    }
+}
+
+/// Wrap `text`, which starts at byte `start` of the line without ANSI escape sequences, in the
+/// hyperlink made by `hyperlink` in the raw line. Return None if the text is interrupted by escape
+/// sequences in the raw line.
+fn hyperlink_text(
+    raw_line: &str,
+    start: usize,
+    text: &str,
+    hyperlink: impl FnOnce(&str) -> String,
+) -> Option<String> {
+    let i = ansi::ansi_preserving_index(raw_line, start)?;
+    let j = i + text.len();
+    (raw_line.get(i..j)? == text)
+        .then(|| format!("{}{}{}", &raw_line[..i], hyperlink(text), &raw_line[j..]))
+}
+
+#[cfg(not(target_os = "windows"))]
+#[cfg(test)]
+mod tests {
+    use crate::tests::integration_test_utils::DeltaTest;
+    use crate::utils;
+
+    const GIT_DIFF_COLOR: &str = "\
+\x1b[33mcommit 7dc2b2d3a2c0ad7bbd1bfa6ee1b74e0e6d2a7a1c\x1b[m
+Author: A <a@example.com>
+
+    Subject
+
+\x1b[1mdiff --git a/src/a.rs b/src/a.rs\x1b[m
+\x1b[1m--- a/src/a.rs\x1b[m
+\x1b[1m+++ b/src/a.rs\x1b[m
+\x1b[36m@@ -1,2 +1,2 @@\x1b[m fn main() {
+\x1b[31m--- x\x1b[m
+\x1b[32m+++ y\x1b[m
+";
+
+    #[test]
+    fn test_hyperlinks_only() {
+        let path = utils::path::fake_delta_cwd_for_tests().join("src/a.rs");
+        let link = |url: &str, text: &str| format!("\x1b]8;;{url}\x1b\\{text}\x1b]8;;\x1b\\");
+        let expected = GIT_DIFF_COLOR
+            .replacen(
+                "7dc2b2d3a2c0ad7bbd1bfa6ee1b74e0e6d2a7a1c",
+                &link(
+                    "https://example.com/7dc2b2d3a2c0ad7bbd1bfa6ee1b74e0e6d2a7a1c",
+                    "7dc2b2d3a2c0ad7bbd1bfa6ee1b74e0e6d2a7a1c",
+                ),
+                1,
+            )
+            .replacen(
+                "--- a/src/a.rs",
+                &format!(
+                    "--- {}",
+                    link(&format!("file://{}:", path.display()), "a/src/a.rs")
+                ),
+                1,
+            )
+            .replacen(
+                "+++ b/src/a.rs",
+                &format!(
+                    "+++ {}",
+                    link(&format!("file://{}:", path.display()), "b/src/a.rs")
+                ),
+                1,
+            )
+            .replacen(
+                "+1,2",
+                &link(&format!("file://{}:1", path.display()), "+1,2"),
+                1,
+            );
+        let output = DeltaTest::with_args(&[
+            "--hyperlinks-only",
+            "--hyperlinks-commit-link-format",
+            "https://example.com/{commit}",
+            "--hyperlinks-file-link-format",
+            "file://{path}:{line}",
+        ])
+        .with_input(GIT_DIFF_COLOR);
+        assert_eq!(output.raw_output, expected);
+    }
+
+    #[test]
+    fn test_hyperlinks_only_grep() {
+        let path = utils::path::fake_delta_cwd_for_tests().join("src/a.rs");
+        DeltaTest::with_args(&[
+            "--hyperlinks-only",
+            "--hyperlinks-file-link-format",
+            "file://{path}:{line}",
+        ])
+        .with_calling_process("git grep -n main")
+        .with_input(
+            "\x1b[35msrc/a.rs\x1b[m\x1b[36m:\x1b[m3\x1b[36m:\x1b[mfn \x1b[1;31mmain\x1b[m() {\n",
+        )
+        .expect_raw_contains(&format!(
+            "\x1b[35m\x1b]8;;file://{}:3\x1b\\src/a.rs\x1b]8;;\x1b\\\x1b[m\x1b[36m:\x1b[m3",
+            path.display()
+        ));
+    }
+}
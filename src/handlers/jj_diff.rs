@@ -0,0 +1,267 @@
+use ansi_term::Color;
+use lazy_static::lazy_static;
+use regex::Regex;
+
+use crate::ansi::{self, iterator::AnsiElementIterator, iterator::Element};
+use crate::delta::{DiffType, Source, State, StateMachine};
+use crate::handlers::diff_header::{get_filename_from_diff_header_line_file_path, FileEvent};
+
+lazy_static! {
+    // E.g. "Modified regular file src/main.rs:", "Renamed regular file src/{a.rs => b.rs}:"
+    static ref FILE_HEADER_REGEX: Regex = Regex::new(
+        r"^(?P<event>Added|Removed|Modified|Renamed|Copied) (?:regular file|executable file|symlink|conflict|git submodule) (?P<path>.+):$"
+    )
+    .unwrap();
+    // E.g. "  12   14: ", the line numbers in the old and new file, either of which is blank for a
+    // removed or added line, before the text of a line.
+    static ref LINE_NUMBERS_REGEX: Regex = Regex::new(r"^ *(?P<minus>\d*) +(?P<plus>\d*): ?").unwrap();
+}
+
+/// Whether the line is the header of a file of the diff of `jj diff`.
+pub fn is_jj_file_header_line(line: &str) -> bool {
+    FILE_HEADER_REGEX.is_match(line)
+}
+
+impl<'a> StateMachine<'a> {
+    #[inline]
+    fn test_jj_diff_line(&self) -> bool {
+        self.source == Source::Jujutsu && !self.config.color_only
+    }
+
+    /// Handle a line of the diff of `jj diff` in its default format, in which the header of a file
+    /// is e.g. "Modified regular file src/main.rs:", and each line of a hunk is preceded by its
+    /// line numbers in the old and new file. The file headers and hunks are displayed as those of
+    /// a git diff. A line in which jj colors the words removed and added is split into a removed
+    /// and an added line, so that delta highlights the changes within it.
+    pub fn handle_jj_diff_line(&mut self) -> std::io::Result<bool> {
+        if !self.test_jj_diff_line() {
+            return Ok(false);
+        }
+        if let Some(caps) = FILE_HEADER_REGEX.captures(&self.line) {
+            let (minus_file, plus_file) = parse_file_paths(&caps["path"]);
+            let (minus_file, plus_file, file_event) = match &caps["event"] {
+                "Added" => ("/dev/null".to_string(), plus_file, FileEvent::Change),
+                "Removed" => (minus_file, "/dev/null".to_string(), FileEvent::Change),
+                "Renamed" => (minus_file, plus_file, FileEvent::Rename),
+                "Copied" => (minus_file, plus_file, FileEvent::Copy),
+                _ => (minus_file, plus_file, FileEvent::Change),
+            };
+            self.painter.paint_buffered_minus_and_plus_lines();
+            self.painter.emit()?;
+            self.state = State::DiffHeader(DiffType::Unified);
+            self.set_file_syntax(
+                get_filename_from_diff_header_line_file_path(if plus_file == "/dev/null" {
+                    &minus_file
+                } else {
+                    &plus_file
+                })
+                .map(String::from),
+            );
+            self.minus_file = minus_file;
+            self.plus_file = plus_file;
+            self.minus_file_event = file_event;
+            self.plus_file_event = FileEvent::Change;
+            self.file_symbols = None;
+            self.current_file_pair = Some((self.minus_file.clone(), self.plus_file.clone()));
+            self._handle_diff_header_header_line(false)?;
+            self.handled_diff_header_header_line_file_pair
+                .clone_from(&self.current_file_pair);
+            return Ok(true);
+        }
+        if !matches!(
+            self.state,
+            State::DiffHeader(_)
+                | State::HunkHeader(_, _, _, _)
+                | State::HunkZero(_, _)
+                | State::HunkMinus(_, _)
+                | State::HunkPlus(_, _)
+        ) {
+            return Ok(false);
+        }
+        if self.line.trim() == "..." {
+            // The separator of hunks: the next line starts a hunk.
+            self.painter.paint_buffered_minus_and_plus_lines();
+            self.painter.emit()?;
+            self.state = State::DiffHeader(DiffType::Unified);
+            return Ok(true);
+        }
+        let line_numbers = LINE_NUMBERS_REGEX.captures(&self.line).and_then(|caps| {
+            let number = |name: &str| caps[name].parse::<usize>().ok();
+            match (number("minus"), number("plus")) {
+                (None, None) => None,
+                (minus, plus) => Some((minus, plus, caps[0].len())),
+            }
+        });
+        let Some((minus_number, plus_number, prefix_length)) = line_numbers else {
+            // The line after the diff of the file, e.g. "    (binary)".
+            self.painter.paint_buffered_minus_and_plus_lines();
+            self.state = State::Unknown;
+            return Ok(false);
+        };
+        let text = ansi::ansi_preserving_slice(&self.raw_line, prefix_length);
+        if matches!(self.state, State::DiffHeader(_)) {
+            // The hunk is given a header, with the line numbers of its first line. A removed or
+            // added line has only one of them, which can only start a hunk without context at the
+            // start of the file.
+            self.line = format!(
+                "@@ -{} +{} @@",
+                minus_number.or(plus_number).unwrap_or_default(),
+                plus_number.or(minus_number).unwrap_or_default()
+            );
+            self.raw_line.clone_from(&self.line);
+            self.handle_hunk_header_line()?;
+        }
+        let lines = match (minus_number, plus_number) {
+            (Some(_), None) => vec![format!("-{}", ansi::strip_ansi_codes(&text))],
+            (None, Some(_)) => vec![format!("+{}", ansi::strip_ansi_codes(&text))],
+            _ => match split_changed_line(&text) {
+                (minus, plus) if minus == plus => vec![format!(" {minus}")],
+                (minus, plus) => vec![format!("-{minus}"), format!("+{plus}")],
+            },
+        };
+        for line in lines {
+            self.raw_line.clone_from(&line);
+            self.line = line;
+            self.handle_hunk_line()?;
+        }
+        Ok(true)
+    }
+}
+
+// The paths of the old and new file in the header of a file, e.g. ("src/a.rs", "src/b.rs") for
+// "src/{a.rs => b.rs}" or "a.rs => b.rs", and the same path for a file which is not renamed.
+fn parse_file_paths(path: &str) -> (String, String) {
+    if let (Some(open), Some(close)) = (path.find('{'), path.rfind('}')) {
+        if let Some((minus, plus)) = path[open + 1..close].split_once(" => ") {
+            let (prefix, suffix) = (&path[..open], &path[close + 1..]);
+            return (
+                format!("{prefix}{minus}{suffix}"),
+                format!("{prefix}{plus}{suffix}"),
+            );
+        }
+    }
+    match path.split_once(" => ") {
+        Some((minus, plus)) => (minus.to_string(), plus.to_string()),
+        None => (path.to_string(), path.to_string()),
+    }
+}
+
+// The old and new versions of a line which jj displays once, with the removed words colored red
+// and the added words green. Without colors, the line is unchanged.
+fn split_changed_line(text: &str) -> (String, String) {
+    let (mut minus, mut plus) = (String::new(), String::new());
+    let mut foreground = None;
+    for element in AnsiElementIterator::new(text) {
+        match element {
+            Element::Sgr(style, _, start, end) => {
+                // Other styles, e.g. underlining, keep the foreground color.
+                if style.foreground.is_some() {
+                    foreground = style.foreground;
+                } else if matches!(&text[start..end], "\x1b[m" | "\x1b[0m" | "\x1b[39m") {
+                    foreground = None;
+                }
+            }
+            Element::Text(start, end) => {
+                let text = &text[start..end];
+                match foreground {
+                    Some(Color::Red | Color::Fixed(1) | Color::Fixed(9)) => minus.push_str(text),
+                    Some(Color::Green | Color::Fixed(2) | Color::Fixed(10)) => plus.push_str(text),
+                    _ => {
+                        minus.push_str(text);
+                        plus.push_str(text);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    (minus, plus)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::integration_test_utils::DeltaTest;
+
+    #[test]
+    fn test_jj_diff() {
+        DeltaTest::with_args(&["--file-modified-label", "modified:"])
+            .with_input(JJ_DIFF)
+            .expect_contains(
+                "\
+modified: src/main.rs
+───────────────────────────────────────────
+
+───┐
+1: │
+───┘
+fn main() {
+    println!(\"hello\");
+    println!(\"world\");
+}
+
+────┐
+10: │
+────┘
+    a();
+    c();
+    b();
+
+added: README.md
+───────────────────────────────────────────
+
+───┐
+1: │
+───┘
+# Title",
+            );
+    }
+
+    #[test]
+    fn test_jj_diff_line_numbers() {
+        DeltaTest::with_args(&["--line-numbers"])
+            .with_input(JJ_DIFF)
+            .expect_contains(
+                "\
+  1 ⋮  1 │fn main() {
+  2 ⋮    │    println!(\"hello\");
+    ⋮  2 │    println!(\"world\");
+  3 ⋮  3 │}",
+            )
+            .expect_contains(
+                "\
+ 10 ⋮ 10 │    a();
+    ⋮ 11 │    c();
+ 11 ⋮ 12 │    b();",
+            );
+    }
+
+    #[test]
+    fn test_parse_file_paths() {
+        assert_eq!(
+            parse_file_paths("src/{a.rs => b.rs}"),
+            ("src/a.rs".to_string(), "src/b.rs".to_string())
+        );
+        assert_eq!(
+            parse_file_paths("a.rs => b.rs"),
+            ("a.rs".to_string(), "b.rs".to_string())
+        );
+        assert_eq!(
+            parse_file_paths("a.rs"),
+            ("a.rs".to_string(), "a.rs".to_string())
+        );
+    }
+
+    const JJ_DIFF: &str = "\
+Modified regular file src/main.rs:
+   1    1: fn main() {
+   2    2:     println!(\"\x1b[38;5;1m\x1b[4mhello\x1b[24m\x1b[39m\x1b[38;5;2m\x1b[4mworld\x1b[24m\x1b[39m\");
+   3    3: }
+    ...
+  10   10:     a();
+       11:     c();
+  11   12:     b();
+Added regular file README.md:
+        1: # Title
+";
+}
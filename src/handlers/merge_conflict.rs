@@ -160,7 +160,12 @@ impl<'a> StateMachine<'a> {
                 ),
                 &mut self.painter.line_numbers_data,
                 &mut self.painter.highlighter,
+                None,
+                None,
+                MinusPlus::new(false, false),
+                MinusPlus::new(&[], &[]),
                 &mut self.painter.output_buffer,
+                self.painter.syntax,
                 self.config,
             );
             self.painter.emit()?;
@@ -184,7 +189,7 @@ fn write_diff_header(
     config: &config::Config,
 ) -> std::io::Result<()> {
     let (mut draw_fn, pad, decoration_ansi_term_style) =
-        draw::get_draw_function(style.decoration_style);
+        draw::get_draw_function(style.decoration_style, config.ascii_decorations);
     let derived_commit_name = &painter.merge_conflict_commit_names[derived_commit_type];
     let text = if let Some(_ancestral_commit) = &painter.merge_conflict_commit_names[Ancestral] {
         format!(
@@ -1,5 +1,6 @@
 /// This module contains functions handling input lines encountered during the
 /// main `StateMachine::consume()` loop.
+pub mod amended_commit;
 pub mod blame;
 pub mod commit_meta;
 pub mod diff_header;
@@ -8,10 +9,16 @@ pub mod diff_header_misc;
 pub mod diff_stat;
 pub mod draw;
 pub mod git_show_file;
+pub mod graphics;
 pub mod grep;
 pub mod hunk;
 pub mod hunk_header;
+pub mod hyperlinks_only;
+pub mod jj_diff;
 pub mod merge_conflict;
+pub mod patch_apply;
+pub mod range_diff;
+pub mod renames;
 mod ripgrep_json;
 pub mod submodule;
 
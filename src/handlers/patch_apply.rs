@@ -0,0 +1,182 @@
+use std::borrow::Cow;
+
+use lazy_static::lazy_static;
+use regex::Regex;
+
+use crate::config::Config;
+use crate::delta::{State, StateMachine};
+use crate::features::hyperlinks;
+use crate::style::Style;
+use crate::utils;
+
+// Progress and failure messages of `git am`, `git apply --reject` and `patch`. The file a message
+// refers to is captured as `path`, and the line at which a patch failed as `line`.
+lazy_static! {
+    static ref APPLYING_REGEX: Regex = Regex::new(r"^Applying: ").unwrap();
+    static ref FAILURE_REGEXES: Vec<Regex> = [
+        r"^error: patch failed: (?P<path>.+):(?P<line>\d+)$",
+        r"^error: (?P<path>.+): (?:patch does not apply|does not exist in index|already exists in (?:index|working directory))$",
+        r"^Patch failed at \d+ ",
+        r"^Applying patch (?P<path>.+) with \d+ rejects?\.\.\.$",
+        r"^Rejected hunk #\d+\.$",
+        r"^\d+ out of \d+ hunks? FAILED -- saving rejects to file (?P<path>.+\.rej)$",
+    ]
+    .iter()
+    .map(|regex| Regex::new(regex).unwrap())
+    .collect();
+}
+
+impl<'a> StateMachine<'a> {
+    #[inline]
+    fn test_patch_apply_line(&self) -> bool {
+        // None of these lines can be a line of a hunk, since they do not start with ' ', '+' or '-'.
+        self.line.starts_with(char::is_alphanumeric)
+    }
+
+    /// Style the messages of `git am` and `git apply` (for example when their output is piped to
+    /// delta), hyperlinking the file and line at which a patch failed to apply.
+    pub fn handle_patch_apply_line(&mut self) -> std::io::Result<bool> {
+        if !self.test_patch_apply_line() || self.config.color_only {
+            return Ok(false);
+        }
+        let formatted_line = if APPLYING_REGEX.is_match(&self.line) {
+            Some(
+                self.config
+                    .patch_applying_style
+                    .paint(&self.line)
+                    .to_string(),
+            )
+        } else {
+            FAILURE_REGEXES.iter().find_map(|regex| {
+                format_failure_line(
+                    regex,
+                    &self.line,
+                    self.config.patch_failure_style,
+                    self.config,
+                )
+            })
+        };
+        let formatted_line = match formatted_line {
+            Some(formatted_line) => formatted_line,
+            None => return Ok(false),
+        };
+        self.painter.paint_buffered_minus_and_plus_lines();
+        self.painter.emit()?;
+        self.state = State::Unknown;
+        writeln!(self.painter.writer, "{formatted_line}")?;
+        Ok(true)
+    }
+}
+
+fn format_failure_line(regex: &Regex, line: &str, style: Style, config: &Config) -> Option<String> {
+    let caps = regex.captures(line)?;
+    let path = match caps.name("path") {
+        Some(path) => path,
+        None => return Some(style.paint(line).to_string()),
+    };
+    // The reference to the file ends with the line number, if there is one.
+    let end = caps.name("line").map_or(path.end(), |line| line.end());
+    let line_number = caps
+        .name("line")
+        .and_then(|line| line.as_str().parse().ok());
+    let text = &line[path.start()..end];
+    let text = match utils::path::absolute_path(path.as_str(), config) {
+        Some(absolute_path) if config.hyperlinks => {
+            hyperlinks::format_osc8_file_hyperlink(absolute_path, line_number, text, config)
+        }
+        _ => Cow::from(text),
+    };
+    Some(format!(
+        "{}{}{}",
+        style.paint(&line[..path.start()]),
+        config.file_style.paint(text),
+        style.paint(&line[end..]),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::integration_test_utils::DeltaTest;
+
+    const GIT_AM_FAILURE: &str = "\
+Applying: Fix the answer
+error: patch failed: src/answer.rs:12
+error: src/answer.rs: patch does not apply
+Patch failed at 0001 Fix the answer
+hint: Use 'git am --show-current-patch=diff' to see the failed patch
+";
+
+    #[test]
+    fn test_git_am_failure() {
+        DeltaTest::with_args(&[
+            "--patch-applying-style",
+            "bold",
+            "--patch-failure-style",
+            "red",
+            "--file-style",
+            "blue",
+        ])
+        .explain_ansi()
+        .with_input(GIT_AM_FAILURE)
+        .expect(
+            r#"
+            (bold normal)Applying: Fix the answer(normal)
+            (red)error: patch failed: (blue)src/answer.rs:12(normal)
+            (red)error: (blue)src/answer.rs(red): patch does not apply(normal)
+            (red)Patch failed at 0001 Fix the answer(normal)
+            hint: Use 'git am --show-current-patch=diff' to see the failed patch"#,
+        );
+    }
+
+    #[test]
+    fn test_rejects() {
+        DeltaTest::with_args(&["--patch-failure-style", "red", "--file-style", "blue"])
+            .explain_ansi()
+            .with_input(
+                "\
+Checking patch src/answer.rs...
+Applying patch src/answer.rs with 1 reject...
+Rejected hunk #1.
+1 out of 2 hunks FAILED -- saving rejects to file answer.rs.rej
+",
+            )
+            .expect(
+                r#"
+            (normal)Checking patch src/answer.rs...
+            (red)Applying patch (blue)src/answer.rs(red) with 1 reject...(normal)
+            (red)Rejected hunk #1.(normal)
+            (red)1 out of 2 hunks FAILED -- saving rejects to file (blue)answer.rs.rej(normal)"#,
+            );
+    }
+
+    #[test]
+    fn test_patch_failure_hyperlink() {
+        DeltaTest::with_args(&[
+            "--hyperlinks",
+            "--hyperlinks-file-link-format",
+            "file-line://{path}:{line}",
+        ])
+        .with_input(GIT_AM_FAILURE)
+        .expect_raw_contains("answer.rs:12\x1b]8;;\x1b\\");
+    }
+
+    #[test]
+    fn test_diff_after_patch_failure() {
+        DeltaTest::with_args(&[])
+            .with_input(
+                "\
+Applying: Fix the answer
+diff --git a/src/answer.rs b/src/answer.rs
+index 1111111..2222222 100644
+--- a/src/answer.rs
++++ b/src/answer.rs
+@@ -1 +1 @@
+-41
++42
+Rejected hunk #1.
+",
+            )
+            .expect_contains("Applying: Fix the answer\n\nsrc/answer.rs")
+            .expect_contains("42\nRejected hunk #1.");
+    }
+}
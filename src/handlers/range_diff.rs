@@ -0,0 +1,397 @@
+use ansi_term::ANSIString;
+use lazy_static::lazy_static;
+use regex::Regex;
+use syntect::highlighting::Style as SyntectStyle;
+
+use crate::config::Config;
+use crate::delta::{DiffType, State, StateMachine};
+use crate::edits;
+use crate::features::hyperlinks;
+use crate::features::side_by_side::{self, LeftRight};
+use crate::minusplus::*;
+use crate::paint::{
+    get_syntax_style_sections_for_lines, BgShouldFill, LineSections, Painter, StyleSectionSpecifier,
+};
+use crate::style::Style;
+use crate::utils::tabs;
+
+lazy_static! {
+    // The header line of a pair of commits, e.g. " 2:  1b2c3d4 ! 2:  5e6f7a8 Subject", or
+    // "-:  ------- > 3:  9a8b7c6 Subject" for a commit only in the new range.
+    static ref RANGE_DIFF_HEADER_REGEX: Regex = Regex::new(
+        r"^ *(?:\d+|-): +(?P<minus>[0-9a-f]{4,}|-+) [=!<>] +(?:\d+|-): +(?P<plus>[0-9a-f]{4,}|-+) "
+    )
+    .unwrap();
+}
+
+// The indentation of the diff between the patches of a pair of commits.
+const INDENT: &str = "    ";
+
+impl<'a> StateMachine<'a> {
+    #[inline]
+    fn test_range_diff_header_line(&self) -> bool {
+        matches!(self.state, State::Unknown | State::RangeDiff)
+            && RANGE_DIFF_HEADER_REGEX.is_match(&self.line)
+    }
+
+    /// Handle a line of `git range-diff` output: the header line of a pair of commits, or a line
+    /// of the indented diff between their patches. The lines of that diff have two markers: that
+    /// of the diff between the patches, followed by that of the line in the patch. Lines which
+    /// are in both patches are painted as lines of a diff, and lines which are only in one of them
+    /// as removed or added lines, with the changes within lines highlighted. With --side-by-side,
+    /// the lines of the old patch are shown in the left panel and those of the new patch in the
+    /// right panel, without the indentation.
+    pub fn handle_range_diff_line(&mut self) -> std::io::Result<bool> {
+        if self.test_range_diff_header_line() {
+            self.paint_buffered_range_diff_lines();
+            self.painter.emit()?;
+            self.state = State::RangeDiff;
+            let header = format_header_line(&self.line, &self.raw_line, self.config);
+            writeln!(self.painter.writer, "{header}")?;
+            return Ok(true);
+        }
+        if self.state != State::RangeDiff {
+            return Ok(false);
+        }
+        let Some(line) = self.line.strip_prefix(INDENT) else {
+            // The line after the range-diff.
+            self.paint_buffered_range_diff_lines();
+            self.state = State::Unknown;
+            return Ok(false);
+        };
+        let line = tabs::expand(line, &self.config.tab_cfg);
+        let (marker, rest) = line.split_at(line.chars().next().map_or(0, char::len_utf8));
+        match marker {
+            "-" | "+" => {
+                let side = if marker == "-" { Minus } else { Plus };
+                // A removed line after added lines starts a new group of changed lines.
+                if side == Minus && !self.range_diff_lines[Plus].is_empty() {
+                    self.paint_buffered_range_diff_lines();
+                }
+                self.range_diff_lines[side].push(rest.to_string());
+            }
+            "@" => {
+                // E.g. "@@ src/main.rs: fn main()", "@@ Metadata"
+                self.paint_buffered_range_diff_lines();
+                let path = line.trim_start_matches('@').trim_start();
+                self.painter.set_syntax(path.split(':').next());
+                self.painter.set_highlighter();
+                self.painter.output_buffer.push_str(indent(self.config));
+                self.painter
+                    .output_buffer
+                    .push_str(&self.config.hunk_header_file_style.paint(&line).to_string());
+                self.painter.output_buffer.push('\n');
+            }
+            _ => {
+                self.paint_buffered_range_diff_lines();
+                // E.g. " ## src/main.rs ##", " ## src/lib.rs (new) ##", " ## Commit message ##"
+                if let Some(path) = rest
+                    .trim_start()
+                    .strip_prefix("## ")
+                    .and_then(|path| path.strip_suffix(" ##"))
+                {
+                    let path = path.split(" (").next().unwrap_or(path);
+                    self.painter.set_syntax(Some(path));
+                    self.painter.set_highlighter();
+                }
+                let (style, state) = match rest.chars().next() {
+                    Some('-') => (
+                        self.config.minus_style,
+                        State::HunkMinus(DiffType::Unified, None),
+                    ),
+                    Some('+') => (
+                        self.config.plus_style,
+                        State::HunkPlus(DiffType::Unified, None),
+                    ),
+                    _ => (
+                        self.config.zero_style,
+                        State::HunkZero(DiffType::Unified, None),
+                    ),
+                };
+                if self.config.side_by_side {
+                    // The line is in both patches.
+                    let lines = vec![(rest.to_string(), state)];
+                    let syntax_sections = get_syntax_style_sections_for_lines(
+                        &lines,
+                        self.painter.highlighter.as_mut(),
+                        self.config,
+                    );
+                    let diff_sections = vec![vec![(style, lines[0].0.as_str())]];
+                    paint_panels(
+                        LeftRight::new(&lines, &lines),
+                        LeftRight::new(syntax_sections.clone(), syntax_sections),
+                        LeftRight::new(diff_sections.clone(), diff_sections),
+                        LeftRight::new(ANSIString::from(marker), ANSIString::from(marker)),
+                        &[(Some(0), Some(0))],
+                        true,
+                        &mut self.painter.output_buffer,
+                        self.config,
+                    );
+                } else {
+                    self.painter.output_buffer.push_str(INDENT);
+                    self.painter.output_buffer.push_str(marker);
+                    self.painter.syntax_highlight_and_paint_line(
+                        rest,
+                        StyleSectionSpecifier::Style(style),
+                        state,
+                        BgShouldFill::default(),
+                    );
+                }
+            }
+        }
+        self.painter.emit()?;
+        Ok(true)
+    }
+
+    /// Paint the buffered lines which are only in one of the patches of a pair of commits of
+    /// `git range-diff` output, highlighting the changes between paired lines.
+    pub fn paint_buffered_range_diff_lines(&mut self) {
+        let lines = std::mem::take(&mut self.range_diff_lines);
+        if lines[Minus].is_empty() && lines[Plus].is_empty() {
+            return;
+        }
+        let config = self.config;
+        let (minus_sections, plus_sections, line_alignment) = edits::infer_edits(
+            lines[Minus].iter().map(String::as_str).collect(),
+            lines[Plus].iter().map(String::as_str).collect(),
+            vec![config.minus_style; lines[Minus].len()],
+            config.minus_emph_style,
+            vec![config.plus_style; lines[Plus].len()],
+            config.plus_emph_style,
+            &edits::Tokenizer::for_syntax(&self.painter.syntax.name, config),
+            config.max_line_distance,
+            config.max_line_distance_for_naively_paired_lines,
+            config.max_highlight_length,
+        );
+        let marker = |side| match side {
+            Minus => config.minus_emph_style.paint("-"),
+            Plus => config.plus_emph_style.paint("+"),
+        };
+        let state = |side| match side {
+            Minus => State::HunkMinus(DiffType::Unified, None),
+            Plus => State::HunkPlus(DiffType::Unified, None),
+        };
+        if config.side_by_side {
+            let lines = LeftRight::new(
+                lines[Minus]
+                    .iter()
+                    .map(|line| (line.to_string(), state(Minus)))
+                    .collect::<Vec<_>>(),
+                lines[Plus]
+                    .iter()
+                    .map(|line| (line.to_string(), state(Plus)))
+                    .collect::<Vec<_>>(),
+            );
+            let mut syntax_sections = LeftRight::<Vec<LineSections<SyntectStyle>>>::default();
+            for side in [Minus, Plus] {
+                syntax_sections[side] = get_syntax_style_sections_for_lines(
+                    &lines[side],
+                    self.painter.highlighter.as_mut(),
+                    config,
+                );
+            }
+            paint_panels(
+                LeftRight::new(&lines[Minus], &lines[Plus]),
+                syntax_sections,
+                LeftRight::new(minus_sections, plus_sections),
+                LeftRight::new(marker(Minus), marker(Plus)),
+                &line_alignment,
+                false,
+                &mut self.painter.output_buffer,
+                config,
+            );
+            return;
+        }
+        for (side, sections) in [(Minus, minus_sections), (Plus, plus_sections)] {
+            for (line, sections) in lines[side].iter().zip(sections) {
+                self.painter.output_buffer.push_str(INDENT);
+                self.painter
+                    .output_buffer
+                    .push_str(&marker(side).to_string());
+                self.painter.syntax_highlight_and_paint_line(
+                    line,
+                    StyleSectionSpecifier::StyleSections(sections),
+                    state(side),
+                    BgShouldFill::default(),
+                );
+            }
+        }
+    }
+}
+
+// The indentation of the lines of the diff between patches, which side-by-side panels omit.
+fn indent(config: &Config) -> &'static str {
+    if config.side_by_side {
+        ""
+    } else {
+        INDENT
+    }
+}
+
+// Paint lines of the patches of a pair of commits side by side: those of the old patch in the
+// left panel and those of the new patch in the right panel, in the rows of the alignment. The
+// rows of lines which are in both patches have no marker in the center gutter.
+#[allow(clippy::too_many_arguments)]
+fn paint_panels<'a>(
+    lines: LeftRight<&'a [(String, State)]>,
+    syntax_sections: LeftRight<Vec<LineSections<'a, SyntectStyle>>>,
+    diff_sections: LeftRight<Vec<LineSections<'a, Style>>>,
+    markers: LeftRight<ANSIString>,
+    line_alignment: &[(Option<usize>, Option<usize>)],
+    in_both_patches: bool,
+    output_buffer: &mut String,
+    config: &Config,
+) {
+    for &(minus_index, plus_index) in line_alignment {
+        let panel_line = |side, index: Option<usize>| {
+            index.map(|i| {
+                Painter::paint_line(
+                    &syntax_sections[side][i],
+                    &diff_sections[side][i],
+                    &lines[side][i].1,
+                    &mut None,
+                    Some(side),
+                    Some(markers[side].clone()),
+                    config,
+                )
+                .0
+            })
+        };
+        let marker = if in_both_patches {
+            ' '
+        } else {
+            side_by_side::center_gutter_marker(minus_index, plus_index)
+        };
+        side_by_side::paint_panel_row(
+            LeftRight::new(panel_line(Minus, minus_index), panel_line(Plus, plus_index)),
+            marker,
+            output_buffer,
+            config,
+        );
+    }
+}
+
+// The header line of a pair of commits, with hyperlinks to the commits if --hyperlinks is set.
+fn format_header_line(line: &str, raw_line: &str, config: &Config) -> String {
+    if config.range_diff_header_style.is_raw {
+        return raw_line.to_string();
+    }
+    let mut header = line.to_string();
+    if config.hyperlinks {
+        let caps = RANGE_DIFF_HEADER_REGEX.captures(line).unwrap();
+        // The commits are replaced from the end, so that the ranges of the first remain valid.
+        for commit in [caps.name("plus").unwrap(), caps.name("minus").unwrap()] {
+            if !commit.as_str().starts_with('-') {
+                header.replace_range(
+                    commit.range(),
+                    &hyperlinks::format_osc8_commit_hyperlink(
+                        commit.as_str(),
+                        commit.as_str(),
+                        config,
+                    ),
+                );
+            }
+        }
+    }
+    config.range_diff_header_style.paint(header).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ansi::strip_ansi_codes;
+    use crate::tests::integration_test_utils::DeltaTest;
+
+    #[test]
+    fn test_range_diff() {
+        let output = DeltaTest::with_args(&[])
+            .with_input(RANGE_DIFF)
+            .expect_contains(
+                "\
+1:  1111111 = 1:  2222222 Unchanged commit
+2:  3333333 ! 2:  4444444 Changed commit
+    @@ Metadata
+      ## Commit message ##
+    -    Changed commit
+    +    Changed commit, again
+    @@ src/a.rs: fn a()
+       fn a() {
+      -    b();
+    - +    c();
+    + +    d();
+    ++    e();
+       }
+3:  5555555 < -:  ------- Dropped commit
+-:  ------- > 3:  6666666 New commit
+Not a range-diff line",
+            );
+        let raw_lines: Vec<&str> = output.raw_output.lines().collect();
+        // The headers are styled, and the lines changed between the patches are highlighted.
+        assert_eq!(
+            raw_lines[1],
+            "\x1b[1m2:  3333333 ! 2:  4444444 Changed commit\x1b[0m"
+        );
+        let emph = |line: &str| line.matches("\x1b[48;").count();
+        assert!(emph(raw_lines[5]) > emph(raw_lines[4]));
+        assert_eq!(strip_ansi_codes(raw_lines[15]), "Not a range-diff line");
+    }
+
+    #[test]
+    fn test_range_diff_header_hyperlinks() {
+        let output = DeltaTest::with_args(&[
+            "--hyperlinks",
+            "--hyperlinks-commit-link-format",
+            "https://example.com/{commit}",
+        ])
+        .with_input(RANGE_DIFF);
+        assert!(output
+            .raw_output
+            .contains("\x1b]8;;https://example.com/3333333\x1b\\3333333\x1b]8;;\x1b\\ ! 2:  "));
+        assert!(output
+            .raw_output
+            .contains("\x1b]8;;https://example.com/6666666\x1b\\6666666\x1b]8;;\x1b\\ New commit"));
+    }
+
+    #[test]
+    fn test_range_diff_side_by_side() {
+        DeltaTest::with_args(&[
+            "--side-by-side",
+            "--side-by-side-center-gutter",
+            "--width",
+            "64",
+        ])
+        .with_input(RANGE_DIFF)
+        .expect_contains(
+            "\
+2:  3333333 ! 2:  4444444 Changed commit
+@@ Metadata
+  ## Commit message ##             ## Commit message ##
+-    Changed commit            | +    Changed commit, again
+@@ src/a.rs: fn a()
+   fn a() {                         fn a() {
+  -    b();                        -    b();
+- +    c();                    | + +    d();
+                               > ++    e();
+   }                                }
+3:  5555555 < -:  ------- Dropped commit",
+        );
+    }
+
+    const RANGE_DIFF: &str = "\
+1:  1111111 = 1:  2222222 Unchanged commit
+2:  3333333 ! 2:  4444444 Changed commit
+    @@ Metadata
+      ## Commit message ##
+    -    Changed commit
+    +    Changed commit, again
+    @@ src/a.rs: fn a()
+       fn a() {
+      -    b();
+    - +    c();
+    + +    d();
+    ++    e();
+       }
+3:  5555555 < -:  ------- Dropped commit
+-:  ------- > 3:  6666666 New commit
+Not a range-diff line
+";
+}
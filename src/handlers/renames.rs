@@ -0,0 +1,238 @@
+use crate::config::Config;
+use crate::delta::{State, StateMachine};
+use crate::handlers::diff_header::{
+    get_file_change_description_from_file_paths, write_generic_diff_header_header_line, FileEvent,
+};
+use crate::utils;
+
+impl<'a> StateMachine<'a> {
+    /// Follow whether the header of the current file shows it to be renamed without changes, i.e.
+    /// consists of the "diff", "similarity index 100%" and "rename" lines only. Unless the current
+    /// line is such a line, display the files renamed without changes before it, together.
+    pub fn end_pure_renames_before_line(&mut self) -> std::io::Result<()> {
+        if !self.config.group_renames {
+            return Ok(());
+        }
+        if self.line.starts_with("diff --git ") {
+            self.is_pure_rename = true;
+        } else if !(self.is_pure_rename
+            && matches!(self.state, State::DiffHeader(_))
+            && (self.line == "similarity index 100%"
+                || self.line.starts_with("rename from ")
+                || self.line.starts_with("rename to ")))
+        {
+            self.is_pure_rename = false;
+            self.flush_pure_renames()?;
+        }
+        Ok(())
+    }
+
+    /// Display the files renamed without changes since the last other file. Several are
+    /// summarized by the directories from and to which they were moved, e.g. "renamed:
+    /// src/foo/** ⟶ src/bar/** (48 files)", rather than given a header each.
+    pub fn flush_pure_renames(&mut self) -> std::io::Result<()> {
+        let renames = std::mem::take(&mut self.pure_renames);
+        match renames.as_slice() {
+            [] => Ok(()),
+            [(minus_file, plus_file)] => {
+                self.start_log_group(plus_file)?;
+                self.follow_color_scheme();
+                self.follow_terminal_size();
+                self.mark_file_anchor(plus_file)?;
+                let line = get_file_change_description_from_file_paths(
+                    minus_file,
+                    plus_file,
+                    false,
+                    &FileEvent::Rename,
+                    &FileEvent::Rename,
+                    self.config,
+                );
+                write_generic_diff_header_header_line(
+                    &line,
+                    &line,
+                    &mut self.painter,
+                    &mut self.mode_info,
+                    self.config,
+                )
+            }
+            renames => {
+                self.end_log_group()?;
+                let (line, rows) = format_renames_summary(renames, self.config);
+                write_generic_diff_header_header_line(
+                    &line,
+                    &line,
+                    &mut self.painter,
+                    &mut self.mode_info,
+                    self.config,
+                )?;
+                for row in rows {
+                    writeln!(self.painter.writer, "{}", self.config.file_style.paint(row))?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+// The header summarizing the renamed files, and the rows listing the moves of which they consist
+// if there are several: e.g. "src/foo/** ⟶ src/bar/** (3 files)", or "a.rs ⟶ b.rs" for a file
+// which is renamed within its directory.
+fn format_renames_summary(renames: &[(String, String)], config: &Config) -> (String, Vec<String>) {
+    let mut moves: Vec<((String, String), usize)> = Vec::new();
+    for (minus_file, plus_file) in renames {
+        let key = moved_directories(
+            &utils::path::display_path(minus_file, config),
+            &utils::path::display_path(plus_file, config),
+        );
+        match moves.iter_mut().find(|(move_, _)| *move_ == key) {
+            Some((_, n)) => *n += 1,
+            None => moves.push((key, 1)),
+        }
+    }
+    let label = match config.file_renamed_label.as_str() {
+        "" => String::new(),
+        label => format!("{label} "),
+    };
+    let format_move = |(minus, plus): &(String, String), n: usize| match n {
+        1 => format!("{minus} {} {plus}", config.right_arrow),
+        n => format!("{minus} {} {plus} ({n} files)", config.right_arrow),
+    };
+    match moves.as_slice() {
+        [(move_, n)] => (format!("{label}{}", format_move(move_, *n)), Vec::new()),
+        moves => (
+            format!("{label}{} files", renames.len()),
+            moves
+                .iter()
+                .map(|(move_, n)| format!("    {}", format_move(move_, *n)))
+                .collect(),
+        ),
+    }
+}
+
+// The directories from and to which the file was moved, e.g. ("src/foo/**", "src/bar/**") for
+// "src/foo/a/b.rs" and "src/bar/a/b.rs", or the paths of a file whose name changed.
+fn moved_directories(minus_file: &str, plus_file: &str) -> (String, String) {
+    let minus: Vec<&str> = minus_file.split('/').collect();
+    let plus: Vec<&str> = plus_file.split('/').collect();
+    let common_suffix = minus
+        .iter()
+        .rev()
+        .zip(plus.iter().rev())
+        .take_while(|(m, p)| m == p)
+        .count();
+    if common_suffix == 0 {
+        return (minus_file.to_string(), plus_file.to_string());
+    }
+    let directory = |components: &[&str]| match components.len() - common_suffix {
+        0 => "**".to_string(),
+        n => format!("{}/**", components[..n].join("/")),
+    };
+    (directory(&minus), directory(&plus))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::integration_test_utils::DeltaTest;
+
+    #[test]
+    fn test_moved_directories() {
+        let moved = |minus, plus| {
+            let (minus, plus) = moved_directories(minus, plus);
+            format!("{minus} {plus}")
+        };
+        assert_eq!(
+            moved("src/foo/a/b.rs", "src/bar/a/b.rs"),
+            "src/foo/** src/bar/**"
+        );
+        assert_eq!(moved("a.rs", "src/a.rs"), "** src/**");
+        assert_eq!(moved("src/a.rs", "src/b.rs"), "src/a.rs src/b.rs");
+    }
+
+    #[test]
+    fn test_renames_grouped() {
+        DeltaTest::with_args(&[])
+            .with_input(&format!("{RENAMES}{CHANGE}"))
+            .expect_contains(
+                "
+renamed: src/foo/** ⟶   src/bar/** (3 files)
+─────",
+            )
+            .expect_contains("src/c.rs\n")
+            .expect_contains("fn c() {}");
+    }
+
+    #[test]
+    fn test_renames_grouped_by_directories() {
+        let input = format!(
+            "{RENAMES}\
+diff --git a/README.md b/README.rst
+similarity index 100%
+rename from README.md
+rename to README.rst
+"
+        );
+        let output = DeltaTest::with_args(&[]).with_input(&input);
+        output.expect_contains(
+            "
+renamed: 4 files
+───────────────────────────────────────────
+    src/foo/** ⟶   src/bar/** (3 files)
+    README.md ⟶   README.rst
+",
+        );
+    }
+
+    #[test]
+    fn test_renames_expanded() {
+        let output = DeltaTest::with_args(&["--expand-renames"]).with_input(RENAMES);
+        assert_eq!(output.output.matches("renamed:").count(), 3);
+        output.expect_contains("renamed: src/foo/a.rs ⟶   src/bar/a.rs");
+    }
+
+    #[test]
+    fn test_rename_with_changes_not_grouped() {
+        let input = RENAMES.to_string()
+            + "\
+diff --git a/src/foo/d.rs b/src/bar/d.rs
+similarity index 90%
+rename from src/foo/d.rs
+rename to src/bar/d.rs
+index 1111111..2222222 100644
+--- a/src/foo/d.rs
++++ b/src/bar/d.rs
+@@ -1 +1 @@
+-fn d() {}
++fn d() -> u8 {}
+";
+        DeltaTest::with_args(&[])
+            .with_input(&input)
+            .expect_contains("renamed: src/foo/** ⟶   src/bar/** (3 files)")
+            .expect_contains("renamed: src/foo/d.rs ⟶   src/bar/d.rs");
+    }
+
+    const RENAMES: &str = "\
+diff --git a/src/foo/a.rs b/src/bar/a.rs
+similarity index 100%
+rename from src/foo/a.rs
+rename to src/bar/a.rs
+diff --git a/src/foo/b.rs b/src/bar/b.rs
+similarity index 100%
+rename from src/foo/b.rs
+rename to src/bar/b.rs
+diff --git a/src/foo/x/c.rs b/src/bar/x/c.rs
+similarity index 100%
+rename from src/foo/x/c.rs
+rename to src/bar/x/c.rs
+";
+
+    const CHANGE: &str = "\
+diff --git a/src/c.rs b/src/c.rs
+index 1111111..2222222 100644
+--- a/src/c.rs
++++ b/src/c.rs
+@@ -1 +1,2 @@
+ fn c() {}
++fn d() {}
+";
+}
@@ -0,0 +1,408 @@
+mod align;
+mod ansi;
+mod cli;
+mod color;
+mod colors;
+mod config;
+mod delta;
+mod edits;
+mod env;
+mod features;
+mod format;
+mod git_config;
+mod handlers;
+mod minusplus;
+mod options;
+mod paint;
+mod parse_style;
+mod parse_styles;
+mod processor;
+mod style;
+mod utils;
+mod wrapping;
+
+mod subcommands;
+
+#[cfg(test)]
+mod tests;
+
+use std::cell::{Cell, OnceCell};
+use std::ffi::OsString;
+use std::io::{self, Cursor, ErrorKind, IsTerminal, Write};
+use std::panic;
+use std::path::Path;
+use std::process;
+use std::sync::atomic::Ordering;
+
+use crate::cli::Call;
+use crate::color::{Ansi16ColorWriter, ColorDepth};
+use crate::delta::StateMachine;
+use crate::features::color_scheme::{self, ColorSchemeFollower};
+use crate::features::log_graph::{LogGraph, LogGraphWriter};
+use crate::features::parallel_highlighting::HighlightPool;
+use crate::features::terminal_resize::{self, TerminalResizeFollower};
+use crate::git_config::GitConfig;
+use crate::utils::bat::assets::list_languages;
+use crate::utils::bat::output::{OutputType, PagingMode};
+use crate::utils::lines;
+
+// The interface for using delta as a library.
+pub use crate::config::Config;
+pub use crate::processor::{config_from_args, process, DeltaProcessor};
+
+thread_local! {
+    // Whether `fatal` unwinds to `catch_fatal` rather than ending the process.
+    static CATCH_FATAL: Cell<bool> = const { Cell::new(false) };
+}
+
+// The error with which `fatal` unwinds to `catch_fatal`.
+struct FatalError(String);
+
+pub(crate) fn fatal<T>(errmsg: T) -> !
+where
+    T: AsRef<str> + std::fmt::Display,
+{
+    // Without unwinding, e.g. with panic = "abort", the process ends as in the delta program.
+    if cfg!(panic = "unwind") && CATCH_FATAL.with(Cell::get) {
+        // Unlike panic!, resume_unwind does not call the panic hook, which would print the error.
+        panic::resume_unwind(Box::new(FatalError(errmsg.to_string())));
+    }
+    #[cfg(not(test))]
+    {
+        eprintln!("{errmsg}");
+        // As in Config::error_exit_code: use 2 for error
+        // because diff uses 0 and 1 for non-error.
+        process::exit(2);
+    }
+    #[cfg(test)]
+    panic!("{}\n", errmsg);
+}
+
+pub mod errors {
+    pub use anyhow::{anyhow, Context, Error, Result};
+}
+
+/// Call `f`, returning the error of a call to `fatal` during it instead of ending the process, as
+/// needed when delta is used as a library.
+pub(crate) fn catch_fatal<R>(f: impl FnOnce() -> R) -> errors::Result<R> {
+    let catch_fatal = CATCH_FATAL.with(|catch_fatal| catch_fatal.replace(true));
+    let result = panic::catch_unwind(panic::AssertUnwindSafe(f));
+    CATCH_FATAL.with(|cell| cell.set(catch_fatal));
+    result.map_err(|payload| match payload.downcast::<FatalError>() {
+        Ok(error) => errors::anyhow!(error.0),
+        Err(payload) => panic::resume_unwind(payload),
+    })
+}
+
+/// Run the delta program with the arguments of the process, and exit with its exit code.
+#[doc(hidden)]
+#[cfg(not(tarpaulin_include))]
+pub fn main() -> std::io::Result<()> {
+    // Do this first because both parsing all the input in `run_app()` and
+    // listing all processes takes about 50ms on Linux.
+    // It also improves the chance that the calling process is still around when
+    // input is piped into delta (e.g. `git show  --word-diff=color | delta`).
+    utils::process::start_determining_calling_process_in_thread();
+
+    utils::windows::enable_virtual_terminal_processing();
+
+    // Ignore ctrl-c (SIGINT) to avoid leaving an orphaned pager process, except for ending --watch.
+    // See https://github.com/dandavison/delta/issues/681
+    ctrlc::set_handler(|| subcommands::watch::INTERRUPTED.store(true, Ordering::Relaxed))
+        .unwrap_or_else(|err| {
+            utils::warnings::warn(
+                utils::warnings::WarningKind::System,
+                format!("Failed to set ctrl-c handler: {err}"),
+            )
+        });
+    let exit_code = run_app(std::env::args_os().collect::<Vec<_>>(), None)?;
+    // when you call process::exit, no destructors are called, so we want to do it only once, here
+    process::exit(exit_code);
+}
+
+#[doc(hidden)]
+#[cfg(not(tarpaulin_include))]
+// An Ok result contains the desired process exit code. Note that 1 is used to
+// report that two files differ when delta is called with two positional
+// arguments and without standard input; 2 is used to report a real problem.
+pub fn run_app(
+    args: Vec<OsString>,
+    capture_output: Option<&mut Cursor<Vec<u8>>>,
+) -> std::io::Result<i32> {
+    let env = env::DeltaEnv::init();
+    let assets = utils::bat::assets::load_highlighting_assets();
+    let pick_args = args.clone();
+    let opt = cli::Opt::from_args_and_git_config(args, &env, assets);
+
+    let opt = match opt {
+        Call::Version(msg) => {
+            writeln!(std::io::stdout(), "{}", msg.trim_end())?;
+            return Ok(0);
+        }
+        Call::Help(msg) => {
+            OutputType::oneshot_write(msg)?;
+            return Ok(0);
+        }
+        Call::Delta(opt) => opt,
+    };
+    // A warning treated as an error (--strict-warnings) while reading the options.
+    if let Err(error) = utils::warnings::check_error() {
+        fatal(format!("{error}"));
+    }
+
+    if opt.no_process_detection {
+        utils::process::disable_calling_process_detection();
+    }
+    if let Some(calling_process) = opt.input_type.calling_process() {
+        utils::process::set_calling_process(calling_process);
+    }
+
+    #[cfg(feature = "self-update")]
+    if opt.self_update {
+        if let Err(error) = subcommands::self_update::self_update() {
+            fatal(format!("{error:#}"));
+        }
+        return Ok(0);
+    }
+
+    if opt.setup_git {
+        let stdout = io::stdout();
+        let mut stdout = stdout.lock();
+        if let Err(error) = subcommands::setup_git::setup_git(opt.dry_run, opt.force, &mut stdout) {
+            fatal(format!("{error:#}"));
+        }
+        return Ok(0);
+    }
+
+    if opt.pick_theme {
+        let stdout = io::stdout();
+        let mut stdout = stdout.lock();
+        if let Err(error) = subcommands::pick_theme::pick_theme(&pick_args, &env, &mut stdout) {
+            fatal(format!("{error:#}"));
+        }
+        return Ok(0);
+    }
+
+    let subcommand_result = if let Some(shell) = opt.generate_completion {
+        Some(subcommands::generate_completion::generate_completion_file(
+            shell,
+        ))
+    } else if opt.list_languages {
+        Some(list_languages())
+    } else if opt.list_syntax_themes {
+        Some(subcommands::list_syntax_themes::list_syntax_themes())
+    } else if opt.show_syntax_themes {
+        Some(subcommands::show_syntax_themes::show_syntax_themes())
+    } else if opt.show_themes {
+        Some(subcommands::show_themes::show_themes(
+            opt.dark,
+            opt.light,
+            opt.computed.color_mode,
+        ))
+    } else if opt.show_colors {
+        Some(subcommands::show_colors::show_colors())
+    } else if opt.parse_ansi {
+        Some(subcommands::parse_ansi::parse_ansi())
+    } else {
+        None
+    };
+    if let Some(result) = subcommand_result {
+        if let Err(error) = result {
+            match error.kind() {
+                ErrorKind::BrokenPipe => {}
+                _ => fatal(format!("{error}")),
+            }
+        }
+        return Ok(0);
+    };
+
+    let _show_config = opt.show_config;
+    let _check_contrast = opt.check_contrast;
+    let _mark_reviewed = opt.mark_reviewed.clone();
+    let _list_hunks = opt.list_hunks.clone();
+    let _stats = opt.stats;
+    let _output_format = opt.output_format;
+    let _split_output = opt.split_output.clone();
+    let _pick = opt.pick.clone();
+    let _fixed_width = !matches!(opt.width.as_deref(), None | Some("variable"));
+    let config = config::Config::from(opt);
+    if let Err(error) = utils::warnings::check_error() {
+        fatal(format!("{error}"));
+    }
+
+    if _show_config {
+        let stdout = io::stdout();
+        let mut stdout = stdout.lock();
+        subcommands::show_config::show_config(&config, &mut stdout)?;
+        return Ok(0);
+    }
+
+    if _check_contrast {
+        let stdout = io::stdout();
+        let mut stdout = stdout.lock();
+        let readable = subcommands::check_contrast::check_contrast(&config, &mut stdout)?;
+        return Ok(if readable { 0 } else { 1 });
+    }
+
+    if let Some(file) = _mark_reviewed {
+        let Some(repo) = config.git_config().and_then(GitConfig::repo) else {
+            fatal("--mark-reviewed must be run in a git repository.");
+        };
+        let stdout = io::stdout();
+        let mut stdout = stdout.lock();
+        if let Err(error) = subcommands::mark_reviewed::mark_reviewed(&file, &repo, &mut stdout) {
+            fatal(format!("{error:#}"));
+        }
+        return Ok(0);
+    }
+
+    if let Some(format) = _list_hunks {
+        let stdout = io::stdout();
+        let mut stdout = stdout.lock();
+        if let Err(error) = subcommands::list_hunks::list_hunks(&format, &config, &mut stdout) {
+            match error.kind() {
+                ErrorKind::BrokenPipe => {}
+                _ => fatal(format!("{error}")),
+            }
+        }
+        return Ok(0);
+    }
+
+    if _stats {
+        let stdout = io::stdout();
+        let mut stdout = stdout.lock();
+        if let Err(error) = subcommands::stats::stats(&config, &mut stdout) {
+            match error.kind() {
+                ErrorKind::BrokenPipe => {}
+                _ => fatal(format!("{error}")),
+            }
+        }
+        return Ok(0);
+    }
+
+    if let Some(dir) = _split_output {
+        let stdout = io::stdout();
+        let mut stdout = stdout.lock();
+        if let Err(error) = subcommands::split_output::split_output(
+            Path::new(&dir),
+            _output_format,
+            &config,
+            &mut stdout,
+        ) {
+            match error.kind() {
+                ErrorKind::BrokenPipe => {}
+                _ => fatal(format!("{error}")),
+            }
+        }
+        return Ok(0);
+    }
+
+    if _output_format != cli::OutputFormat::Terminal {
+        let stdout = io::stdout();
+        let mut stdout = stdout.lock();
+        return match subcommands::render_html::render_html(&config, _output_format, &mut stdout) {
+            Ok(exit_code) => Ok(exit_code),
+            Err(error) => match error.kind() {
+                ErrorKind::BrokenPipe => Ok(0),
+                _ => fatal(format!("{error}")),
+            },
+        };
+    }
+
+    // The picker needs the terminal, so it has to exit before the pager starts.
+    let picked = match _pick {
+        Some(mode) if !io::stdin().is_terminal() => {
+            match subcommands::pick::pick(&mode, &pick_args, &env) {
+                Ok(Some(picked)) => Some(picked),
+                Ok(None) => return Ok(0),
+                Err(error) => fatal(format!("{error:#}")),
+            }
+        }
+        _ => None,
+    };
+
+    // The following block structure is because of `writer` and related lifetimes:
+    let pager_cfg = (&config).into();
+    let paging_mode = if capture_output.is_some() {
+        PagingMode::Capture
+    } else if config.watch {
+        // The diff is redisplayed on the screen.
+        PagingMode::Never
+    } else {
+        config.paging_mode
+    };
+    let mut output_type =
+        OutputType::from_mode(&env, paging_mode, config.pager.clone(), &pager_cfg).unwrap();
+    let writer: &mut dyn Write = if paging_mode == PagingMode::Capture {
+        &mut capture_output.unwrap()
+    } else {
+        output_type.handle().unwrap()
+    };
+    let mut ansi_16_color_writer;
+    let mut writer: &mut dyn Write = if config.color_depth == ColorDepth::Ansi16 && !config.no_style
+    {
+        ansi_16_color_writer = Ansi16ColorWriter::new(writer);
+        &mut ansi_16_color_writer
+    } else {
+        writer
+    };
+
+    if let (Some(minus_file), Some(plus_file)) = (&config.minus_file, &config.plus_file) {
+        let exit_code = if config.watch {
+            subcommands::watch::watch(minus_file, plus_file, &config, &mut writer)
+        } else {
+            subcommands::diff::diff(minus_file, plus_file, &config, &mut writer)
+        };
+        return Ok(exit_code);
+    }
+
+    if io::stdin().is_terminal() {
+        eprintln!(
+            "\
+    The main way to use delta is to configure it as the pager for git: \
+    see https://github.com/dandavison/delta#get-started. \
+    You can also use delta to diff two files: `delta file_A file_B`."
+        );
+        return Ok(config.error_exit_code);
+    }
+
+    // The terminal can only be queried for its color scheme while no pager reads from it.
+    let other_config = (config.follow_color_scheme
+        && paging_mode == PagingMode::Never
+        && io::stdout().is_terminal())
+    .then(|| color_scheme::make_other_config(&pick_args, &config, &env));
+    // Without a pager, the layout follows the width of the terminal when it is resized, unless
+    // --width fixes it.
+    let follow_terminal_size = terminal_resize::watch_terminal_size(paging_mode, _fixed_width);
+    let resized_configs: [OnceCell<Config>; terminal_resize::MAX_RESIZED_CONFIGS] =
+        Default::default();
+    // The threads highlighting hunks (--threads) end when the state machine is dropped.
+    let result = std::thread::scope(|scope| {
+        let log_graph = LogGraph::default();
+        let mut writer = LogGraphWriter::new(writer, log_graph.clone(), &config);
+        let mut state_machine = StateMachine::new(&mut writer, &config);
+        state_machine.log_graph = Some(log_graph);
+        state_machine.color_scheme_follower = other_config
+            .as_ref()
+            .map(|other_config| ColorSchemeFollower::new(&config, other_config));
+        state_machine.terminal_resize_follower =
+            follow_terminal_size.then(|| TerminalResizeFollower::new(&resized_configs));
+        state_machine.highlight_pool = HighlightPool::start(scope, &config);
+        match (picked, config.no_style) {
+            (Some(picked), true) => state_machine.consume_without_style(picked.as_bytes()),
+            (None, true) => state_machine.consume_without_style(io::stdin().lock()),
+            (Some(picked), false) => state_machine.consume(lines::byte_lines(picked.as_bytes())),
+            (None, false) => state_machine.consume(lines::byte_lines(io::stdin().lock())),
+        }
+    });
+    if let Err(error) = result {
+        match error.kind() {
+            ErrorKind::BrokenPipe => return Ok(0),
+            _ => {
+                eprintln!("{error}");
+                return Ok(2);
+            }
+        }
+    };
+    Ok(0)
+}
@@ -8,8 +8,10 @@ use ProvenancedOptionValue::*;
 
 // Look up a value of type `T` associated with `option name`. The search rules are:
 //
-// 1. If there is a value associated with `option_name` in the main [delta] git config
-//    section, then stop searching and return that value (steps 2 and 3 are not executed at all).
+// 1. If there is a value associated with `option_name` in the [delta "lang:`language`"] git
+//    config section of the language whose options are being set, if any, or else in the main
+//    [delta] git config section, then stop searching and return that value (steps 2 and 3 are not
+//    executed at all).
 //
 // 2. For each feature in the ordered list of enabled features:
 //
@@ -57,6 +59,26 @@ pub fn get_themes(git_config: Option<git_config::GitConfig>) -> Vec<String> {
     themes
 }
 
+static GIT_CONFIG_LANGUAGE_REGEX: &str = r"^delta\.lang:[^.]+\.";
+
+/// The languages with a [delta "lang:<language>"] section in git config, e.g. "python" or "py".
+pub fn get_languages(git_config: Option<&git_config::GitConfig>) -> Vec<String> {
+    let mut languages: Vec<String> = Vec::new();
+    let Some(git_config) = git_config.filter(|git_config| git_config.enabled) else {
+        return languages;
+    };
+    git_config.for_each(GIT_CONFIG_LANGUAGE_REGEX, |name, _| {
+        if let Some(name) = name.strip_prefix("delta.lang:") {
+            if let Some((language, _)) = name.rsplit_once('.') {
+                if !languages.iter().any(|l| l == language) {
+                    languages.push(language.to_owned());
+                }
+            }
+        }
+    });
+    languages
+}
+
 pub trait GetOptionValue {
     fn get_option_value(
         option_name: &str,
@@ -71,6 +93,14 @@ pub trait GetOptionValue {
         Self: Into<OptionValue>,
     {
         if let Some(git_config) = git_config {
+            // The options of the language of a file override all others set in git config.
+            if let Some(language) = &opt.computed.language {
+                if let Some(value) =
+                    git_config.get::<Self>(&format!("delta.lang:{language}.{option_name}"))
+                {
+                    return Some(value);
+                }
+            }
             if let Some(value) = git_config.get::<Self>(&format!("delta.{option_name}")) {
                 return Some(value);
             }
@@ -7,6 +7,7 @@ use bat::assets::HighlightingAssets;
 use console::Term;
 
 use crate::cli;
+use crate::color::ColorDepth;
 use crate::config;
 use crate::env::DeltaEnv;
 use crate::errors::*;
@@ -15,7 +16,9 @@ use crate::features;
 use crate::git_config::GitConfig;
 use crate::options::option_value::{OptionValue, ProvenancedOptionValue};
 use crate::options::theme;
+use crate::utils;
 use crate::utils::bat::output::PagingMode;
+use crate::utils::warnings::{self, WarningKind, WarningSettings, WarningsFormat};
 
 macro_rules! set_options {
     ([$( $field_ident:ident ),* ],
@@ -41,9 +44,16 @@ macro_rules! set_options {
         if $check_names {
             option_names.extend(&[
                 "24-bit-color",
+                "ci", // Does not exist as a flag on config
+                "deuteranopia", // Does not exist as a flag on config
                 "diff-highlight", // Does not exist as a flag on config
                 "diff-so-fancy", // Does not exist as a flag on config
+                "diff-so-fancy-strict", // Does not exist as a flag on config
+                "github", // Does not exist as a flag on config
+                "high-contrast", // Does not exist as a flag on config
+                "tritanopia", // Does not exist as a flag on config
                 "detect-dark-light", // Does not exist as a flag on config
+                "detect-dark-light-timeout", // Does not exist as a flag on config
                 "features",  // Processed differently
                 // Set prior to the rest
                 "no-gitconfig",
@@ -78,9 +88,15 @@ pub fn set_options(
         }
     }
     opt.navigate = opt.navigate || opt.env.navigate.is_some();
+    if opt.html {
+        opt.output_format = cli::OutputFormat::Html;
+    }
     if opt.syntax_theme.is_none() {
         opt.syntax_theme.clone_from(&opt.env.bat_theme);
     }
+    if opt.line_map.is_none() {
+        opt.line_map.clone_from(&opt.env.line_map);
+    }
 
     let option_names = cli::Opt::get_argument_and_option_names();
 
@@ -127,21 +143,57 @@ pub fn set_options(
 
     set_options!(
         [
+            added_keyword_styles,
+            amended_commit_messages,
+            ascii_decorations,
+            blame_boundary_style,
             blame_code_style,
             blame_format,
+            blame_heatmap,
+            blame_heatmap_newest,
+            blame_heatmap_oldest,
+            blame_ignored_style,
             blame_separator_format,
             blame_palette,
             blame_separator_style,
+            blame_side_by_side,
             blame_timestamp_format,
             blame_timestamp_output_format,
+            canonicalize_json,
+            color_depth,
+            color_moved,
             color_only,
+            comment_check_command,
+            comment_check_style,
             config,
+            commit_block_format,
+            commit_columns,
+            commit_columns_min_width,
             commit_decoration_style,
+            commit_message_code_style,
+            commit_message_markdown,
             commit_regex,
             commit_style,
+            context,
+            context_dim,
+            context_fade,
+            context_fold_style,
+            conventional_commits,
+            conventional_commit_breaking_style,
+            conventional_commit_scope_style,
+            conventional_commit_type_style,
+            coverage_covered_style,
+            coverage_file,
+            coverage_uncovered_style,
             default_language,
+            detect_secrets,
+            diagnostics_file,
+            diagnostics_style,
+            diff_algorithm,
             diff_args,
             diff_stat_align_width,
+            exclude,
+            expand_renames,
             file_added_label,
             file_copied_label,
             file_decoration_style,
@@ -149,9 +201,14 @@ pub fn set_options(
             file_removed_label,
             file_renamed_label,
             file_regex_replacement,
+            filter,
+            follow_color_scheme,
             right_arrow,
             hunk_label,
             file_style,
+            function_separators,
+            function_separator_style,
+            github_actions_groups,
             grep_context_line_style,
             grep_file_style,
             grep_header_decoration_style,
@@ -161,6 +218,11 @@ pub fn set_options(
             grep_match_line_style,
             grep_match_word_style,
             grep_separator_symbol,
+            hunk_apply_commands,
+            hunk_apply_command_style,
+            hunk_change_density,
+            hunk_change_density_minus_style,
+            hunk_change_density_plus_style,
             hunk_header_decoration_style,
             hunk_header_file_style,
             hunk_header_line_number_style,
@@ -168,12 +230,26 @@ pub fn set_options(
             hyperlinks,
             hyperlinks_commit_link_format,
             hyperlinks_file_link_format,
+            hyperlinks_issue_link_format,
+            hyperlinks_issue_rules,
+            hyperlinks_minus_file_link_format,
+            hyperlinks_only,
+            ignore_whitespace,
+            gutter_command,
+            gutter_style,
+            inline_blame,
+            inline_blame_style,
             inline_hint_style,
             inspect_raw_lines,
             keep_plus_minus_markers,
             line_buffer_size,
             map_styles,
+            max_buffer_lines,
+            max_code_line_warn,
+            max_code_line_warn_style,
+            max_context_lines,
             max_line_distance,
+            max_highlight_length,
             max_line_length,
             max_syntax_length,
             // Hack: minus-style must come before minus-*emph-style because the latter default
@@ -184,14 +260,30 @@ pub fn set_options(
             merge_conflict_ours_diff_header_style,
             merge_conflict_theirs_diff_header_decoration_style,
             merge_conflict_theirs_diff_header_style,
+            merge_parent_style,
+            merge_parent_subjects,
             minus_style,
+            missing_newline_marker,
+            missing_newline_marker_style,
+            moved_minus_alt_style,
+            moved_minus_style,
+            moved_plus_alt_style,
+            moved_plus_style,
             minus_emph_style,
             minus_empty_line_marker_style,
             minus_non_emph_style,
             minus_non_emph_style,
             navigate,
+            navigate_keymap,
+            navigate_prefill_search,
             navigate_regex,
+            no_bat_cache,
+            no_process_detection,
+            no_style,
+            no_style_verify,
+            offline,
             line_fill_method,
+            line_map,
             line_numbers,
             line_numbers_left_format,
             line_numbers_left_style,
@@ -200,28 +292,64 @@ pub fn set_options(
             line_numbers_right_format,
             line_numbers_right_style,
             line_numbers_zero_style,
+            locale,
+            log_graph_style,
             pager,
             paging_mode,
             parse_ansi,
+            patch_applying_style,
+            patch_failure_style,
+            path_rewrite,
+            path_rewrite_hyperlinks,
+            path_strip,
             // Hack: plus-style must come before plus-*emph-style because the latter default
             // dynamically to the value of the former.
             plus_style,
             plus_emph_style,
             plus_empty_line_marker_style,
             plus_non_emph_style,
+            preprocess,
+            pull_request_titles,
+            pull_request_title_style,
+            quiet,
+            range_diff_header_style,
             raw,
+            redact_secrets,
+            redact_secrets_regex,
+            redacted_style,
             relative_paths,
+            secret_warning_style,
             show_colors,
             show_themes,
             side_by_side,
+            side_by_side_center_gutter,
+            side_by_side_center_gutter_style,
+            side_by_side_center_line_numbers,
+            side_by_side_order,
+            side_by_side_split,
+            split_hunks,
+            split_hunks_style,
+            strict_warnings,
+            strip_cr,
+            strip_path_prefix,
+            summary_create_style,
+            summary_delete_style,
+            summary_similarity_style,
+            syntax_highlight_changes_only,
+            wrap_continuation_indent,
             wrap_max_lines,
+            wrap_mode,
             wrap_right_prefix_symbol,
             wrap_right_percent,
             wrap_right_symbol,
             wrap_left_symbol,
             tab_width,
+            threads,
             tokenization_regex,
+            tree_sitter_tokenization,
             true_color,
+            warnings_format,
+            watch,
             whitespace_error_style,
             width,
             zero_style
@@ -237,10 +365,22 @@ pub fn set_options(
     // Setting ComputedValues
     set_widths_and_isatty(opt);
     set_true_color(opt);
-    theme::set__color_mode__syntax_theme__syntax_set(opt, assets);
+    let assets = if opt.no_bat_cache {
+        HighlightingAssets::from_binary()
+    } else {
+        assets
+    };
+    theme::set__syntax_theme__syntax_set(opt, assets);
     opt.computed.inspect_raw_lines =
         cli::InspectRawLines::from_str(&opt.inspect_raw_lines).unwrap();
     opt.computed.paging_mode = parse_paging_mode(&opt.paging_mode);
+    // Do not page output which is already being paged by a wrapping tool.
+    if opt.computed.paging_mode == PagingMode::QuitIfOneScreen
+        && !config::user_supplied_option("paging_mode", arg_matches)
+        && is_inside_pager(&opt.env, opt.computed.stdout_is_term)
+    {
+        opt.computed.paging_mode = PagingMode::Never;
+    }
 
     // --color-only is used for interactive.diffFilter (git add -p). side-by-side, and
     // **-decoration-style cannot be used there (does not emit lines in 1-1 correspondence with raw git output).
@@ -251,6 +391,32 @@ pub fn set_options(
         opt.commit_decoration_style = "none".to_string();
         opt.hunk_header_decoration_style = "none".to_string();
     }
+
+    warnings::configure(WarningSettings {
+        quiet: opt.quiet,
+        strict: opt.strict_warnings,
+        format: parse_warnings_format(&opt.warnings_format),
+    });
+
+    // Hyperlinks enabled by a feature are disabled where they are printed as garbage, but those
+    // requested explicitly are kept.
+    if opt.hyperlinks && !utils::windows::supports_hyperlinks(&opt.env) {
+        if config::user_supplied_option("hyperlinks", arg_matches)
+            || git_config
+                .as_ref()
+                .and_then(|git_config| git_config.get::<bool>("delta.hyperlinks"))
+                .is_some()
+        {
+            warnings::warn(
+                WarningKind::System,
+                "Hyperlinks are likely not supported by this console: \
+                 they are only known to work in Windows Terminal."
+                    .to_string(),
+            );
+        } else {
+            opt.hyperlinks = false;
+        }
+    }
 }
 
 #[allow(non_snake_case)]
@@ -288,6 +454,7 @@ fn set__light__dark__syntax_theme__options(
         option_names,
         false
     );
+    theme::set__color_mode(opt);
 }
 
 // Features are processed differently from all other options. The role of this function is to
@@ -376,18 +543,42 @@ fn gather_features(
     if opt.raw {
         gather_builtin_features_recursively("raw", &mut features, builtin_features, opt);
     }
+    if opt.ci {
+        gather_builtin_features_recursively("ci", &mut features, builtin_features, opt);
+    }
     if opt.color_only {
         gather_builtin_features_recursively("color-only", &mut features, builtin_features, opt);
     }
+    if opt.deuteranopia {
+        gather_builtin_features_recursively("deuteranopia", &mut features, builtin_features, opt);
+    }
     if opt.diff_highlight {
         gather_builtin_features_recursively("diff-highlight", &mut features, builtin_features, opt);
     }
     if opt.diff_so_fancy {
         gather_builtin_features_recursively("diff-so-fancy", &mut features, builtin_features, opt);
     }
+    if opt.diff_so_fancy_strict {
+        gather_builtin_features_recursively(
+            "diff-so-fancy-strict",
+            &mut features,
+            builtin_features,
+            opt,
+        );
+    }
+    if opt.github {
+        gather_builtin_features_recursively("github", &mut features, builtin_features, opt);
+    }
+    if opt.high_contrast {
+        gather_builtin_features_recursively("high-contrast", &mut features, builtin_features, opt);
+    }
     if opt.hyperlinks {
         gather_builtin_features_recursively("hyperlinks", &mut features, builtin_features, opt);
     }
+    // Before line-numbers, whose line number styles it overrides.
+    if opt.tritanopia {
+        gather_builtin_features_recursively("tritanopia", &mut features, builtin_features, opt);
+    }
     if opt.line_numbers {
         gather_builtin_features_recursively("line-numbers", &mut features, builtin_features, opt);
     }
@@ -437,6 +628,15 @@ fn gather_features_recursively(
     if builtin_features.contains_key(feature) {
         gather_builtin_features_recursively(feature, features, builtin_features, opt);
     } else {
+        if !git_config.has_key_with_prefix(&format!("delta.{feature}.")) {
+            warnings::warn(
+                WarningKind::UnknownOption,
+                format!(
+                    "Unknown feature '{feature}': it is neither a builtin feature nor a \
+                     [delta \"{feature}\"] section of git config."
+                ),
+            );
+        }
         features.push_front(feature.to_string());
     }
     if let Some(child_features) = git_config.get::<String>(&format!("delta.{feature}.features")) {
@@ -558,6 +758,18 @@ fn parse_paging_mode(paging_mode_string: &str) -> PagingMode {
     }
 }
 
+fn parse_warnings_format(warnings_format_string: &str) -> WarningsFormat {
+    match warnings_format_string.to_lowercase().as_str() {
+        "text" => WarningsFormat::Text,
+        "json" => WarningsFormat::Json,
+        _ => {
+            fatal(format!(
+                "Invalid value for --warnings-format option: {warnings_format_string} (valid values are \"text\" and \"json\")",
+            ));
+        }
+    }
+}
+
 fn parse_width_specifier(width_arg: &str, terminal_width: usize) -> Result<usize, String> {
     let width_arg = width_arg.trim();
 
@@ -602,6 +814,43 @@ fn parse_width_specifier(width_arg: &str, terminal_width: usize) -> Result<usize
     Ok(width)
 }
 
+/// Whether delta is running inside another pager, i.e. its output goes to a tool such as tig or
+/// magit which set GIT_PAGER_IN_USE (as git does for its own pager) and then display the output
+/// themselves.
+fn is_inside_pager(env: &DeltaEnv, stdout_is_term: bool) -> bool {
+    let git_pager_in_use = match env.git_pager_in_use.as_deref() {
+        Some(value) => !matches!(
+            value.to_lowercase().as_str(),
+            "" | "0" | "false" | "no" | "off"
+        ),
+        None => false,
+    };
+    git_pager_in_use && !stdout_is_term
+}
+
+// The width announced by a wrapping tool which pages the output of delta, if delta is run inside
+// one. Other output which is not written to a terminal, e.g. piped to a file, keeps the width of
+// the terminal.
+fn pager_width(env: &DeltaEnv, stdout_is_term: bool) -> Option<usize> {
+    if is_inside_pager(env, stdout_is_term) {
+        columns_from_env(env)
+    } else {
+        None
+    }
+}
+
+fn columns_from_env(env: &DeltaEnv) -> Option<usize> {
+    let columns = env.columns.as_deref()?;
+    let parsed = columns.trim().parse().ok().filter(|columns| *columns > 0);
+    if parsed.is_none() && !columns.is_empty() {
+        warnings::warn(
+            WarningKind::InvalidValue,
+            format!("Ignoring COLUMNS={columns:?}, which is not a positive number."),
+        );
+    }
+    parsed
+}
+
 fn set_widths_and_isatty(opt: &mut cli::Opt) {
     let term_stdout = Term::stdout();
     opt.computed.stdout_is_term = term_stdout.is_term();
@@ -610,6 +859,9 @@ fn set_widths_and_isatty(opt: &mut cli::Opt) {
     // as an argument, also see #41, #10, #115 and #727.
     opt.computed.available_terminal_width =
         crate::utils::workarounds::windows_msys2_width_fix(term_stdout.size(), &term_stdout);
+    if let Some(columns) = pager_width(&opt.env, opt.computed.stdout_is_term) {
+        opt.computed.available_terminal_width = columns;
+    }
 
     let (decorations_width, background_color_extends_to_terminal_width) = match opt.width.as_deref()
     {
@@ -651,7 +903,10 @@ fn set_true_color(opt: &mut cli::Opt) {
     opt.computed.true_color = match opt.true_color.as_ref() {
         "always" => true,
         "never" => false,
-        "auto" => is_truecolor_terminal(&opt.env),
+        // Browsers and mail clients display all colors.
+        "auto" => {
+            opt.output_format != cli::OutputFormat::Terminal || is_truecolor_terminal(&opt.env)
+        }
         _ => {
             fatal(format!(
                 "Invalid value for --true-color option: {} (valid values are \"always\", \"never\", and \"auto\")",
@@ -659,6 +914,22 @@ fn set_true_color(opt: &mut cli::Opt) {
             ));
         }
     };
+
+    opt.computed.color_depth = match opt.color_depth.as_ref() {
+        "24bit" => ColorDepth::TrueColor,
+        "256" => ColorDepth::Ansi256,
+        "16" => ColorDepth::Ansi16,
+        "auto" if opt.computed.true_color => ColorDepth::TrueColor,
+        "auto" if is_16_color_terminal(&opt.env) => ColorDepth::Ansi16,
+        "auto" => ColorDepth::Ansi256,
+        _ => {
+            fatal(format!(
+                "Invalid value for --color-depth option: {} (valid values are \"auto\", \"24bit\", \"256\", and \"16\")",
+                opt.color_depth
+            ));
+        }
+    };
+    opt.computed.true_color = opt.computed.color_depth == ColorDepth::TrueColor;
 }
 
 fn is_truecolor_terminal(env: &DeltaEnv) -> bool {
@@ -666,6 +937,15 @@ fn is_truecolor_terminal(env: &DeltaEnv) -> bool {
         .as_ref()
         .map(|colorterm| colorterm == "truecolor" || colorterm == "24bit")
         .unwrap_or(false)
+        || utils::windows::supports_true_color(env)
+}
+
+// Whether the terminal named by TERM only supports the 16 colors of its palette, as the Linux
+// console does.
+fn is_16_color_terminal(env: &DeltaEnv) -> bool {
+    env.term.as_deref().is_some_and(|term| {
+        matches!(term, "linux" | "ansi" | "cons25") || term.ends_with("-16color")
+    })
 }
 
 #[cfg(test)]
@@ -863,4 +1143,83 @@ pub mod tests {
         assert_eq!(parse_width_specifier(" - 12 ", term_width).unwrap(), 0);
         assert_eq!(parse_width_specifier(" 2 - 2 ", term_width).unwrap(), 0);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_is_inside_pager() {
+        use super::is_inside_pager;
+        use crate::env::DeltaEnv;
+
+        let env = |value: Option<&str>| DeltaEnv {
+            git_pager_in_use: value.map(str::to_string),
+            ..DeltaEnv::default()
+        };
+        assert!(is_inside_pager(&env(Some("true")), false));
+        assert!(is_inside_pager(&env(Some("1")), false));
+        // git sets GIT_PAGER_IN_USE when it starts delta as its own pager.
+        assert!(!is_inside_pager(&env(Some("true")), true));
+        assert!(!is_inside_pager(&env(Some("false")), false));
+        assert!(!is_inside_pager(&env(None), false));
+    }
+
+    #[test]
+    fn test_columns_from_env() {
+        use super::columns_from_env;
+        use crate::env::DeltaEnv;
+
+        let env = |value: &str| DeltaEnv {
+            columns: Some(value.to_string()),
+            ..DeltaEnv::default()
+        };
+        assert_eq!(columns_from_env(&env("120")), Some(120));
+        assert_eq!(columns_from_env(&env(" 80 ")), Some(80));
+        assert_eq!(columns_from_env(&env("0")), None);
+        assert_eq!(columns_from_env(&env("wide")), None);
+        assert_eq!(columns_from_env(&DeltaEnv::default()), None);
+    }
+
+    #[test]
+    fn test_pager_width() {
+        use super::pager_width;
+        use crate::env::DeltaEnv;
+
+        let env = |git_pager_in_use: Option<&str>| DeltaEnv {
+            columns: Some("120".to_string()),
+            git_pager_in_use: git_pager_in_use.map(str::to_string),
+            ..DeltaEnv::default()
+        };
+        assert_eq!(pager_width(&env(Some("true")), false), Some(120));
+        // Output piped to another program or a file keeps the width of the terminal.
+        assert_eq!(pager_width(&env(None), false), None);
+        assert_eq!(pager_width(&env(Some("true")), true), None);
+    }
+
+    #[test]
+    fn test_color_depth() {
+        use super::is_16_color_terminal;
+        use crate::color::ColorDepth;
+        use crate::env::DeltaEnv;
+
+        let env = |value: &str| DeltaEnv {
+            term: Some(value.to_string()),
+            ..DeltaEnv::default()
+        };
+        assert!(is_16_color_terminal(&env("linux")));
+        assert!(is_16_color_terminal(&env("xterm-16color")));
+        assert!(!is_16_color_terminal(&env("xterm-256color")));
+        assert!(!is_16_color_terminal(&DeltaEnv::default()));
+
+        for (args, color_depth) in [
+            (&["--color-depth=16"][..], ColorDepth::Ansi16),
+            (
+                &["--color-depth=256", "--true-color=always"],
+                ColorDepth::Ansi256,
+            ),
+            (&["--true-color=always"], ColorDepth::TrueColor),
+            (&["--true-color=never"], ColorDepth::Ansi256),
+        ] {
+            let config = integration_test_utils::make_config_from_args(args);
+            assert_eq!(config.color_depth, color_depth, "{args:?}");
+            assert_eq!(config.true_color, color_depth == ColorDepth::TrueColor);
+        }
+    }
+}
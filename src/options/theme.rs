@@ -14,6 +14,7 @@
 //! In the absence of other factors, the default assumes a dark terminal background.
 
 use std::io::{stdout, IsTerminal};
+use std::time::Duration;
 
 use bat;
 use bat::assets::HighlightingAssets;
@@ -22,19 +23,44 @@ use terminal_colorsaurus::{color_scheme, QueryOptions};
 
 use crate::cli::{self, DetectDarkLight};
 use crate::color::{ColorMode, ColorMode::*};
+use crate::utils;
+use crate::utils::warnings::{self, WarningKind};
 
+/// Set the color mode. This must be done before builtin features are evaluated, since their values
+/// may depend on it.
 #[allow(non_snake_case)]
-pub fn set__color_mode__syntax_theme__syntax_set(opt: &mut cli::Opt, assets: HighlightingAssets) {
-    let (color_mode, syntax_theme_name) =
+pub fn set__color_mode(opt: &mut cli::Opt) {
+    let (color_mode, _) =
         get_color_mode_and_syntax_theme_name(opt.syntax_theme.as_ref(), get_color_mode(opt));
     opt.computed.color_mode = color_mode;
+}
+
+#[allow(non_snake_case)]
+pub fn set__syntax_theme__syntax_set(opt: &mut cli::Opt, assets: HighlightingAssets) {
+    let (_, syntax_theme_name) = get_color_mode_and_syntax_theme_name(
+        opt.syntax_theme.as_ref(),
+        Some(opt.computed.color_mode),
+    );
 
     opt.computed.syntax_theme = if is_no_syntax_highlighting_syntax_theme_name(&syntax_theme_name) {
         None
     } else {
-        Some(assets.get_theme(&syntax_theme_name).clone())
+        let is_known = assets.themes().any(|theme| theme == syntax_theme_name)
+            // Deprecated names which bat replaces.
+            || matches!(syntax_theme_name.as_str(), "ansi-light" | "ansi-dark");
+        let name = if is_known {
+            syntax_theme_name.as_str()
+        } else {
+            let default = HighlightingAssets::default_theme();
+            warnings::warn(
+                WarningKind::Assets,
+                format!("Unknown syntax theme '{syntax_theme_name}': using {default}."),
+            );
+            default
+        };
+        Some(assets.get_theme(name).clone())
     };
-    opt.computed.syntax_set = assets.get_syntax_set().unwrap().clone();
+    opt.computed.syntax_set = utils::bat::assets::get_syntax_set(&assets);
 }
 
 pub fn is_light_syntax_theme(theme: &str) -> bool {
@@ -86,7 +112,7 @@ fn get_color_mode(opt: &cli::Opt) -> Option<ColorMode> {
     } else if opt.dark {
         Some(Dark)
     } else if should_detect_color_mode(opt) {
-        detect_color_mode()
+        detect_color_mode(Duration::from_millis(opt.detect_dark_light_timeout))
     } else {
         None
     }
@@ -101,11 +127,12 @@ fn should_detect_color_mode(opt: &cli::Opt) -> bool {
     }
 }
 
+/// Query the terminal for its colors, waiting at most `timeout` for its answer.
 #[cfg(not(test))]
-fn detect_color_mode() -> Option<ColorMode> {
-    color_scheme(QueryOptions::default())
-        .ok()
-        .map(ColorMode::from)
+pub fn detect_color_mode(timeout: Duration) -> Option<ColorMode> {
+    let mut options = QueryOptions::default();
+    options.timeout = timeout;
+    color_scheme(options).ok().map(ColorMode::from)
 }
 
 impl From<terminal_colorsaurus::ColorScheme> for ColorMode {
@@ -118,7 +145,7 @@ impl From<terminal_colorsaurus::ColorScheme> for ColorMode {
 }
 
 #[cfg(test)]
-fn detect_color_mode() -> Option<ColorMode> {
+pub fn detect_color_mode(_timeout: Duration) -> Option<ColorMode> {
     None
 }
 
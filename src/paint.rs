@@ -1,17 +1,27 @@
 use std::borrow::Cow;
 use std::collections::HashMap;
 use std::io::Write;
+use std::ops::Range;
 
 use ansi_term::ANSIString;
 use itertools::Itertools;
-use syntect::easy::HighlightLines;
+use regex::Regex;
 use syntect::highlighting::Style as SyntectStyle;
 use syntect::parsing::{SyntaxReference, SyntaxSet};
+use unicode_width::UnicodeWidthChar;
 
 use crate::config::{self, delta_unreachable, Config};
 use crate::delta::{DiffType, InMergeConflict, MergeParents, State};
+use crate::features::color_moved::{self, MovedLines};
+use crate::features::comment_check::{self, CommentChecker};
+use crate::features::diagnostics::DiagnosticsOverlay;
+use crate::features::gutter::{self, Gutter};
+use crate::features::html_anchors::HtmlAnchors;
 use crate::features::hyperlinks;
+use crate::features::inline_blame::InlineBlame;
 use crate::features::line_numbers::{self, LineNumbersData};
+use crate::features::parallel_highlighting::Highlighter;
+use crate::features::secrets;
 use crate::features::side_by_side::ansifill;
 use crate::features::side_by_side::{self, PanelSide};
 use crate::handlers::merge_conflict;
@@ -23,12 +33,22 @@ use crate::{edits, utils, utils::tabs};
 
 pub type LineSections<'a, S> = Vec<(S, &'a str)>;
 
+const CARRIAGE_RETURN_MARKER: &str = "␍";
+
 pub struct Painter<'p> {
     pub minus_lines: Vec<(String, State)>,
     pub plus_lines: Vec<(String, State)>,
+    // Whether a carriage return was removed from the end of each buffered minus and plus line.
+    pub lines_had_cr: MinusPlus<Vec<bool>>,
+    // Unchanged lines, buffered in order to fold long runs of them (--max-context-lines).
+    pub zero_lines: Vec<(String, State)>,
+    // Whether the buffered unchanged lines follow a change in the current hunk.
+    pub zero_lines_follow_change: bool,
+    // Whether the last buffered minus and plus line was followed by "\ No newline at end of file".
+    pub lines_missing_final_newline: MinusPlus<bool>,
     pub writer: &'p mut dyn Write,
     pub syntax: &'p SyntaxReference,
-    pub highlighter: Option<HighlightLines<'p>>,
+    pub highlighter: Option<Highlighter<'p>>,
     pub config: &'p config::Config,
     pub output_buffer: String,
     // If config.line_numbers is true, then the following is always Some().
@@ -37,6 +57,16 @@ pub struct Painter<'p> {
     pub line_numbers_data: Option<line_numbers::LineNumbersData<'p>>,
     pub merge_conflict_lines: merge_conflict::MergeConflictLines,
     pub merge_conflict_commit_names: merge_conflict::MergeConflictCommitNames,
+    // The authors of the lines of the current hunk (--inline-blame).
+    pub inline_blame: Option<InlineBlame>,
+    // The anchors of the files and lines of the HTML page (--html).
+    pub html_anchors: Option<HtmlAnchors>,
+    pub gutters: Vec<Gutter>,
+    pub diagnostics: Option<DiagnosticsOverlay>,
+    // The removed and added lines of the whole input (--color-moved).
+    pub moved_lines: Option<MovedLines>,
+    // The command checking added comment lines (--comment-check-command).
+    pub comment_checker: Option<CommentChecker>,
 }
 
 // How the background of a line is filled up to the end
@@ -71,9 +101,42 @@ pub enum StyleSectionSpecifier<'l> {
 impl<'p> Painter<'p> {
     pub fn new(writer: &'p mut dyn Write, config: &'p config::Config) -> Self {
         let default_syntax = Self::get_syntax(&config.syntax_set, None, &config.default_language);
-        let panel_width_fix = ansifill::UseFullPanelWidth::new(config);
+        let line_numbers_data = Self::make_line_numbers_data(config);
+        Self {
+            minus_lines: Vec::new(),
+            plus_lines: Vec::new(),
+            lines_had_cr: MinusPlus::new(Vec::new(), Vec::new()),
+            zero_lines: Vec::new(),
+            zero_lines_follow_change: false,
+            lines_missing_final_newline: MinusPlus::new(false, false),
+            output_buffer: String::new(),
+            syntax: default_syntax,
+            highlighter: None,
+            writer,
+            config,
+            line_numbers_data,
+            merge_conflict_lines: merge_conflict::MergeConflictLines::new(),
+            merge_conflict_commit_names: merge_conflict::MergeConflictCommitNames::new(),
+            inline_blame: None,
+            html_anchors: config.html_anchors.then(HtmlAnchors::default),
+            gutters: gutter::make_gutters(config),
+            diagnostics: config
+                .diagnostics_file
+                .as_deref()
+                .map(DiagnosticsOverlay::new),
+            moved_lines: None,
+            comment_checker: config
+                .comment_check_command
+                .as_deref()
+                .map(CommentChecker::new),
+        }
+    }
 
-        let line_numbers_data = if config.line_numbers {
+    fn make_line_numbers_data(
+        config: &'p config::Config,
+    ) -> Option<line_numbers::LineNumbersData<'p>> {
+        let panel_width_fix = ansifill::UseFullPanelWidth::new(config);
+        if config.line_numbers {
             Some(line_numbers::LineNumbersData::from_format_strings(
                 &config.line_numbers_format,
                 panel_width_fix,
@@ -87,21 +150,16 @@ impl<'p> Painter<'p> {
             ))
         } else {
             None
-        };
-        Self {
-            minus_lines: Vec::new(),
-            plus_lines: Vec::new(),
-            output_buffer: String::new(),
-            syntax: default_syntax,
-            highlighter: None,
-            writer,
-            config,
-            line_numbers_data,
-            merge_conflict_lines: merge_conflict::MergeConflictLines::new(),
-            merge_conflict_commit_names: merge_conflict::MergeConflictCommitNames::new(),
         }
     }
 
+    /// Paint the following files with `config`, which differs from the current one in the width
+    /// of the terminal.
+    pub fn set_config(&mut self, config: &'p config::Config) {
+        self.config = config;
+        self.line_numbers_data = Self::make_line_numbers_data(config);
+    }
+
     pub fn set_syntax(&mut self, filename: Option<&str>) {
         self.syntax = Painter::get_syntax(
             &self.config.syntax_set,
@@ -110,7 +168,7 @@ impl<'p> Painter<'p> {
         );
     }
 
-    fn get_syntax<'a>(
+    pub fn get_syntax<'a>(
         syntax_set: &'a SyntaxSet,
         filename: Option<&str>,
         fallback: &str,
@@ -150,29 +208,230 @@ impl<'p> Painter<'p> {
 
     pub fn set_highlighter(&mut self) {
         if let Some(ref syntax_theme) = self.config.syntax_theme {
-            self.highlighter = Some(HighlightLines::new(self.syntax, syntax_theme))
+            self.highlighter = Some(Highlighter::new(self.syntax, syntax_theme))
         };
     }
 
     pub fn paint_buffered_minus_and_plus_lines(&mut self) {
+        if !self.zero_lines.is_empty() {
+            self.paint_buffered_zero_lines(false);
+        }
         if self.minus_lines.is_empty() && self.plus_lines.is_empty() {
             return;
         }
+        self.zero_lines_follow_change = true;
+        let lines_missing_final_newline = std::mem::replace(
+            &mut self.lines_missing_final_newline,
+            MinusPlus::new(false, false),
+        );
+        if (lines_missing_final_newline[Minus] || lines_missing_final_newline[Plus])
+            && self.paint_final_newline_change(&lines_missing_final_newline)
+        {
+            self.minus_lines.clear();
+            self.plus_lines.clear();
+            self.lines_had_cr[Minus].clear();
+            self.lines_had_cr[Plus].clear();
+            return;
+        }
+        if self.config.detect_secrets {
+            let kinds = secrets::find_secrets(&self.plus_lines);
+            if !kinds.is_empty() {
+                self.output_buffer
+                    .push_str(&secrets::format_warning(&kinds, self.config));
+                self.output_buffer.push('\n');
+            }
+        }
+        let plus_lines = &self.plus_lines;
+        let comment_check_spans = self
+            .comment_checker
+            .as_mut()
+            .map(|checker| checker.check(plus_lines));
+        let painted_from = self.output_buffer.len();
         paint_minus_and_plus_lines(
             MinusPlus::new(&self.minus_lines, &self.plus_lines),
             &mut self.line_numbers_data,
             &mut self.highlighter,
+            self.moved_lines.as_ref(),
+            comment_check_spans.as_deref(),
+            lines_missing_final_newline,
+            MinusPlus::new(&self.lines_had_cr[Minus], &self.lines_had_cr[Plus]),
             &mut self.output_buffer,
+            self.syntax,
             self.config,
         );
+        if let Some(inline_blame) = &mut self.inline_blame {
+            inline_blame.annotate(
+                &mut self.output_buffer,
+                painted_from,
+                self.minus_lines.len(),
+                self.config,
+            );
+        }
+        if let Some(html_anchors) = &mut self.html_anchors {
+            html_anchors.annotate(
+                &mut self.output_buffer,
+                painted_from,
+                self.minus_lines.len(),
+                self.plus_lines.len(),
+                self.config,
+            );
+        }
+        for gutter in &mut self.gutters {
+            gutter.annotate(
+                &mut self.output_buffer,
+                painted_from,
+                self.minus_lines.len(),
+                true,
+                self.config,
+            );
+        }
+        if let Some(diagnostics) = &mut self.diagnostics {
+            diagnostics.annotate(
+                &mut self.output_buffer,
+                painted_from,
+                self.minus_lines.len(),
+                &self.plus_lines,
+                self.config,
+            );
+        }
         self.minus_lines.clear();
         self.plus_lines.clear();
+        self.lines_had_cr[Minus].clear();
+        self.lines_had_cr[Plus].clear();
+    }
+
+    /// If the only change is a newline added or removed at the end of the file, paint the line
+    /// once, followed by a notice, and return true.
+    fn paint_final_newline_change(
+        &mut self,
+        lines_missing_final_newline: &MinusPlus<bool>,
+    ) -> bool {
+        let marker = match &self.config.missing_newline_marker {
+            Some(marker) => marker,
+            None => return false,
+        };
+        if let ([(minus_line, _)], [(plus_line, State::HunkPlus(diff_type, _))]) =
+            (&self.minus_lines[..], &self.plus_lines[..])
+        {
+            if minus_line == plus_line && self.lines_had_cr[Minus] == self.lines_had_cr[Plus] {
+                let (style, change) = if lines_missing_final_newline[Minus] {
+                    (self.config.plus_style, "added")
+                } else {
+                    (self.config.minus_style, "removed")
+                };
+                let notice = format!("{marker} newline {change} at end of file");
+                let (line, state) = (plus_line.clone(), State::HunkZero(diff_type.clone(), None));
+                self.paint_zero_line(&line, state, None);
+                self.output_buffer.pop();
+                self.output_buffer.push(' ');
+                self.output_buffer
+                    .push_str(&style.paint(format!(" {notice} ")).to_string());
+                self.output_buffer.push('\n');
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Whether as many lines are buffered as --max-buffer-lines allows, so that they must be
+    /// painted before another line is buffered.
+    pub fn is_buffer_full(&self) -> bool {
+        let n_lines = self.minus_lines.len() + self.plus_lines.len() + self.zero_lines.len();
+        self.config.max_buffer_lines > 0 && n_lines >= self.config.max_buffer_lines
+    }
+
+    /// Paint the buffered unchanged lines, keeping at most --max-context-lines of them next to the
+    /// preceding and following changes of the hunk, and replacing the lines in between by a marker.
+    pub fn paint_buffered_zero_lines(&mut self, followed_by_change: bool) {
+        let lines = std::mem::take(&mut self.zero_lines);
+        let n = self.config.max_context_lines;
+        let (n_head, n_tail) = match (self.zero_lines_follow_change, followed_by_change) {
+            _ if n == 0 => (lines.len(), 0),
+            (true, true) => (n, n),
+            (true, false) => (n, 0),
+            (false, true) => (0, n),
+            (false, false) => (lines.len(), 0),
+        };
+        // Folding a single line would not make the hunk any shorter.
+        let (n_head, n_tail) = if n_head + n_tail + 1 >= lines.len() {
+            (lines.len(), 0)
+        } else {
+            (n_head, n_tail)
+        };
+        // The number of lines between the i-th line and the nearest changed line of the hunk
+        // (--context-fade), or usize::MAX if the hunk has none.
+        let (after_change, before_change) = (self.zero_lines_follow_change, followed_by_change);
+        let n_lines = lines.len();
+        let distance_from_change = |i: usize| {
+            let after = after_change.then_some(i + 1);
+            let before = before_change.then_some(n_lines - i);
+            Some(after.into_iter().chain(before).min().unwrap_or(usize::MAX))
+        };
+        let (head, rest) = lines.split_at(n_head);
+        let (folded, tail) = rest.split_at(rest.len() - n_tail);
+        for (i, (line, state)) in head.iter().enumerate() {
+            self.paint_zero_line(line, state.clone(), distance_from_change(i));
+        }
+        if !folded.is_empty() {
+            self.skip_zero_lines(folded);
+            self.output_buffer.push_str(
+                &self
+                    .config
+                    .context_fold_style
+                    .paint(format!("⋯ {} unchanged lines ⋯", folded.len()))
+                    .to_string(),
+            );
+            self.output_buffer.push('\n');
+        }
+        for (i, (line, state)) in tail.iter().enumerate() {
+            let i = n_lines - n_tail + i;
+            self.paint_zero_line(line, state.clone(), distance_from_change(i));
+        }
+    }
+
+    // Account for unchanged lines which are not displayed.
+    fn skip_zero_lines(&mut self, lines: &[(String, State)]) {
+        // Keep the state of the syntax highlighter up to date, e.g. in case the lines open a
+        // multi-line comment.
+        get_syntax_style_sections_for_lines(lines, self.highlighter.as_mut(), self.config);
+        if let Some(line_numbers_data) = &mut self.line_numbers_data {
+            line_numbers_data.line_number[side_by_side::Left] += lines.len();
+            line_numbers_data.line_number[side_by_side::Right] += lines.len();
+        }
+        if let Some(inline_blame) = &mut self.inline_blame {
+            inline_blame.skip_lines(lines.len());
+        }
+        if let Some(html_anchors) = &mut self.html_anchors {
+            html_anchors.skip_lines(lines.len());
+        }
+        for gutter in &mut self.gutters {
+            gutter.skip_lines(lines.len());
+        }
+        if let Some(diagnostics) = &mut self.diagnostics {
+            diagnostics.skip_lines(lines.len());
+        }
     }
 
-    pub fn paint_zero_line(&mut self, line: &str, state: State) {
+    /// Paint an unchanged line, given its distance from the nearest changed line of the hunk if
+    /// it is known.
+    pub fn paint_zero_line(
+        &mut self,
+        line: &str,
+        state: State,
+        distance_from_change: Option<usize>,
+    ) {
         let lines = &[(line.to_string(), state.clone())];
-        let syntax_style_sections =
+        let mut syntax_style_sections =
             get_syntax_style_sections_for_lines(lines, self.highlighter.as_mut(), self.config);
+        let context_dim = match (self.config.context_fade, distance_from_change) {
+            (fade, Some(distance)) if fade > 0 => {
+                self.config.context_dim * distance.min(fade) as f64 / fade as f64
+            }
+            _ => self.config.context_dim,
+        };
+        if context_dim > 0.0 {
+            dim_syntax_style_sections(&mut syntax_style_sections, context_dim, self.config);
+        }
         let mut diff_style_sections = vec![vec![(self.config.zero_style, lines[0].0.as_str())]]; // TODO: compute style from state
         Painter::update_diff_style_sections(
             lines,
@@ -182,6 +441,10 @@ impl<'p> Painter<'p> {
             &[false],
             self.config,
         );
+        let painted_from = self.output_buffer.len();
+        if self.config.redact_secrets {
+            secrets::apply_redacted_style(&mut diff_style_sections[0], self.config);
+        }
         if self.config.side_by_side {
             // `lines[0].0` so the line has the '\n' already added (as in the +- case)
             side_by_side::paint_zero_lines_side_by_side(
@@ -206,6 +469,18 @@ impl<'p> Painter<'p> {
                 None,
                 BgShouldFill::With(BgFillMethod::Spaces),
             );
+            if let Some(inline_blame) = &mut self.inline_blame {
+                inline_blame.annotate(&mut self.output_buffer, painted_from, 1, self.config);
+            }
+            for gutter in &mut self.gutters {
+                gutter.annotate(&mut self.output_buffer, painted_from, 0, false, self.config);
+            }
+            if let Some(diagnostics) = &mut self.diagnostics {
+                diagnostics.skip_lines(1);
+            }
+        }
+        if let Some(html_anchors) = &mut self.html_anchors {
+            html_anchors.annotate_zero_line(&mut self.output_buffer, painted_from);
         }
     }
 
@@ -320,6 +595,16 @@ impl<'p> Painter<'p> {
         config: &config::Config,
     ) -> (Option<BgFillMethod>, Style) {
         let fill_style = match state {
+            State::HunkMinus(_, None) | State::HunkMinusWrapped
+                if color_moved::is_moved_line(diff_sections, Minus, config) =>
+            {
+                diff_sections[0].0
+            }
+            State::HunkPlus(_, None) | State::HunkPlusWrapped
+                if color_moved::is_moved_line(diff_sections, Plus, config) =>
+            {
+                diff_sections[0].0
+            }
             State::HunkMinus(_, None) | State::HunkMinusWrapped => {
                 if let Some(true) = line_has_homolog {
                     config.minus_non_emph_style
@@ -407,6 +692,7 @@ impl<'p> Painter<'p> {
         mut painted_prefix: Option<ansi_term::ANSIString>,
         config: &config::Config,
     ) -> (String, bool) {
+        let mut line = String::new();
         let mut ansi_strings = Vec::new();
 
         let output_line_numbers = line_numbers_data.is_some();
@@ -447,14 +733,33 @@ impl<'p> Painter<'p> {
             }
 
             if !text.is_empty() {
-                ansi_strings.push(section_style.paint(text.as_str()));
+                let text = if config.hyperlinks && matches!(state, State::HunkZero(_, _)) {
+                    hyperlinks::format_issue_references_with_osc8_hyperlinks(text, config)
+                } else {
+                    Cow::from(text.as_str())
+                };
+                match section_style.sgr_params {
+                    // ansi_term cannot emit these parameters of a style of the input, so they
+                    // follow the style's own, and are reset after the section.
+                    Some(sgr_params) => {
+                        line.push_str(&ansi_term::ANSIStrings(&ansi_strings).to_string());
+                        ansi_strings.clear();
+                        line.push_str(&format!(
+                            "{}\x1b[{}m{text}\x1b[0m",
+                            section_style.ansi_term_style.prefix(),
+                            sgr_params.as_str()
+                        ));
+                    }
+                    None => ansi_strings.push(section_style.paint(text)),
+                }
             }
             handled_prefix = true;
         }
 
         // Only if syntax is empty (implies diff empty) can a line actually be empty.
         let is_empty = syntax_sections.is_empty();
-        (ansi_term::ANSIStrings(&ansi_strings).to_string(), is_empty)
+        line.push_str(&ansi_term::ANSIStrings(&ansi_strings).to_string());
+        (line, is_empty)
     }
 
     /// Write output buffer to output stream, and clear the buffer.
@@ -500,7 +805,8 @@ impl<'p> Painter<'p> {
             | State::HunkPlusWrapped
             | State::MergeConflict(_, _)
             | State::SubmoduleLog
-            | State::SubmoduleShort(_) => {
+            | State::SubmoduleShort(_)
+            | State::RangeDiff => {
                 panic!(
                     "should_compute_syntax_highlighting is undefined for state {:?}",
                     state
@@ -578,6 +884,7 @@ impl<'p> Painter<'p> {
     }
 }
 
+// Lines are terminated by '\n', see `prepare`.
 /// Remove initial -/+ character, expand tabs as spaces, and terminate with newline.
 // Terminating with newline character is necessary for many of the sublime syntax definitions to
 // highlight correctly.
@@ -603,18 +910,24 @@ pub fn prepare_raw_line(raw_line: &str, prefix_length: usize, config: &config::C
     ansi::ansi_preserving_slice(&line, prefix_length)
 }
 
-pub fn paint_minus_and_plus_lines(
-    lines: MinusPlus<&Vec<(String, State)>>,
+#[allow(clippy::too_many_arguments)]
+pub fn paint_minus_and_plus_lines<'a>(
+    lines: MinusPlus<&'a Vec<(String, State)>>,
     line_numbers_data: &mut Option<LineNumbersData>,
-    highlighter: &mut Option<HighlightLines>,
+    highlighter: &mut Option<Highlighter>,
+    moved_lines: Option<&MovedLines>,
+    comment_check_spans: Option<&[Vec<Range<usize>>]>,
+    lines_missing_final_newline: MinusPlus<bool>,
+    lines_had_cr: MinusPlus<&[bool]>,
     output_buffer: &mut String,
-    config: &config::Config,
+    syntax: &SyntaxReference,
+    config: &'a config::Config,
 ) {
-    let syntax_style_sections = MinusPlus::new(
+    let mut syntax_style_sections = MinusPlus::new(
         get_syntax_style_sections_for_lines(lines[Minus], highlighter.as_mut(), config),
         get_syntax_style_sections_for_lines(lines[Plus], highlighter.as_mut(), config),
     );
-    let (mut diff_style_sections, line_alignment) = get_diff_style_sections(&lines, config);
+    let (mut diff_style_sections, line_alignment) = get_diff_style_sections(&lines, syntax, config);
     let lines_have_homolog = edits::make_lines_have_homolog(&line_alignment);
     Painter::update_diff_style_sections(
         lines[Minus],
@@ -640,6 +953,73 @@ pub fn paint_minus_and_plus_lines(
         &lines_have_homolog[Plus],
         config,
     );
+    if !config.added_keyword_styles.is_empty() {
+        for style_sections in diff_style_sections[Plus].iter_mut() {
+            apply_keyword_styles(style_sections, &config.added_keyword_styles);
+        }
+    }
+    if config.redact_secrets {
+        for side in [Minus, Plus] {
+            for style_sections in diff_style_sections[side].iter_mut() {
+                secrets::apply_redacted_style(style_sections, config);
+            }
+        }
+    }
+    if let Some(comment_check_spans) = comment_check_spans {
+        for (style_sections, spans) in diff_style_sections[Plus]
+            .iter_mut()
+            .zip(comment_check_spans)
+        {
+            comment_check::apply_comment_check_style(
+                style_sections,
+                spans,
+                config.comment_check_style,
+            );
+        }
+    }
+    if config.max_code_line_warn > 0 {
+        for style_sections in diff_style_sections[Plus].iter_mut() {
+            apply_max_code_line_warn_style(
+                style_sections,
+                config.max_code_line_warn,
+                config.max_code_line_warn_style,
+            );
+        }
+    }
+    if let Some(moved_lines) = moved_lines {
+        moved_lines.apply_moved_styles(&lines, &mut diff_style_sections, config);
+    }
+    if let Some(marker) = &config.missing_newline_marker {
+        for side in [Minus, Plus] {
+            if let (true, Some(syntax_sections), Some(diff_sections)) = (
+                lines_missing_final_newline[side],
+                syntax_style_sections[side].last_mut(),
+                diff_style_sections[side].last_mut(),
+            ) {
+                append_missing_newline_marker(syntax_sections, diff_sections, marker, config);
+            }
+        }
+    }
+    if config.strip_cr == config::StripCr::Auto {
+        // Where a removed line and the added line aligned with it differ in whether they ended
+        // with a (removed) carriage return, this is made visible by marking the one which did.
+        for (minus, plus) in &line_alignment {
+            let (Some(minus), Some(plus)) = (*minus, *plus) else {
+                continue;
+            };
+            let (side, i) = match (lines_had_cr[Minus].get(minus), lines_had_cr[Plus].get(plus)) {
+                (Some(true), Some(false)) => (Minus, minus),
+                (Some(false), Some(true)) => (Plus, plus),
+                _ => continue,
+            };
+            if let (Some(syntax_sections), Some(diff_sections)) = (
+                syntax_style_sections[side].get_mut(i),
+                diff_style_sections[side].get_mut(i),
+            ) {
+                append_carriage_return_marker(syntax_sections, diff_sections, config);
+            }
+        }
+    }
     if config.side_by_side {
         side_by_side::paint_minus_and_plus_lines_side_by_side(
             lines,
@@ -682,9 +1062,88 @@ pub fn paint_minus_and_plus_lines(
     }
 }
 
+/// Append the missing newline marker to the sections of a line which lacks a final newline, in
+/// missing-newline-marker-style on top of the style of the end of the line.
+fn append_missing_newline_marker<'a>(
+    syntax_sections: &mut LineSections<'a, SyntectStyle>,
+    diff_sections: &mut LineSections<'a, Style>,
+    marker: &'a str,
+    config: &config::Config,
+) {
+    let style = diff_sections
+        .iter()
+        .rev()
+        .find(|(_, text)| *text != "\n")
+        .map_or(config.null_style, |(style, _)| *style);
+    insert_section_before_newline(syntax_sections, (config.null_syntect_style, marker));
+    insert_section_before_newline(
+        diff_sections,
+        (config.missing_newline_marker_style.on_top_of(style), marker),
+    );
+}
+
+/// Append the carriage return marker to the sections of a line, in the style of the end of the
+/// line.
+fn append_carriage_return_marker(
+    syntax_sections: &mut LineSections<SyntectStyle>,
+    diff_sections: &mut LineSections<Style>,
+    config: &config::Config,
+) {
+    let style = diff_sections
+        .iter()
+        .rev()
+        .find(|(_, text)| *text != "\n")
+        .map_or(config.null_style, |(style, _)| *style);
+    insert_section_before_newline(
+        syntax_sections,
+        (config.null_syntect_style, CARRIAGE_RETURN_MARKER),
+    );
+    insert_section_before_newline(diff_sections, (style, CARRIAGE_RETURN_MARKER));
+}
+
+// Sections of lines are terminated by '\n', see `prepare`.
+fn insert_section_before_newline<'a, T: Copy>(
+    sections: &mut LineSections<'a, T>,
+    section: (T, &'a str),
+) {
+    match sections.pop() {
+        Some((style, text)) if text.ends_with('\n') => {
+            let (text, newline) = text.split_at(text.len() - 1);
+            if !text.is_empty() {
+                sections.push((style, text));
+            }
+            sections.push(section);
+            sections.push((style, newline));
+        }
+        last => {
+            sections.extend(last);
+            sections.push(section);
+        }
+    }
+}
+
+/// Blend the foreground colors towards the background color of the syntax theme (--context-dim).
+fn dim_syntax_style_sections(
+    syntax_style_sections: &mut [LineSections<SyntectStyle>],
+    context_dim: f64,
+    config: &config::Config,
+) {
+    let background = config.context_dim_background;
+    let blend = |c: u8, b: u8| (c as f64 + (b as f64 - c as f64) * context_dim).round() as u8;
+    for (style, _) in syntax_style_sections.iter_mut().flatten() {
+        let fg = &mut style.foreground;
+        // Colors with alpha 0 encode a terminal palette color, see `to_ansi_color`.
+        if fg.a != 0 {
+            fg.r = blend(fg.r, background.r);
+            fg.g = blend(fg.g, background.g);
+            fg.b = blend(fg.b, background.b);
+        }
+    }
+}
+
 pub fn get_syntax_style_sections_for_lines<'a>(
     lines: &'a [(String, State)],
-    highlighter: Option<&mut HighlightLines>,
+    highlighter: Option<&mut Highlighter>,
     config: &config::Config,
 ) -> Vec<LineSections<'a, SyntectStyle>> {
     let mut line_sections = Vec::new();
@@ -696,6 +1155,10 @@ pub fn get_syntax_style_sections_for_lines<'a>(
     ) {
         (Some(highlighter), true) => {
             for (line, _) in lines.iter() {
+                if edits::is_longer_than(line, config.max_highlight_length) {
+                    line_sections.push(vec![(config.null_syntect_style, line.as_str())]);
+                    continue;
+                }
                 // Fast but simple length comparison. Overcounts non-printable ansi
                 // characters or wider UTF-8, but `truncate_str_short` in the
                 // else branch corrects that.
@@ -745,6 +1208,7 @@ pub fn get_syntax_style_sections_for_lines<'a>(
 #[allow(clippy::type_complexity)]
 fn get_diff_style_sections<'a>(
     lines: &MinusPlus<&'a Vec<(String, State)>>,
+    syntax: &SyntaxReference,
     config: &config::Config,
 ) -> (
     MinusPlus<Vec<LineSections<'a, Style>>>,
@@ -766,9 +1230,10 @@ fn get_diff_style_sections<'a>(
             config.minus_emph_style, // FIXME
             plus_styles,
             config.plus_emph_style, // FIXME
-            &config.tokenization_regex,
+            &edits::Tokenizer::for_syntax(&syntax.name, config),
             config.max_line_distance,
             config.max_line_distance_for_naively_paired_lines,
+            config.max_highlight_length,
         );
     let diff_sections = MinusPlus::new(
         minus_line_diff_style_sections,
@@ -777,23 +1242,81 @@ fn get_diff_style_sections<'a>(
     (diff_sections, line_alignment)
 }
 
-fn painted_prefix(state: State, config: &config::Config) -> Option<ANSIString> {
+/// Split the style sections of a line at the matches of the regexes, and style the matches.
+pub fn apply_keyword_styles<'a>(
+    style_sections: &mut LineSections<'a, Style>,
+    keyword_styles: &[(Regex, Style)],
+) {
+    for (regex, keyword_style) in keyword_styles {
+        let mut new_sections = Vec::with_capacity(style_sections.len());
+        for (style, text) in style_sections.drain(..) {
+            let mut offset = 0;
+            for m in regex.find_iter(text).filter(|m| !m.is_empty()) {
+                if m.start() > offset {
+                    new_sections.push((style, &text[offset..m.start()]));
+                }
+                new_sections.push((keyword_style.on_top_of(style), m.as_str()));
+                offset = m.end();
+            }
+            if offset < text.len() {
+                new_sections.push((style, &text[offset..]));
+            }
+        }
+        *style_sections = new_sections;
+    }
+}
+
+/// Split the style sections of a line after its first `max_width` columns, and style the rest
+/// of the line, but not its newline (--max-code-line-warn).
+pub fn apply_max_code_line_warn_style<'a>(
+    style_sections: &mut LineSections<'a, Style>,
+    max_width: usize,
+    warn_style: Style,
+) {
+    let mut width = 0;
+    let mut new_sections = Vec::with_capacity(style_sections.len() + 2);
+    for (style, text) in style_sections.drain(..) {
+        let mut split = if width >= max_width { 0 } else { text.len() };
+        for (i, c) in text.char_indices() {
+            if width >= max_width {
+                split = i;
+                break;
+            }
+            width += c.width().unwrap_or(0);
+        }
+        let (within, beyond) = text.split_at(split);
+        let (beyond, newline) = beyond.split_at(beyond.trim_end_matches('\n').len());
+        for (style, text) in [
+            (style, within),
+            (warn_style.on_top_of(style), beyond),
+            (style, newline),
+        ] {
+            if !text.is_empty() {
+                new_sections.push((style, text));
+            }
+        }
+    }
+    *style_sections = new_sections;
+}
+
+fn painted_prefix(state: State, config: &config::Config) -> Option<ANSIString<'_>> {
     use DiffType::*;
     use State::*;
     match (state, config.keep_plus_minus_markers) {
         // For a combined diff, unless we are in a merge conflict, we do not honor
         // keep_plus_minus_markers -- i.e. we always emit the prefix -- because there is currently
-        // no way to distinguish, say, a '+ ' line from a ' +' line, by styles alone. In a merge
-        // conflict we do honor the setting because the way merge conflicts are displayed indicates
-        // from which commit the lines derive.
+        // no way to distinguish, say, a '+ ' line from a ' +' line, by styles alone. The column of
+        // each parent is painted in the emph style of its marker, so that the parents relative to
+        // which a line was changed stand out. In a merge conflict we do honor the setting because
+        // the way merge conflicts are displayed indicates from which commit the lines derive.
         (HunkMinus(Combined(MergeParents::Prefix(prefix), InMergeConflict::No), _), _) => {
-            Some(config.minus_style.paint(prefix))
+            Some(paint_combined_prefix(&prefix, config.minus_style, config))
         }
         (HunkZero(Combined(MergeParents::Prefix(prefix), InMergeConflict::No), _), _) => {
             Some(config.zero_style.paint(prefix))
         }
         (HunkPlus(Combined(MergeParents::Prefix(prefix), InMergeConflict::No), _), _) => {
-            Some(config.plus_style.paint(prefix))
+            Some(paint_combined_prefix(&prefix, config.plus_style, config))
         }
         // But otherwise we honor keep_plus_minus_markers
         (HunkMinus(_, _), true) => Some(config.minus_style.paint("-".to_string())),
@@ -803,6 +1326,27 @@ fn painted_prefix(state: State, config: &config::Config) -> Option<ANSIString> {
     }
 }
 
+// Paint the markers of the parents in the prefix of a changed line of a combined diff, e.g. "+ ",
+// in their emph styles, and the other columns in the style of the line.
+fn paint_combined_prefix<'a>(
+    prefix: &str,
+    line_style: Style,
+    config: &config::Config,
+) -> ANSIString<'a> {
+    let painted: String = prefix
+        .chars()
+        .map(|c| {
+            let style = match c {
+                '-' => config.minus_emph_style,
+                '+' => config.plus_emph_style,
+                _ => line_style,
+            };
+            style.paint(c.to_string()).to_string()
+        })
+        .collect();
+    ansi_term::Style::new().paint(painted)
+}
+
 // Parse ANSI styles encountered in `raw_line` and apply `styles_map`.
 pub fn parse_style_sections<'a>(
     raw_line: &'a str,
@@ -810,14 +1354,15 @@ pub fn parse_style_sections<'a>(
 ) -> LineSections<'a, Style> {
     let empty_map = HashMap::new();
     let styles_map = config.styles_map.as_ref().unwrap_or(&empty_map);
-    ansi::parse_style_sections(raw_line)
+    ansi::parse_style_sections_with_sgr_params(raw_line)
         .iter()
-        .map(|(original_style, s)| {
+        .map(|(original_style, sgr_params, s)| {
             match styles_map.get(&style::ansi_term_style_equality_key(*original_style)) {
                 Some(mapped_style) => (*mapped_style, *s),
                 None => (
                     Style {
                         ansi_term_style: *original_style,
+                        sgr_params: *sgr_params,
                         ..Style::default()
                     },
                     *s,
@@ -840,12 +1385,7 @@ pub fn paint_file_path_with_line_number(
 ) -> String {
     let mut file_with_line_number = Vec::new();
     if let Some(file_style) = file_style {
-        let file_path = if let Some(regex_replacement) = &config.file_regex_replacement {
-            regex_replacement.execute(file_path)
-        } else {
-            Cow::from(file_path)
-        };
-        file_with_line_number.push(file_style.paint(file_path))
+        file_with_line_number.push(file_style.paint(utils::path::display_path(file_path, config)))
     };
     if let Some(line_number) = line_number {
         if let Some(line_number_style) = line_number_style {
@@ -876,7 +1416,7 @@ pub fn paint_file_path_with_line_number(
     }
     let file_with_line_number = ansi_term::ANSIStrings(&file_with_line_number).to_string();
     match if config.hyperlinks && !file_with_line_number.is_empty() {
-        utils::path::absolute_path(file_path, config)
+        utils::path::hyperlink_target_path(file_path, config)
     } else {
         None
     } {
@@ -1041,6 +1581,7 @@ mod superimpose_style_sections {
                 is_raw: false,
                 is_syntax_highlighted: true,
                 decoration_style: DecorationStyle::NoDecoration,
+                sgr_params: None,
             };
         }
         lazy_static! {
@@ -1056,6 +1597,7 @@ mod superimpose_style_sections {
                 is_raw: false,
                 is_syntax_highlighted: false,
                 decoration_style: DecorationStyle::NoDecoration,
+                sgr_params: None,
             };
         }
         lazy_static! {
@@ -1071,6 +1613,7 @@ mod superimpose_style_sections {
                 is_raw: false,
                 is_syntax_highlighted: true,
                 decoration_style: DecorationStyle::NoDecoration,
+                sgr_params: None,
             };
         }
 
@@ -1130,3 +1673,287 @@ mod superimpose_style_sections {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use regex::Regex;
+
+    use syntect::highlighting::{Color as SyntectColor, Style as SyntectStyle};
+
+    use super::{apply_keyword_styles, apply_max_code_line_warn_style, dim_syntax_style_sections};
+    use crate::style::Style;
+    use crate::tests::integration_test_utils::{self, DeltaTest};
+
+    #[test]
+    fn test_apply_keyword_styles() {
+        let plus_style = Style::from_colors(None, Some(ansi_term::Color::Green));
+        let yellow = Style::from_colors(Some(ansi_term::Color::Yellow), None);
+        let mut sections = vec![(plus_style, "// TODO: "), (plus_style, "FIXME TODO\n")];
+        apply_keyword_styles(
+            &mut sections,
+            &[(Regex::new("TODO|FIXME").unwrap(), yellow)],
+        );
+        let yellow_on_green = Style::from_colors(
+            Some(ansi_term::Color::Yellow),
+            Some(ansi_term::Color::Green),
+        );
+        assert_eq!(
+            sections,
+            vec![
+                (plus_style, "// "),
+                (yellow_on_green, "TODO"),
+                (plus_style, ": "),
+                (yellow_on_green, "FIXME"),
+                (plus_style, " "),
+                (yellow_on_green, "TODO"),
+                (plus_style, "\n"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_added_keyword_styles_with_keywords() {
+        DeltaTest::with_args(&[
+            "--added-keyword-styles",
+            r"TODO=bold yellow, FIXME = red blue, @todo=ul, \bXXX\b => bold",
+            "--plus-style",
+            "green",
+            "--plus-emph-style",
+            "green",
+            "--minus-style",
+            "normal",
+            "--minus-emph-style",
+            "normal",
+        ])
+        .explain_ansi()
+        .with_input(
+            "\
+--- a/a.txt
++++ b/a.txt
+@@ -1 +1,2 @@
+-TODO: FIXME
++TODOS: FIXME @todo XXX
+",
+        )
+        .expect_after_header(
+            r#"
+            TODO: FIXME
+            (green)TODOS: (red blue)FIXME(green) (ul normal)@todo(green) (bold normal)XXX(normal)"#,
+        );
+    }
+
+    #[test]
+    fn test_added_keyword_styles() {
+        DeltaTest::with_args(&[
+            "--added-keyword-styles",
+            r"TODO => bold yellow, \bunsafe\b => red blue",
+            "--plus-style",
+            "green",
+            "--minus-style",
+            "normal",
+        ])
+        .explain_ansi()
+        .with_input(
+            "\
+--- a/a.txt
++++ b/a.txt
+@@ -1 +1,2 @@
+-unsafe TODO
++unsafe TODO
++unsafely
+",
+        )
+        .expect_after_header(
+            r#"
+            unsafe TODO
+            (red blue)unsafe(green) (bold yellow)TODO(normal)
+            (green)unsafely(normal)"#,
+        );
+    }
+
+    #[test]
+    fn test_apply_max_code_line_warn_style() {
+        let plus_style = Style::from_colors(None, Some(ansi_term::Color::Green));
+        let underline = Style {
+            ansi_term_style: ansi_term::Style::new().underline(),
+            ..Style::new()
+        };
+        let underline_on_green = underline.on_top_of(plus_style);
+        let apply = |mut sections: Vec<(Style, &'static str)>, max_width| {
+            apply_max_code_line_warn_style(&mut sections, max_width, underline);
+            sections
+        };
+        assert_eq!(
+            apply(vec![(plus_style, "let a"), (plus_style, " = 1;\n")], 7),
+            vec![
+                (plus_style, "let a"),
+                (plus_style, " ="),
+                (underline_on_green, " 1;"),
+                (plus_style, "\n"),
+            ]
+        );
+        // Wide characters take two columns.
+        assert_eq!(
+            apply(vec![(plus_style, "日本語\n")], 4),
+            vec![
+                (plus_style, "日本"),
+                (underline_on_green, "語"),
+                (plus_style, "\n")
+            ]
+        );
+        assert_eq!(
+            apply(vec![(plus_style, "short\n")], 5),
+            vec![(plus_style, "short"), (plus_style, "\n")]
+        );
+    }
+
+    #[test]
+    fn test_max_code_line_warn() {
+        DeltaTest::with_args(&[
+            "--max-code-line-warn",
+            "6",
+            "--max-code-line-warn-style",
+            "red",
+            "--plus-style",
+            "green",
+            "--minus-style",
+            "normal",
+        ])
+        .explain_ansi()
+        .with_input(
+            "\
+--- a/a.txt
++++ b/a.txt
+@@ -1 +1,2 @@
+-a long line
++a long line
++short
+",
+        )
+        .expect_after_header(
+            r#"
+            a long line
+            (green)a long(red) line(normal)
+            (green)short(normal)"#,
+        );
+    }
+
+    #[test]
+    fn test_dim_syntax_style_sections() {
+        let config = integration_test_utils::make_config_from_args(&[
+            "--context-dim",
+            "0.25",
+            "--syntax-theme",
+            "none",
+            "--light",
+        ]);
+        let style = |r, g, b, a| SyntectStyle {
+            foreground: SyntectColor { r, g, b, a },
+            ..SyntectStyle::default()
+        };
+        let mut sections = vec![vec![
+            (style(0x00, 0x40, 0x80, 0xff), "fn "),
+            (style(0x01, 0x00, 0x00, 0x00), "main"),
+        ]];
+        dim_syntax_style_sections(&mut sections, config.context_dim, &config);
+        assert_eq!(
+            sections,
+            vec![vec![
+                (style(0x40, 0x70, 0xa0, 0xff), "fn "),
+                // A terminal palette color is not changed.
+                (style(0x01, 0x00, 0x00, 0x00), "main"),
+            ]]
+        );
+    }
+
+    #[test]
+    fn test_context_dim() {
+        DeltaTest::with_args(&[
+            "--context-dim",
+            "0.5",
+            "--syntax-theme",
+            "Monokai Extended",
+            "--true-color",
+            "always",
+        ])
+        .with_input(
+            "\
+--- a/a.rs
++++ b/a.rs
+@@ -1,2 +1,2 @@
+ fn f() {}
+-fn g() {}
++fn h() {}
+",
+        )
+        // The color of "fn" is #66d9ef, blended halfway towards the theme's background #222222.
+        .expect_raw_contains("\x1b[38;2;68;126;137mfn")
+        .expect_raw_contains("38;2;102;217;239mfn");
+    }
+
+    #[test]
+    fn test_context_fade() {
+        let output = DeltaTest::with_args(&[
+            "--context-dim",
+            "0.5",
+            "--context-fade",
+            "2",
+            "--syntax-theme",
+            "Monokai Extended",
+            "--true-color",
+            "always",
+        ])
+        .with_input(
+            "\
+--- a/a.rs
++++ b/a.rs
+@@ -1,4 +1,4 @@
+ fn a() {}
+ fn b() {}
+-fn c() {}
++fn d() {}
+ fn e() {}
+",
+        );
+        let fn_colors: Vec<&str> = output
+            .raw_output
+            .lines()
+            .filter_map(|line| line.find("mfn").map(|i| &line[..i]))
+            .collect();
+        // Two lines away from the change, "fn" is dimmed halfway towards the background, and next
+        // to it, a quarter of the way.
+        assert!(fn_colors[0].ends_with("\x1b[38;2;68;126;137"));
+        assert!(fn_colors[1].ends_with("\x1b[38;2;85;171;188"));
+        assert!(fn_colors[4].ends_with("\x1b[38;2;85;171;188"));
+    }
+
+    #[test]
+    fn test_combined_diff_prefix_styles() {
+        DeltaTest::with_args(&[
+            "--minus-style",
+            "red",
+            "--minus-emph-style",
+            "bold red",
+            "--plus-style",
+            "green",
+            "--plus-emph-style",
+            "bold green",
+        ])
+        .with_input(
+            "\
+diff --cc f
+index 1111111,2222222..3333333
+--- a/f
++++ b/f
+@@@ -1,1 -1,1 +1,2 @@@
+ -b
++ x
+++y
+",
+        )
+        // The marker of each parent is in the emph style of the marker.
+        .expect_raw_contains("\x1b[31m \x1b[0m\x1b[1;31m-\x1b[0m")
+        .expect_raw_contains("\x1b[1;32m+\x1b[0m\x1b[32m \x1b[0m")
+        .expect_raw_contains("\x1b[1;32m+\x1b[0m\x1b[1;32m+\x1b[0m");
+    }
+}
@@ -32,6 +32,7 @@ impl Style {
             is_raw,
             is_syntax_highlighted,
             decoration_style,
+            sgr_params: None,
         }
     }
 
@@ -1,5 +1,7 @@
 use std::collections::{HashMap, HashSet};
 
+use regex::Regex;
+
 use crate::cli;
 use crate::color;
 use crate::fatal;
@@ -37,6 +39,18 @@ pub fn parse_styles(opt: &cli::Opt) -> HashMap<String, Style> {
         .get_mut("plus-emph-style")
         .unwrap_or_else(|| panic!("plus-emph-style not found in resolved styles"))
         .is_emph = true;
+
+    if opt.syntax_highlight_changes_only {
+        for name in ["zero-style", "minus-non-emph-style", "plus-non-emph-style"] {
+            resolved_styles.get_mut(name).unwrap().is_syntax_highlighted = false;
+        }
+        for name in ["minus-emph-style", "plus-emph-style"] {
+            let style = resolved_styles.get_mut(name).unwrap();
+            if style.ansi_term_style.foreground.is_none() {
+                style.is_syntax_highlighted = true;
+            }
+        }
+    }
     resolved_styles
 }
 
@@ -60,6 +74,54 @@ pub fn parse_styles_map(opt: &cli::Opt) -> Option<HashMap<style::AnsiTermStyleEq
     }
 }
 
+/// Parse rules of the form `REGEX => STYLE, ...`, such as the value of added-keyword-styles. A rule
+/// may also have the form `KEYWORD=STYLE`, whose regex matches the keyword as a whole word.
+pub fn parse_keyword_styles(keyword_styles_str: &str, opt: &cli::Opt) -> Vec<(Regex, Style)> {
+    keyword_styles_str
+        .split(',')
+        .filter(|rule| !rule.trim().is_empty())
+        .map(|rule| {
+            let (regex, style) = match rule.rsplit_once("=>") {
+                Some((regex, style)) => (
+                    Regex::new(regex.trim()).unwrap_or_else(|err| {
+                        fatal(format!(
+                            "Invalid regex in keyword style rule {rule:?}: {err}"
+                        ))
+                    }),
+                    style,
+                ),
+                None => match rule.split_once('=') {
+                    Some((keyword, style)) if !keyword.trim().is_empty() => {
+                        (keyword_regex(keyword.trim()), style)
+                    }
+                    _ => fatal(format!(
+                        "Invalid keyword style rule {rule:?}: expected REGEX => STYLE or KEYWORD=STYLE."
+                    )),
+                },
+            };
+            (
+                regex,
+                parse_as_style_or_reference_to_git_config(style.trim(), opt),
+            )
+        })
+        .collect()
+}
+
+/// A regex matching `keyword` literally, and as a whole word at the ends which are word characters.
+fn keyword_regex(keyword: &str) -> Regex {
+    let boundary = |c: Option<char>| match c {
+        Some(c) if c.is_alphanumeric() || c == '_' => r"\b",
+        _ => "",
+    };
+    Regex::new(&format!(
+        "{}{}{}",
+        boundary(keyword.chars().next()),
+        regex::escape(keyword),
+        boundary(keyword.chars().last())
+    ))
+    .unwrap()
+}
+
 fn resolve_style_references(
     edges: HashMap<&str, StyleReference>,
     opt: &cli::Opt,
@@ -232,6 +294,10 @@ fn make_hunk_styles(opt: &cli::Opt, styles: &mut HashMap<&str, StyleReference>)
         opt.git_config(),
     );
 
+    // The styles of the lines of moved blocks (--color-moved).
+    let moved_style =
+        |style_string: &str| style_from_str(style_string, None, None, true_color, opt.git_config());
+
     styles.extend([
         ("minus-style", minus_style),
         ("minus-emph-style", minus_emph_style),
@@ -246,6 +312,16 @@ fn make_hunk_styles(opt: &cli::Opt, styles: &mut HashMap<&str, StyleReference>)
         ("plus-non-emph-style", plus_non_emph_style),
         ("plus-empty-line-marker-style", plus_empty_line_marker_style),
         ("whitespace-error-style", whitespace_error_style),
+        ("moved-minus-style", moved_style(&opt.moved_minus_style)),
+        (
+            "moved-minus-alt-style",
+            moved_style(&opt.moved_minus_alt_style),
+        ),
+        ("moved-plus-style", moved_style(&opt.moved_plus_style)),
+        (
+            "moved-plus-alt-style",
+            moved_style(&opt.moved_plus_alt_style),
+        ),
     ])
 }
 
@@ -401,6 +477,48 @@ fn make_commit_file_hunk_header_styles(opt: &cli::Opt, styles: &mut HashMap<&str
 }
 
 fn make_blame_styles(opt: &cli::Opt, styles: &mut HashMap<&str, StyleReference>) {
+    styles.extend([
+        (
+            "blame-boundary-style",
+            style_from_str(
+                &opt.blame_boundary_style,
+                None,
+                None,
+                opt.computed.true_color,
+                opt.git_config(),
+            ),
+        ),
+        (
+            "blame-heatmap-newest",
+            style_from_str(
+                &opt.blame_heatmap_newest,
+                None,
+                None,
+                opt.computed.true_color,
+                opt.git_config(),
+            ),
+        ),
+        (
+            "blame-heatmap-oldest",
+            style_from_str(
+                &opt.blame_heatmap_oldest,
+                None,
+                None,
+                opt.computed.true_color,
+                opt.git_config(),
+            ),
+        ),
+        (
+            "blame-ignored-style",
+            style_from_str(
+                &opt.blame_ignored_style,
+                None,
+                None,
+                opt.computed.true_color,
+                opt.git_config(),
+            ),
+        ),
+    ]);
     if let Some(style_string) = &opt.blame_code_style {
         styles.insert(
             "blame-code-style",
@@ -500,6 +618,312 @@ fn make_merge_conflict_styles(opt: &cli::Opt, styles: &mut HashMap<&str, StyleRe
 }
 
 fn make_misc_styles(opt: &cli::Opt, styles: &mut HashMap<&str, StyleReference>) {
+    styles.insert(
+        "context-fold-style",
+        style_from_str(
+            &opt.context_fold_style,
+            None,
+            None,
+            opt.computed.true_color,
+            opt.git_config(),
+        ),
+    );
+    styles.insert(
+        "function-separator-style",
+        style_from_str(
+            &opt.function_separator_style,
+            None,
+            None,
+            opt.computed.true_color,
+            opt.git_config(),
+        ),
+    );
+    styles.insert(
+        "hunk-apply-command-style",
+        style_from_str(
+            &opt.hunk_apply_command_style,
+            None,
+            None,
+            opt.computed.true_color,
+            opt.git_config(),
+        ),
+    );
+    styles.extend([
+        (
+            "hunk-change-density-minus-style",
+            style_from_str(
+                &opt.hunk_change_density_minus_style,
+                None,
+                None,
+                opt.computed.true_color,
+                opt.git_config(),
+            ),
+        ),
+        (
+            "hunk-change-density-plus-style",
+            style_from_str(
+                &opt.hunk_change_density_plus_style,
+                None,
+                None,
+                opt.computed.true_color,
+                opt.git_config(),
+            ),
+        ),
+    ]);
+    styles.insert(
+        "range-diff-header-style",
+        style_from_str(
+            &opt.range_diff_header_style,
+            None,
+            None,
+            opt.computed.true_color,
+            opt.git_config(),
+        ),
+    );
+    styles.insert(
+        "log-graph-style",
+        style_from_str(
+            &opt.log_graph_style,
+            None,
+            None,
+            opt.computed.true_color,
+            opt.git_config(),
+        ),
+    );
+    styles.insert(
+        "comment-check-style",
+        style_from_str(
+            &opt.comment_check_style,
+            None,
+            None,
+            opt.computed.true_color,
+            opt.git_config(),
+        ),
+    );
+    styles.insert(
+        "max-code-line-warn-style",
+        style_from_str(
+            &opt.max_code_line_warn_style,
+            None,
+            None,
+            opt.computed.true_color,
+            opt.git_config(),
+        ),
+    );
+    styles.insert(
+        "missing-newline-marker-style",
+        style_from_str(
+            &opt.missing_newline_marker_style,
+            None,
+            None,
+            opt.computed.true_color,
+            opt.git_config(),
+        ),
+    );
+    styles.insert(
+        "split-hunks-style",
+        style_from_str(
+            &opt.split_hunks_style,
+            None,
+            None,
+            opt.computed.true_color,
+            opt.git_config(),
+        ),
+    );
+    styles.insert(
+        "pull-request-title-style",
+        style_from_str(
+            &opt.pull_request_title_style,
+            None,
+            None,
+            opt.computed.true_color,
+            opt.git_config(),
+        ),
+    );
+    styles.insert(
+        "merge-parent-style",
+        style_from_str(
+            &opt.merge_parent_style,
+            None,
+            None,
+            opt.computed.true_color,
+            opt.git_config(),
+        ),
+    );
+    styles.extend([
+        (
+            "conventional-commit-breaking-style",
+            style_from_str(
+                &opt.conventional_commit_breaking_style,
+                None,
+                None,
+                opt.computed.true_color,
+                opt.git_config(),
+            ),
+        ),
+        (
+            "conventional-commit-scope-style",
+            style_from_str(
+                &opt.conventional_commit_scope_style,
+                None,
+                None,
+                opt.computed.true_color,
+                opt.git_config(),
+            ),
+        ),
+        (
+            "conventional-commit-type-style",
+            style_from_str(
+                &opt.conventional_commit_type_style,
+                None,
+                None,
+                opt.computed.true_color,
+                opt.git_config(),
+            ),
+        ),
+    ]);
+    styles.insert(
+        "commit-message-code-style",
+        style_from_str(
+            &opt.commit_message_code_style,
+            None,
+            None,
+            opt.computed.true_color,
+            opt.git_config(),
+        ),
+    );
+    styles.extend([
+        (
+            "summary-create-style",
+            style_from_str(
+                &opt.summary_create_style,
+                None,
+                None,
+                opt.computed.true_color,
+                opt.git_config(),
+            ),
+        ),
+        (
+            "summary-delete-style",
+            style_from_str(
+                &opt.summary_delete_style,
+                None,
+                None,
+                opt.computed.true_color,
+                opt.git_config(),
+            ),
+        ),
+        (
+            "summary-similarity-style",
+            style_from_str(
+                &opt.summary_similarity_style,
+                None,
+                None,
+                opt.computed.true_color,
+                opt.git_config(),
+            ),
+        ),
+    ]);
+    styles.insert(
+        "redacted-style",
+        style_from_str(
+            &opt.redacted_style,
+            None,
+            None,
+            opt.computed.true_color,
+            opt.git_config(),
+        ),
+    );
+    styles.insert(
+        "secret-warning-style",
+        style_from_str(
+            &opt.secret_warning_style,
+            None,
+            None,
+            opt.computed.true_color,
+            opt.git_config(),
+        ),
+    );
+    styles.insert(
+        "coverage-covered-style",
+        style_from_str(
+            &opt.coverage_covered_style,
+            None,
+            None,
+            opt.computed.true_color,
+            opt.git_config(),
+        ),
+    );
+    styles.insert(
+        "coverage-uncovered-style",
+        style_from_str(
+            &opt.coverage_uncovered_style,
+            None,
+            None,
+            opt.computed.true_color,
+            opt.git_config(),
+        ),
+    );
+    styles.insert(
+        "diagnostics-style",
+        style_from_str(
+            &opt.diagnostics_style,
+            None,
+            None,
+            opt.computed.true_color,
+            opt.git_config(),
+        ),
+    );
+    styles.insert(
+        "gutter-style",
+        style_from_str(
+            &opt.gutter_style,
+            None,
+            None,
+            opt.computed.true_color,
+            opt.git_config(),
+        ),
+    );
+    styles.insert(
+        "inline-blame-style",
+        style_from_str(
+            &opt.inline_blame_style,
+            None,
+            None,
+            opt.computed.true_color,
+            opt.git_config(),
+        ),
+    );
+    styles.insert(
+        "side-by-side-center-gutter-style",
+        style_from_str(
+            &opt.side_by_side_center_gutter_style,
+            None,
+            None,
+            opt.computed.true_color,
+            opt.git_config(),
+        ),
+    );
+    styles.insert(
+        "patch-applying-style",
+        style_from_str(
+            &opt.patch_applying_style,
+            None,
+            None,
+            opt.computed.true_color,
+            opt.git_config(),
+        ),
+    );
+    styles.insert(
+        "patch-failure-style",
+        style_from_str(
+            &opt.patch_failure_style,
+            None,
+            None,
+            opt.computed.true_color,
+            opt.git_config(),
+        ),
+    );
     styles.insert(
         "inline-hint-style",
         style_from_str(
@@ -670,4 +1094,22 @@ mod tests {
             "__cycle__"
         );
     }
+
+    #[test]
+    fn test_syntax_highlight_changes_only() {
+        let opt = integration_test_utils::make_options_from_args(&[
+            "--syntax-highlight-changes-only",
+            "--plus-emph-style",
+            "red green",
+        ]);
+        let styles = parse_styles(&opt);
+        assert!(!styles["zero-style"].is_syntax_highlighted);
+        assert!(!styles["minus-non-emph-style"].is_syntax_highlighted);
+        assert!(!styles["plus-non-emph-style"].is_syntax_highlighted);
+        assert!(styles["minus-emph-style"].is_syntax_highlighted);
+        // An explicit foreground color is kept.
+        assert!(!styles["plus-emph-style"].is_syntax_highlighted);
+        // Lines which are not paired with another line are entirely changed.
+        assert!(styles["plus-style"].is_syntax_highlighted);
+    }
 }
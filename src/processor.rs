@@ -0,0 +1,266 @@
+use std::ffi::OsString;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::sync::mpsc;
+use std::thread;
+
+use crate::cli::{self, Call};
+use crate::config::Config;
+use crate::delta;
+use crate::env::DeltaEnv;
+use crate::errors::{anyhow, Result};
+use crate::utils::{self, lines};
+
+/// Make the config for command-line arguments of delta, e.g. `["--side-by-side"]`, as the delta
+/// program does: the [delta] section of the git config and the environment are also read, unless
+/// --no-gitconfig is given. Invalid arguments, or values of options, are returned as errors; when
+/// built with panic = "abort" they end the process with an error message, as in the delta program.
+pub fn config_from_args<I, S>(args: I) -> Result<Config>
+where
+    I: IntoIterator<Item = S>,
+    S: Into<OsString>,
+{
+    // The first argument is the name of the program.
+    let args = std::iter::once("delta".into())
+        .chain(args.into_iter().map(Into::into))
+        .collect();
+    crate::catch_fatal(|| {
+        let env = DeltaEnv::init();
+        let assets = utils::bat::assets::load_highlighting_assets();
+        match cli::Opt::try_from_args_and_git_config(args, &env, assets)? {
+            Call::Delta(opt) => {
+                let config = Config::from(opt);
+                utils::warnings::check_error()?;
+                Ok(config)
+            }
+            Call::Help(_) | Call::Version(_) => Err(anyhow!("The arguments do not make a config.")),
+        }
+    })?
+}
+
+/// Display the diff (or other input of delta) read from `input` in `writer`, with the styles of
+/// the config. Errors which end the delta program, e.g. an invalid color found while processing,
+/// are returned.
+pub fn process<R: BufRead>(input: R, writer: &mut dyn Write, config: &Config) -> io::Result<()> {
+    crate::catch_fatal(|| delta::delta(lines::byte_lines(input), writer, config))
+        .map_err(io::Error::other)?
+}
+
+/// A streaming interface to delta: the input written to it is displayed in the writer, as delta
+/// displays its standard input. The input is processed in another thread, as it is written.
+pub struct DeltaProcessor<W> {
+    sender: Option<mpsc::Sender<Vec<u8>>>,
+    thread: Option<thread::JoinHandle<io::Result<W>>>,
+}
+
+impl<W: Write + Send + 'static> DeltaProcessor<W> {
+    /// Start processing with the config, e.g. made by `config_from_args`.
+    pub fn new(config: Config, mut writer: W) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let thread = thread::spawn(move || {
+            let input = BufReader::new(ChannelReader {
+                receiver,
+                chunk: Vec::new(),
+                position: 0,
+            });
+            process(input, &mut writer, &config)?;
+            writer.flush()?;
+            Ok(writer)
+        });
+        Self {
+            sender: Some(sender),
+            thread: Some(thread),
+        }
+    }
+
+    /// Start processing with the config for the command-line arguments (see `config_from_args`).
+    pub fn from_args<I, S>(args: I, writer: W) -> Result<Self>
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<OsString>,
+    {
+        Ok(Self::new(config_from_args(args)?, writer))
+    }
+
+    /// Process the rest of the input, and return the writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        // The end of the input is signaled by closing the channel.
+        self.sender = None;
+        match self.thread.take().map(thread::JoinHandle::join) {
+            Some(Ok(result)) => result,
+            Some(Err(_)) => Err(io::Error::other(
+                "delta panicked while processing the input",
+            )),
+            None => Err(io::Error::other(
+                "delta has already finished processing the input",
+            )),
+        }
+    }
+}
+
+impl<W> Write for DeltaProcessor<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let sent = match &self.sender {
+            Some(sender) => sender.send(buf.to_vec()).is_ok(),
+            None => false,
+        };
+        if !sent {
+            // The thread has stopped, e.g. because writing the output failed.
+            return Err(io::Error::new(
+                io::ErrorKind::BrokenPipe,
+                "delta stopped processing the input",
+            ));
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl<W> Drop for DeltaProcessor<W> {
+    fn drop(&mut self) {
+        self.sender = None;
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+// Reads the chunks of input sent to a DeltaProcessor, until it is finished.
+struct ChannelReader {
+    receiver: mpsc::Receiver<Vec<u8>>,
+    chunk: Vec<u8>,
+    position: usize,
+}
+
+impl Read for ChannelReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.position == self.chunk.len() {
+            match self.receiver.recv() {
+                Ok(chunk) => {
+                    self.chunk = chunk;
+                    self.position = 0;
+                }
+                Err(_) => return Ok(0),
+            }
+        }
+        let n = buf.len().min(self.chunk.len() - self.position);
+        buf[..n].copy_from_slice(&self.chunk[self.position..self.position + n]);
+        self.position += n;
+        Ok(n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ansi;
+    use crate::tests::test_utils::temp_path;
+
+    #[test]
+    fn test_delta_processor() {
+        let args = ["--no-gitconfig", "--side-by-side", "--width", "60"];
+        let mut expected = Vec::new();
+        process(
+            DIFF.as_bytes(),
+            &mut expected,
+            &config_from_args(args).unwrap(),
+        )
+        .unwrap();
+        let mut processor = DeltaProcessor::from_args(args, Vec::new()).unwrap();
+        // The input is written in chunks which are not lines.
+        for chunk in DIFF.as_bytes().chunks(7) {
+            processor.write_all(chunk).unwrap();
+        }
+        let output = processor.finish().unwrap();
+        assert_eq!(output, expected);
+        let output = ansi::strip_ansi_codes(&String::from_utf8(output).unwrap());
+        assert!(output.contains("│  2 │fn c() {}"));
+
+        assert!(DeltaProcessor::from_args(["--help"], Vec::new()).is_err());
+
+        // A config can be made before processing, and moved into the processing thread.
+        let config = config_from_args(args).unwrap();
+        let mut processor = DeltaProcessor::new(config, Vec::new());
+        processor.write_all(DIFF.as_bytes()).unwrap();
+        assert_eq!(processor.finish().unwrap(), expected);
+    }
+
+    #[test]
+    fn test_process() {
+        let config = config_from_args(["--no-gitconfig", "--raw"]).unwrap();
+        let mut output = Vec::new();
+        process(DIFF.as_bytes(), &mut output, &config).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert_eq!(ansi::strip_ansi_codes(&output), DIFF);
+
+        assert!(config_from_args(["--version"]).is_err());
+    }
+
+    #[test]
+    fn test_config_from_invalid_args() {
+        let error = |args: &[&str]| config_from_args(args).err().unwrap().to_string();
+        assert!(error(&["--no-such-option"]).contains("--no-such-option"));
+        assert_eq!(
+            error(&["--no-gitconfig", "--minus-style", "no-such-color"]),
+            "Invalid color or style attribute: no-such-color"
+        );
+        assert!(error(&["--no-gitconfig", "--side-by-side-split", "1:2:3"]).contains("1:2:3"));
+        assert!(DeltaProcessor::from_args(["--no-such-option"], Vec::new()).is_err());
+    }
+
+    #[test]
+    fn test_process_error() {
+        let path = temp_path("no_such_dir").join("line-map");
+        let args = [
+            "--no-gitconfig",
+            "--color-only",
+            "--line-map",
+            path.to_str().unwrap(),
+        ];
+        let config = config_from_args(args).unwrap();
+        let error = process(DIFF.as_bytes(), &mut Vec::new(), &config).unwrap_err();
+        assert!(error
+            .to_string()
+            .starts_with("Could not create the line map file"));
+    }
+
+    #[test]
+    fn test_process_fatal_error() {
+        // The colors of the blame palette are parsed when blame output is processed.
+        let args = ["--no-gitconfig", "--blame-palette", "no-such-color"];
+        let blame = "aaaaaaa1 (Ada 2020-01-01 00:00:00 +0000 1) fn a() {}\n";
+        let error = process(
+            blame.as_bytes(),
+            &mut Vec::new(),
+            &config_from_args(args).unwrap(),
+        )
+        .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Invalid color or style attribute: no-such-color"
+        );
+
+        let mut processor = DeltaProcessor::from_args(args, Vec::new()).unwrap();
+        let _ = processor.write_all(blame.as_bytes());
+        assert!(processor.finish().is_err());
+    }
+
+    #[test]
+    fn config_is_send() {
+        fn assert_send<T: Send>() {}
+        assert_send::<Config>();
+    }
+
+    const DIFF: &str = "\
+diff --git a/file.rs b/file.rs
+index 1111111..2222222 100644
+--- a/file.rs
++++ b/file.rs
+@@ -1,2 +1,2 @@
+ fn a() {}
+-fn b() {}
++fn c() {}
+";
+}
@@ -5,6 +5,7 @@ use std::hash::{Hash, Hasher};
 use lazy_static::lazy_static;
 
 use crate::ansi;
+use crate::ansi::iterator::SgrParams;
 use crate::color;
 use crate::git_config::GitConfig;
 
@@ -17,6 +18,8 @@ pub struct Style {
     pub is_raw: bool,
     pub is_syntax_highlighted: bool,
     pub decoration_style: DecorationStyle,
+    // The parameters of a style of the input which ansi_term cannot represent.
+    pub sgr_params: Option<SgrParams>,
 }
 
 // More compact debug output, replace false/empty with lowercase and true with uppercase.
@@ -55,7 +58,7 @@ impl fmt::Debug for Style {
     }
 }
 
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
 pub enum DecorationStyle {
     Box(ansi_term::Style),
     Underline(ansi_term::Style),
@@ -64,15 +67,10 @@ pub enum DecorationStyle {
     BoxWithUnderline(ansi_term::Style),
     BoxWithOverline(ansi_term::Style),
     BoxWithUnderOverline(ansi_term::Style),
+    #[default]
     NoDecoration,
 }
 
-impl Default for DecorationStyle {
-    fn default() -> Self {
-        Self::NoDecoration
-    }
-}
-
 impl Style {
     pub fn new() -> Self {
         Self {
@@ -82,6 +80,7 @@ impl Style {
             is_raw: false,
             is_syntax_highlighted: false,
             decoration_style: DecorationStyle::NoDecoration,
+            sgr_params: None,
         }
     }
 
@@ -118,6 +117,20 @@ impl Style {
         }
     }
 
+    /// This style, taking the colors which it does not specify from `other`.
+    pub fn on_top_of(self, other: Style) -> Style {
+        let mut style = self;
+        if style.ansi_term_style.foreground.is_none() && !style.is_syntax_highlighted {
+            style.ansi_term_style.foreground = other.ansi_term_style.foreground;
+            style.is_syntax_highlighted = other.is_syntax_highlighted;
+        }
+        if style.ansi_term_style.background.is_none() {
+            style.ansi_term_style.background = other.ansi_term_style.background;
+        }
+        style.is_emph = other.is_emph;
+        style
+    }
+
     pub fn is_applied_to(&self, s: &str) -> bool {
         match ansi::parse_first_style(s) {
             Some(parsed_style) => ansi_term_style_equality(parsed_style, self.ansi_term_style),
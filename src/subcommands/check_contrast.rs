@@ -0,0 +1,216 @@
+use std::io::{self, Write};
+
+use crate::color::{self, ColorMode};
+use crate::config::Config;
+use crate::minusplus::MinusPlus;
+use crate::style::Style;
+use crate::utils::warnings::{self, WarningKind};
+
+/// The minimum contrast ratio of text and its background in WCAG level AA.
+const MIN_CONTRAST_RATIO: f64 = 4.5;
+
+/// Write the contrast ratio of the foreground and background colors of each style which sets
+/// either, and warn about those too low for the text to be readable. Return whether all are
+/// readable.
+pub fn check_contrast(config: &Config, writer: &mut dyn Write) -> io::Result<bool> {
+    let mut readable = true;
+    for (name, foreground, background, ratio) in contrast_ratios(config) {
+        writeln!(
+            writer,
+            "{:<28} {} on {}  {:>5.2}:1",
+            name,
+            format_rgb(foreground),
+            format_rgb(background),
+            ratio
+        )?;
+        if ratio < MIN_CONTRAST_RATIO {
+            readable = false;
+            warnings::warn(
+                WarningKind::Contrast,
+                format!(
+                    "The text of {name} is hard to read: its contrast ratio is {ratio:.2}:1, \
+                     below {MIN_CONTRAST_RATIO}:1."
+                ),
+            );
+        }
+    }
+    Ok(readable)
+}
+
+type Rgb = (u8, u8, u8);
+
+// The foreground and background colors of each style which sets either, and their contrast ratio.
+fn contrast_ratios(config: &Config) -> Vec<(&'static str, Rgb, Rgb, f64)> {
+    let (default_foreground, default_background) = match config.color_mode {
+        ColorMode::Light => ((0x00, 0x00, 0x00), (0xff, 0xff, 0xff)),
+        ColorMode::Dark => ((0xff, 0xff, 0xff), (0x00, 0x00, 0x00)),
+    };
+    let syntax_foreground = config
+        .syntax_theme
+        .as_ref()
+        .and_then(|theme| theme.settings.foreground)
+        .map(|c| (c.r, c.g, c.b))
+        .unwrap_or(default_foreground);
+    checked_styles(config)
+        .into_iter()
+        .filter(|(_, style)| {
+            let ansi_style = style.ansi_term_style;
+            !(style.is_omitted || style.is_raw)
+                && (ansi_style.foreground.is_some()
+                    || ansi_style.background.is_some()
+                    || style.is_syntax_highlighted)
+        })
+        .map(|(name, style)| {
+            let ansi_style = style.ansi_term_style;
+            let foreground = match ansi_style.foreground {
+                Some(color) => color::rgb(color),
+                None if style.is_syntax_highlighted => syntax_foreground,
+                None => default_foreground,
+            };
+            let background = ansi_style
+                .background
+                .map(color::rgb)
+                .unwrap_or(default_background);
+            let (foreground, background) = if ansi_style.is_reverse {
+                (background, foreground)
+            } else {
+                (foreground, background)
+            };
+            let ratio = contrast_ratio(foreground, background);
+            (name, foreground, background, ratio)
+        })
+        .collect()
+}
+
+fn checked_styles(config: &Config) -> Vec<(&'static str, Style)> {
+    let MinusPlus {
+        minus: line_numbers_minus_style,
+        plus: line_numbers_plus_style,
+    } = config.line_numbers_style_minusplus;
+    vec![
+        ("commit-style", config.commit_style),
+        ("file-style", config.file_style),
+        ("hunk-header-style", config.hunk_header_style),
+        ("minus-style", config.minus_style),
+        ("minus-non-emph-style", config.minus_non_emph_style),
+        ("minus-emph-style", config.minus_emph_style),
+        ("zero-style", config.zero_style),
+        ("plus-style", config.plus_style),
+        ("plus-non-emph-style", config.plus_non_emph_style),
+        ("plus-emph-style", config.plus_emph_style),
+        ("line-numbers-minus-style", line_numbers_minus_style),
+        ("line-numbers-zero-style", config.line_numbers_zero_style),
+        ("line-numbers-plus-style", line_numbers_plus_style),
+        ("grep-file-style", config.grep_file_style),
+        ("grep-line-number-style", config.grep_line_number_style),
+        ("whitespace-error-style", config.whitespace_error_style),
+    ]
+}
+
+fn format_rgb((r, g, b): Rgb) -> String {
+    format!("#{r:02x}{g:02x}{b:02x}")
+}
+
+/// The contrast ratio of two colors, from 1:1 to 21:1, as defined by WCAG 2: the ratio of their
+/// relative luminances, each increased by 0.05.
+pub fn contrast_ratio(a: Rgb, b: Rgb) -> f64 {
+    let (a, b) = (relative_luminance(a), relative_luminance(b));
+    (a.max(b) + 0.05) / (a.min(b) + 0.05)
+}
+
+fn relative_luminance((r, g, b): Rgb) -> f64 {
+    let linear = |c: u8| {
+        let c = c as f64 / 255.0;
+        if c <= 0.03928 {
+            c / 12.92
+        } else {
+            ((c + 0.055) / 1.055).powf(2.4)
+        }
+    };
+    0.2126 * linear(r) + 0.7152 * linear(g) + 0.0722 * linear(b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::features;
+    use crate::tests::integration_test_utils;
+
+    #[test]
+    fn test_contrast_ratio() {
+        assert_eq!(contrast_ratio((0, 0, 0), (0xff, 0xff, 0xff)), 21.0);
+        assert_eq!(contrast_ratio((0x77, 0x77, 0x77), (0x77, 0x77, 0x77)), 1.0);
+        // #767676 is the lightest grey readable on white.
+        assert!(contrast_ratio((0x76, 0x76, 0x76), (0xff, 0xff, 0xff)) > MIN_CONTRAST_RATIO);
+        assert!(contrast_ratio((0x77, 0x77, 0x77), (0xff, 0xff, 0xff)) < MIN_CONTRAST_RATIO);
+    }
+
+    #[test]
+    fn test_check_contrast() {
+        let config = integration_test_utils::make_config_from_args(&[
+            "--dark",
+            "--true-color",
+            "always",
+            "--minus-style",
+            "#444444 #222222",
+            "--plus-style",
+            "syntax #002800",
+        ]);
+        let mut output = Vec::new();
+        assert!(!check_contrast(&config, &mut output).unwrap());
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains("minus-style                  #444444 on #222222   1.63:1\n"));
+        assert!(output.contains("plus-style                   #f8f8f2 on #002800  "));
+    }
+
+    #[test]
+    fn test_reverse_style() {
+        let config = integration_test_utils::make_config_from_args(&[
+            "--light",
+            "--true-color",
+            "always",
+            "--whitespace-error-style",
+            "#111111 reverse",
+        ]);
+        let ratios = contrast_ratios(&config);
+        let (_, foreground, background, _) = ratios
+            .iter()
+            .find(|(name, ..)| *name == "whitespace-error-style")
+            .unwrap();
+        assert_eq!(
+            (*foreground, *background),
+            ((0xff, 0xff, 0xff), (0x11, 0x11, 0x11))
+        );
+    }
+
+    #[test]
+    fn test_accessible_features_are_readable() {
+        let builtin_features = features::make_builtin_features();
+        for feature in ["deuteranopia", "tritanopia", "high-contrast"] {
+            for mode in ["--light", "--dark"] {
+                for true_color in ["always", "never"] {
+                    let config = integration_test_utils::make_config_from_args(&[
+                        "--features",
+                        feature,
+                        mode,
+                        "--true-color",
+                        true_color,
+                    ]);
+                    // The styles set by the feature, rather than those left at their defaults.
+                    for (name, _, _, ratio) in contrast_ratios(&config) {
+                        if builtin_features[feature].contains_key(name) {
+                            assert!(
+                                ratio >= MIN_CONTRAST_RATIO,
+                                "{} {} {}: {}",
+                                feature,
+                                mode,
+                                name,
+                                ratio
+                            );
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
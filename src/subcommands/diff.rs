@@ -1,12 +1,16 @@
-use std::io::{BufRead, ErrorKind, Write};
+use std::collections::BTreeSet;
+use std::fs::{self, File};
+use std::io::{BufRead, Cursor, ErrorKind, Read, Write};
 use std::path::{Path, PathBuf};
 use std::process;
 
-use bytelines::ByteLinesReader;
+use regex::bytes::{NoExpand, Regex};
 
 use crate::config::{self, delta_unreachable};
 use crate::delta;
 use crate::utils::git::retrieve_git_version;
+use crate::utils::lines;
+use crate::utils::private_dir::new_private_temp_dir;
 
 #[derive(Debug, PartialEq)]
 enum Differ {
@@ -15,7 +19,8 @@ enum Differ {
 }
 
 /// Run `git diff` on the files provided on the command line and display the output. Fall back to
-/// `diff` if the supplied "files" use process substitution.
+/// `diff` if the supplied "files" use process substitution. Two directories are compared file by
+/// file (see `diff_dirs`).
 pub fn diff(
     minus_file: &Path,
     plus_file: &Path,
@@ -24,67 +29,59 @@ pub fn diff(
 ) -> i32 {
     use std::io::BufReader;
 
-    let mut diff_args = match shell_words::split(config.diff_args.trim()) {
-        Ok(words) => words,
+    let diff_args = match parse_diff_args(config) {
+        Ok(diff_args) => diff_args,
         Err(err) => {
-            eprintln!("Failed to parse diff args: {}: {err}", config.diff_args);
+            eprintln!("{err}");
             return config.error_exit_code;
         }
     };
-    // Permit e.g. -@U1
-    if diff_args
-        .first()
-        .map(|arg| !arg.is_empty() && !arg.starts_with('-'))
-        .unwrap_or(false)
-    {
-        diff_args[0] = format!("-{}", diff_args[0])
-    }
 
-    let via_process_substitution =
-        |f: &Path| f.starts_with("/proc/self/fd/") || f.starts_with("/dev/fd/");
+    if minus_file.is_dir() && plus_file.is_dir() {
+        return diff_dirs(minus_file, plus_file, &diff_args, config, writer);
+    }
 
-    // https://stackoverflow.com/questions/22706714/why-does-git-diff-not-work-with-process-substitution
-    // git <2.42 does not support process substitution
-    let (differ, mut diff_cmd) = match retrieve_git_version() {
-        Some(version)
-            if version >= (2, 42)
-                || !(via_process_substitution(minus_file)
-                    || via_process_substitution(plus_file)) =>
-        {
-            (
-                Differ::GitDiff,
-                vec!["git", "diff", "--no-index", "--color"],
-            )
-        }
-        _ => (
-            Differ::Diff,
-            if diff_args_set_unified_context(&diff_args) {
-                vec!["diff"]
-            } else {
-                vec!["diff", "-U3"]
-            },
+    // With --preprocess, the normalized files are diffed instead of the files.
+    let preprocessed = match &config.preprocess {
+        Some(command) => match PreprocessedFiles::new(command, minus_file, plus_file) {
+            Ok(preprocessed) => Some(preprocessed),
+            Err(err) => {
+                eprintln!("{err}");
+                return config.error_exit_code;
+            }
+        },
+        None => None,
+    };
+    let (minus_file, plus_file) = match &preprocessed {
+        Some(preprocessed) => (
+            preprocessed.minus_file.as_path(),
+            preprocessed.plus_file.as_path(),
         ),
+        None => (minus_file, plus_file),
     };
 
-    diff_cmd.extend(
-        diff_args
-            .iter()
-            .filter(|s| !s.is_empty())
-            .map(String::as_str),
-    );
-    diff_cmd.push("--");
+    let via_process_substitution =
+        |f: &Path| f.starts_with("/proc/self/fd/") || f.starts_with("/dev/fd/");
 
-    let (diff_bin, diff_cmd) = diff_cmd.split_first().unwrap();
-    let diff_path = match grep_cli::resolve_binary(PathBuf::from(diff_bin)) {
-        Ok(path) => path,
+    let DifferCommand {
+        differ,
+        bin: diff_bin,
+        path: diff_path,
+        args: diff_cmd,
+    } = match DifferCommand::new(
+        &diff_args,
+        via_process_substitution(minus_file) || via_process_substitution(plus_file),
+        config,
+    ) {
+        Ok(command) => command,
         Err(err) => {
-            eprintln!("Failed to resolve command '{diff_bin}': {err}");
+            eprintln!("{err}");
             return config.error_exit_code;
         }
     };
 
     let diff_process = process::Command::new(&diff_path)
-        .args(diff_cmd)
+        .args(&diff_cmd)
         .args([minus_file, plus_file])
         .stdout(process::Stdio::piped())
         .stderr(process::Stdio::piped())
@@ -96,15 +93,28 @@ pub fn diff(
     }
     let mut diff_process = diff_process.unwrap();
 
-    if let Err(error) = delta::delta(
-        BufReader::new(diff_process.stdout.take().unwrap()).byte_lines(),
-        writer,
-        config,
-    ) {
+    let diff_output = BufReader::new(diff_process.stdout.take().unwrap());
+    let diff_output: Box<dyn BufRead> = match &preprocessed {
+        Some(preprocessed) => match preprocessed.read_with_original_paths(diff_output) {
+            Ok(diff_output) => Box::new(Cursor::new(diff_output)),
+            Err(err) => {
+                eprintln!("Failed to read the output of '{diff_bin}': {err}");
+                let _ = diff_process.wait();
+                return config.error_exit_code;
+            }
+        },
+        None => Box::new(diff_output),
+    };
+
+    if let Err(error) = delta::delta(lines::byte_lines(diff_output), writer, config) {
         match error.kind() {
-            ErrorKind::BrokenPipe => return 0,
+            ErrorKind::BrokenPipe => {
+                let _ = diff_process.wait();
+                return 0;
+            }
             _ => {
                 eprintln!("{error}");
+                let _ = diff_process.wait();
                 return config.error_exit_code;
             }
         }
@@ -137,7 +147,7 @@ pub fn diff(
             format_args!(
                 "{} {} {} {}",
                 diff_path.display(),
-                shell_words::join(diff_cmd),
+                shell_words::join(&diff_cmd),
                 minus_file.display(),
                 plus_file.display()
             )
@@ -148,6 +158,373 @@ pub fn diff(
     }
 }
 
+/// Compare two directories: run the differ on each pair of files with the same path relative to
+/// them, or on a file and /dev/null for a file in only one of them, and display the diffs as one
+/// diff. Files and directories matching --exclude are skipped.
+fn diff_dirs(
+    minus_dir: &Path,
+    plus_dir: &Path,
+    diff_args: &[String],
+    config: &config::Config,
+    writer: &mut dyn Write,
+) -> i32 {
+    let command = match DifferCommand::new(diff_args, false, config) {
+        Ok(command) => command,
+        Err(err) => {
+            eprintln!("{err}");
+            return config.error_exit_code;
+        }
+    };
+    let pairs = match file_pairs(minus_dir, plus_dir, &config.exclude) {
+        Ok(pairs) => pairs,
+        Err(err) => {
+            eprintln!("Failed to read the directories to compare: {err}");
+            return config.error_exit_code;
+        }
+    };
+    let mut diff_output = DirDiffOutput {
+        command: &command,
+        preprocess: config.preprocess.as_deref(),
+        pairs: pairs.into_iter(),
+        current: Cursor::new(Vec::new()),
+        code: 0,
+        error: None,
+    };
+    let result = delta::delta(
+        lines::byte_lines(std::io::BufReader::new(&mut diff_output)),
+        writer,
+        config,
+    );
+    match (result, diff_output.error) {
+        (Err(error), _) if error.kind() == ErrorKind::BrokenPipe => 0,
+        (Err(error), _) => {
+            eprintln!("{error}");
+            config.error_exit_code
+        }
+        (Ok(()), Some(error)) => {
+            eprintln!("{error}");
+            config.error_exit_code
+        }
+        // As with two files, 0 if the directories are the same and 1 if they differ.
+        (Ok(()), None) => diff_output.code,
+    }
+}
+
+/// The diffs of the pairs of files of two directories. The differ is run on a pair when the diff
+/// of the previous one has been read, so that the diffs are displayed as they are computed.
+struct DirDiffOutput<'a> {
+    command: &'a DifferCommand,
+    preprocess: Option<&'a str>,
+    pairs: std::vec::IntoIter<(PathBuf, PathBuf)>,
+    current: Cursor<Vec<u8>>,
+    // The greatest exit code of the differ, and the error ending the diffs, if any.
+    code: i32,
+    error: Option<String>,
+}
+
+impl Read for DirDiffOutput<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        loop {
+            let n = self.current.read(buf)?;
+            if n > 0 || self.error.is_some() {
+                return Ok(n);
+            }
+            let Some((minus_file, plus_file)) = self.pairs.next() else {
+                return Ok(0);
+            };
+            match self.diff_pair(&minus_file, &plus_file) {
+                Ok(diff) => self.current = Cursor::new(diff),
+                Err(error) => self.error = Some(error),
+            }
+        }
+    }
+}
+
+impl DirDiffOutput<'_> {
+    fn diff_pair(&mut self, minus_file: &Path, plus_file: &Path) -> Result<Vec<u8>, String> {
+        let DifferCommand {
+            differ,
+            bin,
+            path,
+            args,
+        } = self.command;
+        let preprocessed = match self.preprocess {
+            Some(command) => Some(PreprocessedFiles::new(command, minus_file, plus_file)?),
+            None => None,
+        };
+        let (minus_path, plus_path) = match &preprocessed {
+            Some(preprocessed) => (
+                preprocessed.minus_file.as_path(),
+                preprocessed.plus_file.as_path(),
+            ),
+            None => (minus_file, plus_file),
+        };
+        let output = process::Command::new(path)
+            .args(args)
+            .args([minus_path, plus_path])
+            .output()
+            .map_err(|err| format!("Failed to execute the command '{bin}': {err}"))?;
+        match output.status.code() {
+            Some(code @ (0 | 1)) => {
+                self.code = self.code.max(code);
+                match &preprocessed {
+                    Some(preprocessed) => preprocessed
+                        .read_with_original_paths(&output.stdout[..])
+                        .map_err(|err| format!("Failed to read the output of '{bin}': {err}")),
+                    None => Ok(output.stdout),
+                }
+            }
+            code => {
+                let stderr = String::from_utf8_lossy(&output.stderr);
+                // As for two files, only the error message of `git diff` for an unknown option,
+                // without its help text.
+                let mut error = match (code, differ) {
+                    (Some(129), Differ::GitDiff) => stderr.lines().next().unwrap_or_default(),
+                    _ => stderr.trim_end(),
+                }
+                .to_string();
+                if !error.is_empty() {
+                    error.push('\n');
+                }
+                error.push_str(&format!(
+                    "'{bin}' process failed with exit status {}. Command was: {} {} {} {}",
+                    code.map_or("none".to_string(), |code| code.to_string()),
+                    path.display(),
+                    shell_words::join(args),
+                    minus_file.display(),
+                    plus_file.display()
+                ));
+                Err(error)
+            }
+        }
+    }
+}
+
+// The pairs of files to compare in the two directories, in the order of their paths.
+fn file_pairs(
+    minus_dir: &Path,
+    plus_dir: &Path,
+    exclude: &[regex::Regex],
+) -> std::io::Result<Vec<(PathBuf, PathBuf)>> {
+    let mut minus_files = BTreeSet::new();
+    let mut plus_files = BTreeSet::new();
+    find_files(minus_dir, Path::new(""), exclude, &mut minus_files)?;
+    find_files(plus_dir, Path::new(""), exclude, &mut plus_files)?;
+    let side = |dir: &Path, files: &BTreeSet<PathBuf>, file: &Path| {
+        if files.contains(file) {
+            dir.join(file)
+        } else {
+            PathBuf::from("/dev/null")
+        }
+    };
+    Ok(minus_files
+        .union(&plus_files)
+        .map(|file| {
+            (
+                side(minus_dir, &minus_files, file),
+                side(plus_dir, &plus_files, file),
+            )
+        })
+        .collect())
+}
+
+// Add the paths, relative to `root`, of the files in the directory `dir` relative to it, and in
+// its subdirectories. Symbolic links are not followed.
+fn find_files(
+    root: &Path,
+    dir: &Path,
+    exclude: &[regex::Regex],
+    files: &mut BTreeSet<PathBuf>,
+) -> std::io::Result<()> {
+    for entry in fs::read_dir(root.join(dir))? {
+        let entry = entry?;
+        let path = dir.join(entry.file_name());
+        let glob_path = path.to_string_lossy().replace('\\', "/");
+        if exclude.iter().any(|regex| regex.is_match(&glob_path)) {
+            continue;
+        }
+        if entry.file_type()?.is_dir() {
+            find_files(root, &path, exclude, files)?;
+        } else {
+            files.insert(path);
+        }
+    }
+    Ok(())
+}
+
+// The arguments of --diff-args.
+fn parse_diff_args(config: &config::Config) -> Result<Vec<String>, String> {
+    let mut diff_args = shell_words::split(config.diff_args.trim())
+        .map_err(|err| format!("Failed to parse diff args: {}: {err}", config.diff_args))?;
+    // Permit e.g. -@U1
+    if diff_args
+        .first()
+        .map(|arg| !arg.is_empty() && !arg.starts_with('-'))
+        .unwrap_or(false)
+    {
+        diff_args[0] = format!("-{}", diff_args[0])
+    }
+    Ok(diff_args)
+}
+
+/// The command diffing two files, which are given after its arguments.
+struct DifferCommand {
+    differ: Differ,
+    // The name of the program, and its path.
+    bin: String,
+    path: PathBuf,
+    args: Vec<String>,
+}
+
+impl DifferCommand {
+    fn new(
+        diff_args: &[String],
+        via_process_substitution: bool,
+        config: &config::Config,
+    ) -> Result<Self, String> {
+        // https://stackoverflow.com/questions/22706714/why-does-git-diff-not-work-with-process-substitution
+        // git <2.42 does not support process substitution
+        let (differ, mut diff_cmd) = match retrieve_git_version() {
+            Some(version) if version >= (2, 42) || !via_process_substitution => (
+                Differ::GitDiff,
+                vec!["git", "diff", "--no-index", "--color"],
+            ),
+            _ => (
+                Differ::Diff,
+                if config.context.is_some() || diff_args_set_unified_context(diff_args) {
+                    vec!["diff"]
+                } else {
+                    vec!["diff", "-U3"]
+                },
+            ),
+        };
+
+        // The options are given before diff-args, which may override them.
+        let options = differ_options(&differ, config);
+        diff_cmd.extend(options.iter().map(String::as_str));
+        diff_cmd.extend(
+            diff_args
+                .iter()
+                .filter(|s| !s.is_empty())
+                .map(String::as_str),
+        );
+        diff_cmd.push("--");
+
+        let (diff_bin, diff_cmd) = diff_cmd.split_first().unwrap();
+        let path = grep_cli::resolve_binary(PathBuf::from(diff_bin))
+            .map_err(|err| format!("Failed to resolve command '{diff_bin}': {err}"))?;
+        Ok(Self {
+            differ,
+            bin: diff_bin.to_string(),
+            path,
+            args: diff_cmd.iter().map(|arg| arg.to_string()).collect(),
+        })
+    }
+}
+
+/// The files to diff, normalized by the --preprocess command, in a temporary directory which is
+/// removed when this is dropped.
+struct PreprocessedFiles {
+    dir: PathBuf,
+    minus_file: PathBuf,
+    plus_file: PathBuf,
+    // Each path in the temporary directory, and the path of the file it was made from.
+    original_paths: Vec<(PathBuf, PathBuf)>,
+}
+
+impl PreprocessedFiles {
+    fn new(command: &str, minus_file: &Path, plus_file: &Path) -> Result<Self, String> {
+        let dir = new_private_temp_dir("delta-preprocess")
+            .map_err(|err| format!("Failed to create a temporary directory: {err}"))?;
+        let mut preprocessed = Self {
+            dir,
+            minus_file: minus_file.into(),
+            plus_file: plus_file.into(),
+            original_paths: Vec::new(),
+        };
+        for (side, file) in [("minus", minus_file), ("plus", plus_file)] {
+            // An absent file is displayed as added or removed, rather than as an empty file.
+            if file == Path::new("/dev/null") {
+                continue;
+            }
+            let name = file.file_name().unwrap_or(side.as_ref());
+            let path = preprocessed.dir.join(side).join(name);
+            fs::create_dir_all(path.parent().unwrap())
+                .and_then(|_| fs::write(&path, preprocess(command, file)?))
+                .map_err(|err| format!("Failed to preprocess {}: {err}", file.display()))?;
+            preprocessed
+                .original_paths
+                .push((path.clone(), file.into()));
+            match side {
+                "minus" => preprocessed.minus_file = path,
+                _ => preprocessed.plus_file = path,
+            }
+        }
+        Ok(preprocessed)
+    }
+
+    /// Read the diff of the preprocessed files, replacing their paths by those of the original
+    /// files.
+    fn read_with_original_paths(&self, mut diff_output: impl Read) -> std::io::Result<Vec<u8>> {
+        let mut output = Vec::new();
+        diff_output.read_to_end(&mut output)?;
+        for (path, original_path) in &self.original_paths {
+            // git displays absolute paths without the leading slash, e.g. "a/tmp/file".
+            let trim = |path: &Path| path.to_string_lossy().trim_start_matches('/').to_string();
+            let regex = Regex::new(&regex::escape(&trim(path))).unwrap();
+            output = regex
+                .replace_all(&output, NoExpand(trim(original_path).as_bytes()))
+                .into_owned();
+        }
+        Ok(output)
+    }
+}
+
+impl Drop for PreprocessedFiles {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.dir);
+    }
+}
+
+/// The output of `command` with the contents of `file` as its standard input.
+fn preprocess(command: &str, file: &Path) -> std::io::Result<Vec<u8>> {
+    let words = shell_words::split(command)
+        .map_err(|err| std::io::Error::new(ErrorKind::InvalidInput, err))?;
+    let (program, args) = words.split_first().ok_or_else(|| {
+        std::io::Error::new(ErrorKind::InvalidInput, "the preprocess command is empty")
+    })?;
+    let output = process::Command::new(program)
+        .args(args)
+        .stdin(File::open(file)?)
+        .stderr(process::Stdio::inherit())
+        .output()?;
+    if !output.status.success() {
+        return Err(std::io::Error::other(format!(
+            "'{command}' failed with {}",
+            output.status
+        )));
+    }
+    Ok(output.stdout)
+}
+
+/// The arguments of the differ for the diff-algorithm, context and ignore-whitespace options.
+fn differ_options(differ: &Differ, config: &config::Config) -> Vec<String> {
+    let mut options = Vec::new();
+    match (differ, config.diff_algorithm.as_deref()) {
+        (Differ::GitDiff, Some(algorithm)) => options.push(format!("--diff-algorithm={algorithm}")),
+        // `diff` has no other algorithm than its own.
+        (Differ::Diff, Some("minimal")) => options.push("--minimal".to_string()),
+        _ => {}
+    }
+    if let Some(context) = config.context {
+        options.push(format!("-U{context}"));
+    }
+    if config.ignore_whitespace {
+        options.push("-w".to_string());
+    }
+    options
+}
+
 /// Do the user-supplied `diff` args set the unified context?
 fn diff_args_set_unified_context<I, S>(args: I) -> bool
 where
@@ -175,7 +552,9 @@ mod main_tests {
     use std::ffi::OsString;
     use std::io::Cursor;
 
-    use super::diff_args_set_unified_context;
+    use super::{diff_args_set_unified_context, differ_options, Differ};
+    use crate::tests::integration_test_utils;
+    use crate::tests::test_utils::temp_path;
 
     use rstest::rstest;
 
@@ -195,6 +574,24 @@ mod main_tests {
         assert_eq!(diff_args_set_unified_context(diff_args), expected)
     }
 
+    #[test]
+    fn test_differ_options() {
+        let config = integration_test_utils::make_config_from_args(&[
+            "--diff-algorithm=patience",
+            "--context=7",
+            "--ignore-whitespace",
+        ]);
+        assert_eq!(
+            differ_options(&Differ::GitDiff, &config),
+            vec!["--diff-algorithm=patience", "-U7", "-w"]
+        );
+        assert_eq!(differ_options(&Differ::Diff, &config), vec!["-U7", "-w"]);
+        let config = integration_test_utils::make_config_from_args(&["--diff-algorithm=minimal"]);
+        assert_eq!(differ_options(&Differ::Diff, &config), vec!["--minimal"]);
+        let config = integration_test_utils::make_config_from_args(&[]);
+        assert!(differ_options(&Differ::GitDiff, &config).is_empty());
+    }
+
     enum ExpectDiff {
         Yes,
         No,
@@ -234,4 +631,84 @@ mod main_tests {
             }
         );
     }
+
+    #[cfg(not(target_os = "windows"))]
+    #[test]
+    fn test_diff_preprocess() {
+        let dir = temp_path("test_diff_preprocess");
+        std::fs::create_dir_all(&dir).unwrap();
+        let (file_a, file_b) = (dir.join("a.txt"), dir.join("b.txt"));
+        std::fs::write(&file_a, "x = 1\ny = 2\n").unwrap();
+        std::fs::write(&file_b, "x=1\ny=2\n").unwrap();
+        let run = |file_b: &std::path::Path| {
+            let mut writer = Cursor::new(vec![]);
+            let args = [
+                OsString::from(&file_a),
+                OsString::from(file_b),
+                OsString::from("--preprocess=tr -d ' '"),
+            ];
+            let exit_code = crate::run_app(args.to_vec(), Some(&mut writer)).unwrap();
+            (exit_code, String::from_utf8(writer.into_inner()).unwrap())
+        };
+        // The files differ only in the whitespace removed by the preprocess command.
+        assert_eq!(run(&file_b), (0, "".to_string()));
+
+        std::fs::write(&file_b, "x=1\ny=3\n").unwrap();
+        let (exit_code, output) = run(&file_b);
+        assert_eq!(exit_code, 1);
+        let output = crate::ansi::strip_ansi_codes(&output);
+        assert!(output.contains(&format!("{} ⟶", file_a.display())));
+        assert!(output.contains("\ny=2\ny=3\n"));
+        assert!(!output.contains("delta-preprocess"));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[test]
+    fn test_diff_dirs() {
+        let dir = temp_path("test_diff_dirs");
+        let _ = std::fs::remove_dir_all(&dir);
+        let (dir_a, dir_b) = (dir.join("a"), dir.join("b"));
+        for (path, contents) in [
+            ("a/x.txt", "1\n"),
+            ("b/x.txt", "2\n"),
+            ("a/sub/same.txt", "same\n"),
+            ("b/sub/same.txt", "same\n"),
+            ("a/sub/removed.txt", "removed\n"),
+            ("b/sub/added.txt", "added\n"),
+            ("b/target/build.log", "excluded\n"),
+            ("b/excluded.log", "excluded\n"),
+        ] {
+            let path = dir.join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, contents).unwrap();
+        }
+        let run = |dir_b: &std::path::Path| {
+            let mut writer = Cursor::new(vec![]);
+            let args = [
+                OsString::from(&dir_a),
+                OsString::from(dir_b),
+                OsString::from("--exclude=*.log, target/"),
+            ];
+            let exit_code = crate::run_app(args.to_vec(), Some(&mut writer)).unwrap();
+            let output = String::from_utf8(writer.into_inner()).unwrap();
+            (exit_code, crate::ansi::strip_ansi_codes(&output))
+        };
+        let (exit_code, output) = run(&dir_b);
+        assert_eq!(exit_code, 1);
+        let headers: Vec<&str> = output.lines().filter(|line| line.contains("txt")).collect();
+        let (a, b) = (dir_a.display(), dir_b.display());
+        assert_eq!(
+            headers,
+            vec![
+                format!("added: {b}/sub/added.txt"),
+                format!("removed: {a}/sub/removed.txt"),
+                format!("{a}/x.txt ⟶   {b}/x.txt"),
+            ]
+        );
+        assert!(!output.contains("excluded"));
+
+        assert_eq!(run(&dir_a), (0, "".to_string()));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
 }
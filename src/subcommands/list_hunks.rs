@@ -0,0 +1,283 @@
+use std::borrow::Cow;
+use std::cell::Cell;
+use std::io::{self, Write};
+use std::rc::Rc;
+
+use serde::Serialize;
+
+use crate::config::Config;
+use crate::delta::StateMachine;
+use crate::utils::lines;
+
+/// The location of a hunk, both in the diff and in delta's rendering of it.
+#[derive(Debug, PartialEq, Eq, Serialize)]
+pub struct HunkRecord {
+    pub path: String,
+    pub old_start: usize,
+    pub old_count: usize,
+    pub new_start: usize,
+    pub new_count: usize,
+    /// Byte offset of the hunk header in the rendered output.
+    pub offset: usize,
+    /// 1-based line number of the hunk header in the rendered output.
+    pub line: usize,
+    /// The command applying the hunk alone (--hunk-apply-commands).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub apply_command: Option<String>,
+}
+
+/// Collects a [`HunkRecord`] for every hunk rendered by the state machine.
+pub struct HunkIndex {
+    position: Rc<Cell<(usize, usize)>>,
+    pub records: Vec<HunkRecord>,
+}
+
+impl HunkIndex {
+    pub fn record(&mut self, path: &str, line_numbers_and_hunk_lengths: &[(usize, usize)]) {
+        // In a combined diff, the first range is that of the first parent.
+        let (old_start, old_count) = line_numbers_and_hunk_lengths
+            .first()
+            .copied()
+            .unwrap_or_default();
+        let (new_start, new_count) = line_numbers_and_hunk_lengths
+            .last()
+            .copied()
+            .unwrap_or_default();
+        let (offset, lines) = self.position.get();
+        self.records.push(HunkRecord {
+            path: path.to_string(),
+            old_start,
+            old_count,
+            new_start,
+            new_count,
+            offset,
+            line: lines + 1,
+            apply_command: None,
+        });
+    }
+}
+
+/// A writer which keeps track of the number of bytes and lines written through it.
+struct PositionTrackingWriter<W: Write> {
+    inner: W,
+    position: Rc<Cell<(usize, usize)>>,
+}
+
+impl<W: Write> Write for PositionTrackingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        let (bytes, lines) = self.position.get();
+        let newlines = buf[..n].iter().filter(|b| **b == b'\n').count();
+        self.position.set((bytes + n, lines + newlines));
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Render the diff on stdin without displaying it, and write the location of each hunk, as
+/// tab-separated values or JSON lines.
+#[cfg(not(tarpaulin_include))]
+pub fn list_hunks(format: &str, config: &Config, writer: &mut dyn Write) -> io::Result<()> {
+    let records = index_hunks(io::stdin().lock(), config)?;
+    write_hunk_records(&records, format, writer)
+}
+
+pub fn index_hunks<R: io::BufRead>(input: R, config: &Config) -> io::Result<Vec<HunkRecord>> {
+    let position = Rc::new(Cell::new((0, 0)));
+    let mut rendered = PositionTrackingWriter {
+        inner: io::sink(),
+        position: Rc::clone(&position),
+    };
+    let mut state_machine = StateMachine::new(&mut rendered, config);
+    state_machine.hunk_index = Some(HunkIndex {
+        position,
+        records: Vec::new(),
+    });
+    state_machine.consume(lines::byte_lines(input))?;
+    Ok(state_machine
+        .hunk_index
+        .take()
+        .map(|index| index.records)
+        .unwrap_or_default())
+}
+
+pub fn write_hunk_records(
+    records: &[HunkRecord],
+    format: &str,
+    writer: &mut dyn Write,
+) -> io::Result<()> {
+    for record in records {
+        if format == "json" {
+            writeln!(writer, "{}", serde_json::to_string(record)?)?;
+        } else {
+            write!(
+                writer,
+                "{}\t{}\t{}\t{}\t{}\t{}\t{}",
+                escape_tsv_value(&record.path),
+                record.old_start,
+                record.old_count,
+                record.new_start,
+                record.new_count,
+                record.offset,
+                record.line,
+            )?;
+            match &record.apply_command {
+                Some(command) => writeln!(writer, "\t{}", escape_tsv_value(command))?,
+                None => writeln!(writer)?,
+            }
+        }
+    }
+    Ok(())
+}
+
+// Escape the characters which cannot appear in a value of tab-separated values, as in the
+// "linear TSV" format.
+fn escape_tsv_value(value: &str) -> Cow<'_, str> {
+    if !value.contains(['\t', '\n', '\r', '\\']) {
+        return Cow::Borrowed(value);
+    }
+    let mut escaped = String::with_capacity(value.len() + 2);
+    for c in value.chars() {
+        match c {
+            '\t' => escaped.push_str("\\t"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\\' => escaped.push_str("\\\\"),
+            c => escaped.push(c),
+        }
+    }
+    Cow::Owned(escaped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ansi;
+    use crate::tests::integration_test_utils::make_config_from_args;
+
+    const DIFF: &str = "\
+diff --git a/src/a.rs b/src/a.rs
+index 1111111..2222222 100644
+--- a/src/a.rs
++++ b/src/a.rs
+@@ -1,2 +1,2 @@ fn main() {
+-    let a = 1;
++    let a = 2;
+     println!();
+@@ -10 +10,2 @@
+ x
++y
+diff --git a/b.txt b/b.txt
+deleted file mode 100644
+index 3333333..0000000
+--- a/b.txt
++++ /dev/null
+@@ -1 +0,0 @@
+-gone
+";
+
+    #[test]
+    fn test_index_hunks() {
+        let config = make_config_from_args(&[]);
+        let records = index_hunks(DIFF.as_bytes(), &config).unwrap();
+        let summary: Vec<_> = records
+            .iter()
+            .map(|r| {
+                (
+                    r.path.as_str(),
+                    r.old_start,
+                    r.old_count,
+                    r.new_start,
+                    r.new_count,
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("src/a.rs", 1, 2, 1, 2),
+                ("src/a.rs", 10, 1, 10, 2),
+                ("b.txt", 1, 1, 0, 0),
+            ]
+        );
+        assert!(records[0].offset > 0);
+        assert!(records[0].offset < records[1].offset);
+        assert!(records[1].offset < records[2].offset);
+        assert!(records[0].line < records[1].line);
+    }
+
+    #[test]
+    fn test_offsets_point_to_hunk_headers() {
+        let config = make_config_from_args(&["--hunk-header-decoration-style", "none"]);
+        let records = index_hunks(DIFF.as_bytes(), &config).unwrap();
+
+        let mut rendered = Vec::new();
+        crate::delta::delta(lines::byte_lines(DIFF.as_bytes()), &mut rendered, &config).unwrap();
+        let rendered = String::from_utf8(rendered).unwrap();
+        let hunk_headers: Vec<_> = records
+            .iter()
+            .map(|record| {
+                assert_eq!(
+                    rendered[..record.offset].matches('\n').count() + 1,
+                    record.line
+                );
+                // The hunk header is preceded by an empty line.
+                let header = rendered[record.offset..].lines().nth(1).unwrap();
+                ansi::strip_ansi_codes(header)
+            })
+            .collect();
+        assert_eq!(hunk_headers, vec!["1: fn main() { ", "10: ", "0: "]);
+    }
+
+    #[test]
+    fn test_write_hunk_records() {
+        let records = vec![HunkRecord {
+            path: "src/a.rs".into(),
+            old_start: 1,
+            old_count: 2,
+            new_start: 1,
+            new_count: 3,
+            offset: 120,
+            line: 5,
+            apply_command: None,
+        }];
+        let mut tsv = Vec::new();
+        write_hunk_records(&records, "tsv", &mut tsv).unwrap();
+        assert_eq!(
+            String::from_utf8(tsv).unwrap(),
+            "src/a.rs\t1\t2\t1\t3\t120\t5\n"
+        );
+
+        let mut json = Vec::new();
+        write_hunk_records(&records, "json", &mut json).unwrap();
+        assert_eq!(
+            String::from_utf8(json).unwrap(),
+            r#"{"path":"src/a.rs","old_start":1,"old_count":2,"new_start":1,"new_count":3,"offset":120,"line":5}"#
+                .to_string()
+                + "\n"
+        );
+    }
+
+    #[test]
+    fn test_write_hunk_records_escapes_paths() {
+        let records = vec![HunkRecord {
+            path: "a\tb\nc\\d.txt".into(),
+            old_start: 1,
+            old_count: 1,
+            new_start: 1,
+            new_count: 1,
+            offset: 0,
+            line: 1,
+            apply_command: None,
+        }];
+        let mut tsv = Vec::new();
+        write_hunk_records(&records, "tsv", &mut tsv).unwrap();
+        assert_eq!(
+            String::from_utf8(tsv).unwrap(),
+            "a\\tb\\nc\\\\d.txt\t1\t1\t1\t1\t0\t1\n"
+        );
+    }
+}
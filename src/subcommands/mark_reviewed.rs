@@ -0,0 +1,101 @@
+use std::fs;
+use std::io::Write;
+use std::path::{Component, Path};
+
+use crate::errors::*;
+use crate::features::review_bookmarks::{self, ReviewBookmarks};
+
+/// Record the file as reviewed, with its current contents and the commit checked out in the
+/// repository (--mark-reviewed).
+pub fn mark_reviewed(file: &str, repo: &git2::Repository, writer: &mut dyn Write) -> Result<()> {
+    let workdir = repo
+        .workdir()
+        .ok_or_else(|| anyhow!("The repository has no working tree."))?;
+    let absolute_path =
+        fs::canonicalize(file).with_context(|| format!("Could not find the file {file}."))?;
+    let path = repo_relative_path(&absolute_path, &fs::canonicalize(workdir)?)
+        .ok_or_else(|| anyhow!("{file} is not in the repository."))?;
+    let blob = repo.blob_path(&absolute_path)?;
+    let commit = repo
+        .head()
+        .and_then(|head| head.peel_to_commit())
+        .context("The repository has no commit checked out.")?
+        .id();
+
+    let state_file = review_bookmarks::state_file(repo);
+    let mut bookmarks = ReviewBookmarks::read(&state_file);
+    bookmarks.mark(&path, &blob.to_string(), &commit.to_string());
+    bookmarks
+        .write(&state_file)
+        .with_context(|| format!("Could not write {}.", state_file.display()))?;
+    writeln!(writer, "✓ {path} reviewed at {}", &commit.to_string()[..7])?;
+    Ok(())
+}
+
+// The path of the file relative to the root of the repository, with '/' as separator as in diffs.
+fn repo_relative_path(path: &Path, workdir: &Path) -> Option<String> {
+    let components: Option<Vec<&str>> = path
+        .strip_prefix(workdir)
+        .ok()?
+        .components()
+        .map(|component| match component {
+            Component::Normal(name) => name.to_str(),
+            _ => None,
+        })
+        .collect();
+    components.map(|components| components.join("/"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::test_utils::temp_path;
+
+    #[test]
+    fn test_repo_relative_path() {
+        assert_eq!(
+            repo_relative_path(Path::new("/repo/src/lib.rs"), Path::new("/repo")),
+            Some("src/lib.rs".to_string())
+        );
+        assert_eq!(
+            repo_relative_path(Path::new("/other/lib.rs"), Path::new("/repo")),
+            None
+        );
+    }
+
+    #[test]
+    fn test_mark_reviewed() {
+        let dir = temp_path("test_mark_reviewed");
+        let _ = fs::remove_dir_all(&dir);
+        let repo = git2::Repository::init(&dir).unwrap();
+        fs::create_dir(dir.join("src")).unwrap();
+        fs::write(dir.join("src/a.rs"), "").unwrap();
+        let signature = git2::Signature::now("Delta", "delta@example.com").unwrap();
+        let tree = repo.find_tree(repo.index().unwrap().write_tree().unwrap());
+        let commit = repo
+            .commit(
+                Some("HEAD"),
+                &signature,
+                &signature,
+                "Initial",
+                &tree.unwrap(),
+                &[],
+            )
+            .unwrap();
+
+        let mut output = Vec::new();
+        let file = dir.join("src/a.rs");
+        mark_reviewed(file.to_str().unwrap(), &repo, &mut output).unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            format!("✓ src/a.rs reviewed at {}\n", &commit.to_string()[..7])
+        );
+        // The blob of the empty file.
+        let bookmarks = ReviewBookmarks::read(&review_bookmarks::state_file(&repo));
+        assert_eq!(
+            bookmarks.reviewed_at("src/a.rs", "e69de29"),
+            Some(commit.to_string().as_str())
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,9 +1,21 @@
+pub mod check_contrast;
 pub mod diff;
 pub mod generate_completion;
+pub mod list_hunks;
 pub mod list_syntax_themes;
+pub mod mark_reviewed;
 pub mod parse_ansi;
+pub mod pick;
+pub mod pick_theme;
+pub mod render_html;
 mod sample_diff;
+#[cfg(feature = "self-update")]
+pub mod self_update;
+pub mod setup_git;
 pub mod show_colors;
 pub mod show_config;
 pub mod show_syntax_themes;
 pub mod show_themes;
+pub mod split_output;
+pub mod stats;
+pub mod watch;
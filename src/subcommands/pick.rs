@@ -0,0 +1,348 @@
+use std::ffi::OsString;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use crate::ansi;
+use crate::env::DeltaEnv;
+use crate::errors::*;
+use crate::utils::private_dir::new_private_temp_dir;
+
+/// A part of the input diff which can be picked: a file, or a hunk with its file's header.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Section {
+    pub label: String,
+    pub text: String,
+}
+
+/// Let the user choose files (or hunks) of the diff on stdin with an interactive picker, such as
+/// fzf, which previews each of them as rendered by delta. Return the chosen parts of the input,
+/// or None if nothing was chosen.
+#[cfg(not(tarpaulin_include))]
+pub fn pick(mode: &str, args: &[OsString], env: &DeltaEnv) -> Result<Option<String>> {
+    let mut input = String::new();
+    io::stdin().read_to_string(&mut input)?;
+    let sections = split_into_sections(&input, mode == "hunk");
+    if sections.is_empty() {
+        return Ok(None);
+    }
+
+    let dir = new_private_temp_dir("delta-pick")?;
+    let result = run_picker(&sections, &dir, args, env);
+    let _ = fs::remove_dir_all(&dir);
+    let chosen = result?;
+
+    if chosen.is_empty() {
+        Ok(None)
+    } else {
+        Ok(Some(chosen.iter().map(|&i| &*sections[i].text).collect()))
+    }
+}
+
+fn run_picker(
+    sections: &[Section],
+    dir: &Path,
+    args: &[OsString],
+    env: &DeltaEnv,
+) -> Result<Vec<usize>> {
+    for (i, section) in sections.iter().enumerate() {
+        fs::write(section_path(dir, i), &section.text)?;
+    }
+    let picker = env.picker.as_deref().unwrap_or("fzf");
+    let mut picker_words = shell_words::split(picker).context("Could not parse picker command.")?;
+    if picker_words.is_empty() {
+        return Err(anyhow!("DELTA_PICKER is empty."));
+    }
+    let picker_name = picker_words.remove(0);
+    let mut command = Command::new(&picker_name);
+    command.args(&picker_words);
+    if is_fzf_compatible(&picker_name) {
+        command.args(fzf_args(&preview_command(dir, args)?));
+    }
+    let mut process = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .with_context(|| format!("Could not run picker {picker_name:?}."))?;
+
+    let mut stdin = process.stdin.take().unwrap();
+    for (i, section) in sections.iter().enumerate() {
+        writeln!(stdin, "{i}\t{}", section.label)?;
+    }
+    drop(stdin);
+
+    let output = process.wait_with_output()?;
+    // fzf exits with 1 if there was no match, and 130 if it was cancelled.
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| line.split('\t').next()?.parse().ok())
+        .filter(|i| *i < sections.len())
+        .collect())
+}
+
+pub(super) fn is_fzf_compatible(picker_name: &str) -> bool {
+    matches!(
+        Path::new(picker_name).file_stem().and_then(|s| s.to_str()),
+        Some("fzf") | Some("sk")
+    )
+}
+
+fn fzf_args(preview_command: &str) -> Vec<String> {
+    vec![
+        "--multi".into(),
+        "--ansi".into(),
+        "--delimiter=\t".into(),
+        "--with-nth=2..".into(),
+        format!("--preview={preview_command}"),
+        "--preview-window=right,70%".into(),
+    ]
+}
+
+/// The shell command which renders section `{1}` with the current delta executable and the
+/// options delta was called with.
+fn preview_command(dir: &Path, args: &[OsString]) -> Result<String> {
+    let delta = std::env::current_exe()?;
+    let mut words = vec![delta.to_string_lossy().into_owned()];
+    words.extend(preview_args(args));
+    words.push("--paging=never".into());
+    Ok(format!(
+        "{} --width=${{FZF_PREVIEW_COLUMNS:-80}} < {}/{{1}}.diff",
+        shell_words::join(words),
+        shell_words::quote(&dir.to_string_lossy())
+    ))
+}
+
+/// The command line arguments of delta, without the executable and the --pick option.
+fn preview_args(args: &[OsString]) -> Vec<String> {
+    let mut preview_args = Vec::new();
+    let mut args = args.iter().skip(1).map(|arg| arg.to_string_lossy());
+    while let Some(arg) = args.next() {
+        if arg == "--pick" {
+            let mut rest = args.clone();
+            if matches!(rest.next().as_deref(), Some("file") | Some("hunk")) {
+                args = rest;
+            }
+        } else if !arg.starts_with("--pick=") {
+            preview_args.push(arg.into_owned());
+        }
+    }
+    preview_args
+}
+
+fn section_path(dir: &Path, i: usize) -> PathBuf {
+    dir.join(format!("{i}.diff"))
+}
+
+/// Split a diff into one section per file, or per hunk. Commit metadata (e.g. in the output of
+/// `git log -p`) is dropped, and each hunk section starts with the header of its file.
+pub fn split_into_sections(input: &str, per_hunk: bool) -> Vec<Section> {
+    let mut files: Vec<(Vec<&str>, Vec<Vec<&str>>)> = Vec::new();
+    // Whether the current line belongs to the last file, rather than to commit metadata.
+    let mut in_file = false;
+    for line in input.lines() {
+        let stripped = ansi::strip_ansi_codes(line);
+        if stripped.starts_with("diff ") {
+            files.push((vec![line], Vec::new()));
+            in_file = true;
+        } else if stripped.starts_with("commit ") {
+            // The next commit: nothing more belongs to this file.
+            in_file = false;
+        } else if let (true, Some((header, hunks))) = (in_file, files.last_mut()) {
+            if stripped.starts_with("@@") {
+                hunks.push(vec![line]);
+            } else if let Some(hunk) = hunks.last_mut() {
+                hunk.push(line);
+            } else {
+                header.push(line);
+            }
+        }
+    }
+
+    let join = |lines: &[&str]| {
+        lines
+            .iter()
+            .map(|line| format!("{line}\n"))
+            .collect::<String>()
+    };
+    let mut sections = Vec::new();
+    for (header, hunks) in &files {
+        let path = file_path(header);
+        if per_hunk && !hunks.is_empty() {
+            for hunk in hunks {
+                sections.push(Section {
+                    label: format!("{path}\t{}", ansi::strip_ansi_codes(hunk[0])),
+                    text: join(header) + &join(hunk),
+                });
+            }
+        } else {
+            sections.push(Section {
+                label: path,
+                text: join(header) + &hunks.iter().map(|hunk| join(hunk)).collect::<String>(),
+            });
+        }
+    }
+    sections
+}
+
+fn file_path(header: &[&str]) -> String {
+    let lines: Vec<_> = header.iter().map(|l| ansi::strip_ansi_codes(l)).collect();
+    let find = |prefix: &str| {
+        lines
+            .iter()
+            .find_map(|line| line.strip_prefix(prefix))
+            .map(|path| path.trim_end_matches('\t').to_string())
+    };
+    let plus_path = find("+++ ").filter(|path| path != "/dev/null");
+    plus_path
+        .map(|path| path.strip_prefix("b/").map(String::from).unwrap_or(path))
+        .or_else(|| {
+            find("--- ").map(|path| path.strip_prefix("a/").map(String::from).unwrap_or(path))
+        })
+        .or_else(|| find("rename to "))
+        .or_else(|| find("diff --git ").or_else(|| find("diff ")))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DIFF: &str = "\
+commit 1111111111111111111111111111111111111111
+Author: A <a@example.com>
+
+    Subject
+
+diff --git a/src/a.rs b/src/a.rs
+index 1111111..2222222 100644
+--- a/src/a.rs
++++ b/src/a.rs
+@@ -1,2 +1,2 @@ fn main() {
+-    let a = 1;
++    let a = 2;
+@@ -10 +10,2 @@
+ x
++y
+diff --git a/b.txt b/b.txt
+deleted file mode 100644
+index 3333333..0000000
+--- a/b.txt
++++ /dev/null
+@@ -1 +0,0 @@
+-gone
+diff --git a/old.txt b/new.txt
+similarity index 100%
+rename from old.txt
+rename to new.txt
+";
+
+    #[test]
+    fn test_split_into_file_sections() {
+        let sections = split_into_sections(DIFF, false);
+        let labels: Vec<_> = sections.iter().map(|s| s.label.as_str()).collect();
+        assert_eq!(labels, vec!["src/a.rs", "b.txt", "new.txt"]);
+        assert!(sections[0]
+            .text
+            .starts_with("diff --git a/src/a.rs b/src/a.rs\n"));
+        assert!(sections[0].text.ends_with("+y\n"));
+        assert!(sections[1].text.ends_with("-gone\n"));
+        assert!(!sections.iter().any(|s| s.text.contains("Subject")));
+    }
+
+    #[test]
+    fn test_split_into_hunk_sections() {
+        let sections = split_into_sections(DIFF, true);
+        let labels: Vec<_> = sections.iter().map(|s| s.label.as_str()).collect();
+        assert_eq!(
+            labels,
+            vec![
+                "src/a.rs\t@@ -1,2 +1,2 @@ fn main() {",
+                "src/a.rs\t@@ -10 +10,2 @@",
+                "b.txt\t@@ -1 +0,0 @@",
+                "new.txt",
+            ]
+        );
+        assert_eq!(
+            sections[1].text,
+            "\
+diff --git a/src/a.rs b/src/a.rs
+index 1111111..2222222 100644
+--- a/src/a.rs
++++ b/src/a.rs
+@@ -10 +10,2 @@
+ x
++y
+"
+        );
+    }
+
+    #[test]
+    fn test_split_into_sections_of_commits() {
+        let input = format!(
+            "{DIFF}\
+commit 2222222222222222222222222222222222222222
+Author: B <b@example.com>
+Date:   Mon Jan 1 00:00:00 2024 +0000
+
+    Another subject
+
+diff --git a/c.txt b/c.txt
+index 4444444..5555555 100644
+--- a/c.txt
++++ b/c.txt
+@@ -1 +1 @@
+-c
++C
+"
+        );
+        let sections = split_into_sections(&input, false);
+        let labels: Vec<_> = sections.iter().map(|s| s.label.as_str()).collect();
+        assert_eq!(labels, vec!["src/a.rs", "b.txt", "new.txt", "c.txt"]);
+        assert_eq!(
+            sections[2].text,
+            DIFF[DIFF.find("diff --git a/old.txt").unwrap()..]
+        );
+        assert!(!sections
+            .iter()
+            .any(|s| s.text.contains("Author") || s.text.contains("subject")));
+
+        let sections = split_into_sections(&input, true);
+        assert_eq!(sections.len(), 5);
+        assert_eq!(sections[4].label, "c.txt\t@@ -1 +1 @@");
+    }
+
+    #[test]
+    fn test_split_into_sections_with_color() {
+        let input = "\x1b[1mdiff --git a/x b/x\x1b[m\n\x1b[1m--- a/x\x1b[m\n\x1b[1m+++ b/x\x1b[m\n\x1b[36m@@ -1 +1 @@\x1b[m\n\x1b[31m-a\x1b[m\n\x1b[32m+b\x1b[m\n";
+        let sections = split_into_sections(input, true);
+        assert_eq!(sections.len(), 1);
+        assert_eq!(sections[0].label, "x\t@@ -1 +1 @@");
+        assert_eq!(sections[0].text, input);
+    }
+
+    #[test]
+    fn test_split_into_sections_without_diff() {
+        assert!(split_into_sections("not a diff\n", false).is_empty());
+    }
+
+    #[test]
+    fn test_preview_args() {
+        let args = |args: &[&str]| args.iter().map(OsString::from).collect::<Vec<_>>();
+        assert_eq!(
+            preview_args(&args(&["delta", "--pick", "-s", "--pick=hunk"])),
+            vec!["-s"]
+        );
+        assert_eq!(
+            preview_args(&args(&["delta", "--pick", "hunk", "--light"])),
+            vec!["--light"]
+        );
+    }
+
+    #[test]
+    fn test_is_fzf_compatible() {
+        assert!(is_fzf_compatible("fzf"));
+        assert!(is_fzf_compatible("/usr/local/bin/sk"));
+        assert!(!is_fzf_compatible("peco"));
+    }
+}
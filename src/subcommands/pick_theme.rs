@@ -0,0 +1,255 @@
+use std::ffi::OsString;
+use std::fs;
+use std::io::{self, IsTerminal, Read, Write};
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use itertools::Itertools;
+
+use super::sample_diff::DIFF;
+use super::setup_git::global_gitconfig_path;
+use crate::env::DeltaEnv;
+use crate::errors::*;
+use crate::git_config::GitConfig;
+use crate::options::get::get_themes;
+use crate::utils;
+use crate::utils::private_dir::new_private_temp_dir;
+
+// The builtin features which set the colors of delta.
+const BUILTIN_COLOR_FEATURES: &[&str] = &["diff-highlight", "diff-so-fancy", "github"];
+
+/// A theme which can be picked: the name of the option setting it, and its name.
+#[derive(Debug, PartialEq, Eq)]
+struct Choice {
+    option: &'static str,
+    name: String,
+}
+
+/// Let the user choose a syntax theme, or a feature setting delta's colors, with an interactive
+/// picker which previews the diff on stdin (or a sample diff) rendered with the selected theme.
+/// The chosen theme is written to the user's global gitconfig.
+#[cfg(not(tarpaulin_include))]
+pub fn pick_theme(args: &[OsString], env: &DeltaEnv, writer: &mut dyn Write) -> Result<()> {
+    let mut input = Vec::new();
+    if !io::stdin().is_terminal() {
+        io::stdin().read_to_end(&mut input)?;
+    }
+    if input.is_empty() {
+        input = DIFF.to_vec();
+    }
+    let git_config = GitConfig::try_create(env);
+    let feature_themes = match git_config {
+        Some(git_config) => get_themes(Some(git_config)),
+        None => Vec::new(),
+    };
+    let syntax_themes = utils::bat::assets::load_highlighting_assets()
+        .themes()
+        .map(String::from)
+        .collect::<Vec<_>>();
+    let choices = make_choices(&syntax_themes, &feature_themes);
+
+    let dir = new_private_temp_dir("delta-pick-theme")?;
+    let result = run_picker(&choices, &input, &dir, args, env);
+    let _ = fs::remove_dir_all(&dir);
+    let choice = match result? {
+        Some(choice) => choice,
+        None => return Ok(()),
+    };
+
+    let path = global_gitconfig_path()?;
+    let mut config =
+        git2::Config::open(&path).with_context(|| format!("Could not open {}.", path.display()))?;
+    let key = format!("delta.{}", choice.option);
+    let previous_value = config.get_string(&key).ok();
+    let value = match choice.option {
+        "features" => updated_features(previous_value.as_deref(), &choice.name, &choices),
+        _ => choice.name,
+    };
+    config
+        .set_str(&key, &value)
+        .with_context(|| format!("Could not set {key} in {}.", path.display()))?;
+    match previous_value {
+        Some(previous_value) => writeln!(
+            writer,
+            "Set {key} = {value} in {} (was: {previous_value})",
+            path.display()
+        )?,
+        None => writeln!(writer, "Set {key} = {value} in {}", path.display())?,
+    }
+    Ok(())
+}
+
+fn make_choices(syntax_themes: &[String], feature_themes: &[String]) -> Vec<Choice> {
+    let features = BUILTIN_COLOR_FEATURES
+        .iter()
+        .map(|name| name.to_string())
+        .chain(feature_themes.iter().cloned());
+    features
+        .map(|name| Choice {
+            option: "features",
+            name,
+        })
+        .chain(syntax_themes.iter().map(|name| Choice {
+            option: "syntax-theme",
+            name: name.clone(),
+        }))
+        .collect()
+}
+
+fn run_picker(
+    choices: &[Choice],
+    input: &[u8],
+    dir: &Path,
+    args: &[OsString],
+    env: &DeltaEnv,
+) -> Result<Option<Choice>> {
+    let input_path = dir.join("input.diff");
+    fs::write(&input_path, input)?;
+    let picker = env.picker.as_deref().unwrap_or("fzf");
+    let mut picker_words = shell_words::split(picker).context("Could not parse picker command.")?;
+    if picker_words.is_empty() {
+        return Err(anyhow!("DELTA_PICKER is empty."));
+    }
+    let picker_name = picker_words.remove(0);
+    let mut command = Command::new(&picker_name);
+    command.args(&picker_words);
+    if super::pick::is_fzf_compatible(&picker_name) {
+        command.args([
+            "--delimiter=\t".into(),
+            "--with-nth=2,1".into(),
+            format!("--preview={}", preview_command(&input_path, args)?),
+            "--preview-window=down,80%".into(),
+        ]);
+    }
+    let mut process = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .with_context(|| format!("Could not run picker {picker_name:?}."))?;
+
+    let mut stdin = process.stdin.take().unwrap();
+    for choice in choices {
+        writeln!(stdin, "{}\t{}", choice.option, choice.name)?;
+    }
+    drop(stdin);
+
+    let output = process.wait_with_output()?;
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .find_map(|line| parse_choice(line, choices)))
+}
+
+fn parse_choice(line: &str, choices: &[Choice]) -> Option<Choice> {
+    let (option, name) = line.split_once('\t')?;
+    choices
+        .iter()
+        .find(|choice| choice.option == option && choice.name == name)
+        .map(|choice| Choice {
+            option: choice.option,
+            name: choice.name.clone(),
+        })
+}
+
+/// The shell command which renders the input with the current delta executable, the options delta
+/// was called with, and the selected theme `--{1}={2}`.
+fn preview_command(input_path: &Path, args: &[OsString]) -> Result<String> {
+    let delta = std::env::current_exe()?;
+    let mut words = vec![delta.to_string_lossy().into_owned()];
+    words.extend(preview_args(args));
+    words.push("--paging=never".into());
+    Ok(format!(
+        "{} --{{1}}={{2}} --width=${{FZF_PREVIEW_COLUMNS:-80}} < {}",
+        shell_words::join(words),
+        shell_words::quote(&input_path.to_string_lossy())
+    ))
+}
+
+/// The command line arguments of delta, without the executable and the --pick-theme option.
+fn preview_args(args: &[OsString]) -> Vec<String> {
+    args.iter()
+        .skip(1)
+        .map(|arg| arg.to_string_lossy().into_owned())
+        .filter(|arg| arg != "--pick-theme")
+        .collect()
+}
+
+/// The features with `chosen` replacing any other theme among them.
+fn updated_features(features: Option<&str>, chosen: &str, choices: &[Choice]) -> String {
+    let is_theme = |feature: &str| {
+        choices
+            .iter()
+            .any(|choice| choice.option == "features" && choice.name == feature)
+    };
+    features
+        .unwrap_or_default()
+        .split_whitespace()
+        .filter(|feature| !is_theme(feature))
+        .chain([chosen])
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn choices() -> Vec<Choice> {
+        make_choices(
+            &["Dracula".to_string(), "Monokai Extended".to_string()],
+            &["collared-trogon".to_string()],
+        )
+    }
+
+    #[test]
+    fn test_make_choices() {
+        let choices = choices();
+        assert_eq!(
+            choices
+                .iter()
+                .map(|choice| format!("{}\t{}", choice.option, choice.name))
+                .collect::<Vec<_>>(),
+            vec![
+                "features\tdiff-highlight",
+                "features\tdiff-so-fancy",
+                "features\tgithub",
+                "features\tcollared-trogon",
+                "syntax-theme\tDracula",
+                "syntax-theme\tMonokai Extended",
+            ]
+        );
+        assert_eq!(
+            parse_choice("syntax-theme\tMonokai Extended", &choices),
+            Some(Choice {
+                option: "syntax-theme",
+                name: "Monokai Extended".to_string()
+            })
+        );
+        assert_eq!(parse_choice("features\tside-by-side", &choices), None);
+    }
+
+    #[test]
+    fn test_updated_features() {
+        let choices = choices();
+        assert_eq!(updated_features(None, "github", &choices), "github");
+        assert_eq!(
+            updated_features(
+                Some("side-by-side collared-trogon line-numbers"),
+                "github",
+                &choices
+            ),
+            "side-by-side line-numbers github"
+        );
+        assert_eq!(
+            updated_features(Some("github"), "github", &choices),
+            "github"
+        );
+    }
+
+    #[test]
+    fn test_preview_args() {
+        let args = |args: &[&str]| args.iter().map(OsString::from).collect::<Vec<_>>();
+        assert_eq!(
+            preview_args(&args(&["delta", "--pick-theme", "--side-by-side"])),
+            vec!["--side-by-side"]
+        );
+    }
+}
@@ -0,0 +1,548 @@
+use std::io::{self, Write};
+
+use ansi_term::{Color, Style};
+
+use crate::ansi::iterator::{AnsiElementIterator, Element};
+use crate::ansi::osc8_hyperlink_url;
+use crate::cli::OutputFormat;
+use crate::color::{rgb, ColorMode};
+use crate::config::Config;
+use crate::delta::StateMachine;
+use crate::features::html_anchors::parse_anchor_sequence;
+use crate::subcommands;
+use crate::utils::lines;
+
+/// Render the diff on stdin, or the diff of the two files given, as a static HTML page in which
+/// the styles of delta's output are inline CSS (--html), or as HTML for the body of an email
+/// (--output-format=html-email). Return the exit code of the diff.
+#[cfg(not(tarpaulin_include))]
+pub fn render_html(
+    config: &Config,
+    format: OutputFormat,
+    writer: &mut dyn Write,
+) -> io::Result<i32> {
+    let mut output = Vec::new();
+    let exit_code = match (&config.minus_file, &config.plus_file) {
+        (Some(minus_file), Some(plus_file)) => {
+            subcommands::diff::diff(minus_file, plus_file, config, &mut output)
+        }
+        _ => {
+            StateMachine::new(&mut output, config)
+                .consume(lines::byte_lines(io::stdin().lock()))?;
+            0
+        }
+    };
+    let output = String::from_utf8_lossy(&output);
+    match format {
+        OutputFormat::HtmlEmail => write_html_email(&output, config, writer)?,
+        _ => write_html(&output, config, writer)?,
+    }
+    Ok(exit_code)
+}
+
+/// Write delta's output, which contains ANSI escape sequences, as an HTML page. The lines marked
+/// with anchors (see html_anchors) have these ids, and the files are listed in an index beside
+/// the diff, linking to their headers.
+pub fn write_html(output: &str, config: &Config, writer: &mut dyn Write) -> io::Result<()> {
+    let page_colors = page_colors(config);
+    let (background, foreground) = (css_color(page_colors.0), css_color(page_colors.1));
+    writeln!(
+        writer,
+        "<!DOCTYPE html>\n\
+         <html>\n\
+         <head>\n\
+         <meta charset=\"utf-8\">\n\
+         <title>delta</title>\n\
+         </head>\n\
+         <body style=\"margin:0;display:flex;background-color:{background}\">"
+    )?;
+    let files = output
+        .lines()
+        .flat_map(anchors)
+        .filter_map(|(id, label)| Some((id, label?)))
+        .collect::<Vec<_>>();
+    if !files.is_empty() {
+        writeln!(
+            writer,
+            "<nav style=\"position:sticky;top:0;align-self:flex-start;flex:none;max-width:25%;\
+             max-height:100vh;overflow:auto;box-sizing:border-box;padding:1em;\
+             font-family:monospace;color:{foreground};border-right:1px solid {foreground}\">"
+        )?;
+        for (id, label) in files {
+            writeln!(
+                writer,
+                "<a href=\"#{}\" style=\"display:block;color:inherit;white-space:nowrap\">{}</a>",
+                escape(id),
+                escape(label)
+            )?;
+        }
+        writeln!(writer, "</nav>")?;
+    }
+    writeln!(
+        writer,
+        "<pre style=\"margin:0;padding:1em;flex:auto;min-width:0;overflow-x:auto;\
+         font-family:monospace;background-color:{background};color:{foreground}\">"
+    )?;
+    for line in output.lines() {
+        for (id, _) in anchors(line) {
+            write!(writer, "<span id=\"{}\"></span>", escape(id))?;
+        }
+        writeln!(writer, "{}", ansi_to_html(line, page_colors))?;
+    }
+    writeln!(writer, "</pre>\n</body>\n</html>")
+}
+
+// The ids of the anchors marking a line of delta's output, and the labels of their entries in
+// the index of the page.
+fn anchors(line: &str) -> Vec<(&str, Option<&str>)> {
+    AnsiElementIterator::new(line)
+        .filter_map(|element| match element {
+            Element::Osc(i, j) => parse_anchor_sequence(&line[i..j]),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Write delta's output as HTML for the body of an email. Mail clients ignore style sheets and
+/// support little of CSS, so the lines are the rows of a table, and all styles are inline.
+pub fn write_html_email(output: &str, config: &Config, writer: &mut dyn Write) -> io::Result<()> {
+    let page_colors = page_colors(config);
+    let (background, foreground) = (css_color(page_colors.0), css_color(page_colors.1));
+    writeln!(
+        writer,
+        "<!DOCTYPE html>\n\
+         <html>\n\
+         <head>\n\
+         <meta http-equiv=\"Content-Type\" content=\"text/html; charset=us-ascii\">\n\
+         <title>delta</title>\n\
+         </head>\n\
+         <body style=\"margin:0;padding:0\">\n\
+         <table role=\"presentation\" width=\"100%\" cellpadding=\"0\" cellspacing=\"0\"\n\
+         border=\"0\" bgcolor=\"{background}\"\n\
+         style=\"border-collapse:collapse;\n\
+         background-color:{background}\">"
+    )?;
+    for line in output.lines() {
+        writeln!(
+            writer,
+            "<tr><td\n\
+             style=\"font-family:Menlo,Consolas,monospace;\n\
+             font-size:12px;\n\
+             line-height:16px;\n\
+             color:{foreground};\n\
+             white-space:nowrap\">{}</td></tr>",
+            ansi_to_email_html(line, page_colors)
+        )?;
+    }
+    writeln!(writer, "</table>\n</body>\n</html>")
+}
+
+/// The background and foreground colors of the page: those of the syntax theme, if it has
+/// them, and otherwise those of a terminal of the color mode.
+fn page_colors(config: &Config) -> (Color, Color) {
+    let theme_color = |color: Option<syntect::highlighting::Color>| {
+        // Colors with alpha 0 or 1 stand for terminal colors (see to_ansi_color).
+        color
+            .filter(|color| color.a > 1)
+            .map(|color| Color::RGB(color.r, color.g, color.b))
+    };
+    let settings = config.syntax_theme.as_ref().map(|theme| &theme.settings);
+    let (background, foreground) = match config.color_mode {
+        ColorMode::Dark => (Color::RGB(0, 0, 0), Color::RGB(255, 255, 255)),
+        ColorMode::Light => (Color::RGB(255, 255, 255), Color::RGB(0, 0, 0)),
+    };
+    (
+        theme_color(settings.and_then(|settings| settings.background)).unwrap_or(background),
+        theme_color(settings.and_then(|settings| settings.foreground)).unwrap_or(foreground),
+    )
+}
+
+// A run of the text of a line of delta's output, with its style and the URL of its hyperlink.
+struct Segment<'a> {
+    text: &'a str,
+    style: Style,
+    url: Option<&'a str>,
+}
+
+// The runs of text of a line of delta's output. Escape sequences other than those of styles and
+// hyperlinks are dropped.
+fn segments(line: &str) -> Vec<Segment<'_>> {
+    let mut segments = Vec::new();
+    let mut style = Style::default();
+    let mut url = None;
+    let mut after_osc = false;
+    for element in AnsiElementIterator::new(line) {
+        match element {
+            Element::Sgr(sgr_style, _, _, _) => style = sgr_style,
+            Element::Osc(i, j) => {
+                if let Some(osc_url) = osc8_hyperlink_url(&line[i..j]) {
+                    url = Some(osc_url).filter(|url| !url.is_empty());
+                }
+            }
+            Element::Text(i, j) => {
+                // The OSC element does not include the final byte of an ESC \ terminator.
+                let text = &line[i..j];
+                let text = if after_osc {
+                    text.strip_prefix('\\').unwrap_or(text)
+                } else {
+                    text
+                };
+                if !text.is_empty() {
+                    segments.push(Segment { text, style, url });
+                }
+            }
+            _ => {}
+        }
+        after_osc = matches!(element, Element::Osc(..));
+    }
+    segments
+}
+
+/// Convert a line of delta's output to HTML: styled text becomes a span with inline CSS, and
+/// hyperlinks become links. Other escape sequences are dropped.
+fn ansi_to_html(line: &str, page_colors: (Color, Color)) -> String {
+    let mut html = String::new();
+    let mut url = None;
+    for segment in segments(line) {
+        if segment.url != url {
+            if url.is_some() {
+                html.push_str("</a>");
+            }
+            if let Some(url) = segment.url {
+                html.push_str(&format!("<a href=\"{}\">", escape(url)));
+            }
+            url = segment.url;
+        }
+        let css = style_declarations(segment.style, page_colors).join(";");
+        if css.is_empty() {
+            html.push_str(&escape(segment.text));
+        } else {
+            html.push_str(&format!(
+                "<span style=\"{css}\">{}</span>",
+                escape(segment.text)
+            ));
+        }
+    }
+    if url.is_some() {
+        html.push_str("</a>");
+    }
+    html
+}
+
+// The maximum length of the HTML of the text of a span in an email.
+const EMAIL_SPAN_TEXT_LENGTH: usize = 16;
+
+/// Convert a line of delta's output to HTML for an email. The HTML is 7-bit ASCII, and no line
+/// of it is longer than the 76 characters of a line of quoted-printable encoding, so that the
+/// encoding leaves it unchanged: the text is split into short spans, and the tags are broken
+/// over lines between attributes and CSS declarations. Spaces are non-breaking, since mail
+/// clients may not preserve them otherwise.
+fn ansi_to_email_html(line: &str, page_colors: (Color, Color)) -> String {
+    let mut html = String::new();
+    let mut url = None;
+    for segment in segments(line) {
+        if segment.url != url {
+            if url.is_some() {
+                html.push_str("</a>");
+            }
+            if let Some(url) = segment.url {
+                html.push_str(&format!("<a\nhref=\"{}\">", to_ascii(&escape(url))));
+            }
+            url = segment.url;
+        }
+        let declarations =
+            style_declarations(blend_dimmed(segment.style, page_colors), page_colors);
+        let span = if declarations.is_empty() {
+            "<span\n>".to_string()
+        } else {
+            format!("<span\nstyle=\"{}\">", declarations.join(";\n"))
+        };
+        for text in email_text_chunks(segment.text) {
+            html.push_str(&span);
+            html.push_str(&text);
+            html.push_str("</span>");
+        }
+    }
+    if url.is_some() {
+        html.push_str("</a>");
+    }
+    if html.is_empty() {
+        // An empty cell would have no height.
+        html.push_str("&nbsp;");
+    }
+    html
+}
+
+// The HTML of the text, in chunks of at most EMAIL_SPAN_TEXT_LENGTH characters.
+fn email_text_chunks(text: &str) -> Vec<String> {
+    let mut chunks = vec![String::new()];
+    for c in text.chars() {
+        let html = match c {
+            ' ' => "&nbsp;".to_string(),
+            '&' => "&amp;".to_string(),
+            '<' => "&lt;".to_string(),
+            '>' => "&gt;".to_string(),
+            '"' => "&quot;".to_string(),
+            c => to_ascii(c.encode_utf8(&mut [0; 4])),
+        };
+        if chunks[chunks.len() - 1].len() + html.len() > EMAIL_SPAN_TEXT_LENGTH {
+            chunks.push(String::new());
+        }
+        chunks.last_mut().unwrap().push_str(&html);
+    }
+    chunks
+}
+
+// Replace the non-ASCII characters of HTML by character references.
+fn to_ascii(html: &str) -> String {
+    html.chars()
+        .map(|c| {
+            if c.is_ascii() {
+                c.to_string()
+            } else {
+                format!("&#x{:x};", c as u32)
+            }
+        })
+        .collect()
+}
+
+// Mail clients do not support opacity, so the color of dimmed text is blended with that of its
+// background instead.
+fn blend_dimmed(mut style: Style, page_colors: (Color, Color)) -> Style {
+    if style.is_dimmed && !style.is_reverse {
+        let (r, g, b) = rgb(style.foreground.unwrap_or(page_colors.1));
+        let (bg_r, bg_g, bg_b) = rgb(style.background.unwrap_or(page_colors.0));
+        let blend =
+            |color: u8, background: u8| ((color as u16 * 6 + background as u16 * 4) / 10) as u8;
+        style.foreground = Some(Color::RGB(blend(r, bg_r), blend(g, bg_g), blend(b, bg_b)));
+        style.is_dimmed = false;
+    }
+    style
+}
+
+/// The inline CSS declarations for text of the style.
+fn style_declarations(style: Style, page_colors: (Color, Color)) -> Vec<String> {
+    let (mut background, mut foreground) = (style.background, style.foreground);
+    if style.is_reverse {
+        (background, foreground) = (
+            foreground.or(Some(page_colors.1)),
+            background.or(Some(page_colors.0)),
+        );
+    }
+    let mut css = Vec::new();
+    if let Some(color) = foreground {
+        css.push(format!("color:{}", css_color(color)));
+    }
+    if let Some(color) = background {
+        css.push(format!("background-color:{}", css_color(color)));
+    }
+    if style.is_bold {
+        css.push("font-weight:bold".to_string());
+    }
+    if style.is_dimmed {
+        css.push("opacity:0.6".to_string());
+    }
+    if style.is_italic {
+        css.push("font-style:italic".to_string());
+    }
+    match (style.is_underline, style.is_strikethrough) {
+        (true, true) => css.push("text-decoration:underline line-through".to_string()),
+        (true, false) => css.push("text-decoration:underline".to_string()),
+        (false, true) => css.push("text-decoration:line-through".to_string()),
+        (false, false) => {}
+    }
+    css
+}
+
+/// The CSS hex color of a terminal color.
+fn css_color(color: Color) -> String {
+    let (r, g, b) = rgb(color);
+    format!("#{r:02x}{g:02x}{b:02x}")
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::integration_test_utils;
+
+    #[test]
+    fn test_ansi_to_html() {
+        let page_colors = (Color::RGB(0, 0, 0), Color::RGB(255, 255, 255));
+        assert_eq!(
+            ansi_to_html("a <b> & c", page_colors),
+            "a &lt;b&gt; &amp; c"
+        );
+        assert_eq!(
+            ansi_to_html("\x1b[1;31mred\x1b[0m plain", page_colors),
+            "<span style=\"color:#cd0000;font-weight:bold\">red</span> plain"
+        );
+        assert_eq!(
+            ansi_to_html("\x1b[38;2;1;2;3;48;5;22mx\x1b[0m", page_colors),
+            "<span style=\"color:#010203;background-color:#005f00\">x</span>"
+        );
+        assert_eq!(
+            ansi_to_html("\x1b[7mreversed\x1b[0m", page_colors),
+            "<span style=\"color:#000000;background-color:#ffffff\">reversed</span>"
+        );
+    }
+
+    #[test]
+    fn test_ansi_to_html_hyperlink() {
+        assert_eq!(
+            ansi_to_html(
+                "see \x1b]8;;file:///a?b&c\x1b\\link\x1b]8;;\x1b\\ here",
+                (Color::Black, Color::White)
+            ),
+            "see <a href=\"file:///a?b&amp;c\">link</a> here"
+        );
+    }
+
+    #[test]
+    fn test_write_html() {
+        let config = integration_test_utils::make_config_from_args(&[
+            "--syntax-theme",
+            "none",
+            "--light",
+            "--minus-style",
+            "red",
+            "--plus-style",
+            "green",
+            "--file-style",
+            "omit",
+            "--hunk-header-style",
+            "omit",
+        ]);
+        let output = integration_test_utils::run_delta(DIFF, &config);
+        let mut html = Vec::new();
+        write_html(&output, &config, &mut html).unwrap();
+        let html = String::from_utf8(html).unwrap();
+        assert!(html.starts_with("<!DOCTYPE html>\n"));
+        assert!(html.contains("background-color:#ffffff;color:#000000\">"));
+        assert!(html.contains(
+            "\n<span style=\"color:#cd0000\">a </span>\
+             <span style=\"background-color:#ffafaf\">&lt;</span>\
+             <span style=\"color:#cd0000\"> b</span>\n"
+        ));
+        assert!(html.contains("<span style=\"background-color:#afffaf\">&gt;</span>"));
+        assert!(html.ends_with("</pre>\n</body>\n</html>\n"));
+    }
+
+    #[test]
+    fn test_write_html_anchors() {
+        let config = integration_test_utils::make_config_from_args(&["--html"]);
+        let output = integration_test_utils::run_delta(TWO_FILES_DIFF, &config);
+        let mut html = Vec::new();
+        write_html(&output, &config, &mut html).unwrap();
+        let html = String::from_utf8(html).unwrap();
+        let nav = &html[html.find("<nav").unwrap()..html.find("</nav>").unwrap()];
+        assert!(nav.contains(
+            "<a href=\"#F1\" style=\"display:block;color:inherit;white-space:nowrap\">a.rs</a>"
+        ));
+        assert!(nav.contains(">&lt;b&gt;.rs</a>"));
+        for id in ["F1", "F1-L10", "F1-L11", "F1-L12", "F2", "F2-L1"] {
+            assert_eq!(
+                html.matches(&format!("<span id=\"{id}\"></span>")).count(),
+                1
+            );
+        }
+        // Removed lines have no anchors.
+        assert_eq!(html.matches("<span id=").count(), 6);
+        assert!(!html.contains("delta-anchor"));
+    }
+
+    #[test]
+    fn test_write_html_anchors_side_by_side() {
+        let config = integration_test_utils::make_config_from_args(&["--html", "--side-by-side"]);
+        let output = integration_test_utils::run_delta(TWO_FILES_DIFF, &config);
+        let mut html = Vec::new();
+        write_html(&output, &config, &mut html).unwrap();
+        let html = String::from_utf8(html).unwrap();
+        // Only the unchanged lines are marked.
+        for id in ["F1", "F1-L10", "F1-L12", "F2"] {
+            assert!(html.contains(&format!("<span id=\"{id}\"></span>")));
+        }
+        assert_eq!(html.matches("<span id=").count(), 4);
+    }
+
+    #[test]
+    fn test_ansi_to_email_html() {
+        let page_colors = (Color::RGB(0, 0, 0), Color::RGB(255, 255, 255));
+        assert_eq!(ansi_to_email_html("", page_colors), "&nbsp;");
+        assert_eq!(
+            ansi_to_email_html("a <b> \u{2502} c", page_colors),
+            "<span\n>a&nbsp;&lt;b&gt;</span><span\n>&nbsp;&#x2502;</span><span\n>&nbsp;c</span>"
+        );
+        assert_eq!(
+            ansi_to_email_html("\x1b[1;31mred\x1b[0m", page_colors),
+            "<span\nstyle=\"color:#cd0000;\nfont-weight:bold\">red</span>"
+        );
+        // Dimmed text is blended with the background.
+        assert_eq!(
+            ansi_to_email_html("\x1b[2mdim\x1b[0m", page_colors),
+            "<span\nstyle=\"color:#999999\">dim</span>"
+        );
+        assert_eq!(
+            ansi_to_email_html(
+                "\x1b]8;;file:///\u{e9}\x1b\\link\x1b]8;;\x1b\\",
+                page_colors
+            ),
+            "<a\nhref=\"file:///&#xe9;\"><span\n>link</span></a>"
+        );
+    }
+
+    #[test]
+    fn test_write_html_email() {
+        let config = integration_test_utils::make_config_from_args(&[
+            "--side-by-side",
+            "--line-numbers",
+            "--width",
+            "120",
+        ]);
+        let output = integration_test_utils::run_delta(DIFF, &config);
+        let mut html = Vec::new();
+        write_html_email(&output, &config, &mut html).unwrap();
+        let html = String::from_utf8(html).unwrap();
+        assert!(html.contains("\n<table role=\"presentation\""));
+        assert!(!html.contains("<style") && !html.contains("flex"));
+        // The HTML is unchanged by quoted-printable encoding.
+        assert!(html.is_ascii());
+        assert!(html.lines().all(|line| line.len() <= 76));
+        assert!(html.contains("&#x2502;"));
+        assert!(html.ends_with("</table>\n</body>\n</html>\n"));
+    }
+
+    const DIFF: &str = "\
+diff --git a/file b/file
+index 1111111..2222222 100644
+--- a/file
++++ b/file
+@@ -1,1 +1,1 @@
+-a < b
++a > b
+";
+
+    const TWO_FILES_DIFF: &str = "\
+diff --git a/a.rs b/a.rs
+index 1111111..2222222 100644
+--- a/a.rs
++++ b/a.rs
+@@ -10,3 +10,3 @@ fn f() {
+ a
+-b
++c
+ d
+diff --git a/<b>.rs b/<b>.rs
+index 1111111..2222222 100644
+--- a/<b>.rs
++++ b/<b>.rs
+@@ -1,0 +1,1 @@
++e
+";
+}
@@ -0,0 +1,313 @@
+use std::env;
+use std::fs::{self, File};
+use std::io::{Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use semver::Version;
+use serde::Deserialize;
+
+use crate::errors::*;
+use crate::utils::private_dir::new_private_temp_dir;
+
+const LATEST_RELEASE_URL: &str = "https://api.github.com/repos/dandavison/delta/releases/latest";
+
+#[derive(Debug, Deserialize)]
+struct Release {
+    tag_name: String,
+    assets: Vec<Asset>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Asset {
+    name: String,
+    browser_download_url: String,
+    // E.g. "sha256:4d1e…"
+    digest: Option<String>,
+}
+
+/// Replace the running delta executable with the latest release from GitHub, after verifying the
+/// checksum of the downloaded artifact. The download, checksum and extraction are done by curl,
+/// sha256sum (shasum on macOS, PowerShell on Windows) and tar.
+#[cfg(not(tarpaulin_include))]
+pub fn self_update() -> Result<()> {
+    let current_version = env!("CARGO_PKG_VERSION");
+    let release: Release = serde_json::from_slice(&download(LATEST_RELEASE_URL)?)
+        .context("Could not parse the latest release of delta.")?;
+    let version = release.tag_name.trim_start_matches('v');
+    if !is_newer(version, current_version)? {
+        println!("delta {current_version} is up to date.");
+        return Ok(());
+    }
+    let target = target_triple(env::consts::ARCH, env::consts::OS).ok_or_else(|| {
+        anyhow!(
+            "There is no release of delta for this platform ({}-{}).",
+            env::consts::ARCH,
+            env::consts::OS
+        )
+    })?;
+    let asset = find_asset(&release.assets, version, target)?;
+    let checksum = asset
+        .digest
+        .as_deref()
+        .and_then(|digest| digest.strip_prefix("sha256:"))
+        .ok_or_else(|| anyhow!("The release does not publish a checksum of {}.", asset.name))?;
+
+    let dir = new_private_temp_dir("delta-self-update")?;
+    let result = download_and_install(asset, checksum, &dir);
+    let _ = fs::remove_dir_all(&dir);
+    result?;
+    println!("Updated delta from {current_version} to {version}.");
+    Ok(())
+}
+
+// Whether the version `version` is more recent than `current_version`. A leading 'v' is ignored.
+fn is_newer(version: &str, current_version: &str) -> Result<bool> {
+    let parse = |version: &str| {
+        Version::parse(version.trim_start_matches('v'))
+            .with_context(|| format!("Could not parse the version {version} of delta."))
+    };
+    Ok(parse(version)? > parse(current_version)?)
+}
+
+// The archive is opened once, and the checksum is computed from, and the executable extracted
+// from, that open file, so that the archive cannot be replaced after it has been verified.
+fn download_and_install(asset: &Asset, checksum: &str, dir: &Path) -> Result<()> {
+    let path = dir.join(&asset.name);
+    fs::write(&path, download(&asset.browser_download_url)?)?;
+    let mut archive = File::open(&path)?;
+    let actual_checksum = sha256(&archive)?;
+    if !actual_checksum.eq_ignore_ascii_case(checksum) {
+        return Err(anyhow!(
+            "The checksum of {} is {actual_checksum}, but {checksum} was expected.",
+            asset.name
+        ));
+    }
+    archive.seek(SeekFrom::Start(0))?;
+    let executable_name = format!("delta{}", env::consts::EXE_SUFFIX);
+    // The archive contains a directory named like the archive, without its extension.
+    let member = format!(
+        "{}/{executable_name}",
+        asset
+            .name
+            .trim_end_matches(".tar.gz")
+            .trim_end_matches(".zip")
+    );
+    // The compression of standard input is not detected by GNU tar.
+    let extract = if asset.name.ends_with(".tar.gz") {
+        "-xzOf"
+    } else {
+        "-xOf"
+    };
+    let new_executable = run_with_input(
+        Command::new("tar").arg(extract).arg("-").arg(&member),
+        &archive,
+    )
+    .with_context(|| format!("Could not extract {executable_name} from {}.", asset.name))?;
+    if new_executable.is_empty() {
+        return Err(anyhow!(
+            "{} does not contain {executable_name}.",
+            asset.name
+        ));
+    }
+    replace_executable(&new_executable, &env::current_exe()?)
+}
+
+// Move the new executable into place with a rename in the directory of the current one, so that
+// delta is never missing or partially written.
+fn replace_executable(new_executable: &[u8], current_executable: &Path) -> Result<()> {
+    let staged = sibling_path(current_executable, ".new");
+    fs::write(&staged, new_executable).with_context(|| {
+        format!(
+            "Could not write to {}. Is delta installed by a package manager?",
+            staged.display()
+        )
+    })?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&staged, fs::Permissions::from_mode(0o755))?;
+    }
+    // A running executable cannot be overwritten on Windows, but it can be renamed.
+    #[cfg(windows)]
+    fs::rename(current_executable, sibling_path(current_executable, ".old"))?;
+    fs::rename(&staged, current_executable)?;
+    Ok(())
+}
+
+fn sibling_path(path: &Path, suffix: &str) -> PathBuf {
+    let mut file_name = path.file_name().unwrap_or_default().to_os_string();
+    file_name.push(suffix);
+    path.with_file_name(file_name)
+}
+
+fn download(url: &str) -> Result<Vec<u8>> {
+    run(Command::new("curl")
+        .args(["--fail", "--silent", "--show-error", "--location"])
+        .args(["--header", "Accept: application/vnd.github+json"])
+        .arg(url))
+    .with_context(|| format!("Could not download {url}."))
+}
+
+fn sha256(file: &File) -> Result<String> {
+    let output = if cfg!(windows) {
+        run_with_input(
+            Command::new("powershell").args([
+                "-NoProfile",
+                "-Command",
+                "(Get-FileHash -Algorithm SHA256 -InputStream ([Console]::OpenStandardInput())).Hash",
+            ]),
+            file,
+        )
+    } else if cfg!(target_os = "macos") {
+        run_with_input(Command::new("shasum").args(["-a", "256", "-"]), file)
+    } else {
+        run_with_input(Command::new("sha256sum").arg("-"), file)
+    }
+    .context("Could not compute the checksum of the download.")?;
+    parse_sha256_output(&String::from_utf8_lossy(&output))
+        .ok_or_else(|| anyhow!("Could not compute the checksum of the download."))
+}
+
+fn run(command: &mut Command) -> Result<Vec<u8>> {
+    output(command.stdin(Stdio::null()))
+}
+
+// Run the command with the file, from its current position, as standard input.
+fn run_with_input(command: &mut Command, file: &File) -> Result<Vec<u8>> {
+    output(command.stdin(file.try_clone()?))
+}
+
+fn output(command: &mut Command) -> Result<Vec<u8>> {
+    let output = command.output()?;
+    if !output.status.success() {
+        return Err(anyhow!(
+            "{}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(output.stdout)
+}
+
+// The first hexadecimal word of 64 characters in the output of sha256sum, shasum or PowerShell.
+fn parse_sha256_output(output: &str) -> Option<String> {
+    output
+        .split_whitespace()
+        .find(|word| word.len() == 64 && word.chars().all(|c| c.is_ascii_hexdigit()))
+        .map(str::to_ascii_lowercase)
+}
+
+/// The target of the release artifact for a platform. On Linux, the statically linked musl build
+/// is used where there is one.
+fn target_triple(arch: &str, os: &str) -> Option<&'static str> {
+    Some(match (arch, os) {
+        ("x86_64", "linux") => "x86_64-unknown-linux-musl",
+        ("aarch64", "linux") => "aarch64-unknown-linux-gnu",
+        ("arm", "linux") => "arm-unknown-linux-gnueabihf",
+        ("x86", "linux") => "i686-unknown-linux-gnu",
+        ("x86_64", "macos") => "x86_64-apple-darwin",
+        ("aarch64", "macos") => "aarch64-apple-darwin",
+        ("x86_64", "windows") => "x86_64-pc-windows-msvc",
+        _ => return None,
+    })
+}
+
+fn find_asset<'a>(assets: &'a [Asset], version: &str, target: &str) -> Result<&'a Asset> {
+    let name = format!("delta-{version}-{target}");
+    assets
+        .iter()
+        .find(|asset| asset.name == format!("{name}.tar.gz") || asset.name == format!("{name}.zip"))
+        .ok_or_else(|| anyhow!("The release of delta {version} has no artifact for {target}."))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RELEASE_JSON: &str = r#"{
+        "tag_name": "0.18.2",
+        "assets": [
+            {
+                "name": "delta-0.18.2-x86_64-unknown-linux-gnu.tar.gz",
+                "browser_download_url": "https://github.com/dandavison/delta/releases/download/0.18.2/delta-0.18.2-x86_64-unknown-linux-gnu.tar.gz",
+                "digest": "sha256:1111111111111111111111111111111111111111111111111111111111111111"
+            },
+            {
+                "name": "delta-0.18.2-x86_64-unknown-linux-musl.tar.gz",
+                "browser_download_url": "https://github.com/dandavison/delta/releases/download/0.18.2/delta-0.18.2-x86_64-unknown-linux-musl.tar.gz",
+                "digest": "sha256:2222222222222222222222222222222222222222222222222222222222222222"
+            },
+            {
+                "name": "delta-0.18.2-x86_64-pc-windows-msvc.zip",
+                "browser_download_url": "https://github.com/dandavison/delta/releases/download/0.18.2/delta-0.18.2-x86_64-pc-windows-msvc.zip",
+                "digest": null
+            }
+        ]
+    }"#;
+
+    #[test]
+    fn test_find_asset() {
+        let release: Release = serde_json::from_str(RELEASE_JSON).unwrap();
+        let target = target_triple("x86_64", "linux").unwrap();
+        let asset = find_asset(&release.assets, "0.18.2", target).unwrap();
+        assert_eq!(asset.name, "delta-0.18.2-x86_64-unknown-linux-musl.tar.gz");
+        let target = target_triple("x86_64", "windows").unwrap();
+        let asset = find_asset(&release.assets, "0.18.2", target).unwrap();
+        assert_eq!(asset.digest, None);
+        let target = target_triple("aarch64", "macos").unwrap();
+        assert!(find_asset(&release.assets, "0.18.2", target).is_err());
+        assert_eq!(target_triple("riscv64", "linux"), None);
+    }
+
+    #[test]
+    fn test_is_newer() {
+        assert!(is_newer("0.18.3", "0.18.2").unwrap());
+        assert!(is_newer("v0.19.0", "0.18.2").unwrap());
+        assert!(is_newer("0.18.10", "0.18.9").unwrap());
+        assert!(!is_newer("0.18.2", "0.18.2").unwrap());
+        assert!(!is_newer("v0.18.2", "0.18.2").unwrap());
+        // An older latest release, e.g. of a development build, is not installed.
+        assert!(!is_newer("0.18.2", "0.19.0").unwrap());
+        assert!(!is_newer("0.19.0-rc.1", "0.19.0").unwrap());
+        assert!(is_newer("0.18", "0.18.2").is_err());
+    }
+
+    #[test]
+    fn test_parse_sha256_output() {
+        let checksum = "4d1e0a9f6c2b7e8d3a5f1c0b9e8d7c6b5a4f3e2d1c0b9a8f7e6d5c4b3a2f1e0d";
+        assert_eq!(
+            parse_sha256_output(&format!("{checksum}  -\n")).as_deref(),
+            Some(checksum)
+        );
+        assert_eq!(
+            parse_sha256_output(&format!("{}\r\n", checksum.to_ascii_uppercase())).as_deref(),
+            Some(checksum)
+        );
+        assert_eq!(
+            parse_sha256_output("sha256sum: delta.tar.gz: No such file"),
+            None
+        );
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_sha256_of_open_file() {
+        let path = crate::tests::test_utils::temp_path("test_sha256_of_open_file");
+        fs::write(&path, "abc").unwrap();
+        let mut file = File::open(&path).unwrap();
+        let checksum = "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad";
+        assert_eq!(sha256(&file).unwrap(), checksum);
+        // The file is read from its current position.
+        file.seek(SeekFrom::Start(0)).unwrap();
+        assert_eq!(sha256(&file).unwrap(), checksum);
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_sibling_path() {
+        assert_eq!(
+            sibling_path(Path::new("/usr/local/bin/delta"), ".new"),
+            PathBuf::from("/usr/local/bin/delta.new")
+        );
+    }
+}
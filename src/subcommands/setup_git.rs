@@ -0,0 +1,248 @@
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::errors::*;
+
+/// The git settings recommended for using delta, as (section, key, value).
+const RECOMMENDED_SETTINGS: &[(&str, &str, &str)] = &[
+    ("core", "pager", "delta"),
+    ("interactive", "diffFilter", "delta --color-only"),
+    ("merge", "conflictStyle", "zdiff3"),
+    ("diff", "colorMoved", "default"),
+];
+
+/// Add the recommended settings to the user's global gitconfig, or with `dry_run` print those
+/// which would be changed. Settings which already have the recommended value, or already run
+/// delta, are left alone, so that running this again changes nothing. Settings with another value
+/// are only replaced with `force`, and are otherwise listed.
+#[cfg(not(tarpaulin_include))]
+pub fn setup_git(dry_run: bool, force: bool, writer: &mut dyn Write) -> Result<()> {
+    setup_gitconfig_file(&global_gitconfig_path()?, dry_run, force, writer)
+}
+
+/// The path of the user's global gitconfig, which need not exist yet.
+pub fn global_gitconfig_path() -> Result<PathBuf> {
+    match git2::Config::find_global() {
+        Ok(path) => Ok(path),
+        Err(_) => Ok(dirs::home_dir()
+            .ok_or_else(|| anyhow!("Could not determine the home directory."))?
+            .join(".gitconfig")),
+    }
+}
+
+fn setup_gitconfig_file(
+    path: &Path,
+    dry_run: bool,
+    force: bool,
+    writer: &mut dyn Write,
+) -> Result<()> {
+    let mut config =
+        git2::Config::open(path).with_context(|| format!("Could not open {}.", path.display()))?;
+    let (changes, kept): (Vec<Change>, Vec<Change>) = changed_settings(&config)
+        .into_iter()
+        .partition(|change| force || change.previous_value.is_none());
+    if changes.is_empty() && kept.is_empty() {
+        writeln!(writer, "{} is already set up for delta.", path.display())?;
+        return Ok(());
+    }
+    if dry_run {
+        if !changes.is_empty() {
+            writeln!(writer, "# Settings to add to {}:", path.display())?;
+            write!(writer, "{}", format_stanza(&changes))?;
+        }
+        for change in &kept {
+            writeln!(
+                writer,
+                "# Keeping {} = {} (--force would set it to {})",
+                change.name(),
+                change.previous_value.as_deref().unwrap_or_default(),
+                change.value
+            )?;
+        }
+        return Ok(());
+    }
+    for change in &changes {
+        config
+            .set_str(&change.name(), change.value)
+            .with_context(|| format!("Could not set {} in {}.", change.name(), path.display()))?;
+        match &change.previous_value {
+            Some(previous_value) => writeln!(
+                writer,
+                "Set {} = {} (was: {previous_value})",
+                change.name(),
+                change.value
+            )?,
+            None => writeln!(writer, "Set {} = {}", change.name(), change.value)?,
+        }
+    }
+    for change in &kept {
+        writeln!(
+            writer,
+            "Kept {} = {} (use --force to set it to {})",
+            change.name(),
+            change.previous_value.as_deref().unwrap_or_default(),
+            change.value
+        )?;
+    }
+    if !changes.is_empty() {
+        writeln!(writer, "Updated {}.", path.display())?;
+    }
+    Ok(())
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct Change {
+    section: &'static str,
+    key: &'static str,
+    value: &'static str,
+    previous_value: Option<String>,
+}
+
+impl Change {
+    fn name(&self) -> String {
+        format!("{}.{}", self.section, self.key)
+    }
+}
+
+// The recommended settings which the config does not have, except those which already run delta
+// with other arguments.
+fn changed_settings(config: &git2::Config) -> Vec<Change> {
+    RECOMMENDED_SETTINGS
+        .iter()
+        .filter_map(|&(section, key, value)| {
+            let previous_value = config.get_string(&format!("{section}.{key}")).ok();
+            let is_set_up = match previous_value.as_deref() {
+                Some(previous_value) => {
+                    previous_value == value
+                        || is_delta_command(value) && is_delta_command(previous_value)
+                }
+                None => false,
+            };
+            (!is_set_up).then_some(Change {
+                section,
+                key,
+                value,
+                previous_value,
+            })
+        })
+        .collect()
+}
+
+// Whether the command runs delta, e.g. "delta --side-by-side" or "/usr/local/bin/delta".
+fn is_delta_command(command: &str) -> bool {
+    command
+        .split_whitespace()
+        .next()
+        .and_then(|program| Path::new(program).file_stem())
+        .is_some_and(|stem| stem == "delta")
+}
+
+// The settings in gitconfig syntax, e.g. "[core]\n    pager = delta\n".
+fn format_stanza(changes: &[Change]) -> String {
+    let mut stanza = String::new();
+    let mut previous_section = None;
+    for change in changes {
+        if previous_section != Some(change.section) {
+            stanza.push_str(&format!("[{}]\n", change.section));
+            previous_section = Some(change.section);
+        }
+        stanza.push_str(&format!("    {} = {}\n", change.key, change.value));
+    }
+    stanza
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::tests::test_utils::temp_path;
+
+    #[test]
+    fn test_setup_git() {
+        let path = temp_path("test_setup_git.gitconfig");
+        fs::write(
+            &path,
+            "[core]\n\tpager = less\n[diff]\n\tcolorMoved = default\n",
+        )
+        .unwrap();
+        let setup = |dry_run, force| {
+            let mut output = Vec::new();
+            setup_gitconfig_file(&path, dry_run, force, &mut output).unwrap();
+            String::from_utf8(output).unwrap()
+        };
+
+        assert_eq!(
+            setup(true, false),
+            format!(
+                "\
+# Settings to add to {}:
+[interactive]
+    diffFilter = delta --color-only
+[merge]
+    conflictStyle = zdiff3
+# Keeping core.pager = less (--force would set it to delta)
+",
+                path.display()
+            )
+        );
+        // A dry run does not change the file.
+        assert!(!fs::read_to_string(&path).unwrap().contains("zdiff3"));
+
+        assert_eq!(
+            setup(false, false),
+            format!(
+                "\
+Set interactive.diffFilter = delta --color-only
+Set merge.conflictStyle = zdiff3
+Kept core.pager = less (use --force to set it to delta)
+Updated {}.
+",
+                path.display()
+            )
+        );
+        assert_eq!(
+            setup(false, false),
+            "Kept core.pager = less (use --force to set it to delta)\n"
+        );
+        assert_eq!(
+            setup(false, true),
+            format!(
+                "Set core.pager = delta (was: less)\nUpdated {}.\n",
+                path.display()
+            )
+        );
+        let config = git2::Config::open(&path).unwrap();
+        assert!(changed_settings(&config).is_empty());
+
+        assert_eq!(
+            setup(false, false),
+            format!("{} is already set up for delta.\n", path.display())
+        );
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_setup_git_keeps_delta_commands() {
+        let path = temp_path("test_setup_git_keeps_delta_commands.gitconfig");
+        fs::write(
+            &path,
+            "[core]\n\tpager = /usr/local/bin/delta --side-by-side\n\
+             [interactive]\n\tdiffFilter = delta --color-only --features=interactive\n\
+             [merge]\n\tconflictStyle = zdiff3\n[diff]\n\tcolorMoved = default\n",
+        )
+        .unwrap();
+        let mut output = Vec::new();
+        setup_gitconfig_file(&path, false, true, &mut output).unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            format!("{} is already set up for delta.\n", path.display())
+        );
+        let config = git2::Config::open(&path).unwrap();
+        assert_eq!(
+            config.get_string("core.pager").unwrap(),
+            "/usr/local/bin/delta --side-by-side"
+        );
+        fs::remove_file(path).unwrap();
+    }
+}
@@ -119,6 +119,7 @@ pub fn show_config(config: &config::Config, writer: &mut dyn Write) -> std::io::
         writer,
         "    max-line-distance             = {max_line_distance}
     max-line-length               = {max_line_length}
+    max-highlight-length          = {max_highlight_length}
     diff-stat-align-width         = {diff_stat_align_width}
     line-fill-method              = {line_fill_method}
     navigate                      = {navigate}
@@ -126,6 +127,7 @@ pub fn show_config(config: &config::Config, writer: &mut dyn Write) -> std::io::
     pager                         = {pager}
     paging                        = {paging_mode}
     side-by-side                  = {side_by_side}
+    strip-cr                      = {strip_cr}
     syntax-theme                  = {syntax_theme}
     width                         = {width}
     tabs                          = {tab_width}
@@ -133,6 +135,7 @@ pub fn show_config(config: &config::Config, writer: &mut dyn Write) -> std::io::
         diff_stat_align_width = config.diff_stat_align_width,
         max_line_distance = config.max_line_distance,
         max_line_length = config.max_line_length,
+        max_highlight_length = config.max_highlight_length,
         line_fill_method = match config.line_fill_method {
             BgFillMethod::TryAnsiSequence => "ansi",
             BgFillMethod::Spaces => "spaces",
@@ -150,6 +153,11 @@ pub fn show_config(config: &config::Config, writer: &mut dyn Write) -> std::io::
             PagingMode::Capture => unreachable!("capture can not be set"),
         },
         side_by_side = config.side_by_side,
+        strip_cr = match config.strip_cr {
+            config::StripCr::Auto => "auto",
+            config::StripCr::Always => "always",
+            config::StripCr::Never => "never",
+        },
         syntax_theme = config
             .syntax_theme
             .clone()
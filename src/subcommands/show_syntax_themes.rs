@@ -36,7 +36,7 @@ pub fn show_syntax_themes() -> std::io::Result<()> {
 
     let make_opt = || {
         let mut opt = cli::Opt::parse();
-        opt.computed.syntax_set = assets.get_syntax_set().unwrap().clone();
+        opt.computed.syntax_set = utils::bat::assets::get_syntax_set(&assets);
         opt
     };
     let opt = make_opt();
@@ -0,0 +1,213 @@
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use lazy_static::lazy_static;
+use regex::Regex;
+
+use crate::ansi;
+use crate::cli::OutputFormat;
+use crate::config::Config;
+use crate::delta;
+use crate::subcommands::render_html::{write_html, write_html_email};
+use crate::utils::lines;
+
+// The maximum length of the part of a file name taken from the subject of its commit.
+const MAX_SUBJECT_LENGTH: usize = 60;
+
+lazy_static! {
+    static ref COMMIT_HASH_REGEX: Regex = Regex::new(r"^commit ([0-9a-f]{7,})").unwrap();
+}
+
+/// Render each commit of the `git log -p` output on stdin to its own file in the directory
+/// (--split-output), and print the paths of the files.
+#[cfg(not(tarpaulin_include))]
+pub fn split_output(
+    dir: &Path,
+    format: OutputFormat,
+    config: &Config,
+    writer: &mut dyn Write,
+) -> io::Result<()> {
+    split_commits(io::stdin().lock(), dir, format, config, writer)
+}
+
+/// Split the input into commits, each starting with a line matching --commit-regex, and render
+/// each of them to a file in the directory. The lines before the first commit, if any, are
+/// rendered to a file of their own.
+pub fn split_commits<R: BufRead>(
+    input: R,
+    dir: &Path,
+    format: OutputFormat,
+    config: &Config,
+    writer: &mut dyn Write,
+) -> io::Result<()> {
+    fs::create_dir_all(dir).map_err(|err| with_path(err, dir))?;
+    let mut commit = String::new();
+    for line in lines::byte_lines(input) {
+        let line = String::from_utf8_lossy(&line?).into_owned();
+        if config.commit_regex.is_match(&ansi::strip_ansi_codes(&line)) {
+            write_commit(&commit, dir, format, config, writer)?;
+            commit.clear();
+        }
+        commit.push_str(&line);
+        commit.push('\n');
+    }
+    write_commit(&commit, dir, format, config, writer)
+}
+
+// Render the lines of a commit to its file, unless they are all blank.
+fn write_commit(
+    commit: &str,
+    dir: &Path,
+    format: OutputFormat,
+    config: &Config,
+    writer: &mut dyn Write,
+) -> io::Result<()> {
+    if commit.trim().is_empty() {
+        return Ok(());
+    }
+    let mut output = Vec::new();
+    delta::delta(lines::byte_lines(commit.as_bytes()), &mut output, config)?;
+    let path = dir.join(file_name(commit, format));
+    let mut file = BufWriter::new(File::create(&path).map_err(|err| with_path(err, &path))?);
+    match format {
+        OutputFormat::Terminal => file.write_all(&output)?,
+        OutputFormat::Html => write_html(&String::from_utf8_lossy(&output), config, &mut file)?,
+        OutputFormat::HtmlEmail => {
+            write_html_email(&String::from_utf8_lossy(&output), config, &mut file)?
+        }
+    }
+    file.flush()?;
+    writeln!(writer, "{}", path.display())
+}
+
+// The name of the file of a commit: its abbreviated hash and its subject, with the extension of
+// the format, e.g. "1a2b3c4d5e6f-fix-the-parser.html".
+fn file_name(commit: &str, format: OutputFormat) -> PathBuf {
+    let mut lines = commit.lines().map(ansi::strip_ansi_codes);
+    let hash = lines.next().and_then(|line| {
+        let captures = COMMIT_HASH_REGEX.captures(&line)?;
+        Some(captures[1].chars().take(12).collect::<String>())
+    });
+    // The subject is the first line of the message, which is indented and follows the header.
+    let subject = lines
+        .skip_while(|line| !line.is_empty())
+        .find(|line| !line.is_empty())
+        .and_then(|line| line.strip_prefix("    ").map(slug))
+        .unwrap_or_default();
+    let name = match (hash, subject.is_empty()) {
+        (Some(hash), true) => hash,
+        (Some(hash), false) => format!("{hash}-{subject}"),
+        (None, _) => "diff".to_string(),
+    };
+    let extension = match format {
+        OutputFormat::Terminal => "txt",
+        OutputFormat::Html | OutputFormat::HtmlEmail => "html",
+    };
+    PathBuf::from(name).with_extension(extension)
+}
+
+// The words of the text, in lower case, separated by hyphens, e.g. "fix-the-parser".
+fn slug(text: &str) -> String {
+    let mut slug = String::new();
+    for word in text
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty())
+    {
+        if slug.len() + word.len() >= MAX_SUBJECT_LENGTH {
+            break;
+        }
+        if !slug.is_empty() {
+            slug.push('-');
+        }
+        slug.push_str(&word.to_ascii_lowercase());
+    }
+    slug
+}
+
+fn with_path(err: io::Error, path: &Path) -> io::Error {
+    io::Error::new(err.kind(), format!("{}: {err}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::integration_test_utils::make_config_from_args;
+    use crate::tests::test_utils::temp_path;
+
+    #[test]
+    fn test_split_output() {
+        let dir = temp_path("test_split_output");
+        let _ = fs::remove_dir_all(&dir);
+        let config = make_config_from_args(&[]);
+        let mut paths = Vec::new();
+        split_commits(
+            LOG.as_bytes(),
+            &dir,
+            OutputFormat::Html,
+            &config,
+            &mut paths,
+        )
+        .unwrap();
+        let paths = String::from_utf8(paths).unwrap();
+        let names: Vec<_> = paths
+            .lines()
+            .map(|path| Path::new(path).file_name().unwrap().to_str().unwrap())
+            .collect();
+        assert_eq!(
+            names,
+            [
+                "1111111111aa-fix-the-parser-s-handling-of-tabs.html",
+                "2222222222bb.html"
+            ]
+        );
+        let first = fs::read_to_string(dir.join(names[0])).unwrap();
+        assert!(first.starts_with("<!DOCTYPE html>"));
+        assert!(first.contains("a.rs"));
+        assert!(!first.contains("b.rs"));
+        let second = fs::read_to_string(dir.join(names[1])).unwrap();
+        assert!(second.contains("b.rs"));
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_file_name() {
+        assert_eq!(
+            file_name("diff --git a/a.rs b/a.rs\n", OutputFormat::Terminal),
+            PathBuf::from("diff.txt")
+        );
+        assert_eq!(
+            slug("Add --split-output (writes one file per commit) and a very long subject line"),
+            "add-split-output-writes-one-file-per-commit-and-a-very-long"
+        );
+    }
+
+    const LOG: &str = "\
+commit 1111111111aaaaaaaaaaaaaaaaaaaaaaaaaaaaaa (HEAD -> main)
+Author: A <a@example.com>
+Date:   Thu Jan 1 00:00:00 2026 +0000
+
+    Fix the parser's handling of tabs
+
+    The body.
+
+diff --git a/a.rs b/a.rs
+index 1111111..2222222 100644
+--- a/a.rs
++++ b/a.rs
+@@ -1 +1 @@
+-a
++b
+commit 2222222222bbbbbbbbbbbbbbbbbbbbbbbbbbbbbb
+Author: A <a@example.com>
+Date:   Thu Jan 1 00:00:00 2026 +0000
+
+diff --git a/b.rs b/b.rs
+index 1111111..2222222 100644
+--- a/b.rs
++++ b/b.rs
+@@ -1 +1 @@
+-c
++d
+";
+}
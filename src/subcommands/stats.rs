@@ -0,0 +1,438 @@
+use std::collections::HashMap;
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::path::Path;
+
+use ansi_term::{Color, Style};
+use lazy_static::lazy_static;
+use regex::Regex;
+
+use crate::ansi;
+use crate::config::Config;
+use crate::paint::Painter;
+use crate::utils::locale::Locale;
+
+const BAR_WIDTH: usize = 20;
+const N_LARGEST_FILES: usize = 10;
+
+/// The number of added and removed lines of a file in the diff.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct FileStats {
+    pub path: String,
+    pub additions: usize,
+    pub deletions: usize,
+}
+
+/// Totals for a group of files (a directory, an extension, or a language).
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct GroupStats {
+    pub name: String,
+    pub files: usize,
+    pub additions: usize,
+    pub deletions: usize,
+}
+
+lazy_static! {
+    static ref HUNK_HEADER_REGEX: Regex =
+        Regex::new(r"^@@ -\d+(?:,(\d+))? \+\d+(?:,(\d+))? @@").unwrap();
+}
+
+/// Read the diff on stdin and write a report of the number of changed lines per directory, file
+/// extension and language, and of the files with the most changes.
+#[cfg(not(tarpaulin_include))]
+pub fn stats(config: &Config, writer: &mut dyn Write) -> io::Result<()> {
+    let files = collect_file_stats(io::stdin().lock())?;
+    let color = io::stdout().is_terminal();
+    write_report(&files, config, color, writer)
+}
+
+/// Count the added and removed lines of each file of a diff. The hunk lengths are used to tell
+/// the lines of a hunk from the header of the next file.
+pub fn collect_file_stats<R: BufRead>(input: R) -> io::Result<Vec<FileStats>> {
+    let mut files: Vec<FileStats> = Vec::new();
+    // The numbers of lines of the old and new file remaining in the current hunk.
+    let (mut old_remaining, mut new_remaining) = (0, 0);
+    for line in input.lines() {
+        let line = line?;
+        let line = ansi::strip_ansi_codes(&line);
+        if old_remaining > 0 || new_remaining > 0 {
+            if let Some(file) = files.last_mut() {
+                match line.chars().next() {
+                    Some('-') => {
+                        file.deletions += 1;
+                        old_remaining -= 1;
+                    }
+                    Some('+') => {
+                        file.additions += 1;
+                        new_remaining -= 1;
+                    }
+                    Some('\\') => {}
+                    _ => {
+                        old_remaining -= 1;
+                        new_remaining -= 1;
+                    }
+                }
+                continue;
+            }
+        }
+        if let Some(caps) = HUNK_HEADER_REGEX.captures(&line) {
+            let length = |i| caps.get(i).map_or(1, |m| m.as_str().parse().unwrap_or(0));
+            old_remaining = length(1);
+            new_remaining = length(2);
+        } else if let Some(paths) = line.strip_prefix("diff --git ") {
+            let path = paths.rsplit_once(" b/").map_or(paths, |(_, path)| path);
+            files.push(FileStats {
+                path: path.to_string(),
+                ..FileStats::default()
+            });
+        } else if let Some(path) = line.strip_prefix("+++ ") {
+            let path = path.split('\t').next().unwrap_or_default();
+            if path != "/dev/null" {
+                set_path(&mut files, path.strip_prefix("b/").unwrap_or(path));
+            }
+        } else if let Some(path) = line.strip_prefix("--- ") {
+            let path = path.split('\t').next().unwrap_or_default();
+            // The path of a removed file; replaced by the +++ line otherwise.
+            if path != "/dev/null" {
+                set_path(&mut files, path.strip_prefix("a/").unwrap_or(path));
+            }
+        } else if let Some(path) = line.strip_prefix("rename to ") {
+            set_path(&mut files, path);
+        }
+    }
+    Ok(files)
+}
+
+fn set_path(files: &mut Vec<FileStats>, path: &str) {
+    match files.last_mut() {
+        // A diff without "diff --git" lines, such as the output of diff -u.
+        Some(file) if file.additions + file.deletions == 0 => file.path = path.to_string(),
+        _ => files.push(FileStats {
+            path: path.to_string(),
+            ..FileStats::default()
+        }),
+    }
+}
+
+/// Sum the stats of files grouped by `key`, in decreasing order of the number of changed lines.
+pub fn group_by<F>(files: &[FileStats], key: F) -> Vec<GroupStats>
+where
+    F: Fn(&FileStats) -> String,
+{
+    let mut groups: HashMap<String, GroupStats> = HashMap::new();
+    for file in files {
+        let name = key(file);
+        let group = groups.entry(name.clone()).or_insert_with(|| GroupStats {
+            name,
+            ..GroupStats::default()
+        });
+        group.files += 1;
+        group.additions += file.additions;
+        group.deletions += file.deletions;
+    }
+    let mut groups: Vec<_> = groups.into_values().collect();
+    groups.sort_by(|a, b| {
+        (b.additions + b.deletions)
+            .cmp(&(a.additions + a.deletions))
+            .then_with(|| a.name.cmp(&b.name))
+    });
+    groups
+}
+
+fn directory(file: &FileStats) -> String {
+    match Path::new(&file.path).parent().and_then(|dir| dir.to_str()) {
+        Some(dir) if !dir.is_empty() => format!("{dir}/"),
+        _ => "./".to_string(),
+    }
+}
+
+fn extension(file: &FileStats) -> String {
+    match Path::new(&file.path)
+        .extension()
+        .and_then(|ext| ext.to_str())
+    {
+        Some(ext) => format!(".{ext}"),
+        None => "(none)".to_string(),
+    }
+}
+
+pub fn write_report(
+    files: &[FileStats],
+    config: &Config,
+    color: bool,
+    writer: &mut dyn Write,
+) -> io::Result<()> {
+    let paint = |style: Style, text: &str| {
+        if color {
+            style.paint(text).to_string()
+        } else {
+            text.to_string()
+        }
+    };
+    let additions: usize = files.iter().map(|file| file.additions).sum();
+    let deletions: usize = files.iter().map(|file| file.deletions).sum();
+    writeln!(
+        writer,
+        "{} file{} changed, {} insertion{}(+), {} deletion{}(-)",
+        config.locale.format_number(files.len()),
+        plural(files.len()),
+        config.locale.format_number(additions),
+        plural(additions),
+        config.locale.format_number(deletions),
+        plural(deletions),
+    )?;
+
+    let language = |file: &FileStats| {
+        Painter::get_syntax(
+            &config.syntax_set,
+            Some(&file.path),
+            &config.default_language,
+        )
+        .name
+        .clone()
+    };
+    let mut largest_files: Vec<_> = files
+        .iter()
+        .map(|file| GroupStats {
+            name: file.path.clone(),
+            files: 1,
+            additions: file.additions,
+            deletions: file.deletions,
+        })
+        .collect();
+    largest_files.sort_by_key(|file| std::cmp::Reverse(file.additions + file.deletions));
+    largest_files.truncate(N_LARGEST_FILES);
+
+    for (title, groups, show_files) in [
+        ("Directories", group_by(files, directory), true),
+        ("Extensions", group_by(files, extension), true),
+        ("Languages", group_by(files, language), true),
+        ("Largest files", largest_files, false),
+    ] {
+        writeln!(writer)?;
+        writeln!(writer, "{}", paint(Style::new().bold(), title))?;
+        write_table(&groups, show_files, &config.locale, &paint, writer)?;
+    }
+    Ok(())
+}
+
+fn write_table<F>(
+    groups: &[GroupStats],
+    show_files: bool,
+    locale: &Locale,
+    paint: &F,
+    writer: &mut dyn Write,
+) -> io::Result<()>
+where
+    F: Fn(Style, &str) -> String,
+{
+    let name_width = groups.iter().map(|g| g.name.len()).max().unwrap_or(0);
+    // The widths in characters, since a thousands separator may take several bytes.
+    let width = |n: usize| locale.format_number(n).chars().count();
+    let files_width = groups.iter().map(|g| width(g.files)).max().unwrap_or(0);
+    let additions_width = groups
+        .iter()
+        .map(|g| width(g.additions) + 1)
+        .max()
+        .unwrap_or(0);
+    let deletions_width = groups
+        .iter()
+        .map(|g| width(g.deletions) + 1)
+        .max()
+        .unwrap_or(0);
+    let max_changes = groups
+        .iter()
+        .map(|g| g.additions + g.deletions)
+        .max()
+        .unwrap_or(0);
+    for group in groups {
+        let files = if show_files {
+            format!(
+                "  {:>files_width$} file{:1}",
+                locale.format_number(group.files),
+                plural(group.files)
+            )
+        } else {
+            String::new()
+        };
+        let bar = match bar(group.additions, group.deletions, max_changes) {
+            (plus_bar, minus_bar) if plus_bar.len() + minus_bar.len() > 0 => format!(
+                "  {}{}",
+                paint(Color::Green.normal(), &plus_bar),
+                paint(Color::Red.normal(), &minus_bar)
+            ),
+            _ => String::new(),
+        };
+        writeln!(
+            writer,
+            "  {:name_width$}{}  {}  {}{}",
+            group.name,
+            files,
+            paint(
+                Color::Green.normal(),
+                &format!(
+                    "{:>additions_width$}",
+                    format!("+{}", locale.format_number(group.additions))
+                )
+            ),
+            paint(
+                Color::Red.normal(),
+                &format!(
+                    "{:>deletions_width$}",
+                    format!("-{}", locale.format_number(group.deletions))
+                )
+            ),
+            bar,
+        )?;
+    }
+    Ok(())
+}
+
+/// A bar of + and - characters, scaled so that the largest group fills the bar.
+fn bar(additions: usize, deletions: usize, max_changes: usize) -> (String, String) {
+    if max_changes == 0 {
+        return (String::new(), String::new());
+    }
+    let scale = |n: usize| (n * BAR_WIDTH).div_ceil(max_changes);
+    ("+".repeat(scale(additions)), "-".repeat(scale(deletions)))
+}
+
+fn plural(n: usize) -> &'static str {
+    if n == 1 {
+        ""
+    } else {
+        "s"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::integration_test_utils::make_config_from_args;
+
+    const DIFF: &str = "\
+diff --git a/src/main.rs b/src/main.rs
+index 1111111..2222222 100644
+--- a/src/main.rs
++++ b/src/main.rs
+@@ -1,3 +1,4 @@
+ fn main() {
+-    println!(\"hello\");
++    println!(\"hello, world\");
++    println!(\"bye\");
+ }
+@@ -10 +11 @@
+--- a comment which looks like a header
++++ a comment which looks like a header
+diff --git a/src/lib.rs b/src/lib.rs
+new file mode 100644
+index 0000000..3333333
+--- /dev/null
++++ b/src/lib.rs
+@@ -0,0 +1,2 @@
++pub mod a;
++pub mod b;
+diff --git a/README.md b/README.md
+deleted file mode 100644
+index 4444444..0000000
+--- a/README.md
++++ /dev/null
+@@ -1 +0,0 @@
+-# Title
+diff --git a/old.py b/new.py
+similarity index 100%
+rename from old.py
+rename to new.py
+";
+
+    #[test]
+    fn test_collect_file_stats() {
+        let files = collect_file_stats(DIFF.as_bytes()).unwrap();
+        let stats = |path: &str, additions, deletions| FileStats {
+            path: path.to_string(),
+            additions,
+            deletions,
+        };
+        assert_eq!(
+            files,
+            vec![
+                stats("src/main.rs", 3, 2),
+                stats("src/lib.rs", 2, 0),
+                stats("README.md", 0, 1),
+                stats("new.py", 0, 0),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_collect_file_stats_diff_u() {
+        let input = "\
+--- a.txt\t2024-01-01
++++ b.txt\t2024-01-02
+@@ -1 +1 @@
+-a
++b
+";
+        let files = collect_file_stats(input.as_bytes()).unwrap();
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].path, "b.txt");
+        assert_eq!((files[0].additions, files[0].deletions), (1, 1));
+    }
+
+    #[test]
+    fn test_group_by() {
+        let files = collect_file_stats(DIFF.as_bytes()).unwrap();
+        let names = |groups: Vec<GroupStats>| {
+            groups
+                .into_iter()
+                .map(|g| (g.name, g.files, g.additions, g.deletions))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            names(group_by(&files, directory)),
+            vec![("src/".to_string(), 2, 5, 2), ("./".to_string(), 2, 0, 1)]
+        );
+        assert_eq!(
+            names(group_by(&files, extension)),
+            vec![
+                (".rs".to_string(), 2, 5, 2),
+                (".md".to_string(), 1, 0, 1),
+                (".py".to_string(), 1, 0, 0),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_write_report() {
+        let config = make_config_from_args(&[]);
+        let files = collect_file_stats(DIFF.as_bytes()).unwrap();
+        let mut output = Vec::new();
+        write_report(&files, &config, false, &mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert_eq!(
+            output,
+            "\
+4 files changed, 5 insertions(+), 3 deletions(-)
+
+Directories
+  src/  2 files  +5  -2  +++++++++++++++------
+  ./    2 files  +0  -1  ---
+
+Extensions
+  .rs  2 files  +5  -2  +++++++++++++++------
+  .md  1 file   +0  -1  ---
+  .py  1 file   +0  -0
+
+Languages
+  Rust      2 files  +5  -2  +++++++++++++++------
+  Markdown  1 file   +0  -1  ---
+  Python    1 file   +0  -0
+
+Largest files
+  src/main.rs  +3  -2  ++++++++++++--------
+  src/lib.rs   +2  -0  ++++++++
+  README.md    +0  -1  ----
+  new.py       +0  -0
+"
+        );
+    }
+}
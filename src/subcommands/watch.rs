@@ -0,0 +1,130 @@
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+use crate::config;
+use crate::subcommands::diff::diff;
+
+/// Set when delta is interrupted (Ctrl-C), which ends --watch.
+pub static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Display the diff of the two files or directories on a cleared screen, each time either of them
+/// changes, until delta is interrupted (--watch). Return the exit code of the last diff.
+pub fn watch(
+    minus_file: &Path,
+    plus_file: &Path,
+    config: &config::Config,
+    writer: &mut dyn Write,
+) -> i32 {
+    loop {
+        // Taken before diffing, so that changes made meanwhile cause the diff to be redisplayed.
+        let modifications = Modifications::of(&[minus_file, plus_file]);
+        if write!(writer, "\x1b[2J\x1b[H").is_err() {
+            return config.error_exit_code;
+        }
+        let exit_code = diff(minus_file, plus_file, config, writer);
+        let _ = writer.flush();
+        while modifications == Modifications::of(&[minus_file, plus_file]) {
+            if INTERRUPTED.load(Ordering::Relaxed) {
+                return exit_code;
+            }
+            thread::sleep(POLL_INTERVAL);
+        }
+    }
+}
+
+/// The path, modification time and size of the files, and of the files in the directories, which
+/// change when a file is modified, added or removed.
+#[derive(Debug, PartialEq, Eq)]
+struct Modifications(Vec<(PathBuf, Option<SystemTime>, u64)>);
+
+impl Modifications {
+    fn of(paths: &[&Path]) -> Self {
+        let mut modifications = Vec::new();
+        for path in paths {
+            collect_modifications(path, &mut modifications);
+        }
+        Self(modifications)
+    }
+}
+
+fn collect_modifications(path: &Path, modifications: &mut Vec<(PathBuf, Option<SystemTime>, u64)>) {
+    let metadata = fs::metadata(path).ok();
+    if metadata.as_ref().is_some_and(|metadata| metadata.is_dir()) {
+        let mut entries = fs::read_dir(path)
+            .map(|entries| {
+                entries
+                    .filter_map(|entry| entry.ok())
+                    .map(|entry| entry.path())
+            })
+            .map(Iterator::collect::<Vec<_>>)
+            .unwrap_or_default();
+        entries.sort();
+        for entry in entries {
+            collect_modifications(&entry, modifications);
+        }
+    } else {
+        modifications.push((
+            path.to_path_buf(),
+            metadata
+                .as_ref()
+                .and_then(|metadata| metadata.modified().ok()),
+            metadata.map_or(0, |metadata| metadata.len()),
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+    use crate::tests::integration_test_utils;
+    use crate::tests::test_utils::temp_path;
+
+    #[test]
+    fn test_modifications() {
+        let dir = temp_path("test_watch_modifications");
+        fs::create_dir_all(dir.join("sub")).unwrap();
+        fs::write(dir.join("sub/a"), "a").unwrap();
+        let modifications = Modifications::of(&[&dir]);
+        assert_eq!(modifications, Modifications::of(&[&dir]));
+
+        fs::write(dir.join("sub/a"), "a longer").unwrap();
+        let modifications_after_write = Modifications::of(&[&dir]);
+        assert_ne!(modifications, modifications_after_write);
+
+        fs::write(dir.join("b"), "b").unwrap();
+        assert_ne!(modifications_after_write, Modifications::of(&[&dir]));
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[test]
+    fn test_watch_until_interrupted() {
+        let dir = temp_path("test_watch_until_interrupted");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("old.txt"), "a\n").unwrap();
+        fs::write(dir.join("new.txt"), "b\n").unwrap();
+        let config = integration_test_utils::make_config_from_args(&[]);
+        let mut writer = Cursor::new(vec![]);
+        INTERRUPTED.store(true, Ordering::Relaxed);
+        let exit_code = watch(
+            &dir.join("old.txt"),
+            &dir.join("new.txt"),
+            &config,
+            &mut writer,
+        );
+        INTERRUPTED.store(false, Ordering::Relaxed);
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(exit_code, 1);
+        let output = String::from_utf8(writer.into_inner()).unwrap();
+        assert!(output.starts_with("\x1b[2J\x1b[H"));
+        assert!(output.contains("new.txt"));
+    }
+}
@@ -220,7 +220,7 @@ pub mod ansi_test_utils {
             None,
             None,
             config.true_color,
-            config.git_config.as_ref(),
+            config.git_config(),
         );
         style.get_matching_substring(line)
     }
@@ -5,7 +5,6 @@ use std::fs::File;
 use std::io::{BufReader, Write};
 use std::path::Path;
 
-use bytelines::ByteLines;
 use itertools::Itertools;
 
 use crate::ansi;
@@ -15,6 +14,7 @@ use crate::delta::delta;
 use crate::env::DeltaEnv;
 use crate::git_config::GitConfig;
 use crate::tests::test_utils;
+use crate::utils::lines;
 use crate::utils::process::tests::FakeParentArgs;
 
 pub fn make_options_from_args_and_git_config(
@@ -303,7 +303,7 @@ pub fn run_delta(input: &str, config: &config::Config) -> String {
     let mut writer: Vec<u8> = Vec::new();
 
     delta(
-        ByteLines::new(BufReader::new(input.as_bytes())),
+        lines::byte_lines(BufReader::new(input.as_bytes())),
         &mut writer,
         config,
     )
@@ -1900,6 +1900,21 @@ src/align.rs:71: impl<'a> Alignment<'a> { │
         ));
     }
 
+    #[test]
+    fn test_raw_line_styles_keep_unknown_sgr_parameters() {
+        let config = integration_test_utils::make_config_from_args(&[]);
+        let input = GIT_DIFF_UNDER_COLOR_MOVED_DIMMED_ZEBRA_WITH_ANSI_ESCAPE_SEQUENCES
+            .replace("\x1b[2m-    \"stddev\"", "\x1b[2;53m-    \"stddev\"");
+        let output = integration_test_utils::run_delta(&input, &config);
+        let line = output
+            .lines()
+            .find(|line| strip_ansi_codes(line).contains("\"stddev\": 0.004157057519168492,"))
+            .unwrap();
+        // Overline (53) cannot be represented by ansi_term, but is emitted again.
+        assert!(line.contains("\x1b[2m\x1b[53m"), "{:?}", line);
+        assert!(line.ends_with("\x1b[0m"));
+    }
+
     #[test]
     fn test_file_mode_change_with_rename() {
         let config = integration_test_utils::make_config_from_args(&["--right-arrow=->"]);
@@ -1994,6 +2009,53 @@ src/align.rs:71: impl<'a> Alignment<'a> { │
             .expect_after_header("#partial\n\nremoved: a");
     }
 
+    #[test]
+    fn test_log_line_range_without_diff_line() {
+        DeltaTest::with_args(&["--line-numbers", "--file-style", "omit"])
+            .with_input(GIT_LOG_LINE_RANGE_WITHOUT_DIFF_LINE)
+            .expect(
+                "
+#indent_mark
+commit a02be8e8a08d4bd95f105f8261d71baffdd80f53
+Author: A <a@b>
+Date:   Thu Oct 15 04:57:02 2026 +0000
+
+    two
+
+
+───┐
+5: │
+───┘
+  5 ⋮  5 │fn b() {
+  6 ⋮    │    2
+    ⋮  6 │    22
+  7 ⋮  7 │}
+
+commit 715b4b57ec6d26900510b82ac2d5fa6301f30c3a
+Author: A <a@b>
+Date:   Thu Oct 15 04:57:02 2026 +0000
+
+    one
+
+
+───┐
+1: │
+───┘
+    ⋮  1 │fn a() {
+    ⋮  2 │    1
+    ⋮  3 │}",
+            );
+    }
+
+    #[test]
+    fn test_log_commits_changing_the_same_file() {
+        let config = integration_test_utils::make_config_from_args(&[]);
+        let output =
+            integration_test_utils::run_delta(GIT_LOG_COMMITS_CHANGING_THE_SAME_FILE, &config);
+        let output = strip_ansi_codes(&output);
+        assert_eq!(output.lines().filter(|line| *line == "f.rs").count(), 2);
+    }
+
     #[test]
     fn test_lines_with_syntax_width_limit() {
         let result = DeltaTest::with_args(&[
@@ -2137,6 +2199,64 @@ src/align.rs:71: impl<'a> Alignment<'a> { │
         "###);
     }
 
+    #[test]
+    fn test_lines_longer_than_max_highlight_length() {
+        let result = DeltaTest::with_args(&["--max-highlight-length=10", "--max-line-length=0"])
+            .explain_ansi()
+            .with_input(GIT_DIFF_ALL_UNICODE_W_FULLWIDTH);
+
+        // Shown in full, but without syntax or emph highlighting.
+        assert_snapshot!(result.output, @r###"
+        (normal)
+
+        (blue)src/a(normal)
+        (blue)───────────────────────────────────────────(normal)
+
+        (blue)───(blue)┐(normal)
+        (blue)1(normal): (blue)│(normal)
+        (blue)───(blue)┘(normal)
+        一æäöø€ÆÄÖ〇Øß一
+        一æäöø€ÆÄÖ〇Øß一
+        (normal 52)二æäöø¢ÆÄÖ〇Øß二(normal)
+        (normal 52)二æäöø¢ÆÄÖ〇Øß二(normal)
+        (normal 22)二æäöø€ÆÄÖ〇Øß二(normal)
+        (normal 22)二æäöø€ÆÄÖ〇Øß二(normal)
+        三æäöø€ÆÄÖ〇Øß三
+        三æäöø€ÆÄÖ〇Øß三
+        (231)¶(normal)
+        "###);
+    }
+
+    #[test]
+    fn test_input_type() {
+        let blame_line =
+            "b2257cfa (Dan Davison  2020-07-18 15:34:43 -0400   1) use std::borrow::Cow;\n";
+        let input = blame_line.to_string() + GIT_DIFF_SINGLE_HUNK;
+
+        let output = DeltaTest::with_args(&[]).with_input(&input).output;
+        assert!(!output.contains(blame_line));
+        assert!(!output.contains("diff --git"));
+
+        // The blame line is not handled in a diff.
+        let output = DeltaTest::with_args(&["--input-type=diff"])
+            .with_input(&input)
+            .output;
+        assert!(output.starts_with(blame_line));
+        assert!(!output.contains("diff --git"));
+
+        // Nor is the diff in blame output.
+        let output = DeltaTest::with_args(&["--input-type=blame"])
+            .with_input(&input)
+            .output;
+        assert!(!output.contains(blame_line));
+        assert!(output.contains("diff --git"));
+
+        let output = DeltaTest::with_args(&["--input-type=raw"])
+            .with_input(&input)
+            .output;
+        assert_eq!(output, input);
+    }
+
     const GIT_DIFF_SINGLE_HUNK: &str = "\
 commit 94907c0f136f46dc46ffae2dc92dca9af7eb7c2e
 Author: Dan Davison <dandavison7@gmail.com>
@@ -3109,6 +3229,69 @@ new file mode 100644
 index 0000000..e69de29
 ";
 
+    const GIT_LOG_LINE_RANGE_WITHOUT_DIFF_LINE: &str = "\
+commit a02be8e8a08d4bd95f105f8261d71baffdd80f53
+Author: A <a@b>
+Date:   Thu Oct 15 04:57:02 2026 +0000
+
+    two
+
+--- a/f.rs
++++ b/f.rs
+@@ -5,3 +5,3 @@
+ fn b() {
+-    2
++    22
+ }
+
+commit 715b4b57ec6d26900510b82ac2d5fa6301f30c3a
+Author: A <a@b>
+Date:   Thu Oct 15 04:57:02 2026 +0000
+
+    one
+
+--- /dev/null
++++ b/f.rs
+@@ -0,0 +1,3 @@
++fn a() {
++    1
++}
+";
+
+    const GIT_LOG_COMMITS_CHANGING_THE_SAME_FILE: &str = "\
+commit a02be8e8a08d4bd95f105f8261d71baffdd80f53
+Author: A <a@b>
+Date:   Thu Oct 15 04:57:02 2026 +0000
+
+    two
+
+diff --git a/f.rs b/f.rs
+index 5e2f1a4..8d1a7f3 100644
+--- a/f.rs
++++ b/f.rs
+@@ -1,3 +1,3 @@
+ fn a() {
+-    1
++    2
+ }
+
+commit 715b4b57ec6d26900510b82ac2d5fa6301f30c3a
+Author: A <a@b>
+Date:   Thu Oct 15 04:57:02 2026 +0000
+
+    one
+
+diff --git a/f.rs b/f.rs
+index 2c4f1d0..5e2f1a4 100644
+--- a/f.rs
++++ b/f.rs
+@@ -1,3 +1,3 @@
+ fn a() {
+-    0
++    1
+ }
+";
+
     const GIT_DIFF_ALL_UNICODE_W_FULLWIDTH: &str = "
 diff --git a/src/a b/src/a
 index 53f98b6..14d6caa 100644
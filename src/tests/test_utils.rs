@@ -7,6 +7,12 @@ pub fn contains_once(s: &str, t: &str) -> bool {
     }
 }
 
+/// A path in the temporary directory which is unique to the test `name` and to this run of the
+/// tests, so that tests run concurrently do not share files.
+pub fn temp_path(name: &str) -> std::path::PathBuf {
+    std::env::temp_dir().join(format!("delta__{name}_{}", std::process::id()))
+}
+
 #[allow(dead_code)]
 pub fn print_with_line_numbers(s: &str) {
     for (i, t) in s.lines().enumerate() {
@@ -2,23 +2,65 @@
 // See src/utils/bat/LICENSE
 
 use std::io::{self, Write};
+use std::path::Path;
 
 use ansi_term::Colour::Green;
 use ansi_term::Style;
-use bat;
+use bat::assets::HighlightingAssets;
+use bat::assets_metadata::AssetsMetadata;
+#[cfg(feature = "minimal-syntax-set")]
+use lazy_static::lazy_static;
+use syntect::parsing::SyntaxSet;
 
 use crate::utils;
 
-pub fn load_highlighting_assets() -> bat::assets::HighlightingAssets {
-    bat::assets::HighlightingAssets::from_cache(utils::bat::dirs::PROJECT_DIRS.cache_dir())
-        .unwrap_or_else(|_| bat::assets::HighlightingAssets::from_binary())
+// The version of the bat library delta is built against, as locked in Cargo.lock (see build.rs).
+// Assets cached by a bat executable of a different minor version may not be readable.
+const BAT_VERSION: &str = env!("BAT_VERSION");
+
+pub fn load_highlighting_assets() -> HighlightingAssets {
+    load_highlighting_assets_from(utils::bat::dirs::PROJECT_DIRS.cache_dir())
+}
+
+/// Load the syntaxes and themes that the user has built with `bat cache --build`, falling back to
+/// those included in the binary if there are none, or if they were built by an incompatible bat.
+/// The cache metadata is consulted on every run, so newly built assets are picked up immediately.
+fn load_highlighting_assets_from(cache_dir: &Path) -> HighlightingAssets {
+    match AssetsMetadata::load_from_folder(cache_dir) {
+        Ok(Some(metadata)) if metadata.is_compatible_with(BAT_VERSION) => {
+            HighlightingAssets::from_cache(cache_dir)
+                .unwrap_or_else(|_| HighlightingAssets::from_binary())
+        }
+        _ => HighlightingAssets::from_binary(),
+    }
+}
+
+#[cfg(feature = "minimal-syntax-set")]
+lazy_static! {
+    static ref MINIMAL_SYNTAX_SET: SyntaxSet = syntect::dumps::from_uncompressed_data(
+        include_bytes!(concat!(env!("OUT_DIR"), "/minimal_syntaxes.bin"))
+    )
+    .unwrap();
+}
+
+/// The syntaxes of `assets`. If delta was built with the `minimal-syntax-set` feature, those of a
+/// curated set of common languages included in the binary instead, deserialized once per process,
+/// so that the complete set is never deserialized (it is still included in the binary by bat).
+pub fn get_syntax_set(assets: &HighlightingAssets) -> SyntaxSet {
+    #[cfg(feature = "minimal-syntax-set")]
+    {
+        let _ = assets;
+        MINIMAL_SYNTAX_SET.clone()
+    }
+    #[cfg(not(feature = "minimal-syntax-set"))]
+    assets.get_syntax_set().unwrap().clone()
 }
 
 pub fn list_languages() -> std::io::Result<()> {
     let assets = utils::bat::assets::load_highlighting_assets();
-    let mut languages = assets
-        .get_syntaxes()
-        .unwrap()
+    let syntax_set = get_syntax_set(&assets);
+    let mut languages = syntax_set
+        .syntaxes()
         .iter()
         .filter(|syntax| !syntax.hidden && !syntax.file_extensions.is_empty())
         .collect::<Vec<_>>();
@@ -79,3 +121,44 @@ pub fn list_languages() -> std::io::Result<()> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::load_highlighting_assets_from;
+    use crate::tests::test_utils::temp_path;
+
+    #[test]
+    fn test_load_highlighting_assets_ignores_incompatible_cache() {
+        let cache_dir = temp_path("test_incompatible_bat_cache");
+        fs::create_dir_all(&cache_dir).unwrap();
+        fs::write(cache_dir.join("metadata.yaml"), "bat_version: 0.1.0\n").unwrap();
+        // A cache that cannot be deserialized must not be used.
+        fs::write(cache_dir.join("syntaxes.bin"), "not a syntax set").unwrap();
+        fs::write(cache_dir.join("themes.bin"), "not a theme set").unwrap();
+
+        let assets = load_highlighting_assets_from(&cache_dir);
+        assert!(assets.get_syntax_set().is_ok());
+        assert!(assets.themes().any(|theme| theme == "GitHub"));
+
+        fs::remove_dir_all(&cache_dir).unwrap();
+    }
+
+    #[test]
+    #[cfg(feature = "minimal-syntax-set")]
+    fn test_minimal_syntax_set() {
+        let syntax_set = super::get_syntax_set(&bat::assets::HighlightingAssets::from_binary());
+        for extension in ["rs", "py", "md", "html"] {
+            assert!(syntax_set.find_syntax_by_extension(extension).is_some());
+        }
+        assert!(syntax_set.find_syntax_by_extension("hs").is_none());
+    }
+
+    #[test]
+    fn test_load_highlighting_assets_without_cache() {
+        let cache_dir = temp_path("test_missing_bat_cache");
+        let assets = load_highlighting_assets_from(&cache_dir);
+        assert!(assets.get_syntax_set().is_ok());
+    }
+}
@@ -2,6 +2,7 @@
 // See src/utils/bat/LICENSE
 
 use lazy_static::lazy_static;
+use std::ffi::OsString;
 use std::path::{Path, PathBuf};
 
 #[cfg(target_os = "macos")]
@@ -10,12 +11,18 @@ use std::env;
 /// Wrapper for 'dirs' that treats MacOS more like Linux, by following the XDG specification.
 /// This means that the `XDG_CACHE_HOME` and `XDG_CONFIG_HOME` environment variables are
 /// checked first. The fallback directories are `~/.cache/bat` and `~/.config/bat`, respectively.
+/// As in bat, the cache directory is that of the `BAT_CACHE_PATH` environment variable if it is
+/// set.
 pub struct BatProjectDirs {
     cache_dir: PathBuf,
 }
 
 impl BatProjectDirs {
     fn new() -> Option<BatProjectDirs> {
+        Self::with_bat_cache_path(std::env::var_os("BAT_CACHE_PATH"))
+    }
+
+    fn with_bat_cache_path(bat_cache_path: Option<OsString>) -> Option<BatProjectDirs> {
         #[cfg(target_os = "macos")]
         let cache_dir_op = env::var_os("XDG_CACHE_HOME")
             .map(PathBuf::from)
@@ -25,7 +32,9 @@ impl BatProjectDirs {
         #[cfg(not(target_os = "macos"))]
         let cache_dir_op = dirs::cache_dir();
 
-        let cache_dir = cache_dir_op.map(|d| d.join("bat"))?;
+        let cache_dir = bat_cache_path
+            .map(PathBuf::from)
+            .or_else(|| cache_dir_op.map(|d| d.join("bat")))?;
 
         Some(BatProjectDirs { cache_dir })
     }
@@ -39,3 +48,17 @@ lazy_static! {
     pub static ref PROJECT_DIRS: BatProjectDirs =
         BatProjectDirs::new().unwrap_or_else(|| panic!("Could not get home directory"));
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bat_cache_path() {
+        let dirs = BatProjectDirs::with_bat_cache_path(Some("/tmp/bat-cache".into())).unwrap();
+        assert_eq!(dirs.cache_dir(), Path::new("/tmp/bat-cache"));
+        if let Some(dirs) = BatProjectDirs::with_bat_cache_path(None) {
+            assert!(dirs.cache_dir().ends_with("bat"));
+        }
+    }
+}
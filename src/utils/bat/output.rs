@@ -3,7 +3,7 @@
 // See src/utils/bat/LICENSE
 use std::ffi::OsString;
 use std::io::{self, Write};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::{Child, Command, Stdio};
 
 use super::less::retrieve_less_version;
@@ -12,20 +12,27 @@ use crate::config;
 use crate::env::DeltaEnv;
 use crate::fatal;
 use crate::features::navigate;
+use crate::utils;
 
 #[derive(Debug, Default)]
 pub struct PagerCfg {
     pub navigate: bool,
+    pub navigate_prefill_search: bool,
     pub show_themes: bool,
     pub navigate_regex: Option<String>,
+    pub navigate_file_regex: Option<String>,
+    pub navigate_keymap: Vec<(String, navigate::NavigateAction)>,
 }
 
 impl From<&config::Config> for PagerCfg {
     fn from(cfg: &config::Config) -> Self {
         PagerCfg {
             navigate: cfg.navigate,
+            navigate_prefill_search: cfg.navigate_prefill_search,
             show_themes: cfg.show_themes,
             navigate_regex: cfg.navigate_regex.clone(),
+            navigate_file_regex: cfg.navigate_file_regex.clone(),
+            navigate_keymap: cfg.navigate_keymap.clone(),
         }
     }
 }
@@ -177,7 +184,13 @@ fn _make_process_from_less_path(
     quit_if_one_screen: bool,
     config: &PagerCfg,
 ) -> Option<Command> {
-    if let Ok(less_path) = grep_cli::resolve_binary(less_path) {
+    let less_path = match grep_cli::resolve_binary(&less_path) {
+        Ok(less_path) => Some(less_path),
+        // Git for Windows ships less, but does not put it on the PATH of cmd or PowerShell.
+        Err(_) if less_path == Path::new("less") => utils::windows::git_for_windows_less(),
+        Err(_) => None,
+    };
+    if let Some(less_path) = less_path {
         let mut p = Command::new(less_path);
         if args.is_empty() || replace_arguments_to_less {
             p.args(vec!["--RAW-CONTROL-CHARS"]);
@@ -226,6 +239,14 @@ fn _make_process_from_less_path(
                 p.env("LESSHISTFILE", hist_file);
                 if config.show_themes {
                     p.arg("+n");
+                } else if let Some(arg) = navigate::less_prefill_search_arg(config) {
+                    p.arg(arg);
+                }
+            }
+            // less reads lesskey source files (the keys of --navigate-keymap) from version 582.
+            if let Ok(Some(lesskey_file)) = navigate::write_lesskey_src_file(config) {
+                if retrieve_less_version().is_some_and(|version| version >= 582) {
+                    p.arg(format!("--lesskey-src={}", lesskey_file.display()));
                 }
             }
         }
@@ -0,0 +1,151 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+/// The time after which the entries of the cache are fetched again.
+pub const TIME_TO_LIVE: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// A value fetched from the network, and when it was fetched, in seconds since the Unix epoch.
+#[derive(Debug, Deserialize, Serialize)]
+struct Entry {
+    fetched: u64,
+    value: String,
+}
+
+/// An on-disk cache of the data which delta fetches from the network, e.g. the titles of pull
+/// requests from the API of a forge. An entry older than its time to live is fetched again, but
+/// is still used when the data cannot be fetched, e.g. when offline (--offline).
+pub struct Cache {
+    dir: Option<PathBuf>,
+    time_to_live: Duration,
+    offline: bool,
+}
+
+impl Cache {
+    /// A cache in the directory, e.g. that of `cache_dir`. Without a directory, values are
+    /// fetched every time.
+    pub fn new(dir: Option<PathBuf>, time_to_live: Duration, offline: bool) -> Self {
+        Self {
+            dir,
+            time_to_live,
+            offline,
+        }
+    }
+
+    /// The value of the key, fetched with `fetch` unless a fresh value is cached. Values which
+    /// cannot be fetched are not cached.
+    pub fn get_or_fetch(
+        &self,
+        key: &str,
+        fetch: impl FnOnce() -> Option<String>,
+    ) -> Option<String> {
+        let path = self.dir.as_deref().map(|dir| entry_path(dir, key));
+        let entry = path.as_deref().and_then(read_entry);
+        let now = now();
+        match entry {
+            Some(entry)
+                if self.offline
+                    || now.saturating_sub(entry.fetched) < self.time_to_live.as_secs() =>
+            {
+                Some(entry.value)
+            }
+            _ if self.offline => None,
+            stale => match fetch() {
+                Some(value) => {
+                    if let Some(path) = &path {
+                        write_entry(
+                            path,
+                            &Entry {
+                                fetched: now,
+                                value: value.clone(),
+                            },
+                        );
+                    }
+                    Some(value)
+                }
+                None => stale.map(|entry| entry.value),
+            },
+        }
+    }
+}
+
+/// delta's cache directory, e.g. ~/.cache/delta on Linux.
+pub fn cache_dir() -> Option<PathBuf> {
+    dirs::cache_dir().map(|dir| dir.join("delta"))
+}
+
+// The file of the entry, named by the key with characters other than letters, digits, '-' and
+// '.' replaced by '_'.
+fn entry_path(dir: &Path, key: &str) -> PathBuf {
+    let name: String = key
+        .chars()
+        .map(|c| match c {
+            'a'..='z' | 'A'..='Z' | '0'..='9' | '-' | '.' => c,
+            _ => '_',
+        })
+        .collect();
+    dir.join(format!("{name}.json"))
+}
+
+fn read_entry(path: &Path) -> Option<Entry> {
+    serde_json::from_slice(&fs::read(path).ok()?).ok()
+}
+
+// A cache which cannot be written only makes delta slower.
+fn write_entry(path: &Path, entry: &Entry) {
+    if let (Some(dir), Ok(json)) = (path.parent(), serde_json::to_vec(entry)) {
+        let _ = fs::create_dir_all(dir).and_then(|_| fs::write(path, json));
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::test_utils::temp_path;
+
+    #[test]
+    fn test_cache() {
+        let dir = temp_path("test_cache");
+        let _ = fs::remove_dir_all(&dir);
+        let cache = Cache::new(Some(dir.clone()), TIME_TO_LIVE, false);
+        let key = "github.com/dandavison/delta/issues/1";
+        assert_eq!(cache.get_or_fetch(key, || None), None);
+        assert_eq!(
+            cache.get_or_fetch(key, || Some("a".to_string())),
+            Some("a".to_string())
+        );
+        // The value is fresh.
+        assert_eq!(
+            cache.get_or_fetch(key, || Some("b".to_string())),
+            Some("a".to_string())
+        );
+        assert!(dir
+            .join("github.com_dandavison_delta_issues_1.json")
+            .is_file());
+
+        // An expired value is fetched again, but used if it cannot be.
+        let expired = Cache::new(Some(dir.clone()), Duration::ZERO, false);
+        assert_eq!(expired.get_or_fetch(key, || None), Some("a".to_string()));
+        assert_eq!(
+            expired.get_or_fetch(key, || Some("b".to_string())),
+            Some("b".to_string())
+        );
+
+        // Offline, nothing is fetched.
+        let offline = Cache::new(Some(dir.clone()), Duration::ZERO, true);
+        assert_eq!(
+            offline.get_or_fetch(key, || panic!("fetched")),
+            Some("b".to_string())
+        );
+        assert_eq!(offline.get_or_fetch("other", || panic!("fetched")), None);
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -10,7 +10,7 @@ use crate::ansi::measure_text_width;
 /// Removes trailing spaces. Leading spaces or enumerations with '- ' continue the indentation on
 /// the wrapped line.
 /// Example:
-/// ```
+/// ```ignore
 /// let wrapped = wrap("ab cd ef\n!NI!123\n|AB CD EF GH\n!NI!|123 456 789", 7, "_", "!NI!", "|");
 /// assert_eq!(wrapped, "\
 ///     _ab cd\n\
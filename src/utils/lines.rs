@@ -0,0 +1,55 @@
+use std::io::{self, BufRead, Read};
+
+use bytelines::ByteLines;
+
+/// The lines of the input, like those of `ByteLinesReader::byte_lines`, except that a carriage
+/// return ending a line (as in files with Windows line endings) is kept, so that delta can mark it
+/// or keep it according to --strip-cr.
+pub fn byte_lines<R: BufRead>(reader: R) -> ByteLines<KeepCarriageReturns<R>> {
+    ByteLines::new(KeepCarriageReturns(reader))
+}
+
+/// A reader which doubles the carriage return of "\r\n" line endings, since `ByteLines` removes
+/// "\r\n" from the end of each line.
+pub struct KeepCarriageReturns<R>(R);
+
+impl<R: Read> Read for KeepCarriageReturns<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.0.read(buf)
+    }
+}
+
+impl<R: BufRead> BufRead for KeepCarriageReturns<R> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        self.0.fill_buf()
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.0.consume(amt)
+    }
+
+    fn read_until(&mut self, byte: u8, buf: &mut Vec<u8>) -> io::Result<usize> {
+        let n = self.0.read_until(byte, buf)?;
+        if byte == b'\n' && n >= 2 && buf.ends_with(b"\r\n") {
+            buf.insert(buf.len() - 1, b'\r');
+            return Ok(n + 1);
+        }
+        Ok(n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_byte_lines_keep_carriage_returns() {
+        let mut lines = byte_lines("a\r\nb\nc\r\r\nd\r".as_bytes());
+        let mut next = || lines.next().map(|line| line.unwrap().to_vec());
+        assert_eq!(next().unwrap(), b"a\r");
+        assert_eq!(next().unwrap(), b"b");
+        assert_eq!(next().unwrap(), b"c\r\r");
+        assert_eq!(next().unwrap(), b"d\r");
+        assert!(next().is_none());
+    }
+}
@@ -0,0 +1,159 @@
+use std::borrow::Cow;
+
+use crate::env::DeltaEnv;
+
+/// How numbers and dates are formatted in the user's locale, which is that of --locale, or else
+/// that of the environment variables LC_ALL, LC_NUMERIC (numbers) or LC_TIME (dates), and LANG.
+/// In the C locale, i.e. without any of them, numbers and dates are formatted as in English, with
+/// numbers not grouped.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Locale {
+    /// The separator of the groups of thousands of numbers, if they are grouped.
+    pub thousands_separator: Option<char>,
+    /// The strftime format of a date, e.g. "%d.%m.%Y", which %x stands for in date formats.
+    pub date_format: &'static str,
+}
+
+impl Default for Locale {
+    fn default() -> Self {
+        Self::new("C", "C")
+    }
+}
+
+impl Locale {
+    /// The locale whose numbers are formatted as in the locale named `numeric` and dates as in
+    /// that named `time`, e.g. "de_DE.UTF-8".
+    pub fn new(numeric: &str, time: &str) -> Self {
+        Self {
+            thousands_separator: thousands_separator(parse_locale_name(numeric)),
+            date_format: date_format(parse_locale_name(time)),
+        }
+    }
+
+    pub fn from_env(locale: Option<&str>, env: &DeltaEnv) -> Self {
+        fn get(var: &Option<String>) -> Option<&str> {
+            var.as_deref().filter(|name| !name.is_empty())
+        }
+        let category = |var| {
+            locale
+                .or_else(|| get(&env.lc_all))
+                .or_else(|| get(var))
+                .or_else(|| get(&env.lang))
+                .unwrap_or("C")
+        };
+        Self::new(category(&env.lc_numeric), category(&env.lc_time))
+    }
+
+    /// The number with its digits grouped by thousands, e.g. "12.345" in German.
+    pub fn format_number(&self, n: usize) -> String {
+        let digits = n.to_string();
+        let Some(separator) = self.thousands_separator else {
+            return digits;
+        };
+        let mut formatted = String::with_capacity(digits.len() + digits.len() / 3);
+        for (i, digit) in digits.chars().enumerate() {
+            if i > 0 && (digits.len() - i).is_multiple_of(3) {
+                formatted.push(separator);
+            }
+            formatted.push(digit);
+        }
+        formatted
+    }
+
+    /// The strftime format with %x replaced by the format of dates of the locale.
+    pub fn localize_time_format<'a>(&self, format: &'a str) -> Cow<'a, str> {
+        if format.contains("%x") {
+            Cow::from(format.replace("%x", self.date_format))
+        } else {
+            Cow::from(format)
+        }
+    }
+}
+
+// The language and territory of a locale name such as "pt_BR.UTF-8" or "de_DE@euro".
+fn parse_locale_name(name: &str) -> (&str, &str) {
+    let name = name.split(['.', '@']).next().unwrap_or_default();
+    name.split_once(['_', '-']).unwrap_or((name, ""))
+}
+
+fn thousands_separator((language, territory): (&str, &str)) -> Option<char> {
+    match (language, territory) {
+        ("C" | "POSIX" | "", _) => None,
+        ("de" | "it" | "rm", "CH" | "LI") => Some('\''),
+        (
+            "da" | "de" | "el" | "es" | "hr" | "id" | "it" | "nl" | "pt" | "ro" | "sl" | "sr"
+            | "tr" | "vi",
+            _,
+        ) => Some('.'),
+        (
+            "bg" | "cs" | "et" | "fi" | "fr" | "hu" | "lt" | "lv" | "nb" | "nn" | "no" | "pl"
+            | "ru" | "sk" | "sv" | "uk",
+            _,
+        ) => Some('\u{a0}'),
+        _ => Some(','),
+    }
+}
+
+fn date_format((language, territory): (&str, &str)) -> &'static str {
+    match (language, territory) {
+        ("C" | "POSIX" | "", _) => "%m/%d/%y",
+        ("en", "US" | "PH") => "%m/%d/%Y",
+        ("en" | "fr", "CA") | ("lt" | "sv", _) => "%Y-%m-%d",
+        ("ja" | "zh", _) => "%Y/%m/%d",
+        ("hu" | "ko", _) => "%Y. %m. %d.",
+        ("nl", _) => "%d-%m-%Y",
+        ("cs" | "de" | "fi" | "nb" | "nn" | "no" | "pl" | "ro" | "ru" | "sk" | "tr" | "uk", _) => {
+            "%d.%m.%Y"
+        }
+        _ => "%d/%m/%Y",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_number() {
+        let format = |locale: &str, n| Locale::new(locale, "C").format_number(n);
+        assert_eq!(format("C", 1234567), "1234567");
+        assert_eq!(format("en_US.UTF-8", 1234567), "1,234,567");
+        assert_eq!(format("de_DE.UTF-8", 1234567), "1.234.567");
+        assert_eq!(format("de_CH", 1234), "1'234");
+        assert_eq!(format("fr_FR@euro", 123456), "123\u{a0}456");
+        assert_eq!(format("en_US", 999), "999");
+        assert_eq!(format("en_US", 0), "0");
+    }
+
+    #[test]
+    fn test_localize_time_format() {
+        let localize = |locale: &str, format| {
+            Locale::new("C", locale)
+                .localize_time_format(format)
+                .into_owned()
+        };
+        assert_eq!(localize("de_DE.UTF-8", "%x %H:%M"), "%d.%m.%Y %H:%M");
+        assert_eq!(localize("en_US.UTF-8", "%x"), "%m/%d/%Y");
+        assert_eq!(localize("en_GB.UTF-8", "%x"), "%d/%m/%Y");
+        assert_eq!(localize("ja_JP.UTF-8", "%x"), "%Y/%m/%d");
+        assert_eq!(localize("C", "%Y-%m-%d"), "%Y-%m-%d");
+    }
+
+    #[test]
+    fn test_locale_from_env() {
+        let env = DeltaEnv {
+            lang: Some("de_DE.UTF-8".to_string()),
+            lc_time: Some("en_US.UTF-8".to_string()),
+            ..DeltaEnv::default()
+        };
+        let locale = Locale::from_env(None, &env);
+        assert_eq!(locale.thousands_separator, Some('.'));
+        assert_eq!(locale.date_format, "%m/%d/%Y");
+        assert_eq!(Locale::from_env(Some("C"), &env), Locale::default());
+        let env = DeltaEnv {
+            lc_all: Some("fr_FR.UTF-8".to_string()),
+            ..env
+        };
+        assert_eq!(Locale::from_env(None, &env), Locale::new("fr_FR", "fr_FR"));
+    }
+}
@@ -1,11 +1,19 @@
 #[cfg(not(tarpaulin_include))]
 pub mod bat;
+pub mod cache;
 pub mod git;
 pub mod helpwrap;
+pub mod lines;
+pub mod locale;
 pub mod path;
+pub mod private_dir;
 pub mod process;
 pub mod regex_replacement;
 pub mod round_char_boundary;
 pub mod syntect;
 pub mod tabs;
+#[cfg(feature = "tree-sitter")]
+pub mod tree_sitter;
+pub mod warnings;
+pub mod windows;
 pub mod workarounds;
@@ -1,5 +1,8 @@
+use std::borrow::Cow;
 use std::path::{Component, Path, PathBuf};
 
+use regex::Regex;
+
 use crate::config::Config;
 
 use super::process::calling_process;
@@ -25,9 +28,44 @@ pub fn absolute_path(relative_path: &str, config: &Config) -> Option<PathBuf> {
     .map(normalize_path)
 }
 
-#[allow(clippy::needless_borrows_for_generic_args)] // Lint has known problems, &path != path
+/// The path to display for `path`, transformed by file-transformation and the path-rewrite rules.
+pub fn display_path<'a>(path: &'a str, config: &Config) -> Cow<'a, str> {
+    let path = match &config.file_regex_replacement {
+        Some(regex_replacement) => regex_replacement.execute(path),
+        None => Cow::from(path),
+    };
+    rewrite_path(path, config)
+}
+
+/// The absolute path which a hyperlink to `path` targets. The path-rewrite rules only apply to it
+/// if path-rewrite-hyperlinks is set.
+pub fn hyperlink_target_path(path: &str, config: &Config) -> Option<PathBuf> {
+    if config.path_rewrite_hyperlinks {
+        absolute_path(&rewrite_path(Cow::from(path), config), config)
+    } else {
+        absolute_path(path, config)
+    }
+}
+
+fn rewrite_path<'a>(mut path: Cow<'a, str>, config: &Config) -> Cow<'a, str> {
+    for rule in &config.path_rewrite {
+        if let Cow::Owned(rewritten) = rule.execute(&path) {
+            path = Cow::from(rewritten);
+        }
+    }
+    path
+}
+
 /// Relativize `path` if delta `config` demands that and paths are not already relativized by git.
 pub fn relativize_path_maybe(path: &mut String, config: &Config) {
+    if !calling_process().paths_in_input_are_relative_to_cwd() {
+        relativize_repo_path_maybe(path, config);
+    }
+}
+
+#[allow(clippy::needless_borrows_for_generic_args)] // Lint has known problems, &path != path
+/// Relativize `path`, which is relative to the repository root, if delta `config` demands that.
+pub fn relativize_repo_path_maybe(path: &mut String, config: &Config) {
     let mut inner_relativize = || -> Option<()> {
         let base = config.cwd_relative_to_repo_root.as_deref()?;
         let relative_path = pathdiff::diff_paths(&path, base)?;
@@ -42,7 +80,7 @@ pub fn relativize_path_maybe(path: &mut String, config: &Config) {
         }
         Some(())
     };
-    if config.relative_paths && !calling_process().paths_in_input_are_relative_to_cwd() {
+    if config.relative_paths {
         let _ = inner_relativize();
     }
 }
@@ -105,6 +143,39 @@ where
     ret
 }
 
+/// Parse comma-separated globs as regexes matching the paths of files (--canonicalize-json,
+/// --exclude).
+pub fn parse_globs(globs: &str) -> Vec<Regex> {
+    globs
+        .split(',')
+        .map(str::trim)
+        .filter(|glob| !glob.is_empty())
+        .map(glob_regex)
+        .collect()
+}
+
+// As in .gitignore, a glob containing '/' other than at its end matches the whole path, and
+// otherwise the file name. A leading '/' and a trailing '/' are dropped.
+fn glob_regex(glob: &str) -> Regex {
+    let glob = glob.strip_suffix('/').unwrap_or(glob);
+    let mut regex = String::from(if glob.contains('/') { "^" } else { "(?:^|/)" });
+    let glob = glob.strip_prefix('/').unwrap_or(glob);
+    let mut chars = glob.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                regex.push_str(".*");
+            }
+            '*' => regex.push_str("[^/]*"),
+            '?' => regex.push_str("[^/]"),
+            c => regex.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    regex.push('$');
+    Regex::new(&regex).unwrap()
+}
+
 #[cfg(test)]
 pub fn fake_delta_cwd_for_tests() -> PathBuf {
     #[cfg(not(target_os = "windows"))]
@@ -116,3 +187,28 @@ pub fn fake_delta_cwd_for_tests() -> PathBuf {
         PathBuf::from(r"C:\fake\delta\cwd")
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_glob_regex() {
+        let globs = parse_globs("*.lock, config/*.pb.json,");
+        let matches = |path| globs.iter().any(|regex| regex.is_match(path));
+        assert!(matches("Cargo.lock"));
+        assert!(matches("a/b/package.lock"));
+        assert!(matches("config/x.pb.json"));
+        assert!(!matches("other/config/x.pb.json"));
+        assert!(!matches("config/a/x.pb.json"));
+        assert!(!matches("lock"));
+        assert!(parse_globs("src/**.json")[0].is_match("src/a/b.json"));
+
+        let globs = parse_globs("target/, /build");
+        let matches = |path| globs.iter().any(|regex| regex.is_match(path));
+        assert!(matches("target"));
+        assert!(matches("a/target"));
+        assert!(matches("build"));
+        assert!(!matches("a/build"));
+    }
+}
@@ -0,0 +1,123 @@
+use std::collections::hash_map::RandomState;
+use std::fs;
+use std::hash::{BuildHasher, Hash, Hasher};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// The directory `dir`, created readable only by the user unless it exists. An existing directory
+/// is refused unless it is owned by the user and not accessible to others, since files written to
+/// it could otherwise be replaced by another user. Its parent is created as needed.
+pub fn open_private_dir(dir: &Path) -> io::Result<PathBuf> {
+    if let Some(parent) = dir.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    match create_private_dir(dir) {
+        Err(err) if err.kind() == io::ErrorKind::AlreadyExists => check_private_dir(dir)?,
+        result => result?,
+    }
+    Ok(dir.to_path_buf())
+}
+
+/// A new directory in the temporary directory, readable only by the user, named by `prefix` and a
+/// random suffix, so that other users can neither guess its name nor create it first.
+pub fn new_private_temp_dir(prefix: &str) -> io::Result<PathBuf> {
+    let temp_dir = std::env::temp_dir();
+    let mut attempts = 0;
+    loop {
+        let dir = temp_dir.join(format!("{prefix}-{:016x}", random_u64()));
+        match create_private_dir(&dir) {
+            Err(err) if err.kind() == io::ErrorKind::AlreadyExists && attempts < 16 => {
+                attempts += 1;
+            }
+            result => return result.map(|_| dir),
+        }
+    }
+}
+
+// Hashers of the standard library are keyed randomly by the operating system.
+fn random_u64() -> u64 {
+    let mut hasher = RandomState::new().build_hasher();
+    std::process::id().hash(&mut hasher);
+    SystemTime::now().hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(unix)]
+fn create_private_dir(dir: &Path) -> io::Result<()> {
+    use std::os::unix::fs::DirBuilderExt;
+    fs::DirBuilder::new().mode(0o700).create(dir)
+}
+
+#[cfg(not(unix))]
+fn create_private_dir(dir: &Path) -> io::Result<()> {
+    fs::create_dir(dir)
+}
+
+#[cfg(unix)]
+fn check_private_dir(dir: &Path) -> io::Result<()> {
+    use std::os::unix::fs::{MetadataExt, PermissionsExt};
+    // A symbolic link could point anywhere, so its own metadata is checked.
+    let metadata = fs::symlink_metadata(dir)?;
+    // SAFETY: geteuid has no preconditions and cannot fail.
+    let uid = unsafe { libc::geteuid() };
+    if !metadata.is_dir() || metadata.uid() != uid || metadata.permissions().mode() & 0o077 != 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            format!(
+                "{} is not a directory owned by the user and private to them.",
+                dir.display()
+            ),
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn check_private_dir(dir: &Path) -> io::Result<()> {
+    if !fs::symlink_metadata(dir)?.is_dir() {
+        return Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            format!("{} is not a directory.", dir.display()),
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::test_utils::temp_path;
+
+    #[test]
+    fn test_new_private_temp_dir() {
+        let dir_1 = new_private_temp_dir("delta__test_new_private_temp_dir").unwrap();
+        let dir_2 = new_private_temp_dir("delta__test_new_private_temp_dir").unwrap();
+        assert_ne!(dir_1, dir_2);
+        assert!(open_private_dir(&dir_1).is_ok());
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&dir_1).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o700);
+        }
+        let _ = fs::remove_dir(&dir_1);
+        let _ = fs::remove_dir(&dir_2);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_open_private_dir_refuses_shared_dir() {
+        use std::os::unix::fs::PermissionsExt;
+        let dir = temp_path("test_open_private_dir").join("private");
+        let _ = fs::remove_dir_all(dir.parent().unwrap());
+        assert_eq!(open_private_dir(&dir).unwrap(), dir);
+        assert!(open_private_dir(&dir).is_ok());
+        fs::set_permissions(&dir, fs::Permissions::from_mode(0o777)).unwrap();
+        assert_eq!(
+            open_private_dir(&dir).unwrap_err().kind(),
+            io::ErrorKind::PermissionDenied
+        );
+        let _ = fs::remove_dir_all(dir.parent().unwrap());
+    }
+}
@@ -27,15 +27,15 @@ impl CallingProcess {
             CallingProcess::GitDiff(cmd) if cmd.long_options.contains("--relative") => true,
             CallingProcess::GitShow(cmd, _) if cmd.long_options.contains("--relative") => true,
             CallingProcess::GitLog(cmd) if cmd.long_options.contains("--relative") => true,
-            CallingProcess::GitBlame(_)
-            | CallingProcess::GitGrep(_)
-            | CallingProcess::OtherGrep => true,
+            // With --full-name, git grep outputs paths relative to the repository root.
+            CallingProcess::GitGrep(cmd) => !cmd.long_options.contains("--full-name"),
+            CallingProcess::GitBlame(_) | CallingProcess::OtherGrep => true,
             _ => false,
         }
     }
 }
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
 pub struct CommandLine {
     pub long_options: HashSet<String>,
     pub short_options: HashSet<String>,
@@ -58,12 +58,30 @@ pub fn start_determining_calling_process_in_thread() {
             let (caller_mutex, determine_done) = &**CALLER;
 
             let mut caller = caller_mutex.lock().unwrap();
-            *caller = calling_process;
+            // Detection may have been disabled meanwhile.
+            if *caller == CallingProcess::Pending {
+                *caller = calling_process;
+            }
             determine_done.notify_all();
         })
         .unwrap();
 }
 
+/// Treat delta as not called by any known process, whatever process is found, so that the output
+/// does not depend on how delta was invoked (--no-process-detection).
+pub fn disable_calling_process_detection() {
+    set_calling_process(CallingProcess::None);
+}
+
+/// Treat delta as called by the given process, whatever process is found (--input-type).
+pub fn set_calling_process(calling_process: CallingProcess) {
+    let (caller_mutex, determine_done) = &**CALLER;
+
+    let mut caller = caller_mutex.lock().unwrap();
+    *caller = calling_process;
+    determine_done.notify_all();
+}
+
 #[cfg(not(test))]
 pub fn calling_process() -> MutexGuard<'static, CallingProcess> {
     let (caller_mutex, determine_done) = &**CALLER;
@@ -490,7 +508,7 @@ where
             // The full scan is expensive on Linux and rarely successful, so disable it by default.
             #[cfg(target_os = "linux")]
             let full_scan = std::env::var("DELTA_CALLING_PROCESS_QUERY_ALL")
-                .map_or(false, |v| !["0", "false", "no"].iter().any(|&n| n == v));
+                .is_ok_and(|v| !["0", "false", "no"].iter().any(|&n| n == v));
 
             if full_scan {
                 info.refresh_processes();
@@ -793,15 +811,15 @@ pub mod tests {
     #[test]
     #[should_panic(expected = "test logic error (in once): wrong FakeParentArgs scope?")]
     fn test_process_testing_assert_for_scope_never_used() {
-        let _args = FakeParentArgs::for_scope(&"never used");
-        let _args = FakeParentArgs::once(&"never used");
+        let _args = FakeParentArgs::for_scope("never used");
+        let _args = FakeParentArgs::once("never used");
     }
 
     #[test]
     #[should_panic(expected = "test logic error (in for_scope): wrong FakeParentArgs scope?")]
     fn test_process_testing_assert_once_never_used2() {
-        let _args = FakeParentArgs::once(&"never used");
-        let _args = FakeParentArgs::for_scope(&"never used");
+        let _args = FakeParentArgs::once("never used");
+        let _args = FakeParentArgs::for_scope("never used");
     }
 
     #[test]
@@ -1,7 +1,10 @@
 use std::borrow::Cow;
+use std::ops::Range;
 
 use regex::{Regex, RegexBuilder};
 
+use crate::fatal;
+
 #[derive(Clone, Debug)]
 pub struct RegexReplacement {
     regex: Regex,
@@ -10,6 +13,14 @@ pub struct RegexReplacement {
 }
 
 impl RegexReplacement {
+    pub fn new(regex: Regex, replacement: String) -> Self {
+        RegexReplacement {
+            regex,
+            replacement,
+            replace_all: false,
+        }
+    }
+
     pub fn from_sed_command(sed_command: &str) -> Option<Self> {
         let sep = sed_command.chars().nth(1)?;
         let mut parts = sed_command[2..].split(sep);
@@ -49,6 +60,40 @@ impl RegexReplacement {
         })
     }
 
+    /// Parse rules of the form `REGEX => REPLACEMENT, ...`, such as the value of path-rewrite.
+    /// The first match of the regex of each rule is replaced.
+    pub fn from_rules(rules: &str) -> Vec<Self> {
+        rules
+            .split(',')
+            .filter(|rule| !rule.trim().is_empty())
+            .map(|rule| match rule.split_once("=>") {
+                Some((regex, replacement)) => RegexReplacement {
+                    regex: Regex::new(regex.trim()).unwrap_or_else(|err| {
+                        fatal(format!("Invalid regex in rewrite rule {rule:?}: {err}"))
+                    }),
+                    replacement: replacement.trim().to_string(),
+                    replace_all: false,
+                },
+                None => fatal(format!(
+                    "Invalid rewrite rule {rule:?}: expected REGEX => REPLACEMENT."
+                )),
+            })
+            .collect()
+    }
+
+    /// The ranges of the matches of the regex in `s`, each with its replacement, in which e.g. $1
+    /// is the first group of the match.
+    pub fn expanded_matches<'t>(
+        &'t self,
+        s: &'t str,
+    ) -> impl Iterator<Item = (Range<usize>, String)> + 't {
+        self.regex.captures_iter(s).map(move |caps| {
+            let mut replacement = String::new();
+            caps.expand(&self.replacement, &mut replacement);
+            (caps.get(0).unwrap().range(), replacement)
+        })
+    }
+
     pub fn execute<'t>(&self, s: &'t str) -> Cow<'t, str> {
         if self.replace_all {
             self.regex.replace_all(s, &self.replacement)
@@ -99,6 +144,38 @@ mod tests {
         assert_eq!(rr.execute("Springsteen, Bruce"), "Bruce Springsteen");
     }
 
+    #[test]
+    fn test_rules() {
+        let rules = RegexReplacement::from_rules(
+            r"^services/(\w+)/src/main/java/com/corp/ => $1:, \.java$ => .j, ",
+        );
+        assert_eq!(rules.len(), 2);
+        let rewrite = |path| {
+            rules
+                .iter()
+                .fold(String::from(path), |path, rule| rule.execute(&path).into())
+        };
+        assert_eq!(
+            rewrite("services/payments/src/main/java/com/corp/Main.java"),
+            "payments:Main.j"
+        );
+        assert_eq!(rewrite("README.md"), "README.md");
+    }
+
+    #[test]
+    fn test_expanded_matches() {
+        let rules = RegexReplacement::from_rules(r"([A-Z]+)-(\d+) => https://$1.example.com/$2");
+        assert_eq!(
+            rules[0]
+                .expanded_matches("ABC-1, XY-23")
+                .collect::<Vec<_>>(),
+            vec![
+                (0..5, "https://ABC.example.com/1".to_string()),
+                (7..12, "https://XY.example.com/23".to_string())
+            ]
+        );
+    }
+
     #[test]
     fn test_sed_command_invalid() {
         assert!(RegexReplacement::from_sed_command("").is_none());
@@ -0,0 +1,113 @@
+use regex::Regex;
+use tree_sitter::{Language, Node, Parser};
+
+use crate::edits;
+
+/// The tree-sitter grammar of the language of a syntax, if delta has one.
+pub fn language(syntax_name: &str) -> Option<Language> {
+    let language = match syntax_name {
+        "Go" => tree_sitter_go::LANGUAGE,
+        "JavaScript" | "JavaScript (Babel)" => tree_sitter_javascript::LANGUAGE,
+        "Python" => tree_sitter_python::LANGUAGE,
+        "Rust" => tree_sitter_rust::LANGUAGE,
+        _ => return None,
+    };
+    Some(language.into())
+}
+
+/// Split a line into the tokens of the language: the leaves of its syntax tree. The text of
+/// strings and comments, and that between the leaves, is split as by `edits::tokenize`. Return
+/// None if the line cannot be parsed.
+pub fn tokenize<'a>(line: &'a str, language: &Language, regex: &Regex) -> Option<Vec<&'a str>> {
+    let mut parser = Parser::new();
+    parser.set_language(language).ok()?;
+    let tree = parser.parse(line, None)?;
+    // The first token is "", as for `edits::tokenize`.
+    let mut tokens = vec![""];
+    let push_words = |tokens: &mut Vec<&'a str>, text: &'a str| {
+        tokens.extend(
+            edits::tokenize(text, regex)
+                .into_iter()
+                .filter(|token| !token.is_empty()),
+        )
+    };
+    let mut offset = 0;
+    let mut cursor = tree.walk();
+    'leaves: loop {
+        let node = cursor.node();
+        if node.child_count() > 0 {
+            cursor.goto_first_child();
+            continue;
+        }
+        // Missing nodes, inserted to recover from syntax errors, are empty.
+        if node.start_byte() >= offset && node.end_byte() > node.start_byte() {
+            push_words(&mut tokens, &line[offset..node.start_byte()]);
+            let text = &line[node.start_byte()..node.end_byte()];
+            if is_text(node) {
+                push_words(&mut tokens, text);
+            } else {
+                tokens.push(text);
+            }
+            offset = node.end_byte();
+        }
+        while !cursor.goto_next_sibling() {
+            if !cursor.goto_parent() {
+                break 'leaves;
+            }
+        }
+    }
+    push_words(&mut tokens, &line[offset..]);
+    Some(tokens)
+}
+
+// Whether the node is the text of a string or a comment, e.g. a "string_content" node of Python
+// or a "line_comment" node of Rust.
+fn is_text(node: Node) -> bool {
+    let kind = node.kind();
+    node.is_named() && (kind.contains("string") || kind.contains("comment"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::integration_test_utils;
+
+    #[test]
+    fn test_tokenize() {
+        let regex = Regex::new(r"\w+").unwrap();
+        let rust = language("Rust").unwrap();
+        assert_eq!(
+            tokenize("x += a::b(1.5); // a comment\n", &rust, &regex).unwrap(),
+            [
+                "", "x", " ", "+=", " ", "a", "::", "b", "(", "1.5", ")", ";", " ", "//", " ", "a",
+                " ", "comment", "\n"
+            ]
+        );
+        let python = language("Python").unwrap();
+        assert_eq!(
+            tokenize("f(\"two words\") != None", &python, &regex).unwrap(),
+            ["", "f", "(", "\"", "two", " ", "words", "\"", ")", " ", "!=", " ", "None"]
+        );
+        assert!(language("Plain Text").is_none());
+    }
+
+    #[test]
+    fn test_tree_sitter_tokenization() {
+        let config = integration_test_utils::make_config_from_args(&["--tree-sitter-tokenization"]);
+        let (minus_lines, plus_lines, _) = edits::infer_edits(
+            vec!["x += 1;"],
+            vec!["x -= 1;"],
+            vec!["noop"],
+            "deletion",
+            vec!["noop"],
+            "insertion",
+            &edits::Tokenizer::for_syntax("Rust", &config),
+            config.max_line_distance,
+            config.max_line_distance_for_naively_paired_lines,
+            config.max_highlight_length,
+        );
+        // The operator is changed as a whole.
+        assert!(minus_lines[0].contains(&("deletion", "+=")));
+        assert!(plus_lines[0].contains(&("insertion", "-=")));
+    }
+}
@@ -0,0 +1,198 @@
+use std::cell::RefCell;
+use std::fmt;
+use std::io;
+use std::sync::Mutex;
+
+use lazy_static::lazy_static;
+use serde::Serialize;
+
+/// The kind of a warning, by which a program reading the warnings in JSON can tell them apart.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum WarningKind {
+    /// A feature which is neither builtin nor defined in git config.
+    UnknownOption,
+    /// A value which cannot be parsed, and is ignored.
+    InvalidValue,
+    /// A problem with the syntaxes and themes used for syntax highlighting.
+    Assets,
+    /// A command run by delta which failed.
+    Command,
+    /// A problem with the environment in which delta runs.
+    System,
+    /// A line of output which delta could not produce as requested.
+    Output,
+    /// A style whose text is hard to read on its background (--check-contrast).
+    Contrast,
+}
+
+/// The format in which warnings are displayed on stderr (--warnings-format).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum WarningsFormat {
+    /// E.g. "warning: Unknown feature 'x'."
+    #[default]
+    Text,
+    /// One JSON object per line, with the keys "level", "kind" and "message".
+    Json,
+}
+
+/// How warnings are displayed.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct WarningSettings {
+    /// Do not display warnings (--quiet).
+    pub quiet: bool,
+    /// Treat warnings as errors, ending delta with exit code 2 at the first (--strict-warnings).
+    /// The first error is returned by `check_error`.
+    pub strict: bool,
+    pub format: WarningsFormat,
+}
+
+#[derive(Serialize)]
+struct Warning<'a> {
+    level: &'static str,
+    kind: WarningKind,
+    message: &'a str,
+}
+
+// The settings, once the options are known, and the warnings issued before then.
+#[derive(Default)]
+struct Warnings {
+    settings: Option<WarningSettings>,
+    pending: Vec<(WarningKind, String)>,
+}
+
+lazy_static! {
+    static ref WARNINGS: Mutex<Warnings> = Mutex::new(Warnings::default());
+}
+
+thread_local! {
+    // The first warning treated as an error in this thread, until it is checked.
+    static ERROR: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+/// A warning treated as an error (--strict-warnings), by which delta stops.
+#[derive(Debug)]
+pub struct WarningError(pub String);
+
+impl fmt::Display for WarningError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for WarningError {}
+
+/// Display a warning on stderr. The warnings issued while the options are being read are held
+/// until they are known, since the options decide how warnings are displayed.
+pub fn warn(kind: WarningKind, message: impl Into<String>) {
+    let mut warnings = WARNINGS.lock().unwrap();
+    match warnings.settings {
+        Some(settings) => {
+            drop(warnings);
+            display(settings, kind, &message.into());
+        }
+        None => warnings.pending.push((kind, message.into())),
+    }
+}
+
+/// Set how warnings are displayed, and display those issued before.
+pub fn configure(settings: WarningSettings) {
+    let pending = {
+        let mut warnings = WARNINGS.lock().unwrap();
+        warnings.settings = Some(settings);
+        std::mem::take(&mut warnings.pending)
+    };
+    for (kind, message) in pending {
+        display(settings, kind, &message);
+    }
+}
+
+/// Return the first warning treated as an error in this thread since the last check, if any.
+pub fn check_error() -> io::Result<()> {
+    match ERROR.with(|error| error.borrow_mut().take()) {
+        Some(line) => Err(io::Error::other(WarningError(line))),
+        None => Ok(()),
+    }
+}
+
+fn display(settings: WarningSettings, kind: WarningKind, message: &str) {
+    match format_warning(settings, kind, message) {
+        Some(line) if settings.strict => ERROR.with(|error| {
+            error.borrow_mut().get_or_insert(line);
+        }),
+        Some(line) => eprintln!("{line}"),
+        None => {}
+    }
+}
+
+// The line displaying the warning, if it is displayed. A warning treated as an error is displayed
+// even with --quiet.
+fn format_warning(settings: WarningSettings, kind: WarningKind, message: &str) -> Option<String> {
+    if settings.quiet && !settings.strict {
+        return None;
+    }
+    let level = if settings.strict { "error" } else { "warning" };
+    Some(match settings.format {
+        WarningsFormat::Text => format!("{level}: {message}"),
+        WarningsFormat::Json => serde_json::to_string(&Warning {
+            level,
+            kind,
+            message,
+        })
+        .unwrap(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_warning() {
+        let settings = WarningSettings::default();
+        let format = |settings| format_warning(settings, WarningKind::Assets, "Unknown theme 'x'.");
+        assert_eq!(
+            format(settings).as_deref(),
+            Some("warning: Unknown theme 'x'.")
+        );
+        assert_eq!(
+            format(WarningSettings {
+                quiet: true,
+                ..settings
+            }),
+            None
+        );
+        assert_eq!(
+            format(WarningSettings {
+                format: WarningsFormat::Json,
+                ..settings
+            })
+            .as_deref(),
+            Some(r#"{"level":"warning","kind":"assets","message":"Unknown theme 'x'."}"#)
+        );
+        assert_eq!(
+            format(WarningSettings {
+                quiet: true,
+                strict: true,
+                format: WarningsFormat::Json,
+            })
+            .as_deref(),
+            Some(r#"{"level":"error","kind":"assets","message":"Unknown theme 'x'."}"#)
+        );
+    }
+
+    #[test]
+    fn test_check_error() {
+        let strict = WarningSettings {
+            strict: true,
+            ..WarningSettings::default()
+        };
+        assert!(check_error().is_ok());
+        display(strict, WarningKind::Output, "first");
+        display(strict, WarningKind::Output, "second");
+        let error = check_error().unwrap_err();
+        assert_eq!(error.to_string(), "error: first");
+        assert!(error.into_inner().unwrap().is::<WarningError>());
+        assert!(check_error().is_ok());
+    }
+}
@@ -0,0 +1,94 @@
+// Support for the Windows console (cmd.exe, PowerShell, Windows Terminal, ConEmu).
+use crate::env::DeltaEnv;
+
+/// Make the console interpret ANSI escape sequences instead of printing them. This is enabled by
+/// Windows Terminal and ConEmu, but not by the classic console host used by cmd and PowerShell.
+#[cfg(target_os = "windows")]
+pub fn enable_virtual_terminal_processing() {
+    // Fails if the output is not a console, in which case there is nothing to do.
+    let _ = ansi_term::enable_ansi_support();
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn enable_virtual_terminal_processing() {}
+
+/// Running in Windows Terminal. Its session variable is also seen by WSL processes.
+pub fn is_windows_terminal(env: &DeltaEnv) -> bool {
+    env.wt_session.is_some()
+}
+
+/// Running in ConEmu (or Cmder) with its ANSI processing enabled.
+pub fn is_conemu(env: &DeltaEnv) -> bool {
+    env.conemu_ansi.as_deref() == Some("ON")
+}
+
+/// Windows Terminal and ConEmu support 24-bit color but, unlike most Unix terminals, do not set
+/// COLORTERM.
+pub fn supports_true_color(env: &DeltaEnv) -> bool {
+    is_windows_terminal(env) || is_conemu(env)
+}
+
+/// The classic Windows console prints OSC 8 hyperlinks as garbage, so only emit them in Windows
+/// Terminal. Elsewhere this is left to the user.
+pub fn supports_hyperlinks(env: &DeltaEnv) -> bool {
+    !cfg!(target_os = "windows") || is_windows_terminal(env)
+}
+
+/// The less executable installed with Git for Windows.
+#[cfg(target_os = "windows")]
+pub fn git_for_windows_less() -> Option<std::path::PathBuf> {
+    ["ProgramW6432", "ProgramFiles"]
+        .iter()
+        .filter_map(std::env::var_os)
+        .map(|dir| {
+            std::path::PathBuf::from(dir)
+                .join("Git")
+                .join("usr")
+                .join("bin")
+                .join("less.exe")
+        })
+        .find(|path| path.is_file())
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn git_for_windows_less() -> Option<std::path::PathBuf> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_supports_true_color() {
+        assert!(!supports_true_color(&DeltaEnv::default()));
+        let env = DeltaEnv {
+            wt_session: Some("0b5c6a5e-2c24-4d8c-9a37-9e8f2a3b1c4d".into()),
+            ..DeltaEnv::default()
+        };
+        assert!(supports_true_color(&env));
+        let env = DeltaEnv {
+            conemu_ansi: Some("ON".into()),
+            ..DeltaEnv::default()
+        };
+        assert!(supports_true_color(&env));
+        let env = DeltaEnv {
+            conemu_ansi: Some("OFF".into()),
+            ..DeltaEnv::default()
+        };
+        assert!(!supports_true_color(&env));
+    }
+
+    #[test]
+    fn test_supports_hyperlinks() {
+        let env = DeltaEnv {
+            wt_session: Some("0b5c6a5e-2c24-4d8c-9a37-9e8f2a3b1c4d".into()),
+            ..DeltaEnv::default()
+        };
+        assert!(supports_hyperlinks(&env));
+        assert_eq!(
+            supports_hyperlinks(&DeltaEnv::default()),
+            !cfg!(target_os = "windows")
+        );
+    }
+}
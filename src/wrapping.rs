@@ -2,6 +2,7 @@ use syntect::highlighting::Style as SyntectStyle;
 use unicode_segmentation::UnicodeSegmentation;
 use unicode_width::UnicodeWidthStr;
 
+use crate::ansi;
 use crate::cli;
 use crate::config::INLINE_SYMBOL_WIDTH_1;
 use crate::fatal;
@@ -28,9 +29,20 @@ pub struct WrapConfig {
     // This value is --wrap-max-lines + 1, and unlimited is 0, see
     // adapt_wrap_max_lines_argument()
     pub max_lines: usize,
+    pub mode: WrapMode,
+    pub continuation_indent: usize,
     pub inline_hint_syntect_style: SyntectStyle,
 }
 
+/// Where lines are broken when wrapped (--wrap-mode).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WrapMode {
+    /// After the last character which fits.
+    Char,
+    /// After the last whitespace or punctuation character which fits, if any, else as `Char`.
+    Word,
+}
+
 impl WrapConfig {
     pub fn from_opt(opt: &cli::Opt, inline_hint_style: Style) -> Self {
         Self {
@@ -60,6 +72,12 @@ impl WrapConfig {
                 }
             },
             max_lines: adapt_wrap_max_lines_argument(opt.wrap_max_lines.clone()),
+            mode: match opt.wrap_mode.as_str() {
+                "char" => WrapMode::Char,
+                "word" => WrapMode::Word,
+                _ => fatal("Invalid option for wrap-mode: Expected \"char\" or \"word\"."),
+            },
+            continuation_indent: opt.wrap_continuation_indent,
             inline_hint_syntect_style: SyntectStyle::from_delta_style(inline_hint_style),
         }
     }
@@ -131,6 +149,10 @@ enum Stop {
 /// then right-align the second line and use the symbols
 /// [right_symbol](WrapConfig::right_symbol) and
 /// on the next line [right_prefix_symbol](WrapConfig::right_prefix_symbol).
+/// Otherwise wrapped lines are indented by
+/// [continuation_indent](WrapConfig::continuation_indent) spaces. Lines are
+/// broken as the [mode](WrapConfig::mode) says, which only moves the breaks:
+/// the styles of the input are kept.
 /// The inserted characters will follow the
 /// [inline_hint_syntect_style](WrapConfig::inline_hint_syntect_style).
 pub fn wrap_line<'a, I, S>(
@@ -145,6 +167,9 @@ where
     <I as IntoIterator>::IntoIter: DoubleEndedIterator,
     S: Copy + Default + std::fmt::Debug,
 {
+    // The inserted spaces, which indent or align a line to the right, point into this string.
+    const SPACES: &str = "                                                                ";
+
     let mut result = Vec::new();
 
     let wrap_config = &config.wrap_config;
@@ -184,6 +209,11 @@ where
 
     let mut stack = line.into_iter().rev().collect::<Vec<_>>();
 
+    // The OSC 8 hyperlink open at the end of the current line, if any. It is closed before the
+    // wrap symbol and re-opened on the next line, so that it neither extends over the padding
+    // nor is lost.
+    let mut open_hyperlink: Option<&'a str> = None;
+
     // If only the wrap symbol and no extra text fits, then wrapping is not possible.
     let max_lines = if line_width <= INLINE_SYMBOL_WIDTH_1 {
         1
@@ -193,6 +223,15 @@ where
 
     let line_limit_reached = |result: &Vec<_>| max_lines > 0 && result.len() + 1 >= max_lines;
 
+    // Leave at least half of the width of the wrapped lines for their content.
+    let continuation_indent = wrap_config
+        .continuation_indent
+        .min(line_width.saturating_sub(INLINE_SYMBOL_WIDTH_1 + 1) / 2)
+        .min(SPACES.len());
+    // The number of segments at the start of the current line which are not part of the input:
+    // the indentation and the re-opened hyperlink of a wrapped line.
+    let mut inserted_segments = 0;
+
     let stop = loop {
         if stack.is_empty() {
             break Stop::StackEmpty;
@@ -202,15 +241,7 @@ where
 
         let (style, text, graphemes) = stack
             .pop()
-            .map(|(style, text)| {
-                (
-                    style,
-                    text,
-                    text.graphemes(true)
-                        .map(|item| (item.len(), item.width()))
-                        .collect::<Vec<_>>(),
-                )
-            })
+            .map(|(style, text)| (style, text, ansi::grapheme_lengths_and_widths(text)))
             .unwrap();
 
         let graphemes_width: usize = graphemes.iter().map(|(_, w)| w).sum();
@@ -219,18 +250,21 @@ where
         #[allow(clippy::comparison_chain)]
         let must_split = if new_len < line_width {
             curr_line.push_and_set_len((style, text), new_len);
+            open_hyperlink = ansi::open_hyperlink_at_end(text, open_hyperlink);
             false
         } else if new_len == line_width {
             match stack.last() {
                 // Perfect fit, no need to make space for a `wrap_symbol`.
                 None => {
                     curr_line.push_and_set_len((style, text), new_len);
+                    open_hyperlink = ansi::open_hyperlink_at_end(text, open_hyperlink);
                     false
                 }
                 #[allow(clippy::identity_op)]
                 // A single '\n' left on the stack can be pushed onto the current line.
                 Some((next_style, nl)) if stack.len() == 1 && *nl == "\n" => {
                     curr_line.push_and_set_len((style, text), new_len);
+                    open_hyperlink = ansi::open_hyperlink_at_end(text, open_hyperlink);
                     // Do not count the '\n': + 0
                     curr_line.push_and_set_len((*next_style, *nl), new_len + 0);
                     stack.pop();
@@ -253,8 +287,8 @@ where
             // at the end, so move the line segments out.
             let mut line_segments = curr_line.line_segments;
 
-            let next_line = if width_left == 0 {
-                text
+            let mut split_pos = if width_left == 0 {
+                None
             } else {
                 let mut byte_split_pos = 0;
                 // After loop byte_split_pos may still equal to 0. If width_left
@@ -267,17 +301,55 @@ where
                         break;
                     }
                 }
+                Some(byte_split_pos)
+            };
+
+            // The last word of the current line, moved to the next one.
+            let mut last_word = Vec::new();
+            if wrap_config.mode == WrapMode::Word {
+                match split_pos.and_then(|pos| word_break(&text[..pos])) {
+                    Some(pos) => split_pos = Some(pos),
+                    None => {
+                        if let Some(word) =
+                            split_off_last_word(&mut line_segments, inserted_segments)
+                        {
+                            last_word = word;
+                            split_pos = None;
+                        }
+                    }
+                }
+            }
 
-                let this_line = &text[..byte_split_pos];
-                line_segments.push((style, this_line));
-                &text[byte_split_pos..]
+            let next_line = match split_pos {
+                None => text,
+                Some(byte_split_pos) => {
+                    let this_line = &text[..byte_split_pos];
+                    line_segments.push((style, this_line));
+                    open_hyperlink = ansi::open_hyperlink_at_end(this_line, open_hyperlink);
+                    &text[byte_split_pos..]
+                }
             };
             stack.push((style, next_line));
+            stack.extend(last_word.into_iter().rev());
 
+            if open_hyperlink.is_some() {
+                line_segments.push((symbol_style, ansi::OSC8_HYPERLINK_END));
+            }
             line_segments.push((symbol_style, &wrap_config.left_symbol));
             result.push(line_segments);
 
             curr_line = CurrLine::reset();
+            if continuation_indent > 0 {
+                curr_line.push_and_set_len(
+                    (*fill_style, &SPACES[..continuation_indent]),
+                    continuation_indent,
+                );
+            }
+            if let Some(hyperlink) = open_hyperlink {
+                // Zero width, so the length of the new line is not changed.
+                curr_line.line_segments.push((style, hyperlink));
+            }
+            inserted_segments = curr_line.line_segments.len();
         }
     };
 
@@ -285,16 +357,14 @@ where
     // Done if wrapping adds exactly one line and this line is less than the given
     // permille wide. Also change the wrap symbol at the end of the previous (first) line.
     if result.len() == 1 && curr_line.has_text() {
-        let current_permille = (curr_line.text_len() * 1000) / line_width;
+        // Without the indentation, which is dropped when aligning to the right.
+        let text_len = curr_line.text_len() - continuation_indent;
+        let current_permille = (text_len * 1000) / line_width;
 
         // pad line will add a wrap_config.right_prefix_symbol
-        let pad_len = line_width
-            .saturating_sub(curr_line.text_len() + wrap_config.right_prefix_symbol.width());
+        let pad_len = line_width.saturating_sub(text_len + wrap_config.right_prefix_symbol.width());
 
         if wrap_config.use_wrap_right_permille > current_permille && pad_len > 0 {
-            // The inserted spaces, which align a line to the right, point into this string.
-            const SPACES: &str = "                                                                ";
-
             match result.last_mut() {
                 Some(ref mut vec) if !vec.is_empty() => {
                     vec.last_mut().unwrap().1 = &wrap_config.right_symbol
@@ -315,7 +385,12 @@ where
 
             right_aligned_line.push((symbol_style, &wrap_config.right_prefix_symbol));
 
-            right_aligned_line.extend(curr_line.line_segments);
+            right_aligned_line.extend(
+                curr_line
+                    .line_segments
+                    .into_iter()
+                    .skip((continuation_indent > 0) as usize),
+            );
 
             curr_line.line_segments = right_aligned_line;
 
@@ -345,6 +420,41 @@ where
     result
 }
 
+/// The position after the last whitespace or punctuation character of `text`, if any, unless it
+/// contains ANSI escape sequences, e.g. of hyperlinks, which are not broken.
+fn word_break(text: &str) -> Option<usize> {
+    if text.contains('\x1b') {
+        return None;
+    }
+    let (pos, c) = text
+        .char_indices()
+        .rev()
+        .find(|&(_, c)| c.is_whitespace() || c.is_ascii_punctuation())?;
+    Some(pos + c.len_utf8())
+}
+
+/// Split the segments of the line being wrapped, after the first `inserted_segments`, after their
+/// last word break, and return those following it. Segments with ANSI escape sequences are kept
+/// on the line, and so is the text before them.
+fn split_off_last_word<'a, S: Copy>(
+    line_segments: &mut LineSections<'a, S>,
+    inserted_segments: usize,
+) -> Option<LineSections<'a, S>> {
+    let (i, pos) = line_segments[inserted_segments..]
+        .iter()
+        .enumerate()
+        .rev()
+        .take_while(|(_, (_, text))| !text.contains('\x1b'))
+        .find_map(|(i, (_, text))| Some((inserted_segments + i, word_break(text)?)))?;
+    let mut word = line_segments.split_off(i + 1);
+    let (style, text) = line_segments[i];
+    if pos < text.len() {
+        line_segments[i].1 = &text[..pos];
+        word.insert(0, (style, &text[pos..]));
+    }
+    Some(word)
+}
+
 fn wrap_if_too_long<'a, S>(
     config: &'a Config,
     wrapped: &mut Vec<LineSections<'a, S>>,
@@ -835,6 +945,116 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_wrap_line_word_mode() {
+        let cfg = make_config_from_args(&default_wrap_cfg_plus(&["--wrap-mode", "word"]));
+
+        {
+            // The break after "ab " is in an earlier segment than the one which does not fit.
+            let line = vec![(*S1, "ab cd"), (*S2, "ef gh")];
+            let lines = wrap_test(&cfg, line, 6);
+            assert_eq!(
+                lines,
+                vec![
+                    vec![(*S1, "ab "), (*SD, W)],
+                    vec![(*S1, "cd"), (*S2, "ef "), (*SD, W)],
+                    vec![(*S2, "gh")]
+                ]
+            );
+        }
+        {
+            let line = vec![(*S1, "f(a,bcd)")];
+            let lines = wrap_test(&cfg, line, 7);
+            assert_eq!(
+                lines,
+                vec![vec![(*S1, "f(a,"), (*SD, W)], vec![(*S1, "bcd)")]]
+            );
+        }
+        {
+            // Without a break, as many characters as fit.
+            let line = vec![(*S1, "abcdefghij")];
+            let lines = wrap_test(&cfg, line, 6);
+            assert_eq!(
+                lines,
+                vec![vec![(*S1, "abcde"), (*SD, W)], vec![(*S1, "fghij")]]
+            );
+        }
+    }
+
+    #[test]
+    fn test_wrap_line_continuation_indent() {
+        let cfg =
+            make_config_from_args(&default_wrap_cfg_plus(&["--wrap-continuation-indent", "2"]));
+
+        {
+            let line = vec![(*S1, "0123456789")];
+            let lines = wrap_test(&cfg, line, 6);
+            assert_eq!(
+                lines,
+                vec![
+                    vec![(*S1, "01234"), (*SD, W)],
+                    vec![(*SD, "  "), (*S1, "567"), (*SD, W)],
+                    vec![(*SD, "  "), (*S1, "89")]
+                ]
+            );
+        }
+        {
+            // Not indented when aligned to the right.
+            let line = vec![(*S1, "0123456789ab")];
+            let lines = wrap_test(&cfg, line, 11);
+            assert_eq!(lines[1], [(*SD, "        "), (*SD, RA), (*S1, "ab")]);
+        }
+    }
+
+    #[test]
+    fn test_wrap_line_hyperlink() {
+        const OPEN: &str = "\x1b]8;;file:///a\x1b\\";
+        const CLOSE: &str = crate::ansi::OSC8_HYPERLINK_END;
+        let mut no_align_right = TEST_WRAP_CFG.clone();
+        no_align_right.use_wrap_right_permille = 1;
+        let cfg = mk_wrap_cfg(&no_align_right);
+
+        {
+            // The hyperlink is closed before the wrap symbol and re-opened on the next line.
+            let link = format!("{OPEN}0123456789{CLOSE}");
+            let line = vec![(*S1, "ab"), (*S2, link.as_str())];
+            let lines = wrap_test(&cfg, line, 6);
+            assert_eq!(
+                lines,
+                vec![
+                    vec![
+                        (*S1, "ab"),
+                        (*S2, &format!("{OPEN}012")[..]),
+                        (*SD, CLOSE),
+                        (*SD, W)
+                    ],
+                    vec![(*S2, OPEN), (*S2, "34567"), (*SD, CLOSE), (*SD, W)],
+                    vec![(*S2, OPEN), (*S2, &format!("89{CLOSE}")[..])],
+                ]
+            );
+        }
+        {
+            // The escape sequence itself is never split, and has no width.
+            let link = format!("0123{OPEN}4{CLOSE}");
+            let line = vec![(*S1, link.as_str())];
+            let lines = wrap_test(&cfg, line, 6);
+            assert_eq!(lines.len(), 1);
+        }
+        {
+            // A hyperlink which was closed before the split is not re-opened.
+            let link = format!("{OPEN}01{CLOSE}");
+            let line = vec![(*S1, link.as_str()), (*S2, "23456789")];
+            let lines = wrap_test(&cfg, line, 6);
+            assert_eq!(
+                lines,
+                vec![
+                    vec![(*S1, link.as_str()), (*S2, "234"), (*SD, W)],
+                    vec![(*S2, "56789")],
+                ]
+            );
+        }
+    }
+
     #[test]
     fn test_wrap_line_newlines() {
         fn mk_input(len: usize) -> LineSections<'static, Style> {
@@ -1166,6 +1386,32 @@ index 223ca50..e69de29 100644
         }
     }
 
+    #[test]
+    fn test_wrap_word_mode_side_by_side() {
+        let config = make_config_from_args(&default_wrap_cfg_plus(&[
+            "--side-by-side",
+            "--width",
+            "61",
+            "--line-fill-method",
+            "spaces",
+            "--wrap-mode",
+            "word",
+            "--wrap-continuation-indent",
+            "2",
+        ]));
+        DeltaTest::with_config(&config)
+            .with_input(&format!(
+                "{HUNK_ALIGN_DIFF_HEADER}-let x = f(a);\n+let x = frobnicate(alpha, beta, gamma, delta);\n",
+            ))
+            .expect_after_header(
+                r#"
+                    │  1 │let x = f(a);           │    │
+                    │    │                        │  1 │let x = frobnicate(+    
+                    │    │                        │    │  alpha, beta, gamma, + 
+                    │    │                        │    │  delta);               "#,
+            );
+    }
+
     #[test]
     fn test_wrap_max_lines_2() {
         // TODO overriding is not possible, need to change config directly